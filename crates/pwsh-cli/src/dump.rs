@@ -0,0 +1,91 @@
+/// Debug dumps of the token stream and parsed AST: `pwsh --tokens` and `pwsh --ast`
+use pwsh_lexer::Lexer;
+use pwsh_parser::Parser;
+
+/// What to dump
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Dump {
+    Tokens,
+    Ast,
+}
+
+/// Lex (and for the AST, parse) a script and describe the result:
+/// one token per line with its span, the AST as an indented tree, or either as JSON
+pub fn dump(source: &str, what: Dump, json: bool) -> Result<String, String> {
+    let tokens = Lexer::new(source)
+        .tokenize()
+        .map_err(|e| format!("Lexer error: {}", e))?;
+
+    match what {
+        Dump::Tokens if json => serde_json::to_string_pretty(&tokens).map_err(|e| e.to_string()),
+        Dump::Tokens => Ok(tokens
+            .iter()
+            .map(|token| format!("{:<12} {:?}\n", format!("{:?}", token.span()), token.token))
+            .collect()),
+        Dump::Ast => {
+            let (program, errors) = Parser::new(tokens).parse_with_recovery();
+            if !errors.is_empty() {
+                return Err(errors
+                    .iter()
+                    .map(|e| format!("Parse error: {}", e))
+                    .collect::<Vec<_>>()
+                    .join("\n"));
+            }
+            if json {
+                serde_json::to_string_pretty(&program).map_err(|e| e.to_string())
+            } else {
+                Ok(format!("{:#?}", program))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dump_tokens() {
+        assert_eq!(
+            dump("$x = 1", Dump::Tokens, false).unwrap(),
+            "1:1..1:3     Variable(\"x\")\n\
+             1:4..1:5     Assignment\n\
+             1:6..1:7     Number(1.0)\n\
+             1:7..1:7     Eof\n"
+        );
+
+        let json: serde_json::Value =
+            serde_json::from_str(&dump("$x", Dump::Tokens, true).unwrap()).unwrap();
+        assert_eq!(json[0]["token"]["Variable"], "x");
+        assert_eq!(json[0]["end"]["column"], 3);
+    }
+
+    #[test]
+    fn test_dump_ast() {
+        let tree = dump("Get-Item -Path 'a'", Dump::Ast, false).unwrap();
+        assert!(tree.starts_with("Program {"), "{}", tree);
+        assert!(tree.contains("name: \"Get-Item\""), "{}", tree);
+        assert!(tree.contains("span: 1:10..1:19"), "{}", tree);
+
+        let json: serde_json::Value =
+            serde_json::from_str(&dump("1 + 2", Dump::Ast, true).unwrap()).unwrap();
+        let sum = &json["statements"][0]["kind"]["Expression"]["kind"]["BinaryOp"];
+        assert_eq!(sum["operator"], "Add");
+        assert_eq!(sum["right"]["span"]["start"]["column"], 5);
+    }
+
+    #[test]
+    fn test_dump_reports_errors() {
+        assert!(dump("'open", Dump::Tokens, false)
+            .unwrap_err()
+            .starts_with("Lexer error:"));
+        assert!(dump("$x = (1 +", Dump::Ast, true)
+            .unwrap_err()
+            .starts_with("Parse error:"));
+
+        // Every statement's error is listed
+        let message = dump("$a = )\n$b = 1\n$c = ]", Dump::Ast, false).unwrap_err();
+        assert_eq!(message.lines().count(), 2);
+        assert!(message.lines().all(|line| line.starts_with("Parse error:")));
+    }
+}
@@ -1,13 +1,23 @@
+mod dump;
+mod terminal;
+
+use dump::Dump;
 use nu_ansi_term::{Color, Style};
-use pwsh_lexer::Lexer;
+use pwsh_lexer::{LexError, Lexer, Token};
 use pwsh_parser::Parser;
-use pwsh_runtime::Evaluator;
+use pwsh_runtime::{
+    ColorScheme, CompletionEngine, Configuration, ConsoleHost, Evaluator, ExecutionPolicy,
+    LanguageMode, Session, SessionBuilder, SessionError,
+};
 use reedline::{
-    ColumnarMenu, Completer, Emacs, FileBackedHistory, Highlighter, KeyCode, KeyModifiers, Prompt,
-    PromptEditMode, PromptHistorySearch, Reedline, ReedlineEvent, ReedlineMenu, Signal, Span,
-    StyledText, Suggestion, ValidationResult, Validator,
+    ColumnarMenu, Completer, Emacs, FileBackedHistory, Highlighter, HistoryItem, KeyCode,
+    KeyModifiers, Prompt, PromptEditMode, PromptHistorySearch, Reedline, ReedlineEvent,
+    ReedlineMenu, Signal, Span, StyledText, Suggestion, ValidationResult, Validator,
 };
 use std::borrow::Cow;
+use std::process::ExitCode;
+use std::sync::{Arc, Mutex};
+use terminal::TerminalHost;
 
 // --- Validator ---
 
@@ -18,36 +28,31 @@ impl Validator for PowerShellValidator {
     fn validate(&self, line: &str) -> ValidationResult {
         let mut brace_count = 0;
         let mut paren_count = 0;
-        let mut in_string = false;
-        let mut string_char = ' ';
-
-        let chars: Vec<char> = line.chars().collect();
-        let mut i = 0;
-        while i < chars.len() {
-            let ch = chars[i];
-            match ch {
-                '\'' | '"' if !in_string => {
-                    in_string = true;
-                    string_char = ch;
-                }
-                ch if in_string && ch == string_char => {
-                    // Check for escaped quote (PowerShell uses double quotes for escaping: "" or '')
-                    if i + 1 < chars.len() && chars[i + 1] == string_char {
-                        i += 1; // skip next char
-                    } else {
-                        in_string = false;
-                    }
+        let mut last = Token::Eof;
+
+        for token in Lexer::new(line) {
+            let token = match token {
+                Ok(located) => located.token,
+                // A string or block comment left open continues on the next line
+                Err(LexError::UnterminatedString { .. } | LexError::UnterminatedComment { .. }) => {
+                    return ValidationResult::Incomplete
                 }
-                '{' if !in_string => brace_count += 1,
-                '}' if !in_string => brace_count -= 1,
-                '(' if !in_string => paren_count += 1,
-                ')' if !in_string => paren_count -= 1,
+                // Other mistakes are for the parser to report
+                Err(_) => return ValidationResult::Complete,
+            };
+            match token {
+                Token::LeftBrace => brace_count += 1,
+                Token::RightBrace => brace_count -= 1,
+                Token::LeftParen => paren_count += 1,
+                Token::RightParen => paren_count -= 1,
+                Token::Newline | Token::Eof => continue,
                 _ => {}
             }
-            i += 1;
+            last = token;
         }
 
-        if brace_count > 0 || paren_count > 0 || in_string {
+        // A trailing pipe continues the pipeline on the next line
+        if brace_count > 0 || paren_count > 0 || last == Token::Pipeline {
             ValidationResult::Incomplete
         } else {
             ValidationResult::Complete
@@ -57,73 +62,91 @@ impl Validator for PowerShellValidator {
 
 // --- Completer ---
 
-/// Case-insensitive completer for PowerShell cmdlets
+/// Completer backed by the runtime's completion engine, which the REPL
+/// refreshes after every command so new functions and variables complete
 struct PowerShellCompleter {
-    commands: Vec<String>,
+    engine: Arc<Mutex<CompletionEngine>>,
 }
 
 impl PowerShellCompleter {
-    fn new(commands: Vec<String>) -> Self {
-        Self { commands }
+    fn new(engine: Arc<Mutex<CompletionEngine>>) -> Self {
+        Self { engine }
     }
 }
 
 impl Completer for PowerShellCompleter {
     fn complete(&mut self, line: &str, pos: usize) -> Vec<Suggestion> {
-        // Extract the word at the cursor position
-        // Find the start of the current word by scanning backwards
-        let bytes_before = &line.as_bytes()[..pos];
-        let mut start = pos;
-
-        // Scan backwards through byte positions to find word boundary
-        for (i, &byte) in bytes_before.iter().enumerate().rev() {
-            let c = byte as char;
-            if c.is_whitespace() || c == '|' || c == ';' {
-                start = i + 1;
-                break;
-            }
-            if i == 0 {
-                start = 0;
-            }
-        }
-
-        let partial = &line[start..pos];
-
-        let partial_lower = partial.to_lowercase();
-
-        // Find matching commands (case-insensitive)
-        // If partial is empty, return all commands
-        // Otherwise, filter by prefix match
-        let mut completions: Vec<Suggestion> = self
-            .commands
-            .iter()
-            .filter(|cmd| {
-                if partial.is_empty() {
-                    true
-                } else {
-                    cmd.to_lowercase().starts_with(&partial_lower)
-                }
-            })
-            .map(|cmd| Suggestion {
-                value: cmd.clone(),
+        let engine = self.engine.lock().unwrap();
+        engine
+            .complete(line, pos)
+            .into_iter()
+            .map(|completion| Suggestion {
+                // Directories stay open so the next path component can be completed
+                append_whitespace: !completion.text.ends_with(['/', '\\']),
+                value: completion.text,
                 description: None,
                 extra: None,
-                span: Span::new(start, pos),
-                append_whitespace: true,
+                span: Span::new(completion.replace.start, completion.replace.end),
             })
-            .collect();
+            .collect()
+    }
+}
+
+/// Completion engine for the session's current cmdlets, functions and variables
+fn session_completions(evaluator: &Evaluator) -> CompletionEngine {
+    let mut engine = CompletionEngine::from_evaluator(evaluator);
+    engine.add_command("exit", &[]);
+    engine
+}
 
-        // Sort completions for consistent ordering
-        completions.sort_by(|a, b| a.value.cmp(&b.value));
+// --- Highlighter ---
 
-        completions
+/// The terminal color for a console color name. The dark console colors
+/// are the terminal's normal ones and the others its bright ones, as in
+/// the Windows console.
+fn terminal_color(name: &str) -> Color {
+    match name {
+        "Black" => Color::Black,
+        "DarkBlue" => Color::Blue,
+        "DarkGreen" => Color::Green,
+        "DarkCyan" => Color::Cyan,
+        "DarkRed" => Color::Red,
+        "DarkMagenta" => Color::Magenta,
+        "DarkYellow" => Color::Yellow,
+        "Gray" => Color::White,
+        "DarkGray" => Color::DarkGray,
+        "Blue" => Color::LightBlue,
+        "Green" => Color::LightGreen,
+        "Cyan" => Color::LightCyan,
+        "Red" => Color::LightRed,
+        "Magenta" => Color::LightMagenta,
+        "Yellow" => Color::LightYellow,
+        "White" => Color::LightGray,
+        _ => Color::Default,
     }
 }
 
-// --- Highlighter ---
+/// Basic syntax highlighter for PowerShell, in the configured colors
+struct PowerShellHighlighter {
+    keyword: Style,
+    variable: Style,
+    parameter: Style,
+    operator: Style,
+    default: Style,
+}
 
-/// Basic syntax highlighter for PowerShell
-struct PowerShellHighlighter;
+impl PowerShellHighlighter {
+    fn new(scheme: &ColorScheme) -> Self {
+        let style = |name: &str| Style::new().fg(terminal_color(name));
+        PowerShellHighlighter {
+            keyword: style(&scheme.keyword).bold(),
+            variable: style(&scheme.variable),
+            parameter: style(&scheme.parameter),
+            operator: style(&scheme.operator),
+            default: style(&scheme.default),
+        }
+    }
+}
 
 impl Highlighter for PowerShellHighlighter {
     fn highlight(&self, line: &str, _cursor: usize) -> StyledText {
@@ -139,20 +162,18 @@ impl Highlighter for PowerShellHighlighter {
                 Style::new()
             } else {
                 match trimmed.to_lowercase().as_str() {
-                    "if" | "else" | "elseif" | "function" | "return" => {
-                        Style::new().fg(Color::Magenta).bold()
-                    }
-                    w if w.starts_with('$') => Style::new().fg(Color::Cyan),
+                    "if" | "else" | "elseif" | "function" | "filter" | "return" | "class"
+                    | "enum" | "foreach" | "try" | "catch" | "finally" | "throw" | "break"
+                    | "continue" => self.keyword,
+                    w if w.starts_with('$') => self.variable,
                     w if w.starts_with('-')
                         && w.len() > 1
                         && w.chars().nth(1).unwrap().is_alphabetic() =>
                     {
-                        Style::new().fg(Color::Yellow)
-                    }
-                    "{" | "}" | "(" | ")" | "|" | ";" | "=" | "." => {
-                        Style::new().fg(Color::LightGray)
+                        self.parameter
                     }
-                    _ => Style::new().fg(Color::White),
+                    "{" | "}" | "(" | ")" | "|" | ";" | "=" | "." => self.operator,
+                    _ => self.default,
                 }
             };
             styled_text.push((style, word.to_string()));
@@ -192,20 +213,248 @@ impl Prompt for PowerShellPrompt {
     }
 }
 
-fn main() -> std::io::Result<()> {
+/// Print a command's output through Out-Default: objects with a view, such
+/// as Get-ChildItem's files, in its table and anything else on its own,
+/// skipping $null as PowerShell's console does. `paged` output taller than
+/// the terminal is shown a screen at a time. False when $OutputRendering
+/// is not a rendering.
+fn show_output(
+    session: &mut Session,
+    source: &str,
+    output: &[pwsh_runtime::Value],
+    paged: bool,
+) -> bool {
+    let evaluator = session.evaluator_mut();
+    let shown = if paged {
+        evaluator.out_paged(output)
+    } else {
+        evaluator.out_default(output)
+    };
+    match shown {
+        Ok(()) => true,
+        Err(e) => {
+            report_error(session, source, SessionError::Runtime(e));
+            false
+        }
+    }
+}
+
+const USAGE: &str = "Usage: pwsh [--tokens | --ast] [--json] FILE
+       pwsh [--no-compile] [--execution-policy POLICY] [--language-mode MODE]
+            [--config FILE] [FILE]
+
+Runs the script FILE, or starts the interactive shell when run without one.
+    --no-compile  Run on the tree-walking evaluator only, without
+                  compiling loop bodies and script blocks (for debugging)
+    --execution-policy POLICY
+                  Which script files may run: Restricted, AllSigned,
+                  RemoteSigned, Unrestricted (the default) or Bypass;
+                  overrides the configuration's ExecutionPolicy
+    --language-mode MODE
+                  How much of the language scripts may use: FullLanguage
+                  (the default), ConstrainedLanguage or NoLanguage
+    --config FILE Read the settings from FILE instead of the user's
+                  pwsh/pwsh.config.json in ~/.config (%APPDATA% on Windows)
+    --tokens  Print the script's tokens with their line:column spans
+    --ast     Print the script's parsed syntax tree
+    --json    Print the tokens or syntax tree as JSON";
+
+/// Run `pwsh --tokens` / `pwsh --ast`
+fn run_dump(args: &[String]) -> ExitCode {
+    let mut what = None;
+    let mut json = false;
+    let mut file = None;
+    for arg in args {
+        match arg.as_str() {
+            "--tokens" => what = Some(Dump::Tokens),
+            "--ast" => what = Some(Dump::Ast),
+            "--json" => json = true,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return ExitCode::SUCCESS;
+            }
+            _ if file.is_none() && !arg.starts_with('-') => file = Some(arg),
+            _ => {
+                eprintln!("{}", USAGE);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+    let (Some(what), Some(file)) = (what, file) else {
+        eprintln!("{}", USAGE);
+        return ExitCode::FAILURE;
+    };
+
+    let result = std::fs::read_to_string(file)
+        .map_err(|e| e.to_string())
+        .and_then(|source| dump::dump(&source, what, json));
+    match result {
+        Ok(output) => {
+            println!("{}", output.trim_end());
+            ExitCode::SUCCESS
+        }
+        Err(message) => {
+            eprintln!("pwsh: {}: {}", file, message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Print a failed command's error, pointing at where a runtime error happened.
+/// A script that fails to parse has all its parse errors listed.
+fn report_error(session: &Session, source: &str, error: SessionError) {
+    match error {
+        e @ SessionError::Lex(_) => eprintln!("Lexer error: {}\n", e),
+        SessionError::Parse(e) => {
+            let errors = match Lexer::new(source).tokenize() {
+                Ok(tokens) => Parser::new(tokens).parse_with_recovery().1,
+                Err(_) => vec![e],
+            };
+            for e in errors {
+                eprintln!("Parse error: {}", e);
+            }
+            eprintln!();
+        }
+        SessionError::Runtime(e) => match session.error_position() {
+            Some(position) => eprintln!("Runtime error: {}\n{}\n", e, position),
+            None => eprintln!("Runtime error: {}\n", e),
+        },
+    }
+}
+
+/// How the shell was started: its command-line options and configuration
+struct Options {
+    compile: bool,
+    configuration: Configuration,
+    /// `--execution-policy`, which wins over the configuration's
+    policy: Option<ExecutionPolicy>,
+    /// `--language-mode`
+    language_mode: LanguageMode,
+}
+
+/// The settings from `--config FILE`, or else from the user's
+/// configuration file when there is one. A broken user file is only
+/// warned about, so it can't keep the shell from starting.
+fn load_configuration(file: Option<&str>) -> Result<Configuration, String> {
+    if let Some(file) = file {
+        return Configuration::load(file).map_err(|e| e.to_string());
+    }
+    match Configuration::user_file().filter(|path| path.is_file()) {
+        Some(path) => Ok(Configuration::load(&path).unwrap_or_else(|e| {
+            eprintln!("pwsh: warning: {}; using the default settings", e);
+            Configuration::default()
+        })),
+        None => Ok(Configuration::default()),
+    }
+}
+
+/// A session with all cmdlets registered, that runs programs from PATH too
+fn new_session(options: &Options) -> Session {
+    let mut builder = SessionBuilder::new()
+        .cmdlets(pwsh_cmdlets::register_all)
+        .host(TerminalHost)
+        .native_commands()
+        .configuration(options.configuration.clone());
+    if let Some(policy) = options.policy {
+        builder = builder.execution_policy(policy);
+    }
+    builder = builder.language_mode(options.language_mode);
+    if !options.compile {
+        builder = builder.without_compilation();
+    }
+    builder.build()
+}
+
+/// Run `pwsh FILE`: the file runs as a script, in a script scope of its own
+fn run_script(file: &str, options: &Options) -> ExitCode {
+    let source = match std::fs::read_to_string(file) {
+        Ok(source) => source,
+        Err(e) => {
+            eprintln!("pwsh: {}: {}", file, e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut session = new_session(options);
+    let result = session.invoke_file(file, &source);
+    // A progress bar the script never completed goes before its output
+    ConsoleHost::clear_progress();
+    match result {
+        Ok(output) if show_output(&mut session, &source, &output, false) => ExitCode::SUCCESS,
+        Ok(_) => ExitCode::FAILURE,
+        Err(e) => {
+            // What ran before the error still goes out, ahead of it
+            let output = session.take_unfinished_output();
+            show_output(&mut session, &source, &output, false);
+            report_error(&session, &source, e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    let compile = !args.iter().any(|arg| arg == "--no-compile");
+    args.retain(|arg| arg != "--no-compile");
+    let mut policy = None;
+    if let Some(index) = args.iter().position(|arg| arg == "--execution-policy") {
+        let name = args.get(index + 1).cloned().unwrap_or_default();
+        let Some(named) = ExecutionPolicy::from_name(&name) else {
+            eprintln!("pwsh: '{}' is not an execution policy\n{}", name, USAGE);
+            return ExitCode::FAILURE;
+        };
+        policy = Some(named);
+        args.drain(index..index + 2);
+    }
+    let mut language_mode = LanguageMode::default();
+    if let Some(index) = args.iter().position(|arg| arg == "--language-mode") {
+        let name = args.get(index + 1).cloned().unwrap_or_default();
+        let Some(named) = LanguageMode::from_name(&name) else {
+            eprintln!("pwsh: '{}' is not a language mode\n{}", name, USAGE);
+            return ExitCode::FAILURE;
+        };
+        language_mode = named;
+        args.drain(index..index + 2);
+    }
+    let mut config_file = None;
+    if let Some(index) = args.iter().position(|arg| arg == "--config") {
+        let Some(file) = args.get(index + 1).cloned() else {
+            eprintln!("{}", USAGE);
+            return ExitCode::FAILURE;
+        };
+        config_file = Some(file);
+        args.drain(index..index + 2);
+    }
+    let configuration = match load_configuration(config_file.as_deref()) {
+        Ok(configuration) => configuration,
+        Err(message) => {
+            eprintln!("pwsh: {}", message);
+            return ExitCode::FAILURE;
+        }
+    };
+    let options = Options {
+        compile,
+        configuration,
+        policy,
+        language_mode,
+    };
+    match args.as_slice() {
+        [] => {}
+        [file] if !file.starts_with('-') => return run_script(file, &options),
+        _ => return run_dump(&args),
+    }
+
     println!("PowerShell Interpreter - Modern REPL");
     let cmdlets = pwsh_cmdlets::cmdlet_names();
     println!("Object Pipeline with {} Cmdlets!", cmdlets.len());
     println!("Available cmdlets: {}", cmdlets.join(", "));
     println!("Type 'exit' to quit, or use Ctrl+D.\n");
 
-    // Create evaluator and register all cmdlets
-    let mut evaluator = Evaluator::new();
-    pwsh_cmdlets::register_all(evaluator.registry_mut());
+    let mut session = new_session(&options);
 
     // Set up reedline components
     let history = Box::new(
-        FileBackedHistory::with_file(1000, "history.txt".into())
+        FileBackedHistory::with_file(options.configuration.history_size, "history.txt".into())
             .expect("Error creating history file"),
     );
 
@@ -232,15 +481,16 @@ fn main() -> std::io::Result<()> {
         ReedlineEvent::MenuPrevious,
     );
 
-    // Add a case-insensitive completer for cmdlets
-    let mut commands = cmdlets;
-    commands.push("exit".to_string());
-    let completer = Box::new(PowerShellCompleter::new(commands));
+    // Add a completer for commands, parameters, variables and paths
+    let completions = Arc::new(Mutex::new(session_completions(session.evaluator())));
+    let completer = Box::new(PowerShellCompleter::new(Arc::clone(&completions)));
 
     // Set up the line editor
     let mut line_editor = Reedline::create()
         .with_validator(Box::new(PowerShellValidator))
-        .with_highlighter(Box::new(PowerShellHighlighter))
+        .with_highlighter(Box::new(PowerShellHighlighter::new(
+            &options.configuration.color_scheme,
+        )))
         .with_history(history)
         .with_completer(completer)
         .with_quick_completions(true)
@@ -273,44 +523,31 @@ fn main() -> std::io::Result<()> {
                     break;
                 }
 
-                // Lex, Parse, and Evaluate the input
-                let mut lexer = Lexer::new(input);
-                match lexer.tokenize() {
-                    Ok(tokens) => {
-                        let mut parser = Parser::new(tokens);
-                        match parser.parse() {
-                            Ok(program) => match evaluator.eval(program) {
-                                Ok(value) => {
-                                    // Handle arrays by printing each element
-                                    match value {
-                                        pwsh_runtime::Value::Array(items) => {
-                                            for item in items {
-                                                if item != pwsh_runtime::Value::Null {
-                                                    println!("{}", item);
-                                                }
-                                            }
-                                        }
-                                        pwsh_runtime::Value::Null => {
-                                            // Don't print null values
-                                        }
-                                        _ => {
-                                            println!("{}", value);
-                                        }
-                                    }
-                                }
-                                Err(e) => {
-                                    eprintln!("Runtime error: {}\n", e);
-                                }
-                            },
-                            Err(e) => {
-                                eprintln!("Parse error: {}\n", e);
-                            }
-                        }
+                let result = session.invoke_interactive(input);
+                ConsoleHost::clear_progress();
+
+                // A line Invoke-History (`r`) ran again goes in the editor's
+                // history too; failing to save only costs the arrow keys it
+                if let Some(entry) = session.evaluator().history().entries().next_back() {
+                    if entry.command_line != input {
+                        let _ = line_editor
+                            .history_mut()
+                            .save(HistoryItem::from_command_line(&entry.command_line));
+                    }
+                }
+
+                match result {
+                    Ok(output) => {
+                        show_output(&mut session, input, &output, options.configuration.paging);
                     }
                     Err(e) => {
-                        eprintln!("Lexer error: {}\n", e);
+                        let output = session.take_unfinished_output();
+                        show_output(&mut session, input, &output, options.configuration.paging);
+                        report_error(&session, input, e);
                     }
                 }
+
+                *completions.lock().unwrap() = session_completions(session.evaluator());
             }
             Ok(Signal::CtrlC) => {
                 // Just clear the line
@@ -327,13 +564,21 @@ fn main() -> std::io::Result<()> {
         }
     }
 
-    Ok(())
+    ExitCode::SUCCESS
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn completer_for(commands: Vec<String>) -> PowerShellCompleter {
+        let mut engine = CompletionEngine::new();
+        for command in &commands {
+            engine.add_command(command, &[]);
+        }
+        PowerShellCompleter::new(Arc::new(Mutex::new(engine)))
+    }
+
     #[test]
     fn test_case_insensitive_completion() {
         let commands = vec![
@@ -343,7 +588,7 @@ mod tests {
             "Select-Object".to_string(),
             "ForEach-Object".to_string(),
         ];
-        let mut completer = PowerShellCompleter::new(commands);
+        let mut completer = completer_for(commands);
 
         // Test lowercase "select" should match "Select-Object"
         let completions = completer.complete("select", 6);
@@ -398,7 +643,7 @@ mod tests {
             "Where-Object".to_string(),
             "Select-Object".to_string(),
         ];
-        let mut completer = PowerShellCompleter::new(commands);
+        let mut completer = completer_for(commands);
 
         // Test "w" prefix should match both Write-Output and Where-Object
         let completions = completer.complete("w", 1);
@@ -425,7 +670,7 @@ mod tests {
             "Get-Content".to_string(),
             "Get-Process".to_string(),
         ];
-        let mut completer = PowerShellCompleter::new(commands);
+        let mut completer = completer_for(commands);
 
         // "get-c" should match both Get-ChildItem and Get-Content
         let completions = completer.complete("get-c", 5);
@@ -434,11 +679,113 @@ mod tests {
         assert_eq!(completions[1].value, "Get-Content");
     }
 
+    #[test]
+    fn test_validator_trailing_pipe_is_incomplete() {
+        let validator = PowerShellValidator;
+        assert!(matches!(
+            validator.validate("$files = Get-ChildItem |"),
+            ValidationResult::Incomplete
+        ));
+        assert!(matches!(
+            validator.validate("$files = Get-ChildItem | Sort-Object Name"),
+            ValidationResult::Complete
+        ));
+    }
+
+    #[test]
+    fn test_validator_reads_tokens() {
+        let validator = PowerShellValidator;
+        let incomplete = [
+            "if ($x) {",
+            "'an open\nstring",
+            "<# a comment\nstill open",
+            "Get-Item (",
+        ];
+        for input in incomplete {
+            assert!(
+                matches!(validator.validate(input), ValidationResult::Incomplete),
+                "{}",
+                input
+            );
+        }
+        // Braces in comments and strings don't count
+        let complete = ["$x = 1 # {", "'{' + \"(\"", "${odd{name} = 1", "$x = 1 $ 2"];
+        for input in complete {
+            assert!(
+                matches!(validator.validate(input), ValidationResult::Complete),
+                "{}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_session_completions_include_parameters_and_variables() {
+        let mut session = SessionBuilder::new()
+            .cmdlets(pwsh_cmdlets::register_all)
+            .build();
+        session.invoke("$total = 1").unwrap();
+
+        let mut completer = PowerShellCompleter::new(Arc::new(Mutex::new(session_completions(
+            session.evaluator(),
+        ))));
+        let completions = completer.complete("Get-ChildItem -Rec", 18);
+        assert_eq!(completions.len(), 1);
+        assert_eq!(completions[0].value, "-Recurse");
+        assert_eq!(completions[0].span, Span::new(14, 18));
+
+        let completions = completer.complete("Write-Output $tot", 17);
+        assert_eq!(completions[0].value, "$total");
+
+        let completions = completer.complete("ex", 2);
+        assert_eq!(completions[0].value, "exit");
+    }
+
+    #[test]
+    fn test_runtime_errors_point_at_the_failing_expression() {
+        let source = "$a = 1\n$b = $a / 0 + 2";
+        let mut session = SessionBuilder::new().build();
+        assert!(session.invoke(source).is_err());
+
+        assert_eq!(
+            session.error_position().unwrap(),
+            "At line:2 char:6\n+ $b = $a / 0 + 2\n+      ~~~~~~"
+        );
+    }
+
+    #[test]
+    fn test_every_output_item_is_printed() {
+        let mut session = SessionBuilder::new()
+            .cmdlets(pwsh_cmdlets::register_all)
+            .build();
+
+        let output = session.invoke("Write-Output 1,2,3").unwrap();
+        assert_eq!(
+            session.evaluator().format_output(&output),
+            vec!["1", "2", "3"]
+        );
+
+        let output = session.invoke("'a'\n$null\n@('b', 'c')").unwrap();
+        assert_eq!(
+            session.evaluator().format_output(&output),
+            vec!["a", "b", "c"]
+        );
+
+        if pwsh_cmdlets::GetProcessCmdlet::AVAILABLE {
+            let processes = session.invoke("Get-Process").unwrap();
+            assert!(processes.len() > 1);
+            // Processes print as one table: a row each under its header
+            let lines = session.evaluator().format_output(&processes);
+            assert_eq!(lines.len(), processes.len() + 4);
+            assert!(lines[1].ends_with("ProcessName"));
+        }
+    }
+
     #[test]
     fn test_cmdlet_names_include_set_content_for_autocomplete() {
         let mut commands = pwsh_cmdlets::cmdlet_names();
         commands.push("exit".to_string());
-        let mut completer = PowerShellCompleter::new(commands);
+        let mut completer = completer_for(commands);
 
         let completions = completer.complete("set", 3);
         assert!(completions.iter().any(|c| c.value == "Set-Content"));
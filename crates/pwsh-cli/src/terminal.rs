@@ -0,0 +1,74 @@
+/// The shell's host: the console host, plus the terminal's size and single key presses
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::terminal;
+use pwsh_runtime::{ConsoleHost, Host, Progress};
+use std::io::{IsTerminal, Write};
+
+/// Writes and reads as `ConsoleHost` does. When stdout is a terminal it
+/// also reports the terminal's size and reads keys without waiting for
+/// Enter, so long output can be paged.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TerminalHost;
+
+impl Host for TerminalHost {
+    fn write(&mut self, text: &str) {
+        ConsoleHost.write(text);
+    }
+
+    fn write_line(&mut self, text: &str) {
+        ConsoleHost.write_line(text);
+    }
+
+    fn write_error_line(&mut self, text: &str) {
+        ConsoleHost.write_error_line(text);
+    }
+
+    fn supports_ansi(&self) -> bool {
+        ConsoleHost.supports_ansi()
+    }
+
+    fn write_progress(&mut self, progress: &Progress) {
+        ConsoleHost.write_progress(progress);
+    }
+
+    fn read_line(&mut self, prompt: &str) -> Option<String> {
+        ConsoleHost.read_line(prompt)
+    }
+
+    fn window_size(&self) -> Option<(usize, usize)> {
+        if !std::io::stdout().is_terminal() {
+            return None;
+        }
+        let (columns, rows) = terminal::size().ok()?;
+        Some((columns as usize, rows as usize))
+    }
+
+    /// The prompt is shown in reverse video and erased once a key is
+    /// pressed; Escape and Ctrl+C read as `q`
+    fn read_key(&mut self, prompt: &str) -> Option<char> {
+        if !std::io::stdin().is_terminal() || terminal::enable_raw_mode().is_err() {
+            return ConsoleHost.read_key(prompt);
+        }
+        print!("\x1b[7m{}\x1b[0m", prompt);
+        let _ = std::io::stdout().flush();
+        let key = loop {
+            match event::read() {
+                Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => match key.code {
+                    KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        break Some('q')
+                    }
+                    KeyCode::Char(c) => break Some(c),
+                    KeyCode::Enter => break Some('\n'),
+                    KeyCode::Esc => break Some('q'),
+                    _ => {}
+                },
+                Ok(_) => {}
+                Err(_) => break None,
+            }
+        };
+        let _ = terminal::disable_raw_mode();
+        print!("\r\x1b[2K");
+        let _ = std::io::stdout().flush();
+        key
+    }
+}
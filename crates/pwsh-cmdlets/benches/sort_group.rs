@@ -0,0 +1,66 @@
+/// Sequential vs parallel Sort-Object and Group-Object
+///
+/// Prints the best of a few runs at each input size; PARALLEL_THRESHOLD in
+/// parallel.rs sits where the parallel column starts winning. Run with `cargo bench -p pwsh-cmdlets`.
+use pwsh_cmdlets::{GroupObjectCmdlet, SortObjectCmdlet};
+use pwsh_runtime::{Cmdlet, CmdletContext, Evaluator, PropertyMap, Value};
+use std::time::{Duration, Instant};
+
+const SIZES: [usize; 5] = [1_000, 5_000, 20_000, 50_000, 200_000];
+const RUNS: usize = 5;
+
+/// Objects with a Name and a Size, in a fixed pseudo-random order
+fn objects(count: usize) -> Vec<Value> {
+    let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+    (0..count)
+        .map(|_| {
+            seed = seed
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1_442_695_040_888_963_407);
+            let n = (seed >> 33) % 1_000;
+            let mut props = PropertyMap::new();
+            props.insert("Name", Value::String(format!("item{}", n % 97).into()));
+            props.insert("Size", Value::Number(n as f64));
+            Value::Object(props.into())
+        })
+        .collect()
+}
+
+/// The fastest of `RUNS` runs of a cmdlet over `input`
+fn time(cmdlet: &dyn Cmdlet, input: &[Value], property: &str, parallel: bool) -> Duration {
+    let mut evaluator = Evaluator::new();
+    (0..RUNS)
+        .map(|_| {
+            let context = CmdletContext::with_input(input.to_vec())
+                .with_parameter("Property".to_string(), Value::String(property.into()))
+                .with_parameter("Parallel".to_string(), Value::Boolean(parallel));
+            let start = Instant::now();
+            cmdlet.execute(context, &mut evaluator).unwrap();
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn main() {
+    let cmdlets: [(&str, &dyn Cmdlet, &str); 2] = [
+        ("Sort-Object", &SortObjectCmdlet, "Size"),
+        ("Group-Object", &GroupObjectCmdlet, "Name"),
+    ];
+    println!(
+        "{:<14}{:>10}{:>14}{:>14}",
+        "cmdlet", "items", "sequential", "parallel"
+    );
+    for (name, cmdlet, property) in cmdlets {
+        for size in SIZES {
+            let input = objects(size);
+            println!(
+                "{:<14}{:>10}{:>14?}{:>14?}",
+                name,
+                size,
+                time(cmdlet, &input, property, false),
+                time(cmdlet, &input, property, true)
+            );
+        }
+    }
+}
@@ -0,0 +1,86 @@
+/// ConvertFrom-Json cmdlet - reads JSON text into objects
+use pwsh_runtime::{Cmdlet, CmdletContext, ErrorCategory, ErrorRecord, RuntimeError, Value};
+
+/// ConvertFrom-Json reads JSON text into objects: JSON objects become
+/// objects with their properties in order, and a JSON array writes its
+/// items to the pipeline one by one. Lines piped in (as from Get-Content)
+/// are joined into one document first. Dates stay strings, so
+/// `ConvertTo-Json | ConvertFrom-Json` gives back the same text.
+pub struct ConvertFromJsonCmdlet;
+
+impl Cmdlet for ConvertFromJsonCmdlet {
+    fn name(&self) -> &str {
+        "ConvertFrom-Json"
+    }
+
+    fn parameters(&self) -> &[&str] {
+        &["InputObject"]
+    }
+
+    fn execute(
+        &self,
+        context: CmdletContext,
+        _evaluator: &mut pwsh_runtime::Evaluator,
+    ) -> Result<Vec<Value>, RuntimeError> {
+        // Support: ConvertFrom-Json $text as well as $text | ConvertFrom-Json
+        let text = match context
+            .get_parameter("InputObject")
+            .or_else(|| context.get_argument(0))
+        {
+            Some(value) => value.to_string(),
+            None => context
+                .pipeline_input
+                .iter()
+                .map(Value::to_string)
+                .collect::<Vec<_>>()
+                .join("\n"),
+        };
+        if text.trim().is_empty() {
+            return Ok(vec![]);
+        }
+
+        let value = Value::from_json(&text).map_err(|error| -> RuntimeError {
+            ErrorRecord::new(
+                ErrorCategory::InvalidData,
+                "InvalidJson,ConvertFrom-Json",
+                format!("ConvertFrom-Json: {}", error),
+            )
+            .into()
+        })?;
+        Ok(match value {
+            Value::Array(items) => items.unwrap_or_clone(),
+            other => vec![other],
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_convertfrom_json_joins_lines_and_enumerates_arrays() {
+        let mut evaluator = pwsh_runtime::Evaluator::new();
+        let lines = ["[", "  {\"Name\": \"a\"},", "  {\"Name\": \"b\"}", "]"];
+        let context =
+            CmdletContext::with_input(lines.iter().map(|l| Value::String((*l).into())).collect());
+        let result = ConvertFromJsonCmdlet
+            .execute(context, &mut evaluator)
+            .unwrap();
+        assert_eq!(result.len(), 2);
+        assert_eq!(
+            result[1].get_property("Name"),
+            Some(Value::String("b".into()))
+        );
+    }
+
+    #[test]
+    fn test_convertfrom_json_invalid_text() {
+        let mut evaluator = pwsh_runtime::Evaluator::new();
+        let context = CmdletContext::new().with_arguments(vec![Value::String("{\"a\":".into())]);
+        let error = ConvertFromJsonCmdlet
+            .execute(context, &mut evaluator)
+            .unwrap_err();
+        assert_eq!(error.error_id(), "InvalidJson,ConvertFrom-Json");
+    }
+}
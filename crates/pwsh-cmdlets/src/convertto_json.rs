@@ -0,0 +1,158 @@
+/// ConvertTo-Json cmdlet - writes objects as JSON text
+use pwsh_runtime::{
+    parse_switch, Cmdlet, CmdletContext, ErrorRecord, JsonOptions, RuntimeError, Value,
+};
+
+/// The deepest -Depth PowerShell accepts
+const MAX_DEPTH: usize = 100;
+
+/// -Depth: how many levels of nested objects to write, 0 to 100
+fn parse_depth(value: Option<&Value>) -> Result<Option<usize>, RuntimeError> {
+    let Some(value) = value else {
+        return Ok(None);
+    };
+    match value.to_number() {
+        Some(n) if n >= 0.0 && n <= MAX_DEPTH as f64 && n.fract() == 0.0 => Ok(Some(n as usize)),
+        _ => Err(ErrorRecord::invalid_argument(
+            "InvalidDepth,ConvertTo-Json",
+            format!(
+                "ConvertTo-Json: -Depth must be a whole number from 0 to {}, not {}",
+                MAX_DEPTH, value
+            ),
+        )
+        .into()),
+    }
+}
+
+/// ConvertTo-Json writes its input as one JSON string, indented unless
+/// -Compress is given. Several pipeline items are written as a JSON array,
+/// one item as itself, or as a one-item array with -AsArray. Properties keep
+/// their order, dates are written in ISO 8601, and enum values as numbers,
+/// or as their names with -EnumsAsStrings. Objects nested deeper than
+/// -Depth (2 by default) are written as their display text, with a warning.
+pub struct ConvertToJsonCmdlet;
+
+impl Cmdlet for ConvertToJsonCmdlet {
+    fn name(&self) -> &str {
+        "ConvertTo-Json"
+    }
+
+    fn parameters(&self) -> &[&str] {
+        &[
+            "InputObject",
+            "Depth",
+            "Compress",
+            "AsArray",
+            "EnumsAsStrings",
+        ]
+    }
+
+    fn execute(
+        &self,
+        context: CmdletContext,
+        evaluator: &mut pwsh_runtime::Evaluator,
+    ) -> Result<Vec<Value>, RuntimeError> {
+        let options = JsonOptions {
+            depth: parse_depth(context.get_parameter("Depth"))?
+                .unwrap_or(JsonOptions::default().depth),
+            enums_as_strings: parse_switch(
+                context.get_parameter("EnumsAsStrings"),
+                "ConvertTo-Json",
+            )?,
+            compress: parse_switch(context.get_parameter("Compress"), "ConvertTo-Json")?,
+        };
+        let as_array = parse_switch(context.get_parameter("AsArray"), "ConvertTo-Json")?;
+
+        // Support: ConvertTo-Json $value as well as $value | ConvertTo-Json
+        let input = match context
+            .get_parameter("InputObject")
+            .or_else(|| context.get_argument(0))
+        {
+            Some(value) => value.clone(),
+            None => match context.pipeline_input.len() {
+                0 if !as_array => return Ok(vec![]),
+                1 => context.pipeline_input[0].clone(),
+                _ => Value::Array(context.pipeline_input.into()),
+            },
+        };
+        let input = match input {
+            Value::Array(_) => input,
+            item if as_array => Value::Array(vec![item].into()),
+            item => item,
+        };
+
+        let (json, truncated) = input.to_json_with(&options);
+        if truncated {
+            evaluator.host_mut().write_warning_line(&format!(
+                "Resulting JSON is truncated as serialization has exceeded the set depth of {}.",
+                options.depth
+            ));
+        }
+        Ok(vec![Value::String(json.into())])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pwsh_runtime::PropertyMap;
+
+    fn run(context: CmdletContext) -> String {
+        let mut evaluator = pwsh_runtime::Evaluator::new();
+        ConvertToJsonCmdlet
+            .execute(context, &mut evaluator)
+            .unwrap()
+            .remove(0)
+            .to_string()
+    }
+
+    fn row(name: &str, size: f64) -> Value {
+        let mut props = PropertyMap::new();
+        props.insert("Name", Value::String(name.into()));
+        props.insert("Size", Value::Number(size));
+        Value::Object(props.into())
+    }
+
+    #[test]
+    fn test_convertto_json_indents_and_keeps_order() {
+        let context = CmdletContext::with_input(vec![row("a", 1.0)]);
+        assert_eq!(run(context), "{\n  \"Name\": \"a\",\n  \"Size\": 1\n}");
+
+        let context = CmdletContext::with_input(vec![row("a", 1.0), row("b", 2.5)])
+            .with_parameter("Compress".to_string(), Value::Boolean(true));
+        assert_eq!(
+            run(context),
+            r#"[{"Name":"a","Size":1},{"Name":"b","Size":2.5}]"#
+        );
+    }
+
+    #[test]
+    fn test_convertto_json_as_array_and_enums() {
+        let color = Value::EnumValue {
+            type_name: "Color".to_string(),
+            name: "Green".to_string(),
+            value: 5,
+        };
+        let context = CmdletContext::with_input(vec![color])
+            .with_parameter("AsArray".to_string(), Value::Boolean(true))
+            .with_parameter("EnumsAsStrings".to_string(), Value::Boolean(true))
+            .with_parameter("Compress".to_string(), Value::Boolean(true));
+        assert_eq!(run(context), r#"["Green"]"#);
+
+        let context = CmdletContext::new()
+            .with_parameter("AsArray".to_string(), Value::Boolean(true))
+            .with_parameter("Compress".to_string(), Value::Boolean(true));
+        assert_eq!(run(context), "[]");
+    }
+
+    #[test]
+    fn test_convertto_json_rejects_bad_depth() {
+        let mut evaluator = pwsh_runtime::Evaluator::new();
+        let context = CmdletContext::with_input(vec![Value::Number(1.0)])
+            .with_parameter("Depth".to_string(), Value::Number(101.0));
+        let error = ConvertToJsonCmdlet
+            .execute(context, &mut evaluator)
+            .unwrap_err();
+        assert_eq!(error.error_id(), "InvalidDepth,ConvertTo-Json");
+    }
+}
@@ -0,0 +1,165 @@
+/// The `Variable:` and `Function:` drives, which list the session's variables and functions
+use pwsh_parser::{Program, Statement, StatementKind};
+use pwsh_runtime::{Evaluator, Function, PropertyMap, RuntimeError, Value, WildcardPattern};
+use std::collections::BTreeMap;
+
+/// A drive backed by the session rather than the filesystem
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum SessionDrive {
+    Variable,
+    Function,
+}
+
+/// Split a path such as `Variable:`, `variable:\count` or `Function:Get-*` into
+/// its session drive and the name pattern after it (empty for the whole drive).
+/// Any other path, including filesystem drives like `C:\`, gives None.
+pub(crate) fn parse_drive_path(path: &str) -> Option<(SessionDrive, &str)> {
+    let (drive, rest) = path.split_once(':')?;
+    let drive = if drive.eq_ignore_ascii_case("Variable") {
+        SessionDrive::Variable
+    } else if drive.eq_ignore_ascii_case("Function") {
+        SessionDrive::Function
+    } else {
+        return None;
+    };
+    Some((drive, rest.trim_start_matches(['\\', '/'])))
+}
+
+/// The items of a session drive whose names match `pattern` (all of them when
+/// it is empty), sorted by name
+pub(crate) fn drive_items(
+    drive: SessionDrive,
+    pattern: &str,
+    evaluator: &Evaluator,
+) -> Result<Vec<(String, Value)>, RuntimeError> {
+    let pattern = (!pattern.is_empty())
+        .then(|| WildcardPattern::new(pattern))
+        .transpose()?;
+    let matches = |name: &str| pattern.as_ref().is_none_or(|p| p.is_match(name));
+
+    let items = match drive {
+        SessionDrive::Variable => evaluator
+            .variable_names()
+            .into_iter()
+            .filter(|name| matches(name))
+            .map(|name| {
+                let value = evaluator.get_variable(&name).unwrap_or(Value::Null);
+                let item = variable_item(&name, value);
+                (name, item)
+            })
+            .collect(),
+        SessionDrive::Function => {
+            // Scopes are listed outermost first, so an inner function replaces
+            // the one it shadows
+            let mut functions = BTreeMap::new();
+            for function in evaluator.functions() {
+                functions.insert(function.name.to_lowercase(), function);
+            }
+            functions
+                .into_values()
+                .filter(|function| matches(&function.name))
+                .map(|function| (function.name.clone(), function_item(function)))
+                .collect()
+        }
+    };
+    Ok(items)
+}
+
+fn variable_item(name: &str, value: Value) -> Value {
+    let mut props = PropertyMap::with_capacity(2);
+    props.insert("Name".to_string(), Value::String(name.into()));
+    props.insert("Value".to_string(), value);
+    Value::Object(props.into())
+}
+
+fn function_item(function: &Function) -> Value {
+    let mut props = PropertyMap::with_capacity(2);
+    props.insert(
+        "Name".to_string(),
+        Value::String(function.name.as_str().into()),
+    );
+    props.insert(
+        "Definition".to_string(),
+        Value::String(definition(function).into()),
+    );
+    Value::Object(props.into())
+}
+
+/// A function's source, printed back from its syntax tree
+fn definition(function: &Function) -> String {
+    let statement = Statement::new(
+        StatementKind::FunctionDef {
+            name: function.name.clone(),
+            parameters: function.parameters.clone(),
+            body: function.body.clone(),
+            is_filter: function.is_filter,
+            begin: function.begin.clone(),
+            process: function.process.clone(),
+            help: function.help.clone(),
+        },
+        function.body.span,
+    );
+    let program = Program {
+        statements: vec![statement],
+        trivia: Vec::new(),
+        requires: Default::default(),
+    };
+    pwsh_fmt::format_program(&program).trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pwsh_lexer::Lexer;
+    use pwsh_parser::Parser;
+
+    fn evaluator_after(script: &str) -> Evaluator {
+        let mut evaluator = Evaluator::new();
+        let tokens = Lexer::new(script).tokenize().unwrap();
+        let program = Parser::new(tokens).parse().unwrap();
+        evaluator.eval(program).unwrap();
+        evaluator
+    }
+
+    fn names(items: &[(String, Value)]) -> Vec<&str> {
+        items.iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_parse_drive_path() {
+        assert_eq!(
+            parse_drive_path("Variable:"),
+            Some((SessionDrive::Variable, ""))
+        );
+        assert_eq!(
+            parse_drive_path("function:\\Get-*"),
+            Some((SessionDrive::Function, "Get-*"))
+        );
+        assert_eq!(parse_drive_path("C:\\temp"), None);
+        assert_eq!(parse_drive_path("./src"), None);
+    }
+
+    #[test]
+    fn test_variable_drive_lists_names_and_values() {
+        let evaluator = evaluator_after("$count = 3\n$name = 'x'");
+        let items = drive_items(SessionDrive::Variable, "", &evaluator).unwrap();
+        assert!(names(&items).contains(&"count"));
+
+        let items = drive_items(SessionDrive::Variable, "co*", &evaluator).unwrap();
+        assert_eq!(names(&items), vec!["count"]);
+        assert_eq!(items[0].1.get_property("Value"), Some(Value::Number(3.0)));
+    }
+
+    #[test]
+    fn test_function_drive_lists_definitions() {
+        let evaluator = evaluator_after("function Get-Size($x) { $x * 2 }\nfilter Keep { $_ }");
+        let items = drive_items(SessionDrive::Function, "", &evaluator).unwrap();
+        assert_eq!(names(&items), vec!["Get-Size", "Keep"]);
+        assert_eq!(
+            items[0].1.get_property("Definition"),
+            Some(Value::String(
+                "function Get-Size($x) {\n    $x * 2\n}".into()
+            ))
+        );
+    }
+}
@@ -9,6 +9,10 @@ impl Cmdlet for ForEachObjectCmdlet {
         "ForEach-Object"
     }
 
+    fn parameters(&self) -> &[&str] {
+        &["MemberName"]
+    }
+
     fn execute(
         &self,
         context: CmdletContext,
@@ -20,9 +24,12 @@ impl Cmdlet for ForEachObjectCmdlet {
             let mut results = Vec::new();
 
             for item in context.pipeline_input {
-                // Execute the script block with $_ set to the current item
-                let result = evaluator.execute_script_block(script_block, item)?;
-                results.push(result);
+                // Execute the script block with $_ set to the current item;
+                // an array it writes goes down the pipeline item by item
+                match evaluator.execute_script_block(script_block, item)? {
+                    Value::Array(items) => results.extend(items.unwrap_or_clone()),
+                    result => results.push(result),
+                }
             }
             return Ok(results);
         }
@@ -51,7 +58,7 @@ impl Cmdlet for ForEachObjectCmdlet {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::HashMap;
+    use pwsh_runtime::PropertyMap;
 
     #[test]
     fn test_foreach_object_no_params() {
@@ -67,23 +74,23 @@ mod tests {
     fn test_foreach_object_with_member_name() {
         let cmdlet = ForEachObjectCmdlet;
 
-        let mut obj1 = HashMap::new();
-        obj1.insert("Name".to_string(), Value::String("Object1".to_string()));
+        let mut obj1 = PropertyMap::new();
+        obj1.insert("Name".to_string(), Value::String("Object1".into()));
         obj1.insert("Value".to_string(), Value::Number(10.0));
 
-        let mut obj2 = HashMap::new();
-        obj2.insert("Name".to_string(), Value::String("Object2".to_string()));
+        let mut obj2 = PropertyMap::new();
+        obj2.insert("Name".to_string(), Value::String("Object2".into()));
         obj2.insert("Value".to_string(), Value::Number(20.0));
 
-        let input = vec![Value::Object(obj1), Value::Object(obj2)];
+        let input = vec![Value::Object(obj1.into()), Value::Object(obj2.into())];
         let context = CmdletContext::with_input(input)
-            .with_parameter("MemberName".to_string(), Value::String("Name".to_string()));
+            .with_parameter("MemberName".to_string(), Value::String("Name".into()));
 
         let mut evaluator = pwsh_runtime::Evaluator::new();
         let result = cmdlet.execute(context, &mut evaluator).unwrap();
         assert_eq!(result.len(), 2);
-        assert_eq!(result[0], Value::String("Object1".to_string()));
-        assert_eq!(result[1], Value::String("Object2".to_string()));
+        assert_eq!(result[0], Value::String("Object1".into()));
+        assert_eq!(result[1], Value::String("Object2".into()));
     }
 
     #[test]
@@ -111,6 +118,7 @@ mod tests {
         // Should return [2, 4, 6, 8, 10, 12, 14, 16, 18, 20]
         match result {
             Value::Array(values) => {
+                let values = values.read();
                 assert_eq!(values.len(), 10);
                 for (i, val) in values.iter().enumerate() {
                     assert_eq!(*val, Value::Number(((i + 1) * 2) as f64));
@@ -0,0 +1,119 @@
+/// Format-List cmdlet - writes each object's properties as `Name : Value` lines
+use pwsh_runtime::{
+    parse_property_list, select_properties, unroll_to_items, Cmdlet, CmdletContext, DisplayLimits,
+    RuntimeError, Value, WildcardPattern,
+};
+
+/// Format-List writes each input object as lines of text, one
+/// `Name : Value` line per property with the names lined up, and an empty
+/// line between objects. -Property (positionally, with wildcards) picks the
+/// properties to show. Collections in property values show at most
+/// $FormatEnumerationLimit items (4 unless set; -1 for all of them).
+pub struct FormatListCmdlet;
+
+impl Cmdlet for FormatListCmdlet {
+    fn name(&self) -> &str {
+        "Format-List"
+    }
+
+    fn parameters(&self) -> &[&str] {
+        &["Property", "InputObject"]
+    }
+
+    fn execute(
+        &self,
+        context: CmdletContext,
+        evaluator: &mut pwsh_runtime::Evaluator,
+    ) -> Result<Vec<Value>, RuntimeError> {
+        // Support: Format-List Name, Port as well as -Property Name, Port
+        let mut names = parse_property_list(context.get_parameter("Property"));
+        for argument in &context.arguments {
+            names.extend(parse_property_list(Some(argument)));
+        }
+        let patterns = names
+            .iter()
+            .map(|name| WildcardPattern::new(name))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let items = match context.get_parameter("InputObject") {
+            Some(input) => unroll_to_items(vec![input.clone()]),
+            None => context.pipeline_input,
+        };
+
+        let limits = evaluator.display_limits(DisplayLimits::default().depth);
+        let mut lines = Vec::new();
+        for (index, item) in items.into_iter().enumerate() {
+            let item = if patterns.is_empty() {
+                item
+            } else {
+                select_properties(item, &patterns)
+            };
+            if index > 0 {
+                lines.push(Value::String("".into()));
+            }
+            lines.extend(
+                limits
+                    .list_lines(&item)
+                    .into_iter()
+                    .map(|line| Value::String(line.into())),
+            );
+        }
+        Ok(lines)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pwsh_runtime::PropertyMap;
+
+    fn server(name: &str, ports: &[f64]) -> Value {
+        let mut props = PropertyMap::new();
+        props.insert("Name", Value::String(name.into()));
+        props.insert(
+            "Ports",
+            Value::Array(
+                ports
+                    .iter()
+                    .map(|&p| Value::Number(p))
+                    .collect::<Vec<_>>()
+                    .into(),
+            ),
+        );
+        Value::Object(props.into())
+    }
+
+    fn lines(result: Vec<Value>) -> Vec<String> {
+        result.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn test_format_list_lines_up_properties() {
+        let mut evaluator = pwsh_runtime::Evaluator::new();
+        let context = CmdletContext::with_input(vec![
+            server("web", &[80.0, 443.0]),
+            server("db", &[1.0, 2.0, 3.0, 4.0, 5.0]),
+        ]);
+        let result = FormatListCmdlet.execute(context, &mut evaluator).unwrap();
+        assert_eq!(
+            lines(result),
+            vec![
+                "Name  : web",
+                "Ports : {80, 443}",
+                "",
+                "Name  : db",
+                "Ports : {1, 2, 3, 4…}",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_list_property_and_enumeration_limit() {
+        let mut evaluator = pwsh_runtime::Evaluator::new();
+        evaluator.set_variable("FormatEnumerationLimit", Value::Number(-1.0));
+        let context = CmdletContext::with_input(vec![server("db", &[1.0, 2.0, 3.0, 4.0, 5.0])])
+            .with_parameter("Property".to_string(), Value::String("P*".into()));
+        let result = FormatListCmdlet.execute(context, &mut evaluator).unwrap();
+        assert_eq!(lines(result), vec!["Ports : {1, 2, 3, 4, 5}"]);
+    }
+}
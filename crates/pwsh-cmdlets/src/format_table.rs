@@ -0,0 +1,202 @@
+/// Format-Table cmdlet - writes objects as the rows of a table
+use pwsh_runtime::{
+    parse_property_list, parse_switch, unroll_to_items, Alignment, Cmdlet, CmdletContext,
+    FormatContext, RuntimeError, TableColumn, TableView, Value, View, WildcardPattern,
+};
+
+/// The columns for `names`, in the order given: a wildcard pattern stands
+/// for the first object's properties it matches. Numbers keep to the right
+/// of their column, as PowerShell shows them.
+fn columns_for(first: &Value, names: &[String]) -> Result<Vec<TableColumn>, RuntimeError> {
+    let properties: Vec<String> = first
+        .properties()
+        .map(|props| props.keys().map(|name| name.to_string()).collect())
+        .unwrap_or_default();
+    let mut selected: Vec<String> = Vec::new();
+    for name in names {
+        let pattern = WildcardPattern::new(name)?;
+        if pattern.is_literal() {
+            selected.push(name.clone());
+            continue;
+        }
+        for property in properties.iter().filter(|p| pattern.is_match(p)) {
+            if !selected.iter().any(|s| s.eq_ignore_ascii_case(property)) {
+                selected.push(property.clone());
+            }
+        }
+    }
+    Ok(selected
+        .iter()
+        .map(|name| {
+            let alignment = match first.get_property(name) {
+                Some(Value::Number(_)) => Alignment::Right,
+                _ => Alignment::Left,
+            };
+            TableColumn {
+                alignment,
+                ..TableColumn::property(name)
+            }
+        })
+        .collect())
+}
+
+/// Format-Table writes its input objects as lines of text: a header row,
+/// its underline, and a row per object. Without -Property the columns are
+/// those of the table view for the first object's type, as the console
+/// shows it, or else the first object's properties. -Property (positionally,
+/// with wildcards) picks the columns. Input without properties is written
+/// as its text. Tables fit the host's window: columns narrow and cells too
+/// long are cut short with `…`, or carried onto more lines with -Wrap.
+/// -AutoSize sizes columns to their contents instead of the view's widths.
+pub struct FormatTableCmdlet;
+
+impl Cmdlet for FormatTableCmdlet {
+    fn name(&self) -> &str {
+        "Format-Table"
+    }
+
+    fn parameters(&self) -> &[&str] {
+        &["Property", "InputObject", "Wrap", "AutoSize"]
+    }
+
+    fn execute(
+        &self,
+        context: CmdletContext,
+        evaluator: &mut pwsh_runtime::Evaluator,
+    ) -> Result<Vec<Value>, RuntimeError> {
+        // Support: Format-Table Name, Port as well as -Property Name, Port
+        let mut names = parse_property_list(context.get_parameter("Property"));
+        for argument in &context.arguments {
+            names.extend(parse_property_list(Some(argument)));
+        }
+
+        let wrap = parse_switch(context.get_parameter("Wrap"), "Format-Table")?;
+        let auto_size = parse_switch(context.get_parameter("AutoSize"), "Format-Table")?;
+        let items = match context.get_parameter("InputObject") {
+            Some(input) => unroll_to_items(vec![input.clone()]),
+            None => context.pipeline_input,
+        };
+        let Some(first) = items.iter().find(|item| item.properties().is_some()) else {
+            return Ok(items
+                .into_iter()
+                .filter(|item| *item != Value::Null)
+                .map(|item| Value::String(item.to_string().into()))
+                .collect());
+        };
+
+        let mut view = match evaluator.formats().view_for(first) {
+            Some(View::Table(view)) if names.is_empty() => view.clone(),
+            _ => {
+                if names.is_empty() {
+                    names = first
+                        .properties()
+                        .map(|props| props.keys().map(|name| name.to_string()).collect())
+                        .unwrap_or_default();
+                }
+                TableView {
+                    type_names: Vec::new(),
+                    columns: columns_for(first, &names)?,
+                    group_by: None,
+                    wrap: false,
+                    auto_size: false,
+                }
+            }
+        };
+        view.wrap |= wrap;
+        view.auto_size |= auto_size;
+        let format = FormatContext {
+            width: evaluator.host().window_size().map(|(columns, _)| columns),
+            ..FormatContext::default()
+        };
+
+        // Objects go in the table; anything else is written where it came
+        let mut lines = Vec::new();
+        let mut rows: Vec<&Value> = Vec::new();
+        for item in &items {
+            if item.properties().is_some() {
+                rows.push(item);
+                continue;
+            }
+            if !rows.is_empty() {
+                lines.extend(view.render(&rows, &format));
+                rows.clear();
+            }
+            if *item != Value::Null {
+                lines.push(item.to_string());
+            }
+        }
+        if !rows.is_empty() {
+            lines.extend(view.render(&rows, &format));
+        }
+        Ok(lines
+            .into_iter()
+            .map(|line| Value::String(line.into()))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pwsh_runtime::PropertyMap;
+
+    fn server(name: &str, port: f64) -> Value {
+        let mut props = PropertyMap::new();
+        props.insert("Name", Value::String(name.into()));
+        props.insert("Port", Value::Number(port));
+        props.insert("Region", Value::String("eu".into()));
+        Value::Object(props.into())
+    }
+
+    fn lines(result: Vec<Value>) -> Vec<String> {
+        result.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn test_format_table_shows_every_property() {
+        let mut evaluator = pwsh_runtime::Evaluator::new();
+        let context = CmdletContext::with_input(vec![server("web", 80.0), server("db", 5432.0)]);
+        let result = FormatTableCmdlet.execute(context, &mut evaluator).unwrap();
+        assert_eq!(
+            lines(result),
+            vec![
+                "",
+                "Name Port Region",
+                "---- ---- ------",
+                "web    80 eu",
+                "db   5432 eu",
+                "",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_table_property_picks_columns() {
+        let mut evaluator = pwsh_runtime::Evaluator::new();
+        let context = CmdletContext::with_input(vec![server("web", 80.0), Value::Number(7.0)])
+            .with_arguments(vec![Value::Array(
+                vec![Value::String("Reg*".into()), Value::String("Name".into())].into(),
+            )]);
+        let result = FormatTableCmdlet.execute(context, &mut evaluator).unwrap();
+        assert_eq!(
+            lines(result),
+            vec!["", "Region Name", "------ ----", "eu     web", "", "7"]
+        );
+    }
+
+    #[test]
+    fn test_format_table_uses_the_view_for_the_type() {
+        let mut evaluator = pwsh_runtime::Evaluator::new();
+        let mut process = PropertyMap::from([
+            ("Name", Value::String("pwsh".into())),
+            ("Id", Value::Number(42.0)),
+            ("CPU", Value::Number(1.0)),
+            ("WorkingSet", Value::Number(1048576.0)),
+        ]);
+        process.insert_type_name("System.Diagnostics.Process");
+        let context = CmdletContext::with_input(vec![Value::Object(process.into())]);
+        let result = lines(FormatTableCmdlet.execute(context, &mut evaluator).unwrap());
+        assert_eq!(result[1], "     WS(M)     CPU(s)      Id ProcessName");
+        assert_eq!(result[3], "      1.00       1.00      42 pwsh");
+    }
+}
@@ -1,26 +1,18 @@
 /// Get-ChildItem cmdlet - lists files and directories in the file system
-use pwsh_runtime::{Cmdlet, CmdletContext, RuntimeError, Value};
-use std::collections::HashMap;
+use crate::drives::{drive_items, parse_drive_path};
+use pwsh_runtime::{
+    parse_number, parse_switch, ActionPreference, Capability, Cmdlet, CmdletContext, ErrorRecord,
+    Evaluator, PropertyMap, RuntimeError, Value, WildcardPattern,
+};
+use std::collections::HashSet;
 use std::fs;
 use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 
-fn get_parameter_ci<'a>(context: &'a CmdletContext, name: &str) -> Option<&'a Value> {
-    // Try exact match first
-    if let Some(v) = context.parameters.get(name) {
-        return Some(v);
-    }
-
-    let name_lower = name.to_lowercase();
-    context
-        .parameters
-        .iter()
-        .find(|(k, _)| k.to_lowercase() == name_lower)
-        .map(|(_, v)| v)
-}
-
-fn parse_string_patterns(value: Option<&Value>) -> Vec<String> {
-    match value {
+/// Wildcard patterns from a string or list, where a string may also hold
+/// several comma-separated patterns
+fn parse_patterns(value: Option<&Value>) -> Result<Vec<WildcardPattern>, RuntimeError> {
+    let patterns: Vec<String> = match value {
         None => vec![],
         Some(Value::String(s)) => s
             .split(',')
@@ -29,6 +21,7 @@ fn parse_string_patterns(value: Option<&Value>) -> Vec<String> {
             .map(|p| p.to_string())
             .collect(),
         Some(Value::Array(items)) => items
+            .read()
             .iter()
             .filter_map(|v| match v {
                 Value::String(s) => Some(s.clone()),
@@ -43,7 +36,8 @@ fn parse_string_patterns(value: Option<&Value>) -> Vec<String> {
             })
             .collect(),
         Some(other) => vec![other.to_string()],
-    }
+    };
+    patterns.iter().map(|p| WildcardPattern::new(p)).collect()
 }
 
 fn resolve_path(path: &str) -> Result<PathBuf, RuntimeError> {
@@ -52,73 +46,23 @@ fn resolve_path(path: &str) -> Result<PathBuf, RuntimeError> {
         return Ok(p);
     }
 
-    let cwd = std::env::current_dir().map_err(|e| {
-        RuntimeError::InvalidOperation(format!("Failed to get current directory: {}", e))
-    })?;
-    Ok(cwd.join(p))
+    Ok(current_dir()?.join(p))
 }
 
-fn wildcard_match_case_insensitive(pattern: &str, text: &str) -> bool {
-    // Supports: '*' (0+ chars) and '?' (exactly 1 char)
-    let p = pattern.as_bytes();
-    let t = text.as_bytes();
-
-    let mut pi: usize = 0;
-    let mut ti: usize = 0;
-    let mut star_idx: Option<usize> = None;
-    let mut match_ti: usize = 0;
-
-    while ti < t.len() {
-        if pi < p.len() && (p[pi] == b'?' || p[pi].eq_ignore_ascii_case(&t[ti])) {
-            pi += 1;
-            ti += 1;
-        } else if pi < p.len() && p[pi] == b'*' {
-            star_idx = Some(pi);
-            pi += 1;
-            match_ti = ti;
-        } else if let Some(si) = star_idx {
-            pi = si + 1;
-            match_ti += 1;
-            ti = match_ti;
-        } else {
-            return false;
-        }
-    }
-
-    while pi < p.len() && p[pi] == b'*' {
-        pi += 1;
-    }
-
-    pi == p.len()
+fn current_dir() -> Result<PathBuf, RuntimeError> {
+    std::env::current_dir().map_err(|e| {
+        ErrorRecord::file_system(
+            &e,
+            "Get-ChildItem",
+            ".",
+            format!("Failed to get current directory: {}", e),
+        )
+        .into()
+    })
 }
 
-fn matches_any_pattern(name: &str, patterns: &[String]) -> bool {
-    patterns
-        .iter()
-        .any(|p| wildcard_match_case_insensitive(p, name))
-}
-
-fn parse_switch(value: Option<&Value>) -> Result<bool, RuntimeError> {
-    match value {
-        None => Ok(false),
-        Some(Value::Boolean(b)) => Ok(*b),
-        Some(Value::Number(n)) => Ok(*n != 0.0),
-        Some(Value::String(s)) => {
-            let v = s.trim().to_ascii_lowercase();
-            match v.as_str() {
-                "true" | "t" | "1" | "yes" | "y" => Ok(true),
-                "false" | "f" | "0" | "no" | "n" => Ok(false),
-                _ => Err(RuntimeError::InvalidOperation(format!(
-                    "Invalid boolean value: {}",
-                    s
-                ))),
-            }
-        }
-        Some(other) => Err(RuntimeError::InvalidOperation(format!(
-            "Invalid boolean value: {}",
-            other
-        ))),
-    }
+fn matches_any_pattern(name: &str, patterns: &[WildcardPattern]) -> bool {
+    patterns.iter().any(|p| p.is_match(name))
 }
 
 fn parse_optional_depth(value: Option<&Value>) -> Result<Option<usize>, RuntimeError> {
@@ -126,30 +70,35 @@ fn parse_optional_depth(value: Option<&Value>) -> Result<Option<usize>, RuntimeE
         None => Ok(None),
         Some(Value::Number(n)) => {
             if *n < 0.0 {
-                return Err(RuntimeError::InvalidOperation(
+                return Err(ErrorRecord::invalid_argument(
+                    "InvalidDepth,Get-ChildItem",
                     "Depth must be a non-negative number".to_string(),
-                ));
+                )
+                .into());
             }
             Ok(Some(*n as usize))
         }
-        Some(Value::String(s)) => {
-            let parsed: usize = s.trim().parse().map_err(|_| {
-                RuntimeError::InvalidOperation(format!("Depth must be an integer, got: {}", s))
-            })?;
-            Ok(Some(parsed))
-        }
-        Some(other) => Err(RuntimeError::InvalidOperation(format!(
-            "Depth must be a number, got: {}",
-            other
-        ))),
+        Some(Value::String(s)) => match parse_number(s) {
+            Some(n) if n >= 0.0 && n.fract() == 0.0 => Ok(Some(n as usize)),
+            _ => Err(ErrorRecord::invalid_argument(
+                "InvalidDepth,Get-ChildItem",
+                format!("Depth must be an integer, got: {}", s),
+            )
+            .into()),
+        },
+        Some(other) => Err(ErrorRecord::invalid_argument(
+            "InvalidDepth,Get-ChildItem",
+            format!("Depth must be a number, got: {}", other),
+        )
+        .into()),
     }
 }
 
 fn should_output_name(
     name: &str,
-    filter_patterns: &[String],
-    include_patterns: &[String],
-    exclude_patterns: &[String],
+    filter_patterns: &[WildcardPattern],
+    include_patterns: &[WildcardPattern],
+    exclude_patterns: &[WildcardPattern],
 ) -> bool {
     if !filter_patterns.is_empty() && !matches_any_pattern(name, filter_patterns) {
         return false;
@@ -163,32 +112,104 @@ fn should_output_name(
     true
 }
 
-fn collect_directory_items(
-    root: &Path,
+/// Where failures below the root directory go: each is a non-terminating
+/// error, so one unreadable subdirectory doesn't end the listing
+struct ErrorSink<'a> {
+    evaluator: &'a mut Evaluator,
+    action: ActionPreference,
+}
+
+impl ErrorSink<'_> {
+    fn report(&mut self, record: ErrorRecord) -> Result<(), RuntimeError> {
+        self.evaluator.write_error(record.into(), self.action)
+    }
+
+    fn warn(&mut self, text: &str) {
+        self.evaluator.host_mut().write_warning_line(text);
+    }
+}
+
+/// Which subdirectories a listing descends into
+struct Traversal {
     recurse: bool,
     max_depth: Option<usize>,
-    filter_patterns: &[String],
-    include_patterns: &[String],
-    exclude_patterns: &[String],
+    /// Enter symlinked directories too (-FollowSymlink)
+    follow_symlinks: bool,
+}
+
+/// What identifies a directory however it is reached: device and inode on
+/// Unix, the canonical path elsewhere
+#[cfg(unix)]
+type DirectoryId = (u64, u64);
+#[cfg(not(unix))]
+type DirectoryId = PathBuf;
+
+#[cfg(unix)]
+fn directory_id(path: &Path) -> std::io::Result<DirectoryId> {
+    use std::os::unix::fs::MetadataExt;
+
+    let metadata = fs::metadata(path)?;
+    Ok((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn directory_id(path: &Path) -> std::io::Result<DirectoryId> {
+    fs::canonicalize(path)
+}
+
+fn collect_directory_items(
+    root: &Path,
+    traversal: &Traversal,
+    filter_patterns: &[WildcardPattern],
+    include_patterns: &[WildcardPattern],
+    exclude_patterns: &[WildcardPattern],
+    errors: &mut ErrorSink,
 ) -> Result<Vec<Value>, RuntimeError> {
     // Depth is measured in "directory hops" from the root.
     // depth=0 means: list only the root directory entries (no recursion).
     let mut items: Vec<Value> = Vec::new();
     let mut stack: Vec<(PathBuf, usize)> = vec![(root.to_path_buf(), 0)];
+    // Directories entered so far, when following symlinks: a link whose
+    // target is among them (such as a link back to an ancestor) is not entered
+    let mut visited: HashSet<DirectoryId> = HashSet::new();
+    if traversal.follow_symlinks {
+        visited.extend(directory_id(root).ok());
+    }
 
     while let Some((dir, depth)) = stack.pop() {
-        let entries = fs::read_dir(&dir).map_err(|e| {
-            RuntimeError::InvalidOperation(format!(
-                "Failed to read directory '{}': {}",
-                dir.display(),
-                e
-            ))
-        })?;
+        // A deep tree can take long enough to run into the time limit
+        errors.evaluator.check_time_limit()?;
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                let record = ErrorRecord::file_system(
+                    &e,
+                    "Get-ChildItem",
+                    dir.display(),
+                    format!("Failed to read directory '{}': {}", dir.display(), e),
+                );
+                // Only the directory that was asked for must be readable
+                if depth == 0 {
+                    return Err(record.into());
+                }
+                errors.report(record)?;
+                continue;
+            }
+        };
 
         for entry in entries {
-            let entry = entry.map_err(|e| {
-                RuntimeError::InvalidOperation(format!("Failed to read directory entry: {}", e))
-            })?;
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(e) => {
+                    errors.report(ErrorRecord::file_system(
+                        &e,
+                        "Get-ChildItem",
+                        dir.display(),
+                        format!("Failed to read directory entry: {}", e),
+                    ))?;
+                    continue;
+                }
+            };
 
             let entry_path = entry.path();
             let name = entry.file_name().to_string_lossy().to_string();
@@ -196,23 +217,65 @@ fn collect_directory_items(
             // Decide recursion separately from output filtering:
             // we still want to traverse subdirectories even when filter/include don't match
             // directory names (e.g. -Recurse -Filter *.rs).
-            if recurse {
-                let within_depth = max_depth.map(|d| depth < d).unwrap_or(true);
+            if traversal.recurse {
+                let within_depth = traversal.max_depth.map(|d| depth < d).unwrap_or(true);
                 if within_depth {
-                    let ft = fs::symlink_metadata(&entry_path)
-                        .map(|m| m.file_type())
-                        .map_err(|e| {
-                            RuntimeError::InvalidOperation(format!(
-                                "Failed to read metadata for '{}': {}",
+                    match fs::symlink_metadata(&entry_path) {
+                        Ok(metadata) => {
+                            let is_symlink = metadata.file_type().is_symlink();
+                            // Symlinked directories are only entered with -FollowSymlink,
+                            // and then only once per target, so a link back to an
+                            // ancestor can't loop forever
+                            let enter = if !entry_path.is_dir()
+                                || (is_symlink && !traversal.follow_symlinks)
+                            {
+                                false
+                            } else if !traversal.follow_symlinks {
+                                true
+                            } else {
+                                match directory_id(&entry_path) {
+                                    Ok(id) => {
+                                        let first_visit = visited.insert(id);
+                                        if is_symlink && !first_visit {
+                                            errors.warn(&format!(
+                                                "Skipping '{}': its target was already listed",
+                                                entry_path.display()
+                                            ));
+                                        }
+                                        first_visit || !is_symlink
+                                    }
+                                    Err(e) => {
+                                        errors.report(ErrorRecord::file_system(
+                                            &e,
+                                            "Get-ChildItem",
+                                            entry_path.display(),
+                                            format!(
+                                                "Failed to follow '{}': {}",
+                                                entry_path.display(),
+                                                e
+                                            ),
+                                        ))?;
+                                        false
+                                    }
+                                }
+                            };
+                            if enter {
+                                stack.push((entry_path.clone(), depth + 1));
+                            }
+                        }
+                        Err(e) => {
+                            errors.report(ErrorRecord::file_system(
+                                &e,
+                                "Get-ChildItem",
                                 entry_path.display(),
-                                e
-                            ))
-                        })?;
-
-                    let is_symlink = ft.is_symlink();
-                    // Avoid infinite loops: do not recurse into symlinked directories.
-                    if !is_symlink && entry_path.is_dir() {
-                        stack.push((entry_path.clone(), depth + 1));
+                                format!(
+                                    "Failed to read metadata for '{}': {}",
+                                    entry_path.display(),
+                                    e
+                                ),
+                            ))?;
+                            continue;
+                        }
                     }
                 }
             }
@@ -221,7 +284,11 @@ fn collect_directory_items(
                 continue;
             }
 
-            items.push(build_file_object(&entry_path, name)?);
+            match build_file_object(&entry_path, name) {
+                Ok(item) => items.push(item),
+                Err(RuntimeError::Record(record)) => errors.report(*record)?,
+                Err(e) => return Err(e),
+            }
         }
     }
 
@@ -291,13 +358,21 @@ fn system_time_to_unix_epoch_seconds(t: SystemTime) -> f64 {
 }
 
 fn build_file_object(path: &Path, name: String) -> Result<Value, RuntimeError> {
-    let metadata = fs::metadata(path).map_err(|e| {
-        RuntimeError::InvalidOperation(format!(
-            "Failed to read metadata for '{}': {}",
+    let link_metadata = fs::symlink_metadata(path).map_err(|e| {
+        ErrorRecord::file_system(
+            &e,
+            "Get-ChildItem",
             path.display(),
-            e
-        ))
+            format!("Failed to read metadata for '{}': {}", path.display(), e),
+        )
     })?;
+    let is_symlink = link_metadata.file_type().is_symlink();
+    // A link is described by its target; a dangling one by the link itself
+    let metadata = if is_symlink {
+        fs::metadata(path).unwrap_or(link_metadata)
+    } else {
+        link_metadata
+    };
 
     let is_dir = metadata.is_dir();
 
@@ -319,15 +394,36 @@ fn build_file_object(path: &Path, name: String) -> Result<Value, RuntimeError> {
         .map(|e| format!(".{}", e))
         .unwrap_or_default();
 
-    let mut props = HashMap::with_capacity(6);
-    props.insert("Name".to_string(), Value::String(name));
-    props.insert("Extension".to_string(), Value::String(extension));
+    let (link_type, link_target) = if is_symlink {
+        let target = fs::read_link(path)
+            .map(|t| Value::String(t.display().to_string().into()))
+            .unwrap_or(Value::Null);
+        (Value::String("SymbolicLink".into()), target)
+    } else {
+        (Value::Null, Value::Null)
+    };
+
+    let mut props = PropertyMap::with_capacity(9);
+    props.insert("Name".to_string(), Value::String(name.into()));
+    props.insert(
+        "FullName".to_string(),
+        Value::String(path.display().to_string().into()),
+    );
+    props.insert("Extension".to_string(), Value::String(extension.into()));
     props.insert("Length".to_string(), Value::Number(length));
     props.insert("LastWriteTime".to_string(), Value::Number(last_write_time));
-    props.insert("Mode".to_string(), Value::String(mode));
+    props.insert("Mode".to_string(), Value::String(mode.into()));
     props.insert("Directory".to_string(), Value::Boolean(is_dir));
+    props.insert("LinkType".to_string(), link_type);
+    props.insert("LinkTarget".to_string(), link_target);
+    props.insert_type_name("System.IO.FileSystemInfo");
+    props.insert_type_name(if is_dir {
+        "System.IO.DirectoryInfo"
+    } else {
+        "System.IO.FileInfo"
+    });
 
-    Ok(Value::Object(props))
+    Ok(Value::Object(props.into()))
 }
 
 /// Get-ChildItem cmdlet retrieves child items (files/directories) in a location
@@ -338,37 +434,75 @@ impl Cmdlet for GetChildItemCmdlet {
         "Get-ChildItem"
     }
 
+    fn parameters(&self) -> &[&str] {
+        &[
+            "Path",
+            "Filter",
+            "Include",
+            "Exclude",
+            "Recurse",
+            "Depth",
+            "FollowSymlink",
+        ]
+    }
+
+    fn capabilities(&self) -> &[Capability] {
+        &[Capability::FileSystem]
+    }
+
     fn execute(
         &self,
         context: CmdletContext,
-        _evaluator: &mut pwsh_runtime::Evaluator,
+        evaluator: &mut Evaluator,
     ) -> Result<Vec<Value>, RuntimeError> {
         // Parameters
-        let filter_patterns = parse_string_patterns(get_parameter_ci(&context, "Filter"));
-        let include_patterns = parse_string_patterns(get_parameter_ci(&context, "Include"));
-        let exclude_patterns = parse_string_patterns(get_parameter_ci(&context, "Exclude"));
-
-        let recurse = parse_switch(get_parameter_ci(&context, "Recurse"))?;
-        let max_depth = parse_optional_depth(get_parameter_ci(&context, "Depth"))?;
+        let filter_patterns = parse_patterns(context.get_parameter("Filter"))?;
+        let include_patterns = parse_patterns(context.get_parameter("Include"))?;
+        let exclude_patterns = parse_patterns(context.get_parameter("Exclude"))?;
+
+        let traversal = Traversal {
+            recurse: parse_switch(context.get_parameter("Recurse"), "Get-ChildItem")?,
+            max_depth: parse_optional_depth(context.get_parameter("Depth"))?,
+            follow_symlinks: parse_switch(context.get_parameter("FollowSymlink"), "Get-ChildItem")?,
+        };
+        let action = context.error_action;
 
         // Get path from parameters or arguments, default to current directory
-        let path = if let Some(Value::String(p)) = get_parameter_ci(&context, "Path") {
-            resolve_path(p)?
-        } else if let Some(Value::String(p)) = context.get_argument(0) {
-            resolve_path(p)?
-        } else {
-            std::env::current_dir().map_err(|e| {
-                RuntimeError::InvalidOperation(format!("Failed to get current directory: {}", e))
-            })?
+        let requested = match (context.get_parameter("Path"), context.get_argument(0)) {
+            (Some(Value::String(p)), _) | (_, Some(Value::String(p))) => Some(p),
+            _ => None,
+        };
+
+        // Variable: and Function: list the session instead of the filesystem
+        if let Some((drive, pattern)) = requested.and_then(|p| parse_drive_path(p)) {
+            return Ok(drive_items(drive, pattern, evaluator)?
+                .into_iter()
+                .filter(|(name, _)| {
+                    should_output_name(name, &filter_patterns, &include_patterns, &exclude_patterns)
+                })
+                .map(|(_, item)| item)
+                .collect());
+        }
+
+        let path = match requested {
+            Some(p) => resolve_path(p)?,
+            None => current_dir()?,
         };
 
-        let metadata = fs::metadata(&path).map_err(|e| {
-            RuntimeError::InvalidOperation(format!(
-                "Failed to access path '{}': {}",
-                path.display(),
-                e
-            ))
-        })?;
+        // A missing path is a non-terminating error like those below it
+        let metadata = match fs::metadata(&path) {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                let record = ErrorRecord::file_system(
+                    &e,
+                    "Get-ChildItem",
+                    path.display(),
+                    format!("Failed to access path '{}': {}", path.display(), e),
+                );
+                evaluator.write_error(record.into(), action)?;
+                return Ok(vec![]);
+            }
+        };
 
         // If it's a file, return just that file as a single item.
         if metadata.is_file() {
@@ -378,13 +512,12 @@ impl Cmdlet for GetChildItemCmdlet {
                 .unwrap_or_else(|| path.to_string_lossy().to_string());
 
             // Apply name-based filters
-            if !filter_patterns.is_empty() && !matches_any_pattern(&name, &filter_patterns) {
-                return Ok(vec![]);
-            }
-            if !include_patterns.is_empty() && !matches_any_pattern(&name, &include_patterns) {
-                return Ok(vec![]);
-            }
-            if !exclude_patterns.is_empty() && matches_any_pattern(&name, &exclude_patterns) {
+            if !should_output_name(
+                &name,
+                &filter_patterns,
+                &include_patterns,
+                &exclude_patterns,
+            ) {
                 return Ok(vec![]);
             }
 
@@ -394,11 +527,11 @@ impl Cmdlet for GetChildItemCmdlet {
         // Otherwise, read directory contents (optionally recursively)
         collect_directory_items(
             &path,
-            recurse,
-            max_depth,
+            &traversal,
             &filter_patterns,
             &include_patterns,
             &exclude_patterns,
+            &mut ErrorSink { evaluator, action },
         )
     }
 }
@@ -406,6 +539,7 @@ impl Cmdlet for GetChildItemCmdlet {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use pwsh_runtime::ErrorCategory;
     use std::fs::{self, File};
     use std::path::PathBuf;
     use tempfile::TempDir;
@@ -424,7 +558,7 @@ mod tests {
         // Execute cmdlet with path as argument
         let cmdlet = GetChildItemCmdlet;
         let context = CmdletContext::new()
-            .with_arguments(vec![Value::String(temp_path.to_string_lossy().to_string())]);
+            .with_arguments(vec![Value::String(temp_path.to_string_lossy().into())]);
         let mut evaluator = pwsh_runtime::Evaluator::new();
         let result = cmdlet.execute(context, &mut evaluator).unwrap();
 
@@ -442,16 +576,17 @@ mod tests {
 
         for item in &result {
             if let Value::Object(props) = item {
+                let props = props.read();
                 assert!(
                     props.contains_key("Name"),
                     "Each item should have a Name property"
                 );
                 if let Some(Value::String(name)) = props.get("Name") {
-                    if name == "file1.txt" {
+                    if &**name == "file1.txt" {
                         found_file1 = true;
-                    } else if name == "file2.txt" {
+                    } else if &**name == "file2.txt" {
                         found_file2 = true;
-                    } else if name == "file3.rs" {
+                    } else if &**name == "file3.rs" {
                         found_file3 = true;
                     }
                 } else {
@@ -476,7 +611,7 @@ mod tests {
         // Execute cmdlet with path as argument
         let cmdlet = GetChildItemCmdlet;
         let context = CmdletContext::new()
-            .with_arguments(vec![Value::String(temp_path.to_string_lossy().to_string())]);
+            .with_arguments(vec![Value::String(temp_path.to_string_lossy().into())]);
         let mut evaluator = pwsh_runtime::Evaluator::new();
         let result = cmdlet.execute(context, &mut evaluator).unwrap();
 
@@ -497,7 +632,7 @@ mod tests {
         // Execute cmdlet with path as argument
         let cmdlet = GetChildItemCmdlet;
         let context = CmdletContext::new()
-            .with_arguments(vec![Value::String(temp_path.to_string_lossy().to_string())]);
+            .with_arguments(vec![Value::String(temp_path.to_string_lossy().into())]);
         let mut evaluator = pwsh_runtime::Evaluator::new();
         let result = cmdlet.execute(context, &mut evaluator).unwrap();
 
@@ -509,10 +644,11 @@ mod tests {
         let mut found_dir = false;
         for item in &result {
             if let Value::Object(props) = item {
+                let props = props.read();
                 if let Some(Value::String(name)) = props.get("Name") {
-                    if name == "file.txt" {
+                    if &**name == "file.txt" {
                         found_file = true;
-                    } else if name == "subdir" {
+                    } else if &**name == "subdir" {
                         found_dir = true;
                     }
                 }
@@ -522,6 +658,57 @@ mod tests {
         assert!(found_dir, "Should find subdir directory");
     }
 
+    #[test]
+    fn test_get_childitem_items_are_shown_in_the_file_view() {
+        let temp_dir = TempDir::new().unwrap();
+        File::create(temp_dir.path().join("file.txt")).unwrap();
+        fs::create_dir(temp_dir.path().join("subdir")).unwrap();
+
+        let context = CmdletContext::new().with_arguments(vec![Value::String(
+            temp_dir.path().to_string_lossy().into(),
+        )]);
+        let mut evaluator = pwsh_runtime::Evaluator::new();
+        let result = GetChildItemCmdlet.execute(context, &mut evaluator).unwrap();
+        for item in &result {
+            let type_names = item.properties().unwrap().type_names().to_vec();
+            let expected = if item.get_property("Directory") == Some(Value::Boolean(true)) {
+                "System.IO.DirectoryInfo"
+            } else {
+                "System.IO.FileInfo"
+            };
+            assert_eq!(type_names, [expected, "System.IO.FileSystemInfo"]);
+        }
+
+        let lines = evaluator.format_output(&result);
+        assert_eq!(
+            lines[1],
+            format!("    Directory: {}", temp_dir.path().display())
+        );
+        assert!(lines[3].starts_with("Mode "));
+        assert!(lines.iter().any(|line| line.ends_with(" 0 file.txt")));
+        assert!(lines.iter().any(|line| line.ends_with(" subdir")));
+    }
+
+    #[test]
+    fn test_get_childitem_stops_at_the_time_limit() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::create_dir(temp_dir.path().join("subdir")).unwrap();
+        let context = CmdletContext::new()
+            .with_arguments(vec![Value::String(
+                temp_dir.path().to_string_lossy().into(),
+            )])
+            .with_parameter("Recurse".to_string(), Value::Boolean(true));
+        let mut evaluator = pwsh_runtime::Evaluator::new();
+        evaluator.set_limits(pwsh_runtime::Limits {
+            max_duration: Some(std::time::Duration::ZERO),
+            ..Default::default()
+        });
+        let error = GetChildItemCmdlet
+            .execute(context, &mut evaluator)
+            .unwrap_err();
+        assert_eq!(error.error_id(), "ScriptTimeLimitExceeded");
+    }
+
     #[test]
     fn test_get_childitem_nonexistent_directory() {
         // Test error handling for non-existent directory
@@ -533,9 +720,11 @@ mod tests {
         } else {
             std::path::PathBuf::from("/definitely/nonexistent/directory/path")
         };
-        let context = CmdletContext::new().with_arguments(vec![Value::String(
-            nonexistent_path.to_string_lossy().to_string(),
-        )]);
+        let context = CmdletContext::new()
+            .with_arguments(vec![Value::String(
+                nonexistent_path.to_string_lossy().into(),
+            )])
+            .with_error_action(ActionPreference::Stop);
         let mut evaluator = pwsh_runtime::Evaluator::new();
         let result = cmdlet.execute(context, &mut evaluator);
 
@@ -545,18 +734,15 @@ mod tests {
             "Should return error for non-existent directory"
         );
 
-        // Verify the error message contains relevant information
-        if let Err(e) = result {
-            let error_msg = e.to_string();
-            assert!(
-                error_msg.contains("Failed to access path")
-                    || error_msg.contains("Failed to read directory")
-                    || error_msg.contains("The system cannot find the path specified")
-                    || error_msg.contains("No such file or directory"),
-                "Error message should indicate directory read failure: {}",
-                error_msg
-            );
-        }
+        // The error says what went wrong and where, whatever the OS message
+        let error = result.unwrap_err();
+        assert_eq!(error.category(), ErrorCategory::ObjectNotFound);
+        assert_eq!(error.error_id(), "PathNotFound,Get-ChildItem");
+        assert_eq!(
+            error.target(),
+            Some(nonexistent_path.display().to_string().as_str())
+        );
+        assert_eq!(error.io_kind(), Some(std::io::ErrorKind::NotFound));
     }
 
     #[test]
@@ -568,7 +754,7 @@ mod tests {
         let cmdlet = GetChildItemCmdlet;
         let context = CmdletContext::new().with_parameter(
             "path".to_string(),
-            Value::String(temp_path.to_string_lossy().to_string()),
+            Value::String(temp_path.to_string_lossy().into()),
         );
         let mut evaluator = pwsh_runtime::Evaluator::new();
         let result = cmdlet.execute(context, &mut evaluator).unwrap();
@@ -596,7 +782,7 @@ mod tests {
         let cmdlet = GetChildItemCmdlet;
         let context = CmdletContext::new().with_parameter(
             "Path".to_string(),
-            Value::String(rel_dir.to_string_lossy().to_string()),
+            Value::String(rel_dir.to_string_lossy().into()),
         );
         let mut evaluator = pwsh_runtime::Evaluator::new();
         let result = cmdlet.execute(context, &mut evaluator).unwrap();
@@ -607,10 +793,8 @@ mod tests {
 
         assert_eq!(result.len(), 1);
         if let Value::Object(props) = &result[0] {
-            assert_eq!(
-                props.get("Name"),
-                Some(&Value::String("file1.txt".to_string()))
-            );
+            let props = props.read();
+            assert_eq!(props.get("Name"), Some(&Value::String("file1.txt".into())));
         } else {
             panic!("Expected object");
         }
@@ -625,14 +809,16 @@ mod tests {
 
         let cmdlet = GetChildItemCmdlet;
         let context = CmdletContext::new()
-            .with_arguments(vec![Value::String(temp_path.to_string_lossy().to_string())]);
+            .with_arguments(vec![Value::String(temp_path.to_string_lossy().into())]);
         let mut evaluator = pwsh_runtime::Evaluator::new();
         let result = cmdlet.execute(context, &mut evaluator).unwrap();
         assert_eq!(result.len(), 2);
 
         for item in &result {
             if let Value::Object(props) = item {
+                let props = props.read();
                 assert!(props.contains_key("Name"));
+                assert!(props.contains_key("FullName"));
                 assert!(props.contains_key("Length"));
                 assert!(props.contains_key("LastWriteTime"));
                 assert!(props.contains_key("Mode"));
@@ -657,12 +843,13 @@ mod tests {
 
         let cmdlet = GetChildItemCmdlet;
         let context = CmdletContext::new()
-            .with_arguments(vec![Value::String(temp_path.to_string_lossy().to_string())]);
+            .with_arguments(vec![Value::String(temp_path.to_string_lossy().into())]);
         let mut evaluator = pwsh_runtime::Evaluator::new();
         let result = cmdlet.execute(context, &mut evaluator).unwrap();
 
         assert_eq!(result.len(), 1);
         if let Value::Object(props) = &result[0] {
+            let props = props.read();
             let mode = match props.get("Mode") {
                 Some(Value::String(s)) => s,
                 _ => panic!("Expected Mode to be a string"),
@@ -691,16 +878,14 @@ mod tests {
         let context = CmdletContext::new()
             .with_parameter(
                 "Path".to_string(),
-                Value::String(temp_path.to_string_lossy().to_string()),
+                Value::String(temp_path.to_string_lossy().into()),
             )
-            .with_parameter("Filter".to_string(), Value::String("*.rs".to_string()));
+            .with_parameter("Filter".to_string(), Value::String("*.rs".into()));
         let result = cmdlet.execute(context, &mut evaluator).unwrap();
         assert_eq!(result.len(), 1);
         if let Value::Object(props) = &result[0] {
-            assert_eq!(
-                props.get("Name"),
-                Some(&Value::String("file2.rs".to_string()))
-            );
+            let props = props.read();
+            assert_eq!(props.get("Name"), Some(&Value::String("file2.rs".into())));
         } else {
             panic!("Expected object");
         }
@@ -709,26 +894,41 @@ mod tests {
         let context = CmdletContext::new()
             .with_parameter(
                 "Path".to_string(),
-                Value::String(temp_path.to_string_lossy().to_string()),
+                Value::String(temp_path.to_string_lossy().into()),
             )
             .with_parameter(
                 "Include".to_string(),
-                Value::Array(vec![
-                    Value::String("*.md".to_string()),
-                    Value::String("*.txt".to_string()),
-                ]),
+                Value::Array(
+                    vec![Value::String("*.md".into()), Value::String("*.txt".into())].into(),
+                ),
             )
-            .with_parameter("Exclude".to_string(), Value::String("README*".to_string()));
+            .with_parameter("Exclude".to_string(), Value::String("README*".into()));
         let result = cmdlet.execute(context, &mut evaluator).unwrap();
         assert_eq!(result.len(), 1);
         if let Value::Object(props) = &result[0] {
-            assert_eq!(
-                props.get("Name"),
-                Some(&Value::String("file1.txt".to_string()))
-            );
+            let props = props.read();
+            assert_eq!(props.get("Name"), Some(&Value::String("file1.txt".into())));
         } else {
             panic!("Expected object");
         }
+
+        // Character classes, and an invalid pattern
+        let context = |pattern: &str| {
+            CmdletContext::new()
+                .with_parameter(
+                    "Path".to_string(),
+                    Value::String(temp_path.to_string_lossy().into()),
+                )
+                .with_parameter("Exclude".to_string(), Value::String(pattern.into()))
+        };
+        let result = cmdlet
+            .execute(context("file[0-1].*"), &mut evaluator)
+            .unwrap();
+        assert_eq!(result.len(), 2);
+        let error = cmdlet
+            .execute(context("file[0-1"), &mut evaluator)
+            .unwrap_err();
+        assert_eq!(error.error_id(), "WildcardPatternInvalid");
     }
 
     #[test]
@@ -743,7 +943,7 @@ mod tests {
         let context = CmdletContext::new()
             .with_parameter(
                 "Path".to_string(),
-                Value::String(root.to_string_lossy().to_string()),
+                Value::String(root.to_string_lossy().into()),
             )
             .with_parameter("Recurse".to_string(), Value::Boolean(true));
         let mut evaluator = pwsh_runtime::Evaluator::new();
@@ -753,10 +953,11 @@ mod tests {
         let mut found_nested = false;
         for item in &result {
             if let Value::Object(props) = item {
+                let props = props.read();
                 if let Some(Value::String(name)) = props.get("Name") {
-                    if name == "root.txt" {
+                    if &**name == "root.txt" {
                         found_root = true;
-                    } else if name == "nested.txt" {
+                    } else if &**name == "nested.txt" {
                         found_nested = true;
                     }
                 }
@@ -779,7 +980,7 @@ mod tests {
         let context = CmdletContext::new()
             .with_parameter(
                 "Path".to_string(),
-                Value::String(root.to_string_lossy().to_string()),
+                Value::String(root.to_string_lossy().into()),
             )
             .with_parameter("Recurse".to_string(), Value::Boolean(true))
             .with_parameter("Depth".to_string(), Value::Number(1.0));
@@ -790,10 +991,11 @@ mod tests {
         let mut found_depth2 = false;
         for item in &result {
             if let Value::Object(props) = item {
+                let props = props.read();
                 if let Some(Value::String(name)) = props.get("Name") {
-                    if name == "depth1.txt" {
+                    if &**name == "depth1.txt" {
                         found_depth1 = true;
-                    } else if name == "depth2.txt" {
+                    } else if &**name == "depth2.txt" {
                         found_depth2 = true;
                     }
                 }
@@ -838,7 +1040,7 @@ mod tests {
         let context = CmdletContext::new()
             .with_parameter(
                 "Path".to_string(),
-                Value::String(root.to_string_lossy().to_string()),
+                Value::String(root.to_string_lossy().into()),
             )
             .with_parameter("Recurse".to_string(), Value::Boolean(true))
             .with_parameter("Depth".to_string(), Value::Number(5.0));
@@ -850,8 +1052,9 @@ mod tests {
         let mut found_inside = false;
         for item in &result {
             if let Value::Object(props) = item {
+                let props = props.read();
                 if let Some(Value::String(name)) = props.get("Name") {
-                    if name == "inside.txt" {
+                    if &**name == "inside.txt" {
                         found_inside = true;
                     }
                 }
@@ -862,4 +1065,106 @@ mod tests {
             "Should find nested file inside real directory"
         );
     }
+
+    fn names(items: &[Value]) -> Vec<String> {
+        let mut names: Vec<String> = items
+            .iter()
+            .filter_map(|item| match item {
+                Value::Object(props) => props.read().get("Name").map(|n| n.to_string()),
+                _ => None,
+            })
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_get_childitem_follow_symlink_enters_each_target_once() {
+        use std::os::unix::fs::symlink;
+
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("root");
+        let outside = temp_dir.path().join("outside");
+        fs::create_dir_all(&root).unwrap();
+        fs::create_dir_all(&outside).unwrap();
+        File::create(outside.join("linked.txt")).unwrap();
+        // One link leads out of the tree, the other back to its root
+        symlink(&outside, root.join("out")).unwrap();
+        symlink(&root, root.join("loop")).unwrap();
+
+        let cmdlet = GetChildItemCmdlet;
+        let context = |follow: bool| {
+            CmdletContext::new()
+                .with_parameter(
+                    "Path".to_string(),
+                    Value::String(root.to_string_lossy().into()),
+                )
+                .with_parameter("Recurse".to_string(), Value::Boolean(true))
+                .with_parameter("FollowSymlink".to_string(), Value::Boolean(follow))
+        };
+        let mut evaluator = pwsh_runtime::Evaluator::new();
+
+        let unfollowed = cmdlet.execute(context(false), &mut evaluator).unwrap();
+        assert_eq!(names(&unfollowed), ["loop", "out"]);
+
+        let followed = cmdlet.execute(context(true), &mut evaluator).unwrap();
+        assert_eq!(names(&followed), ["linked.txt", "loop", "out"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_get_childitem_link_type_and_target() {
+        use std::os::unix::fs::symlink;
+
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(root.join("plain.txt"), "hello").unwrap();
+        symlink("plain.txt", root.join("link.txt")).unwrap();
+        symlink("missing.txt", root.join("dangling.txt")).unwrap();
+
+        let cmdlet = GetChildItemCmdlet;
+        let context = CmdletContext::new().with_parameter(
+            "Path".to_string(),
+            Value::String(root.to_string_lossy().into()),
+        );
+        let mut evaluator = pwsh_runtime::Evaluator::new();
+        let result = cmdlet.execute(context, &mut evaluator).unwrap();
+
+        let item = |name: &str| {
+            result
+                .iter()
+                .find_map(|item| match item {
+                    Value::Object(props)
+                        if props.read().get("Name") == Some(&Value::String(name.into())) =>
+                    {
+                        Some(props.read().clone())
+                    }
+                    _ => None,
+                })
+                .unwrap_or_else(|| panic!("{} should be listed", name))
+        };
+
+        let plain = item("plain.txt");
+        assert_eq!(plain.get("LinkType"), Some(&Value::Null));
+        assert_eq!(plain.get("LinkTarget"), Some(&Value::Null));
+
+        // A link reports its target's length, and where it points
+        let link = item("link.txt");
+        assert_eq!(
+            link.get("LinkType"),
+            Some(&Value::String("SymbolicLink".into()))
+        );
+        assert_eq!(
+            link.get("LinkTarget"),
+            Some(&Value::String("plain.txt".into()))
+        );
+        assert_eq!(link.get("Length"), Some(&Value::Number(5.0)));
+
+        let dangling = item("dangling.txt");
+        assert_eq!(
+            dangling.get("LinkTarget"),
+            Some(&Value::String("missing.txt".into()))
+        );
+    }
 }
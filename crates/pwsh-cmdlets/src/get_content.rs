@@ -1,26 +1,14 @@
 /// Get-Content cmdlet - reads a file and returns its contents as an array of strings (one per line)
 use encoding_rs::Encoding;
 use encoding_rs_io::DecodeReaderBytesBuilder;
-use pwsh_runtime::{Cmdlet, CmdletContext, RuntimeError, Value};
+use pwsh_runtime::{
+    parse_number, Capability, Cmdlet, CmdletContext, ErrorRecord, RuntimeError, Value,
+};
 use std::collections::VecDeque;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::path::{Path, PathBuf};
 
-fn get_parameter_ci<'a>(context: &'a CmdletContext, name: &str) -> Option<&'a Value> {
-    // Try exact match first
-    if let Some(v) = context.parameters.get(name) {
-        return Some(v);
-    }
-
-    let name_lower = name.to_lowercase();
-    context
-        .parameters
-        .iter()
-        .find(|(k, _)| k.to_lowercase() == name_lower)
-        .map(|(_, v)| v)
-}
-
 fn resolve_path(path: &str) -> Result<PathBuf, RuntimeError> {
     let p = PathBuf::from(path);
     if p.is_absolute() {
@@ -28,7 +16,12 @@ fn resolve_path(path: &str) -> Result<PathBuf, RuntimeError> {
     }
 
     let cwd = std::env::current_dir().map_err(|e| {
-        RuntimeError::InvalidOperation(format!("Failed to get current directory: {}", e))
+        ErrorRecord::file_system(
+            &e,
+            "Get-Content",
+            ".",
+            format!("Failed to get current directory: {}", e),
+        )
     })?;
     Ok(cwd.join(p))
 }
@@ -39,12 +32,13 @@ fn parse_encoding(value: Option<&Value>) -> Result<Option<&'static Encoding>, Ru
     };
 
     let s = match v {
-        Value::String(s) => s.as_str(),
+        Value::String(s) => &**s,
         other => {
-            return Err(RuntimeError::InvalidOperation(format!(
-                "Encoding must be a string, got: {}",
-                other
-            )))
+            return Err(ErrorRecord::invalid_argument(
+                "InvalidEncoding,Get-Content",
+                format!("Encoding must be a string, got: {}", other),
+            )
+            .into())
         }
     };
 
@@ -61,17 +55,20 @@ fn parse_encoding(value: Option<&Value>) -> Result<Option<&'static Encoding>, Ru
 
         // Not supported by encoding_rs
         "utf32" | "utf-32" | "utf-32le" | "utf-32be" => {
-            return Err(RuntimeError::InvalidOperation(
-                "Unsupported encoding: UTF-32".to_string(),
-            ));
+            return Err(ErrorRecord::invalid_argument(
+                "UnsupportedEncoding,Get-Content",
+                "Unsupported encoding: UTF-32",
+            )
+            .into());
         }
 
         "" => None,
         other => {
-            return Err(RuntimeError::InvalidOperation(format!(
-                "Unsupported encoding: {}",
-                other
-            )))
+            return Err(ErrorRecord::invalid_argument(
+                "UnsupportedEncoding,Get-Content",
+                format!("Unsupported encoding: {}", other),
+            )
+            .into())
         }
     };
 
@@ -79,55 +76,65 @@ fn parse_encoding(value: Option<&Value>) -> Result<Option<&'static Encoding>, Ru
 }
 
 fn parse_count_param(context: &CmdletContext, name: &str) -> Result<Option<usize>, RuntimeError> {
-    let Some(v) = get_parameter_ci(context, name) else {
+    let Some(v) = context.get_parameter(name) else {
         return Ok(None);
     };
 
     let n = match v {
         Value::Number(n) => *n,
-        Value::String(s) => s.trim().parse::<f64>().map_err(|_| {
-            RuntimeError::InvalidOperation(format!(
-                "{name} must be a non-negative integer, got: {s}"
-            ))
+        Value::String(s) => parse_number(s).ok_or_else(|| {
+            ErrorRecord::invalid_argument(
+                "InvalidCount,Get-Content",
+                format!("{name} must be a non-negative integer, got: {s}"),
+            )
         })?,
         other => {
-            return Err(RuntimeError::InvalidOperation(format!(
-                "{name} must be a non-negative integer, got: {other}"
-            )))
+            return Err(ErrorRecord::invalid_argument(
+                "InvalidCount,Get-Content",
+                format!("{name} must be a non-negative integer, got: {other}"),
+            )
+            .into())
         }
     };
 
     if n.is_nan() || n.is_infinite() || n < 0.0 || n.fract() != 0.0 {
-        return Err(RuntimeError::InvalidOperation(format!(
-            "{name} must be a non-negative integer, got: {n}"
-        )));
+        return Err(ErrorRecord::invalid_argument(
+            "InvalidCount,Get-Content",
+            format!("{name} must be a non-negative integer, got: {n}"),
+        )
+        .into());
     }
 
     if n > (usize::MAX as f64) {
-        return Err(RuntimeError::InvalidOperation(format!(
-            "{name} is too large: {n}"
-        )));
+        return Err(ErrorRecord::invalid_argument(
+            "InvalidCount,Get-Content",
+            format!("{name} is too large: {n}"),
+        )
+        .into());
     }
 
     Ok(Some(n as usize))
 }
 
+fn open_file(path: &Path) -> Result<File, RuntimeError> {
+    File::open(path).map_err(|e| {
+        ErrorRecord::file_system(
+            &e,
+            "Get-Content",
+            path.display(),
+            format!("Failed to open file '{}': {}", path.display(), e),
+        )
+        .into()
+    })
+}
+
 fn read_lines_filtered(
+    file: File,
     path: &Path,
     encoding: Option<&'static Encoding>,
     total_count: Option<usize>,
     tail: Option<usize>,
 ) -> Result<Vec<Value>, RuntimeError> {
-    if total_count.is_some() && tail.is_some() {
-        return Err(RuntimeError::InvalidOperation(
-            "Get-Content does not support using -TotalCount and -Tail together".to_string(),
-        ));
-    }
-
-    let file = File::open(path).map_err(|e| {
-        RuntimeError::InvalidOperation(format!("Failed to open file '{}': {}", path.display(), e))
-    })?;
-
     // Stream-decoding reader:
     // - If -Encoding is provided, use it.
     // - Always BOM sniff so UTF-8/UTF-16 files with BOM read correctly.
@@ -150,14 +157,15 @@ fn read_lines_filtered(
 
         for line in reader.lines() {
             let line = line.map_err(|e| {
-                RuntimeError::InvalidOperation(format!(
-                    "Failed to read file '{}': {}",
+                ErrorRecord::file_system(
+                    &e,
+                    "Get-Content",
                     path.display(),
-                    e
-                ))
+                    format!("Failed to read file '{}': {}", path.display(), e),
+                )
             })?;
 
-            buf.push_back(Value::String(line));
+            buf.push_back(Value::String(line.into()));
             if buf.len() > tail {
                 buf.pop_front();
             }
@@ -172,18 +180,19 @@ fn read_lines_filtered(
 
     for (taken, line) in reader.lines().enumerate() {
         let line = line.map_err(|e| {
-            RuntimeError::InvalidOperation(format!(
-                "Failed to read file '{}': {}",
+            ErrorRecord::file_system(
+                &e,
+                "Get-Content",
                 path.display(),
-                e
-            ))
+                format!("Failed to read file '{}': {}", path.display(), e),
+            )
         })?;
 
         if taken >= max_take {
             break;
         }
 
-        out.push(Value::String(line));
+        out.push(Value::String(line.into()));
     }
 
     Ok(out)
@@ -197,37 +206,71 @@ impl Cmdlet for GetContentCmdlet {
         "Get-Content"
     }
 
+    fn parameters(&self) -> &[&str] {
+        &["Path", "TotalCount", "Tail", "Encoding"]
+    }
+
+    fn capabilities(&self) -> &[Capability] {
+        &[Capability::FileSystem]
+    }
+
     fn execute(
         &self,
         context: CmdletContext,
-        _evaluator: &mut pwsh_runtime::Evaluator,
+        evaluator: &mut pwsh_runtime::Evaluator,
     ) -> Result<Vec<Value>, RuntimeError> {
-        let encoding = parse_encoding(get_parameter_ci(&context, "Encoding"))?;
+        // Without -Encoding, the configuration's DefaultEncoding applies
+        let configured = evaluator
+            .configuration()
+            .default_encoding
+            .as_deref()
+            .map(|name| Value::String(name.into()));
+        let encoding = parse_encoding(context.get_parameter("Encoding").or(configured.as_ref()))?;
 
         // Align with native PowerShell:
         // -TotalCount N (first N lines)
         // -Tail N (last N lines)
         let total_count = parse_count_param(&context, "TotalCount")?;
         let tail = parse_count_param(&context, "Tail")?;
+        if total_count.is_some() && tail.is_some() {
+            return Err(ErrorRecord::invalid_argument(
+                "TotalCountAndTail,Get-Content",
+                "Get-Content does not support using -TotalCount and -Tail together",
+            )
+            .into());
+        }
 
         // Get path from parameters or arguments
-        let path = if let Some(Value::String(p)) = get_parameter_ci(&context, "Path") {
+        let path = if let Some(Value::String(p)) = context.get_parameter("Path") {
             resolve_path(p)?
         } else if let Some(Value::String(p)) = context.get_argument(0) {
             resolve_path(p)?
         } else {
-            return Err(RuntimeError::InvalidOperation(
-                "Get-Content requires a file path".to_string(),
-            ));
+            return Err(ErrorRecord::invalid_argument(
+                "PathRequired,Get-Content",
+                "Get-Content requires a file path",
+            )
+            .into());
+        };
+
+        // A file that can't be opened is a non-terminating error: the
+        // script goes on unless -ErrorAction says to stop
+        let file = match open_file(&path) {
+            Ok(file) => file,
+            Err(error) => {
+                evaluator.write_error(error, context.error_action)?;
+                return Ok(vec![]);
+            }
         };
 
-        read_lines_filtered(&path, encoding, total_count, tail)
+        read_lines_filtered(file, &path, encoding, total_count, tail)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use pwsh_runtime::ActionPreference;
     use std::fs;
     use tempfile::TempDir;
 
@@ -239,16 +282,16 @@ mod tests {
 
         let cmdlet = GetContentCmdlet;
         let context = CmdletContext::new()
-            .with_arguments(vec![Value::String(file_path.to_string_lossy().to_string())]);
+            .with_arguments(vec![Value::String(file_path.to_string_lossy().into())]);
         let mut evaluator = pwsh_runtime::Evaluator::new();
         let result = cmdlet.execute(context, &mut evaluator).unwrap();
 
         assert_eq!(
             result,
             vec![
-                Value::String("one".to_string()),
-                Value::String("two".to_string()),
-                Value::String("three".to_string())
+                Value::String("one".into()),
+                Value::String("two".into()),
+                Value::String("three".into())
             ]
         );
     }
@@ -262,7 +305,7 @@ mod tests {
         let cmdlet = GetContentCmdlet;
         let context = CmdletContext::new().with_parameter(
             "Path".to_string(),
-            Value::String(file_path.to_string_lossy().to_string()),
+            Value::String(file_path.to_string_lossy().into()),
         );
         let mut evaluator = pwsh_runtime::Evaluator::new();
         let result = cmdlet.execute(context, &mut evaluator).unwrap();
@@ -276,10 +319,12 @@ mod tests {
         let file_path = temp_dir.path().join("does_not_exist.txt");
 
         let cmdlet = GetContentCmdlet;
-        let context = CmdletContext::new().with_parameter(
-            "Path".to_string(),
-            Value::String(file_path.to_string_lossy().to_string()),
-        );
+        let context = CmdletContext::new()
+            .with_parameter(
+                "Path".to_string(),
+                Value::String(file_path.to_string_lossy().into()),
+            )
+            .with_error_action(ActionPreference::Stop);
         let mut evaluator = pwsh_runtime::Evaluator::new();
         let result = cmdlet.execute(context, &mut evaluator);
 
@@ -303,17 +348,14 @@ mod tests {
 
         let cmdlet = GetContentCmdlet;
         let context = CmdletContext::new()
-            .with_parameter("Encoding".to_string(), Value::String("Unicode".to_string()))
-            .with_arguments(vec![Value::String(file_path.to_string_lossy().to_string())]);
+            .with_parameter("Encoding".to_string(), Value::String("Unicode".into()))
+            .with_arguments(vec![Value::String(file_path.to_string_lossy().into())]);
         let mut evaluator = pwsh_runtime::Evaluator::new();
         let result = cmdlet.execute(context, &mut evaluator).unwrap();
 
         assert_eq!(
             result,
-            vec![
-                Value::String("one".to_string()),
-                Value::String("two".to_string())
-            ]
+            vec![Value::String("one".into()), Value::String("two".into())]
         );
     }
 
@@ -325,10 +367,10 @@ mod tests {
 
         let cmdlet = GetContentCmdlet;
         let context = CmdletContext::new()
-            .with_parameter("Encoding".to_string(), Value::String("utf32".to_string()))
+            .with_parameter("Encoding".to_string(), Value::String("utf32".into()))
             .with_parameter(
                 "Path".to_string(),
-                Value::String(file_path.to_string_lossy().to_string()),
+                Value::String(file_path.to_string_lossy().into()),
             );
         let mut evaluator = pwsh_runtime::Evaluator::new();
         let result = cmdlet.execute(context, &mut evaluator);
@@ -338,6 +380,27 @@ mod tests {
         assert!(msg.to_ascii_lowercase().contains("unsupported encoding"));
     }
 
+    #[test]
+    fn test_get_content_uses_configured_default_encoding() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("utf16.txt");
+        // UTF-16LE without a byte order mark, so only the encoding can tell
+        let bytes: Vec<u8> = "hi\n".encode_utf16().flat_map(u16::to_le_bytes).collect();
+        fs::write(&file_path, bytes).unwrap();
+
+        let cmdlet = GetContentCmdlet;
+        let mut evaluator = pwsh_runtime::Evaluator::new();
+        evaluator.set_configuration(pwsh_runtime::Configuration {
+            default_encoding: Some("Unicode".to_string()),
+            ..Default::default()
+        });
+        let context = CmdletContext::new()
+            .with_arguments(vec![Value::String(file_path.to_string_lossy().into())]);
+        let result = cmdlet.execute(context, &mut evaluator).unwrap();
+
+        assert_eq!(result, vec![Value::String("hi".into())]);
+    }
+
     #[test]
     fn test_get_content_total_count() {
         let temp_dir = TempDir::new().unwrap();
@@ -348,7 +411,7 @@ mod tests {
         let context = CmdletContext::new()
             .with_parameter(
                 "Path".to_string(),
-                Value::String(file_path.to_string_lossy().to_string()),
+                Value::String(file_path.to_string_lossy().into()),
             )
             .with_parameter("TotalCount".to_string(), Value::Number(2.0));
         let mut evaluator = pwsh_runtime::Evaluator::new();
@@ -356,10 +419,7 @@ mod tests {
 
         assert_eq!(
             result,
-            vec![
-                Value::String("one".to_string()),
-                Value::String("two".to_string())
-            ]
+            vec![Value::String("one".into()), Value::String("two".into())]
         );
     }
 
@@ -373,7 +433,7 @@ mod tests {
         let context = CmdletContext::new()
             .with_parameter(
                 "Path".to_string(),
-                Value::String(file_path.to_string_lossy().to_string()),
+                Value::String(file_path.to_string_lossy().into()),
             )
             .with_parameter("Tail".to_string(), Value::Number(2.0));
         let mut evaluator = pwsh_runtime::Evaluator::new();
@@ -381,10 +441,7 @@ mod tests {
 
         assert_eq!(
             result,
-            vec![
-                Value::String("four".to_string()),
-                Value::String("five".to_string())
-            ]
+            vec![Value::String("four".into()), Value::String("five".into())]
         );
     }
 
@@ -398,7 +455,7 @@ mod tests {
         let context = CmdletContext::new()
             .with_parameter(
                 "Path".to_string(),
-                Value::String(file_path.to_string_lossy().to_string()),
+                Value::String(file_path.to_string_lossy().into()),
             )
             .with_parameter("TotalCount".to_string(), Value::Number(1.0))
             .with_parameter("Tail".to_string(), Value::Number(1.0));
@@ -420,7 +477,7 @@ mod tests {
         let context = CmdletContext::new()
             .with_parameter(
                 "Path".to_string(),
-                Value::String(file_path.to_string_lossy().to_string()),
+                Value::String(file_path.to_string_lossy().into()),
             )
             .with_parameter("TotalCount".to_string(), Value::Number(-1.0));
         let mut evaluator = pwsh_runtime::Evaluator::new();
@@ -441,7 +498,7 @@ mod tests {
         let context = CmdletContext::new()
             .with_parameter(
                 "Path".to_string(),
-                Value::String(file_path.to_string_lossy().to_string()),
+                Value::String(file_path.to_string_lossy().into()),
             )
             .with_parameter("Tail".to_string(), Value::Number(1.5));
         let mut evaluator = pwsh_runtime::Evaluator::new();
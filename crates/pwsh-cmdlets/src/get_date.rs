@@ -0,0 +1,230 @@
+/// Get-Date cmdlet - the current date and time, or a given one, optionally formatted
+use chrono::{Datelike, NaiveDate, NaiveDateTime, Timelike};
+use pwsh_runtime::{Cmdlet, CmdletContext, ErrorCategory, ErrorRecord, RuntimeError, Value};
+use std::fmt::Write;
+
+/// The value of a -Year, -Month, ... parameter, if it was given
+fn parse_part(context: &CmdletContext, name: &str) -> Result<Option<u32>, RuntimeError> {
+    let Some(value) = context.get_parameter(name) else {
+        return Ok(None);
+    };
+    match value.to_number() {
+        Some(n) if n >= 0.0 && n.fract() == 0.0 && n <= u32::MAX as f64 => Ok(Some(n as u32)),
+        _ => Err(ErrorRecord::invalid_argument(
+            "InvalidDatePart,Get-Date",
+            format!(
+                "Cannot convert value \"{}\" of -{} to a date part.",
+                value, name
+            ),
+        )
+        .into()),
+    }
+}
+
+/// Replace the parts of `date` that were given as parameters. They are
+/// replaced together, so `-Month 2 -Day 28` works on January 31.
+fn with_parts(date: NaiveDateTime, context: &CmdletContext) -> Result<NaiveDateTime, RuntimeError> {
+    let year = parse_part(context, "Year")?.map_or(date.year() as i64, i64::from);
+    let month = parse_part(context, "Month")?.unwrap_or(date.month());
+    let day = parse_part(context, "Day")?.unwrap_or(date.day());
+    let hour = parse_part(context, "Hour")?.unwrap_or(date.hour());
+    let minute = parse_part(context, "Minute")?.unwrap_or(date.minute());
+    let second = parse_part(context, "Second")?.unwrap_or(date.second());
+    let out_of_range = || -> RuntimeError {
+        ErrorRecord::new(
+            ErrorCategory::InvalidArgument,
+            "ArgumentOutOfRange,Get-Date",
+            "The date and time parameters describe an un-representable DateTime.",
+        )
+        .into()
+    };
+    let nanosecond = match parse_part(context, "Millisecond")? {
+        Some(millisecond) if millisecond < 1000 => millisecond * 1_000_000,
+        Some(_) => return Err(out_of_range()),
+        None => date.nanosecond() % 1_000_000_000,
+    };
+
+    i32::try_from(year)
+        .ok()
+        .and_then(|year| NaiveDate::from_ymd_opt(year, month, day))
+        .and_then(|d| d.and_hms_nano_opt(hour, minute, second, nanosecond))
+        .ok_or_else(out_of_range)
+}
+
+/// Get-Date writes the current date and time (read from the session's clock),
+/// or the one given by -Date (positionally or from the pipeline), with any of
+/// -Year, -Month, -Day, -Hour, -Minute, -Second and -Millisecond replaced.
+/// -Format writes it as a
+/// string using a .NET format string (`yyyy-MM-dd`, or a standard one like
+/// `D`) in the session's culture; -UFormat uses Unix `date` specifiers
+/// (`%Y-%m-%d`).
+pub struct GetDateCmdlet;
+
+impl Cmdlet for GetDateCmdlet {
+    fn name(&self) -> &str {
+        "Get-Date"
+    }
+
+    fn parameters(&self) -> &[&str] {
+        &[
+            "Date",
+            "Format",
+            "UFormat",
+            "Year",
+            "Month",
+            "Day",
+            "Hour",
+            "Minute",
+            "Second",
+            "Millisecond",
+        ]
+    }
+
+    fn execute(
+        &self,
+        context: CmdletContext,
+        evaluator: &mut pwsh_runtime::Evaluator,
+    ) -> Result<Vec<Value>, RuntimeError> {
+        // Support: Get-Date '2024-03-05' and '2024-03-05' | Get-Date
+        let given = context
+            .get_parameter("Date")
+            .or_else(|| context.get_argument(0))
+            .or_else(|| context.pipeline_input.first());
+        let date = match given {
+            Some(value) => value.to_datetime().ok_or_else(|| {
+                ErrorRecord::invalid_argument(
+                    "InvalidDate,Get-Date",
+                    format!(
+                        "Cannot convert value \"{}\" to type \"System.DateTime\".",
+                        value
+                    ),
+                )
+            })?,
+            None => evaluator.clock().now(),
+        };
+        let date = with_parts(date, &context)?;
+
+        let format = context.get_parameter("Format").map(|f| f.to_string());
+        let uformat = context.get_parameter("UFormat").map(|f| f.to_string());
+        let output = match (format, uformat) {
+            (Some(_), Some(_)) => {
+                return Err(ErrorRecord::invalid_argument(
+                    "AmbiguousParameterSet,Get-Date",
+                    "Get-Date: -Format and -UFormat cannot be used together",
+                )
+                .into())
+            }
+            (Some(format), None) => Value::String(
+                evaluator
+                    .format_value(&Value::DateTime(date), &format)?
+                    .into(),
+            ),
+            (None, Some(uformat)) => {
+                let mut text = String::new();
+                write!(text, "{}", date.format(&uformat)).map_err(|_| {
+                    ErrorRecord::invalid_argument(
+                        "InvalidUFormat,Get-Date",
+                        format!("Get-Date: invalid -UFormat string '{}'", uformat),
+                    )
+                })?;
+                Value::String(text.into())
+            }
+            (None, None) => Value::DateTime(date),
+        };
+        Ok(vec![output])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pwsh_runtime::{Culture, FixedClock};
+
+    fn run(context: CmdletContext, evaluator: &mut pwsh_runtime::Evaluator) -> Value {
+        GetDateCmdlet.execute(context, evaluator).unwrap().remove(0)
+    }
+
+    fn date_context() -> CmdletContext {
+        CmdletContext::new().with_arguments(vec![Value::String("2024-03-05 14:07:09".into())])
+    }
+
+    #[test]
+    fn test_get_date_formats_in_session_culture() {
+        let mut evaluator = pwsh_runtime::Evaluator::new();
+        let context =
+            date_context().with_parameter("Format".to_string(), Value::String("D".into()));
+        assert_eq!(
+            run(context, &mut evaluator),
+            Value::String("Tuesday, March 5, 2024".into())
+        );
+
+        evaluator.set_culture(Culture::from_name("de-DE").unwrap());
+        let context = date_context().with_parameter(
+            "Format".to_string(),
+            Value::String("dd/MM/yyyy HH:mm".into()),
+        );
+        assert_eq!(
+            run(context, &mut evaluator),
+            Value::String("05.03.2024 14:07".into())
+        );
+
+        let context =
+            date_context().with_parameter("UFormat".to_string(), Value::String("%Y%m%d".into()));
+        assert_eq!(
+            run(context, &mut evaluator),
+            Value::String("20240305".into())
+        );
+    }
+
+    #[test]
+    fn test_get_date_replaces_parts() {
+        let mut evaluator = pwsh_runtime::Evaluator::new();
+        let context = date_context()
+            .with_parameter("Year".to_string(), Value::Number(2023.0))
+            .with_parameter("Hour".to_string(), Value::Number(0.0));
+        let expected = chrono::NaiveDate::from_ymd_opt(2023, 3, 5)
+            .unwrap()
+            .and_hms_opt(0, 7, 9)
+            .unwrap();
+        assert_eq!(run(context, &mut evaluator), Value::DateTime(expected));
+
+        let now = run(CmdletContext::new(), &mut evaluator);
+        assert!(matches!(now, Value::DateTime(_)));
+    }
+
+    #[test]
+    fn test_get_date_reads_the_session_clock() {
+        let start = chrono::NaiveDate::from_ymd_opt(2024, 3, 5)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap();
+        let clock = FixedClock::new(start);
+        let mut evaluator = pwsh_runtime::Evaluator::new();
+        evaluator.set_clock(Box::new(clock.clone()));
+        assert_eq!(
+            run(CmdletContext::new(), &mut evaluator),
+            Value::DateTime(start)
+        );
+
+        clock.advance(chrono::Duration::seconds(90));
+        let context = CmdletContext::new()
+            .with_parameter("Format".to_string(), Value::String("HH:mm:ss".into()));
+        assert_eq!(
+            run(context, &mut evaluator),
+            Value::String("09:01:30".into())
+        );
+    }
+
+    #[test]
+    fn test_get_date_errors() {
+        let mut evaluator = pwsh_runtime::Evaluator::new();
+        let context = CmdletContext::new().with_arguments(vec![Value::String("someday".into())]);
+        let error = GetDateCmdlet.execute(context, &mut evaluator).unwrap_err();
+        assert_eq!(error.error_id(), "InvalidDate,Get-Date");
+
+        let context = date_context().with_parameter("Day".to_string(), Value::Number(31.0));
+        let context = context.with_parameter("Month".to_string(), Value::Number(2.0));
+        let error = GetDateCmdlet.execute(context, &mut evaluator).unwrap_err();
+        assert_eq!(error.error_id(), "ArgumentOutOfRange,Get-Date");
+    }
+}
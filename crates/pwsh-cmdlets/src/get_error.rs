@@ -0,0 +1,187 @@
+/// Get-Error cmdlet - shows errors in full: their exceptions, category, target and position
+use pwsh_runtime::{
+    unroll_to_items, Cmdlet, CmdletContext, DisplayLimits, ErrorRecord, RuntimeError, Value,
+};
+
+/// The -Newest parameter: how many of the latest errors to show
+fn parse_newest(value: Option<&Value>) -> Result<usize, RuntimeError> {
+    let Some(value) = value else {
+        return Ok(1);
+    };
+    match value.to_number() {
+        Some(n) if n >= 1.0 && n.fract() == 0.0 => Ok(n as usize),
+        _ => Err(ErrorRecord::invalid_argument(
+            "InvalidNewest,Get-Error",
+            format!("-Newest must be a whole number above 0, not {}", value),
+        )
+        .into()),
+    }
+}
+
+/// Whether a property has nothing to show
+fn is_empty(value: &Value) -> bool {
+    match value {
+        Value::Null => true,
+        Value::String(text) => text.is_empty(),
+        _ => false,
+    }
+}
+
+/// `value`'s properties as aligned `Name : value` lines, `indent` spaces
+/// in. A property holding an object, such as an Exception's
+/// InnerException, is shown as a block of its own under its name, four
+/// spaces further in; the lines of a multi-line value line up under its
+/// first. Empty properties are left out.
+fn detail_lines(value: &Value, indent: usize, lines: &mut Vec<String>) {
+    let Some(properties) = value.properties() else {
+        return;
+    };
+    let shown: Vec<(String, &Value)> = properties
+        .iter()
+        .filter(|(_, value)| !is_empty(value))
+        .map(|(name, value)| (name.to_string(), value))
+        .collect();
+    let width = shown
+        .iter()
+        .map(|(name, _)| name.chars().count())
+        .max()
+        .unwrap_or(0);
+    let pad = " ".repeat(indent);
+    for (name, value) in shown {
+        if value.properties().is_some() {
+            lines.push(format!("{}{:<width$} :", pad, name, width = width));
+            detail_lines(value, indent + 4, lines);
+            continue;
+        }
+        let text = match value {
+            Value::String(text) => text.to_string(),
+            other => DisplayLimits::default().inline(other),
+        };
+        let mut text_lines = text.lines();
+        lines.push(format!(
+            "{}{:<width$} : {}",
+            pad,
+            name,
+            text_lines.next().unwrap_or_default(),
+            width = width
+        ));
+        for more in text_lines {
+            lines.push(format!("{}{}{}", pad, " ".repeat(width + 3), more));
+        }
+    }
+}
+
+/// Get-Error shows errors in full, where the console shows just their
+/// message: each error's Exception (with the InnerException chain of the
+/// errors that caused it), TargetObject, CategoryInfo,
+/// FullyQualifiedErrorId and InvocationInfo (where it happened), as lines
+/// of text. Without input it shows the latest error in `$error`, or the
+/// latest -Newest of them, each under its ErrorIndex; errors piped in (or
+/// given as -InputObject) are shown instead.
+pub struct GetErrorCmdlet;
+
+impl Cmdlet for GetErrorCmdlet {
+    fn name(&self) -> &str {
+        "Get-Error"
+    }
+
+    fn parameters(&self) -> &[&str] {
+        &["Newest", "InputObject"]
+    }
+
+    fn execute(
+        &self,
+        context: CmdletContext,
+        evaluator: &mut pwsh_runtime::Evaluator,
+    ) -> Result<Vec<Value>, RuntimeError> {
+        let newest = parse_newest(context.get_parameter("Newest"))?;
+        let given = match context.get_parameter("InputObject") {
+            Some(input) => unroll_to_items(vec![input.clone()]),
+            None => unroll_to_items(context.pipeline_input),
+        };
+        let errors = if given.is_empty() {
+            let recorded = match evaluator.get_variable("error") {
+                Some(Value::Array(items)) => items.unwrap_or_clone(),
+                _ => Vec::new(),
+            };
+            recorded.into_iter().take(newest).collect()
+        } else {
+            given
+        };
+        if let Some(other) = errors
+            .iter()
+            .find(|error| ErrorRecord::from_value(error).is_none())
+        {
+            return Err(ErrorRecord::invalid_argument(
+                "NotAnErrorRecord,Get-Error",
+                format!("Get-Error shows error records, not {}", other),
+            )
+            .into());
+        }
+
+        let mut lines = Vec::new();
+        for (index, error) in errors.iter().enumerate() {
+            lines.push(String::new());
+            if errors.len() > 1 {
+                lines.push(format!("   ErrorIndex: {}", index));
+                lines.push(String::new());
+            }
+            detail_lines(error, 0, &mut lines);
+        }
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        Ok(lines
+            .into_iter()
+            .map(|line| Value::String(line.into()))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pwsh_runtime::PropertyMap;
+
+    #[test]
+    fn test_details_nest_objects_and_skip_empty_properties() {
+        let value = Value::Object(
+            PropertyMap::from([
+                (
+                    "Exception",
+                    Value::Object(
+                        PropertyMap::from([("Message", Value::String("boom\nagain".into()))])
+                            .into(),
+                    ),
+                ),
+                ("TargetObject", Value::Null),
+                ("FullyQualifiedErrorId", Value::String("boom".into())),
+            ])
+            .into(),
+        );
+        let mut lines = Vec::new();
+        detail_lines(&value, 0, &mut lines);
+        assert_eq!(
+            lines,
+            vec![
+                "Exception             :",
+                "    Message : boom",
+                "              again",
+                "FullyQualifiedErrorId : boom",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_error_without_errors_shows_nothing() {
+        let mut evaluator = pwsh_runtime::Evaluator::new();
+        let result = GetErrorCmdlet
+            .execute(CmdletContext::new(), &mut evaluator)
+            .unwrap();
+        assert!(result.is_empty());
+
+        let context = CmdletContext::new().with_parameter("Newest".to_string(), Value::Number(0.0));
+        let error = GetErrorCmdlet.execute(context, &mut evaluator).unwrap_err();
+        assert_eq!(error.error_id(), "InvalidNewest,Get-Error");
+    }
+}
@@ -0,0 +1,22 @@
+/// Get-ExecutionPolicy cmdlet - the session's execution policy
+use pwsh_runtime::{Cmdlet, CmdletContext, RuntimeError, Value};
+
+/// Get-ExecutionPolicy writes the name of the policy that decides which
+/// script files the session runs: `Restricted`, `AllSigned`,
+/// `RemoteSigned`, `Unrestricted` or `Bypass`.
+pub struct GetExecutionPolicyCmdlet;
+
+impl Cmdlet for GetExecutionPolicyCmdlet {
+    fn name(&self) -> &str {
+        "Get-ExecutionPolicy"
+    }
+
+    fn execute(
+        &self,
+        _context: CmdletContext,
+        evaluator: &mut pwsh_runtime::Evaluator,
+    ) -> Result<Vec<Value>, RuntimeError> {
+        let policy = evaluator.execution_policy().to_string();
+        Ok(vec![Value::String(policy.into())])
+    }
+}
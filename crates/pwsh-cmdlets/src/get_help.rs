@@ -0,0 +1,207 @@
+/// Get-Help cmdlet - shows the comment-based help of functions and the syntax of cmdlets
+use pwsh_parser::CommentHelp;
+use pwsh_runtime::{
+    parse_switch, Cmdlet, CmdletContext, ErrorCategory, ErrorRecord, Evaluator, Function,
+    PropertyMap, RuntimeError, Value,
+};
+
+fn string(text: &str) -> Value {
+    Value::String(text.into())
+}
+
+fn optional_string(text: Option<&str>) -> Value {
+    text.map_or(Value::Null, string)
+}
+
+fn parameter_item(name: &str, description: Option<&str>) -> Value {
+    let mut props = PropertyMap::with_capacity(2);
+    props.insert("Name".to_string(), string(name));
+    props.insert("Description".to_string(), optional_string(description));
+    Value::Object(props.into())
+}
+
+/// A command's help: the parts its comment-based help gives, with the
+/// syntax worked out from its parameters
+struct HelpTopic {
+    name: String,
+    syntax: String,
+    /// Declared parameters, or those the help describes when none are
+    parameters: Vec<String>,
+    help: CommentHelp,
+}
+
+impl HelpTopic {
+    fn for_function(function: &Function) -> Self {
+        let help = function.help.as_deref().cloned().unwrap_or_default();
+        let mut parameters: Vec<String> = function
+            .parameters
+            .iter()
+            .map(|parameter| parameter.name.clone())
+            .collect();
+        if parameters.is_empty() {
+            parameters = help.parameters.iter().map(|p| p.name.clone()).collect();
+        }
+        let syntax = syntax(&function.name, &parameters);
+        HelpTopic {
+            name: function.name.clone(),
+            syntax,
+            parameters,
+            help,
+        }
+    }
+
+    fn for_cmdlet(cmdlet: &dyn Cmdlet) -> Self {
+        let parameters: Vec<String> = cmdlet.parameters().iter().map(|p| p.to_string()).collect();
+        HelpTopic {
+            name: cmdlet.name().to_string(),
+            syntax: syntax(cmdlet.name(), &parameters),
+            parameters,
+            help: CommentHelp::default(),
+        }
+    }
+
+    fn parameter(&self, name: &str) -> Option<Value> {
+        let declared = self
+            .parameters
+            .iter()
+            .find(|parameter| parameter.eq_ignore_ascii_case(name))?;
+        let description = self
+            .help
+            .parameter(declared)
+            .map(|p| p.description.as_str());
+        Some(parameter_item(declared, description))
+    }
+
+    /// The whole topic, or with `examples_only` just its name, synopsis and examples
+    fn item(&self, examples_only: bool) -> Value {
+        let mut props = PropertyMap::with_capacity(9);
+        props.insert("Name".to_string(), string(&self.name));
+        // Without help, PowerShell shows the syntax as the synopsis
+        let synopsis = self.help.synopsis.as_deref().unwrap_or(&self.syntax);
+        props.insert("Synopsis".to_string(), string(synopsis));
+        let examples: Vec<Value> = self.help.examples.iter().map(|e| string(e)).collect();
+        if examples_only {
+            props.insert("Examples".to_string(), Value::Array(examples.into()));
+            return Value::Object(props.into());
+        }
+
+        props.insert("Syntax".to_string(), string(&self.syntax));
+        props.insert(
+            "Description".to_string(),
+            optional_string(self.help.description.as_deref()),
+        );
+        let parameters: Vec<Value> = self
+            .parameters
+            .iter()
+            .filter_map(|name| self.parameter(name))
+            .collect();
+        props.insert("Parameters".to_string(), Value::Array(parameters.into()));
+        props.insert("Examples".to_string(), Value::Array(examples.into()));
+        props.insert(
+            "Inputs".to_string(),
+            optional_string(self.help.inputs.as_deref()),
+        );
+        props.insert(
+            "Outputs".to_string(),
+            optional_string(self.help.outputs.as_deref()),
+        );
+        props.insert(
+            "Notes".to_string(),
+            optional_string(self.help.notes.as_deref()),
+        );
+        let links: Vec<Value> = self.help.links.iter().map(|l| string(l)).collect();
+        props.insert("Links".to_string(), Value::Array(links.into()));
+        Value::Object(props.into())
+    }
+}
+
+/// `Name [-First] [-Second]`
+fn syntax(name: &str, parameters: &[String]) -> String {
+    std::iter::once(name.to_string())
+        .chain(parameters.iter().map(|p| format!("[-{}]", p)))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The topic for a function, or else a cmdlet, named `name`
+fn find_topic(name: &str, evaluator: &Evaluator) -> Option<HelpTopic> {
+    // Functions are listed outermost scope first; the innermost one wins
+    let function = evaluator
+        .functions()
+        .into_iter()
+        .rev()
+        .find(|function| function.name.eq_ignore_ascii_case(name));
+    match function {
+        Some(function) => Some(HelpTopic::for_function(function)),
+        None => evaluator.registry().get(name).map(HelpTopic::for_cmdlet),
+    }
+}
+
+/// Get-Help writes the help for a function or cmdlet: an object with its
+/// `Name`, `Synopsis`, `Syntax`, `Description`, `Parameters` (each with a
+/// `Name` and `Description`), `Examples`, `Inputs`, `Outputs`, `Notes` and
+/// `Links`. Functions get theirs from comment-based help; a command without
+/// any still has its syntax. `-Parameter` writes just one parameter's
+/// entry, and `-Examples` just the name, synopsis and examples.
+pub struct GetHelpCmdlet;
+
+impl Cmdlet for GetHelpCmdlet {
+    fn name(&self) -> &str {
+        "Get-Help"
+    }
+
+    fn parameters(&self) -> &[&str] {
+        &["Name", "Parameter", "Examples"]
+    }
+
+    fn execute(
+        &self,
+        context: CmdletContext,
+        evaluator: &mut Evaluator,
+    ) -> Result<Vec<Value>, RuntimeError> {
+        let mut name = context.get_parameter("Name").map(|name| name.to_string());
+        // The parser hands a switch the word after it, so in
+        // `Get-Help -Examples Greet` the "value" of -Examples is the name
+        let examples = match context.get_parameter("Examples") {
+            Some(value @ Value::String(s))
+                if name.is_none() && parse_switch(Some(value), "Get-Help").is_err() =>
+            {
+                name = Some(s.to_string());
+                true
+            }
+            value => parse_switch(value, "Get-Help")?,
+        };
+        let name = name
+            .or_else(|| context.get_argument(0).map(|name| name.to_string()))
+            .unwrap_or_else(|| "Get-Help".to_string());
+
+        let Some(topic) = find_topic(&name, evaluator) else {
+            return Err(ErrorRecord::new(
+                ErrorCategory::ObjectNotFound,
+                "HelpNotFound,Get-Help",
+                format!(
+                    "Get-Help could not find {} in a help file in this session.",
+                    name
+                ),
+            )
+            .with_target(name.as_str())
+            .into());
+        };
+
+        if let Some(parameter) = context.get_parameter("Parameter") {
+            let parameter = parameter.to_string();
+            return match topic.parameter(&parameter) {
+                Some(item) => Ok(vec![item]),
+                None => Err(ErrorRecord::new(
+                    ErrorCategory::ObjectNotFound,
+                    "NoParmsFound,Get-Help",
+                    format!("No parameter matches criteria {}.", parameter),
+                )
+                .with_target(parameter.as_str())
+                .into()),
+            };
+        }
+
+        Ok(vec![topic.item(examples)])
+    }
+}
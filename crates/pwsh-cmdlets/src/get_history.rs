@@ -0,0 +1,107 @@
+/// Get-History cmdlet - lists the lines run at the prompt in this session
+use pwsh_runtime::{
+    unroll_to_items, Cmdlet, CmdletContext, ErrorCategory, ErrorRecord, RuntimeError, Value,
+};
+
+/// A whole number from 1 up, for -Id or -Count
+fn parse_count(name: &str, value: &Value) -> Result<usize, RuntimeError> {
+    match value.to_number() {
+        Some(n) if n >= 1.0 && n.fract() == 0.0 => Ok(n as usize),
+        _ => Err(ErrorRecord::invalid_argument(
+            &format!("Invalid{},Get-History", name),
+            format!("-{} must be a whole number above 0, not {}", name, value),
+        )
+        .into()),
+    }
+}
+
+/// Get-History writes the lines run at the prompt, oldest first, as
+/// HistoryInfo objects: Id, CommandLine, ExecutionStatus,
+/// StartExecutionTime and EndExecutionTime. -Count keeps only the last
+/// so many; -Id (also by position) picks out lines by their ids, and an id
+/// no longer in the history is a non-terminating error. Invoke-History
+/// (`r`) runs one of them again.
+pub struct GetHistoryCmdlet;
+
+impl Cmdlet for GetHistoryCmdlet {
+    fn name(&self) -> &str {
+        "Get-History"
+    }
+
+    fn parameters(&self) -> &[&str] {
+        &["Id", "Count"]
+    }
+
+    fn execute(
+        &self,
+        context: CmdletContext,
+        evaluator: &mut pwsh_runtime::Evaluator,
+    ) -> Result<Vec<Value>, RuntimeError> {
+        let count = context
+            .get_parameter("Count")
+            .map(|count| parse_count("Count", count))
+            .transpose()?;
+        let Some(ids) = context.get_parameter("Id").or(context.get_argument(0)) else {
+            let history = evaluator.history();
+            let skip = count.map_or(0, |count| history.entries().count().saturating_sub(count));
+            return Ok(history
+                .entries()
+                .skip(skip)
+                .map(|entry| entry.to_value())
+                .collect());
+        };
+
+        let ids = unroll_to_items(vec![ids.clone()])
+            .iter()
+            .map(|id| parse_count("Id", id))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut output = Vec::new();
+        for id in ids {
+            match evaluator.history().get(id) {
+                Some(entry) => output.push(entry.to_value()),
+                None => {
+                    let error = ErrorRecord::new(
+                        ErrorCategory::ObjectNotFound,
+                        "GetHistoryIdNotFound,Get-History",
+                        format!("Cannot locate history for Id {}.", id),
+                    )
+                    .with_target(id);
+                    evaluator.write_error(error.into(), context.error_action)?;
+                }
+            }
+        }
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_get_history_keeps_the_last_count_lines() {
+        let mut evaluator = pwsh_runtime::Evaluator::new();
+        let time = NaiveDate::from_ymd_opt(2024, 3, 1)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap();
+        for line in ["Get-Date", "$x = 1", "$x"] {
+            evaluator.history_mut().add(line, true, time, time);
+        }
+        let context = CmdletContext::new().with_parameter("Count".to_string(), Value::Number(2.0));
+        let lines: Vec<String> = GetHistoryCmdlet
+            .execute(context, &mut evaluator)
+            .unwrap()
+            .iter()
+            .map(|entry| entry.get_property("CommandLine").unwrap().to_string())
+            .collect();
+        assert_eq!(lines, vec!["$x = 1", "$x"]);
+
+        let context = CmdletContext::new().with_parameter("Count".to_string(), Value::Number(0.0));
+        let error = GetHistoryCmdlet
+            .execute(context, &mut evaluator)
+            .unwrap_err();
+        assert_eq!(error.error_id(), "InvalidCount,Get-History");
+    }
+}
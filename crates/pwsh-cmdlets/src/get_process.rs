@@ -1,117 +1,477 @@
 /// Get-Process cmdlet - retrieves system process information
-use pwsh_runtime::{Cmdlet, CmdletContext, RuntimeError, Value};
-use std::collections::HashMap;
+use chrono::NaiveDateTime;
+use pwsh_runtime::{
+    parse_switch, Capability, Cmdlet, CmdletContext, PropertyMap, RuntimeError, Value,
+    WildcardPattern,
+};
 
-/// Get-Process cmdlet retrieves process information
+/// What is known about one process. Properties the platform can't report
+/// for a process (the path of a kernel thread, the memory of a process
+/// that has no pages of its own) are None and written as $null.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+struct ProcessInfo {
+    name: String,
+    id: u32,
+    cpu: Option<f64>,
+    working_set: Option<u64>,
+    path: Option<String>,
+    start_time: Option<NaiveDateTime>,
+    user_name: Option<String>,
+}
+
+impl ProcessInfo {
+    /// The process object; UserName is only added when it was asked for,
+    /// as with PowerShell's -IncludeUserName
+    fn to_value(&self, include_user_name: bool) -> Value {
+        let mut props = PropertyMap::with_capacity(7);
+        props.insert("Name".to_string(), Value::String(self.name.as_str().into()));
+        props.insert("Id".to_string(), Value::Number(self.id as f64));
+        props.insert(
+            "CPU".to_string(),
+            self.cpu.map_or(Value::Null, Value::Number),
+        );
+        props.insert(
+            "WorkingSet".to_string(),
+            self.working_set
+                .map_or(Value::Null, |bytes| Value::Number(bytes as f64)),
+        );
+        props.insert(
+            "Path".to_string(),
+            self.path
+                .as_deref()
+                .map_or(Value::Null, |p| Value::String(p.into())),
+        );
+        props.insert(
+            "StartTime".to_string(),
+            self.start_time.map_or(Value::Null, Value::DateTime),
+        );
+        if include_user_name {
+            props.insert(
+                "UserName".to_string(),
+                self.user_name
+                    .as_deref()
+                    .map_or(Value::Null, |u| Value::String(u.into())),
+            );
+        }
+        props.insert_type_name("System.Diagnostics.Process");
+        Value::Object(props.into())
+    }
+}
+
+/// Get-Process lists processes, sorted by name (ignoring case) and then by
+/// id, as PowerShell lists them. -Name keeps the processes whose names match
+/// any of the given wildcard patterns. Each process has Name, Id, CPU,
+/// WorkingSet, Path and StartTime, and UserName with -IncludeUserName;
+/// a property the platform can't report for a process is $null.
 pub struct GetProcessCmdlet;
 
+impl GetProcessCmdlet {
+    /// Whether this platform has a process table Get-Process can read;
+    /// where it hasn't, Get-Process reports a PlatformNotSupported error
+    pub const AVAILABLE: bool = cfg!(target_os = "linux");
+}
+
 impl Cmdlet for GetProcessCmdlet {
     fn name(&self) -> &str {
         "Get-Process"
     }
 
+    fn parameters(&self) -> &[&str] {
+        &["Name", "IncludeUserName"]
+    }
+
+    fn capabilities(&self) -> &[Capability] {
+        &[Capability::Process]
+    }
+
     fn execute(
         &self,
         context: CmdletContext,
         _evaluator: &mut pwsh_runtime::Evaluator,
     ) -> Result<Vec<Value>, RuntimeError> {
-        // For now, we use mock process data for demonstration
-        // In a production implementation, this would read from the OS
+        let include_user_name =
+            parse_switch(context.get_parameter("IncludeUserName"), "Get-Process")?;
 
-        let mock_processes = create_mock_processes();
+        let mut processes = list_processes(include_user_name)?;
+        processes.sort_by(|a, b| {
+            a.name
+                .to_lowercase()
+                .cmp(&b.name.to_lowercase())
+                .then(a.id.cmp(&b.id))
+        });
 
-        // Check if we have a -Name parameter to filter by name
+        // Check if we have a -Name parameter to filter by name; each name
+        // may be a wildcard pattern such as "chr*"
         if let Some(name_value) = context.get_parameter("Name") {
-            let filter_name = name_value.to_string().to_lowercase();
-            let filtered: Vec<Value> = mock_processes
-                .into_iter()
-                .filter(|proc| {
-                    if let Value::Object(props) = proc {
-                        if let Some(Value::String(name)) = props.get("Name") {
-                            return name.to_lowercase().contains(&filter_name);
-                        }
-                    }
-                    false
-                })
-                .collect();
-            return Ok(filtered);
+            let patterns = match name_value {
+                Value::Array(names) => names
+                    .read()
+                    .iter()
+                    .map(|n| WildcardPattern::new(&n.to_string()))
+                    .collect::<Result<Vec<_>, _>>()?,
+                name => vec![WildcardPattern::new(&name.to_string())?],
+            };
+            processes.retain(|process| patterns.iter().any(|p| p.is_match(&process.name)));
         }
 
-        Ok(mock_processes)
+        Ok(processes
+            .iter()
+            .map(|process| process.to_value(include_user_name))
+            .collect())
     }
 }
 
-/// Create mock process data for demonstration
-fn create_mock_processes() -> Vec<Value> {
-    vec![
-        create_process("System", 4, 0.0, 1024),
-        create_process("explorer", 1234, 15.5, 102400),
-        create_process("chrome", 5678, 45.2, 512000),
-        create_process("code", 9012, 23.1, 256000),
-        create_process("pwsh", 3456, 5.0, 51200),
-    ]
+#[cfg(target_os = "linux")]
+use procfs::list_processes;
+
+#[cfg(not(target_os = "linux"))]
+fn list_processes(_include_user_name: bool) -> Result<Vec<ProcessInfo>, RuntimeError> {
+    Err(pwsh_runtime::ErrorRecord::new(
+        pwsh_runtime::ErrorCategory::InvalidOperation,
+        "PlatformNotSupported,Get-Process",
+        "Get-Process can't list the processes of this platform",
+    )
+    .into())
 }
 
-/// Helper to create a process object
-fn create_process(name: &str, id: i32, cpu: f64, memory: i64) -> Value {
-    let mut props = HashMap::new();
-    props.insert("Name".to_string(), Value::String(name.to_string()));
-    props.insert("Id".to_string(), Value::Number(id as f64));
-    props.insert("CPU".to_string(), Value::Number(cpu));
-    props.insert("WorkingSet".to_string(), Value::Number(memory as f64));
-    Value::Object(props)
+/// The processes as Linux describes them under /proc
+#[cfg(target_os = "linux")]
+mod procfs {
+    use super::ProcessInfo;
+    use chrono::{Local, NaiveDateTime, TimeZone};
+    use pwsh_runtime::{ErrorRecord, RuntimeError};
+    use std::collections::HashMap;
+    use std::fs;
+    use std::path::Path;
+
+    /// Clock ticks per second in /proc's times: the kernel's USER_HZ, which
+    /// is 100 on the architectures Linux runs on today
+    const TICKS_PER_SECOND: u64 = 100;
+
+    /// Every process that is still running while it is read; one that ends
+    /// part way through is left out
+    pub(super) fn list_processes(
+        include_user_name: bool,
+    ) -> Result<Vec<ProcessInfo>, RuntimeError> {
+        let entries = fs::read_dir("/proc").map_err(|e| {
+            ErrorRecord::io(
+                &e,
+                "ProcessListUnavailable,Get-Process",
+                format!("Failed to read /proc: {}", e),
+            )
+        })?;
+        let boot_time = fs::read_to_string("/proc/stat")
+            .ok()
+            .and_then(|stat| parse_boot_time(&stat));
+        let users = if include_user_name {
+            fs::read_to_string("/etc/passwd")
+                .map(|passwd| parse_user_names(&passwd))
+                .unwrap_or_default()
+        } else {
+            HashMap::new()
+        };
+
+        Ok(entries
+            .flatten()
+            .filter_map(|entry| {
+                let id = entry.file_name().to_str()?.parse().ok()?;
+                read_process(&entry.path(), id, boot_time, &users)
+            })
+            .collect())
+    }
+
+    fn read_process(
+        dir: &Path,
+        id: u32,
+        boot_time: Option<i64>,
+        users: &HashMap<u32, String>,
+    ) -> Option<ProcessInfo> {
+        let name = fs::read_to_string(dir.join("comm")).ok()?;
+        let times = fs::read_to_string(dir.join("stat"))
+            .ok()
+            .and_then(|stat| parse_stat(&stat));
+        let status = fs::read_to_string(dir.join("status")).unwrap_or_default();
+        Some(ProcessInfo {
+            name: name.trim_end_matches('\n').to_string(),
+            id,
+            cpu: times.map(|t| t.cpu_seconds()),
+            working_set: status_field(&status, "VmRSS")
+                .and_then(|rss| rss.trim_end_matches("kB").trim().parse::<u64>().ok())
+                .map(|kilobytes| kilobytes * 1024),
+            // A kernel thread has no executable, and another user's
+            // process may not let us see it
+            path: fs::read_link(dir.join("exe"))
+                .ok()
+                .map(|exe| exe.to_string_lossy().into_owned()),
+            start_time: times
+                .zip(boot_time)
+                .and_then(|(t, boot)| t.start_time(boot)),
+            user_name: status_field(&status, "Uid")
+                .and_then(|uids| uids.split_whitespace().next()?.parse().ok())
+                .and_then(|uid| users.get(&uid).cloned()),
+        })
+    }
+
+    /// The times in /proc/<id>/stat, in clock ticks
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub(super) struct StatTimes {
+        user: u64,
+        system: u64,
+        /// Since the computer started
+        start: u64,
+    }
+
+    impl StatTimes {
+        pub(super) fn cpu_seconds(&self) -> f64 {
+            (self.user + self.system) as f64 / TICKS_PER_SECOND as f64
+        }
+
+        pub(super) fn start_time(&self, boot_time: i64) -> Option<NaiveDateTime> {
+            let seconds = boot_time.checked_add((self.start / TICKS_PER_SECOND) as i64)?;
+            let nanos = (self.start % TICKS_PER_SECOND * 1_000_000_000 / TICKS_PER_SECOND) as u32;
+            Some(Local.timestamp_opt(seconds, nanos).single()?.naive_local())
+        }
+    }
+
+    /// The times from the text of /proc/<id>/stat. The name in parentheses
+    /// may hold spaces and parentheses itself, so the fields are counted
+    /// from the last ')': utime, stime and starttime are the 14th, 15th and
+    /// 22nd fields of the line.
+    pub(super) fn parse_stat(stat: &str) -> Option<StatTimes> {
+        let fields: Vec<&str> = stat[stat.rfind(')')? + 1..].split_whitespace().collect();
+        let field = |number: usize| fields.get(number - 3)?.parse().ok();
+        Some(StatTimes {
+            user: field(14)?,
+            system: field(15)?,
+            start: field(22)?,
+        })
+    }
+
+    /// When the computer started, in seconds since 1970: /proc/stat's btime
+    pub(super) fn parse_boot_time(stat: &str) -> Option<i64> {
+        stat.lines()
+            .find_map(|line| line.strip_prefix("btime "))?
+            .trim()
+            .parse()
+            .ok()
+    }
+
+    /// The value of a `Name:\tvalue` line of /proc/<id>/status
+    pub(super) fn status_field<'a>(status: &'a str, name: &str) -> Option<&'a str> {
+        status.lines().find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            (key == name).then(|| value.trim())
+        })
+    }
+
+    /// User names by user id, from the text of /etc/passwd
+    pub(super) fn parse_user_names(passwd: &str) -> HashMap<u32, String> {
+        passwd
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split(':');
+                let name = fields.next()?;
+                let uid = fields.nth(1)?.parse().ok()?;
+                Some((uid, name.to_string()))
+            })
+            .collect()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    #[test]
-    fn test_get_process_all() {
-        let cmdlet = GetProcessCmdlet;
-        let context = CmdletContext::new();
+    #[cfg(target_os = "linux")]
+    fn run(context: CmdletContext) -> Vec<Value> {
         let mut evaluator = pwsh_runtime::Evaluator::new();
-        let result = cmdlet.execute(context, &mut evaluator).unwrap();
-        assert_eq!(result.len(), 5);
+        GetProcessCmdlet.execute(context, &mut evaluator).unwrap()
+    }
+
+    #[cfg(target_os = "linux")]
+    fn with_name(name: Value) -> CmdletContext {
+        CmdletContext::new().with_parameter("Name".to_string(), name)
+    }
+
+    #[cfg(target_os = "linux")]
+    fn ids(processes: &[Value]) -> Vec<u32> {
+        processes
+            .iter()
+            .filter_map(|p| match p.get_property("Id") {
+                Some(Value::Number(id)) => Some(id as u32),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// This test's own process, as Get-Process lists it
+    #[cfg(target_os = "linux")]
+    fn this_process(include_user_name: bool) -> Value {
+        let context = CmdletContext::new().with_parameter(
+            "IncludeUserName".to_string(),
+            Value::Boolean(include_user_name),
+        );
+        run(context)
+            .into_iter()
+            .find(|p| p.get_property("Id") == Some(Value::Number(std::process::id() as f64)))
+            .expect("Get-Process lists the running test")
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_get_process_lists_this_process() {
+        let process = this_process(false);
+        assert!(matches!(process.get_property("Name"), Some(Value::String(n)) if !n.is_empty()));
+        assert!(matches!(process.get_property("CPU"), Some(Value::Number(cpu)) if cpu >= 0.0));
+        assert!(matches!(process.get_property("WorkingSet"), Some(Value::Number(ws)) if ws > 0.0));
+        let exe = std::env::current_exe().unwrap();
+        assert_eq!(
+            process.get_property("Path"),
+            Some(Value::String(exe.to_string_lossy().as_ref().into()))
+        );
+        assert!(matches!(
+            process.get_property("StartTime"),
+            Some(Value::DateTime(_))
+        ));
+        assert_eq!(process.get_property("UserName"), None);
+        assert_eq!(
+            process.properties().unwrap().type_names().first(),
+            Some(&"System.Diagnostics.Process".to_string())
+        );
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_get_process_user_name() {
+        let process = this_process(true);
+        assert!(matches!(
+            process.get_property("UserName"),
+            Some(Value::String(_))
+        ));
+
+        // -IncludeUserName:$false leaves it out
+        assert_eq!(this_process(false).get_property("UserName"), None);
     }
 
     #[test]
+    #[cfg(target_os = "linux")]
     fn test_get_process_by_name() {
-        let cmdlet = GetProcessCmdlet;
-        let context = CmdletContext::new()
-            .with_parameter("Name".to_string(), Value::String("chrome".to_string()));
-        let mut evaluator = pwsh_runtime::Evaluator::new();
-        let result = cmdlet.execute(context, &mut evaluator).unwrap();
-        assert_eq!(result.len(), 1);
+        let name = this_process(false).get_property("Name").unwrap();
+        let result = run(with_name(name.clone()));
+        assert!(ids(&result).contains(&std::process::id()));
+        assert!(result
+            .iter()
+            .all(|p| p.get_property("Name").as_ref() == Some(&name)));
+    }
 
-        if let Value::Object(props) = &result[0] {
-            assert_eq!(
-                props.get("Name"),
-                Some(&Value::String("chrome".to_string()))
-            );
-        } else {
-            panic!("Expected object result");
-        }
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_get_process_by_wildcard_names() {
+        let name = this_process(false)
+            .get_property("Name")
+            .unwrap()
+            .to_string();
+        let prefix: String = name.chars().take(name.chars().count() - 1).collect();
+
+        let names = Value::Array(
+            vec![
+                Value::String(format!("{prefix}*").into()),
+                Value::String("no-such-process".into()),
+            ]
+            .into(),
+        );
+        assert!(ids(&run(with_name(names))).contains(&std::process::id()));
+
+        // A plain name is matched whole, not as a prefix
+        let result = run(with_name(Value::String(prefix.into())));
+        assert!(!ids(&result).contains(&std::process::id()));
     }
 
     #[test]
-    fn test_get_process_properties() {
-        let cmdlet = GetProcessCmdlet;
-        let context = CmdletContext::new();
+    #[cfg(target_os = "linux")]
+    fn test_get_process_sorted_by_name_then_id() {
+        let result = run(CmdletContext::new());
+        let keys: Vec<(String, u32)> = result
+            .iter()
+            .map(|p| p.get_property("Name").unwrap().to_string().to_lowercase())
+            .zip(ids(&result))
+            .collect();
+        let mut sorted = keys.clone();
+        sorted.sort();
+        assert_eq!(keys, sorted);
+    }
+
+    #[test]
+    #[cfg(not(target_os = "linux"))]
+    fn test_get_process_is_not_supported() {
         let mut evaluator = pwsh_runtime::Evaluator::new();
-        let result = cmdlet.execute(context, &mut evaluator).unwrap();
+        let error = GetProcessCmdlet
+            .execute(CmdletContext::new(), &mut evaluator)
+            .unwrap_err();
+        assert_eq!(error.error_id(), "PlatformNotSupported,Get-Process");
+    }
 
-        if let Value::Object(props) = &result[0] {
-            assert!(props.contains_key("Name"));
-            assert!(props.contains_key("Id"));
-            assert!(props.contains_key("CPU"));
-            assert!(props.contains_key("WorkingSet"));
-        } else {
-            panic!("Expected object result");
-        }
+    #[test]
+    fn test_unknown_properties_are_null() {
+        let kernel_thread = ProcessInfo {
+            name: "kthreadd".to_string(),
+            id: 2,
+            cpu: Some(0.5),
+            working_set: None,
+            path: None,
+            start_time: None,
+            user_name: None,
+        };
+        let value = kernel_thread.to_value(true);
+        assert_eq!(value.get_property("CPU"), Some(Value::Number(0.5)));
+        // What the platform doesn't report is $null rather than missing
+        assert_eq!(value.get_property("WorkingSet"), Some(Value::Null));
+        assert_eq!(value.get_property("Path"), Some(Value::Null));
+        assert_eq!(value.get_property("StartTime"), Some(Value::Null));
+        assert_eq!(value.get_property("UserName"), Some(Value::Null));
+        assert_eq!(kernel_thread.to_value(false).get_property("UserName"), None);
+    }
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_read_proc_files() {
+        use chrono::{Local, TimeZone};
+        use procfs::{parse_boot_time, parse_stat, parse_user_names, status_field};
+
+        // The name may hold spaces and parentheses
+        let stat = "1234 (my (odd) name) S 1 1234 1234 0 -1 4194560 500 0 0 0 \
+                    250 50 0 0 20 0 1 0 9000 10000 100";
+        let times = parse_stat(stat).unwrap();
+        assert_eq!(times.cpu_seconds(), 3.0);
+        let boot = Local
+            .with_ymd_and_hms(2024, 3, 5, 8, 0, 0)
+            .unwrap()
+            .timestamp();
+        assert_eq!(
+            times.start_time(boot),
+            chrono::NaiveDate::from_ymd_opt(2024, 3, 5).and_then(|d| d.and_hms_opt(8, 1, 30))
+        );
+        assert_eq!(parse_stat("1234 (cut"), None);
+
+        assert_eq!(
+            parse_boot_time("cpu  1 2 3\nbtime 1709625600\nprocesses 42\n"),
+            Some(1709625600)
+        );
+
+        let status = "Name:\tbash\nUid:\t1000\t1000\t1000\t1000\nVmRSS:\t    5120 kB\n";
+        assert_eq!(status_field(status, "VmRSS"), Some("5120 kB"));
+        assert_eq!(status_field(status, "Uid"), Some("1000\t1000\t1000\t1000"));
+        assert_eq!(status_field(status, "VmSwap"), None);
+
+        let users = parse_user_names(
+            "root:x:0:0:root:/root:/bin/bash\nann:x:1000:1000::/home/ann:/bin/sh\n",
+        );
+        assert_eq!(users.get(&1000).map(String::as_str), Some("ann"));
+        assert_eq!(users.get(&0).map(String::as_str), Some("root"));
     }
 
     #[test]
+    #[cfg(target_os = "linux")]
     fn test_week14_success_criteria() {
         // Week 14 Success Criteria from ROADMAP.md:
         // Get-Process |
@@ -123,8 +483,13 @@ mod tests {
         use pwsh_parser::Parser;
         use pwsh_runtime::Evaluator;
 
-        let code = r#"Get-Process | Where-Object { $_.CPU -gt 10 } | Select-Object Name, CPU"#;
-        let mut lexer = Lexer::new(code);
+        // Which processes are busy changes from run to run, so this picks
+        // out the test's own process
+        let code = format!(
+            "Get-Process | Where-Object {{ $_.Id -eq {} }} | Select-Object Name, CPU",
+            std::process::id()
+        );
+        let mut lexer = Lexer::new(&code);
         let tokens = lexer.tokenize().unwrap();
         let mut parser = Parser::new(tokens);
         let program = parser.parse().unwrap();
@@ -135,31 +500,22 @@ mod tests {
 
         let result = evaluator.eval(program).unwrap();
 
-        // Should return array of objects with Name and CPU properties
-        // Processes with CPU > 10: explorer (15.5), chrome (45.2), code (23.1)
+        // The result has just the Name and CPU properties
         match result {
-            Value::Array(values) => {
-                assert_eq!(values.len(), 3, "Expected 3 processes with CPU > 10");
-
-                // Verify each result has Name and CPU properties
-                for val in &values {
-                    if let Value::Object(props) = val {
-                        assert!(
-                            props.contains_key("Name"),
-                            "Result should have Name property"
-                        );
-                        assert!(props.contains_key("CPU"), "Result should have CPU property");
-                        assert_eq!(
-                            props.len(),
-                            2,
-                            "Result should only have Name and CPU properties"
-                        );
-                    } else {
-                        panic!("Expected Object in results, got {:?}", val);
-                    }
-                }
+            Value::Object(props) => {
+                let props = props.read();
+                assert!(
+                    props.contains_key("Name"),
+                    "Result should have Name property"
+                );
+                assert!(props.contains_key("CPU"), "Result should have CPU property");
+                assert_eq!(
+                    props.len(),
+                    2,
+                    "Result should only have Name and CPU properties"
+                );
             }
-            _ => panic!("Expected Array result, got {:?}", result),
+            _ => panic!("Expected Object result, got {:?}", result),
         }
     }
 }
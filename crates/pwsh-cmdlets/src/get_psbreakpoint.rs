@@ -0,0 +1,47 @@
+/// Get-PSBreakpoint cmdlet - lists the breakpoints set in the session
+use pwsh_runtime::{unroll_to_items, Cmdlet, CmdletContext, ErrorRecord, RuntimeError, Value};
+
+/// Get-PSBreakpoint writes the breakpoints Set-PSBreakpoint set, in the
+/// order they were set, with how many times each was hit; -Id picks out
+/// the breakpoints with those ids.
+pub struct GetPSBreakpointCmdlet;
+
+impl Cmdlet for GetPSBreakpointCmdlet {
+    fn name(&self) -> &str {
+        "Get-PSBreakpoint"
+    }
+
+    fn parameters(&self) -> &[&str] {
+        &["Id"]
+    }
+
+    fn execute(
+        &self,
+        context: CmdletContext,
+        evaluator: &mut pwsh_runtime::Evaluator,
+    ) -> Result<Vec<Value>, RuntimeError> {
+        let ids = match context.get_parameter("Id").or(context.get_argument(0)) {
+            None => None,
+            Some(ids) => Some(
+                unroll_to_items(vec![ids.clone()])
+                    .iter()
+                    .map(|id| match id.to_number() {
+                        Some(n) if n >= 0.0 && n.fract() == 0.0 => Ok(n as usize),
+                        _ => Err(ErrorRecord::invalid_argument(
+                            "InvalidId,Get-PSBreakpoint",
+                            format!("-Id must be a breakpoint's number, not {}", id),
+                        )
+                        .into()),
+                    })
+                    .collect::<Result<Vec<_>, RuntimeError>>()?,
+            ),
+        };
+        Ok(evaluator
+            .debugger()
+            .breakpoints()
+            .iter()
+            .filter(|breakpoint| ids.as_ref().is_none_or(|ids| ids.contains(&breakpoint.id)))
+            .map(|breakpoint| breakpoint.to_value())
+            .collect())
+    }
+}
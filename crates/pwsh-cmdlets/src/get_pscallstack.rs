@@ -0,0 +1,28 @@
+/// Get-PSCallStack cmdlet - the calls running: each function or script block, its arguments and where it is
+use pwsh_runtime::{Cmdlet, CmdletContext, RuntimeError, Value};
+
+/// Get-PSCallStack writes a frame for each call running, innermost first:
+/// the function (or `<ScriptBlock>` for a script or script block), the
+/// arguments it was given and its location, `deploy.ps1: line 12`. The
+/// innermost frame is where Get-PSCallStack was called; each of the others
+/// is at its call to the frame before it.
+pub struct GetPSCallStackCmdlet;
+
+impl Cmdlet for GetPSCallStackCmdlet {
+    fn name(&self) -> &str {
+        "Get-PSCallStack"
+    }
+
+    fn execute(
+        &self,
+        _context: CmdletContext,
+        evaluator: &mut pwsh_runtime::Evaluator,
+    ) -> Result<Vec<Value>, RuntimeError> {
+        Ok(evaluator
+            .call_stack()
+            .iter()
+            .rev()
+            .map(|frame| frame.to_value())
+            .collect())
+    }
+}
@@ -0,0 +1,71 @@
+/// Get-PwshConfiguration cmdlet - the settings the session started with
+use pwsh_runtime::{strict_mode_name, Cmdlet, CmdletContext, PropertyMap, RuntimeError, Value};
+
+fn string(text: &str) -> Value {
+    Value::String(text.into())
+}
+
+/// Get-PwshConfiguration writes the session's configuration, as read from
+/// `pwsh.config.json` or given by the host: an object with its `Path` ($null
+/// when there was no file), `DefaultEncoding`, `StrictMode`, `HistorySize`,
+/// `Paging`, `ColorScheme` (a color for each of `Keyword`, `Variable`, `Parameter`,
+/// `Operator` and `Default`), `ModulePaths` and `ExecutionPolicy`. These are
+/// the starting settings; Set-StrictMode and Set-ExecutionPolicy don't
+/// change them.
+pub struct GetPwshConfigurationCmdlet;
+
+impl Cmdlet for GetPwshConfigurationCmdlet {
+    fn name(&self) -> &str {
+        "Get-PwshConfiguration"
+    }
+
+    fn execute(
+        &self,
+        _context: CmdletContext,
+        evaluator: &mut pwsh_runtime::Evaluator,
+    ) -> Result<Vec<Value>, RuntimeError> {
+        let config = evaluator.configuration();
+
+        let mut colors = PropertyMap::with_capacity(5);
+        for (name, color) in config.color_scheme.entries() {
+            colors.insert(name.to_string(), string(color));
+        }
+        let module_paths: Vec<Value> = config
+            .module_paths
+            .iter()
+            .map(|path| string(&path.display().to_string()))
+            .collect();
+
+        let mut props = PropertyMap::with_capacity(8);
+        props.insert(
+            "Path".to_string(),
+            config
+                .path
+                .as_ref()
+                .map_or(Value::Null, |path| string(&path.display().to_string())),
+        );
+        props.insert(
+            "DefaultEncoding".to_string(),
+            config
+                .default_encoding
+                .as_deref()
+                .map_or(Value::Null, string),
+        );
+        props.insert(
+            "StrictMode".to_string(),
+            string(&strict_mode_name(config.strict_mode)),
+        );
+        props.insert(
+            "HistorySize".to_string(),
+            Value::Number(config.history_size as f64),
+        );
+        props.insert("Paging".to_string(), Value::Boolean(config.paging));
+        props.insert("ColorScheme".to_string(), Value::Object(colors.into()));
+        props.insert("ModulePaths".to_string(), Value::Array(module_paths.into()));
+        props.insert(
+            "ExecutionPolicy".to_string(),
+            string(&config.execution_policy.to_string()),
+        );
+        Ok(vec![Value::Object(props.into())])
+    }
+}
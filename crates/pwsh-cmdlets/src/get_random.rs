@@ -0,0 +1,338 @@
+/// Get-Random cmdlet - random numbers, or random picks from the input
+use pwsh_runtime::{
+    parse_switch, unroll_to_items, Cmdlet, CmdletContext, ErrorRecord, RuntimeError, Value,
+};
+
+/// The largest number Get-Random returns by default (Int32.MaxValue)
+const DEFAULT_MAXIMUM: f64 = 2147483647.0;
+
+/// A number parameter such as -Minimum, if it was given
+fn parse_number(value: Option<&Value>, name: &str) -> Result<Option<f64>, RuntimeError> {
+    let Some(value) = value else {
+        return Ok(None);
+    };
+    match value.to_number() {
+        Some(n) if n.is_finite() => Ok(Some(n)),
+        _ => Err(ErrorRecord::invalid_argument(
+            "InvalidNumber,Get-Random",
+            format!(
+                "Cannot convert value \"{}\" of -{} to a number.",
+                value, name
+            ),
+        )
+        .into()),
+    }
+}
+
+/// -Count: how many numbers or items to return
+fn parse_count(value: Option<&Value>) -> Result<Option<usize>, RuntimeError> {
+    match parse_number(value, "Count")? {
+        Some(n) if n >= 1.0 && n.fract() == 0.0 => Ok(Some(n as usize)),
+        Some(n) => Err(ErrorRecord::invalid_argument(
+            "InvalidCount,Get-Random",
+            format!(
+                "Get-Random: -Count must be a positive whole number, not {}",
+                n
+            ),
+        )
+        .into()),
+        None => Ok(None),
+    }
+}
+
+/// Each item's weight: the -Weight property, or what the -Weight script block
+/// returns for it as $_
+fn item_weights(
+    items: &[Value],
+    weight: &Value,
+    evaluator: &mut pwsh_runtime::Evaluator,
+) -> Result<Vec<f64>, RuntimeError> {
+    let mut weights = Vec::with_capacity(items.len());
+    for item in items {
+        let value = match weight {
+            Value::ScriptBlock(block) => evaluator.execute_script_block(block, item.clone())?,
+            property => item
+                .get_property(&property.to_string())
+                .unwrap_or(Value::Null),
+        };
+        match value.to_number() {
+            Some(n) if n >= 0.0 && n.is_finite() => weights.push(n),
+            _ => {
+                return Err(ErrorRecord::invalid_argument(
+                    "InvalidWeight,Get-Random",
+                    format!(
+                        "Get-Random: the weight of '{}' must be a number of 0 or more, not '{}'",
+                        item, value
+                    ),
+                )
+                .into())
+            }
+        }
+    }
+    Ok(weights)
+}
+
+/// Pick up to `count` different items, each with a chance in proportion to
+/// its weight among the items not picked yet. Items weighing 0 are never picked.
+fn pick_weighted(
+    mut items: Vec<Value>,
+    mut weights: Vec<f64>,
+    count: usize,
+    evaluator: &mut pwsh_runtime::Evaluator,
+) -> Vec<Value> {
+    let mut picked = Vec::with_capacity(count.min(items.len()));
+    while picked.len() < count {
+        let total: f64 = weights.iter().sum();
+        if total <= 0.0 {
+            break;
+        }
+        let mut target = evaluator.random().next_f64() * total;
+        let mut index = weights.iter().rposition(|w| *w > 0.0).unwrap_or(0);
+        for (i, weight) in weights.iter().enumerate() {
+            if *weight > 0.0 && target < *weight {
+                index = i;
+                break;
+            }
+            target -= weight;
+        }
+        weights.swap_remove(index);
+        picked.push(items.swap_remove(index));
+    }
+    picked
+}
+
+/// Get-Random returns a random whole number from 0 up to (not including)
+/// Int32.MaxValue, or from -Minimum up to -Maximum; a fractional bound gives
+/// a fractional number. Given input (by the pipeline or -InputObject) it
+/// returns -Count different items picked from it instead, -Shuffle returns
+/// all of them in a random order, and -Weight (a property name or script
+/// block) makes some items likelier than others; with -Shuffle, likelier to
+/// come first. Items weighing 0 are never returned. -SetSeed seeds the
+/// session's generator, so the results that follow repeat from run to run.
+pub struct GetRandomCmdlet;
+
+impl Cmdlet for GetRandomCmdlet {
+    fn name(&self) -> &str {
+        "Get-Random"
+    }
+
+    fn parameters(&self) -> &[&str] {
+        &[
+            "InputObject",
+            "Minimum",
+            "Maximum",
+            "Count",
+            "SetSeed",
+            "Shuffle",
+            "Weight",
+        ]
+    }
+
+    fn execute(
+        &self,
+        context: CmdletContext,
+        evaluator: &mut pwsh_runtime::Evaluator,
+    ) -> Result<Vec<Value>, RuntimeError> {
+        if let Some(seed) = parse_number(context.get_parameter("SetSeed"), "SetSeed")? {
+            evaluator.set_random_seed(seed as i64 as u64);
+        }
+        let count = parse_count(context.get_parameter("Count"))?;
+        let shuffle = parse_switch(context.get_parameter("Shuffle"), "Get-Random")?;
+        let weight = context.get_parameter("Weight").cloned();
+
+        // Support: Get-Random 10 (a maximum), Get-Random @(1, 2, 3) and
+        // Get-Random 'a', 'b' (items to pick from)
+        let positional_items = context.arguments.len() > 1
+            || matches!(context.arguments.first(), Some(Value::Array(_)));
+        let items = match context.get_parameter("InputObject") {
+            Some(input) => Some(unroll_to_items(vec![input.clone()])),
+            None if !context.pipeline_input.is_empty() => Some(context.pipeline_input.clone()),
+            None if positional_items => Some(unroll_to_items(context.arguments.clone())),
+            None => None,
+        };
+
+        if let Some(mut items) = items {
+            if let Some(weight) = weight {
+                let weights = item_weights(&items, &weight, evaluator)?;
+                let count = if shuffle {
+                    items.len()
+                } else {
+                    count.unwrap_or(1)
+                };
+                return Ok(pick_weighted(items, weights, count, evaluator));
+            }
+            // Pick by shuffling the front of the list: each of the first
+            // `count` places gets a random item from those left
+            let count = if shuffle {
+                items.len()
+            } else {
+                count.unwrap_or(1).min(items.len())
+            };
+            for i in 0..count {
+                let j = i + evaluator.random().below((items.len() - i) as u64) as usize;
+                items.swap(i, j);
+            }
+            items.truncate(count);
+            return Ok(items);
+        }
+        if shuffle || weight.is_some() {
+            return Ok(Vec::new());
+        }
+
+        let maximum = parse_number(
+            context
+                .get_parameter("Maximum")
+                .or_else(|| context.get_argument(0)),
+            "Maximum",
+        )?;
+        let minimum = parse_number(context.get_parameter("Minimum"), "Minimum")?;
+        let (low, high) = (minimum.unwrap_or(0.0), maximum.unwrap_or(DEFAULT_MAXIMUM));
+        if low >= high {
+            return Err(ErrorRecord::invalid_argument(
+                "MinGreaterThanOrEqualMax,Get-Random",
+                format!(
+                    "The Minimum value ({}) cannot be greater than or equal to the Maximum value ({}).",
+                    low, high
+                ),
+            ).into());
+        }
+
+        let whole = low.fract() == 0.0 && high.fract() == 0.0;
+        let numbers = (0..count.unwrap_or(1))
+            .map(|_| {
+                let random = evaluator.random();
+                let n = if whole {
+                    low + random.below((high - low) as u64) as f64
+                } else {
+                    low + random.next_f64() * (high - low)
+                };
+                Value::Number(n)
+            })
+            .collect();
+        Ok(numbers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(context: CmdletContext, evaluator: &mut pwsh_runtime::Evaluator) -> Vec<Value> {
+        GetRandomCmdlet.execute(context, evaluator).unwrap()
+    }
+
+    fn numbers(range: std::ops::Range<u32>) -> Vec<Value> {
+        range.map(|n| Value::Number(n as f64)).collect()
+    }
+
+    #[test]
+    fn test_get_random_numbers_in_range() {
+        let mut evaluator = pwsh_runtime::Evaluator::new();
+        let context = CmdletContext::new()
+            .with_parameter("Minimum".to_string(), Value::Number(5.0))
+            .with_parameter("Maximum".to_string(), Value::Number(8.0))
+            .with_parameter("Count".to_string(), Value::Number(50.0));
+        let result = run(context, &mut evaluator);
+        assert_eq!(result.len(), 50);
+        assert!(result.iter().all(|v| matches!(
+            v,
+            Value::Number(n) if (5.0..8.0).contains(n) && n.fract() == 0.0
+        )));
+
+        let context = CmdletContext::new().with_arguments(vec![Value::Number(0.5)]);
+        let result = run(context, &mut evaluator);
+        assert!(matches!(result[0], Value::Number(n) if (0.0..0.5).contains(&n)));
+    }
+
+    #[test]
+    fn test_get_random_seed_repeats_results() {
+        let mut evaluator = pwsh_runtime::Evaluator::new();
+        let seeded = || {
+            CmdletContext::with_input(numbers(0..10))
+                .with_parameter("SetSeed".to_string(), Value::Number(42.0))
+                .with_parameter("Shuffle".to_string(), Value::Boolean(true))
+        };
+        let first = run(seeded(), &mut evaluator);
+        assert_eq!(run(seeded(), &mut evaluator), first);
+        assert_ne!(first, numbers(0..10));
+
+        let mut sorted = first.clone();
+        sorted.sort_by(|a, b| a.to_number().partial_cmp(&b.to_number()).unwrap());
+        assert_eq!(sorted, numbers(0..10));
+    }
+
+    #[test]
+    fn test_get_random_picks_distinct_items() {
+        let mut evaluator = pwsh_runtime::Evaluator::new();
+        let context = CmdletContext::new()
+            .with_parameter(
+                "InputObject".to_string(),
+                Value::Array(numbers(0..5).into()),
+            )
+            .with_parameter("Count".to_string(), Value::Number(3.0));
+        let result = run(context, &mut evaluator);
+        assert_eq!(result.len(), 3);
+        for (i, value) in result.iter().enumerate() {
+            assert!(numbers(0..5).contains(value));
+            assert!(!result[..i].contains(value));
+        }
+
+        let context = CmdletContext::with_input(numbers(0..3))
+            .with_parameter("Count".to_string(), Value::Number(10.0));
+        assert_eq!(run(context, &mut evaluator).len(), 3);
+    }
+
+    #[test]
+    fn test_get_random_weighted_selection() {
+        let mut evaluator = pwsh_runtime::Evaluator::new();
+        let item = |name: &str, weight: f64| {
+            let mut props = pwsh_runtime::PropertyMap::new();
+            props.insert("Name".to_string(), Value::String(name.into()));
+            props.insert("Weight".to_string(), Value::Number(weight));
+            Value::Object(props.into())
+        };
+        let items = vec![item("never", 0.0), item("rare", 1.0), item("common", 99.0)];
+
+        let mut common = 0;
+        for _ in 0..200 {
+            let context = CmdletContext::with_input(items.clone())
+                .with_parameter("Weight".to_string(), Value::String("Weight".into()));
+            let picked = run(context, &mut evaluator);
+            let name = picked[0].get_property("Name").unwrap().to_string();
+            assert_ne!(name, "never");
+            common += usize::from(name == "common");
+        }
+        assert!(common > 150);
+
+        // Without replacement, and never the items that weigh nothing
+        let context = CmdletContext::with_input(items)
+            .with_parameter("Weight".to_string(), Value::String("Weight".into()))
+            .with_parameter("Count".to_string(), Value::Number(3.0));
+        assert_eq!(run(context, &mut evaluator).len(), 2);
+    }
+
+    #[test]
+    fn test_get_random_errors() {
+        let mut evaluator = pwsh_runtime::Evaluator::new();
+        let context = CmdletContext::new()
+            .with_parameter("Minimum".to_string(), Value::Number(5.0))
+            .with_parameter("Maximum".to_string(), Value::Number(5.0));
+        let error = GetRandomCmdlet
+            .execute(context, &mut evaluator)
+            .unwrap_err();
+        assert_eq!(error.error_id(), "MinGreaterThanOrEqualMax,Get-Random");
+
+        let context = CmdletContext::with_input(vec![Value::String("a".into())])
+            .with_parameter("Weight".to_string(), Value::String("Missing".into()));
+        let error = GetRandomCmdlet
+            .execute(context, &mut evaluator)
+            .unwrap_err();
+        assert_eq!(error.error_id(), "InvalidWeight,Get-Random");
+
+        let context = CmdletContext::new().with_parameter("Count".to_string(), Value::Number(0.0));
+        let error = GetRandomCmdlet
+            .execute(context, &mut evaluator)
+            .unwrap_err();
+        assert_eq!(error.error_id(), "InvalidCount,Get-Random");
+    }
+}
@@ -0,0 +1,151 @@
+/// Get-Variable cmdlet - lists variables, optionally those of one scope
+use pwsh_runtime::{
+    parse_property_list, parse_switch, Cmdlet, CmdletContext, ErrorCategory, ErrorRecord,
+    PropertyMap, RuntimeError, Value, WildcardPattern,
+};
+
+/// The -ValueOnly switch. The parser hands a switch the word after it, so in
+/// `Get-Variable -ValueOnly count` the "value" of -ValueOnly is really the name.
+fn parse_value_only(value: Option<&Value>, names: &mut Vec<String>) -> Result<bool, RuntimeError> {
+    match value {
+        Some(Value::String(_)) | Some(Value::Array(_))
+            if parse_switch(value, "Get-Variable").is_err() =>
+        {
+            names.extend(parse_property_list(value));
+            Ok(true)
+        }
+        _ => parse_switch(value, "Get-Variable"),
+    }
+}
+
+/// Get-Variable writes a Name/Value object (or with -ValueOnly just the
+/// value) for each variable whose name matches. -Scope lists only the
+/// variables defined in one scope: `Global`, `Local`, `Script` or a number of
+/// scopes up, so `-Scope 1` inside a function looks at its caller's own
+/// variables, not those its caller sees from further out.
+pub struct GetVariableCmdlet;
+
+impl Cmdlet for GetVariableCmdlet {
+    fn name(&self) -> &str {
+        "Get-Variable"
+    }
+
+    fn parameters(&self) -> &[&str] {
+        &["Name", "Scope", "ValueOnly"]
+    }
+
+    fn execute(
+        &self,
+        context: CmdletContext,
+        evaluator: &mut pwsh_runtime::Evaluator,
+    ) -> Result<Vec<Value>, RuntimeError> {
+        let mut names = parse_property_list(context.get_parameter("Name"));
+        names.extend(parse_property_list(
+            context.get_argument(0).filter(|_| names.is_empty()),
+        ));
+        let value_only = parse_value_only(context.get_parameter("ValueOnly"), &mut names)?;
+        if names.is_empty() {
+            names.push("*".to_string());
+        }
+        let patterns = names
+            .iter()
+            .map(|name| WildcardPattern::new(name))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let scope = context
+            .get_parameter("Scope")
+            .map(|scope| scope.to_string());
+        let visible = match &scope {
+            Some(scope) => evaluator.variable_names_in_scope(scope)?,
+            None => evaluator.variable_names(),
+        };
+        let action = context.error_action;
+
+        // A name without wildcards that matches nothing is an error;
+        // a pattern that matches nothing is not
+        for (name, pattern) in names.iter().zip(&patterns) {
+            let is_literal = !name.contains(['*', '?', '[']);
+            if is_literal && !visible.iter().any(|v| pattern.is_match(v)) {
+                let error = ErrorRecord::new(
+                    ErrorCategory::ObjectNotFound,
+                    "VariableNotFound,Get-Variable",
+                    format!("Cannot find a variable with the name '{}'.", name),
+                )
+                .with_target(name.as_str());
+                evaluator.write_error(error.into(), action)?;
+            }
+        }
+
+        let mut output = Vec::new();
+        for name in visible {
+            if !patterns.iter().any(|pattern| pattern.is_match(&name)) {
+                continue;
+            }
+            let value = match &scope {
+                Some(scope) => evaluator.get_variable_in_scope(&name, scope)?,
+                None => evaluator.get_variable(&name),
+            }
+            .unwrap_or(Value::Null);
+
+            if value_only {
+                output.push(value);
+            } else {
+                let mut props = PropertyMap::with_capacity(2);
+                props.insert("Name".to_string(), Value::String(name.into()));
+                props.insert("Value".to_string(), value);
+                output.push(Value::Object(props.into()));
+            }
+        }
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pwsh_runtime::ActionPreference;
+
+    fn run(context: CmdletContext, evaluator: &mut pwsh_runtime::Evaluator) -> Vec<Value> {
+        GetVariableCmdlet.execute(context, evaluator).unwrap()
+    }
+
+    #[test]
+    fn test_get_variable_by_pattern() {
+        let mut evaluator = pwsh_runtime::Evaluator::new();
+        evaluator.set_variable("count", Value::Number(2.0));
+        evaluator.set_variable("color", Value::String("red".into()));
+        evaluator.set_variable("size", Value::Number(9.0));
+
+        let context = CmdletContext::new().with_arguments(vec![Value::String("co*".into())]);
+        let result = run(context, &mut evaluator);
+        assert_eq!(result.len(), 2);
+        assert_eq!(
+            result[0].get_property("Name"),
+            Some(Value::String("color".into()))
+        );
+
+        // `-ValueOnly size` arrives as ValueOnly = "size"
+        let context = CmdletContext::new()
+            .with_parameter("ValueOnly".to_string(), Value::String("size".into()));
+        assert_eq!(run(context, &mut evaluator), vec![Value::Number(9.0)]);
+    }
+
+    #[test]
+    fn test_get_variable_errors() {
+        let mut evaluator = pwsh_runtime::Evaluator::new();
+        let context = CmdletContext::new()
+            .with_arguments(vec![Value::String("missing".into())])
+            .with_error_action(ActionPreference::Stop);
+        let error = GetVariableCmdlet
+            .execute(context, &mut evaluator)
+            .unwrap_err();
+        assert_eq!(error.error_id(), "VariableNotFound,Get-Variable");
+        assert_eq!(error.category(), ErrorCategory::ObjectNotFound);
+
+        let context = CmdletContext::new().with_parameter("Scope".to_string(), Value::Number(3.0));
+        let error = GetVariableCmdlet
+            .execute(context, &mut evaluator)
+            .unwrap_err();
+        assert_eq!(error.error_id(), "ArgumentOutOfRange");
+    }
+}
@@ -1,69 +1,10 @@
 /// Group-Object cmdlet - group pipeline objects by value or property
-use pwsh_runtime::{Cmdlet, CmdletContext, RuntimeError, Value};
-use std::collections::{BTreeMap, HashMap};
-
-fn get_parameter_ci<'a>(context: &'a CmdletContext, name: &str) -> Option<&'a Value> {
-    if let Some(v) = context.parameters.get(name) {
-        return Some(v);
-    }
-
-    let name_lower = name.to_lowercase();
-    context
-        .parameters
-        .iter()
-        .find(|(k, _)| k.to_lowercase() == name_lower)
-        .map(|(_, v)| v)
-}
-
-fn parse_switch(value: Option<&Value>) -> Result<bool, RuntimeError> {
-    match value {
-        None => Ok(false),
-        Some(Value::Boolean(b)) => Ok(*b),
-        Some(Value::Number(n)) => Ok(*n != 0.0),
-        Some(Value::String(s)) => {
-            let v = s.trim().to_ascii_lowercase();
-            match v.as_str() {
-                "true" | "t" | "1" | "yes" | "y" => Ok(true),
-                "false" | "f" | "0" | "no" | "n" => Ok(false),
-                _ => Err(RuntimeError::InvalidOperation(format!(
-                    "Invalid boolean value: {}",
-                    s
-                ))),
-            }
-        }
-        Some(other) => Err(RuntimeError::InvalidOperation(format!(
-            "Invalid boolean value: {}",
-            other
-        ))),
-    }
-}
-
-fn parse_property_list(value: Option<&Value>) -> Vec<String> {
-    match value {
-        None => vec![],
-        Some(Value::String(s)) => vec![s.clone()],
-        Some(Value::Array(items)) => items
-            .iter()
-            .filter_map(|v| match v {
-                Value::String(s) => Some(s.clone()),
-                _ => None,
-            })
-            .collect(),
-        Some(other) => vec![other.to_string()],
-    }
-}
-
-fn unroll_to_items(values: Vec<Value>) -> Vec<Value> {
-    let mut out = Vec::new();
-    for v in values {
-        if let Value::Array(items) = v {
-            out.extend(items);
-        } else {
-            out.push(v);
-        }
-    }
-    out
-}
+use crate::parallel::{parallel_switch, run_in_parallel};
+use pwsh_runtime::{
+    parse_property_list, parse_switch, unroll_to_items, Cmdlet, CmdletContext, PropertyMap,
+    RuntimeError, Value,
+};
+use std::collections::BTreeMap;
 
 fn group_key_for_item(item: &Value, properties: &[String]) -> String {
     if properties.is_empty() {
@@ -81,17 +22,81 @@ fn group_key_for_item(item: &Value, properties: &[String]) -> String {
     parts.join(",")
 }
 
-fn build_group_info(name: String, group: Vec<Value>, no_element: bool) -> Value {
-    let mut props = HashMap::new();
+/// Group items by key (sorted), keeping each group's items in input order
+fn partition(input: Vec<Value>, properties: &[String]) -> BTreeMap<String, Vec<Value>> {
+    let mut groups: BTreeMap<String, Vec<Value>> = BTreeMap::new();
+    for item in input {
+        let key = group_key_for_item(&item, properties);
+        groups.entry(key).or_default().push(item);
+    }
+    groups
+}
+
+/// Partition consecutive chunks of the input on all cores, then merge the
+/// chunks' groups left to right so every group keeps input order, the same
+/// result as `partition`
+#[cfg(feature = "parallel")]
+fn partition_parallel(input: Vec<Value>, properties: &[String]) -> BTreeMap<String, Vec<Value>> {
+    use rayon::prelude::*;
+
+    input
+        .into_par_iter()
+        .fold(
+            BTreeMap::new,
+            |mut groups: BTreeMap<String, Vec<Value>>, item| {
+                let key = group_key_for_item(&item, properties);
+                groups.entry(key).or_default().push(item);
+                groups
+            },
+        )
+        .reduce(BTreeMap::new, |mut left, right| {
+            for (key, items) in right {
+                left.entry(key).or_default().extend(items);
+            }
+            left
+        })
+}
+
+#[cfg(not(feature = "parallel"))]
+fn partition_parallel(input: Vec<Value>, properties: &[String]) -> BTreeMap<String, Vec<Value>> {
+    partition(input, properties)
+}
+
+/// The values a group was keyed on, as they are rather than joined into its
+/// name: one per grouped property, or the item itself without properties
+fn group_values(item: &Value, properties: &[String]) -> Vec<Value> {
+    if properties.is_empty() {
+        return vec![item.clone()];
+    }
+    properties
+        .iter()
+        .map(|prop| item.get_property(prop).unwrap_or(Value::Null))
+        .collect()
+}
+
+fn build_group_info(
+    name: String,
+    group: Vec<Value>,
+    properties: &[String],
+    no_element: bool,
+) -> Value {
+    let values = group
+        .first()
+        .map(|item| group_values(item, properties))
+        .unwrap_or_default();
+    let mut props = PropertyMap::new();
     props.insert("Count".to_string(), Value::Number(group.len() as f64));
-    props.insert("Name".to_string(), Value::String(name));
+    props.insert("Name".to_string(), Value::String(name.into()));
+    props.insert("Values".to_string(), Value::Array(values.into()));
     if !no_element {
-        props.insert("Group".to_string(), Value::Array(group));
+        props.insert("Group".to_string(), Value::Array(group.into()));
     }
-    Value::Object(props)
+    Value::Object(props.into())
 }
 
 /// Group-Object groups values/objects by one or more properties.
+/// Groups come out sorted by name, or with -SortByCount largest first
+/// (names breaking ties), saving the usual `| Sort-Object Count -Descending`.
 pub struct GroupObjectCmdlet;
 
 impl Cmdlet for GroupObjectCmdlet {
@@ -99,15 +104,29 @@ impl Cmdlet for GroupObjectCmdlet {
         "Group-Object"
     }
 
+    fn parameters(&self) -> &[&str] {
+        &[
+            "Property",
+            "NoElement",
+            "AsHashTable",
+            "SortByCount",
+            "Parallel",
+        ]
+    }
+
     fn execute(
         &self,
         context: CmdletContext,
         _evaluator: &mut pwsh_runtime::Evaluator,
     ) -> Result<Vec<Value>, RuntimeError> {
-        let no_element = parse_switch(get_parameter_ci(&context, "NoElement"))?;
-        let as_hash_table = parse_switch(get_parameter_ci(&context, "AsHashTable"))?;
+        let no_element = parse_switch(context.get_parameter("NoElement"), "Group-Object")?;
+        let as_hash_table = parse_switch(context.get_parameter("AsHashTable"), "Group-Object")?;
+        let sort_by_count = parse_switch(context.get_parameter("SortByCount"), "Group-Object")?;
+        // -Parallel forces parallel grouping and -Parallel:$false turns it off;
+        // without it only large inputs are grouped in parallel, and only with more than one core
+        let parallel = parallel_switch(&context, "Group-Object")?;
 
-        let mut properties = parse_property_list(get_parameter_ci(&context, "Property"));
+        let mut properties = parse_property_list(context.get_parameter("Property"));
 
         // Input and positional-property behavior mirrors Sort-Object.
         let input = if !context.pipeline_input.is_empty() {
@@ -117,7 +136,7 @@ impl Cmdlet for GroupObjectCmdlet {
                     .arguments
                     .iter()
                     .filter_map(|v| match v {
-                        Value::String(s) => Some(s.clone()),
+                        Value::String(s) => Some(s.to_string()),
                         _ => None,
                     })
                     .collect::<Vec<_>>();
@@ -132,24 +151,30 @@ impl Cmdlet for GroupObjectCmdlet {
         };
 
         // Group deterministically using a BTreeMap (sorted by key).
-        let mut groups: BTreeMap<String, Vec<Value>> = BTreeMap::new();
-        for item in input {
-            let key = group_key_for_item(&item, &properties);
-            groups.entry(key).or_default().push(item);
-        }
+        let groups = if run_in_parallel(parallel, input.len()) {
+            partition_parallel(input, &properties)
+        } else {
+            partition(input, &properties)
+        };
 
         if as_hash_table {
             // Return a single hashtable-like object mapping group name -> GroupInfo.
-            let mut map = HashMap::new();
+            let mut map = PropertyMap::new();
             for (k, v) in groups {
-                map.insert(k.clone(), build_group_info(k, v, no_element));
+                map.insert(k.clone(), build_group_info(k, v, &properties, no_element));
             }
-            return Ok(vec![Value::Object(map)]);
+            return Ok(vec![Value::Object(map.into())]);
+        }
+
+        let mut groups: Vec<(String, Vec<Value>)> = groups.into_iter().collect();
+        if sort_by_count {
+            // A stable sort keeps groups of the same size in name order
+            groups.sort_by_key(|(_, items)| std::cmp::Reverse(items.len()));
         }
 
         let mut output = Vec::new();
         for (k, v) in groups {
-            output.push(build_group_info(k, v, no_element));
+            output.push(build_group_info(k, v, &properties, no_element));
         }
 
         Ok(output)
@@ -159,6 +184,7 @@ impl Cmdlet for GroupObjectCmdlet {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::parallel::PARALLEL_THRESHOLD;
 
     #[test]
     fn test_group_object_numbers() {
@@ -223,6 +249,7 @@ mod tests {
         let Value::Object(map) = &result[0] else {
             panic!("Expected object hashtable result");
         };
+        let map = map.read();
 
         assert!(map.contains_key("1"));
         assert!(map.contains_key("2"));
@@ -235,24 +262,31 @@ mod tests {
     fn test_group_object_by_property() {
         let cmdlet = GroupObjectCmdlet;
 
-        let a = Value::Object(HashMap::from([
-            ("Extension".to_string(), Value::String(".rs".to_string())),
-            ("Name".to_string(), Value::String("a".to_string())),
-        ]));
-        let b = Value::Object(HashMap::from([
-            ("Extension".to_string(), Value::String(".rs".to_string())),
-            ("Name".to_string(), Value::String("b".to_string())),
-        ]));
-        let c = Value::Object(HashMap::from([
-            ("Extension".to_string(), Value::String(".txt".to_string())),
-            ("Name".to_string(), Value::String("c".to_string())),
-        ]));
-
-        let context = CmdletContext::with_input(vec![a, b, c]).with_parameter(
-            "Property".to_string(),
-            Value::String("Extension".to_string()),
+        let a = Value::Object(
+            PropertyMap::from([
+                ("Extension".to_string(), Value::String(".rs".into())),
+                ("Name".to_string(), Value::String("a".into())),
+            ])
+            .into(),
+        );
+        let b = Value::Object(
+            PropertyMap::from([
+                ("Extension".to_string(), Value::String(".rs".into())),
+                ("Name".to_string(), Value::String("b".into())),
+            ])
+            .into(),
+        );
+        let c = Value::Object(
+            PropertyMap::from([
+                ("Extension".to_string(), Value::String(".txt".into())),
+                ("Name".to_string(), Value::String("c".into())),
+            ])
+            .into(),
         );
 
+        let context = CmdletContext::with_input(vec![a, b, c])
+            .with_parameter("Property".to_string(), Value::String("Extension".into()));
+
         let mut evaluator = pwsh_runtime::Evaluator::new();
         let result = cmdlet.execute(context, &mut evaluator).unwrap();
         assert_eq!(result.len(), 2);
@@ -277,25 +311,31 @@ mod tests {
     fn test_group_object_multiple_properties() {
         let cmdlet = GroupObjectCmdlet;
 
-        let x1 = Value::Object(HashMap::from([
-            ("A".to_string(), Value::String("one".to_string())),
-            ("B".to_string(), Value::String("two".to_string())),
-        ]));
-        let x2 = Value::Object(HashMap::from([
-            ("A".to_string(), Value::String("one".to_string())),
-            ("B".to_string(), Value::String("two".to_string())),
-        ]));
-        let y = Value::Object(HashMap::from([
-            ("A".to_string(), Value::String("one".to_string())),
-            ("B".to_string(), Value::String("three".to_string())),
-        ]));
+        let x1 = Value::Object(
+            PropertyMap::from([
+                ("A".to_string(), Value::String("one".into())),
+                ("B".to_string(), Value::String("two".into())),
+            ])
+            .into(),
+        );
+        let x2 = Value::Object(
+            PropertyMap::from([
+                ("A".to_string(), Value::String("one".into())),
+                ("B".to_string(), Value::String("two".into())),
+            ])
+            .into(),
+        );
+        let y = Value::Object(
+            PropertyMap::from([
+                ("A".to_string(), Value::String("one".into())),
+                ("B".to_string(), Value::String("three".into())),
+            ])
+            .into(),
+        );
 
         let context = CmdletContext::with_input(vec![x1, x2, y]).with_parameter(
             "Property".to_string(),
-            Value::Array(vec![
-                Value::String("A".to_string()),
-                Value::String("B".to_string()),
-            ]),
+            Value::Array(vec![Value::String("A".into()), Value::String("B".into())].into()),
         );
 
         let mut evaluator = pwsh_runtime::Evaluator::new();
@@ -309,5 +349,63 @@ mod tests {
             .map(|v| v.to_string())
             .collect();
         assert_eq!(names, vec!["one,three".to_string(), "one,two".to_string()]);
+
+        // Values keeps the keys apart
+        assert_eq!(
+            result[1].get_property("Values"),
+            Some(Value::Array(
+                vec![Value::String("one".into()), Value::String("two".into())].into()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_group_object_sort_by_count() {
+        let input = [3.0, 1.0, 2.0, 3.0, 2.0, 3.0, 4.0]
+            .into_iter()
+            .map(Value::Number)
+            .collect::<Vec<_>>();
+        let context = CmdletContext::with_input(input)
+            .with_parameter("SortByCount".to_string(), Value::Boolean(true));
+        let mut evaluator = pwsh_runtime::Evaluator::new();
+        let result = GroupObjectCmdlet.execute(context, &mut evaluator).unwrap();
+
+        // Largest first; groups of one stay in name order
+        let values: Vec<Value> = result
+            .iter()
+            .filter_map(|group| group.get_property("Values"))
+            .collect();
+        let number = |n: f64| Value::Array(vec![Value::Number(n)].into());
+        assert_eq!(
+            values,
+            vec![number(3.0), number(2.0), number(1.0), number(4.0)]
+        );
+    }
+
+    #[test]
+    fn test_group_object_parallel_matches_sequential() {
+        // Each group must list its items' ids in input order
+        let input: Vec<Value> = (0..PARALLEL_THRESHOLD + 500)
+            .map(|i| {
+                Value::Object(
+                    PropertyMap::from([
+                        ("Key", Value::Number(((i * 7919) % 13) as f64)),
+                        ("Id", Value::Number(i as f64)),
+                    ])
+                    .into(),
+                )
+            })
+            .collect();
+
+        let group = |parallel: bool| {
+            let context = CmdletContext::with_input(input.clone())
+                .with_parameter("Property".to_string(), Value::String("Key".into()))
+                .with_parameter("Parallel".to_string(), Value::Boolean(parallel));
+            let mut evaluator = pwsh_runtime::Evaluator::new();
+            GroupObjectCmdlet.execute(context, &mut evaluator).unwrap()
+        };
+        let parallel = group(true);
+        assert_eq!(parallel.len(), 13);
+        assert_eq!(parallel, group(false));
     }
 }
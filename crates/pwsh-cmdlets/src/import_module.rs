@@ -0,0 +1,50 @@
+/// Import-Module cmdlet - loads a cmdlet pack or a script module
+use pwsh_runtime::{Cmdlet, CmdletContext, ErrorRecord, RuntimeError, Value};
+
+/// Import-Module cmdlet makes the commands of one or more modules
+/// available: a cmdlet pack registered with the session, a script module
+/// on the module path, or a `.psm1` file given by its path
+pub struct ImportModuleCmdlet;
+
+impl Cmdlet for ImportModuleCmdlet {
+    fn name(&self) -> &str {
+        "Import-Module"
+    }
+
+    fn parameters(&self) -> &[&str] {
+        &["Name"]
+    }
+
+    fn execute(
+        &self,
+        context: CmdletContext,
+        evaluator: &mut pwsh_runtime::Evaluator,
+    ) -> Result<Vec<Value>, RuntimeError> {
+        // Name: -Name, the first positional argument, or each pipeline item
+        let names = if let Some(name) = context.get_parameter("Name") {
+            vec![name.clone()]
+        } else if let Some(name) = context.get_argument(0) {
+            vec![name.clone()]
+        } else {
+            context.pipeline_input.clone()
+        };
+        if names.is_empty() {
+            return Err(ErrorRecord::invalid_argument(
+                "NameRequired,Import-Module",
+                "Import-Module: a module name is required",
+            )
+            .into());
+        }
+
+        for name in names {
+            let names = match name {
+                Value::Array(items) => items.unwrap_or_clone(),
+                other => vec![other],
+            };
+            for name in names {
+                evaluator.import_module(&name.to_string())?;
+            }
+        }
+        Ok(vec![])
+    }
+}
@@ -0,0 +1,157 @@
+/// Invoke-History cmdlet - runs a line from the history again
+use pwsh_runtime::{
+    unroll_to_items, Cmdlet, CmdletContext, ErrorCategory, ErrorRecord, RuntimeError, Value,
+};
+
+/// Invoke-History runs a line from the session's history again, where it
+/// is called, showing it first: the last line, or with -Id (also by
+/// position) the line with that id, or else the last one starting with the
+/// given text. The line goes in the history in place of the one that ran
+/// it. `r` is its alias, so a function named `r` still comes first.
+pub struct InvokeHistoryCmdlet;
+
+impl Cmdlet for InvokeHistoryCmdlet {
+    fn name(&self) -> &str {
+        "Invoke-History"
+    }
+
+    fn parameters(&self) -> &[&str] {
+        &["Id"]
+    }
+
+    fn aliases(&self) -> &[&str] {
+        &["r"]
+    }
+
+    fn execute(
+        &self,
+        context: CmdletContext,
+        evaluator: &mut pwsh_runtime::Evaluator,
+    ) -> Result<Vec<Value>, RuntimeError> {
+        let which = context
+            .get_parameter("Id")
+            .or(context.get_argument(0))
+            .map(Value::to_string);
+        let line = evaluator
+            .history()
+            .recall(which.as_deref())?
+            .command_line
+            .clone();
+
+        if !evaluator.history_mut().begin_recall(&line) {
+            return Err(ErrorRecord::new(
+                ErrorCategory::InvalidOperation,
+                "InvokeHistoryLoop,Invoke-History",
+                "Invoke-History can't run a line while it is running another one",
+            )
+            .with_target(&line)
+            .into());
+        }
+        evaluator.host_mut().write_line(&line);
+        let result = evaluator.eval_line(&line);
+        evaluator.history_mut().end_recall();
+
+        Ok(match result? {
+            Value::Null => Vec::new(),
+            value => unroll_to_items(vec![value]),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{NaiveDate, NaiveDateTime};
+
+    fn time() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 3, 1)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap()
+    }
+
+    fn evaluator_with_history(lines: &[&str]) -> pwsh_runtime::Evaluator {
+        let mut evaluator = pwsh_runtime::Evaluator::new();
+        crate::register_all(evaluator.registry_mut());
+        for line in lines {
+            evaluator.history_mut().add(*line, true, time(), time());
+        }
+        evaluator
+    }
+
+    #[test]
+    fn test_invoke_history_runs_the_line_asked_for() {
+        let mut evaluator = evaluator_with_history(&["$x = 20", "$x + 1", "'last'"]);
+        let run = |evaluator: &mut pwsh_runtime::Evaluator, which: Option<Value>| {
+            let context = match which {
+                Some(which) => CmdletContext::new().with_arguments(vec![which]),
+                None => CmdletContext::new(),
+            };
+            InvokeHistoryCmdlet.execute(context, evaluator)
+        };
+
+        assert_eq!(
+            run(&mut evaluator, None).unwrap(),
+            vec![Value::String("last".into())]
+        );
+        // By id, and by the start of the line; the line runs where
+        // Invoke-History is, so it sees and sets the caller's variables
+        assert_eq!(run(&mut evaluator, Some(Value::Number(1.0))).unwrap(), []);
+        assert_eq!(
+            run(&mut evaluator, Some(Value::String("$X +".into()))).unwrap(),
+            vec![Value::Number(21.0)]
+        );
+        assert_eq!(
+            evaluator.history_mut().take_recalled().as_deref(),
+            Some("$x + 1")
+        );
+
+        let error = run(&mut evaluator, Some(Value::Number(9.0))).unwrap_err();
+        assert_eq!(error.error_id(), "InvokeHistoryNotFound,Invoke-History");
+    }
+
+    #[test]
+    fn test_invoke_history_does_not_run_itself() {
+        let mut evaluator = evaluator_with_history(&["Invoke-History"]);
+        let error = InvokeHistoryCmdlet
+            .execute(CmdletContext::new(), &mut evaluator)
+            .unwrap_err();
+        assert_eq!(error.error_id(), "InvokeHistoryLoop,Invoke-History");
+
+        // Once it has stopped, a line can be run again
+        evaluator.history_mut().add("'again'", true, time(), time());
+        assert_eq!(
+            InvokeHistoryCmdlet
+                .execute(CmdletContext::new(), &mut evaluator)
+                .unwrap(),
+            vec![Value::String("again".into())]
+        );
+    }
+
+    #[test]
+    fn test_r_runs_invoke_history_unless_a_function_is_named_r() {
+        let mut session = pwsh_runtime::SessionBuilder::new()
+            .cmdlets(crate::register_all)
+            .build();
+        session.invoke_interactive("$n = 1").unwrap();
+        session.invoke_interactive("$n = $n * 2; $n").unwrap();
+        assert_eq!(
+            session.invoke_interactive("r").unwrap(),
+            vec![Value::Number(4.0)]
+        );
+        // The line run again goes in the history, not `r`
+        let lines: Vec<String> = session
+            .evaluator()
+            .history()
+            .entries()
+            .map(|entry| entry.command_line.clone())
+            .collect();
+        assert_eq!(lines, ["$n = 1", "$n = $n * 2; $n", "$n = $n * 2; $n"]);
+
+        session.invoke_interactive("function r { 'mine' }").unwrap();
+        assert_eq!(
+            session.invoke_interactive("r").unwrap(),
+            vec![Value::String("mine".into())]
+        );
+    }
+}
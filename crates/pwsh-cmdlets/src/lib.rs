@@ -1,67 +1,232 @@
+mod convertfrom_json;
+mod convertto_json;
+#[cfg(feature = "filesystem")]
+mod drives;
 mod foreach_object;
+mod format_custom;
+mod format_list;
+mod format_table;
+#[cfg(feature = "filesystem")]
 mod get_childitem;
+#[cfg(feature = "filesystem")]
 mod get_content;
+mod get_date;
+mod get_error;
+mod get_execution_policy;
+mod get_help;
+mod get_history;
+#[cfg(feature = "process")]
 mod get_process;
+mod get_psbreakpoint;
+mod get_pscallstack;
+mod get_pwsh_configuration;
+mod get_random;
+mod get_variable;
 mod group_object;
+mod import_module;
+mod invoke_history;
+mod measure_object;
+#[cfg(feature = "filesystem")]
 mod new_item;
+mod new_object;
+mod out_default;
+mod out_paged;
+mod parallel;
+mod read_host;
+#[cfg(feature = "filesystem")]
 mod remove_item;
+mod remove_psbreakpoint;
 mod select_object;
+mod select_string;
+#[cfg(feature = "filesystem")]
 mod set_content;
+mod set_execution_policy;
+mod set_psbreakpoint;
+mod set_psdebug;
+mod set_strict_mode;
+mod set_variable;
 mod sort_object;
+#[cfg(feature = "filesystem")]
 mod test_path;
+mod update_format_data;
 mod where_object;
 /// PowerShell built-in cmdlets
+mod write_error;
+mod write_host;
 mod write_output;
+mod write_progress;
+mod write_warning;
 
 // Re-export cmdlets
+pub use convertfrom_json::ConvertFromJsonCmdlet;
+pub use convertto_json::ConvertToJsonCmdlet;
 pub use foreach_object::ForEachObjectCmdlet;
+pub use format_custom::FormatCustomCmdlet;
+pub use format_list::FormatListCmdlet;
+pub use format_table::FormatTableCmdlet;
+#[cfg(feature = "filesystem")]
 pub use get_childitem::GetChildItemCmdlet;
+#[cfg(feature = "filesystem")]
 pub use get_content::GetContentCmdlet;
+pub use get_date::GetDateCmdlet;
+pub use get_error::GetErrorCmdlet;
+pub use get_execution_policy::GetExecutionPolicyCmdlet;
+pub use get_help::GetHelpCmdlet;
+pub use get_history::GetHistoryCmdlet;
+#[cfg(feature = "process")]
 pub use get_process::GetProcessCmdlet;
+pub use get_psbreakpoint::GetPSBreakpointCmdlet;
+pub use get_pscallstack::GetPSCallStackCmdlet;
+pub use get_pwsh_configuration::GetPwshConfigurationCmdlet;
+pub use get_random::GetRandomCmdlet;
+pub use get_variable::GetVariableCmdlet;
 pub use group_object::GroupObjectCmdlet;
+pub use import_module::ImportModuleCmdlet;
+pub use invoke_history::InvokeHistoryCmdlet;
+pub use measure_object::MeasureObjectCmdlet;
+#[cfg(feature = "filesystem")]
 pub use new_item::NewItemCmdlet;
+pub use new_object::NewObjectCmdlet;
+pub use out_default::OutDefaultCmdlet;
+pub use out_paged::OutPagedCmdlet;
+pub use read_host::ReadHostCmdlet;
+#[cfg(feature = "filesystem")]
 pub use remove_item::RemoveItemCmdlet;
+pub use remove_psbreakpoint::RemovePSBreakpointCmdlet;
 pub use select_object::SelectObjectCmdlet;
+pub use select_string::SelectStringCmdlet;
+#[cfg(feature = "filesystem")]
 pub use set_content::SetContentCmdlet;
+pub use set_execution_policy::SetExecutionPolicyCmdlet;
+pub use set_psbreakpoint::SetPSBreakpointCmdlet;
+pub use set_psdebug::SetPSDebugCmdlet;
+pub use set_strict_mode::SetStrictModeCmdlet;
+pub use set_variable::SetVariableCmdlet;
 pub use sort_object::SortObjectCmdlet;
+#[cfg(feature = "filesystem")]
 pub use test_path::TestPathCmdlet;
+pub use update_format_data::UpdateFormatDataCmdlet;
 pub use where_object::WhereObjectCmdlet;
+pub use write_error::WriteErrorCmdlet;
+pub use write_host::WriteHostCmdlet;
 pub use write_output::WriteOutputCmdlet;
+pub use write_progress::WriteProgressCmdlet;
+pub use write_warning::WriteWarningCmdlet;
 
 /// Return the names of all built-in cmdlets registered by `register_all`.
 ///
 /// This is intended for UI/REPL features like autocomplete.
 pub fn cmdlet_names() -> Vec<String> {
-    vec![
-        "Write-Output".to_string(),
-        "Where-Object".to_string(),
-        "Select-Object".to_string(),
-        "ForEach-Object".to_string(),
-        "Get-Process".to_string(),
-        "Get-ChildItem".to_string(),
-        "Get-Content".to_string(),
-        "Set-Content".to_string(),
-        "Test-Path".to_string(),
-        "New-Item".to_string(),
-        "Remove-Item".to_string(),
-        "Sort-Object".to_string(),
-        "Group-Object".to_string(),
-    ]
+    let mut names = vec![
+        "Write-Output",
+        "Where-Object",
+        "Select-Object",
+        "ForEach-Object",
+    ];
+    #[cfg(feature = "process")]
+    names.push("Get-Process");
+    #[cfg(feature = "filesystem")]
+    names.extend([
+        "Get-ChildItem",
+        "Get-Content",
+        "Set-Content",
+        "Test-Path",
+        "New-Item",
+        "Remove-Item",
+    ]);
+    names.extend([
+        "Sort-Object",
+        "Group-Object",
+        "Measure-Object",
+        "New-Object",
+        "Write-Host",
+        "Write-Warning",
+        "Write-Progress",
+        "Read-Host",
+        "Import-Module",
+        "Get-Variable",
+        "Set-Variable",
+        "Get-Date",
+        "Get-Random",
+        "Format-List",
+        "Format-Table",
+        "Format-Custom",
+        "ConvertTo-Json",
+        "ConvertFrom-Json",
+        "Select-String",
+        "Write-Error",
+        "Get-Error",
+        "Get-PSCallStack",
+        "Set-PSBreakpoint",
+        "Get-PSBreakpoint",
+        "Remove-PSBreakpoint",
+        "Set-PSDebug",
+        "Get-History",
+        "Invoke-History",
+        "Get-Help",
+        "Get-ExecutionPolicy",
+        "Set-ExecutionPolicy",
+        "Get-PwshConfiguration",
+        "Set-StrictMode",
+        "Update-FormatData",
+        "Out-Default",
+        "Out-Paged",
+    ]);
+    names.into_iter().map(String::from).collect()
 }
 
 /// Register all built-in cmdlets
+/// File system and process cmdlets are left out when their features are off.
 pub fn register_all(registry: &mut pwsh_runtime::CmdletRegistry) {
     registry.register(Box::new(WriteOutputCmdlet));
     registry.register(Box::new(WhereObjectCmdlet));
     registry.register(Box::new(SelectObjectCmdlet));
     registry.register(Box::new(ForEachObjectCmdlet));
+    #[cfg(feature = "process")]
     registry.register(Box::new(GetProcessCmdlet));
-    registry.register(Box::new(GetChildItemCmdlet));
-    registry.register(Box::new(GetContentCmdlet));
-    registry.register(Box::new(SetContentCmdlet));
-    registry.register(Box::new(TestPathCmdlet));
-    registry.register(Box::new(NewItemCmdlet));
-    registry.register(Box::new(RemoveItemCmdlet));
+    #[cfg(feature = "filesystem")]
+    {
+        registry.register(Box::new(GetChildItemCmdlet));
+        registry.register(Box::new(GetContentCmdlet));
+        registry.register(Box::new(SetContentCmdlet));
+        registry.register(Box::new(TestPathCmdlet));
+        registry.register(Box::new(NewItemCmdlet));
+        registry.register(Box::new(RemoveItemCmdlet));
+    }
     registry.register(Box::new(SortObjectCmdlet));
     registry.register(Box::new(GroupObjectCmdlet));
+    registry.register(Box::new(MeasureObjectCmdlet));
+    registry.register(Box::new(NewObjectCmdlet));
+    registry.register(Box::new(WriteHostCmdlet));
+    registry.register(Box::new(WriteWarningCmdlet));
+    registry.register(Box::new(WriteProgressCmdlet));
+    registry.register(Box::new(ReadHostCmdlet));
+    registry.register(Box::new(ImportModuleCmdlet));
+    registry.register(Box::new(GetVariableCmdlet));
+    registry.register(Box::new(SetVariableCmdlet));
+    registry.register(Box::new(GetDateCmdlet));
+    registry.register(Box::new(GetRandomCmdlet));
+    registry.register(Box::new(FormatListCmdlet));
+    registry.register(Box::new(FormatTableCmdlet));
+    registry.register(Box::new(FormatCustomCmdlet));
+    registry.register(Box::new(ConvertToJsonCmdlet));
+    registry.register(Box::new(ConvertFromJsonCmdlet));
+    registry.register(Box::new(SelectStringCmdlet));
+    registry.register(Box::new(WriteErrorCmdlet));
+    registry.register(Box::new(GetErrorCmdlet));
+    registry.register(Box::new(GetPSCallStackCmdlet));
+    registry.register(Box::new(SetPSBreakpointCmdlet));
+    registry.register(Box::new(GetPSBreakpointCmdlet));
+    registry.register(Box::new(RemovePSBreakpointCmdlet));
+    registry.register(Box::new(SetPSDebugCmdlet));
+    registry.register(Box::new(GetHistoryCmdlet));
+    registry.register(Box::new(InvokeHistoryCmdlet));
+    registry.register(Box::new(GetHelpCmdlet));
+    registry.register(Box::new(GetExecutionPolicyCmdlet));
+    registry.register(Box::new(SetExecutionPolicyCmdlet));
+    registry.register(Box::new(GetPwshConfigurationCmdlet));
+    registry.register(Box::new(SetStrictModeCmdlet));
+    registry.register(Box::new(UpdateFormatDataCmdlet));
+    registry.register(Box::new(OutDefaultCmdlet));
+    registry.register(Box::new(OutPagedCmdlet));
 }
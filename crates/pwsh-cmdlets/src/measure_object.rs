@@ -0,0 +1,406 @@
+/// Measure-Object cmdlet - count pipeline objects and sum, average or find the extremes of a property
+use pwsh_runtime::{
+    parse_property_list, parse_switch, Cmdlet, CmdletContext, ErrorCategory, ErrorRecord,
+    PropertyMap, RuntimeError, Value, WildcardPattern,
+};
+
+/// A statistic switch such as -Sum. The parser hands a switch the word after
+/// it, so in `Measure-Object -Sum Length` the "value" of -Sum is really the
+/// property to measure, which PowerShell would bind by position.
+fn parse_statistic(
+    value: Option<&Value>,
+    properties: &mut Vec<String>,
+) -> Result<bool, RuntimeError> {
+    match value {
+        Some(Value::String(_)) | Some(Value::Array(_))
+            if parse_switch(value, "Measure-Object").is_err() =>
+        {
+            properties.extend(parse_property_list(value));
+            Ok(true)
+        }
+        _ => parse_switch(value, "Measure-Object"),
+    }
+}
+
+/// Which statistics to compute besides the count
+#[derive(Default)]
+struct Statistics {
+    sum: bool,
+    average: bool,
+    maximum: bool,
+    minimum: bool,
+    standard_deviation: bool,
+}
+
+impl Statistics {
+    /// Every statistic, as -AllStats asks for
+    fn all() -> Self {
+        Statistics {
+            sum: true,
+            average: true,
+            maximum: true,
+            minimum: true,
+            standard_deviation: true,
+        }
+    }
+
+    fn any(&self) -> bool {
+        self.sum || self.average || self.maximum || self.minimum || self.standard_deviation
+    }
+}
+
+/// The names of the properties to measure. A name with wildcards stands for
+/// every property of the input it matches, in the order they first appear;
+/// one that matches nothing is an error, as a missing property is.
+fn expand_properties(input: &[Value], names: &[String]) -> Result<Vec<String>, RuntimeError> {
+    let mut properties: Vec<String> = Vec::new();
+    let mut add = |name: &str| {
+        if !properties.iter().any(|p| p.eq_ignore_ascii_case(name)) {
+            properties.push(name.to_string());
+        }
+    };
+    for name in names {
+        let pattern = WildcardPattern::new(name)?;
+        if pattern.is_literal() {
+            add(name);
+            continue;
+        }
+        let matches: Vec<String> = input
+            .iter()
+            .filter_map(Value::properties)
+            .flat_map(|props| {
+                props
+                    .keys()
+                    .filter(|key| pattern.is_match(key))
+                    .map(|key| key.to_string())
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        if matches.is_empty() && !input.is_empty() {
+            return Err(property_not_found(name));
+        }
+        matches.iter().for_each(|name| add(name));
+    }
+    Ok(properties)
+}
+
+fn property_not_found(name: &str) -> RuntimeError {
+    ErrorRecord::invalid_argument(
+        "GenericMeasurePropertyNotFound,Measure-Object",
+        format!(
+            "The property \"{}\" cannot be found in the input for any objects.",
+            name
+        ),
+    )
+    .into()
+}
+
+/// Measure the input items, or one property of them. Items without the
+/// property are left out, and every measured value must be numeric when a
+/// statistic is asked for.
+fn measure(
+    input: &[Value],
+    property: Option<&str>,
+    statistics: &Statistics,
+) -> Result<Value, RuntimeError> {
+    let mut count = 0usize;
+    let mut sum = 0.0;
+    let mut maximum: Option<f64> = None;
+    let mut minimum: Option<f64> = None;
+    // Running mean and sum of squared differences from it (Welford's method)
+    let mut mean = 0.0;
+    let mut squares = 0.0;
+
+    for item in input {
+        let value = match property {
+            Some(name) => match item.get_property(name) {
+                Some(value) => value,
+                None => continue,
+            },
+            None if *item == Value::Null => continue,
+            None => item.clone(),
+        };
+        count += 1;
+
+        if statistics.any() {
+            let n = value.to_number().ok_or_else(|| -> RuntimeError {
+                ErrorRecord::new(
+                    ErrorCategory::InvalidType,
+                    "NonNumericInputObject,Measure-Object",
+                    format!("Input object \"{}\" is not numeric.", value),
+                )
+                .with_target(&value)
+                .into()
+            })?;
+            sum += n;
+            maximum = Some(maximum.map_or(n, |m| m.max(n)));
+            minimum = Some(minimum.map_or(n, |m| m.min(n)));
+            let delta = n - mean;
+            mean += delta / count as f64;
+            squares += delta * (n - mean);
+        }
+    }
+
+    if let Some(name) = property {
+        if count == 0 && !input.is_empty() {
+            return Err(property_not_found(name));
+        }
+    }
+
+    let number_or_null = |n: Option<f64>| n.map(Value::Number).unwrap_or(Value::Null);
+    let average = (count > 0).then(|| sum / count as f64);
+    // The sample standard deviation; a single value doesn't vary
+    let standard_deviation = match count {
+        0 => None,
+        1 => Some(0.0),
+        _ => Some((squares / (count - 1) as f64).sqrt()),
+    };
+
+    let mut props = PropertyMap::with_capacity(7);
+    props.insert("Count".to_string(), Value::Number(count as f64));
+    props.insert(
+        "Average".to_string(),
+        number_or_null(average.filter(|_| statistics.average)),
+    );
+    props.insert(
+        "Sum".to_string(),
+        number_or_null(Some(sum).filter(|_| statistics.sum)),
+    );
+    props.insert(
+        "Maximum".to_string(),
+        number_or_null(maximum.filter(|_| statistics.maximum)),
+    );
+    props.insert(
+        "Minimum".to_string(),
+        number_or_null(minimum.filter(|_| statistics.minimum)),
+    );
+    props.insert(
+        "StandardDeviation".to_string(),
+        number_or_null(standard_deviation.filter(|_| statistics.standard_deviation)),
+    );
+    props.insert(
+        "Property".to_string(),
+        property
+            .map(|p| Value::String(p.into()))
+            .unwrap_or(Value::Null),
+    );
+    props.insert_type_name("Microsoft.PowerShell.Commands.GenericMeasureInfo");
+    Ok(Value::Object(props.into()))
+}
+
+/// Measure-Object counts its input and computes -Sum, -Average, -Maximum,
+/// -Minimum and -StandardDeviation (or all of them with -AllStats), over the
+/// input itself or over each named property, writing one result per property.
+pub struct MeasureObjectCmdlet;
+
+impl Cmdlet for MeasureObjectCmdlet {
+    fn name(&self) -> &str {
+        "Measure-Object"
+    }
+
+    fn parameters(&self) -> &[&str] {
+        &[
+            "Property",
+            "Sum",
+            "Average",
+            "Maximum",
+            "Minimum",
+            "StandardDeviation",
+            "AllStats",
+        ]
+    }
+
+    fn execute(
+        &self,
+        context: CmdletContext,
+        _evaluator: &mut pwsh_runtime::Evaluator,
+    ) -> Result<Vec<Value>, RuntimeError> {
+        let mut properties = parse_property_list(context.get_parameter("Property"));
+        // Support: $items | Measure-Object Length -Sum
+        properties.extend(parse_property_list(
+            context.get_argument(0).filter(|_| properties.is_empty()),
+        ));
+
+        let mut statistics = Statistics {
+            sum: parse_statistic(context.get_parameter("Sum"), &mut properties)?,
+            average: parse_statistic(context.get_parameter("Average"), &mut properties)?,
+            maximum: parse_statistic(context.get_parameter("Maximum"), &mut properties)?,
+            minimum: parse_statistic(context.get_parameter("Minimum"), &mut properties)?,
+            standard_deviation: parse_statistic(
+                context.get_parameter("StandardDeviation"),
+                &mut properties,
+            )?,
+        };
+        if parse_statistic(context.get_parameter("AllStats"), &mut properties)? {
+            statistics = Statistics::all();
+        }
+        let properties = expand_properties(&context.pipeline_input, &properties)?;
+
+        if properties.is_empty() {
+            return Ok(vec![measure(&context.pipeline_input, None, &statistics)?]);
+        }
+        properties
+            .iter()
+            .map(|property| measure(&context.pipeline_input, Some(property), &statistics))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn file(name: &str, length: f64) -> Value {
+        let mut props = PropertyMap::new();
+        props.insert("Name".to_string(), Value::String(name.into()));
+        props.insert("Length".to_string(), Value::Number(length));
+        Value::Object(props.into())
+    }
+
+    fn run(context: CmdletContext) -> Result<Vec<Value>, RuntimeError> {
+        let mut evaluator = pwsh_runtime::Evaluator::new();
+        MeasureObjectCmdlet.execute(context, &mut evaluator)
+    }
+
+    #[test]
+    fn test_measure_object_counts_input() {
+        let input = vec![Value::Number(1.0), Value::Null, Value::String("x".into())];
+        let result = run(CmdletContext::with_input(input)).unwrap();
+
+        assert_eq!(result[0].get_property("Count"), Some(Value::Number(2.0)));
+        assert_eq!(result[0].get_property("Sum"), Some(Value::Null));
+        assert_eq!(result[0].get_property("Property"), Some(Value::Null));
+    }
+
+    #[test]
+    fn test_measure_object_property_statistics() {
+        let input = vec![file("a", 10.0), file("b", 30.0), file("c", 20.0)];
+        let context = CmdletContext::with_input(input)
+            .with_parameter("Property".to_string(), Value::String("length".into()))
+            .with_parameter("Sum".to_string(), Value::Boolean(true))
+            .with_parameter("Average".to_string(), Value::Boolean(true))
+            .with_parameter("Maximum".to_string(), Value::Boolean(true))
+            .with_parameter("Minimum".to_string(), Value::Boolean(true));
+        let result = run(context).unwrap();
+
+        let measured = &result[0];
+        assert_eq!(measured.get_property("Count"), Some(Value::Number(3.0)));
+        assert_eq!(measured.get_property("Sum"), Some(Value::Number(60.0)));
+        assert_eq!(measured.get_property("Average"), Some(Value::Number(20.0)));
+        assert_eq!(measured.get_property("Maximum"), Some(Value::Number(30.0)));
+        assert_eq!(measured.get_property("Minimum"), Some(Value::Number(10.0)));
+        assert_eq!(
+            measured.get_property("Property"),
+            Some(Value::String("length".into()))
+        );
+    }
+
+    #[test]
+    fn test_measure_object_all_stats() {
+        let input = [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0]
+            .into_iter()
+            .map(Value::Number)
+            .collect();
+        let context = CmdletContext::with_input(input)
+            .with_parameter("AllStats".to_string(), Value::Boolean(true));
+        let measured = &run(context).unwrap()[0];
+
+        assert_eq!(measured.get_property("Sum"), Some(Value::Number(40.0)));
+        assert_eq!(measured.get_property("Average"), Some(Value::Number(5.0)));
+        assert_eq!(measured.get_property("Maximum"), Some(Value::Number(9.0)));
+        assert_eq!(measured.get_property("Minimum"), Some(Value::Number(2.0)));
+        let Some(Value::Number(deviation)) = measured.get_property("StandardDeviation") else {
+            panic!("expected a standard deviation");
+        };
+        assert!((deviation - (32.0f64 / 7.0).sqrt()).abs() < 1e-12);
+
+        // Only when asked for
+        let context = CmdletContext::with_input(vec![Value::Number(1.0)])
+            .with_parameter("Sum".to_string(), Value::Boolean(true));
+        assert_eq!(
+            run(context).unwrap()[0].get_property("StandardDeviation"),
+            Some(Value::Null)
+        );
+    }
+
+    #[test]
+    fn test_measure_object_wildcard_properties() {
+        let item = |cpu: f64, handles: f64| {
+            let mut props = PropertyMap::new();
+            props.insert("Name".to_string(), Value::String("p".into()));
+            props.insert("CPU".to_string(), Value::Number(cpu));
+            props.insert("Handles".to_string(), Value::Number(handles));
+            props.insert("HandleCount".to_string(), Value::Number(handles * 2.0));
+            Value::Object(props.into())
+        };
+        let input = vec![item(1.0, 10.0), item(3.0, 20.0)];
+        // `-Sum Handle*` arrives as Sum = "Handle*"
+        let context = CmdletContext::with_input(input.clone())
+            .with_parameter("Sum".to_string(), Value::String("Handle*".into()));
+        let result = run(context).unwrap();
+
+        let measured: Vec<(Value, Value)> = result
+            .iter()
+            .map(|m| {
+                (
+                    m.get_property("Property").unwrap(),
+                    m.get_property("Sum").unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            measured,
+            vec![
+                (Value::String("Handles".into()), Value::Number(30.0)),
+                (Value::String("HandleCount".into()), Value::Number(60.0)),
+            ]
+        );
+
+        let context = CmdletContext::with_input(input)
+            .with_parameter("Property".to_string(), Value::String("Memory*".into()));
+        let error = run(context).unwrap_err();
+        assert_eq!(
+            error.error_id(),
+            "GenericMeasurePropertyNotFound,Measure-Object"
+        );
+    }
+
+    #[test]
+    fn test_measure_object_switch_followed_by_property() {
+        // `-Sum Length` arrives as Sum = "Length"
+        let input = vec![file("a", 1.0), file("b", 2.0)];
+        let context = CmdletContext::with_input(input)
+            .with_parameter("Sum".to_string(), Value::String("Length".into()));
+        let result = run(context).unwrap();
+
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].get_property("Sum"), Some(Value::Number(3.0)));
+        assert_eq!(result[0].get_property("Average"), Some(Value::Null));
+    }
+
+    #[test]
+    fn test_measure_object_empty_input() {
+        let context = CmdletContext::new().with_parameter("Sum".to_string(), Value::Boolean(true));
+        let result = run(context).unwrap();
+
+        assert_eq!(result[0].get_property("Count"), Some(Value::Number(0.0)));
+        assert_eq!(result[0].get_property("Sum"), Some(Value::Number(0.0)));
+    }
+
+    #[test]
+    fn test_measure_object_errors() {
+        let context = CmdletContext::with_input(vec![Value::String("abc".into())])
+            .with_parameter("Sum".to_string(), Value::Boolean(true));
+        let error = run(context).unwrap_err();
+        assert_eq!(error.error_id(), "NonNumericInputObject,Measure-Object");
+        assert_eq!(error.category(), ErrorCategory::InvalidType);
+
+        let context = CmdletContext::with_input(vec![file("a", 1.0)])
+            .with_parameter("Property".to_string(), Value::String("Size".into()));
+        let error = run(context).unwrap_err();
+        assert_eq!(
+            error.error_id(),
+            "GenericMeasurePropertyNotFound,Measure-Object"
+        );
+    }
+}
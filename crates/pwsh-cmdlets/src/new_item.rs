@@ -1,21 +1,19 @@
 /// New-Item cmdlet - creates a file or directory
-use pwsh_runtime::{Cmdlet, CmdletContext, RuntimeError, Value};
-use std::collections::HashMap;
+use pwsh_runtime::{
+    Capability, Cmdlet, CmdletContext, ErrorCategory, ErrorRecord, PropertyMap, RuntimeError, Value,
+};
 use std::fs;
 use std::path::{Path, PathBuf};
 
-fn get_parameter_ci<'a>(context: &'a CmdletContext, name: &str) -> Option<&'a Value> {
-    // Try exact match first
-    if let Some(v) = context.parameters.get(name) {
-        return Some(v);
-    }
-
-    let name_lower = name.to_lowercase();
-    context
-        .parameters
-        .iter()
-        .find(|(k, _)| k.to_lowercase() == name_lower)
-        .map(|(_, v)| v)
+/// The item to create is already there and -Force wasn't given
+fn already_exists(path: &Path) -> RuntimeError {
+    ErrorRecord::new(
+        ErrorCategory::ResourceExists,
+        "ItemExists,New-Item",
+        format!("Path already exists: {}", path.display()),
+    )
+    .with_target(path.display())
+    .into()
 }
 
 fn resolve_path(path: &str) -> Result<PathBuf, RuntimeError> {
@@ -25,33 +23,42 @@ fn resolve_path(path: &str) -> Result<PathBuf, RuntimeError> {
     }
 
     let cwd = std::env::current_dir().map_err(|e| {
-        RuntimeError::InvalidOperation(format!("Failed to get current directory: {}", e))
+        ErrorRecord::file_system(
+            &e,
+            "New-Item",
+            ".",
+            format!("Failed to get current directory: {}", e),
+        )
     })?;
     Ok(cwd.join(p))
 }
 
 fn extract_path(context: &CmdletContext) -> Result<PathBuf, RuntimeError> {
-    if let Some(Value::String(p)) = get_parameter_ci(context, "Path") {
+    if let Some(Value::String(p)) = context.get_parameter("Path") {
         resolve_path(p)
     } else if let Some(Value::String(p)) = context.get_argument(0) {
         resolve_path(p)
     } else {
-        Err(RuntimeError::InvalidOperation(
-            "New-Item requires a path".to_string(),
-        ))
+        Err(
+            ErrorRecord::invalid_argument("PathRequired,New-Item", "New-Item requires a path")
+                .into(),
+        )
     }
 }
 
 fn parse_item_type(context: &CmdletContext) -> Result<String, RuntimeError> {
     // Support both -Type (from our plan) and the standard PowerShell -ItemType.
-    let v = get_parameter_ci(context, "Type")
-        .or_else(|| get_parameter_ci(context, "ItemType"))
+    let v = context
+        .get_parameter("Type")
+        .or_else(|| context.get_parameter("ItemType"))
         .or_else(|| context.get_argument(1));
 
     let Some(v) = v else {
-        return Err(RuntimeError::InvalidOperation(
-            "New-Item requires -Type (File or Directory)".to_string(),
-        ));
+        return Err(ErrorRecord::invalid_argument(
+            "TypeRequired,New-Item",
+            "New-Item requires -Type (File or Directory)",
+        )
+        .into());
     };
 
     let s = match v {
@@ -63,28 +70,33 @@ fn parse_item_type(context: &CmdletContext) -> Result<String, RuntimeError> {
 }
 
 fn parse_force(context: &CmdletContext) -> bool {
-    get_parameter_ci(context, "Force")
+    context
+        .get_parameter("Force")
         .map(|v| v.to_bool())
         .unwrap_or(false)
 }
 
 fn build_item_object(path: &Path, item_type: &str, is_dir: bool) -> Value {
-    let mut props = HashMap::new();
+    let mut props = PropertyMap::new();
     props.insert(
         "FullName".to_string(),
-        Value::String(path.to_string_lossy().to_string()),
+        Value::String(path.to_string_lossy().into()),
     );
     props.insert(
         "Name".to_string(),
         Value::String(
             path.file_name()
                 .map(|n| n.to_string_lossy().to_string())
-                .unwrap_or_else(|| path.to_string_lossy().to_string()),
+                .unwrap_or_else(|| path.to_string_lossy().to_string())
+                .into(),
         ),
     );
-    props.insert("ItemType".to_string(), Value::String(item_type.to_string()));
+    props.insert(
+        "ItemType".to_string(),
+        Value::String(item_type.to_string().into()),
+    );
     props.insert("Directory".to_string(), Value::Boolean(is_dir));
-    Value::Object(props)
+    Value::Object(props.into())
 }
 
 /// New-Item cmdlet creates a file or directory
@@ -95,6 +107,14 @@ impl Cmdlet for NewItemCmdlet {
         "New-Item"
     }
 
+    fn parameters(&self) -> &[&str] {
+        &["Path", "ItemType", "Force"]
+    }
+
+    fn capabilities(&self) -> &[Capability] {
+        &[Capability::FileSystem]
+    }
+
     fn execute(
         &self,
         context: CmdletContext,
@@ -109,18 +129,16 @@ impl Cmdlet for NewItemCmdlet {
             "directory" | "dir" | "folder" => {
                 if path.exists() {
                     if !force {
-                        return Err(RuntimeError::InvalidOperation(format!(
-                            "Path already exists: {}",
-                            path.display()
-                        )));
+                        return Err(already_exists(&path));
                     }
                 } else {
                     fs::create_dir_all(&path).map_err(|e| {
-                        RuntimeError::InvalidOperation(format!(
-                            "Failed to create directory '{}': {}",
+                        ErrorRecord::file_system(
+                            &e,
+                            "New-Item",
                             path.display(),
-                            e
-                        ))
+                            format!("Failed to create directory '{}': {}", path.display(), e),
+                        )
                     })?;
                 }
 
@@ -129,34 +147,37 @@ impl Cmdlet for NewItemCmdlet {
             "file" => {
                 if let Some(parent) = path.parent() {
                     if !parent.as_os_str().is_empty() && !parent.exists() {
-                        return Err(RuntimeError::InvalidOperation(format!(
-                            "Parent directory does not exist: {}",
-                            parent.display()
-                        )));
+                        return Err(RuntimeError::from(
+                            ErrorRecord::new(
+                                ErrorCategory::ObjectNotFound,
+                                "ParentNotFound,New-Item",
+                                format!("Parent directory does not exist: {}", parent.display()),
+                            )
+                            .with_target(parent.display()),
+                        ));
                     }
                 }
 
                 if path.exists() && !force {
-                    return Err(RuntimeError::InvalidOperation(format!(
-                        "Path already exists: {}",
-                        path.display()
-                    )));
+                    return Err(already_exists(&path));
                 }
 
                 fs::File::create(&path).map_err(|e| {
-                    RuntimeError::InvalidOperation(format!(
-                        "Failed to create file '{}': {}",
+                    ErrorRecord::file_system(
+                        &e,
+                        "New-Item",
                         path.display(),
-                        e
-                    ))
+                        format!("Failed to create file '{}': {}", path.display(), e),
+                    )
                 })?;
 
                 Ok(vec![build_item_object(&path, "File", false)])
             }
-            other => Err(RuntimeError::InvalidOperation(format!(
-                "Unsupported -Type for New-Item: {}",
-                other
-            ))),
+            other => Err(ErrorRecord::invalid_argument(
+                "UnsupportedType,New-Item",
+                format!("Unsupported -Type for New-Item: {}", other),
+            )
+            .into()),
         }
     }
 }
@@ -175,9 +196,9 @@ mod tests {
         let context = CmdletContext::new()
             .with_parameter(
                 "Path".to_string(),
-                Value::String(dir_path.to_string_lossy().to_string()),
+                Value::String(dir_path.to_string_lossy().into()),
             )
-            .with_parameter("Type".to_string(), Value::String("Directory".to_string()));
+            .with_parameter("Type".to_string(), Value::String("Directory".into()));
         let mut evaluator = pwsh_runtime::Evaluator::new();
         let _ = cmdlet.execute(context, &mut evaluator).unwrap();
 
@@ -192,8 +213,8 @@ mod tests {
 
         let cmdlet = NewItemCmdlet;
         let context = CmdletContext::new().with_arguments(vec![
-            Value::String(file_path.to_string_lossy().to_string()),
-            Value::String("File".to_string()),
+            Value::String(file_path.to_string_lossy().into()),
+            Value::String("File".into()),
         ]);
         let mut evaluator = pwsh_runtime::Evaluator::new();
         let _ = cmdlet.execute(context, &mut evaluator).unwrap();
@@ -0,0 +1,76 @@
+/// New-Object cmdlet - creates an instance of a class or a custom object
+use pwsh_runtime::{Cmdlet, CmdletContext, ErrorRecord, PropertyMap, RuntimeError, Value};
+
+/// New-Object cmdlet creates objects
+/// - New-Object Person "Ann"                      -> [Person]::new("Ann")
+/// - New-Object -TypeName Person -ArgumentList a, b
+/// - New-Object PSObject -Property @{Name = "Ann"}
+pub struct NewObjectCmdlet;
+
+impl Cmdlet for NewObjectCmdlet {
+    fn name(&self) -> &str {
+        "New-Object"
+    }
+
+    fn parameters(&self) -> &[&str] {
+        &["TypeName", "ArgumentList", "Property"]
+    }
+
+    fn execute(
+        &self,
+        context: CmdletContext,
+        evaluator: &mut pwsh_runtime::Evaluator,
+    ) -> Result<Vec<Value>, RuntimeError> {
+        // The type comes from -TypeName or the first positional argument;
+        // constructor arguments from -ArgumentList or the remaining positionals
+        let mut positional = context.arguments.iter().cloned();
+        let type_name = match context.get_parameter("TypeName") {
+            Some(v) => v.to_string(),
+            None => positional.next().map(|v| v.to_string()).ok_or_else(|| {
+                ErrorRecord::invalid_argument(
+                    "TypeNameRequired,New-Object",
+                    "New-Object requires a type name",
+                )
+            })?,
+        };
+
+        let args: Vec<Value> = match context.get_parameter("ArgumentList") {
+            Some(Value::Array(items)) => items.read().to_vec(),
+            Some(value) => vec![value.clone()],
+            None => positional
+                .flat_map(|v| match v {
+                    Value::Array(items) => items.unwrap_or_clone(),
+                    other => vec![other],
+                })
+                .collect(),
+        };
+
+        let object = match type_name.to_lowercase().as_str() {
+            "psobject" | "pscustomobject" | "system.management.automation.psobject" => {
+                Value::Object(PropertyMap::new().into())
+            }
+            _ => evaluator.construct_instance(&type_name, args)?,
+        };
+
+        // -Property sets properties after construction
+        match context.get_parameter("Property") {
+            Some(Value::Object(props)) => {
+                for (key, value) in props.read().iter() {
+                    object
+                        .set_property(key, value.clone())
+                        .map_err(RuntimeError::InvalidOperation)?;
+                }
+            }
+            Some(other) => {
+                return Err(ErrorRecord::invalid_argument(
+                    "InvalidProperty,New-Object",
+                    format!("New-Object -Property must be a hashtable, got: {}", other),
+                )
+                .into())
+            }
+            None => {}
+        }
+
+        Ok(vec![object])
+    }
+}
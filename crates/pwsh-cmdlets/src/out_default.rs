@@ -0,0 +1,31 @@
+/// Out-Default cmdlet - shows objects on the host as the console would
+use pwsh_runtime::{Cmdlet, CmdletContext, RuntimeError, Value};
+
+/// Out-Default formats its input as the console shows a pipeline's output
+/// (in the views for the objects' types) and hands the lines to the host
+/// instead of the pipeline. ANSI styling is kept or taken out as
+/// $OutputRendering says.
+pub struct OutDefaultCmdlet;
+
+impl Cmdlet for OutDefaultCmdlet {
+    fn name(&self) -> &str {
+        "Out-Default"
+    }
+
+    fn parameters(&self) -> &[&str] {
+        &["InputObject"]
+    }
+
+    fn execute(
+        &self,
+        context: CmdletContext,
+        evaluator: &mut pwsh_runtime::Evaluator,
+    ) -> Result<Vec<Value>, RuntimeError> {
+        let items = match context.get_parameter("InputObject") {
+            Some(input) => vec![input.clone()],
+            None => context.pipeline_input,
+        };
+        evaluator.out_default(&items)?;
+        Ok(vec![])
+    }
+}
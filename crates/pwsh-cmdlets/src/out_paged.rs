@@ -0,0 +1,32 @@
+/// Out-Paged cmdlet - shows objects on the host a screen at a time
+use pwsh_runtime::{Cmdlet, CmdletContext, RuntimeError, Value};
+
+/// Out-Paged shows its input as Out-Default does, but output taller than
+/// the host's window is shown a screen at a time, as `more` shows it:
+/// Space shows the next screen, Enter the next line and Q stops. On a host
+/// without a window, such as when output is redirected, everything is
+/// shown at once.
+pub struct OutPagedCmdlet;
+
+impl Cmdlet for OutPagedCmdlet {
+    fn name(&self) -> &str {
+        "Out-Paged"
+    }
+
+    fn parameters(&self) -> &[&str] {
+        &["InputObject"]
+    }
+
+    fn execute(
+        &self,
+        context: CmdletContext,
+        evaluator: &mut pwsh_runtime::Evaluator,
+    ) -> Result<Vec<Value>, RuntimeError> {
+        let items = match context.get_parameter("InputObject") {
+            Some(input) => vec![input.clone()],
+            None => context.pipeline_input,
+        };
+        evaluator.out_paged(&items)?;
+        Ok(vec![])
+    }
+}
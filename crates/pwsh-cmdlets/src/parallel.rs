@@ -0,0 +1,37 @@
+/// When Sort-Object and Group-Object spread their work over all cores
+use pwsh_runtime::{CmdletContext, ErrorRecord, RuntimeError, Value};
+
+/// Inputs at least this long are sorted or grouped on all cores; below it
+/// starting the threads costs more than it saves (measured with
+/// `cargo bench -p pwsh-cmdlets`)
+pub(crate) const PARALLEL_THRESHOLD: usize = 20_000;
+
+/// Whether there is more than one core to spread the work over
+fn has_multiple_cores() -> bool {
+    std::thread::available_parallelism().is_ok_and(|cores| cores.get() > 1)
+}
+
+/// What -Parallel asks for, if it is given. A value given with
+/// `-Parallel:` must be `$true`, `$false` or `$null` (off), as for a
+/// PowerShell switch; a bare word such as `false` is an error.
+pub(crate) fn parallel_switch(
+    context: &CmdletContext,
+    command: &str,
+) -> Result<Option<bool>, RuntimeError> {
+    match context.get_parameter("Parallel") {
+        None => Ok(None),
+        Some(Value::Null) => Ok(Some(false)),
+        Some(Value::Boolean(on)) => Ok(Some(*on)),
+        Some(other) => Err(ErrorRecord::invalid_argument(
+            &format!("InvalidBoolean,{}", command),
+            format!("-Parallel takes $true or $false, not {}", other),
+        )
+        .into()),
+    }
+}
+
+/// Whether to work on `len` inputs in parallel: as -Parallel says when it
+/// is given, or else only for large inputs with more than one core
+pub(crate) fn run_in_parallel(switch: Option<bool>, len: usize) -> bool {
+    switch.unwrap_or_else(|| len >= PARALLEL_THRESHOLD && has_multiple_cores())
+}
@@ -0,0 +1,38 @@
+/// Read-Host cmdlet - reads a line of input from the host
+use pwsh_runtime::{Cmdlet, CmdletContext, ErrorCategory, ErrorRecord, RuntimeError, Value};
+
+/// Read-Host cmdlet prompts for a line of input and outputs it as a string
+pub struct ReadHostCmdlet;
+
+impl Cmdlet for ReadHostCmdlet {
+    fn name(&self) -> &str {
+        "Read-Host"
+    }
+
+    fn parameters(&self) -> &[&str] {
+        &["Prompt"]
+    }
+
+    fn execute(
+        &self,
+        context: CmdletContext,
+        evaluator: &mut pwsh_runtime::Evaluator,
+    ) -> Result<Vec<Value>, RuntimeError> {
+        // Prompt: -Prompt or the first positional argument, shown as "Prompt: "
+        let prompt = context
+            .get_parameter("Prompt")
+            .or_else(|| context.get_argument(0))
+            .map(|v| format!("{}: ", v))
+            .unwrap_or_default();
+
+        match evaluator.host_mut().read_line(&prompt) {
+            Some(line) => Ok(vec![Value::String(line.into())]),
+            None => Err(ErrorRecord::new(
+                ErrorCategory::ReadError,
+                "NoInput,Read-Host",
+                "Read-Host: no input is available",
+            )
+            .into()),
+        }
+    }
+}
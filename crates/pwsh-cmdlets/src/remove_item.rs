@@ -1,21 +1,7 @@
 /// Remove-Item cmdlet - deletes a file or directory
-use pwsh_runtime::{Cmdlet, CmdletContext, RuntimeError, Value};
+use pwsh_runtime::{Capability, Cmdlet, CmdletContext, ErrorRecord, RuntimeError, Value};
 use std::path::PathBuf;
 
-fn get_parameter_ci<'a>(context: &'a CmdletContext, name: &str) -> Option<&'a Value> {
-    // Try exact match first
-    if let Some(v) = context.parameters.get(name) {
-        return Some(v);
-    }
-
-    let name_lower = name.to_lowercase();
-    context
-        .parameters
-        .iter()
-        .find(|(k, _)| k.to_lowercase() == name_lower)
-        .map(|(_, v)| v)
-}
-
 fn resolve_path(path: &str) -> Result<PathBuf, RuntimeError> {
     let p = PathBuf::from(path);
     if p.is_absolute() {
@@ -23,25 +9,33 @@ fn resolve_path(path: &str) -> Result<PathBuf, RuntimeError> {
     }
 
     let cwd = std::env::current_dir().map_err(|e| {
-        RuntimeError::InvalidOperation(format!("Failed to get current directory: {}", e))
+        ErrorRecord::file_system(
+            &e,
+            "Remove-Item",
+            ".",
+            format!("Failed to get current directory: {}", e),
+        )
     })?;
     Ok(cwd.join(p))
 }
 
 fn extract_path(context: &CmdletContext) -> Result<PathBuf, RuntimeError> {
-    if let Some(Value::String(p)) = get_parameter_ci(context, "Path") {
+    if let Some(Value::String(p)) = context.get_parameter("Path") {
         resolve_path(p)
     } else if let Some(Value::String(p)) = context.get_argument(0) {
         resolve_path(p)
     } else {
-        Err(RuntimeError::InvalidOperation(
-            "Remove-Item requires a path".to_string(),
-        ))
+        Err(ErrorRecord::invalid_argument(
+            "PathRequired,Remove-Item",
+            "Remove-Item requires a path",
+        )
+        .into())
     }
 }
 
 fn parse_recurse(context: &CmdletContext) -> bool {
-    get_parameter_ci(context, "Recurse")
+    context
+        .get_parameter("Recurse")
         .map(|v| v.to_bool())
         .unwrap_or(false)
 }
@@ -54,47 +48,66 @@ impl Cmdlet for RemoveItemCmdlet {
         "Remove-Item"
     }
 
+    fn parameters(&self) -> &[&str] {
+        &["Path", "Recurse"]
+    }
+
+    fn capabilities(&self) -> &[Capability] {
+        &[Capability::FileSystem]
+    }
+
     fn execute(
         &self,
         context: CmdletContext,
-        _evaluator: &mut pwsh_runtime::Evaluator,
+        evaluator: &mut pwsh_runtime::Evaluator,
     ) -> Result<Vec<Value>, RuntimeError> {
         let path = extract_path(&context)?;
         let recurse = parse_recurse(&context);
 
-        let metadata = std::fs::metadata(&path).map_err(|e| {
-            RuntimeError::InvalidOperation(format!(
-                "Failed to access path '{}': {}",
-                path.display(),
-                e
-            ))
-        })?;
+        // A missing path is a non-terminating error: the script goes on
+        // unless -ErrorAction says to stop
+        let metadata = match std::fs::metadata(&path) {
+            Ok(metadata) => metadata,
+            Err(e) => {
+                let record = ErrorRecord::file_system(
+                    &e,
+                    "Remove-Item",
+                    path.display(),
+                    format!("Failed to access path '{}': {}", path.display(), e),
+                );
+                evaluator.write_error(record.into(), context.error_action)?;
+                return Ok(vec![]);
+            }
+        };
 
         if metadata.is_dir() {
             if recurse {
                 std::fs::remove_dir_all(&path).map_err(|e| {
-                    RuntimeError::InvalidOperation(format!(
-                        "Failed to remove directory '{}': {}",
+                    ErrorRecord::file_system(
+                        &e,
+                        "Remove-Item",
                         path.display(),
-                        e
-                    ))
+                        format!("Failed to remove directory '{}': {}", path.display(), e),
+                    )
                 })?;
             } else {
                 std::fs::remove_dir(&path).map_err(|e| {
-                    RuntimeError::InvalidOperation(format!(
-                        "Failed to remove directory '{}': {}",
+                    ErrorRecord::file_system(
+                        &e,
+                        "Remove-Item",
                         path.display(),
-                        e
-                    ))
+                        format!("Failed to remove directory '{}': {}", path.display(), e),
+                    )
                 })?;
             }
         } else {
             std::fs::remove_file(&path).map_err(|e| {
-                RuntimeError::InvalidOperation(format!(
-                    "Failed to remove file '{}': {}",
+                ErrorRecord::file_system(
+                    &e,
+                    "Remove-Item",
                     path.display(),
-                    e
-                ))
+                    format!("Failed to remove file '{}': {}", path.display(), e),
+                )
             })?;
         }
 
@@ -118,7 +131,7 @@ mod tests {
         let cmdlet = RemoveItemCmdlet;
         let context = CmdletContext::new().with_parameter(
             "Path".to_string(),
-            Value::String(file_path.to_string_lossy().to_string()),
+            Value::String(file_path.to_string_lossy().into()),
         );
         let mut evaluator = pwsh_runtime::Evaluator::new();
         cmdlet.execute(context, &mut evaluator).unwrap();
@@ -137,7 +150,7 @@ mod tests {
         let context = CmdletContext::new()
             .with_parameter(
                 "Path".to_string(),
-                Value::String(dir_path.to_string_lossy().to_string()),
+                Value::String(dir_path.to_string_lossy().into()),
             )
             .with_parameter("Recurse".to_string(), Value::Boolean(true));
         let mut evaluator = pwsh_runtime::Evaluator::new();
@@ -0,0 +1,63 @@
+/// Remove-PSBreakpoint cmdlet - deletes breakpoints, by id or as Get-PSBreakpoint wrote them
+use pwsh_runtime::{
+    unroll_to_items, Cmdlet, CmdletContext, ErrorCategory, ErrorRecord, RuntimeError, Value,
+};
+
+/// The id of a breakpoint given as its number or as the breakpoint itself
+fn breakpoint_id(value: &Value) -> Result<usize, RuntimeError> {
+    let id = value.get_property("Id").unwrap_or_else(|| value.clone());
+    match id.to_number() {
+        Some(n) if n >= 0.0 && n.fract() == 0.0 => Ok(n as usize),
+        _ => Err(ErrorRecord::invalid_argument(
+            "InvalidId,Remove-PSBreakpoint",
+            format!(
+                "Remove-PSBreakpoint needs a breakpoint or its id, not {}",
+                value
+            ),
+        )
+        .into()),
+    }
+}
+
+/// Remove-PSBreakpoint deletes the breakpoints with each -Id, or the
+/// breakpoints piped in, such as `Get-PSBreakpoint | Remove-PSBreakpoint`
+/// to delete them all. An id no breakpoint has is a non-terminating error.
+pub struct RemovePSBreakpointCmdlet;
+
+impl Cmdlet for RemovePSBreakpointCmdlet {
+    fn name(&self) -> &str {
+        "Remove-PSBreakpoint"
+    }
+
+    fn parameters(&self) -> &[&str] {
+        &["Id"]
+    }
+
+    fn execute(
+        &self,
+        context: CmdletContext,
+        evaluator: &mut pwsh_runtime::Evaluator,
+    ) -> Result<Vec<Value>, RuntimeError> {
+        let given = match context.get_parameter("Id").or(context.get_argument(0)) {
+            Some(ids) => unroll_to_items(vec![ids.clone()]),
+            None => unroll_to_items(context.pipeline_input),
+        };
+        let ids = given
+            .iter()
+            .map(breakpoint_id)
+            .collect::<Result<Vec<_>, _>>()?;
+        let action = context.error_action;
+        for id in ids {
+            if !evaluator.debugger_mut().remove(id) {
+                let error = ErrorRecord::new(
+                    ErrorCategory::ObjectNotFound,
+                    "PSBreakpointNotFound,Remove-PSBreakpoint",
+                    format!("There is no breakpoint with id {}", id),
+                )
+                .with_target(id);
+                evaluator.write_error(error.into(), action)?;
+            }
+        }
+        Ok(Vec::new())
+    }
+}
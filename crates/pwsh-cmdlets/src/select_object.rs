@@ -1,55 +1,74 @@
 /// Select-Object cmdlet - selects specific properties from objects
-use pwsh_runtime::{Cmdlet, CmdletContext, RuntimeError, Value};
-use std::collections::HashMap;
-
-fn get_parameter_ci<'a>(context: &'a CmdletContext, name: &str) -> Option<&'a Value> {
-    // Try exact match first
-    if let Some(v) = context.parameters.get(name) {
-        return Some(v);
-    }
-
-    let name_lower = name.to_lowercase();
-    context
-        .parameters
-        .iter()
-        .find(|(k, _)| k.to_lowercase() == name_lower)
-        .map(|(_, v)| v)
-}
+use pwsh_runtime::{
+    parse_number, Cmdlet, CmdletContext, ErrorRecord, PropertyMap, RuntimeError, Value,
+    WildcardPattern,
+};
 
 fn parse_count_param(context: &CmdletContext, name: &str) -> Result<Option<usize>, RuntimeError> {
-    let Some(v) = get_parameter_ci(context, name) else {
+    let Some(v) = context.get_parameter(name) else {
         return Ok(None);
     };
 
     let n = match v {
         Value::Number(n) => *n,
-        Value::String(s) => s.trim().parse::<f64>().map_err(|_| {
-            RuntimeError::InvalidOperation(format!(
-                "{name} must be a non-negative integer, got: {s}"
-            ))
+        Value::String(s) => parse_number(s).ok_or_else(|| {
+            ErrorRecord::invalid_argument(
+                "InvalidCount,Select-Object",
+                format!("{name} must be a non-negative integer, got: {s}"),
+            )
         })?,
         other => {
-            return Err(RuntimeError::InvalidOperation(format!(
-                "{name} must be a non-negative integer, got: {other}"
-            )))
+            return Err(ErrorRecord::invalid_argument(
+                "InvalidCount,Select-Object",
+                format!("{name} must be a non-negative integer, got: {other}"),
+            )
+            .into())
         }
     };
 
     if n.is_nan() || n.is_infinite() || n < 0.0 || n.fract() != 0.0 {
-        return Err(RuntimeError::InvalidOperation(format!(
-            "{name} must be a non-negative integer, got: {n}"
-        )));
+        return Err(ErrorRecord::invalid_argument(
+            "InvalidCount,Select-Object",
+            format!("{name} must be a non-negative integer, got: {n}"),
+        )
+        .into());
     }
 
     if n > (usize::MAX as f64) {
-        return Err(RuntimeError::InvalidOperation(format!(
-            "{name} is too large: {n}"
-        )));
+        return Err(ErrorRecord::invalid_argument(
+            "InvalidCount,Select-Object",
+            format!("{name} is too large: {n}"),
+        )
+        .into());
     }
 
     Ok(Some(n as usize))
 }
 
+/// Copy the properties `specifiers` name from `item` into a new object, in
+/// the order they are named. A name with wildcards (`*Time`, or `*` for all)
+/// takes every property it matches, in the object's order, that isn't
+/// already selected.
+fn select_properties(item: &Value, specifiers: &[(String, WildcardPattern)]) -> Value {
+    let mut selected = PropertyMap::new();
+    for (name, pattern) in specifiers {
+        if pattern.is_literal() {
+            // Use case-insensitive property lookup
+            if let Some(value) = item.get_property(name) {
+                selected.insert(name.clone(), value);
+            }
+        } else if let Some(properties) = item.properties() {
+            for (property, value) in properties.iter() {
+                if pattern.is_match(property.as_str()) && !selected.contains_key(property.as_str())
+                {
+                    selected.insert(property.clone(), value.clone());
+                }
+            }
+        }
+    }
+    Value::Object(selected.into())
+}
+
 /// Select-Object cmdlet selects properties from objects
 pub struct SelectObjectCmdlet;
 
@@ -58,13 +77,17 @@ impl Cmdlet for SelectObjectCmdlet {
         "Select-Object"
     }
 
+    fn parameters(&self) -> &[&str] {
+        &["Property", "First", "Last", "Skip"]
+    }
+
     fn execute(
         &self,
         context: CmdletContext,
         _evaluator: &mut pwsh_runtime::Evaluator,
     ) -> Result<Vec<Value>, RuntimeError> {
         // Extract parameters before consuming pipeline_input
-        let property_param = get_parameter_ci(&context, "Property").cloned();
+        let property_param = context.get_parameter("Property").cloned();
         let first = parse_count_param(&context, "First")?;
         let last = parse_count_param(&context, "Last")?;
         let skip = parse_count_param(&context, "Skip")?.unwrap_or(0);
@@ -73,52 +96,44 @@ impl Cmdlet for SelectObjectCmdlet {
         let mut input = context.pipeline_input;
 
         // Check for -Property parameter (select specific properties)
-        // Also support positional arguments: Select-Object Name, CPU
-        let property_value = if let Some(prop) = property_param {
-            Some(prop)
-        } else if !context.arguments.is_empty() {
-            // Use positional arguments as properties
-            Some(Value::Array(context.arguments.clone()))
-        } else {
-            None
-        };
-
-        if let Some(property_value) = property_value {
-            // Property can be a string (single property) or array (multiple properties)
-            let properties = match property_value {
-                Value::String(s) => vec![s.clone()],
-                Value::Array(arr) => arr
-                    .iter()
-                    .filter_map(|v| {
-                        if let Value::String(s) = v {
-                            Some(s.clone())
-                        } else {
-                            None
-                        }
-                    })
-                    .collect(),
-                _ => vec![],
-            };
+        // Also support positional arguments: Select-Object Name, CPU, which
+        // add to -Property.
+        let mut property_values: Vec<Value> = property_param.into_iter().collect();
+        property_values.extend(context.arguments.iter().cloned());
+
+        if !property_values.is_empty() {
+            // Each value can be a string (single property) or array (multiple properties)
+            let properties: Vec<String> = property_values
+                .iter()
+                .flat_map(|value| match value {
+                    Value::Array(arr) => arr.read().to_vec(),
+                    other => vec![other.clone()],
+                })
+                .filter_map(|v| {
+                    if let Value::String(s) = v {
+                        Some(s.to_string())
+                    } else {
+                        None
+                    }
+                })
+                .collect();
+            let specifiers = properties
+                .into_iter()
+                .map(|name| {
+                    let pattern = WildcardPattern::new(&name)?;
+                    Ok((name, pattern))
+                })
+                .collect::<Result<Vec<_>, RuntimeError>>()?;
 
             // Select only specified properties from each object
-            let mut results = Vec::new();
-            for item in input {
-                match item {
-                    Value::Object(_) => {
-                        let mut new_obj = HashMap::new();
-                        for prop_name in &properties {
-                            // Use case-insensitive property lookup
-                            if let Some(value) = item.get_property(prop_name) {
-                                new_obj.insert(prop_name.clone(), value);
-                            }
-                        }
-                        results.push(Value::Object(new_obj));
-                    }
+            input = input
+                .into_iter()
+                .map(|item| match item {
+                    Value::Object(_) => select_properties(&item, &specifiers),
                     // For non-objects, just pass through
-                    _ => results.push(item),
-                }
-            }
-            input = results;
+                    _ => item,
+                })
+                .collect();
         }
 
         // -Skip: skip N items from the beginning (PowerShell-aligned)
@@ -167,22 +182,23 @@ mod tests {
     fn test_select_object_property() {
         let cmdlet = SelectObjectCmdlet;
 
-        let mut obj = HashMap::new();
-        obj.insert("Name".to_string(), Value::String("Test".to_string()));
+        let mut obj = PropertyMap::new();
+        obj.insert("Name".to_string(), Value::String("Test".into()));
         obj.insert("Value".to_string(), Value::Number(42.0));
-        obj.insert("Extra".to_string(), Value::String("Ignore".to_string()));
+        obj.insert("Extra".to_string(), Value::String("Ignore".into()));
 
-        let input = vec![Value::Object(obj)];
+        let input = vec![Value::Object(obj.into())];
         let context = CmdletContext::with_input(input)
-            .with_parameter("Property".to_string(), Value::String("Name".to_string()));
+            .with_parameter("Property".to_string(), Value::String("Name".into()));
 
         let mut evaluator = pwsh_runtime::Evaluator::new();
         let result = cmdlet.execute(context, &mut evaluator).unwrap();
         assert_eq!(result.len(), 1);
 
         if let Value::Object(props) = &result[0] {
+            let props = props.read();
             assert_eq!(props.len(), 1);
-            assert_eq!(props.get("Name"), Some(&Value::String("Test".to_string())));
+            assert_eq!(props.get("Name"), Some(&Value::String("Test".into())));
         } else {
             panic!("Expected object result");
         }
@@ -219,27 +235,24 @@ mod tests {
     fn test_select_object_multiple_properties() {
         let cmdlet = SelectObjectCmdlet;
 
-        let mut obj1 = HashMap::new();
-        obj1.insert("Name".to_string(), Value::String("chrome".to_string()));
+        let mut obj1 = PropertyMap::new();
+        obj1.insert("Name".to_string(), Value::String("chrome".into()));
         obj1.insert("CPU".to_string(), Value::Number(45.2));
         obj1.insert("Id".to_string(), Value::Number(5678.0));
         obj1.insert("WorkingSet".to_string(), Value::Number(512000.0));
 
-        let mut obj2 = HashMap::new();
-        obj2.insert("Name".to_string(), Value::String("code".to_string()));
+        let mut obj2 = PropertyMap::new();
+        obj2.insert("Name".to_string(), Value::String("code".into()));
         obj2.insert("CPU".to_string(), Value::Number(23.1));
         obj2.insert("Id".to_string(), Value::Number(9012.0));
         obj2.insert("WorkingSet".to_string(), Value::Number(256000.0));
 
-        let input = vec![Value::Object(obj1), Value::Object(obj2)];
+        let input = vec![Value::Object(obj1.into()), Value::Object(obj2.into())];
 
         // Select Name and CPU properties
-        let properties = vec![
-            Value::String("Name".to_string()),
-            Value::String("CPU".to_string()),
-        ];
+        let properties = vec![Value::String("Name".into()), Value::String("CPU".into())];
         let context = CmdletContext::with_input(input)
-            .with_parameter("Property".to_string(), Value::Array(properties));
+            .with_parameter("Property".to_string(), Value::Array(properties.into()));
 
         let mut evaluator = pwsh_runtime::Evaluator::new();
         let result = cmdlet.execute(context, &mut evaluator).unwrap();
@@ -247,11 +260,9 @@ mod tests {
 
         // Verify first object only has Name and CPU
         if let Value::Object(props) = &result[0] {
+            let props = props.read();
             assert_eq!(props.len(), 2);
-            assert_eq!(
-                props.get("Name"),
-                Some(&Value::String("chrome".to_string()))
-            );
+            assert_eq!(props.get("Name"), Some(&Value::String("chrome".into())));
             assert_eq!(props.get("CPU"), Some(&Value::Number(45.2)));
             assert_eq!(props.get("Id"), None);
         } else {
@@ -260,8 +271,9 @@ mod tests {
 
         // Verify second object only has Name and CPU
         if let Value::Object(props) = &result[1] {
+            let props = props.read();
             assert_eq!(props.len(), 2);
-            assert_eq!(props.get("Name"), Some(&Value::String("code".to_string())));
+            assert_eq!(props.get("Name"), Some(&Value::String("code".into())));
             assert_eq!(props.get("CPU"), Some(&Value::Number(23.1)));
             assert_eq!(props.get("WorkingSet"), None);
         } else {
@@ -269,30 +281,62 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_select_object_wildcard_property() {
+        let cmdlet = SelectObjectCmdlet;
+
+        let mut obj = PropertyMap::new();
+        obj.insert("Name".to_string(), Value::String("Test".into()));
+        obj.insert("StartTime".to_string(), Value::Number(1.0));
+        obj.insert("ExitTime".to_string(), Value::Number(2.0));
+
+        // Positional properties, one of them an array
+        let context =
+            CmdletContext::with_input(vec![Value::Object(obj.into())]).with_arguments(vec![
+                Value::Array(
+                    vec![Value::String("*time".into()), Value::String("Name".into())].into(),
+                ),
+            ]);
+        let mut evaluator = pwsh_runtime::Evaluator::new();
+        let result = cmdlet.execute(context, &mut evaluator).unwrap();
+        let keys: Vec<String> = result[0]
+            .properties()
+            .unwrap()
+            .keys()
+            .map(|key| key.to_string())
+            .collect();
+        assert_eq!(keys, vec!["StartTime", "ExitTime", "Name"]);
+
+        let context = CmdletContext::new()
+            .with_parameter("Property".to_string(), Value::String("[ab".into()));
+        let error = cmdlet.execute(context, &mut evaluator).unwrap_err();
+        assert_eq!(error.error_id(), "WildcardPatternInvalid");
+    }
+
     #[test]
     fn test_select_object_property_then_first() {
         let cmdlet = SelectObjectCmdlet;
 
-        let mut obj1 = HashMap::new();
-        obj1.insert("Name".to_string(), Value::String("Test1".to_string()));
+        let mut obj1 = PropertyMap::new();
+        obj1.insert("Name".to_string(), Value::String("Test1".into()));
         obj1.insert("Value".to_string(), Value::Number(10.0));
 
-        let mut obj2 = HashMap::new();
-        obj2.insert("Name".to_string(), Value::String("Test2".to_string()));
+        let mut obj2 = PropertyMap::new();
+        obj2.insert("Name".to_string(), Value::String("Test2".into()));
         obj2.insert("Value".to_string(), Value::Number(20.0));
 
-        let mut obj3 = HashMap::new();
-        obj3.insert("Name".to_string(), Value::String("Test3".to_string()));
+        let mut obj3 = PropertyMap::new();
+        obj3.insert("Name".to_string(), Value::String("Test3".into()));
         obj3.insert("Value".to_string(), Value::Number(30.0));
 
         let input = vec![
-            Value::Object(obj1),
-            Value::Object(obj2),
-            Value::Object(obj3),
+            Value::Object(obj1.into()),
+            Value::Object(obj2.into()),
+            Value::Object(obj3.into()),
         ];
 
         let context = CmdletContext::with_input(input)
-            .with_parameter("Property".to_string(), Value::String("Name".to_string()))
+            .with_parameter("Property".to_string(), Value::String("Name".into()))
             .with_parameter("First".to_string(), Value::Number(2.0));
 
         let mut evaluator = pwsh_runtime::Evaluator::new();
@@ -301,8 +345,9 @@ mod tests {
 
         // Verify objects only have Name property
         if let Value::Object(props) = &result[0] {
+            let props = props.read();
             assert_eq!(props.len(), 1);
-            assert_eq!(props.get("Name"), Some(&Value::String("Test1".to_string())));
+            assert_eq!(props.get("Name"), Some(&Value::String("Test1".into())));
         } else {
             panic!("Expected object result");
         }
@@ -314,17 +359,17 @@ mod tests {
         let cmdlet = SelectObjectCmdlet;
 
         // Create object with properties in specific case
-        let mut obj = HashMap::new();
+        let mut obj = PropertyMap::new();
         obj.insert("CPU".to_string(), Value::Number(45.2));
-        obj.insert("Name".to_string(), Value::String("pwsh".to_string()));
+        obj.insert("Name".to_string(), Value::String("pwsh".into()));
         obj.insert("Id".to_string(), Value::Number(3456.0));
 
-        let input = vec![Value::Object(obj)];
+        let input = vec![Value::Object(obj.into())];
 
         // Select using different case - "CPu" instead of "CPU"
-        let properties = vec![Value::String("CPu".to_string())];
+        let properties = vec![Value::String("CPu".into())];
         let context = CmdletContext::with_input(input)
-            .with_parameter("Property".to_string(), Value::Array(properties));
+            .with_parameter("Property".to_string(), Value::Array(properties.into()));
 
         let mut evaluator = pwsh_runtime::Evaluator::new();
         let result = cmdlet.execute(context, &mut evaluator).unwrap();
@@ -332,6 +377,7 @@ mod tests {
 
         // Verify the property was found despite case difference
         if let Value::Object(props) = &result[0] {
+            let props = props.read();
             assert_eq!(props.len(), 1);
             // The result should contain the property with the value
             // Note: The key uses the requested case "CPu"
@@ -347,34 +393,31 @@ mod tests {
         let cmdlet = SelectObjectCmdlet;
 
         // Create mock process objects
-        let mut chrome = HashMap::new();
-        chrome.insert("Name".to_string(), Value::String("chrome".to_string()));
+        let mut chrome = PropertyMap::new();
+        chrome.insert("Name".to_string(), Value::String("chrome".into()));
         chrome.insert("CPU".to_string(), Value::Number(45.2));
         chrome.insert("Id".to_string(), Value::Number(5678.0));
 
-        let mut code = HashMap::new();
-        code.insert("Name".to_string(), Value::String("code".to_string()));
+        let mut code = PropertyMap::new();
+        code.insert("Name".to_string(), Value::String("code".into()));
         code.insert("CPU".to_string(), Value::Number(23.1));
         code.insert("Id".to_string(), Value::Number(9012.0));
 
-        let mut pwsh = HashMap::new();
-        pwsh.insert("Name".to_string(), Value::String("pwsh".to_string()));
+        let mut pwsh = PropertyMap::new();
+        pwsh.insert("Name".to_string(), Value::String("pwsh".into()));
         pwsh.insert("CPU".to_string(), Value::Number(5.0));
         pwsh.insert("Id".to_string(), Value::Number(3456.0));
 
         let input = vec![
-            Value::Object(chrome),
-            Value::Object(code),
-            Value::Object(pwsh),
+            Value::Object(chrome.into()),
+            Value::Object(code.into()),
+            Value::Object(pwsh.into()),
         ];
 
         // Test: $objects | Select-Object Name, CPU
-        let properties = vec![
-            Value::String("Name".to_string()),
-            Value::String("CPU".to_string()),
-        ];
+        let properties = vec![Value::String("Name".into()), Value::String("CPU".into())];
         let context = CmdletContext::with_input(input.clone())
-            .with_parameter("Property".to_string(), Value::Array(properties));
+            .with_parameter("Property".to_string(), Value::Array(properties.into()));
 
         let mut evaluator = pwsh_runtime::Evaluator::new();
         let result = cmdlet.execute(context, &mut evaluator).unwrap();
@@ -383,6 +426,7 @@ mod tests {
         // Verify each object only has Name and CPU
         for obj in result {
             if let Value::Object(props) = obj {
+                let props = props.read();
                 assert_eq!(props.len(), 2);
                 assert!(props.contains_key("Name"));
                 assert!(props.contains_key("CPU"));
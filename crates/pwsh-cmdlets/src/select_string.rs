@@ -0,0 +1,378 @@
+/// Select-String cmdlet - finds text matching a pattern in strings and files
+use pwsh_runtime::{
+    parse_switch, unroll_to_items, Capability, Cmdlet, CmdletContext, ErrorRecord, Evaluator,
+    PropertyMap, RuntimeError, Value,
+};
+use regex::{Regex, RegexBuilder};
+use std::path::PathBuf;
+
+/// The Filename and Path of matches in strings rather than files
+const INPUT_STREAM: &str = "InputStream";
+
+fn resolve_path(path: &str) -> Result<PathBuf, RuntimeError> {
+    let p = PathBuf::from(path);
+    if p.is_absolute() {
+        return Ok(p);
+    }
+
+    let cwd = std::env::current_dir().map_err(|e| {
+        ErrorRecord::file_system(
+            &e,
+            "Select-String",
+            ".",
+            format!("Failed to get current directory: {}", e),
+        )
+    })?;
+    Ok(cwd.join(p))
+}
+
+/// A -Pattern, compiled
+struct Pattern {
+    text: String,
+    regex: Regex,
+}
+
+/// Where lines come from: piped strings, or a file
+enum Source {
+    Text(String),
+    File(PathBuf),
+}
+
+/// How lines are matched and which matches are kept
+struct Search {
+    patterns: Vec<Pattern>,
+    case_sensitive: bool,
+    not_match: bool,
+    /// Only the first match of each source (-List)
+    list: bool,
+}
+
+impl Search {
+    /// The MatchInfo for `line`, if it is one to keep
+    fn match_line(
+        &self,
+        line: &str,
+        line_number: usize,
+        filename: &str,
+        path: &str,
+    ) -> Option<Value> {
+        let found = self.patterns.iter().find(|p| p.regex.is_match(line));
+        let (pattern, matches) = match (found, self.not_match) {
+            (Some(pattern), false) => {
+                let matched = pattern.regex.find(line).map(|m| m.as_str()).unwrap_or("");
+                (
+                    pattern.text.as_str(),
+                    vec![Value::String(matched.to_string().into())],
+                )
+            }
+            // -NotMatch: lines that no pattern matches, reported with the first pattern
+            (None, true) => (self.patterns[0].text.as_str(), vec![]),
+            _ => return None,
+        };
+
+        let mut props = PropertyMap::with_capacity(7);
+        props.insert("IgnoreCase", Value::Boolean(!self.case_sensitive));
+        props.insert("LineNumber", Value::Number(line_number as f64));
+        props.insert("Line", Value::String(line.into()));
+        props.insert("Filename", Value::String(filename.into()));
+        props.insert("Path", Value::String(path.into()));
+        props.insert("Pattern", Value::String(pattern.into()));
+        props.insert("Matches", Value::Array(matches.into()));
+        Some(Value::Object(props.into()))
+    }
+}
+
+/// The sources in the input: objects with a FullName (as Get-ChildItem
+/// writes) are files, directories are skipped, and anything else is text
+fn input_sources(items: Vec<Value>) -> Vec<Source> {
+    items
+        .into_iter()
+        .filter_map(|item| {
+            let full_name = match &item {
+                Value::Object(_) | Value::Instance { .. } => item.get_property("FullName"),
+                _ => None,
+            };
+            match full_name {
+                Some(_) if item.get_property("Directory").is_some_and(|d| d.to_bool()) => None,
+                Some(full_name) => Some(Source::File(PathBuf::from(full_name.to_string()))),
+                None => Some(Source::Text(item.to_string())),
+            }
+        })
+        .collect()
+}
+
+/// Select-String finds lines matching a regular expression (or, with
+/// -SimpleMatch, containing a string), ignoring case unless -CaseSensitive
+/// is given. Lines come from -Path, from files piped in by Get-ChildItem, or
+/// from strings piped in or given by -InputObject. Each match is written as
+/// a MatchInfo object (LineNumber, Line, Filename, Path, Pattern, Matches).
+///
+/// -NotMatch keeps the lines that don't match instead, -List keeps only the
+/// first match of each file, and -Quiet writes just `$true` or `$false`
+/// for whether anything matched. A file that can't be read is a
+/// non-terminating error.
+pub struct SelectStringCmdlet;
+
+impl Cmdlet for SelectStringCmdlet {
+    fn name(&self) -> &str {
+        "Select-String"
+    }
+
+    fn parameters(&self) -> &[&str] {
+        &[
+            "Pattern",
+            "Path",
+            "InputObject",
+            "SimpleMatch",
+            "CaseSensitive",
+            "NotMatch",
+            "List",
+            "Quiet",
+        ]
+    }
+
+    fn capabilities(&self) -> &[Capability] {
+        &[Capability::FileSystem]
+    }
+
+    fn execute(
+        &self,
+        context: CmdletContext,
+        evaluator: &mut Evaluator,
+    ) -> Result<Vec<Value>, RuntimeError> {
+        let simple_match = parse_switch(context.get_parameter("SimpleMatch"), "Select-String")?;
+        let case_sensitive = parse_switch(context.get_parameter("CaseSensitive"), "Select-String")?;
+        let quiet = parse_switch(context.get_parameter("Quiet"), "Select-String")?;
+        let action = context.error_action;
+
+        // Support: Select-String 'error' and Select-String 'error' log.txt
+        let pattern_value = context
+            .get_parameter("Pattern")
+            .or_else(|| context.get_argument(0))
+            .cloned()
+            .ok_or_else(|| {
+                ErrorRecord::invalid_argument(
+                    "MissingPattern,Select-String",
+                    "Select-String: -Pattern is required",
+                )
+            })?;
+        let patterns = unroll_to_items(vec![pattern_value])
+            .into_iter()
+            .map(|value| {
+                let text = value.to_string();
+                let source = if simple_match {
+                    regex::escape(&text)
+                } else {
+                    text.clone()
+                };
+                let regex = RegexBuilder::new(&source)
+                    .case_insensitive(!case_sensitive)
+                    .build()
+                    .map_err(|_| {
+                        ErrorRecord::invalid_argument(
+                            "InvalidRegex,Select-String",
+                            format!("The regular expression pattern {} is not valid", text),
+                        )
+                    })?;
+                Ok(Pattern { text, regex })
+            })
+            .collect::<Result<Vec<_>, RuntimeError>>()?;
+        if patterns.is_empty() {
+            return Err(ErrorRecord::invalid_argument(
+                "MissingPattern,Select-String",
+                "Select-String: -Pattern is required",
+            )
+            .into());
+        }
+        let search = Search {
+            patterns,
+            case_sensitive,
+            not_match: parse_switch(context.get_parameter("NotMatch"), "Select-String")?,
+            list: parse_switch(context.get_parameter("List"), "Select-String")?,
+        };
+
+        let path_value = context
+            .get_parameter("Path")
+            .or_else(|| context.get_argument(1))
+            .cloned();
+        let sources = match path_value {
+            Some(paths) => unroll_to_items(vec![paths])
+                .iter()
+                .map(|path| resolve_path(&path.to_string()).map(Source::File))
+                .collect::<Result<Vec<_>, _>>()?,
+            None => match context.get_parameter("InputObject") {
+                Some(input) => input_sources(unroll_to_items(vec![input.clone()])),
+                None => input_sources(context.pipeline_input),
+            },
+        };
+
+        let mut results = Vec::new();
+        // Piped strings are numbered as the lines of one stream
+        let mut stream_line = 0;
+        let mut stream_listed = false;
+        for source in sources {
+            match source {
+                Source::Text(text) => {
+                    stream_line += 1;
+                    if search.list && stream_listed {
+                        continue;
+                    }
+                    if let Some(info) =
+                        search.match_line(&text, stream_line, INPUT_STREAM, INPUT_STREAM)
+                    {
+                        results.push(info);
+                        stream_listed = true;
+                    }
+                }
+                Source::File(path) => {
+                    let bytes = match std::fs::read(&path) {
+                        Ok(bytes) => bytes,
+                        Err(e) => {
+                            let record = ErrorRecord::file_system(
+                                &e,
+                                "Select-String",
+                                path.display(),
+                                format!("Failed to read file '{}': {}", path.display(), e),
+                            );
+                            evaluator.write_error(record.into(), action)?;
+                            continue;
+                        }
+                    };
+                    let content = String::from_utf8_lossy(&bytes);
+                    let content = content.strip_prefix('\u{feff}').unwrap_or(&content);
+                    let filename = path
+                        .file_name()
+                        .map(|n| n.to_string_lossy().to_string())
+                        .unwrap_or_default();
+                    let full_path = path.display().to_string();
+                    for (index, line) in content.lines().enumerate() {
+                        if let Some(info) =
+                            search.match_line(line, index + 1, &filename, &full_path)
+                        {
+                            results.push(info);
+                            if search.list {
+                                break;
+                            }
+                        }
+                    }
+                }
+            }
+            if quiet && !results.is_empty() {
+                break;
+            }
+        }
+
+        if quiet {
+            return Ok(vec![Value::Boolean(!results.is_empty())]);
+        }
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pwsh_runtime::ActionPreference;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn strings(items: &[&str]) -> Vec<Value> {
+        items.iter().map(|s| Value::String((*s).into())).collect()
+    }
+
+    fn run(context: CmdletContext) -> Vec<Value> {
+        let mut evaluator = Evaluator::new();
+        SelectStringCmdlet.execute(context, &mut evaluator).unwrap()
+    }
+
+    fn property(value: &Value, name: &str) -> String {
+        value.get_property(name).unwrap().to_string()
+    }
+
+    #[test]
+    fn test_select_string_matches_piped_strings() {
+        let context = CmdletContext::with_input(strings(&["ok", "ERROR: disk", "error: net"]))
+            .with_arguments(vec![Value::String("error".into())]);
+        let result = run(context);
+        assert_eq!(result.len(), 2);
+        assert_eq!(property(&result[0], "LineNumber"), "2");
+        assert_eq!(property(&result[0], "Filename"), "InputStream");
+        assert_eq!(property(&result[1], "Line"), "error: net");
+
+        let context = CmdletContext::with_input(strings(&["ok", "ERROR: disk", "error: net"]))
+            .with_arguments(vec![Value::String("error".into())])
+            .with_parameter("CaseSensitive".to_string(), Value::Boolean(true))
+            .with_parameter("NotMatch".to_string(), Value::Boolean(true));
+        let result = run(context);
+        let lines: Vec<String> = result.iter().map(|r| property(r, "Line")).collect();
+        assert_eq!(lines, vec!["ok", "ERROR: disk"]);
+    }
+
+    #[test]
+    fn test_select_string_quiet_and_simple_match() {
+        let context = CmdletContext::with_input(strings(&["a.b", "axb"]))
+            .with_parameter("Pattern".to_string(), Value::String("a.b".into()))
+            .with_parameter("SimpleMatch".to_string(), Value::Boolean(true));
+        assert_eq!(run(context).len(), 1);
+
+        let context = CmdletContext::with_input(strings(&["a", "b"]))
+            .with_arguments(vec![Value::String("z".into())])
+            .with_parameter("Quiet".to_string(), Value::Boolean(true));
+        assert_eq!(run(context), vec![Value::Boolean(false)]);
+    }
+
+    #[test]
+    fn test_select_string_reads_files_from_get_childitem() {
+        let temp_dir = TempDir::new().unwrap();
+        let first = temp_dir.path().join("a.log");
+        fs::write(&first, "start\nfail one\nfail two\n").unwrap();
+        fs::write(temp_dir.path().join("b.log"), "fail three\n").unwrap();
+
+        let mut file = PropertyMap::new();
+        file.insert("Name", Value::String("a.log".into()));
+        file.insert(
+            "FullName",
+            Value::String(first.display().to_string().into()),
+        );
+        file.insert("Directory", Value::Boolean(false));
+        let context = CmdletContext::with_input(vec![Value::Object(file.into())])
+            .with_arguments(vec![Value::String("fail".into())])
+            .with_parameter("List".to_string(), Value::Boolean(true));
+        let result = run(context);
+        assert_eq!(result.len(), 1);
+        assert_eq!(property(&result[0], "LineNumber"), "2");
+        assert_eq!(property(&result[0], "Filename"), "a.log");
+
+        let paths = Value::Array(
+            vec![
+                Value::String(first.display().to_string().into()),
+                Value::String(temp_dir.path().join("b.log").display().to_string().into()),
+            ]
+            .into(),
+        );
+        let context = CmdletContext::new()
+            .with_arguments(vec![Value::String("fail".into())])
+            .with_parameter("Path".to_string(), paths);
+        assert_eq!(run(context).len(), 3);
+    }
+
+    #[test]
+    fn test_select_string_missing_file_is_non_terminating() {
+        let mut evaluator = Evaluator::new();
+        let context = CmdletContext::new()
+            .with_arguments(vec![
+                Value::String("x".into()),
+                Value::String("/no/such/file.txt".into()),
+            ])
+            .with_error_action(ActionPreference::SilentlyContinue);
+        let result = SelectStringCmdlet.execute(context, &mut evaluator).unwrap();
+        assert!(result.is_empty());
+
+        let context =
+            CmdletContext::new().with_parameter("Pattern".to_string(), Value::String("(".into()));
+        let error = SelectStringCmdlet
+            .execute(context, &mut evaluator)
+            .unwrap_err();
+        assert_eq!(error.error_id(), "InvalidRegex,Select-String");
+    }
+}
@@ -1,22 +1,8 @@
 /// Set-Content cmdlet - writes content to a file (overwriting existing content)
-use pwsh_runtime::{Cmdlet, CmdletContext, RuntimeError, Value};
+use pwsh_runtime::{Capability, Cmdlet, CmdletContext, ErrorRecord, RuntimeError, Value};
 use std::fs;
 use std::path::PathBuf;
 
-fn get_parameter_ci<'a>(context: &'a CmdletContext, name: &str) -> Option<&'a Value> {
-    // Try exact match first
-    if let Some(v) = context.parameters.get(name) {
-        return Some(v);
-    }
-
-    let name_lower = name.to_lowercase();
-    context
-        .parameters
-        .iter()
-        .find(|(k, _)| k.to_lowercase() == name_lower)
-        .map(|(_, v)| v)
-}
-
 fn resolve_path(path: &str) -> Result<PathBuf, RuntimeError> {
     let p = PathBuf::from(path);
     if p.is_absolute() {
@@ -24,14 +10,19 @@ fn resolve_path(path: &str) -> Result<PathBuf, RuntimeError> {
     }
 
     let cwd = std::env::current_dir().map_err(|e| {
-        RuntimeError::InvalidOperation(format!("Failed to get current directory: {}", e))
+        ErrorRecord::file_system(
+            &e,
+            "Set-Content",
+            ".",
+            format!("Failed to get current directory: {}", e),
+        )
     })?;
     Ok(cwd.join(p))
 }
 
 fn normalize_value_to_lines(value: Value) -> Vec<String> {
     match value {
-        Value::Array(items) => items.into_iter().map(|v| v.to_string()).collect(),
+        Value::Array(items) => items.read().iter().map(|v| v.to_string()).collect(),
         other => vec![other.to_string()],
     }
 }
@@ -63,49 +54,62 @@ impl Cmdlet for SetContentCmdlet {
         "Set-Content"
     }
 
+    fn parameters(&self) -> &[&str] {
+        &["Path", "Value"]
+    }
+
+    fn capabilities(&self) -> &[Capability] {
+        &[Capability::FileSystem]
+    }
+
     fn execute(
         &self,
         context: CmdletContext,
         _evaluator: &mut pwsh_runtime::Evaluator,
     ) -> Result<Vec<Value>, RuntimeError> {
         // Path: -Path or first positional argument
-        let path = if let Some(Value::String(p)) = get_parameter_ci(&context, "Path") {
+        let path = if let Some(Value::String(p)) = context.get_parameter("Path") {
             resolve_path(p)?
         } else if let Some(Value::String(p)) = context.get_argument(0) {
             resolve_path(p)?
         } else {
-            return Err(RuntimeError::InvalidOperation(
-                "Set-Content requires a file path".to_string(),
-            ));
+            return Err(ErrorRecord::invalid_argument(
+                "PathRequired,Set-Content",
+                "Set-Content requires a file path",
+            )
+            .into());
         };
 
         // Value: -Value takes precedence, else pipeline input, else second positional argument
-        let values: Vec<Value> = if let Some(v) = get_parameter_ci(&context, "Value") {
+        let values: Vec<Value> = if let Some(v) = context.get_parameter("Value") {
             normalize_value_to_lines(v.clone())
                 .into_iter()
-                .map(Value::String)
+                .map(|line| Value::String(line.into()))
                 .collect()
         } else if !context.pipeline_input.is_empty() {
             context.pipeline_input
         } else if let Some(v) = context.get_argument(1) {
             normalize_value_to_lines(v.clone())
                 .into_iter()
-                .map(Value::String)
+                .map(|line| Value::String(line.into()))
                 .collect()
         } else {
-            return Err(RuntimeError::InvalidOperation(
-                "Set-Content requires a value to write (use -Value or pipeline input)".to_string(),
-            ));
+            return Err(ErrorRecord::invalid_argument(
+                "ValueRequired,Set-Content",
+                "Set-Content requires a value to write (use -Value or pipeline input)",
+            )
+            .into());
         };
 
         let data = values_to_file_string(values);
 
         fs::write(&path, data).map_err(|e| {
-            RuntimeError::InvalidOperation(format!(
-                "Failed to write file '{}': {}",
+            ErrorRecord::file_system(
+                &e,
+                "Set-Content",
                 path.display(),
-                e
-            ))
+                format!("Failed to write file '{}': {}", path.display(), e),
+            )
         })?;
 
         // Set-Content does not emit pipeline output by default.
@@ -126,8 +130,8 @@ mod tests {
 
         let cmdlet = SetContentCmdlet;
         let context = CmdletContext::new().with_arguments(vec![
-            Value::String(file_path.to_string_lossy().to_string()),
-            Value::String("Hello".to_string()),
+            Value::String(file_path.to_string_lossy().into()),
+            Value::String("Hello".into()),
         ]);
         let mut evaluator = pwsh_runtime::Evaluator::new();
         let result = cmdlet.execute(context, &mut evaluator);
@@ -146,15 +150,18 @@ mod tests {
         let context = CmdletContext::new()
             .with_parameter(
                 "Path".to_string(),
-                Value::String(file_path.to_string_lossy().to_string()),
+                Value::String(file_path.to_string_lossy().into()),
             )
             .with_parameter(
                 "Value".to_string(),
-                Value::Array(vec![
-                    Value::String("Line 1".to_string()),
-                    Value::String("Line 2".to_string()),
-                    Value::String("Line 3".to_string()),
-                ]),
+                Value::Array(
+                    vec![
+                        Value::String("Line 1".into()),
+                        Value::String("Line 2".into()),
+                        Value::String("Line 3".into()),
+                    ]
+                    .into(),
+                ),
             );
         let mut evaluator = pwsh_runtime::Evaluator::new();
         let result = cmdlet.execute(context, &mut evaluator);
@@ -172,8 +179,8 @@ mod tests {
 
         let cmdlet = SetContentCmdlet;
         let context = CmdletContext::new().with_arguments(vec![
-            Value::String(file_path.to_string_lossy().to_string()),
-            Value::String("New".to_string()),
+            Value::String(file_path.to_string_lossy().into()),
+            Value::String("New".into()),
         ]);
         let mut evaluator = pwsh_runtime::Evaluator::new();
         cmdlet.execute(context, &mut evaluator).unwrap();
@@ -188,11 +195,9 @@ mod tests {
         let file_path = temp_dir.path().join("out.txt");
 
         let cmdlet = SetContentCmdlet;
-        let context = CmdletContext::with_input(vec![
-            Value::String("a".to_string()),
-            Value::String("b".to_string()),
-        ])
-        .with_arguments(vec![Value::String(file_path.to_string_lossy().to_string())]);
+        let context =
+            CmdletContext::with_input(vec![Value::String("a".into()), Value::String("b".into())])
+                .with_arguments(vec![Value::String(file_path.to_string_lossy().into())]);
         let mut evaluator = pwsh_runtime::Evaluator::new();
         cmdlet.execute(context, &mut evaluator).unwrap();
 
@@ -203,8 +208,8 @@ mod tests {
     #[test]
     fn test_set_content_missing_path_errors() {
         let cmdlet = SetContentCmdlet;
-        let context = CmdletContext::new()
-            .with_parameter("Value".to_string(), Value::String("Hello".to_string()));
+        let context =
+            CmdletContext::new().with_parameter("Value".to_string(), Value::String("Hello".into()));
         let mut evaluator = pwsh_runtime::Evaluator::new();
         let result = cmdlet.execute(context, &mut evaluator);
         assert!(result.is_err());
@@ -219,9 +224,9 @@ mod tests {
         let context = CmdletContext::new()
             .with_parameter(
                 "Path".to_string(),
-                Value::String(file_path.to_string_lossy().to_string()),
+                Value::String(file_path.to_string_lossy().into()),
             )
-            .with_arguments(vec![Value::String(file_path.to_string_lossy().to_string())]);
+            .with_arguments(vec![Value::String(file_path.to_string_lossy().into())]);
 
         let mut evaluator = pwsh_runtime::Evaluator::new();
         let result = cmdlet.execute(context, &mut evaluator);
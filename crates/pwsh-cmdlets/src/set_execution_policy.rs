@@ -0,0 +1,69 @@
+/// Set-ExecutionPolicy cmdlet - changes which script files the session runs
+use pwsh_runtime::{
+    Cmdlet, CmdletContext, ErrorCategory, ErrorRecord, ExecutionPolicy, RuntimeError, Value,
+};
+
+/// Set-ExecutionPolicy changes the session's execution policy. The policy
+/// belongs to the session, so it lasts until the session ends, and a host
+/// that locked it refuses the change with an `ExecutionPolicyOverride`
+/// error.
+pub struct SetExecutionPolicyCmdlet;
+
+impl Cmdlet for SetExecutionPolicyCmdlet {
+    fn name(&self) -> &str {
+        "Set-ExecutionPolicy"
+    }
+
+    fn parameters(&self) -> &[&str] {
+        &["ExecutionPolicy"]
+    }
+
+    fn execute(
+        &self,
+        context: CmdletContext,
+        evaluator: &mut pwsh_runtime::Evaluator,
+    ) -> Result<Vec<Value>, RuntimeError> {
+        let Some(name) = context
+            .get_parameter("ExecutionPolicy")
+            .or_else(|| context.get_argument(0))
+            .map(|value| value.to_string())
+        else {
+            return Err(ErrorRecord::new(
+                ErrorCategory::InvalidArgument,
+                "MissingExecutionPolicy,Set-ExecutionPolicy",
+                "Set-ExecutionPolicy needs the -ExecutionPolicy to set.",
+            )
+            .into());
+        };
+        let policy = ExecutionPolicy::from_name(&name).ok_or_else(|| {
+            let names: Vec<String> = ExecutionPolicy::ALL.iter().map(|p| p.to_string()).collect();
+            RuntimeError::from(
+                ErrorRecord::new(
+                    ErrorCategory::InvalidArgument,
+                    "InvalidExecutionPolicy,Set-ExecutionPolicy",
+                    format!(
+                        "Cannot convert '{}' to an execution policy. Specify one of the following values: {}.",
+                        name,
+                        names.join(", ")
+                    ),
+                )
+                .with_target(name.as_str()),
+            )
+        })?;
+
+        if evaluator.execution_policy_locked() {
+            return Err(ErrorRecord::new(
+                ErrorCategory::SecurityError,
+                "ExecutionPolicyOverride,Set-ExecutionPolicy",
+                format!(
+                    "The execution policy can't be changed to {}: the host has set it to {} for this session.",
+                    policy,
+                    evaluator.execution_policy()
+                ),
+            )
+            .into());
+        }
+        evaluator.set_execution_policy(policy);
+        Ok(Vec::new())
+    }
+}
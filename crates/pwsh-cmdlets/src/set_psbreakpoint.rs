@@ -0,0 +1,194 @@
+/// Set-PSBreakpoint cmdlet - stops a script at a line, a command call or a variable access and opens the debugger
+use pwsh_runtime::{
+    resolve_script, unroll_to_items, BreakpointKind, Cmdlet, CmdletContext, ErrorCategory,
+    ErrorRecord, RuntimeError, Value, VariableAccess, WildcardPattern,
+};
+use std::path::PathBuf;
+
+/// A line or column number: a whole number from 1 up
+fn parse_position(name: &str, value: &Value) -> Result<usize, RuntimeError> {
+    match value.to_number() {
+        Some(n) if n >= 1.0 && n.fract() == 0.0 => Ok(n as usize),
+        _ => Err(ErrorRecord::invalid_argument(
+            &format!("Invalid{},Set-PSBreakpoint", name),
+            format!("-{} must be a whole number above 0, not {}", name, value),
+        )
+        .into()),
+    }
+}
+
+/// The -Script parameter: a script that exists, resolved the way the
+/// debugger compares the scripts code runs from
+fn parse_script(value: &Value) -> Result<PathBuf, RuntimeError> {
+    let path = value.to_string();
+    resolve_script(&PathBuf::from(&path)).ok_or_else(|| {
+        ErrorRecord::new(
+            ErrorCategory::ObjectNotFound,
+            "NewBreakpointScriptNotFound,Set-PSBreakpoint",
+            format!("Cannot set a breakpoint in '{}': it does not exist", path),
+        )
+        .with_target(&path)
+        .into()
+    })
+}
+
+/// Set-PSBreakpoint sets breakpoints: at each -Line of a -Script (at
+/// -Column of it, if given), at calls to each -Command (a wildcard
+/// pattern), or at accesses to each -Variable — written to by default,
+/// or read with `-Mode Read` or either with `-Mode ReadWrite`. Command and
+/// variable breakpoints stop anywhere unless given a -Script. -Script and
+/// -Line can also be given by position.
+///
+/// When code reaches a breakpoint the debugger stops there and reads
+/// commands at a `[DBG]: PS>` prompt; with an -Action, it runs the action
+/// instead, and stops only if the action ends with `break`. The new
+/// breakpoints are written out.
+pub struct SetPSBreakpointCmdlet;
+
+impl Cmdlet for SetPSBreakpointCmdlet {
+    fn name(&self) -> &str {
+        "Set-PSBreakpoint"
+    }
+
+    fn parameters(&self) -> &[&str] {
+        &[
+            "Script", "Line", "Column", "Command", "Variable", "Mode", "Action",
+        ]
+    }
+
+    fn execute(
+        &self,
+        context: CmdletContext,
+        evaluator: &mut pwsh_runtime::Evaluator,
+    ) -> Result<Vec<Value>, RuntimeError> {
+        let script = context
+            .get_parameter("Script")
+            .or(context.get_argument(0))
+            .map(parse_script)
+            .transpose()?;
+        let lines = context
+            .get_parameter("Line")
+            .or(context.get_argument(1))
+            .map(|lines| unroll_to_items(vec![lines.clone()]))
+            .unwrap_or_default()
+            .iter()
+            .map(|line| parse_position("Line", line))
+            .collect::<Result<Vec<_>, _>>()?;
+        let column = context
+            .get_parameter("Column")
+            .map(|column| parse_position("Column", column))
+            .transpose()?
+            .unwrap_or(0);
+        let names = |parameter: &str| -> Vec<String> {
+            context
+                .get_parameter(parameter)
+                .map(|names| unroll_to_items(vec![names.clone()]))
+                .unwrap_or_default()
+                .iter()
+                .map(|name| name.to_string())
+                .collect()
+        };
+        let commands = names("Command");
+        let variables = names("Variable");
+        let action = match context.get_parameter("Action") {
+            None | Some(Value::Null) => None,
+            Some(Value::ScriptBlock(block)) => Some(block.clone()),
+            Some(other) => {
+                return Err(ErrorRecord::invalid_argument(
+                    "InvalidAction,Set-PSBreakpoint",
+                    format!("-Action must be a script block, not {}", other),
+                )
+                .into())
+            }
+        };
+
+        let kinds: Vec<BreakpointKind> =
+            match (lines.is_empty(), commands.is_empty(), variables.is_empty()) {
+                (false, true, true) => {
+                    if script.is_none() {
+                        return Err(ErrorRecord::invalid_argument(
+                            "ScriptRequired,Set-PSBreakpoint",
+                            "A line breakpoint needs the -Script it is in",
+                        )
+                        .into());
+                    }
+                    lines
+                        .into_iter()
+                        .map(|line| BreakpointKind::Line { line, column })
+                        .collect()
+                }
+                (true, false, true) => {
+                    for command in &commands {
+                        WildcardPattern::new(command)?;
+                    }
+                    commands.into_iter().map(BreakpointKind::Command).collect()
+                }
+                (true, true, false) => {
+                    let mode = match context.get_parameter("Mode") {
+                        None => VariableAccess::Write,
+                        Some(mode) => {
+                            VariableAccess::from_name(&mode.to_string()).ok_or_else(|| {
+                                ErrorRecord::invalid_argument(
+                                    "InvalidMode,Set-PSBreakpoint",
+                                    format!("-Mode must be Read, Write or ReadWrite, not {}", mode),
+                                )
+                            })?
+                        }
+                    };
+                    variables
+                        .into_iter()
+                        .map(|name| BreakpointKind::Variable {
+                            name: name.trim_start_matches('$').to_string(),
+                            mode,
+                        })
+                        .collect()
+                }
+                _ => {
+                    return Err(ErrorRecord::invalid_argument(
+                        "BreakpointKindRequired,Set-PSBreakpoint",
+                        "Set-PSBreakpoint needs one of -Line, -Command or -Variable",
+                    )
+                    .into())
+                }
+            };
+
+        let debugger = evaluator.debugger_mut();
+        Ok(kinds
+            .into_iter()
+            .map(|kind| {
+                debugger
+                    .add(kind, script.clone(), action.clone())
+                    .to_value()
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_psbreakpoint_needs_one_kind_of_breakpoint() {
+        let mut evaluator = pwsh_runtime::Evaluator::new();
+        let error = SetPSBreakpointCmdlet
+            .execute(CmdletContext::new(), &mut evaluator)
+            .unwrap_err();
+        assert_eq!(error.error_id(), "BreakpointKindRequired,Set-PSBreakpoint");
+
+        let context = CmdletContext::new().with_parameter("Line".to_string(), Value::Number(3.0));
+        let error = SetPSBreakpointCmdlet
+            .execute(context, &mut evaluator)
+            .unwrap_err();
+        assert_eq!(error.error_id(), "ScriptRequired,Set-PSBreakpoint");
+
+        let context = CmdletContext::new()
+            .with_parameter("Variable".to_string(), Value::String("$total".into()))
+            .with_parameter("Mode".to_string(), Value::String("Sometimes".into()));
+        let error = SetPSBreakpointCmdlet
+            .execute(context, &mut evaluator)
+            .unwrap_err();
+        assert_eq!(error.error_id(), "InvalidMode,Set-PSBreakpoint");
+        assert!(evaluator.debugger().breakpoints().is_empty());
+    }
+}
@@ -0,0 +1,64 @@
+/// Set-StrictMode cmdlet - turns strict mode on or off for the session
+use pwsh_runtime::{
+    parse_strict_mode, parse_switch, Cmdlet, CmdletContext, ErrorRecord, RuntimeError, Value,
+};
+
+/// Set-StrictMode -Version 1.0, 2.0, 3.0 or Latest turns strict mode on;
+/// -Off turns it off. From 1.0 on, reading a variable that was never set
+/// fails instead of giving $null; from 2.0 on, so does expanding one in a
+/// double-quoted string. Unlike PowerShell's, the mode covers the whole
+/// session rather than the scope that set it.
+pub struct SetStrictModeCmdlet;
+
+impl Cmdlet for SetStrictModeCmdlet {
+    fn name(&self) -> &str {
+        "Set-StrictMode"
+    }
+
+    fn parameters(&self) -> &[&str] {
+        &["Version", "Off"]
+    }
+
+    fn execute(
+        &self,
+        context: CmdletContext,
+        evaluator: &mut pwsh_runtime::Evaluator,
+    ) -> Result<Vec<Value>, RuntimeError> {
+        let off = parse_switch(context.get_parameter("Off"), "Set-StrictMode")?;
+        let version = context
+            .get_parameter("Version")
+            .or_else(|| context.get_argument(0));
+        let version = match (off, version) {
+            (true, None) => 0,
+            (true, Some(_)) => {
+                return Err(ErrorRecord::invalid_argument(
+                    "AmbiguousParameterSet,Set-StrictMode",
+                    "Set-StrictMode takes either -Version or -Off, not both.",
+                )
+                .into())
+            }
+            (false, None) => {
+                return Err(ErrorRecord::invalid_argument(
+                    "MissingVersion,Set-StrictMode",
+                    "Set-StrictMode needs a -Version, or -Off.",
+                )
+                .into())
+            }
+            (false, Some(value)) => {
+                // -Version 2 arrives as a number, -Version 2.0 or Latest as text
+                let text = value.to_string();
+                parse_strict_mode(&text).filter(|v| *v > 0).ok_or_else(|| {
+                    ErrorRecord::invalid_argument(
+                        "InvalidVersion,Set-StrictMode",
+                        format!(
+                            "'{}' is not a strict mode version. Specify 1.0, 2.0, 3.0 or Latest.",
+                            text
+                        ),
+                    )
+                })?
+            }
+        };
+        evaluator.set_strict_mode(version);
+        Ok(vec![])
+    }
+}
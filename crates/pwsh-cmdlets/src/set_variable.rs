@@ -0,0 +1,107 @@
+/// Set-Variable cmdlet - creates or changes a variable, optionally in another scope
+use pwsh_runtime::{
+    parse_property_list, parse_switch, Cmdlet, CmdletContext, ErrorRecord, PropertyMap,
+    RuntimeError, Value,
+};
+
+/// Set-Variable sets each named variable to -Value (or the pipeline input).
+/// It sets the variable in the current scope, or with -Scope in `Global`,
+/// `Local`, `Script` or the scope a number of scopes up, so a helper function
+/// can write into its caller's scope with `-Scope 1`.
+pub struct SetVariableCmdlet;
+
+impl Cmdlet for SetVariableCmdlet {
+    fn name(&self) -> &str {
+        "Set-Variable"
+    }
+
+    fn parameters(&self) -> &[&str] {
+        &["Name", "Value", "Scope", "PassThru"]
+    }
+
+    fn execute(
+        &self,
+        context: CmdletContext,
+        evaluator: &mut pwsh_runtime::Evaluator,
+    ) -> Result<Vec<Value>, RuntimeError> {
+        // Support: Set-Variable count 3
+        let mut positional = context.arguments.iter();
+        let names = match context.get_parameter("Name") {
+            Some(name) => parse_property_list(Some(name)),
+            None => parse_property_list(positional.next()),
+        };
+        if names.is_empty() {
+            return Err(ErrorRecord::invalid_argument(
+                "NameRequired,Set-Variable",
+                "Set-Variable: a variable name is required",
+            )
+            .into());
+        }
+
+        let value = match context.get_parameter("Value").or_else(|| positional.next()) {
+            Some(value) => value.clone(),
+            None => match context.pipeline_input.len() {
+                0 => Value::Null,
+                1 => context.pipeline_input[0].clone(),
+                _ => Value::Array(context.pipeline_input.clone().into()),
+            },
+        };
+        let scope = context
+            .get_parameter("Scope")
+            .map(|scope| scope.to_string())
+            .unwrap_or_else(|| "Local".to_string());
+        let pass_thru = parse_switch(context.get_parameter("PassThru"), "Set-Variable")?;
+
+        let mut output = Vec::new();
+        for name in names {
+            evaluator.set_variable_in_scope(&name, value.clone(), &scope)?;
+            if pass_thru {
+                let mut props = PropertyMap::with_capacity(2);
+                props.insert("Name".to_string(), Value::String(name.into()));
+                props.insert("Value".to_string(), value.clone());
+                output.push(Value::Object(props.into()));
+            }
+        }
+        Ok(output)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_variable_positional_and_pipeline_value() {
+        let mut evaluator = pwsh_runtime::Evaluator::new();
+
+        let context = CmdletContext::new()
+            .with_arguments(vec![Value::String("count".into()), Value::Number(3.0)]);
+        SetVariableCmdlet.execute(context, &mut evaluator).unwrap();
+        assert_eq!(evaluator.get_variable("count"), Some(Value::Number(3.0)));
+
+        let context = CmdletContext::with_input(vec![Value::Number(1.0), Value::Number(2.0)])
+            .with_parameter("Name".to_string(), Value::String("items".into()))
+            .with_parameter("PassThru".to_string(), Value::Boolean(true));
+        let result = SetVariableCmdlet.execute(context, &mut evaluator).unwrap();
+        let items = Value::Array(vec![Value::Number(1.0), Value::Number(2.0)].into());
+        assert_eq!(evaluator.get_variable("items"), Some(items.clone()));
+        assert_eq!(result[0].get_property("Value"), Some(items));
+    }
+
+    #[test]
+    fn test_set_variable_errors() {
+        let mut evaluator = pwsh_runtime::Evaluator::new();
+        let error = SetVariableCmdlet
+            .execute(CmdletContext::new(), &mut evaluator)
+            .unwrap_err();
+        assert_eq!(error.error_id(), "NameRequired,Set-Variable");
+
+        let context = CmdletContext::new()
+            .with_arguments(vec![Value::String("x".into())])
+            .with_parameter("Scope".to_string(), Value::String("Parent".into()));
+        let error = SetVariableCmdlet
+            .execute(context, &mut evaluator)
+            .unwrap_err();
+        assert_eq!(error.error_id(), "ArgumentOutOfRange");
+    }
+}
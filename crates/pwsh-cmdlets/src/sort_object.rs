@@ -1,86 +1,27 @@
 /// Sort-Object cmdlet - sort pipeline objects by value or property
-use pwsh_runtime::{Cmdlet, CmdletContext, RuntimeError, Value};
-
-fn get_parameter_ci<'a>(context: &'a CmdletContext, name: &str) -> Option<&'a Value> {
-    if let Some(v) = context.parameters.get(name) {
-        return Some(v);
-    }
-
-    let name_lower = name.to_lowercase();
-    context
-        .parameters
-        .iter()
-        .find(|(k, _)| k.to_lowercase() == name_lower)
-        .map(|(_, v)| v)
-}
-
-fn parse_switch(value: Option<&Value>) -> Result<bool, RuntimeError> {
-    match value {
-        None => Ok(false),
-        Some(Value::Boolean(b)) => Ok(*b),
-        Some(Value::Number(n)) => Ok(*n != 0.0),
-        Some(Value::String(s)) => {
-            let v = s.trim().to_ascii_lowercase();
-            match v.as_str() {
-                "true" | "t" | "1" | "yes" | "y" => Ok(true),
-                "false" | "f" | "0" | "no" | "n" => Ok(false),
-                _ => Err(RuntimeError::InvalidOperation(format!(
-                    "Invalid boolean value: {}",
-                    s
-                ))),
-            }
-        }
-        Some(other) => Err(RuntimeError::InvalidOperation(format!(
-            "Invalid boolean value: {}",
-            other
-        ))),
-    }
-}
-
-fn parse_property_list(value: Option<&Value>) -> Vec<String> {
-    match value {
-        None => vec![],
-        Some(Value::String(s)) => vec![s.clone()],
-        Some(Value::Array(items)) => items
-            .iter()
-            .filter_map(|v| match v {
-                Value::String(s) => Some(s.clone()),
-                _ => None,
-            })
-            .collect(),
-        Some(other) => vec![other.to_string()],
-    }
-}
-
-fn unroll_to_items(values: Vec<Value>) -> Vec<Value> {
-    let mut out = Vec::new();
-    for v in values {
-        if let Value::Array(items) = v {
-            out.extend(items);
-        } else {
-            out.push(v);
-        }
-    }
-    out
-}
+use crate::parallel::{parallel_switch, run_in_parallel};
+use pwsh_runtime::{
+    parse_property_list, parse_switch, unroll_to_items, Cmdlet, CmdletContext, RuntimeError, Value,
+};
+use std::cmp::Ordering;
 
 fn cmp_values(a: &Value, b: &Value) -> std::cmp::Ordering {
     // Nulls sort first
     if matches!(a, Value::Null) && matches!(b, Value::Null) {
-        return std::cmp::Ordering::Equal;
+        return Ordering::Equal;
     }
     if matches!(a, Value::Null) {
-        return std::cmp::Ordering::Less;
+        return Ordering::Less;
     }
     if matches!(b, Value::Null) {
-        return std::cmp::Ordering::Greater;
+        return Ordering::Greater;
     }
 
     // Prefer numeric comparison when both sides can be treated as numbers.
     let an = a.to_number();
     let bn = b.to_number();
     if let (Some(an), Some(bn)) = (an, bn) {
-        return an.partial_cmp(&bn).unwrap_or(std::cmp::Ordering::Equal);
+        return an.partial_cmp(&bn).unwrap_or(Ordering::Equal);
     }
 
     // Fall back to case-insensitive string comparison (PowerShell default).
@@ -89,6 +30,23 @@ fn cmp_values(a: &Value, b: &Value) -> std::cmp::Ordering {
         .cmp(&b.to_string().to_ascii_lowercase())
 }
 
+/// Stable sort, on all cores when `parallel`; both keep equal items in input
+/// order, so the result is the same either way
+fn sort_values<F>(items: &mut [Value], parallel: bool, compare: F)
+where
+    F: Fn(&Value, &Value) -> Ordering + Sync,
+{
+    #[cfg(feature = "parallel")]
+    if parallel {
+        use rayon::slice::ParallelSliceMut;
+        items.par_sort_by(compare);
+        return;
+    }
+    #[cfg(not(feature = "parallel"))]
+    let _ = parallel;
+    items.sort_by(compare);
+}
+
 /// Sort-Object sorts values/objects by one or more properties.
 pub struct SortObjectCmdlet;
 
@@ -97,17 +55,24 @@ impl Cmdlet for SortObjectCmdlet {
         "Sort-Object"
     }
 
+    fn parameters(&self) -> &[&str] {
+        &["Property", "Descending", "Parallel"]
+    }
+
     fn execute(
         &self,
         context: CmdletContext,
         _evaluator: &mut pwsh_runtime::Evaluator,
     ) -> Result<Vec<Value>, RuntimeError> {
-        let descending = parse_switch(get_parameter_ci(&context, "Descending"))?;
+        let descending = parse_switch(context.get_parameter("Descending"), "Sort-Object")?;
+        // -Parallel forces a parallel sort and -Parallel:$false turns it off;
+        // without it only large inputs are sorted in parallel, and only with more than one core
+        let parallel = parallel_switch(&context, "Sort-Object")?;
 
         let pipeline_has_input = !context.pipeline_input.is_empty();
 
         // Determine property list *before* moving vectors out of context.
-        let mut properties = parse_property_list(get_parameter_ci(&context, "Property"));
+        let mut properties = parse_property_list(context.get_parameter("Property"));
 
         if pipeline_has_input && properties.is_empty() {
             // Support: $items | Sort-Object Name, CPU
@@ -116,7 +81,7 @@ impl Cmdlet for SortObjectCmdlet {
                 .arguments
                 .iter()
                 .filter_map(|v| match v {
-                    Value::String(s) => Some(s.clone()),
+                    Value::String(s) => Some(s.to_string()),
                     _ => None,
                 })
                 .collect::<Vec<_>>();
@@ -135,9 +100,11 @@ impl Cmdlet for SortObjectCmdlet {
             unroll_to_items(context.arguments)
         };
 
+        let parallel = run_in_parallel(parallel, input.len());
+
         // Sort in-place for performance.
         if properties.is_empty() {
-            input.sort_by(|a, b| {
+            sort_values(&mut input, parallel, |a, b| {
                 let ord = cmp_values(a, b);
                 if descending {
                     ord.reverse()
@@ -149,16 +116,16 @@ impl Cmdlet for SortObjectCmdlet {
             return Ok(input);
         }
 
-        input.sort_by(|a, b| {
+        sort_values(&mut input, parallel, |a, b| {
             for prop in &properties {
                 let av = a.get_property(prop).unwrap_or(Value::Null);
                 let bv = b.get_property(prop).unwrap_or(Value::Null);
                 let ord = cmp_values(&av, &bv);
-                if ord != std::cmp::Ordering::Equal {
+                if ord != Ordering::Equal {
                     return if descending { ord.reverse() } else { ord };
                 }
             }
-            std::cmp::Ordering::Equal
+            Ordering::Equal
         });
 
         Ok(input)
@@ -168,7 +135,8 @@ impl Cmdlet for SortObjectCmdlet {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::HashMap;
+    use crate::parallel::PARALLEL_THRESHOLD;
+    use pwsh_runtime::PropertyMap;
 
     #[test]
     fn test_sort_object_numbers_ascending() {
@@ -187,9 +155,9 @@ mod tests {
     fn test_sort_object_strings_case_insensitive() {
         let cmdlet = SortObjectCmdlet;
         let input = vec![
-            Value::String("b".to_string()),
-            Value::String("A".to_string()),
-            Value::String("c".to_string()),
+            Value::String("b".into()),
+            Value::String("A".into()),
+            Value::String("c".into()),
         ];
         let context = CmdletContext::with_input(input);
         let mut evaluator = pwsh_runtime::Evaluator::new();
@@ -197,9 +165,9 @@ mod tests {
         assert_eq!(
             result,
             vec![
-                Value::String("A".to_string()),
-                Value::String("b".to_string()),
-                Value::String("c".to_string()),
+                Value::String("A".into()),
+                Value::String("b".into()),
+                Value::String("c".into()),
             ]
         );
     }
@@ -208,22 +176,24 @@ mod tests {
     fn test_sort_object_by_property() {
         let cmdlet = SortObjectCmdlet;
 
-        let mut o1 = HashMap::new();
-        o1.insert("Name".to_string(), Value::String("b".to_string()));
+        let mut o1 = PropertyMap::new();
+        o1.insert("Name".to_string(), Value::String("b".into()));
         o1.insert("CPU".to_string(), Value::Number(2.0));
 
-        let mut o2 = HashMap::new();
-        o2.insert("Name".to_string(), Value::String("a".to_string()));
+        let mut o2 = PropertyMap::new();
+        o2.insert("Name".to_string(), Value::String("a".into()));
         o2.insert("CPU".to_string(), Value::Number(1.0));
 
-        let context = CmdletContext::with_input(vec![Value::Object(o1), Value::Object(o2)])
-            .with_parameter("Property".to_string(), Value::String("CPU".to_string()));
+        let context =
+            CmdletContext::with_input(vec![Value::Object(o1.into()), Value::Object(o2.into())])
+                .with_parameter("Property".to_string(), Value::String("CPU".into()));
 
         let mut evaluator = pwsh_runtime::Evaluator::new();
         let result = cmdlet.execute(context, &mut evaluator).unwrap();
 
         if let Value::Object(props) = &result[0] {
-            assert_eq!(props.get("Name"), Some(&Value::String("a".to_string())));
+            let props = props.read();
+            assert_eq!(props.get("Name"), Some(&Value::String("a".into())));
         } else {
             panic!("Expected object");
         }
@@ -247,29 +217,26 @@ mod tests {
     fn test_sort_object_multiple_properties() {
         let cmdlet = SortObjectCmdlet;
 
-        let mut o1 = HashMap::new();
-        o1.insert("Name".to_string(), Value::String("b".to_string()));
+        let mut o1 = PropertyMap::new();
+        o1.insert("Name".to_string(), Value::String("b".into()));
         o1.insert("CPU".to_string(), Value::Number(1.0));
 
-        let mut o2 = HashMap::new();
-        o2.insert("Name".to_string(), Value::String("a".to_string()));
+        let mut o2 = PropertyMap::new();
+        o2.insert("Name".to_string(), Value::String("a".into()));
         o2.insert("CPU".to_string(), Value::Number(1.0));
 
-        let mut o3 = HashMap::new();
-        o3.insert("Name".to_string(), Value::String("c".to_string()));
+        let mut o3 = PropertyMap::new();
+        o3.insert("Name".to_string(), Value::String("c".into()));
         o3.insert("CPU".to_string(), Value::Number(0.0));
 
         let context = CmdletContext::with_input(vec![
-            Value::Object(o1),
-            Value::Object(o2),
-            Value::Object(o3),
+            Value::Object(o1.into()),
+            Value::Object(o2.into()),
+            Value::Object(o3.into()),
         ])
         .with_parameter(
             "Property".to_string(),
-            Value::Array(vec![
-                Value::String("CPU".to_string()),
-                Value::String("Name".to_string()),
-            ]),
+            Value::Array(vec![Value::String("CPU".into()), Value::String("Name".into())].into()),
         );
 
         let mut evaluator = pwsh_runtime::Evaluator::new();
@@ -286,4 +253,28 @@ mod tests {
             vec!["c".to_string(), "a".to_string(), "b".to_string()]
         );
     }
+
+    #[test]
+    fn test_sort_object_parallel_matches_sequential() {
+        // Many equal keys, so a sort that isn't stable would reorder the ids
+        let input: Vec<Value> = (0..PARALLEL_THRESHOLD + 500)
+            .map(|i| {
+                let mut props = PropertyMap::new();
+                props.insert("Key", Value::Number(((i * 7919) % 13) as f64));
+                props.insert("Id", Value::Number(i as f64));
+                Value::Object(props.into())
+            })
+            .collect();
+
+        let sort = |parallel: bool| {
+            let context = CmdletContext::with_input(input.clone())
+                .with_parameter("Property".to_string(), Value::String("Key".into()))
+                .with_parameter("Parallel".to_string(), Value::Boolean(parallel));
+            let mut evaluator = pwsh_runtime::Evaluator::new();
+            SortObjectCmdlet.execute(context, &mut evaluator).unwrap()
+        };
+        let parallel = sort(true);
+        assert_eq!(parallel, sort(false));
+        assert_eq!(parallel.len(), input.len());
+    }
 }
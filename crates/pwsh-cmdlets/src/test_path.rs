@@ -1,21 +1,7 @@
 /// Test-Path cmdlet - checks if a file system path exists
-use pwsh_runtime::{Cmdlet, CmdletContext, RuntimeError, Value};
+use pwsh_runtime::{Capability, Cmdlet, CmdletContext, ErrorRecord, RuntimeError, Value};
 use std::path::PathBuf;
 
-fn get_parameter_ci<'a>(context: &'a CmdletContext, name: &str) -> Option<&'a Value> {
-    // Try exact match first
-    if let Some(v) = context.parameters.get(name) {
-        return Some(v);
-    }
-
-    let name_lower = name.to_lowercase();
-    context
-        .parameters
-        .iter()
-        .find(|(k, _)| k.to_lowercase() == name_lower)
-        .map(|(_, v)| v)
-}
-
 fn resolve_path(path: &str) -> Result<PathBuf, RuntimeError> {
     let p = PathBuf::from(path);
     if p.is_absolute() {
@@ -23,20 +9,26 @@ fn resolve_path(path: &str) -> Result<PathBuf, RuntimeError> {
     }
 
     let cwd = std::env::current_dir().map_err(|e| {
-        RuntimeError::InvalidOperation(format!("Failed to get current directory: {}", e))
+        ErrorRecord::file_system(
+            &e,
+            "Test-Path",
+            ".",
+            format!("Failed to get current directory: {}", e),
+        )
     })?;
     Ok(cwd.join(p))
 }
 
 fn extract_path(context: &CmdletContext) -> Result<PathBuf, RuntimeError> {
-    if let Some(Value::String(p)) = get_parameter_ci(context, "Path") {
+    if let Some(Value::String(p)) = context.get_parameter("Path") {
         resolve_path(p)
     } else if let Some(Value::String(p)) = context.get_argument(0) {
         resolve_path(p)
     } else {
-        Err(RuntimeError::InvalidOperation(
-            "Test-Path requires a path".to_string(),
-        ))
+        Err(
+            ErrorRecord::invalid_argument("PathRequired,Test-Path", "Test-Path requires a path")
+                .into(),
+        )
     }
 }
 
@@ -48,6 +40,14 @@ impl Cmdlet for TestPathCmdlet {
         "Test-Path"
     }
 
+    fn parameters(&self) -> &[&str] {
+        &["Path"]
+    }
+
+    fn capabilities(&self) -> &[Capability] {
+        &[Capability::FileSystem]
+    }
+
     fn execute(
         &self,
         context: CmdletContext,
@@ -59,11 +59,13 @@ impl Cmdlet for TestPathCmdlet {
             Ok(_) => true,
             Err(e) if e.kind() == std::io::ErrorKind::NotFound => false,
             Err(e) => {
-                return Err(RuntimeError::InvalidOperation(format!(
-                    "Failed to check path '{}': {}",
+                return Err(ErrorRecord::file_system(
+                    &e,
+                    "Test-Path",
                     path.display(),
-                    e
-                )))
+                    format!("Failed to check path '{}': {}", path.display(), e),
+                )
+                .into())
             }
         };
 
@@ -86,7 +88,7 @@ mod tests {
         let cmdlet = TestPathCmdlet;
         let context = CmdletContext::new().with_parameter(
             "Path".to_string(),
-            Value::String(file_path.to_string_lossy().to_string()),
+            Value::String(file_path.to_string_lossy().into()),
         );
         let mut evaluator = pwsh_runtime::Evaluator::new();
         let result = cmdlet.execute(context, &mut evaluator).unwrap();
@@ -100,7 +102,7 @@ mod tests {
 
         let cmdlet = TestPathCmdlet;
         let context = CmdletContext::new()
-            .with_arguments(vec![Value::String(file_path.to_string_lossy().to_string())]);
+            .with_arguments(vec![Value::String(file_path.to_string_lossy().into())]);
         let mut evaluator = pwsh_runtime::Evaluator::new();
         let result = cmdlet.execute(context, &mut evaluator).unwrap();
         assert_eq!(result, vec![Value::Boolean(false)]);
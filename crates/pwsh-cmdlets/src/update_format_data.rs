@@ -0,0 +1,197 @@
+/// Update-FormatData cmdlet - adds views for objects' types from view files or hashtables
+use pwsh_runtime::{
+    views_from_json, Capability, Cmdlet, CmdletContext, ErrorCategory, ErrorRecord, RuntimeError,
+    Value, View,
+};
+
+/// A view file or view that can't be used
+fn invalid_data(message: impl Into<String>) -> RuntimeError {
+    ErrorRecord::new(
+        ErrorCategory::InvalidData,
+        "InvalidFormatData,Update-FormatData",
+        message,
+    )
+    .into()
+}
+
+fn unroll_to_items(value: Option<&Value>) -> Vec<Value> {
+    match value {
+        None | Some(Value::Null) => Vec::new(),
+        Some(Value::Array(items)) => items.clone().unwrap_or_clone(),
+        Some(other) => vec![other.clone()],
+    }
+}
+
+/// The views in each of the files at `paths`
+fn read_view_files(paths: Option<&Value>) -> Result<Vec<View>, RuntimeError> {
+    let mut views = Vec::new();
+    for path in unroll_to_items(paths) {
+        let path = path.to_string();
+        let text = std::fs::read_to_string(&path).map_err(|e| {
+            RuntimeError::from(ErrorRecord::file_system(
+                &e,
+                "Update-FormatData",
+                &path,
+                format!("Cannot read the view file '{}': {}", path, e),
+            ))
+        })?;
+        views.extend(views_from_json(&text).map_err(|e| invalid_data(format!("{}: {}", path, e)))?);
+    }
+    Ok(views)
+}
+
+/// Update-FormatData adds views the console and Format-Table show objects
+/// of a type in. -AppendPath and -PrependPath read JSON view files
+/// (`{"Views": [...]}`); appended views only apply to types nothing else
+/// has a view for, while prepended ones win over the built-in views. -View
+/// takes views as hashtables, with the settings of a view file's views,
+/// and they win too. A mistake in any of them adds none.
+pub struct UpdateFormatDataCmdlet;
+
+impl Cmdlet for UpdateFormatDataCmdlet {
+    fn name(&self) -> &str {
+        "Update-FormatData"
+    }
+
+    fn parameters(&self) -> &[&str] {
+        &["AppendPath", "PrependPath", "View"]
+    }
+
+    fn capabilities(&self) -> &[Capability] {
+        &[Capability::FileSystem]
+    }
+
+    fn execute(
+        &self,
+        context: CmdletContext,
+        evaluator: &mut pwsh_runtime::Evaluator,
+    ) -> Result<Vec<Value>, RuntimeError> {
+        let appended = read_view_files(
+            context
+                .get_parameter("AppendPath")
+                .or_else(|| context.get_argument(0)),
+        )?;
+        let prepended = read_view_files(context.get_parameter("PrependPath"))?;
+        let specs = unroll_to_items(context.get_parameter("View"))
+            .into_iter()
+            .map(|spec| match spec {
+                Value::Object(_) => {
+                    View::from_json(&spec.to_json()).map_err(|e| invalid_data(e.to_string()))
+                }
+                other => Err(invalid_data(format!(
+                    "A view is a hashtable with a TypeName and Table or List, not {}",
+                    other
+                ))),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let formats = evaluator.formats_mut();
+        for view in appended {
+            formats.append(view);
+        }
+        for view in prepended.into_iter().chain(specs) {
+            formats.register(view);
+        }
+        Ok(Vec::new())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pwsh_runtime::PropertyMap;
+
+    fn tagged(type_name: &str) -> Value {
+        let mut props = PropertyMap::from([("Name", Value::String("web".into()))]);
+        props.insert_type_name(type_name);
+        Value::Object(props.into())
+    }
+
+    #[test]
+    fn test_update_format_data_reads_view_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("views.json");
+        std::fs::write(
+            &path,
+            r#"{"Views": [{"TypeName": "Demo.Server", "List": ["Name"]}]}"#,
+        )
+        .unwrap();
+        let mut evaluator = pwsh_runtime::Evaluator::new();
+        let context = CmdletContext::new().with_parameter(
+            "AppendPath".to_string(),
+            Value::String(path.to_string_lossy().into()),
+        );
+        UpdateFormatDataCmdlet
+            .execute(context, &mut evaluator)
+            .unwrap();
+        assert_eq!(
+            evaluator.format_output(&[tagged("Demo.Server")]),
+            vec!["", "Name : web", ""]
+        );
+    }
+
+    #[test]
+    fn test_appended_views_do_not_replace_built_in_ones() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("views.json");
+        std::fs::write(
+            &path,
+            r#"{"Views": [{"TypeName": "System.Diagnostics.Process", "List": ["Name"]}]}"#,
+        )
+        .unwrap();
+        let mut evaluator = pwsh_runtime::Evaluator::new();
+        let path = Value::String(path.to_string_lossy().into());
+        let context = CmdletContext::new().with_parameter("AppendPath".to_string(), path.clone());
+        UpdateFormatDataCmdlet
+            .execute(context, &mut evaluator)
+            .unwrap();
+        let process = tagged("System.Diagnostics.Process");
+        assert!(evaluator.format_output(std::slice::from_ref(&process))[1].ends_with("ProcessName"));
+
+        let context = CmdletContext::new().with_parameter("PrependPath".to_string(), path);
+        UpdateFormatDataCmdlet
+            .execute(context, &mut evaluator)
+            .unwrap();
+        assert_eq!(evaluator.format_output(&[process])[1], "Name : web");
+    }
+
+    #[test]
+    fn test_update_format_data_errors() {
+        let mut evaluator = pwsh_runtime::Evaluator::new();
+        let error_id = |context: CmdletContext, evaluator: &mut pwsh_runtime::Evaluator| {
+            UpdateFormatDataCmdlet
+                .execute(context, evaluator)
+                .unwrap_err()
+                .error_id()
+                .to_string()
+        };
+
+        let context = CmdletContext::new().with_parameter(
+            "AppendPath".to_string(),
+            Value::String("/no/such/views.json".into()),
+        );
+        assert_eq!(
+            error_id(context, &mut evaluator),
+            "PathNotFound,Update-FormatData"
+        );
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("views.json");
+        std::fs::write(&path, r#"{"Views": [{"TypeName": "T"}]}"#).unwrap();
+        let context = CmdletContext::new().with_parameter(
+            "AppendPath".to_string(),
+            Value::String(path.to_string_lossy().into()),
+        );
+        assert_eq!(
+            error_id(context, &mut evaluator),
+            "InvalidFormatData,Update-FormatData"
+        );
+
+        let context = CmdletContext::new()
+            .with_parameter("View".to_string(), Value::String("Demo.Server".into()));
+        assert_eq!(
+            error_id(context, &mut evaluator),
+            "InvalidFormatData,Update-FormatData"
+        );
+    }
+}
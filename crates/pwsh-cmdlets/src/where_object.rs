@@ -9,6 +9,10 @@ impl Cmdlet for WhereObjectCmdlet {
         "Where-Object"
     }
 
+    fn parameters(&self) -> &[&str] {
+        &["Property"]
+    }
+
     fn execute(
         &self,
         context: CmdletContext,
@@ -56,7 +60,7 @@ impl Cmdlet for WhereObjectCmdlet {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::HashMap;
+    use pwsh_runtime::PropertyMap;
 
     #[test]
     fn test_where_object_no_filter() {
@@ -73,32 +77,32 @@ mod tests {
         let cmdlet = WhereObjectCmdlet;
 
         // Create objects with properties
-        let mut obj1 = HashMap::new();
+        let mut obj1 = PropertyMap::new();
         obj1.insert("Active".to_string(), Value::Boolean(true));
-        obj1.insert("Name".to_string(), Value::String("Object1".to_string()));
+        obj1.insert("Name".to_string(), Value::String("Object1".into()));
 
-        let mut obj2 = HashMap::new();
+        let mut obj2 = PropertyMap::new();
         obj2.insert("Active".to_string(), Value::Boolean(false));
-        obj2.insert("Name".to_string(), Value::String("Object2".to_string()));
+        obj2.insert("Name".to_string(), Value::String("Object2".into()));
 
-        let mut obj3 = HashMap::new();
+        let mut obj3 = PropertyMap::new();
         obj3.insert("Active".to_string(), Value::Boolean(true));
-        obj3.insert("Name".to_string(), Value::String("Object3".to_string()));
+        obj3.insert("Name".to_string(), Value::String("Object3".into()));
 
         let input = vec![
-            Value::Object(obj1.clone()),
-            Value::Object(obj2),
-            Value::Object(obj3.clone()),
+            Value::Object(obj1.clone().into()),
+            Value::Object(obj2.into()),
+            Value::Object(obj3.clone().into()),
         ];
 
         let context = CmdletContext::with_input(input)
-            .with_parameter("Property".to_string(), Value::String("Active".to_string()));
+            .with_parameter("Property".to_string(), Value::String("Active".into()));
 
         let mut evaluator = pwsh_runtime::Evaluator::new();
         let result = cmdlet.execute(context, &mut evaluator).unwrap();
         assert_eq!(result.len(), 2);
-        assert_eq!(result[0], Value::Object(obj1));
-        assert_eq!(result[1], Value::Object(obj3));
+        assert_eq!(result[0], Value::Object(obj1.into()));
+        assert_eq!(result[1], Value::Object(obj3.into()));
     }
 
     #[test]
@@ -125,6 +129,7 @@ mod tests {
         // Should return [3, 4, 5]
         match result {
             Value::Array(values) => {
+                let values = values.read();
                 assert_eq!(values.len(), 3);
                 assert_eq!(values[0], Value::Number(3.0));
                 assert_eq!(values[1], Value::Number(4.0));
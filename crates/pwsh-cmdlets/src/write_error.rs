@@ -0,0 +1,105 @@
+/// Write-Error cmdlet - writes a non-terminating error
+use pwsh_runtime::{Cmdlet, CmdletContext, ErrorCategory, ErrorRecord, RuntimeError, Value};
+
+/// The FullyQualifiedErrorId of an error written without -ErrorId
+const DEFAULT_ERROR_ID: &str = "Microsoft.PowerShell.Commands.WriteErrorException";
+
+/// -Category: an ErrorCategory name, ignoring case
+fn parse_category(value: Option<&Value>) -> Result<ErrorCategory, RuntimeError> {
+    let Some(value) = value else {
+        return Ok(ErrorCategory::NotSpecified);
+    };
+    let name = value.to_string();
+    ErrorCategory::from_name(&name).ok_or_else(|| {
+        ErrorRecord::new(
+            ErrorCategory::InvalidArgument,
+            "InvalidCategory,Write-Error",
+            format!("Write-Error: '{}' is not an error category", name),
+        )
+        .into()
+    })
+}
+
+/// Write-Error reports each message as a non-terminating error, which goes
+/// to the error stream and $error, or as -ErrorAction and
+/// $ErrorActionPreference say: with Stop it ends the script, or the try
+/// block it is in.
+pub struct WriteErrorCmdlet;
+
+impl Cmdlet for WriteErrorCmdlet {
+    fn name(&self) -> &str {
+        "Write-Error"
+    }
+
+    fn parameters(&self) -> &[&str] {
+        &["Message", "Category", "ErrorId", "TargetObject"]
+    }
+
+    fn execute(
+        &self,
+        context: CmdletContext,
+        evaluator: &mut pwsh_runtime::Evaluator,
+    ) -> Result<Vec<Value>, RuntimeError> {
+        let category = parse_category(context.get_parameter("Category"))?;
+        let error_id = context
+            .get_parameter("ErrorId")
+            .map_or_else(|| DEFAULT_ERROR_ID.to_string(), Value::to_string);
+        let target = context.get_parameter("TargetObject");
+
+        // Message: -Message, the first positional argument, or each pipeline item
+        let messages = if let Some(message) = context.get_parameter("Message") {
+            vec![message.clone()]
+        } else if let Some(message) = context.get_argument(0) {
+            vec![message.clone()]
+        } else {
+            context.pipeline_input.clone()
+        };
+
+        for message in messages {
+            let mut record = ErrorRecord::new(category, &error_id, message.to_string());
+            if let Some(target) = target {
+                record = record.with_target(target);
+            }
+            evaluator.write_error(record.into(), context.error_action)?;
+        }
+        Ok(vec![])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pwsh_runtime::ActionPreference;
+
+    #[test]
+    fn test_write_error_stops_with_the_record() {
+        let mut evaluator = pwsh_runtime::Evaluator::new();
+        let context = CmdletContext::new()
+            .with_arguments(vec![Value::String("disk full".into())])
+            .with_parameter("Category".to_string(), Value::String("writeerror".into()))
+            .with_parameter("ErrorId".to_string(), Value::String("DiskFull".into()))
+            .with_error_action(ActionPreference::Stop);
+        let error = WriteErrorCmdlet
+            .execute(context, &mut evaluator)
+            .unwrap_err();
+        assert_eq!(error.to_string(), "disk full");
+        assert_eq!(error.category(), ErrorCategory::WriteError);
+        assert_eq!(error.error_id(), "DiskFull");
+    }
+
+    #[test]
+    fn test_write_error_continues_by_default() {
+        let mut evaluator = pwsh_runtime::Evaluator::new();
+        let context = CmdletContext::with_input(vec![Value::String("a".into())]);
+        let result = WriteErrorCmdlet.execute(context, &mut evaluator).unwrap();
+        assert!(result.is_empty());
+
+        let context = CmdletContext::new()
+            .with_arguments(vec![Value::String("b".into())])
+            .with_parameter("Category".to_string(), Value::String("Oops".into()));
+        let error = WriteErrorCmdlet
+            .execute(context, &mut evaluator)
+            .unwrap_err();
+        assert_eq!(error.error_id(), "InvalidCategory,Write-Error");
+    }
+}
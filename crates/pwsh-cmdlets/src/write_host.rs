@@ -0,0 +1,89 @@
+/// Write-Host cmdlet - writes text to the host instead of the pipeline
+use pwsh_runtime::{
+    console_color, styled, Cmdlet, CmdletContext, ErrorRecord, RuntimeError, Value,
+};
+
+/// The sequence for the console color in parameter `name`, if given
+fn color(context: &CmdletContext, name: &str, background: bool) -> Result<String, RuntimeError> {
+    match context.get_parameter(name) {
+        None | Some(Value::Null) => Ok(String::new()),
+        Some(value) => {
+            let color = value.to_string();
+            console_color(&color, background).ok_or_else(|| {
+                ErrorRecord::invalid_argument(
+                    "InvalidColor,Write-Host",
+                    format!(
+                        "Cannot bind parameter '{}': '{}' is not a console color, such as Red, DarkGreen or Gray",
+                        name, color
+                    ),
+                ).into()
+            })
+        }
+    }
+}
+
+/// Write-Host cmdlet displays values without sending them down the pipeline.
+/// -ForegroundColor and -BackgroundColor take console colors; their styling,
+/// and any in the text (`$PSStyle.Foreground.Red`), is taken out when the
+/// output rendering is PlainText or the host doesn't show it.
+pub struct WriteHostCmdlet;
+
+impl Cmdlet for WriteHostCmdlet {
+    fn name(&self) -> &str {
+        "Write-Host"
+    }
+
+    fn parameters(&self) -> &[&str] {
+        &[
+            "Object",
+            "NoNewline",
+            "Separator",
+            "ForegroundColor",
+            "BackgroundColor",
+        ]
+    }
+
+    fn execute(
+        &self,
+        context: CmdletContext,
+        evaluator: &mut pwsh_runtime::Evaluator,
+    ) -> Result<Vec<Value>, RuntimeError> {
+        // Object: -Object, the positional arguments, or the pipeline input
+        let objects = if let Some(object) = context.get_parameter("Object") {
+            vec![object.clone()]
+        } else if !context.arguments.is_empty() {
+            context.arguments.clone()
+        } else {
+            context.pipeline_input.clone()
+        };
+
+        let separator = context
+            .get_parameter("Separator")
+            .map(|v| v.to_string())
+            .unwrap_or_else(|| " ".to_string());
+        // Arrays are written item by item
+        let text = objects
+            .into_iter()
+            .flat_map(|value| match value {
+                Value::Array(items) => items.unwrap_or_clone(),
+                other => vec![other],
+            })
+            .map(|value| value.to_string())
+            .collect::<Vec<_>>()
+            .join(&separator);
+        let style =
+            color(&context, "ForegroundColor", false)? + &color(&context, "BackgroundColor", true)?;
+        let text = evaluator.host_text(&styled(&style, &text))?;
+
+        let host = evaluator.host_mut();
+        if context
+            .get_parameter("NoNewline")
+            .is_some_and(|v| v.to_bool())
+        {
+            host.write(&text);
+        } else {
+            host.write_line(&text);
+        }
+        Ok(vec![])
+    }
+}
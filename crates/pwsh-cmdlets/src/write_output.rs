@@ -1,7 +1,9 @@
 /// Write-Output cmdlet - outputs values to the pipeline
 use pwsh_runtime::{Cmdlet, CmdletContext, RuntimeError, Value};
 
-/// Write-Output cmdlet sends objects to the output stream
+/// Write-Output cmdlet sends objects to the output stream. Arrays are
+/// written item by item unless -NoEnumerate is given, which writes each
+/// argument as a single object.
 pub struct WriteOutputCmdlet;
 
 impl Cmdlet for WriteOutputCmdlet {
@@ -9,11 +11,34 @@ impl Cmdlet for WriteOutputCmdlet {
         "Write-Output"
     }
 
+    fn parameters(&self) -> &[&str] {
+        &["NoEnumerate"]
+    }
+
     fn execute(
         &self,
-        context: CmdletContext,
+        mut context: CmdletContext,
         _evaluator: &mut pwsh_runtime::Evaluator,
     ) -> Result<Vec<Value>, RuntimeError> {
+        let no_enumerate = match context.get_parameter("NoEnumerate").cloned() {
+            None | Some(Value::Null) => false,
+            Some(Value::Boolean(b)) => b,
+            // The parser hands a switch the word after it, so in
+            // `Write-Output -NoEnumerate @(1, 2)` the array is its "value"
+            Some(value) => {
+                context.arguments.insert(0, value);
+                true
+            }
+        };
+        if no_enumerate {
+            // Pipeline input already arrives one item at a time
+            return Ok(if context.pipeline_input.is_empty() {
+                context.arguments
+            } else {
+                context.pipeline_input
+            });
+        }
+
         let mut output = Vec::new();
 
         // If there's pipeline input, output it
@@ -21,7 +46,7 @@ impl Cmdlet for WriteOutputCmdlet {
             for value in context.pipeline_input {
                 // Unroll arrays to the pipeline
                 if let Value::Array(items) = value {
-                    output.extend(items);
+                    output.extend(items.unwrap_or_clone());
                 } else {
                     output.push(value);
                 }
@@ -34,7 +59,7 @@ impl Cmdlet for WriteOutputCmdlet {
             for value in context.arguments {
                 // Unroll arrays to the pipeline
                 if let Value::Array(items) = value {
-                    output.extend(items);
+                    output.extend(items.unwrap_or_clone());
                 } else {
                     output.push(value);
                 }
@@ -63,7 +88,7 @@ mod tests {
     #[test]
     fn test_write_output_with_pipeline_input() {
         let cmdlet = WriteOutputCmdlet;
-        let input = vec![Value::String("Hello".to_string())];
+        let input = vec![Value::String("Hello".into())];
         let context = CmdletContext::with_input(input.clone());
         let mut evaluator = pwsh_runtime::Evaluator::new();
         let result = cmdlet.execute(context, &mut evaluator).unwrap();
@@ -79,4 +104,30 @@ mod tests {
         let result = cmdlet.execute(context, &mut evaluator).unwrap();
         assert_eq!(result, values);
     }
+
+    #[test]
+    fn test_write_output_enumerates_arrays() {
+        let array = Value::Array(vec![Value::Number(1.0), Value::Number(2.0)].into());
+        let context = CmdletContext::new().with_arguments(vec![array]);
+        let mut evaluator = pwsh_runtime::Evaluator::new();
+        let result = WriteOutputCmdlet.execute(context, &mut evaluator).unwrap();
+        assert_eq!(result, vec![Value::Number(1.0), Value::Number(2.0)]);
+    }
+
+    #[test]
+    fn test_write_output_no_enumerate_keeps_arrays_whole() {
+        let array = Value::Array(vec![Value::Number(1.0), Value::Number(2.0)].into());
+        let mut evaluator = pwsh_runtime::Evaluator::new();
+
+        let context = CmdletContext::new()
+            .with_arguments(vec![array.clone()])
+            .with_parameter("NoEnumerate".to_string(), Value::Boolean(true));
+        let result = WriteOutputCmdlet.execute(context, &mut evaluator).unwrap();
+        assert_eq!(result, vec![array.clone()]);
+
+        // `-NoEnumerate @(1, 2)` arrives as the switch's value
+        let context = CmdletContext::new().with_parameter("NoEnumerate".to_string(), array.clone());
+        let result = WriteOutputCmdlet.execute(context, &mut evaluator).unwrap();
+        assert_eq!(result, vec![array]);
+    }
 }
@@ -0,0 +1,62 @@
+/// Write-Progress cmdlet - reports the progress of a long-running operation
+use pwsh_runtime::{Cmdlet, CmdletContext, ErrorRecord, Progress, RuntimeError, Value};
+
+/// Write-Progress cmdlet passes a progress update to the host
+pub struct WriteProgressCmdlet;
+
+impl Cmdlet for WriteProgressCmdlet {
+    fn name(&self) -> &str {
+        "Write-Progress"
+    }
+
+    fn parameters(&self) -> &[&str] {
+        &["Activity", "Status", "PercentComplete", "Completed"]
+    }
+
+    fn execute(
+        &self,
+        context: CmdletContext,
+        evaluator: &mut pwsh_runtime::Evaluator,
+    ) -> Result<Vec<Value>, RuntimeError> {
+        // Activity and Status: named, or the first and second positional arguments
+        let activity = context
+            .get_parameter("Activity")
+            .or_else(|| context.get_argument(0))
+            .map(|v| v.to_string())
+            .ok_or_else(|| {
+                ErrorRecord::invalid_argument(
+                    "ActivityRequired,Write-Progress",
+                    "Write-Progress requires an activity",
+                )
+            })?;
+        let status = context
+            .get_parameter("Status")
+            .or_else(|| context.get_argument(1))
+            .map(|v| v.to_string())
+            .unwrap_or_else(|| "Processing".to_string());
+
+        let percent_complete = match context.get_parameter("PercentComplete") {
+            Some(value) => {
+                let percent = value.to_number().ok_or_else(|| {
+                    ErrorRecord::invalid_argument(
+                        "InvalidPercentComplete,Write-Progress",
+                        format!("Cannot convert '{}' to a percentage", value),
+                    )
+                })?;
+                Some(percent.clamp(0.0, 100.0) as u32)
+            }
+            None => None,
+        };
+        let completed = context
+            .get_parameter("Completed")
+            .is_some_and(|v| v.to_bool());
+
+        evaluator.host_mut().write_progress(&Progress {
+            activity,
+            status,
+            percent_complete,
+            completed,
+        });
+        Ok(vec![])
+    }
+}
@@ -0,0 +1,37 @@
+/// Write-Warning cmdlet - writes a warning message to the host
+use pwsh_runtime::{Cmdlet, CmdletContext, RuntimeError, Value};
+
+/// Write-Warning cmdlet shows a warning for each message
+pub struct WriteWarningCmdlet;
+
+impl Cmdlet for WriteWarningCmdlet {
+    fn name(&self) -> &str {
+        "Write-Warning"
+    }
+
+    fn parameters(&self) -> &[&str] {
+        &["Message"]
+    }
+
+    fn execute(
+        &self,
+        context: CmdletContext,
+        evaluator: &mut pwsh_runtime::Evaluator,
+    ) -> Result<Vec<Value>, RuntimeError> {
+        // Message: -Message, the first positional argument, or each pipeline item
+        let messages = if let Some(message) = context.get_parameter("Message") {
+            vec![message.clone()]
+        } else if let Some(message) = context.get_argument(0) {
+            vec![message.clone()]
+        } else {
+            context.pipeline_input.clone()
+        };
+
+        for message in messages {
+            evaluator
+                .host_mut()
+                .write_warning_line(&message.to_string());
+        }
+        Ok(vec![])
+    }
+}
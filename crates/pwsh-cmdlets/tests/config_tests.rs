@@ -0,0 +1,130 @@
+/// The session's configuration: read from pwsh.config.json or given by the host, shown by Get-PwshConfiguration
+use pwsh_runtime::{Configuration, ExecutionPolicy, SessionBuilder, SessionError, Value};
+
+fn property(value: &Value, name: &str) -> Value {
+    value
+        .get_property(name)
+        .unwrap_or_else(|| panic!("No {} in {}", name, value))
+}
+
+fn error_id(error: SessionError) -> String {
+    match error {
+        SessionError::Runtime(error) => error.error_id().to_string(),
+        other => panic!("Expected a runtime error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_configuration_file_sets_up_the_session() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let path = dir.path().join("pwsh.config.json");
+    std::fs::write(
+        &path,
+        r#"{
+            "StrictMode": "1.0",
+            "HistorySize": 200,
+            "ColorScheme": { "Keyword": "Green" },
+            "ModulePaths": ["modules"],
+            "ExecutionPolicy": "AllSigned"
+        }"#,
+    )
+    .unwrap();
+
+    let mut session = SessionBuilder::new()
+        .cmdlets(pwsh_cmdlets::register_all)
+        .configuration(Configuration::load(&path).unwrap())
+        .build();
+    assert_eq!(
+        session.invoke("Get-ExecutionPolicy").unwrap(),
+        vec![Value::String("AllSigned".into())]
+    );
+    assert_eq!(
+        error_id(session.invoke("$undefined").unwrap_err()),
+        "VariableIsUndefined"
+    );
+
+    let config = session.invoke("Get-PwshConfiguration").unwrap();
+    let config = &config[0];
+    assert_eq!(
+        property(config, "Path"),
+        Value::String(path.display().to_string().into())
+    );
+    assert_eq!(property(config, "StrictMode"), Value::String("1.0".into()));
+    assert_eq!(property(config, "HistorySize"), Value::Number(200.0));
+    assert_eq!(property(config, "Paging"), Value::Boolean(false));
+    assert_eq!(property(config, "DefaultEncoding"), Value::Null);
+    assert_eq!(
+        property(&property(config, "ColorScheme"), "Keyword"),
+        Value::String("Green".into())
+    );
+    assert_eq!(
+        session
+            .invoke("(Get-PwshConfiguration).ModulePaths[0]")
+            .unwrap(),
+        vec![Value::String("modules".into())]
+    );
+}
+
+#[test]
+fn test_builder_settings_win_over_the_configuration() {
+    let mut session = SessionBuilder::new()
+        .cmdlets(pwsh_cmdlets::register_all)
+        .execution_policy(ExecutionPolicy::Bypass)
+        .configuration(Configuration {
+            execution_policy: ExecutionPolicy::Restricted,
+            ..Default::default()
+        })
+        .build();
+    assert_eq!(
+        session.invoke("Get-ExecutionPolicy").unwrap(),
+        vec![Value::String("Bypass".into())]
+    );
+    // The configuration still says what it was given
+    assert_eq!(
+        session
+            .invoke("(Get-PwshConfiguration).ExecutionPolicy")
+            .unwrap(),
+        vec![Value::String("Restricted".into())]
+    );
+}
+
+#[test]
+fn test_set_strict_mode() {
+    let mut session = SessionBuilder::new()
+        .cmdlets(pwsh_cmdlets::register_all)
+        .build();
+    assert_eq!(session.invoke("$missing").unwrap(), vec![]);
+    assert_eq!(
+        session.invoke("\"[$missing]\"").unwrap(),
+        vec![Value::String("[]".into())]
+    );
+
+    session.invoke("Set-StrictMode -Version 1.0").unwrap();
+    assert_eq!(
+        error_id(session.invoke("$missing").unwrap_err()),
+        "VariableIsUndefined"
+    );
+    // Version 1 still lets strings expand undefined variables to nothing
+    assert_eq!(
+        session.invoke("\"[$missing]\"").unwrap(),
+        vec![Value::String("[]".into())]
+    );
+    // Variables that are set, automatic ones and environment ones still read
+    assert_eq!(
+        session
+            .invoke("$x = 1; $x; $null -eq $env:PWSH_NOT_SET")
+            .unwrap(),
+        vec![Value::Number(1.0), Value::Boolean(true)]
+    );
+
+    session.invoke("Set-StrictMode -Version Latest").unwrap();
+    assert!(session.invoke("\"[$missing]\"").is_err());
+
+    session.invoke("Set-StrictMode -Off").unwrap();
+    assert_eq!(session.invoke("$missing").unwrap(), vec![]);
+
+    assert_eq!(
+        error_id(session.invoke("Set-StrictMode -Version 9").unwrap_err()),
+        "InvalidVersion,Set-StrictMode"
+    );
+}
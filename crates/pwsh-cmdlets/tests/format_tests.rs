@@ -0,0 +1,194 @@
+/// Type names on objects and the views the console and Format-Table show them in
+use pwsh_runtime::{FixedClock, Session, SessionBuilder, Value};
+
+fn session() -> Session {
+    SessionBuilder::new()
+        .cmdlets(pwsh_cmdlets::register_all)
+        .build()
+}
+
+fn strings(values: Vec<Value>) -> Vec<String> {
+    values.iter().map(|v| v.to_string()).collect()
+}
+
+#[test]
+fn test_pstypename_tags_a_custom_object() {
+    let mut session = session();
+    let output = session
+        .invoke("$o = [PSCustomObject]@{PSTypeName = 'Demo.Server'; Name = 'web'}\n$o.PSTypeNames\n$o.Name")
+        .unwrap();
+    assert_eq!(
+        strings(output),
+        [
+            "Demo.Server",
+            "System.Management.Automation.PSCustomObject",
+            "System.Object",
+            "web"
+        ]
+    );
+
+    let output = session
+        .invoke("([PSCustomObject]@{PSTypeName = 'Demo.Server'; Name = 'web'}).Keys")
+        .unwrap();
+    assert_eq!(strings(output), vec!["Name"]);
+}
+
+#[test]
+fn test_cmdlet_output_is_tagged_for_its_view() {
+    let mut session = session();
+    let output = session
+        .invoke("(@(1, 2) | Measure-Object).PSTypeNames[0]")
+        .unwrap();
+    assert_eq!(
+        strings(output),
+        vec!["Microsoft.PowerShell.Commands.GenericMeasureInfo"]
+    );
+
+    let output = session.invoke("@(1, 2) | Measure-Object -Sum").unwrap();
+    let lines = session.evaluator().format_output(&output);
+    assert_eq!(lines[1], "Count             : 2");
+    assert_eq!(lines[3], "Sum               : 3");
+}
+
+#[test]
+fn test_format_table_of_script_objects() {
+    let mut session = session();
+    let output = session
+        .invoke(
+            "@([PSCustomObject]@{Name = 'web'; Port = 80}, [PSCustomObject]@{Name = 'database'; Port = 5432}) | Format-Table",
+        )
+        .unwrap();
+    assert_eq!(
+        strings(output),
+        vec![
+            "",
+            "Name     Port",
+            "----     ----",
+            "web        80",
+            "database 5432",
+            "",
+        ]
+    );
+}
+
+// Get-Process is built with the process feature, and lists processes where
+// the platform has a process table to read
+#[test]
+#[cfg(all(feature = "process", target_os = "linux"))]
+fn test_processes_are_tagged_and_formatted() {
+    let mut session = session();
+    let output = session.invoke("(Get-Process)[0].PSTypeNames[0]").unwrap();
+    assert_eq!(strings(output), vec!["System.Diagnostics.Process"]);
+
+    let output = session
+        .invoke("Get-Process | Select-Object -First 1 | Format-Table Name, Id")
+        .unwrap();
+    let header = &strings(output)[1];
+    assert!(header.starts_with("Name ") && header.ends_with(" Id"));
+}
+
+#[test]
+fn test_registered_views_apply_to_tagged_objects() {
+    let mut session = session();
+    session
+        .evaluator_mut()
+        .formats_mut()
+        .register_json(
+            r#"{"Views": [{"TypeName": "Demo.Server", "Table": [{"Property": "Name", "Label": "Server"}]}]}"#,
+        )
+        .unwrap();
+    let output = session
+        .invoke("[PSCustomObject]@{PSTypeName = 'Demo.Server'; Name = 'web'; Port = 80}")
+        .unwrap();
+    assert_eq!(
+        session.evaluator().format_output(&output),
+        vec!["", "Server", "------", "web", ""]
+    );
+}
+
+#[test]
+fn test_scripts_register_views_with_update_format_data() {
+    let mut session = session();
+    let output = session
+        .invoke(
+            "Update-FormatData -View @{TypeName = 'Demo.Server'; Table = @('Name', @{Property = 'Port'; Width = 6; Alignment = 'Right'})}
+[PSCustomObject]@{PSTypeName = 'Demo.Server'; Name = 'web'; Port = 80}
+[PSCustomObject]@{PSTypeName = 'Demo.Server'; Name = 'db'; Port = 5432}",
+        )
+        .unwrap();
+    assert_eq!(
+        session.evaluator().format_output(&output),
+        vec![
+            "",
+            "Name   Port",
+            "----   ----",
+            "web      80",
+            "db     5432",
+            ""
+        ]
+    );
+
+    let error = session
+        .invoke("Update-FormatData -View @{TypeName = 'Demo.Server'; Tabel = 'Name'}")
+        .unwrap_err();
+    assert!(error.to_string().contains("Unknown view setting 'Tabel'"));
+}
+
+#[test]
+fn test_format_table_wrap_and_auto_size() {
+    let mut session = session();
+    session
+        .invoke(r#"Update-FormatData -View @{TypeName = 'Demo.Server'; Table = @(@{Property = 'Name'; Width = 4}, 'Port')}"#)
+        .unwrap();
+    let server = "[PSCustomObject]@{PSTypeName = 'Demo.Server'; Name = 'webserver'; Port = 80}";
+
+    let output = session
+        .invoke(&format!("{} | Format-Table", server))
+        .unwrap();
+    assert_eq!(strings(output)[3], "web… 80");
+    let output = session
+        .invoke(&format!("{} | Format-Table -Wrap", server))
+        .unwrap();
+    assert_eq!(strings(output)[3..6], ["webs 80", "erve", "r"]);
+    let output = session
+        .invoke(&format!("{} | Format-Table -AutoSize", server))
+        .unwrap();
+    assert_eq!(
+        strings(output)[1..4],
+        ["Name      Port", "----      ----", "webserver 80"]
+    );
+}
+
+#[test]
+fn test_get_history_shows_the_lines_run_at_the_prompt() {
+    let start = chrono::NaiveDate::from_ymd_opt(2024, 3, 1)
+        .unwrap()
+        .and_hms_opt(9, 0, 0)
+        .unwrap();
+    let mut session = SessionBuilder::new()
+        .cmdlets(pwsh_cmdlets::register_all)
+        .clock(FixedClock::new(start))
+        .build();
+    session.invoke_interactive("$x = 5").unwrap();
+    assert!(session.invoke_interactive("Get-Nothing").is_err());
+    session.invoke_interactive("$x * 2").unwrap();
+
+    let output = session.invoke("Get-History -Count 2").unwrap();
+    assert_eq!(
+        session.evaluator().format_output(&output),
+        vec![
+            "",
+            "Id Duration CommandLine",
+            "-- -------- -----------",
+            " 2     0.00 Get-Nothing",
+            " 3     0.00 $x * 2",
+            "",
+        ]
+    );
+    let output = session.invoke("(Get-History 2).ExecutionStatus").unwrap();
+    assert_eq!(strings(output), vec!["Failed"]);
+    let output = session
+        .invoke("Get-History 9 -ErrorAction SilentlyContinue; $error[0].FullyQualifiedErrorId")
+        .unwrap();
+    assert_eq!(strings(output), vec!["GetHistoryIdNotFound,Get-History"]);
+}
@@ -0,0 +1,489 @@
+/// Cmdlets that talk to the host write through it, never to the console
+use pwsh_runtime::{Host, Progress, RuntimeError, Session, SessionBuilder, SessionError, Value};
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
+
+/// Host that records what it's given and answers prompts from a queue
+#[derive(Clone, Default)]
+struct RecordingHost {
+    output: Rc<RefCell<String>>,
+    errors: Rc<RefCell<Vec<String>>>,
+    progress: Rc<RefCell<Vec<Progress>>>,
+    prompts: Rc<RefCell<Vec<String>>>,
+    input: Rc<RefCell<VecDeque<String>>>,
+    /// Out-Default's lines, kept apart from other output
+    output_lines: Rc<RefCell<Vec<String>>>,
+    /// Whether the host claims to show ANSI styling
+    ansi: bool,
+    /// The height of the host's window, if it has one
+    rows: Option<usize>,
+}
+
+impl Host for RecordingHost {
+    fn write(&mut self, text: &str) {
+        self.output.borrow_mut().push_str(text);
+    }
+
+    fn write_error_line(&mut self, text: &str) {
+        self.errors.borrow_mut().push(text.to_string());
+    }
+
+    fn write_progress(&mut self, progress: &Progress) {
+        self.progress.borrow_mut().push(progress.clone());
+    }
+
+    fn write_output_lines(&mut self, lines: &[String]) {
+        self.output_lines.borrow_mut().extend_from_slice(lines);
+    }
+
+    fn supports_ansi(&self) -> bool {
+        self.ansi
+    }
+
+    fn window_size(&self) -> Option<(usize, usize)> {
+        self.rows.map(|rows| (80, rows))
+    }
+
+    fn read_line(&mut self, prompt: &str) -> Option<String> {
+        self.prompts.borrow_mut().push(prompt.to_string());
+        self.input.borrow_mut().pop_front()
+    }
+}
+
+fn session(host: &RecordingHost) -> Session {
+    SessionBuilder::new()
+        .cmdlets(pwsh_cmdlets::register_all)
+        .host(host.clone())
+        .build()
+}
+
+#[test]
+fn test_write_host_writes_to_the_host_not_the_pipeline() {
+    let host = RecordingHost::default();
+    let mut session = session(&host);
+
+    let output = session
+        .invoke("Write-Host 'a' @(1, 2)\nWrite-Host 'x' 'y' -Separator ', ' -NoNewline")
+        .unwrap();
+    assert_eq!(output, vec![]);
+    assert_eq!(*host.output.borrow(), "a 1 2\nx, y");
+
+    session.invoke("@(3, 4) | Write-Host").unwrap();
+    assert_eq!(*host.output.borrow(), "a 1 2\nx, y3 4\n");
+}
+
+#[test]
+fn test_write_warning_goes_to_the_warning_stream() {
+    let host = RecordingHost::default();
+    let mut session = session(&host);
+
+    session
+        .invoke("Write-Warning 'Low disk'; @('a', 'b') | Write-Warning")
+        .unwrap();
+    assert_eq!(
+        *host.errors.borrow(),
+        vec!["WARNING: Low disk", "WARNING: a", "WARNING: b"]
+    );
+    assert_eq!(*host.output.borrow(), "");
+}
+
+#[test]
+fn test_write_progress_reports_to_the_host() {
+    let host = RecordingHost::default();
+    let mut session = session(&host);
+
+    session
+        .invoke("Write-Progress -Activity 'Copying' -Status 'file.txt' -PercentComplete 150")
+        .unwrap();
+    session
+        .invoke("Write-Progress 'Copying' -Completed")
+        .unwrap();
+    assert_eq!(
+        *host.progress.borrow(),
+        vec![
+            Progress {
+                activity: "Copying".to_string(),
+                status: "file.txt".to_string(),
+                percent_complete: Some(100),
+                completed: false,
+            },
+            Progress {
+                activity: "Copying".to_string(),
+                status: "Processing".to_string(),
+                percent_complete: None,
+                completed: true,
+            },
+        ]
+    );
+    assert!(session.invoke("Write-Progress").is_err());
+}
+
+#[test]
+fn test_read_host_prompts_through_the_host() {
+    let host = RecordingHost::default();
+    host.input.borrow_mut().push_back("Ada".to_string());
+    let mut session = session(&host);
+
+    assert_eq!(
+        session
+            .invoke("$name = Read-Host 'Name'; \"Hi $name\"")
+            .unwrap(),
+        vec![Value::String("Hi Ada".into())]
+    );
+    assert_eq!(*host.prompts.borrow(), vec!["Name: "]);
+
+    // No more input
+    assert!(session.invoke("Read-Host").is_err());
+}
+
+/// An unreadable subdirectory is a non-terminating error: reported on the
+/// error stream (unless -ErrorAction says otherwise) while the rest is listed
+#[cfg(unix)]
+#[test]
+fn test_get_childitem_reports_unreadable_directories_and_goes_on() {
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let locked = temp_dir.path().join("locked");
+    fs::create_dir(&locked).unwrap();
+    fs::create_dir(temp_dir.path().join("open")).unwrap();
+    fs::write(temp_dir.path().join("open").join("a.txt"), "a").unwrap();
+    fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+    // Privileged users can read it anyway, so there may be nothing to report
+    let unreadable = fs::read_dir(&locked).is_err();
+
+    let host = RecordingHost::default();
+    let mut session = session(&host);
+    let dir = temp_dir.path().to_string_lossy().replace('\\', "/");
+    let listing = session.invoke(&format!("Get-ChildItem '{}' -Recurse", dir));
+    let quiet = session.invoke(&format!(
+        "Get-ChildItem '{}' -Recurse -ErrorAction SilentlyContinue",
+        dir
+    ));
+    let stop = session.invoke(&format!(
+        "Get-ChildItem '{}' -Recurse -ErrorAction Stop",
+        dir
+    ));
+    fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let names: Vec<String> = listing
+        .unwrap()
+        .iter()
+        .filter_map(|item| item.get_property("Name"))
+        .map(|name| name.to_string())
+        .collect();
+    assert!(names.contains(&"a.txt".to_string()));
+    assert!(names.contains(&"locked".to_string()));
+    assert_eq!(quiet.unwrap().len(), names.len());
+
+    if unreadable {
+        let errors = host.errors.borrow();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("locked"), "{}", errors[0]);
+        assert!(stop.is_err());
+    } else {
+        assert!(host.errors.borrow().is_empty());
+        assert!(stop.is_ok());
+    }
+}
+
+#[test]
+fn test_convertto_json_warns_when_it_truncates() {
+    let host = RecordingHost::default();
+    let mut session = session(&host);
+
+    session
+        .invoke("@{A=@{B=@{C=@{D=1}}}} | ConvertTo-Json")
+        .unwrap();
+    assert_eq!(
+        *host.errors.borrow(),
+        vec!["WARNING: Resulting JSON is truncated as serialization has exceeded the set depth of 2."]
+    );
+}
+
+#[test]
+fn test_out_default_hands_formatted_lines_to_the_host() {
+    let host = RecordingHost::default();
+    let mut session = session(&host);
+
+    let output = session
+        .invoke("@('a', $null, [PSCustomObject]@{PSTypeName = 'Microsoft.PowerShell.Commands.GenericMeasureInfo'; Count = 2}) | Out-Default")
+        .unwrap();
+    assert_eq!(output, vec![]);
+    assert_eq!(
+        host.output_lines.borrow()[..3],
+        ["a", "", "Count             : 2"]
+    );
+    assert_eq!(*host.output.borrow(), "");
+}
+
+#[test]
+fn test_out_paged_shows_a_screen_at_a_time() {
+    let host = RecordingHost {
+        rows: Some(4),
+        ..RecordingHost::default()
+    };
+    host.input
+        .borrow_mut()
+        .extend(["".to_string(), " ".to_string(), "q".to_string()]);
+    let mut session = session(&host);
+    let numbers = |count: usize| {
+        let items: Vec<String> = (1..=count).map(|i| i.to_string()).collect();
+        format!("@({}) | Out-Paged", items.join(", "))
+    };
+
+    // A screen of 3, Enter for one more, Space for 3 more, then Q
+    session.invoke(&numbers(20)).unwrap();
+    let shown: Vec<String> = (1..=7).map(|i| i.to_string()).collect();
+    assert_eq!(*host.output_lines.borrow(), shown);
+    assert_eq!(host.prompts.borrow().len(), 3);
+
+    // Output that fits, or a host without a window, isn't paged
+    host.output_lines.borrow_mut().clear();
+    session.invoke(&numbers(3)).unwrap();
+    assert_eq!(host.output_lines.borrow().len(), 3);
+    let plain = RecordingHost::default();
+    let mut plain_session = self::session(&plain);
+    plain_session.invoke(&numbers(20)).unwrap();
+    assert_eq!(plain.output_lines.borrow().len(), 20);
+    assert!(plain.prompts.borrow().is_empty());
+}
+
+#[test]
+fn test_output_rendering_strips_or_keeps_ansi_styling() {
+    let host = RecordingHost {
+        ansi: true,
+        ..RecordingHost::default()
+    };
+    let mut session = session(&host);
+    let styled = "\x1b[1mbold\x1b[0m";
+
+    session
+        .invoke(&format!("'{}' | Out-Default", styled))
+        .unwrap();
+    session
+        .invoke(&format!(
+            "$OutputRendering = 'PlainText'\n'{}' | Out-Default",
+            styled
+        ))
+        .unwrap();
+    assert_eq!(*host.output_lines.borrow(), vec![styled, "bold"]);
+
+    // Without $OutputRendering, $PSStyle.OutputRendering decides
+    session
+        .invoke(&format!(
+            "$OutputRendering = $null\n$PSStyle.OutputRendering = 'PlainText'\n'{}' | Out-Default",
+            styled
+        ))
+        .unwrap();
+    assert_eq!(host.output_lines.borrow()[2], "bold");
+
+    let error = session
+        .invoke("$OutputRendering = 'Color'\n'x' | Out-Default")
+        .unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("Use one of: Host, Ansi, PlainText"));
+}
+
+#[test]
+fn test_psstyle_styles_text_and_write_host() {
+    let host = RecordingHost {
+        ansi: true,
+        ..RecordingHost::default()
+    };
+    let mut session = session(&host);
+    session
+        .invoke("$red = $PSStyle.Foreground.Red\nWrite-Host ($red + 'red' + $PSStyle.Reset) ($PSStyle.Bold + 'bold' + $PSStyle.BoldOff)")
+        .unwrap();
+    session
+        .invoke("Write-Host 'warn' -ForegroundColor Yellow -BackgroundColor DarkBlue")
+        .unwrap();
+    assert_eq!(
+        *host.output.borrow(),
+        "\x1b[31mred\x1b[0m \x1b[1mbold\x1b[22m\n\x1b[93m\x1b[44mwarn\x1b[0m\n"
+    );
+
+    // Table headers and list labels take $PSStyle.Formatting's accents
+    session
+        .invoke("[PSCustomObject]@{PSTypeName = 'Microsoft.PowerShell.Commands.GenericMeasureInfo'; Count = 2} | Out-Default")
+        .unwrap();
+    assert_eq!(
+        host.output_lines.borrow()[1],
+        "\x1b[32;1mCount             : \x1b[0m2"
+    );
+
+    // PlainText takes the styling out
+    host.output.borrow_mut().clear();
+    session
+        .invoke("$PSStyle.OutputRendering = 'PlainText'\n$blue = $PSStyle.Background.BrightBlue\nWrite-Host \"${blue}plain\" -ForegroundColor Red")
+        .unwrap();
+    assert_eq!(*host.output.borrow(), "plain\n");
+
+    let error = session
+        .invoke("Write-Host 'x' -ForegroundColor Pink")
+        .unwrap_err();
+    assert!(error.to_string().contains("'Pink' is not a console color"));
+}
+
+#[test]
+fn test_file_listing_is_styled_by_psstyle_file_info() {
+    let dir = tempfile::TempDir::new().unwrap();
+    std::fs::create_dir(dir.path().join("src")).unwrap();
+    std::fs::write(dir.path().join("build.ps1"), "").unwrap();
+    std::fs::write(dir.path().join("notes.txt"), "").unwrap();
+    let listing = format!("Get-ChildItem '{}' | Out-Default", dir.path().display());
+
+    let host = RecordingHost {
+        ansi: true,
+        ..RecordingHost::default()
+    };
+    let mut session = session(&host);
+    session.invoke(&listing).unwrap();
+    let lines = host.output_lines.borrow().clone();
+    assert!(lines.iter().any(|l| l.ends_with(" \x1b[44;1msrc\x1b[0m")));
+    assert!(lines
+        .iter()
+        .any(|l| l.ends_with(" \x1b[33;1mbuild.ps1\x1b[0m")));
+    assert!(lines.iter().any(|l| l.ends_with(" notes.txt")));
+
+    host.output_lines.borrow_mut().clear();
+    session
+        .invoke(&format!(
+            "$PSStyle.FileInfo.Directory = ''\n$PSStyle.FileInfo.Extension['.txt'] = $PSStyle.Reset\n{}",
+            listing
+        ))
+        .unwrap();
+    let lines = host.output_lines.borrow().clone();
+    assert!(lines.iter().any(|l| l.ends_with(" src")));
+    assert!(lines
+        .iter()
+        .any(|l| l.ends_with(" \x1b[0mnotes.txt\x1b[0m")));
+
+    // A host that doesn't show styling gets plain names
+    let plain = RecordingHost::default();
+    let mut plain_session = self::session(&plain);
+    plain_session.invoke(&listing).unwrap();
+    assert!(plain
+        .output_lines
+        .borrow()
+        .iter()
+        .all(|l| !l.contains('\x1b')));
+}
+
+/// A breakpoint stops the script and reads debugger commands from the
+/// host: expressions run in the stopped code's scope, s steps into calls,
+/// k shows the call stack and c carries on
+#[test]
+fn test_breakpoints_stop_at_the_debug_prompt() {
+    let temp_dir = tempfile::TempDir::new().unwrap();
+    let script = temp_dir.path().join("add.ps1");
+    let source = "function Add-One($n) {\n    $r = $n + 1\n    $r\n}\n$x = 5\n$y = Add-One $x\n\"y is $y\"\n";
+    std::fs::write(&script, source).unwrap();
+
+    let host = RecordingHost::default();
+    let mut session = session(&host);
+    let set = session
+        .invoke(&format!(
+            "Set-PSBreakpoint -Script '{}' -Line 6",
+            script.display()
+        ))
+        .unwrap();
+    assert_eq!(set[0].get_property("Id"), Some(Value::Number(0.0)));
+
+    host.input
+        .borrow_mut()
+        .extend(["$x", "s", "$n", "k", "c"].map(String::from));
+    let output = session.invoke_file(&script, source).unwrap();
+    assert_eq!(output, vec![Value::String("y is 6".into())]);
+    assert_eq!(host.prompts.borrow().len(), 5);
+    assert!(host.prompts.borrow().iter().all(|p| p == "[DBG]: PS> "));
+
+    let lines = host.output_lines.borrow();
+    assert!(lines
+        .iter()
+        .any(|line| line.starts_with("Hit Line breakpoint on '")));
+    assert!(lines.contains(&"+ $y = Add-One $x".to_string()));
+    assert!(lines.contains(&"+     $r = $n + 1".to_string()));
+    assert_eq!(lines.iter().filter(|line| *line == "5").count(), 2);
+    assert!(lines
+        .iter()
+        .any(|line| line.starts_with("Add-One") && line.contains("{n=5}")));
+
+    let hits = session.invoke("(Get-PSBreakpoint).HitCount").unwrap();
+    assert_eq!(hits, vec![Value::Number(1.0)]);
+}
+
+/// Command and variable breakpoints; an action runs instead of stopping,
+/// and q stops the script in a way try can't catch
+#[test]
+fn test_breakpoint_actions_and_quitting_the_debugger() {
+    let host = RecordingHost::default();
+    let mut session = session(&host);
+
+    session
+        .invoke("$null = Set-PSBreakpoint -Command Get-Da* -Action { Write-Host 'dating' }")
+        .unwrap();
+    session.invoke("$now = Get-Date").unwrap();
+    assert_eq!(*host.output.borrow(), "dating\n");
+    assert!(host.prompts.borrow().is_empty());
+
+    session
+        .invoke("$null = Set-PSBreakpoint -Variable total")
+        .unwrap();
+    host.input.borrow_mut().push_back("q".to_string());
+    let error = session.invoke("try { $total = 1 } catch { 'caught' }");
+    assert!(
+        matches!(error, Err(SessionError::Runtime(RuntimeError::Stopped))),
+        "{:?}",
+        error
+    );
+    assert_eq!(host.prompts.borrow().len(), 1);
+    assert!(host
+        .output_lines
+        .borrow()
+        .contains(&"Hit Variable breakpoint on '$total' (Write access)".to_string()));
+
+    let ids = session
+        .invoke("Get-PSBreakpoint | ForEach-Object { $_.Id }")
+        .unwrap();
+    assert_eq!(ids, vec![Value::Number(0.0), Value::Number(1.0)]);
+    session.invoke("Remove-PSBreakpoint -Id 0").unwrap();
+    session
+        .invoke("Get-PSBreakpoint | Remove-PSBreakpoint")
+        .unwrap();
+    assert_eq!(session.invoke("Get-PSBreakpoint").unwrap(), vec![]);
+    session.invoke("Remove-PSBreakpoint -Id 1").unwrap();
+    assert_eq!(
+        *host.errors.borrow(),
+        vec!["There is no breakpoint with id 1"]
+    );
+    session.invoke("$total = 2").unwrap();
+    assert_eq!(host.prompts.borrow().len(), 1);
+}
+
+/// Set-PSDebug traces statements to the debug stream, and with -Trace 2
+/// assignments and calls too; a level set in a function ends with it
+#[test]
+fn test_set_psdebug_traces_statements_in_its_scope() {
+    let host = RecordingHost::default();
+    let mut session = session(&host);
+
+    let script = "function Add-One($n) {\n    Set-PSDebug -Trace 2\n    $r = $n + 1\n    $r\n}\nSet-PSDebug -Trace 1\n$x = 5\n$y = Add-One $x\nSet-PSDebug -Off\n$z = 1\n";
+    let output = session.invoke(script).unwrap();
+    assert_eq!(output, vec![]);
+    assert_eq!(
+        *host.errors.borrow(),
+        vec![
+            "DEBUG:    7+  >>>> $x = 5",
+            "DEBUG:    8+  >>>> $y = Add-One $x",
+            "DEBUG:    2+  >>>> Set-PSDebug -Trace 2",
+            "DEBUG:    3+  >>>> $r = $n + 1",
+            "DEBUG:     ! SET $r = '6'.",
+            "DEBUG:    4+  >>>> $r",
+            "DEBUG:    9+  >>>> Set-PSDebug -Off",
+        ]
+    );
+}
@@ -1,7 +1,7 @@
 /// Integration tests for Week 6: Object Pipeline with 5 Cmdlets
 use pwsh_lexer::Lexer;
 use pwsh_parser::Parser;
-use pwsh_runtime::{Evaluator, Value};
+use pwsh_runtime::{ErrorCategory, Evaluator, Value};
 use std::fs;
 use tempfile::TempDir;
 
@@ -28,10 +28,13 @@ fn test_write_output_simple() {
 #[test]
 fn test_write_output_string() {
     let result = eval_with_cmdlets("Write-Output \"Hello World\"").unwrap();
-    assert_eq!(result, Value::String("Hello World".to_string()));
+    assert_eq!(result, Value::String("Hello World".into()));
 }
 
+// Get-Process is built with the process feature, and lists processes where
+// the platform has a process table to read
 #[test]
+#[cfg(all(feature = "process", target_os = "linux"))]
 fn test_get_process_basic() {
     let result = eval_with_cmdlets("Get-Process").unwrap();
     // Get-Process returns an array, but statement returns the last value
@@ -69,11 +72,31 @@ fn test_cmdlet_not_found() {
     }
 }
 
+#[test]
+fn test_cmdlet_not_found_suggests_similar_names() {
+    let err = eval_with_cmdlets("Get-ChildItm").unwrap_err();
+    match &err {
+        pwsh_runtime::RuntimeError::UndefinedFunction { name, suggestions } => {
+            assert_eq!(name, "Get-ChildItm");
+            assert_eq!(
+                suggestions.first().map(String::as_str),
+                Some("Get-ChildItem")
+            );
+        }
+        other => panic!("Expected UndefinedFunction, got {:?}", other),
+    }
+    assert!(err.to_string().contains("Did you mean Get-ChildItem"));
+
+    // Nothing close enough: no suggestion text
+    let err = eval_with_cmdlets("Frobnicate").unwrap_err();
+    assert!(!err.to_string().contains("Did you mean"));
+}
+
 #[test]
 fn test_week6_success_criteria_basic() {
     // From ROADMAP: Write-Output "Hello World"
     let result = eval_with_cmdlets("Write-Output \"Hello World\"").unwrap();
-    assert_eq!(result, Value::String("Hello World".to_string()));
+    assert_eq!(result, Value::String("Hello World".into()));
 }
 
 #[test]
@@ -97,7 +120,7 @@ fn test_function_calling_cmdlet_with_parameter() {
         "function Greet($name) { Write-Output \"Hello $name\" }\nGreet \"Alice\"",
     )
     .unwrap();
-    assert_eq!(result, Value::String("Hello Alice".to_string()));
+    assert_eq!(result, Value::String("Hello Alice".into()));
 }
 
 #[test]
@@ -125,7 +148,7 @@ fn test_function_with_default_and_cmdlet() {
         "function Greet($name = \"World\") { Write-Output \"Hello $name\" }\nGreet",
     )
     .unwrap();
-    assert_eq!(result, Value::String("Hello World".to_string()));
+    assert_eq!(result, Value::String("Hello World".into()));
 }
 
 #[test]
@@ -184,11 +207,13 @@ fn test_week12_select_object_properties() {
 
     // Result should be an array
     if let Value::Array(items) = result {
+        let items = items.read();
         assert_eq!(items.len(), 2);
 
         // Each item should be an object with only Name and CPU
-        for item in items {
+        for item in items.iter() {
             if let Value::Object(props) = item {
+                let props = props.read();
                 assert_eq!(props.len(), 2);
                 assert!(props.contains_key("Name"));
                 assert!(props.contains_key("CPU"));
@@ -216,6 +241,7 @@ fn test_week12_select_object_first() {
     let result = eval_with_cmdlets(code).unwrap();
 
     if let Value::Array(items) = result {
+        let items = items.read();
         assert_eq!(items.len(), 2);
     } else {
         panic!("Expected array result");
@@ -232,6 +258,7 @@ fn test_week12_select_object_last() {
     let result = eval_with_cmdlets(code).unwrap();
 
     if let Value::Array(items) = result {
+        let items = items.read();
         assert_eq!(items.len(), 2);
         assert_eq!(items[0], Value::Number(4.0));
         assert_eq!(items[1], Value::Number(5.0));
@@ -254,11 +281,13 @@ fn test_week12_combined_property_and_first() {
     let result = eval_with_cmdlets(code).unwrap();
 
     if let Value::Array(items) = result {
+        let items = items.read();
         assert_eq!(items.len(), 2);
 
         // Each item should have only Name and CPU
-        for item in items {
+        for item in items.iter() {
             if let Value::Object(props) = item {
+                let props = props.read();
                 assert_eq!(props.len(), 2);
                 assert!(props.contains_key("Name"));
                 assert!(props.contains_key("CPU"));
@@ -286,11 +315,13 @@ fn test_select_object_positional_arguments() {
 
     // Result should be an array
     if let Value::Array(items) = result {
+        let items = items.read();
         assert_eq!(items.len(), 3);
 
         // Each item should be an object with only Name and CPU
-        for item in items {
+        for item in items.iter() {
             if let Value::Object(props) = item {
+                let props = props.read();
                 assert_eq!(props.len(), 2);
                 assert!(props.contains_key("Name"));
                 assert!(props.contains_key("CPU"));
@@ -315,11 +346,12 @@ fn test_select_object_case_insensitive_property_lookup() {
     let result = eval_with_cmdlets(code).unwrap();
 
     if let Value::Object(props) = result {
+        let props = props.read();
         assert_eq!(props.len(), 2);
         // The keys in the result object should match the requested case
         assert!(props.contains_key("name"));
         assert!(props.contains_key("cpu"));
-        assert_eq!(props.get("name"), Some(&Value::String("Test".to_string())));
+        assert_eq!(props.get("name"), Some(&Value::String("Test".into())));
         assert_eq!(props.get("cpu"), Some(&Value::Number(10.0)));
     } else {
         panic!("Expected object result, got {:?}", result);
@@ -336,10 +368,11 @@ fn test_select_object_mixed_case_input_and_request() {
     let result = eval_with_cmdlets(code).unwrap();
 
     if let Value::Object(props) = result {
+        let props = props.read();
         assert_eq!(props.len(), 2);
         assert!(props.contains_key("Name"));
         assert!(props.contains_key("CPU"));
-        assert_eq!(props.get("Name"), Some(&Value::String("Mixed".to_string())));
+        assert_eq!(props.get("Name"), Some(&Value::String("Mixed".into())));
         assert_eq!(props.get("CPU"), Some(&Value::Number(20.0)));
     } else {
         panic!("Expected object result, got {:?}", result);
@@ -347,14 +380,118 @@ fn test_select_object_mixed_case_input_and_request() {
 }
 
 #[test]
+fn test_select_object_wildcard_properties() {
+    let names = |code: &str| match eval_with_cmdlets(code).unwrap() {
+        Value::Object(props) => props
+            .read()
+            .keys()
+            .map(|key| key.to_string())
+            .collect::<Vec<_>>(),
+        other => panic!("Expected object result, got {:?}", other),
+    };
+    let object = "$obj = [PSCustomObject]@{Name='job'; StartTime=1; Id=7; ExitTime=2}\n";
+
+    assert_eq!(
+        names(&format!("{}$obj | Select-Object Name, *Time", object)),
+        vec!["Name", "StartTime", "ExitTime"]
+    );
+    // A property named before a wildcard keeps its place
+    assert_eq!(
+        names(&format!("{}$obj | Select-Object -Property Id, *", object)),
+        vec!["Id", "Name", "StartTime", "ExitTime"]
+    );
+    assert!(names(&format!("{}$obj | Select-Object 'Missing*'", object)).is_empty());
+}
+
+#[test]
+fn test_select_object_bare_words_are_never_commands() {
+    let names = |code: &str| match eval_with_cmdlets(code).unwrap() {
+        Value::Object(props) => props
+            .read()
+            .keys()
+            .map(|key| key.to_string())
+            .collect::<Vec<_>>(),
+        other => panic!("Expected object result, got {:?}", other),
+    };
+    let object = "$obj = [PSCustomObject]@{Name='job'; CPU=3; Id=7}\n";
+
+    // A bare word is a string wherever it is, whatever follows it: none of
+    // these run a command called Name
+    assert_eq!(
+        names(&format!(
+            "{}$obj | Select-Object -Property Name, CPU",
+            object
+        )),
+        vec!["Name", "CPU"]
+    );
+    assert_eq!(
+        names(&format!("{}$obj | Select-Object -First 1 Name", object)),
+        vec!["Name"]
+    );
+    assert_eq!(
+        names(&format!(
+            "{}$obj | Select-Object Name,\n    Id | Select-Object Id",
+            object
+        )),
+        vec!["Id"]
+    );
+    assert_eq!(
+        names(&format!("{}$obj | Select-Object Name -First 1", object)),
+        vec!["Name"]
+    );
+}
+
+#[test]
+fn test_select_object_count_from_an_expression() {
+    let count = |code: &str| match eval_with_cmdlets(code).unwrap() {
+        Value::Array(items) => items.read().len(),
+        _ => 1,
+    };
+    let numbers = "$numbers = @(1, 2, 3, 4, 5)\n";
+
+    assert_eq!(
+        count(&format!(
+            "{}$n = 2\n$numbers | Select-Object -First ($n * 2)",
+            numbers
+        )),
+        4
+    );
+    assert_eq!(
+        count(&format!(
+            "{}$limits = @{{Top = 3}}\n$numbers | Select-Object -First $limits.Top",
+            numbers
+        )),
+        3
+    );
+    assert_eq!(
+        count(&format!(
+            "{}$sizes = @(2, 4)\n$numbers | Select-Object -Last $sizes[0]",
+            numbers
+        )),
+        2
+    );
+    // A variable value, followed by another parameter
+    assert_eq!(
+        count(&format!(
+            "{}$count = 3\n$numbers | Select-Object -First $count -Skip 1",
+            numbers
+        )),
+        3
+    );
+}
+
+#[test]
+#[cfg(all(feature = "process", target_os = "linux"))]
 fn test_select_object_get_process_case_insensitive() {
     let code = "Get-Process | Select-Object name";
     let result = eval_with_cmdlets(code).unwrap();
 
     if let Value::Array(items) = result {
+        let items = items.read();
         assert!(!items.is_empty());
-        for item in items {
+        for item in items.iter() {
             if let Value::Object(props) = item {
+                let props = props.read();
                 assert!(
                     props.contains_key("Name") || props.contains_key("name"),
                     "Should contain name property. Props: {:?}",
@@ -365,6 +502,7 @@ fn test_select_object_get_process_case_insensitive() {
             }
         }
     } else if let Value::Object(props) = result {
+        let props = props.read();
         assert!(
             props.contains_key("Name") || props.contains_key("name"),
             "Should contain name property. Props: {:?}",
@@ -381,9 +519,11 @@ fn test_week15_get_childitem_basic() {
     let result = eval_with_cmdlets("Get-ChildItem").unwrap();
 
     if let Value::Array(items) = result {
+        let items = items.read();
         assert!(!items.is_empty(), "Expected at least one item");
-        for item in items {
+        for item in items.iter() {
             if let Value::Object(props) = item {
+                let props = props.read();
                 assert!(
                     props.contains_key("Name") || props.contains_key("name"),
                     "File object should have a Name property. Props: {:?}",
@@ -405,11 +545,13 @@ fn test_week15_get_childitem_with_select() {
 
     // Result should be an array
     if let Value::Array(items) = result {
+        let items = items.read();
         assert!(!items.is_empty(), "Should return at least some items");
 
         // Each item should only have Name property
-        for item in items {
+        for item in items.iter() {
             if let Value::Object(props) = item {
+                let props = props.read();
                 assert_eq!(props.len(), 1, "Should only have Name property");
                 assert!(
                     props.contains_key("Name") || props.contains_key("name"),
@@ -439,9 +581,11 @@ fn test_week15_get_childitem_path_parameter() {
     let result = eval_with_cmdlets(&code).unwrap();
 
     if let Value::Array(items) = result {
+        let items = items.read();
         assert_eq!(items.len(), 2);
-        for item in items {
+        for item in items.iter() {
             if let Value::Object(props) = item {
+                let props = props.read();
                 assert_eq!(props.len(), 1);
                 assert!(props.contains_key("Name") || props.contains_key("name"));
             } else {
@@ -472,15 +616,13 @@ fn test_week15_get_childitem_filter_include_exclude_integration() {
     );
     let result = eval_with_cmdlets(&code).unwrap();
     let items: Vec<Value> = match result {
-        Value::Array(items) => items,
+        Value::Array(items) => items.unwrap_or_clone(),
         other => vec![other],
     };
     assert_eq!(items.len(), 1);
     if let Value::Object(props) = &items[0] {
-        assert_eq!(
-            props.get("Name"),
-            Some(&Value::String("file2.rs".to_string()))
-        );
+        let props = props.read();
+        assert_eq!(props.get("Name"), Some(&Value::String("file2.rs".into())));
     } else {
         panic!("Expected object");
     }
@@ -492,15 +634,13 @@ fn test_week15_get_childitem_filter_include_exclude_integration() {
     );
     let result = eval_with_cmdlets(&code).unwrap();
     let items: Vec<Value> = match result {
-        Value::Array(items) => items,
+        Value::Array(items) => items.unwrap_or_clone(),
         other => vec![other],
     };
     assert_eq!(items.len(), 1);
     if let Value::Object(props) = &items[0] {
-        assert_eq!(
-            props.get("Name"),
-            Some(&Value::String("file1.txt".to_string()))
-        );
+        let props = props.read();
+        assert_eq!(props.get("Name"), Some(&Value::String("file1.txt".into())));
     } else {
         panic!("Expected object");
     }
@@ -520,12 +660,13 @@ fn test_week16_get_content_reads_text_file() {
     let result = eval_with_cmdlets(&code).unwrap();
 
     if let Value::Array(items) = result {
+        let items = items.read();
         assert_eq!(
-            items,
+            *items,
             vec![
-                Value::String("one".to_string()),
-                Value::String("two".to_string()),
-                Value::String("three".to_string())
+                Value::String("one".into()),
+                Value::String("two".into()),
+                Value::String("three".into())
             ]
         );
     } else {
@@ -533,6 +674,163 @@ fn test_week16_get_content_reads_text_file() {
     }
 }
 
+#[test]
+fn test_parenthesized_get_content_length_and_index() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("lines.txt");
+    fs::write(&file_path, "one\ntwo\nthree\n").unwrap();
+    let path_str = file_path.to_string_lossy().replace('\\', "/");
+
+    let result = eval_with_cmdlets(&format!("(Get-Content '{}').Length", path_str)).unwrap();
+    assert_eq!(result, Value::Number(3.0));
+
+    let result = eval_with_cmdlets(&format!("(Get-Content '{}')[1]", path_str)).unwrap();
+    assert_eq!(result, Value::String("two".into()));
+}
+
+#[test]
+fn test_parenthesized_pipeline_count_in_arithmetic() {
+    let result =
+        eval_with_cmdlets("(@(1,2,3,4,5) | Where-Object { $_ -gt 2 }).Count * 10").unwrap();
+    assert_eq!(result, Value::Number(30.0));
+
+    // No matches still yields a count of zero
+    let result = eval_with_cmdlets("(@(1,2,3) | Where-Object { $_ -gt 5 }).Count").unwrap();
+    assert_eq!(result, Value::Number(0.0));
+}
+
+#[test]
+fn test_where_object_null_filters() {
+    let result =
+        eval_with_cmdlets("@(1, $null, 0, $unset) | Where-Object { $_ -ne $null }").unwrap();
+    assert_eq!(
+        result,
+        Value::Array(vec![Value::Number(1.0), Value::Number(0.0)].into())
+    );
+
+    let result =
+        eval_with_cmdlets("(@('a', '', $null, 'b') | Where-Object { -not $_ }).Count").unwrap();
+    assert_eq!(result, Value::Number(2.0));
+}
+
+#[test]
+fn test_function_and_cmdlet_output_is_enumerated() {
+    let result = eval_with_cmdlets(
+        "function Get-Pair($n) { $n; $n * 10 }\n@(1, 2) | ForEach-Object { Get-Pair $_ } | Where-Object { $_ -gt 5 }",
+    )
+    .unwrap();
+    assert_eq!(
+        result,
+        Value::Array(vec![Value::Number(10.0), Value::Number(20.0)].into())
+    );
+
+    // A function that writes nothing sends nothing down the pipeline
+    let result = eval_with_cmdlets("function f { $x = 1 }\nf | ForEach-Object { 'ran' }").unwrap();
+    assert_eq!(result, Value::Null);
+}
+
+#[test]
+fn test_write_output_no_enumerate() {
+    let count = |script: &str| eval_with_cmdlets(script).unwrap();
+
+    assert_eq!(
+        count("(Write-Output @(1, 2, 3) | Measure-Object).Count"),
+        Value::Number(3.0)
+    );
+    assert_eq!(
+        count("(Write-Output -NoEnumerate @(1, 2, 3) | Measure-Object).Count"),
+        Value::Number(1.0)
+    );
+    assert_eq!(
+        count("(Write-Output @(1, 2, 3) -NoEnumerate | Measure-Object).Count"),
+        Value::Number(1.0)
+    );
+    // Piped items were enumerated before they reached Write-Output
+    assert_eq!(
+        count("(@(1, 2, 3) | Write-Output -NoEnumerate | Measure-Object).Count"),
+        Value::Number(3.0)
+    );
+}
+
+#[test]
+fn test_get_childitem_variable_and_function_drives() {
+    let result = eval_with_cmdlets(
+        "$limit = 10\n$label = 'x'\nGet-ChildItem 'Variable:l*' | ForEach-Object { $_.Name + '=' + $_.Value }",
+    )
+    .unwrap();
+    assert_eq!(
+        result,
+        Value::Array(
+            vec![
+                Value::String("label=x".into()),
+                Value::String("limit=10".into())
+            ]
+            .into()
+        )
+    );
+
+    let result = eval_with_cmdlets(
+        "function Get-Size($x) { $x * 2 }\nfunction Get-Name { 'n' }\n(Get-ChildItem -Path Function: -Filter 'Get-S*').Definition",
+    )
+    .unwrap();
+    assert_eq!(
+        result,
+        Value::String("function Get-Size($x) {\n    $x * 2\n}".into())
+    );
+}
+
+#[test]
+fn test_set_and_get_variable_in_the_callers_scope() {
+    // A helper that writes into its caller's scope, even from inside an if
+    let script = "function Set-Result($value) { if ($value) { Set-Variable -Name result -Value $value -Scope 1 } }\n\
+                  function Run { Set-Result 42\n $result }\n\
+                  Run\n\
+                  $result";
+    assert_eq!(eval_with_cmdlets(script).unwrap(), Value::Number(42.0));
+
+    let script = "$level = 'outer'\n\
+                  function Peek($level) { Get-Variable level -Scope 1 -ValueOnly }\n\
+                  Peek 'inner'";
+    assert_eq!(
+        eval_with_cmdlets(script).unwrap(),
+        Value::String("outer".into())
+    );
+
+    // Only the scope's own variables: the parameter $level shadows the
+    // global one, and without it the function's scope has no $level
+    let script = "$level = 'outer'\n\
+                  function Peek($level) { Get-Variable level -Scope 0 -ValueOnly }\n\
+                  Peek 'inner'";
+    assert_eq!(
+        eval_with_cmdlets(script).unwrap(),
+        Value::String("inner".into())
+    );
+    let script = "$level = 'outer'\n\
+                  function Peek { Get-Variable level -Scope Local -ValueOnly -ErrorAction Stop }\n\
+                  Peek";
+    let error = eval_with_cmdlets(script).unwrap_err();
+    assert_eq!(error.error_id(), "VariableNotFound,Get-Variable");
+
+    let script = "function Deep { Set-Variable 'x' 1 -Scope 5 }\nDeep";
+    let error = eval_with_cmdlets(script).unwrap_err();
+    assert_eq!(error.error_id(), "ArgumentOutOfRange");
+}
+
+#[test]
+fn test_closures_as_pipeline_callbacks() {
+    // Each filter keeps the limit it was made with
+    let script = "function New-Filter($limit) { { $_ -gt $limit }.GetNewClosure() }\n\
+                  $overTwo = New-Filter 2\n\
+                  $overFour = New-Filter 4\n\
+                  $limit = 0\n\
+                  (@(1, 3, 5) | Where-Object $overTwo | Measure-Object).Count\n\
+                  @(1, 3, 5) | Where-Object $overFour";
+    assert_eq!(
+        eval_with_cmdlets(script).unwrap(),
+        Value::Array(vec![Value::Number(2.0), Value::Number(5.0)].into())
+    );
+}
+
 #[test]
 fn test_week16_get_content_reads_empty_file() {
     let temp_dir = TempDir::new().unwrap();
@@ -544,7 +842,7 @@ fn test_week16_get_content_reads_empty_file() {
     let result = eval_with_cmdlets(&code).unwrap();
 
     match result {
-        Value::Array(items) => assert!(items.is_empty()),
+        Value::Array(items) => assert!(items.read().is_empty()),
         Value::Null => {
             // Current evaluator behavior: when a cmdlet produces no output values,
             // the statement evaluates to $null.
@@ -562,7 +860,7 @@ fn test_week16_get_content_nonexistent_file_errors() {
     let file_path = temp_dir.path().join("does_not_exist.txt");
 
     let path_str = file_path.to_string_lossy().replace('\\', "/");
-    let code = format!("Get-Content '{}'", path_str);
+    let code = format!("Get-Content '{}' -ErrorAction Stop", path_str);
     let result = eval_with_cmdlets(&code);
 
     assert!(result.is_err());
@@ -590,12 +888,10 @@ fn test_week16_get_content_with_encoding_unicode_utf16le() {
     let result = eval_with_cmdlets(&code).unwrap();
 
     if let Value::Array(items) = result {
+        let items = items.read();
         assert_eq!(
-            items,
-            vec![
-                Value::String("one".to_string()),
-                Value::String("two".to_string())
-            ]
+            *items,
+            vec![Value::String("one".into()), Value::String("two".into())]
         );
     } else {
         panic!("Expected array result from Get-Content, got {:?}", result);
@@ -616,9 +912,10 @@ fn test_week16_get_content_with_select_object_skip_first() {
     let result = eval_with_cmdlets(&code).unwrap();
 
     if let Value::Array(items) = result {
+        let items = items.read();
         assert_eq!(items.len(), 2);
-        assert_eq!(items[0], Value::String("three".to_string()));
-        assert_eq!(items[1], Value::String("four".to_string()));
+        assert_eq!(items[0], Value::String("three".into()));
+        assert_eq!(items[1], Value::String("four".into()));
     } else {
         panic!("Expected array result");
     }
@@ -640,10 +937,11 @@ fn test_week16_set_content_writes_string_value() {
 
     match result {
         Value::Array(items) => {
-            assert_eq!(items, vec![Value::String("Hello".to_string())]);
+            let items = items.read();
+            assert_eq!(*items, vec![Value::String("Hello".into())]);
         }
         Value::String(s) => {
-            assert_eq!(s, "Hello".to_string());
+            assert_eq!(&*s, "Hello");
         }
         other => panic!(
             "Expected string or array result from Get-Content, got {:?}",
@@ -665,12 +963,13 @@ fn test_week16_set_content_writes_pipeline_values() {
     let result = eval_with_cmdlets(&code).unwrap();
 
     if let Value::Array(items) = result {
+        let items = items.read();
         assert_eq!(
-            items,
+            *items,
             vec![
-                Value::String("a".to_string()),
-                Value::String("b".to_string()),
-                Value::String("c".to_string())
+                Value::String("a".into()),
+                Value::String("b".into()),
+                Value::String("c".into())
             ]
         );
     } else {
@@ -696,8 +995,37 @@ fn test_week16_test_path_true_false() {
     let code = format!("Test-Path '{}'\nTest-Path '{}'", exists_str, missing_str);
     let result = eval_with_cmdlets(&code).unwrap();
 
-    // The statement result is the last cmdlet output.
-    assert_eq!(result, Value::Boolean(false));
+    // Each statement's output is part of the result.
+    assert_eq!(
+        result,
+        Value::Array(vec![Value::Boolean(true), Value::Boolean(false)].into())
+    );
+}
+
+#[test]
+fn test_test_path_negated_in_if_condition() {
+    let temp_dir = TempDir::new().unwrap();
+    let missing_str = temp_dir
+        .path()
+        .join("missing.txt")
+        .to_string_lossy()
+        .replace('\\', "/");
+
+    let code = format!(
+        "if (!(Test-Path '{0}')) {{ \"missing\" }}\nif (-not (Test-Path '{0}')) {{ \"still missing\" }}",
+        missing_str
+    );
+    let result = eval_with_cmdlets(&code).unwrap();
+    assert_eq!(
+        result,
+        Value::Array(
+            vec![
+                Value::String("missing".into()),
+                Value::String("still missing".into())
+            ]
+            .into()
+        )
+    );
 }
 
 #[test]
@@ -714,12 +1042,84 @@ fn test_week16_new_item_creates_file_and_directory() {
         dir_str, file_str, file_str
     );
     let result = eval_with_cmdlets(&code).unwrap();
-    assert_eq!(result, Value::Boolean(true));
+    // Both new items are written out ahead of Test-Path's answer
+    let Value::Array(items) = result else {
+        panic!("expected the output of all three statements");
+    };
+    let items = items.read();
+    assert_eq!(items.len(), 3);
+    assert_eq!(items[2], Value::Boolean(true));
 
     assert!(dir_path.exists() && dir_path.is_dir());
     assert!(file_path.exists() && file_path.is_file());
 }
 
+#[test]
+fn test_file_system_errors_carry_category_id_and_target() {
+    let temp_dir = TempDir::new().unwrap();
+    let file_path = temp_dir.path().join("f.txt");
+    let missing_path = temp_dir.path().join("missing.txt");
+    fs::write(&file_path, "x").unwrap();
+
+    let file_str = file_path.to_string_lossy().replace('\\', "/");
+    let missing_str = missing_path.to_string_lossy().replace('\\', "/");
+
+    let exists = eval_with_cmdlets(&format!("New-Item '{}' -Type File", file_str)).unwrap_err();
+    assert_eq!(exists.category(), ErrorCategory::ResourceExists);
+    assert_eq!(exists.error_id(), "ItemExists,New-Item");
+
+    let missing =
+        eval_with_cmdlets(&format!("Get-Content '{}' -ErrorAction Stop", missing_str)).unwrap_err();
+    assert_eq!(missing.category(), ErrorCategory::ObjectNotFound);
+    assert_eq!(missing.error_id(), "PathNotFound,Get-Content");
+    assert_eq!(
+        missing.target(),
+        Some(missing_path.display().to_string().as_str())
+    );
+    assert_eq!(missing.io_kind(), Some(std::io::ErrorKind::NotFound));
+
+    let bad_depth = eval_with_cmdlets("Get-ChildItem -Depth 'deep'").unwrap_err();
+    assert_eq!(bad_depth.category(), ErrorCategory::InvalidArgument);
+    assert_eq!(bad_depth.error_id(), "InvalidDepth,Get-ChildItem");
+}
+
+#[test]
+fn test_missing_paths_are_non_terminating_errors() {
+    let temp_dir = TempDir::new().unwrap();
+    let missing = temp_dir
+        .path()
+        .join("missing.txt")
+        .to_string_lossy()
+        .replace('\\', "/");
+
+    for cmdlet in ["Get-Content", "Get-ChildItem", "Remove-Item"] {
+        let id = format!("PathNotFound,{cmdlet}");
+
+        // SilentlyContinue: the script goes on, and $Error still has it
+        let code = format!(
+            "{cmdlet} '{missing}' -ErrorAction SilentlyContinue\n\
+             'after ' + $Error[0].FullyQualifiedErrorId"
+        );
+        assert_eq!(
+            eval_with_cmdlets(&code).unwrap(),
+            Value::String(format!("after {id}").into())
+        );
+
+        // Continue: the error is shown and recorded in $Error
+        let code = format!("{cmdlet} '{missing}'\n$Error[0].FullyQualifiedErrorId");
+        assert_eq!(
+            eval_with_cmdlets(&code).unwrap(),
+            Value::String(id.clone().into())
+        );
+
+        // Stop: the error ends the script
+        let code = format!("{cmdlet} '{missing}' -ErrorAction Stop; 'not reached'");
+        let error = eval_with_cmdlets(&code).unwrap_err();
+        assert_eq!(error.error_id(), id);
+        assert_eq!(error.category(), ErrorCategory::ObjectNotFound);
+    }
+}
+
 #[test]
 fn test_week16_remove_item_deletes_file() {
     let temp_dir = TempDir::new().unwrap();
@@ -758,6 +1158,7 @@ fn test_week17_sort_object_numbers() {
     let result = eval_with_cmdlets("@(3,1,4,1,5,9) | Sort-Object").unwrap();
 
     if let Value::Array(items) = result {
+        let items = items.read();
         let nums: Vec<f64> = items.iter().filter_map(|v| v.to_number()).collect();
         assert_eq!(nums, vec![1.0, 1.0, 3.0, 4.0, 5.0, 9.0]);
     } else {
@@ -778,6 +1179,7 @@ fn test_week17_sort_object_by_property_descending() {
 
     let result = eval_with_cmdlets(code).unwrap();
     if let Value::Array(items) = result {
+        let items = items.read();
         let names: Vec<String> = items
             .iter()
             .filter_map(|v| v.get_property("Name"))
@@ -792,11 +1194,41 @@ fn test_week17_sort_object_by_property_descending() {
     }
 }
 
+#[test]
+fn test_assignment_from_pipeline_collects_output() {
+    let result = eval_with_cmdlets("$sorted = @(3,1,2) |\n    Sort-Object\n$sorted").unwrap();
+    assert_eq!(
+        result,
+        Value::Array(vec![Value::Number(1.0), Value::Number(2.0), Value::Number(3.0)].into())
+    );
+
+    // A single result is stored unwrapped
+    let result =
+        eval_with_cmdlets("$one = @(3,1,2) | Sort-Object | Select-Object -First 1\n$one").unwrap();
+    assert_eq!(result, Value::Number(1.0));
+}
+
+#[test]
+fn test_assignment_from_get_childitem_pipeline() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::write(temp_dir.path().join("b.txt"), "b").unwrap();
+    fs::write(temp_dir.path().join("a.txt"), "a").unwrap();
+    let dir_str = temp_dir.path().to_string_lossy().replace('\\', "/");
+
+    let code = format!(
+        "$files = Get-ChildItem '{}' | Sort-Object Name\n$files[0].Name",
+        dir_str
+    );
+    let result = eval_with_cmdlets(&code).unwrap();
+    assert_eq!(result, Value::String("a.txt".into()));
+}
+
 #[test]
 fn test_week17_group_object_numbers() {
     let result = eval_with_cmdlets("@(1,2,2,3,3,3) | Group-Object").unwrap();
 
     if let Value::Array(items) = result {
+        let items = items.read();
         assert_eq!(items.len(), 3);
         let names: Vec<String> = items
             .iter()
@@ -817,6 +1249,7 @@ fn test_week17_group_object_as_hash_table() {
     let result = eval_with_cmdlets("@('a','b','b') | Group-Object -AsHashTable true").unwrap();
 
     if let Value::Object(map) = result {
+        let map = map.read();
         assert!(map.contains_key("a"));
         assert!(map.contains_key("b"));
 
@@ -826,3 +1259,355 @@ fn test_week17_group_object_as_hash_table() {
         panic!("Expected object hashtable result, got {:?}", result);
     }
 }
+
+#[test]
+fn test_sort_and_group_parallel_switch() {
+    let sorted = eval_with_cmdlets("@(3,1,2) | Sort-Object -Parallel:$false").unwrap();
+    assert_eq!(
+        sorted,
+        eval_with_cmdlets("@(3,1,2) | Sort-Object -Parallel").unwrap()
+    );
+    assert_eq!(
+        sorted,
+        Value::Array(vec![Value::Number(1.0), Value::Number(2.0), Value::Number(3.0)].into())
+    );
+
+    let grouped = eval_with_cmdlets("@('b','a','b') | Group-Object -Parallel:$false").unwrap();
+    assert_eq!(
+        grouped,
+        eval_with_cmdlets("@('b','a','b') | Group-Object -Parallel:$true").unwrap()
+    );
+
+    assert!(eval_with_cmdlets("@(1) | Sort-Object -Parallel:maybe").is_err());
+    // A switch takes booleans, not the words for them
+    assert!(eval_with_cmdlets("@(1) | Sort-Object -Parallel:false").is_err());
+    assert!(eval_with_cmdlets("@(1) | Group-Object -Parallel:true").is_err());
+    assert_eq!(
+        eval_with_cmdlets("@(3,1,2) | Sort-Object -Parallel:$null").unwrap(),
+        sorted
+    );
+}
+
+#[test]
+fn test_new_object_constructs_class_instance() {
+    let code = r#"
+class Person { [string]$Name; [int]$Age; Person($n, $a) { $this.Name = $n; $this.Age = $a } }
+$p = New-Object Person -ArgumentList @("Ann", 30)
+$p.Name + " " + $p.Age
+"#;
+    let result = eval_with_cmdlets(code).unwrap();
+    assert_eq!(result, Value::String("Ann 30".into()));
+}
+
+#[test]
+fn test_keywords_as_arguments_keys_and_members() {
+    let strings = |items: &[&str]| {
+        Value::Array(
+            items
+                .iter()
+                .map(|s| Value::String((*s).into()))
+                .collect::<Vec<_>>()
+                .into(),
+        )
+    };
+
+    // Command-mode bare words
+    assert_eq!(
+        eval_with_cmdlets("Write-Output class Enum filter try").unwrap(),
+        strings(&["class", "Enum", "filter", "try"])
+    );
+
+    // Hashtable keys, and members read back by name
+    let code = "$h = @{class = 1; Foreach = 2; throw = 3}\n$h.class + $h.foreach + $h.Throw";
+    assert_eq!(eval_with_cmdlets(code).unwrap(), Value::Number(6.0));
+
+    let code = "$o = [pscustomobject]@{Catch = 'c'; finally = 'f'}\n$o.catch + $o.Finally";
+    assert_eq!(eval_with_cmdlets(code).unwrap(), Value::String("cf".into()));
+
+    // At the start of a statement they are still keywords
+    let code = "try { throw 'x' } catch { 'caught' }";
+    assert_eq!(
+        eval_with_cmdlets(code).unwrap(),
+        Value::String("caught".into())
+    );
+}
+
+#[test]
+fn test_new_object_psobject_with_properties() {
+    let result =
+        eval_with_cmdlets("$o = New-Object PSObject -Property @{Name = 'Bob'}\n$o.Name").unwrap();
+    assert_eq!(result, Value::String("Bob".into()));
+}
+
+#[test]
+fn test_new_object_unknown_type() {
+    let err = eval_with_cmdlets("New-Object Missing").unwrap_err();
+    assert!(err.to_string().contains("Unable to find type [Missing]"));
+}
+
+#[test]
+fn test_get_date_format_matches_f_operator_and_tostring() {
+    let code = r#"
+$d = Get-Date '2024-03-05 14:07:09'
+$a = Get-Date -Date $d -Format 'yyyy-MM-dd HH:mm'
+$b = '{0:yyyy-MM-dd HH:mm}' -f $d
+$c = $d.ToString('yyyy-MM-dd HH:mm')
+"$a|$b|$c"
+"#;
+    assert_eq!(
+        eval_with_cmdlets(code).unwrap(),
+        Value::String("2024-03-05 14:07|2024-03-05 14:07|2024-03-05 14:07".into())
+    );
+    assert_eq!(
+        eval_with_cmdlets("'3/5/2024' | Get-Date -Year 2025 -Format D").unwrap(),
+        Value::String("Wednesday, March 5, 2025".into())
+    );
+}
+
+#[test]
+fn test_get_random_set_seed_is_reproducible() {
+    let code = r#"
+$first = @('a', 'b', 'c', 'd', 'e') | Get-Random -Shuffle -SetSeed 7
+$pick = Get-Random -Minimum 1 -Maximum 100 -Count 3
+"$first / $pick"
+"#;
+    let a = eval_with_cmdlets(code).unwrap();
+    assert_eq!(a, eval_with_cmdlets(code).unwrap());
+
+    let weighted = eval_with_cmdlets(
+        "@(@{Name='x'; Odds=0}, @{Name='y'; Odds=5}) | Get-Random -Weight Odds -Count 2 | ForEach-Object { $_.Name }",
+    )
+    .unwrap();
+    assert_eq!(weighted, Value::String("y".into()));
+}
+
+#[test]
+fn test_format_list_and_custom_render_nested_values() {
+    let list = eval_with_cmdlets(
+        "$FormatEnumerationLimit = 2; @{Name='web'; Ports=@(80, 443, 8080)} | Format-List",
+    )
+    .unwrap();
+    assert_eq!(
+        list,
+        Value::Array(
+            vec![
+                Value::String("Name  : web".into()),
+                Value::String("Ports : {80, 443…}".into()),
+            ]
+            .into()
+        )
+    );
+
+    let custom = eval_with_cmdlets(
+        "@{Name='web'; Tls=@{On=1}} | Format-Custom -Depth 1 | Select-Object -Last 2",
+    )
+    .unwrap();
+    assert_eq!(
+        custom,
+        Value::Array(vec![Value::String("    @{…}".into()), Value::String("}".into())].into())
+    );
+}
+
+#[test]
+fn test_json_round_trip_keeps_text_and_property_order() {
+    let code = r#"
+$rows = @(@{Zeta=1; Alpha='x'; When=[datetime]'2024-03-05 14:07:09'}, @{Zeta=2; Alpha='y'; When=$null})
+$json = $rows | ConvertTo-Json -Compress
+$back = $json | ConvertFrom-Json | ConvertTo-Json -Compress
+"$json|$back"
+"#;
+    let json = r#"[{"Zeta":1,"Alpha":"x","When":"2024-03-05T14:07:09"},{"Zeta":2,"Alpha":"y","When":null}]"#;
+    assert_eq!(
+        eval_with_cmdlets(code).unwrap(),
+        Value::String(format!("{}|{}", json, json).into())
+    );
+
+    let single = eval_with_cmdlets("@{A=1} | ConvertTo-Json -AsArray -Compress").unwrap();
+    assert_eq!(single, Value::String(r#"[{"A":1}]"#.into()));
+    let deep = eval_with_cmdlets("@{A=@{B=@{C=1}}} | ConvertTo-Json -Depth 1 -Compress").unwrap();
+    assert_eq!(deep, Value::String(r#"{"A":{"B":"@{C=1}"}}"#.into()));
+}
+
+#[test]
+fn test_select_string_takes_files_from_get_childitem() {
+    let temp_dir = TempDir::new().unwrap();
+    let temp_path = temp_dir.path();
+    fs::write(temp_path.join("a.log"), "ok\nfail 1\nfail 2\n").unwrap();
+    fs::write(temp_path.join("b.log"), "ok\n").unwrap();
+    fs::create_dir(temp_path.join("sub")).unwrap();
+
+    let path_str = temp_path.to_string_lossy().replace('\\', "/");
+    let code = format!(
+        "Get-ChildItem -Path '{}' | Select-String 'fail' -List | ForEach-Object {{ $_.Filename + ':' + $_.LineNumber }}",
+        path_str
+    );
+    assert_eq!(
+        eval_with_cmdlets(&code).unwrap(),
+        Value::String("a.log:2".into())
+    );
+
+    let code = format!(
+        "Get-ChildItem -Path '{}' -Filter 'b*' | Select-String 'fail' -Quiet",
+        path_str
+    );
+    assert_eq!(eval_with_cmdlets(&code).unwrap(), Value::Boolean(false));
+}
+
+#[test]
+fn test_error_action_preference_stop_makes_errors_catchable() {
+    // Non-terminating by default: the script goes on
+    let result = eval_with_cmdlets("Write-Error 'soft' -ErrorAction SilentlyContinue; 'after'");
+    assert_eq!(result.unwrap(), Value::String("after".into()));
+
+    let result = eval_with_cmdlets(
+        "$ErrorActionPreference = 'Stop'\n\
+         try { Get-Variable missing; 'not reached' }\n\
+         catch { $_.FullyQualifiedErrorId + ': ' + $_.Exception.Message }",
+    );
+    assert_eq!(
+        result.unwrap(),
+        Value::String(
+            "VariableNotFound,Get-Variable: Cannot find a variable with the name 'missing'.".into()
+        )
+    );
+
+    // -ErrorAction wins over the preference
+    let result = eval_with_cmdlets(
+        "$ErrorActionPreference = 'Stop'\n\
+         $out = @('start')\n\
+         try { Write-Error 'y' -ErrorAction Ignore; $out = $out + 'ignored'; Write-Error 'z' }\n\
+         catch { $out = $out + ('caught ' + $_) }\n\
+         $out",
+    );
+    assert_eq!(
+        result.unwrap(),
+        Value::Array(
+            vec![
+                Value::String("start".into()),
+                Value::String("ignored".into()),
+                Value::String("caught z".into()),
+            ]
+            .into()
+        )
+    );
+}
+
+#[test]
+fn test_get_help_shows_comment_based_help() {
+    let script = "<#\n\
+                  .SYNOPSIS\n    Greets someone.\n\
+                  .PARAMETER Name\n    Who to greet.\n\
+                  .EXAMPLE\n    Greet World\n\
+                  #>\n\
+                  function Greet($Name, $Times) { \"Hello $Name\" }\n";
+    let help = |query: &str| eval_with_cmdlets(&format!("{}{}", script, query)).unwrap();
+
+    assert_eq!(
+        help("(Get-Help Greet).Synopsis"),
+        Value::String("Greets someone.".into())
+    );
+    assert_eq!(
+        help("(Get-Help Greet).Syntax"),
+        Value::String("Greet [-Name] [-Times]".into())
+    );
+    assert_eq!(
+        help("(Get-Help Greet -Parameter name).Description"),
+        Value::String("Who to greet.".into())
+    );
+    assert_eq!(
+        help("(Get-Help Greet).Parameters[1].Description"),
+        Value::Null
+    );
+    assert_eq!(
+        help("(Get-Help -Examples Greet).Examples[0]"),
+        Value::String("Greet World".into())
+    );
+    // A cmdlet has no help text, but still its syntax
+    assert_eq!(
+        help("(Get-Help Get-Variable).Synopsis"),
+        Value::String("Get-Variable [-Name] [-Scope] [-ValueOnly]".into())
+    );
+
+    let error = eval_with_cmdlets("Get-Help Missing").unwrap_err();
+    assert_eq!(error.error_id(), "HelpNotFound,Get-Help");
+    assert_eq!(error.category(), ErrorCategory::ObjectNotFound);
+}
+
+#[test]
+fn test_get_error_shows_the_latest_error_in_full() {
+    let mut session = pwsh_runtime::SessionBuilder::new()
+        .cmdlets(pwsh_cmdlets::register_all)
+        .build();
+    session
+        .invoke_script("try {\n    $x = 1\n    throw 'boom'\n} catch {}")
+        .unwrap();
+    let lines: Vec<String> = session
+        .invoke("Get-Error")
+        .unwrap()
+        .iter()
+        .map(|line| line.to_string())
+        .collect();
+    assert!(lines.contains(&"    Message : boom".to_string()));
+    assert!(lines.contains(&"FullyQualifiedErrorId : boom".to_string()));
+    assert!(lines.contains(&"    PositionMessage  : At line:3 char:5".to_string()));
+    assert!(!lines.iter().any(|line| line.contains("ErrorIndex")));
+
+    // -Newest shows more of them, each under its index
+    session
+        .invoke("Write-Error 'soft' -ErrorAction SilentlyContinue")
+        .unwrap();
+    let text = session
+        .invoke("Get-Error -Newest 2")
+        .unwrap()
+        .iter()
+        .map(|line| line.to_string())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let soft = text.find("Message : soft").unwrap();
+    let boom = text.find("Message : boom").unwrap();
+    assert!(text.find("ErrorIndex: 0").unwrap() < soft);
+    assert!(soft < text.find("ErrorIndex: 1").unwrap());
+    assert!(text.find("ErrorIndex: 1").unwrap() < boom);
+
+    let error = session.invoke("'text' | Get-Error").unwrap_err();
+    assert!(error.to_string().contains("not text"));
+}
+
+#[test]
+fn test_get_pscallstack_lists_the_calls_running() {
+    let mut session = pwsh_runtime::SessionBuilder::new()
+        .cmdlets(pwsh_cmdlets::register_all)
+        .build();
+    let script = "function Inner($n, $label) {\n    Get-PSCallStack\n}\nfunction Outer { Inner 5 'five' }\nOuter";
+    let frames = session.invoke_file("deploy.ps1", script).unwrap();
+    let column = |name: &str| -> Vec<String> {
+        frames
+            .iter()
+            .map(|frame| frame.get_property(name).unwrap().to_string())
+            .collect()
+    };
+    assert_eq!(column("Command"), ["Inner", "Outer", "<ScriptBlock>"]);
+    assert_eq!(column("Arguments"), ["{n=5, label=five}", "{}", "{}"]);
+    assert_eq!(
+        column("Location"),
+        [
+            "deploy.ps1: line 2",
+            "deploy.ps1: line 4",
+            "deploy.ps1: line 5"
+        ]
+    );
+
+    let lines = session.evaluator().format_output(&frames);
+    assert_eq!(
+        lines[1].trim_end(),
+        "Command       Arguments         Location"
+    );
+
+    // At the prompt, just the prompt's own frame
+    let frames = session.invoke("Get-PSCallStack").unwrap();
+    assert_eq!(frames.len(), 1);
+    assert_eq!(
+        frames[0].get_property("Location"),
+        Some(Value::String("<No file>: line 1".into()))
+    );
+}
@@ -34,15 +34,14 @@ $processes | Select-Object Name, CPU
 
     // Verify the result structure
     if let Value::Array(items) = result {
+        let items = items.read();
         assert_eq!(items.len(), 5, "Should return all 5 process objects");
 
         // Verify first object (chrome)
         if let Value::Object(props) = &items[0] {
+            let props = props.read();
             assert_eq!(props.len(), 2, "Should have exactly 2 properties");
-            assert_eq!(
-                props.get("Name"),
-                Some(&Value::String("chrome".to_string()))
-            );
+            assert_eq!(props.get("Name"), Some(&Value::String("chrome".into())));
             assert_eq!(props.get("CPU"), Some(&Value::Number(45.2)));
             assert!(props.get("Id").is_none(), "Should not have Id");
             assert!(
@@ -55,13 +54,15 @@ $processes | Select-Object Name, CPU
 
         // Verify second object (code)
         if let Value::Object(props) = &items[1] {
-            assert_eq!(props.get("Name"), Some(&Value::String("code".to_string())));
+            let props = props.read();
+            assert_eq!(props.get("Name"), Some(&Value::String("code".into())));
             assert_eq!(props.get("CPU"), Some(&Value::Number(23.1)));
         }
 
         // Verify all objects only have Name and CPU
         for (i, item) in items.iter().enumerate() {
             if let Value::Object(props) = item {
+                let props = props.read();
                 assert_eq!(props.len(), 2, "Item {} should have 2 properties", i);
                 assert!(props.contains_key("Name"), "Item {} should have Name", i);
                 assert!(props.contains_key("CPU"), "Item {} should have CPU", i);
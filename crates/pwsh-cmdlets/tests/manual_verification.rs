@@ -24,6 +24,7 @@ fn test_case_insensitive_property_selection() {
 
     // Verify the property was found despite different case
     if let Value::Object(props) = result {
+        let props = props.read();
         assert_eq!(props.len(), 1, "Should have exactly 1 property");
         // The property should be found (case-insensitive)
         assert!(props.contains_key("CPu"), "Should have CPu property");
@@ -63,11 +64,13 @@ fn verify_select_object_issue_fix() {
 
     // Verify the result is correct
     if let Value::Array(items) = result {
+        let items = items.read();
         assert_eq!(items.len(), 5, "Should have 5 process objects");
 
         // Check each object only has Name and CPU properties
-        for item in items {
+        for item in items.iter() {
             if let Value::Object(props) = item {
+                let props = props.read();
                 assert_eq!(
                     props.len(),
                     2,
@@ -112,9 +115,11 @@ fn test_select_object_single_property_bare_word() {
     let result = evaluator.eval(program).unwrap();
 
     if let Value::Array(items) = result {
+        let items = items.read();
         assert_eq!(items.len(), 2);
-        for item in items {
+        for item in items.iter() {
             if let Value::Object(props) = item {
+                let props = props.read();
                 assert_eq!(props.len(), 1);
                 assert!(props.contains_key("Name"));
             }
@@ -147,6 +152,7 @@ fn test_select_object_three_properties() {
 
     // When pipeline returns a single item, it's not wrapped in an array
     if let Value::Object(props) = result {
+        let props = props.read();
         assert_eq!(props.len(), 3);
         assert!(props.contains_key("A"));
         assert!(props.contains_key("B"));
@@ -181,9 +187,11 @@ fn test_bare_words_with_first_parameter() {
     let result = evaluator.eval(program).unwrap();
 
     if let Value::Array(items) = result {
+        let items = items.read();
         assert_eq!(items.len(), 2, "Should only return first 2 items");
-        for item in items {
+        for item in items.iter() {
             if let Value::Object(props) = item {
+                let props = props.read();
                 assert_eq!(props.len(), 1);
                 assert!(props.contains_key("Name"));
                 assert!(!props.contains_key("Value"));
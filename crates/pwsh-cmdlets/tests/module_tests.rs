@@ -0,0 +1,185 @@
+/// Modules load with Import-Module, or on first use of one of their commands: cmdlet packs from other crates, and script modules on the module path
+use pwsh_runtime::{Cmdlet, CmdletContext, Evaluator, RuntimeError, SessionBuilder, Value};
+use std::fs;
+use std::path::Path;
+
+/// Stand-in for a third-party cmdlet
+struct GetGreetingCmdlet;
+
+impl Cmdlet for GetGreetingCmdlet {
+    fn name(&self) -> &str {
+        "Get-Greeting"
+    }
+
+    fn execute(
+        &self,
+        context: CmdletContext,
+        _evaluator: &mut Evaluator,
+    ) -> Result<Vec<Value>, RuntimeError> {
+        let name = context
+            .get_argument(0)
+            .map(|v| v.to_string())
+            .unwrap_or_else(|| "World".to_string());
+        Ok(vec![Value::String(format!("Hello {}", name).into())])
+    }
+}
+
+pwsh_runtime::cmdlet_pack!(GreetingPack, "Greetings", [GetGreetingCmdlet]);
+
+#[test]
+fn test_import_module_makes_pack_cmdlets_available() {
+    let mut session = SessionBuilder::new()
+        .cmdlets(pwsh_cmdlets::register_all)
+        .module(GreetingPack)
+        .build();
+
+    assert!(!session.evaluator().registry().contains("Get-Greeting"));
+    assert_eq!(
+        session
+            .invoke("Import-Module Greetings; Get-Greeting 'Ada'")
+            .unwrap(),
+        vec![Value::String("Hello Ada".into())]
+    );
+    // Importing again is harmless
+    session.invoke("Import-Module -Name greetings").unwrap();
+    assert_eq!(
+        session.invoke("Get-Greeting").unwrap(),
+        vec![Value::String("Hello World".into())]
+    );
+}
+
+#[test]
+fn test_import_module_rejects_unknown_modules() {
+    let mut session = SessionBuilder::new()
+        .cmdlets(pwsh_cmdlets::register_all)
+        .module(GreetingPack)
+        .build();
+
+    let error = session
+        .invoke("Import-Module @('Greetings', 'Nope')")
+        .unwrap_err();
+    assert!(error.to_string().contains("'Nope' was not loaded"));
+    assert!(session.invoke("Import-Module").is_err());
+}
+
+fn write(path: &Path, text: &str) {
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, text).unwrap();
+}
+
+/// A session whose configuration puts `dir` on the module path
+fn session_with_modules(dir: &Path) -> pwsh_runtime::Session {
+    SessionBuilder::new()
+        .cmdlets(pwsh_cmdlets::register_all)
+        .module(GreetingPack)
+        .configuration(pwsh_runtime::Configuration {
+            module_paths: vec![dir.to_path_buf()],
+            ..Default::default()
+        })
+        .build()
+}
+
+#[test]
+fn test_commands_load_their_module_on_first_use() {
+    let dir = tempfile::TempDir::new().unwrap();
+    write(
+        &dir.path().join("Tools/Tools.psm1"),
+        "$loaded = 'module scope'\nfunction Get-Tool($name) { \"tool $name\" }\nfunction Get-ToolCount { 2 }",
+    );
+    let mut session = session_with_modules(dir.path());
+
+    // A cmdlet pack's command loads the pack
+    assert_eq!(
+        session.invoke("Get-Greeting 'Ada'").unwrap(),
+        vec![Value::String("Hello Ada".into())]
+    );
+    // A script module's function loads the module, with its other functions
+    assert_eq!(
+        session.invoke("Get-Tool hammer; Get-ToolCount").unwrap(),
+        vec![Value::String("tool hammer".into()), Value::Number(2.0)]
+    );
+    // The module's own variables stay in the module
+    assert_eq!(session.invoke("$loaded").unwrap(), vec![]);
+
+    let error = session.invoke("Get-Nothing").unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("'Get-Nothing' is not recognized"));
+}
+
+#[test]
+fn test_auto_loading_can_be_turned_off() {
+    let dir = tempfile::TempDir::new().unwrap();
+    write(
+        &dir.path().join("Tools.psm1"),
+        "function Get-Tool { 'tool' }",
+    );
+    let mut session = session_with_modules(dir.path());
+
+    session
+        .invoke("$PSModuleAutoLoadingPreference = 'None'")
+        .unwrap();
+    assert!(session.invoke("Get-Tool").is_err());
+    assert!(session.invoke("Get-Greeting").is_err());
+
+    // Import-Module still finds them
+    assert_eq!(
+        session.invoke("Import-Module tools; Get-Tool").unwrap(),
+        vec![Value::String("tool".into())]
+    );
+}
+
+#[test]
+fn test_import_module_by_path_and_requires() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let file = dir.path().join("lib/Local.psm1");
+    write(&file, "function Get-Local { 'local' }");
+    write(
+        &dir.path().join("Net/2.0.0/Net.psm1"),
+        "function Get-Net { 'v2' }",
+    );
+    write(
+        &dir.path().join("Net/1.0.0/Net.psm1"),
+        "function Get-Net { 'v1' }",
+    );
+    let mut session = session_with_modules(dir.path());
+
+    assert_eq!(
+        session
+            .invoke(&format!("Import-Module '{}'; Get-Local", file.display()))
+            .unwrap(),
+        vec![Value::String("local".into())]
+    );
+    // #Requires -Modules finds script modules too, newest version first
+    assert_eq!(
+        session
+            .invoke_script("#Requires -Modules Net\nGet-Net")
+            .unwrap(),
+        vec![Value::String("v2".into())]
+    );
+}
+
+#[test]
+fn test_a_module_that_fails_to_load_keeps_the_cause() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let file = dir.path().join("Bad.psm1");
+    write(&file, "throw 'broken'");
+    let mut session = session_with_modules(dir.path());
+
+    let error = session
+        .invoke(&format!("Import-Module '{}'", file.display()))
+        .unwrap_err();
+    assert!(error.to_string().contains("could not be loaded: broken"));
+    assert_eq!(
+        session
+            .invoke("$error[0].FullyQualifiedErrorId; $error[0].Exception.InnerException.Message")
+            .unwrap(),
+        vec![
+            Value::String("ModuleLoadFailed".into()),
+            Value::String("broken".into())
+        ]
+    );
+    let lines = session.invoke("Get-Error").unwrap();
+    assert!(lines.contains(&Value::String("    InnerException :".into())));
+    assert!(lines.contains(&Value::String("        Message : broken".into())));
+}
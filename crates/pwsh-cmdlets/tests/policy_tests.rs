@@ -0,0 +1,69 @@
+/// The execution policy: set by the host or by Set-ExecutionPolicy, checked before script files run
+use pwsh_runtime::{
+    ErrorCategory, ExecutionPolicy, ScriptTrust, SessionBuilder, SessionError, Value,
+};
+use std::path::Path;
+
+/// Scripts under `downloads/` are remote; none is signed
+struct Downloads;
+
+impl ScriptTrust for Downloads {
+    fn is_remote(&self, path: &Path) -> bool {
+        path.starts_with("downloads")
+    }
+}
+
+fn category(error: SessionError) -> ErrorCategory {
+    match error {
+        SessionError::Runtime(error) => error.category(),
+        other => panic!("Expected a runtime error, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_set_execution_policy_decides_which_files_run() {
+    let mut session = SessionBuilder::new()
+        .cmdlets(pwsh_cmdlets::register_all)
+        .script_trust(Downloads)
+        .build();
+    assert_eq!(
+        session.invoke("Get-ExecutionPolicy").unwrap(),
+        vec![Value::String("Unrestricted".into())]
+    );
+    assert!(session.invoke_file("downloads/a.ps1", "1").is_ok());
+
+    session.invoke("Set-ExecutionPolicy RemoteSigned").unwrap();
+    assert_eq!(
+        session.invoke_file("local.ps1", "1").unwrap(),
+        vec![Value::Number(1.0)]
+    );
+    let error = session.invoke_file("downloads/a.ps1", "1").unwrap_err();
+    assert!(error.to_string().contains("is not digitally signed"));
+    assert_eq!(category(error), ErrorCategory::SecurityError);
+
+    session
+        .invoke("Set-ExecutionPolicy -ExecutionPolicy restricted")
+        .unwrap();
+    assert!(session.invoke_file("local.ps1", "1").is_err());
+    // Commands that aren't script files always run
+    assert_eq!(session.invoke("1").unwrap(), vec![Value::Number(1.0)]);
+
+    assert!(session.invoke("Set-ExecutionPolicy Sometimes").is_err());
+}
+
+#[test]
+fn test_a_locked_execution_policy_cannot_be_changed() {
+    let mut session = SessionBuilder::new()
+        .cmdlets(pwsh_cmdlets::register_all)
+        .execution_policy(ExecutionPolicy::AllSigned)
+        .lock_execution_policy()
+        .build();
+
+    let error = session.invoke("Set-ExecutionPolicy Bypass").unwrap_err();
+    assert_eq!(category(error), ErrorCategory::SecurityError);
+    assert_eq!(
+        session.invoke("Get-ExecutionPolicy").unwrap(),
+        vec![Value::String("AllSigned".into())]
+    );
+    assert!(session.invoke_file("script.ps1", "'unsigned'").is_err());
+}
@@ -0,0 +1,133 @@
+/// End-to-end scenarios: common one-liners that chain several cmdlets
+use pwsh_lexer::Lexer;
+use pwsh_parser::Parser;
+use pwsh_runtime::{Evaluator, Value};
+use std::fs;
+use std::path::Path;
+use tempfile::TempDir;
+
+/// Evaluate a script with every built-in cmdlet registered
+fn eval_with_cmdlets(input: &str) -> Result<Value, pwsh_runtime::RuntimeError> {
+    let mut evaluator = Evaluator::new();
+    pwsh_cmdlets::register_all(evaluator.registry_mut());
+
+    let tokens = Lexer::new(input).tokenize().unwrap();
+    let program = Parser::new(tokens).parse().unwrap();
+    evaluator.eval(program)
+}
+
+/// A directory holding files of known sizes:
+/// a.txt (5 bytes), b.log (3), sub/c.txt (2), sub/deeper/d.txt (10)
+fn sample_tree() -> TempDir {
+    let temp_dir = TempDir::new().unwrap();
+    let root = temp_dir.path();
+    fs::create_dir_all(root.join("sub").join("deeper")).unwrap();
+    fs::write(root.join("a.txt"), "hello").unwrap();
+    fs::write(root.join("b.log"), "abc").unwrap();
+    fs::write(root.join("sub").join("c.txt"), "hi").unwrap();
+    fs::write(root.join("sub").join("deeper").join("d.txt"), "0123456789").unwrap();
+    temp_dir
+}
+
+fn quoted(path: &Path) -> String {
+    format!("'{}'", path.display())
+}
+
+#[test]
+fn test_total_directory_size() {
+    let tree = sample_tree();
+    let root = quoted(tree.path());
+
+    let script = format!("Get-ChildItem -Path {} | Measure-Object -Sum Length", root);
+    let measured = eval_with_cmdlets(&script).unwrap();
+    assert_eq!(measured.get_property("Sum"), Some(Value::Number(8.0)));
+    assert_eq!(
+        measured.get_property("Property"),
+        Some(Value::String("Length".into()))
+    );
+
+    let script = format!(
+        "(Get-ChildItem -Path {} -Recurse | Measure-Object -Sum Length).Sum",
+        root
+    );
+    assert_eq!(eval_with_cmdlets(&script).unwrap(), Value::Number(20.0));
+}
+
+#[test]
+fn test_size_of_matching_files() {
+    let tree = sample_tree();
+    let root = quoted(tree.path());
+
+    let script = format!(
+        "$m = Get-ChildItem {} -Recurse -Filter '*.txt' | Measure-Object -Property Length -Sum -Average -Maximum -Minimum\n\
+         @($m.Count, $m.Sum, $m.Average, $m.Maximum, $m.Minimum)",
+        root
+    );
+    assert_eq!(
+        eval_with_cmdlets(&script).unwrap(),
+        Value::Array(
+            vec![
+                Value::Number(3.0),
+                Value::Number(17.0),
+                Value::Number(17.0 / 3.0),
+                Value::Number(10.0),
+                Value::Number(2.0),
+            ]
+            .into()
+        )
+    );
+}
+
+#[test]
+fn test_filter_then_measure() {
+    let tree = sample_tree();
+    let script = format!(
+        "(Get-ChildItem {} -Recurse | Where-Object {{ $_.Length -gt 4 }} | Measure-Object Length -Sum).Sum",
+        quoted(tree.path())
+    );
+    assert_eq!(eval_with_cmdlets(&script).unwrap(), Value::Number(15.0));
+}
+
+#[test]
+fn test_largest_file() {
+    let tree = sample_tree();
+    let script = format!(
+        "(Get-ChildItem {} -Recurse | Sort-Object Length -Descending | Select-Object -First 1).Name",
+        quoted(tree.path())
+    );
+    assert_eq!(
+        eval_with_cmdlets(&script).unwrap(),
+        Value::String("d.txt".into())
+    );
+}
+
+#[test]
+fn test_count_files_by_extension() {
+    let tree = sample_tree();
+    let script = format!(
+        "Get-ChildItem {} -Recurse -Include @('*.txt', '*.log') | Group-Object Extension | ForEach-Object {{ $_.Name + '=' + $_.Count }}",
+        quoted(tree.path())
+    );
+    assert_eq!(
+        eval_with_cmdlets(&script).unwrap(),
+        Value::Array(
+            vec![
+                Value::String(".log=1".into()),
+                Value::String(".txt=3".into())
+            ]
+            .into()
+        )
+    );
+}
+
+#[test]
+fn test_measure_values_from_a_pipeline() {
+    let result = eval_with_cmdlets(
+        "(@(4, 8, 15, 16, 23, 42) | Where-Object { $_ -gt 10 } | Measure-Object -Average).Average",
+    )
+    .unwrap();
+    assert_eq!(result, Value::Number(24.0));
+
+    let result = eval_with_cmdlets("(@() | Measure-Object -Sum).Sum").unwrap();
+    assert_eq!(result, Value::Number(0.0));
+}
@@ -0,0 +1,79 @@
+/*
+ * C interface to the PowerShell interpreter (libpwsh_ffi)
+ *
+ * A session runs scripts and keeps their variables and functions. Each
+ * script's output comes back as an array value. Values and strings returned
+ * by these functions belong to the caller; release them with pwsh_value_free
+ * and pwsh_string_free. The string from pwsh_last_error belongs to the
+ * session and stays valid until the next call on that session.
+ *
+ *     PwshSession *session = pwsh_session_new();
+ *     PwshValue *output = pwsh_eval(session, "@(1, 2) | Sort-Object -Descending");
+ *     if (output == NULL) {
+ *         fprintf(stderr, "%s\n", pwsh_last_error(session));
+ *     } else {
+ *         for (size_t i = 0; i < pwsh_value_len(output); i++) {
+ *             PwshValue *item = pwsh_value_get_index(output, i);
+ *             char *text = pwsh_value_to_string(item);
+ *             printf("%s\n", text);
+ *             pwsh_string_free(text);
+ *             pwsh_value_free(item);
+ *         }
+ *         pwsh_value_free(output);
+ *     }
+ *     pwsh_session_free(session);
+ */
+#ifndef PWSH_H
+#define PWSH_H
+
+#include <stdbool.h>
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct PwshSession PwshSession;
+typedef struct PwshValue PwshValue;
+
+/* Kinds returned by pwsh_value_kind */
+#define PWSH_NULL 0
+#define PWSH_BOOLEAN 1
+#define PWSH_NUMBER 2
+#define PWSH_STRING 3
+#define PWSH_ARRAY 4
+#define PWSH_OBJECT 5 /* hashtables and class instances */
+#define PWSH_OTHER 6  /* dates, enum values, functions and script blocks */
+
+/* Sessions */
+PwshSession *pwsh_session_new(void);
+void pwsh_session_free(PwshSession *session);
+
+/* Run a script: its output as an array value, or NULL on failure */
+PwshValue *pwsh_eval(PwshSession *session, const char *script);
+/* Why the last call on the session failed, or NULL if it succeeded */
+const char *pwsh_last_error(const PwshSession *session);
+
+/* Variables */
+bool pwsh_set_string(PwshSession *session, const char *name, const char *value);
+bool pwsh_set_number(PwshSession *session, const char *name, double value);
+PwshValue *pwsh_get_variable(const PwshSession *session, const char *name);
+
+/* Values */
+void pwsh_value_free(PwshValue *value);
+int pwsh_value_kind(const PwshValue *value);
+bool pwsh_value_as_bool(const PwshValue *value);
+double pwsh_value_as_number(const PwshValue *value); /* NaN if not numeric */
+char *pwsh_value_to_string(const PwshValue *value);
+size_t pwsh_value_len(const PwshValue *value); /* array items or object properties */
+PwshValue *pwsh_value_get_index(const PwshValue *value, size_t index);
+PwshValue *pwsh_value_get_property(const PwshValue *value, const char *name);
+char *pwsh_value_property_name(const PwshValue *value, size_t index);
+
+void pwsh_string_free(char *text);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* PWSH_H */
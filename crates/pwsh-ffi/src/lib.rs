@@ -0,0 +1,456 @@
+/// C ABI for embedding the interpreter in C, C++, .NET and other hosts
+///
+/// `include/pwsh.h` declares these functions. A session runs scripts and
+/// keeps their state; each script's output comes back as an array value that
+/// the caller walks with the `pwsh_value_*` accessors. Every object this
+/// library hands out is owned by the caller and released with the matching
+/// `*_free` function, except the string from `pwsh_last_error`, which the
+/// session owns.
+use pwsh_runtime::{Session, SessionBuilder, SessionError, Value};
+use std::cell::RefCell;
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+
+/// An interpreter session and the message of its last failed call
+pub struct PwshSession {
+    session: Session,
+    // A cell so calls taking a const session can report errors too
+    last_error: RefCell<Option<CString>>,
+}
+
+impl PwshSession {
+    fn set_error(&self, message: Option<&str>) {
+        *self.last_error.borrow_mut() = message.map(to_c_string);
+    }
+}
+
+/// A value owned by the caller
+pub struct PwshValue(Value);
+
+/// Kinds reported by `pwsh_value_kind`
+pub const PWSH_NULL: c_int = 0;
+pub const PWSH_BOOLEAN: c_int = 1;
+pub const PWSH_NUMBER: c_int = 2;
+pub const PWSH_STRING: c_int = 3;
+pub const PWSH_ARRAY: c_int = 4;
+/// Hashtables and class instances
+pub const PWSH_OBJECT: c_int = 5;
+/// Dates, enum values, functions and script blocks; read them as strings
+pub const PWSH_OTHER: c_int = 6;
+
+fn to_c_string(text: &str) -> CString {
+    // Interior NULs can't cross the C boundary
+    CString::new(text.replace('\0', "")).unwrap_or_default()
+}
+
+/// Borrow a string argument, or say why it can't be read
+unsafe fn str_argument<'a>(text: *const c_char, what: &str) -> Result<&'a str, String> {
+    if text.is_null() {
+        return Err(format!("{} is NULL", what));
+    }
+    CStr::from_ptr(text)
+        .to_str()
+        .map_err(|_| format!("{} is not valid UTF-8", what))
+}
+
+fn into_raw_value(value: Value) -> *mut PwshValue {
+    Box::into_raw(Box::new(PwshValue(value)))
+}
+
+/// Create a session with every built-in cmdlet registered
+#[no_mangle]
+pub extern "C" fn pwsh_session_new() -> *mut PwshSession {
+    Box::into_raw(Box::new(PwshSession {
+        session: SessionBuilder::new()
+            .cmdlets(pwsh_cmdlets::register_all)
+            .build(),
+        last_error: RefCell::new(None),
+    }))
+}
+
+/// Free a session
+///
+/// # Safety
+/// `session` must come from `pwsh_session_new` and not be used afterwards.
+/// NULL is ignored.
+#[no_mangle]
+pub unsafe extern "C" fn pwsh_session_free(session: *mut PwshSession) {
+    if !session.is_null() {
+        drop(Box::from_raw(session));
+    }
+}
+
+/// Run a script; returns its output as an array value, or NULL when it fails
+/// (see `pwsh_last_error`)
+///
+/// # Safety
+/// `session` must be a live session and `script` a NUL-terminated UTF-8
+/// string or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn pwsh_eval(
+    session: *mut PwshSession,
+    script: *const c_char,
+) -> *mut PwshValue {
+    let Some(session) = session.as_mut() else {
+        return ptr::null_mut();
+    };
+    session.set_error(None);
+    let script = match str_argument(script, "Script") {
+        Ok(script) => script,
+        Err(message) => {
+            session.set_error(Some(&message));
+            return ptr::null_mut();
+        }
+    };
+
+    let result = catch_unwind(AssertUnwindSafe(|| session.session.invoke(script)));
+    let message = match result {
+        Ok(Ok(output)) => return into_raw_value(Value::Array(output.into())),
+        Ok(Err(SessionError::Lex(e))) => format!("Lexer error: {}", e),
+        Ok(Err(SessionError::Parse(e))) => format!("Parse error: {}", e),
+        Ok(Err(SessionError::Runtime(e))) => format!("Runtime error: {}", e),
+        Err(_) => "Internal error: the interpreter panicked".to_string(),
+    };
+    session.set_error(Some(&message));
+    ptr::null_mut()
+}
+
+/// Message of the last failed call on the session, or NULL if it succeeded.
+/// The session owns the string; it stays valid until the next call on it.
+///
+/// # Safety
+/// `session` must be a live session.
+#[no_mangle]
+pub unsafe extern "C" fn pwsh_last_error(session: *const PwshSession) -> *const c_char {
+    session
+        .as_ref()
+        .and_then(|session| {
+            let message = session.last_error.borrow();
+            message.as_ref().map(|message| message.as_ptr())
+        })
+        .unwrap_or(ptr::null())
+}
+
+/// Set a variable to a string for later scripts; false when an argument is
+/// NULL or not UTF-8 (see `pwsh_last_error`)
+///
+/// # Safety
+/// `session` must be a live session; `name` and `value` NUL-terminated UTF-8
+/// strings or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn pwsh_set_string(
+    session: *mut PwshSession,
+    name: *const c_char,
+    value: *const c_char,
+) -> bool {
+    let Some(session) = session.as_mut() else {
+        return false;
+    };
+    let arguments = str_argument(name, "Variable name")
+        .and_then(|name| Ok((name, str_argument(value, "Value")?)));
+    let (name, value) = match arguments {
+        Ok(arguments) => arguments,
+        Err(message) => {
+            session.set_error(Some(&message));
+            return false;
+        }
+    };
+    session.set_error(None);
+    session
+        .session
+        .set_variable(name, Value::String(value.to_string().into()));
+    true
+}
+
+/// Set a variable to a number for later scripts; false when `name` is NULL
+/// or not UTF-8 (see `pwsh_last_error`)
+///
+/// # Safety
+/// `session` must be a live session and `name` a NUL-terminated UTF-8 string
+/// or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn pwsh_set_number(
+    session: *mut PwshSession,
+    name: *const c_char,
+    value: f64,
+) -> bool {
+    let Some(session) = session.as_mut() else {
+        return false;
+    };
+    let name = match str_argument(name, "Variable name") {
+        Ok(name) => name,
+        Err(message) => {
+            session.set_error(Some(&message));
+            return false;
+        }
+    };
+    session.set_error(None);
+    session.session.set_variable(name, Value::Number(value));
+    true
+}
+
+/// Get a variable's value, or NULL if it isn't defined or `name` is NULL or
+/// not UTF-8 (see `pwsh_last_error`)
+///
+/// # Safety
+/// `session` must be a live session and `name` a NUL-terminated UTF-8 string
+/// or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn pwsh_get_variable(
+    session: *const PwshSession,
+    name: *const c_char,
+) -> *mut PwshValue {
+    let Some(session) = session.as_ref() else {
+        return ptr::null_mut();
+    };
+    let name = match str_argument(name, "Variable name") {
+        Ok(name) => name,
+        Err(message) => {
+            session.set_error(Some(&message));
+            return ptr::null_mut();
+        }
+    };
+    session.set_error(None);
+    session
+        .session
+        .get_variable(name)
+        .map_or(ptr::null_mut(), into_raw_value)
+}
+
+/// Free a value
+///
+/// # Safety
+/// `value` must come from this library and not be used afterwards. NULL is ignored.
+#[no_mangle]
+pub unsafe extern "C" fn pwsh_value_free(value: *mut PwshValue) {
+    if !value.is_null() {
+        drop(Box::from_raw(value));
+    }
+}
+
+/// One of the `PWSH_*` kinds; NULL counts as PWSH_NULL
+///
+/// # Safety
+/// `value` must be a live value or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn pwsh_value_kind(value: *const PwshValue) -> c_int {
+    match value.as_ref().map(|v| &v.0) {
+        None | Some(Value::Null) => PWSH_NULL,
+        Some(Value::Boolean(_)) => PWSH_BOOLEAN,
+        Some(Value::Number(_)) => PWSH_NUMBER,
+        Some(Value::String(_)) => PWSH_STRING,
+        Some(Value::Array(_)) => PWSH_ARRAY,
+        Some(Value::Object(_) | Value::Instance { .. }) => PWSH_OBJECT,
+        Some(_) => PWSH_OTHER,
+    }
+}
+
+/// The value's truthiness, by PowerShell rules
+///
+/// # Safety
+/// `value` must be a live value or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn pwsh_value_as_bool(value: *const PwshValue) -> bool {
+    value.as_ref().is_some_and(|v| v.0.to_bool())
+}
+
+/// The value as a number; NaN when it can't be converted
+///
+/// # Safety
+/// `value` must be a live value or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn pwsh_value_as_number(value: *const PwshValue) -> f64 {
+    value
+        .as_ref()
+        .and_then(|v| v.0.to_number())
+        .unwrap_or(f64::NAN)
+}
+
+/// The value as PowerShell displays it; free with `pwsh_string_free`
+///
+/// # Safety
+/// `value` must be a live value or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn pwsh_value_to_string(value: *const PwshValue) -> *mut c_char {
+    let text = value.as_ref().map(|v| v.0.to_string()).unwrap_or_default();
+    to_c_string(&text).into_raw()
+}
+
+/// Number of items in an array or properties of an object; 0 for anything else
+///
+/// # Safety
+/// `value` must be a live value or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn pwsh_value_len(value: *const PwshValue) -> usize {
+    match value.as_ref().map(|v| &v.0) {
+        Some(Value::Array(items)) => items.read().len(),
+        Some(other) => other.properties().map_or(0, |props| props.len()),
+        None => 0,
+    }
+}
+
+/// A copy of an array item, or NULL when out of range or not an array
+///
+/// # Safety
+/// `value` must be a live value or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn pwsh_value_get_index(
+    value: *const PwshValue,
+    index: usize,
+) -> *mut PwshValue {
+    match value.as_ref().map(|v| &v.0) {
+        Some(Value::Array(items)) => items
+            .read()
+            .get(index)
+            .map_or(ptr::null_mut(), |item| into_raw_value(item.clone())),
+        _ => ptr::null_mut(),
+    }
+}
+
+/// A copy of an object's property (case-insensitive), or NULL if it has none
+/// or `name` is NULL or not UTF-8
+///
+/// # Safety
+/// `value` must be a live value or NULL, and `name` a NUL-terminated UTF-8
+/// string or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn pwsh_value_get_property(
+    value: *const PwshValue,
+    name: *const c_char,
+) -> *mut PwshValue {
+    let (Some(value), Ok(name)) = (value.as_ref(), str_argument(name, "Property name")) else {
+        return ptr::null_mut();
+    };
+    value
+        .0
+        .get_property(name)
+        .map_or(ptr::null_mut(), into_raw_value)
+}
+
+/// Name of an object's property by position, in definition order; NULL when
+/// out of range. Free with `pwsh_string_free`.
+///
+/// # Safety
+/// `value` must be a live value or NULL.
+#[no_mangle]
+pub unsafe extern "C" fn pwsh_value_property_name(
+    value: *const PwshValue,
+    index: usize,
+) -> *mut c_char {
+    value
+        .as_ref()
+        .and_then(|v| v.0.properties())
+        .and_then(|props| Some(to_c_string(props.get_index(index)?.0).into_raw()))
+        .unwrap_or(ptr::null_mut())
+}
+
+/// Free a string returned by this library
+///
+/// # Safety
+/// `text` must come from `pwsh_value_to_string` or `pwsh_value_property_name`
+/// and not be used afterwards. NULL is ignored.
+#[no_mangle]
+pub unsafe extern "C" fn pwsh_string_free(text: *mut c_char) {
+    if !text.is_null() {
+        drop(CString::from_raw(text));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    unsafe fn eval(session: *mut PwshSession, script: &str) -> *mut PwshValue {
+        let script = CString::new(script).unwrap();
+        pwsh_eval(session, script.as_ptr())
+    }
+
+    unsafe fn take_string(text: *mut c_char) -> String {
+        let owned = CStr::from_ptr(text).to_str().unwrap().to_string();
+        pwsh_string_free(text);
+        owned
+    }
+
+    #[test]
+    fn test_eval_and_walk_the_output() {
+        unsafe {
+            let session = pwsh_session_new();
+            let name = CString::new("greeting").unwrap();
+            let hello = CString::new("Hi").unwrap();
+            assert!(pwsh_set_string(session, name.as_ptr(), hello.as_ptr()));
+
+            let output = eval(session, "@(@{ Text = \"$greeting there\"; Count = 2 }, 7)");
+            assert!(!output.is_null());
+            assert!(pwsh_last_error(session).is_null());
+            assert_eq!(pwsh_value_kind(output), PWSH_ARRAY);
+            assert_eq!(pwsh_value_len(output), 2);
+
+            let table = pwsh_value_get_index(output, 0);
+            assert_eq!(pwsh_value_kind(table), PWSH_OBJECT);
+            assert_eq!(take_string(pwsh_value_property_name(table, 1)), "Count");
+            let key = CString::new("text").unwrap();
+            let text = pwsh_value_get_property(table, key.as_ptr());
+            assert_eq!(take_string(pwsh_value_to_string(text)), "Hi there");
+
+            let seven = pwsh_value_get_index(output, 1);
+            assert_eq!(pwsh_value_as_number(seven), 7.0);
+            assert!(pwsh_value_get_index(output, 2).is_null());
+
+            for value in [text, table, seven, output] {
+                pwsh_value_free(value);
+            }
+            pwsh_session_free(session);
+        }
+    }
+
+    #[test]
+    fn test_failed_eval_sets_last_error() {
+        unsafe {
+            let session = pwsh_session_new();
+            assert!(eval(session, "$x = 1 / 0").is_null());
+            let message = CStr::from_ptr(pwsh_last_error(session));
+            assert_eq!(message.to_str().unwrap(), "Runtime error: Division by zero");
+
+            // State survives between scripts, and success clears the error
+            let output = eval(session, "$n = 4");
+            assert!(pwsh_last_error(session).is_null());
+            pwsh_value_free(output);
+            let name = CString::new("n").unwrap();
+            let n = pwsh_get_variable(session, name.as_ptr());
+            assert_eq!(pwsh_value_as_number(n), 4.0);
+            pwsh_value_free(n);
+            pwsh_session_free(session);
+        }
+    }
+
+    #[test]
+    fn test_null_strings_fail_with_last_error() {
+        unsafe {
+            let session = pwsh_session_new();
+            let last_error = || CStr::from_ptr(pwsh_last_error(session)).to_str().unwrap();
+            assert!(pwsh_eval(session, ptr::null()).is_null());
+            assert_eq!(last_error(), "Script is NULL");
+
+            let name = CString::new("x").unwrap();
+            assert!(!pwsh_set_string(session, name.as_ptr(), ptr::null()));
+            assert_eq!(last_error(), "Value is NULL");
+            assert!(!pwsh_set_string(session, ptr::null(), name.as_ptr()));
+            assert_eq!(last_error(), "Variable name is NULL");
+            assert!(!pwsh_set_number(session, ptr::null(), 1.0));
+            assert!(pwsh_get_variable(session, ptr::null()).is_null());
+            assert_eq!(last_error(), "Variable name is NULL");
+
+            // A good call clears the error
+            assert!(pwsh_set_number(session, name.as_ptr(), 1.0));
+            assert!(pwsh_last_error(session).is_null());
+
+            let output = eval(session, "@{ A = 1 }");
+            let table = pwsh_value_get_index(output, 0);
+            assert!(pwsh_value_get_property(table, ptr::null()).is_null());
+            pwsh_value_free(table);
+            pwsh_value_free(output);
+            pwsh_session_free(session);
+        }
+    }
+}
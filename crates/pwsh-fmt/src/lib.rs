@@ -0,0 +1,78 @@
+/// PowerShell Script Formatter
+///
+/// This crate prints a parsed program back as canonically formatted source:
+/// four-space indentation, opening braces on the same line, spaces around
+/// binary operators and long pipelines wrapped after each `|`. Comments and
+/// blank lines are kept. Formatting never changes meaning; re-parsing the
+/// output yields the same AST.
+mod printer;
+
+use pwsh_lexer::{LexError, Lexer};
+use pwsh_parser::{ParseError, Parser};
+
+pub use printer::format_program;
+
+/// Errors that prevent a script from being formatted
+#[derive(Debug, Clone, PartialEq)]
+pub enum FormatError {
+    /// The source could not be tokenized
+    Lex(LexError),
+    /// The source could not be parsed
+    Parse(ParseError),
+}
+
+impl std::fmt::Display for FormatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            FormatError::Lex(e) => write!(f, "{}", e),
+            FormatError::Parse(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for FormatError {}
+
+impl From<LexError> for FormatError {
+    fn from(e: LexError) -> Self {
+        FormatError::Lex(e)
+    }
+}
+
+impl From<ParseError> for FormatError {
+    fn from(e: ParseError) -> Self {
+        FormatError::Parse(e)
+    }
+}
+
+/// Parse and format a script
+pub fn format_source(source: &str) -> Result<String, FormatError> {
+    let tokens = Lexer::new(source).tokenize()?;
+    let program = Parser::new(tokens).parse()?;
+    Ok(format_program(&program))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_source() {
+        assert_eq!(
+            format_source("$x=1+2;Write-Output $x").unwrap(),
+            "$x = 1 + 2\nWrite-Output $x\n"
+        );
+        assert!(matches!(
+            format_source("$x = (1 +"),
+            Err(FormatError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn test_comments_and_blank_lines_are_kept() {
+        let source = "# Setup\n$x=1 # one\n\n\n<# help #>\nfunction F {\n# nothing yet\n}\n# done";
+        assert_eq!(
+            format_source(source).unwrap(),
+            "# Setup\n$x = 1 # one\n\n<# help #>\nfunction F {\n    # nothing yet\n}\n\n# done\n"
+        );
+    }
+}
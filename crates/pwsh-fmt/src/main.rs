@@ -0,0 +1,81 @@
+use pwsh_fmt::format_source;
+use std::io::Read;
+use std::process::ExitCode;
+
+const USAGE: &str = "Usage: pwsh-fmt [--check] [FILE...]
+
+Formats PowerShell scripts in place, or stdin to stdout when no files are given.
+With --check, files are left untouched and the command fails if any would change.";
+
+fn main() -> ExitCode {
+    let mut check = false;
+    let mut files = Vec::new();
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--check" => check = true,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return ExitCode::SUCCESS;
+            }
+            _ => files.push(arg),
+        }
+    }
+
+    if files.is_empty() {
+        return format_stdin(check);
+    }
+
+    let mut status = ExitCode::SUCCESS;
+    for path in &files {
+        match format_file(path, check) {
+            Ok(true) if check => {
+                println!("Would reformat: {}", path);
+                status = ExitCode::FAILURE;
+            }
+            Ok(_) => {}
+            Err(message) => {
+                eprintln!("pwsh-fmt: {}: {}", path, message);
+                status = ExitCode::FAILURE;
+            }
+        }
+    }
+    status
+}
+
+/// Format a file in place (unless checking); returns whether it changed
+fn format_file(path: &str, check: bool) -> Result<bool, String> {
+    let source = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let formatted = format_source(&source).map_err(|e| e.to_string())?;
+    let changed = formatted != source;
+    if changed && !check {
+        std::fs::write(path, &formatted).map_err(|e| e.to_string())?;
+    }
+    Ok(changed)
+}
+
+fn format_stdin(check: bool) -> ExitCode {
+    let mut source = String::new();
+    if let Err(e) = std::io::stdin().read_to_string(&mut source) {
+        eprintln!("pwsh-fmt: {}", e);
+        return ExitCode::FAILURE;
+    }
+
+    match format_source(&source) {
+        Ok(formatted) if check => {
+            if formatted == source {
+                ExitCode::SUCCESS
+            } else {
+                println!("Would reformat: <stdin>");
+                ExitCode::FAILURE
+            }
+        }
+        Ok(formatted) => {
+            print!("{}", formatted);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("pwsh-fmt: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,914 @@
+/// Pretty-printer from AST back to source
+/// Every node is printed so that it parses back to the same node: parentheses are
+/// added wherever the parser would otherwise group tokens differently.
+use pwsh_lexer::{Lexer, Token};
+use pwsh_parser::{
+    Argument, BinaryOperator, Block, ClassDef, ClassMethod, EnumDef, Expression, ExpressionKind,
+    Literal, Parameter, Pipeline, Program, Statement, StatementKind, StringPart, Trivia,
+    TriviaKind, UnaryOperator,
+};
+
+const INDENT: &str = "    ";
+
+/// Pipelines longer than this are wrapped after each `|`
+const MAX_WIDTH: usize = 100;
+
+/// Script blocks and hashtables longer than this are split over several lines
+const INLINE_WIDTH: usize = 60;
+
+/// Precedence of unary operators and casts (binds tighter than any binary operator)
+const UNARY_PRECEDENCE: u8 = 5;
+
+/// Precedence of literals, variables and member/index access
+const PRIMARY_PRECEDENCE: u8 = 6;
+
+/// Format a program as source text, ending with a newline unless it is empty
+pub fn format_program(program: &Program) -> String {
+    let source = statements(&program.statements, &program.trivia, 0);
+    if source.is_empty() {
+        source
+    } else {
+        source + "\n"
+    }
+}
+
+fn indent(level: usize) -> String {
+    INDENT.repeat(level)
+}
+
+/// Print statements one per line with their comments. Blank lines from the source
+/// are kept (several in a row become one) and definitions are always set apart.
+fn statements(statements: &[Statement], trivia: &[Trivia], level: usize) -> String {
+    let lines: Vec<(String, bool)> = statements
+        .iter()
+        .map(|stmt| (statement(stmt, level), is_definition(stmt)))
+        .collect();
+    commented_lines(&lines, trivia, level)
+}
+
+/// Print lines with the comments and blank lines attached to them, the
+/// trivia's statement index counting lines; a line marked as a definition
+/// is set apart from its neighbours
+fn commented_lines(lines: &[(String, bool)], trivia: &[Trivia], level: usize) -> String {
+    // Trivia for each line, then the trivia after the last one
+    let mut groups = vec![Vec::new(); lines.len() + 1];
+    for t in trivia {
+        groups[t.statement.min(lines.len())].push(&t.kind);
+    }
+
+    let mut out = String::new();
+    let mut blank = false;
+    for (index, group) in groups.into_iter().enumerate() {
+        let current = lines.get(index);
+        let mut separate =
+            index > 0 && (lines[index - 1].1 || current.is_some_and(|(_, definition)| *definition));
+        let mut trailing = Vec::new();
+
+        for kind in group {
+            match kind {
+                TriviaKind::BlankLine => blank = true,
+                TriviaKind::TrailingComment { text, .. } if current.is_some() => {
+                    trailing.push(text)
+                }
+                TriviaKind::Comment { text, .. } | TriviaKind::TrailingComment { text, .. } => {
+                    push_line(&mut out, text, level, blank || separate);
+                    blank = false;
+                    separate = false;
+                }
+            }
+        }
+
+        if let Some((line, _)) = current {
+            let mut line = line.clone();
+            for text in trailing {
+                line.push(' ');
+                line.push_str(text);
+            }
+            push_line(&mut out, &line, level, blank || separate);
+            blank = false;
+        }
+    }
+    out
+}
+
+/// Append an indented line, optionally after an empty one; nothing goes before the first line
+fn push_line(out: &mut String, line: &str, level: usize, blank_before: bool) {
+    if !out.is_empty() {
+        out.push('\n');
+        if blank_before {
+            out.push('\n');
+        }
+    }
+    out.push_str(&indent(level));
+    out.push_str(line);
+}
+
+fn is_definition(stmt: &Statement) -> bool {
+    matches!(
+        stmt.kind,
+        StatementKind::FunctionDef { .. } | StatementKind::ClassDef(_) | StatementKind::EnumDef(_)
+    )
+}
+
+/// Print a statement; the first line is not indented, later lines are
+fn statement(stmt: &Statement, level: usize) -> String {
+    match &stmt.kind {
+        StatementKind::Expression(expr) => expression(expr, level),
+        StatementKind::Assignment { variable, value } => {
+            let target = format!("{} = ", self::variable(variable, false));
+            let value = assigned_value(value, level, target.len());
+            target + &value
+        }
+        StatementKind::MultipleAssignment { variables, value } => {
+            let names: Vec<String> = variables.iter().map(|v| self::variable(v, false)).collect();
+            let target = format!("{} = ", names.join(", "));
+            let value = assigned_value(value, level, target.len());
+            target + &value
+        }
+        StatementKind::TargetAssignment { target, value } => {
+            let target = format!("{} = ", expression(target, level));
+            let value = assigned_value(value, level, target.len());
+            target + &value
+        }
+        StatementKind::FunctionDef {
+            name,
+            parameters,
+            body,
+            is_filter,
+            begin,
+            process,
+            ..
+        } => {
+            let keyword = if *is_filter { "filter" } else { "function" };
+            let body = if begin.is_some() || process.is_some() {
+                named_blocks(begin.as_ref(), process.as_ref(), body, level)
+            } else {
+                block(body, level)
+            };
+            format!(
+                "{} {}{} {}",
+                keyword,
+                name,
+                parameter_list(parameters, level, true),
+                body
+            )
+        }
+        StatementKind::If {
+            condition,
+            then_branch,
+            else_branch,
+        } => {
+            let mut out = format!(
+                "if ({}) {}",
+                expression(condition, level),
+                block(then_branch, level)
+            );
+            if let Some(else_branch) = else_branch {
+                out.push_str(" else ");
+                out.push_str(&block(else_branch, level));
+            }
+            out
+        }
+        StatementKind::Foreach {
+            variable,
+            collection,
+            body,
+        } => format!(
+            "foreach ({} in {}) {}",
+            self::variable(variable, false),
+            expression(collection, level),
+            block(body, level)
+        ),
+        StatementKind::Try {
+            body,
+            catch,
+            finally,
+        } => {
+            let mut out = format!("try {}", block(body, level));
+            if let Some(catch) = catch {
+                out.push_str(" catch ");
+                out.push_str(&block(catch, level));
+            }
+            if let Some(finally) = finally {
+                out.push_str(" finally ");
+                out.push_str(&block(finally, level));
+            }
+            out
+        }
+        StatementKind::ClassDef(class) => class_def(class, level),
+        StatementKind::EnumDef(def) => enum_def(def, level),
+        StatementKind::Return(None) => "return".to_string(),
+        StatementKind::Return(Some(value)) => format!("return {}", expression(value, level)),
+        StatementKind::Break => "break".to_string(),
+        StatementKind::Continue => "continue".to_string(),
+        StatementKind::Throw(None) => "throw".to_string(),
+        StatementKind::Throw(Some(value)) => format!("throw {}", expression(value, level)),
+        StatementKind::Pipeline(pipeline) => wrapped_pipeline(pipeline, level, 0),
+    }
+}
+
+/// The right-hand side of an assignment, which may be an unparenthesized pipeline
+fn assigned_value(value: &Expression, level: usize, prefix_width: usize) -> String {
+    match &value.kind {
+        ExpressionKind::Pipeline(pipeline) => wrapped_pipeline(pipeline, level, prefix_width),
+        _ => expression(value, level),
+    }
+}
+
+/// A statement-level pipeline, wrapped after each `|` when it doesn't fit on one line
+fn wrapped_pipeline(pipeline: &Pipeline, level: usize, prefix_width: usize) -> String {
+    let stages: Vec<String> = pipeline
+        .stages
+        .iter()
+        .map(|stage| expression(stage, level))
+        .collect();
+    let line = stages.join(" | ");
+
+    let width = INDENT.len() * level + prefix_width + line.chars().count();
+    if stages.len() > 1 && width > MAX_WIDTH && !line.contains('\n') {
+        stages.join(&format!(" |\n{}", indent(level + 1)))
+    } else {
+        line
+    }
+}
+
+/// A block on its own lines: {, the indented statements, then }
+fn block(block: &Block, level: usize) -> String {
+    let body = statements(&block.statements, &block.trivia, level + 1);
+    if body.is_empty() {
+        return "{ }".to_string();
+    }
+    format!("{{\n{}\n{}}}", body, indent(level))
+}
+
+/// A script block, kept on one line when it is a single short statement without comments
+fn script_block(body: &Block, level: usize) -> String {
+    let has_comments = body
+        .trivia
+        .iter()
+        .any(|t| !matches!(t.kind, TriviaKind::BlankLine));
+    if let ([stmt], false) = (body.statements.as_slice(), has_comments) {
+        let line = statement(stmt, level + 1);
+        if !is_definition(stmt) && !line.contains('\n') && line.chars().count() <= INLINE_WIDTH {
+            return format!("{{ {} }}", line);
+        }
+    }
+    block(body, level)
+}
+
+/// A function body made of begin/process/end blocks
+fn named_blocks(
+    begin: Option<&Block>,
+    process: Option<&Block>,
+    end: &Block,
+    level: usize,
+) -> String {
+    let mut blocks = Vec::new();
+    let inner = level + 1;
+    if let Some(begin) = begin {
+        blocks.push(format!("{}begin {}", indent(inner), block(begin, inner)));
+    }
+    if let Some(process) = process {
+        blocks.push(format!(
+            "{}process {}",
+            indent(inner),
+            block(process, inner)
+        ));
+    }
+    // A missing end block parses as an empty one
+    let end = block(end, inner);
+    if end != "{ }" {
+        blocks.push(format!("{}end {}", indent(inner), end));
+    }
+    format!("{{\n{}\n{}}}", blocks.join("\n\n"), indent(level))
+}
+
+/// Parameters in parentheses: ($a, [int]$b = 1)
+/// Functions without parameters can leave the parentheses out.
+fn parameter_list(parameters: &[Parameter], level: usize, optional: bool) -> String {
+    if parameters.is_empty() && optional {
+        return String::new();
+    }
+    let parameters: Vec<String> = parameters
+        .iter()
+        .map(|p| {
+            let mut out = type_prefix(p.type_name.as_deref());
+            out.push_str(&format!("${}", p.name));
+            if let Some(default) = &p.default_value {
+                out.push_str(&format!(" = {}", list_item(default, level)));
+            }
+            out
+        })
+        .collect();
+    format!("({})", parameters.join(", "))
+}
+
+fn type_prefix(type_name: Option<&str>) -> String {
+    type_name.map_or_else(String::new, |t| format!("[{}]", t))
+}
+
+fn class_def(class: &ClassDef, level: usize) -> String {
+    let inner = level + 1;
+    let mut sections = Vec::new();
+
+    if !class.properties.is_empty() {
+        let properties: Vec<String> = class
+            .properties
+            .iter()
+            .map(|p| {
+                let mut out = format!(
+                    "{}{}${}",
+                    indent(inner),
+                    type_prefix(p.type_name.as_deref()),
+                    p.name
+                );
+                if let Some(default) = &p.default_value {
+                    out.push_str(&format!(" = {}", expression(default, inner)));
+                }
+                out
+            })
+            .collect();
+        sections.push(properties.join("\n"));
+    }
+    for method in class.constructors.iter().chain(&class.methods) {
+        sections.push(format!("{}{}", indent(inner), class_method(method, inner)));
+    }
+
+    if sections.is_empty() {
+        return format!("class {} {{ }}", class.name);
+    }
+    format!(
+        "class {} {{\n{}\n{}}}",
+        class.name,
+        sections.join("\n\n"),
+        indent(level)
+    )
+}
+
+fn class_method(method: &ClassMethod, level: usize) -> String {
+    let mut out = String::new();
+    if method.is_static {
+        out.push_str("static ");
+    }
+    if let Some(return_type) = &method.return_type {
+        out.push_str(&format!("[{}] ", return_type));
+    }
+    out.push_str(&method.name);
+    out.push_str(&parameter_list(&method.parameters, level, false));
+    out.push(' ');
+    out.push_str(&block(&method.body, level));
+    out
+}
+
+fn enum_def(def: &EnumDef, level: usize) -> String {
+    if def.members.is_empty() {
+        return format!("enum {} {{ }}", def.name);
+    }
+    let members: Vec<String> = def
+        .members
+        .iter()
+        .map(|(name, value)| match value {
+            Some(value) => format!("{}{} = {}", indent(level + 1), name, value),
+            None => format!("{}{}", indent(level + 1), name),
+        })
+        .collect();
+    format!(
+        "enum {} {{\n{}\n{}}}",
+        def.name,
+        members.join("\n"),
+        indent(level)
+    )
+}
+
+/// Print an expression where the parser accepts a full expression
+fn expression(expr: &Expression, level: usize) -> String {
+    match &expr.kind {
+        ExpressionKind::Literal(literal) => self::literal(literal),
+        ExpressionKind::Variable(name) => variable(name, false),
+        ExpressionKind::BinaryOp {
+            left,
+            operator,
+            right,
+        } => {
+            let precedence = binary_precedence(operator);
+            let left = operand(left, precedence, level);
+            // The arguments of -f are a bare comma-separated list
+            let right = match (operator, &right.kind) {
+                (BinaryOperator::Format, ExpressionKind::Array(items)) if items.len() > 1 => items
+                    .iter()
+                    .map(|item| operand(item, precedence + 1, level))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                _ => operand(right, precedence + 1, level),
+            };
+            format!("{} {} {}", left, binary_operator(operator), right)
+        }
+        ExpressionKind::UnaryOp {
+            operator: operator @ (UnaryOperator::Negate | UnaryOperator::Plus),
+            operand,
+        } => {
+            // The operand of unary minus is a primary expression without member access
+            let sign = if *operator == UnaryOperator::Negate {
+                "-"
+            } else {
+                "+"
+            };
+            if negatable(operand) {
+                format!("{}{}", sign, expression(operand, level))
+            } else {
+                format!("{}({})", sign, expression(operand, level))
+            }
+        }
+        ExpressionKind::UnaryOp {
+            operator: operator @ (UnaryOperator::Not | UnaryOperator::Join | UnaryOperator::Split),
+            operand,
+        } => {
+            let name = match operator {
+                UnaryOperator::Join => "-join",
+                UnaryOperator::Split => "-split",
+                _ => "-not",
+            };
+            format!(
+                "{} {}",
+                name,
+                self::operand(operand, UNARY_PRECEDENCE, level)
+            )
+        }
+        ExpressionKind::Call { name, arguments } => call(name, arguments, level),
+        ExpressionKind::Invoke { command, arguments } => {
+            let command = command_argument(
+                command,
+                &Argument::Positional((**command).clone()),
+                !arguments.is_empty(),
+                level,
+            );
+            call(&format!("& {}", command), arguments, level)
+        }
+        ExpressionKind::MemberAccess { object, member } => {
+            format!("{}.{}", postfix_object(object, level), member)
+        }
+        ExpressionKind::Index { object, index } => {
+            // [T][0] would parse as a cast
+            let object = match &object.kind {
+                ExpressionKind::TypeLiteral(_) => format!("({})", expression(object, level)),
+                _ => postfix_object(object, level),
+            };
+            format!("{}[{}]", object, expression(index, level))
+        }
+        ExpressionKind::MethodCall {
+            object,
+            method,
+            arguments,
+        } => format!(
+            "{}.{}({})",
+            postfix_object(object, level),
+            method,
+            list(arguments, level)
+        ),
+        ExpressionKind::TypeLiteral(type_name) => format!("[{}]", type_name),
+        ExpressionKind::Cast {
+            type_name,
+            expression: operand,
+        } => {
+            let operand = if castable(operand) {
+                expression(operand, level)
+            } else {
+                format!("({})", expression(operand, level))
+            };
+            format!("[{}]{}", type_name, operand)
+        }
+        ExpressionKind::StaticMember { type_name, member } => {
+            format!("[{}]::{}", type_name, member)
+        }
+        ExpressionKind::StaticMethodCall {
+            type_name,
+            method,
+            arguments,
+        } => format!("[{}]::{}({})", type_name, method, list(arguments, level)),
+        ExpressionKind::ScriptBlock(body) => script_block(body, level),
+        ExpressionKind::Hashtable { entries, trivia } => hashtable(entries, trivia, level),
+        ExpressionKind::Array(items) => format!("@({})", list(items, level)),
+        ExpressionKind::Pipeline(pipeline) => {
+            let stages: Vec<String> = pipeline
+                .stages
+                .iter()
+                .map(|stage| expression(stage, level))
+                .collect();
+            format!("({})", stages.join(" | "))
+        }
+    }
+}
+
+/// Print an operand, parenthesized if it binds more loosely than `min_precedence`
+fn operand(expr: &Expression, min_precedence: u8, level: usize) -> String {
+    if precedence(expr) < min_precedence {
+        format!("({})", expression(expr, level))
+    } else {
+        expression(expr, level)
+    }
+}
+
+fn precedence(expr: &Expression) -> u8 {
+    match &expr.kind {
+        ExpressionKind::BinaryOp { operator, .. } => binary_precedence(operator),
+        ExpressionKind::UnaryOp { .. } | ExpressionKind::Cast { .. } => UNARY_PRECEDENCE,
+        // A command swallows the tokens after it as arguments
+        ExpressionKind::Call { .. } | ExpressionKind::Invoke { .. } => 0,
+        _ => PRIMARY_PRECEDENCE,
+    }
+}
+
+fn binary_precedence(operator: &BinaryOperator) -> u8 {
+    match operator {
+        BinaryOperator::Equal
+        | BinaryOperator::NotEqual
+        | BinaryOperator::Greater
+        | BinaryOperator::Less
+        | BinaryOperator::GreaterOrEqual
+        | BinaryOperator::LessOrEqual
+        | BinaryOperator::Match
+        | BinaryOperator::NotMatch
+        | BinaryOperator::Like
+        | BinaryOperator::NotLike
+        | BinaryOperator::CLike
+        | BinaryOperator::CNotLike
+        | BinaryOperator::Join
+        | BinaryOperator::Split => 1,
+        BinaryOperator::Add | BinaryOperator::Subtract => 2,
+        BinaryOperator::Multiply | BinaryOperator::Divide | BinaryOperator::Modulo => 3,
+        BinaryOperator::Format => 4,
+    }
+}
+
+fn binary_operator(operator: &BinaryOperator) -> &'static str {
+    match operator {
+        BinaryOperator::Add => "+",
+        BinaryOperator::Subtract => "-",
+        BinaryOperator::Multiply => "*",
+        BinaryOperator::Divide => "/",
+        BinaryOperator::Modulo => "%",
+        BinaryOperator::Equal => "-eq",
+        BinaryOperator::NotEqual => "-ne",
+        BinaryOperator::Greater => "-gt",
+        BinaryOperator::Less => "-lt",
+        BinaryOperator::GreaterOrEqual => "-ge",
+        BinaryOperator::LessOrEqual => "-le",
+        BinaryOperator::Format => "-f",
+        BinaryOperator::Match => "-match",
+        BinaryOperator::NotMatch => "-notmatch",
+        BinaryOperator::Like => "-like",
+        BinaryOperator::NotLike => "-notlike",
+        BinaryOperator::CLike => "-clike",
+        BinaryOperator::CNotLike => "-cnotlike",
+        BinaryOperator::Join => "-join",
+        BinaryOperator::Split => "-split",
+    }
+}
+
+/// Whether an expression can follow unary minus without parentheses
+fn negatable(expr: &Expression) -> bool {
+    match &expr.kind {
+        // -true would read as a parameter name
+        ExpressionKind::Literal(Literal::Boolean(_)) => false,
+        ExpressionKind::Literal(_)
+        | ExpressionKind::Variable(_)
+        | ExpressionKind::UnaryOp { .. }
+        | ExpressionKind::TypeLiteral(_)
+        | ExpressionKind::Cast { .. }
+        | ExpressionKind::StaticMember { .. }
+        | ExpressionKind::StaticMethodCall { .. }
+        | ExpressionKind::ScriptBlock(_)
+        | ExpressionKind::Hashtable { .. }
+        | ExpressionKind::Array(_)
+        | ExpressionKind::Pipeline(_) => true,
+        _ => false,
+    }
+}
+
+/// Whether an expression can follow a type literal as the operand of a cast:
+/// it must start with a token that begins a cast operand and bind at least as tightly
+fn castable(expr: &Expression) -> bool {
+    match &expr.kind {
+        ExpressionKind::MemberAccess { object, .. }
+        | ExpressionKind::MethodCall { object, .. }
+        | ExpressionKind::Index { object, .. } => {
+            castable(object) || precedence(object) < PRIMARY_PRECEDENCE
+        }
+        ExpressionKind::Literal(_)
+        | ExpressionKind::Variable(_)
+        | ExpressionKind::TypeLiteral(_)
+        | ExpressionKind::Cast { .. }
+        | ExpressionKind::StaticMember { .. }
+        | ExpressionKind::StaticMethodCall { .. }
+        | ExpressionKind::Hashtable { .. }
+        | ExpressionKind::Array(_)
+        | ExpressionKind::Pipeline(_) => true,
+        _ => false,
+    }
+}
+
+/// Print the object of a member access, index or method call
+fn postfix_object(object: &Expression, level: usize) -> String {
+    match &object.kind {
+        // 5.Length would lex as the number 5.
+        ExpressionKind::Literal(Literal::Number(_) | Literal::Boolean(_)) => {
+            format!("({})", expression(object, level))
+        }
+        _ => operand(object, PRIMARY_PRECEDENCE, level),
+    }
+}
+
+/// Print a comma-separated list of full expressions
+fn list(items: &[Expression], level: usize) -> String {
+    items
+        .iter()
+        .map(|item| list_item(item, level))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Print an item of a comma-separated list; a command with arguments is
+/// parenthesized so it doesn't take the following items as arguments
+fn list_item(item: &Expression, level: usize) -> String {
+    match &item.kind {
+        ExpressionKind::Call { arguments, .. } | ExpressionKind::Invoke { arguments, .. }
+            if !arguments.is_empty() =>
+        {
+            format!("({})", expression(item, level))
+        }
+        _ => expression(item, level),
+    }
+}
+
+/// Print a command and its arguments
+fn call(name: &str, arguments: &[Argument], level: usize) -> String {
+    let mut out = name.to_string();
+    for (i, argument) in arguments.iter().enumerate() {
+        let next_is_positional = matches!(arguments.get(i + 1), Some(Argument::Positional(_)));
+        let value = match argument {
+            Argument::Positional(value) => {
+                out.push(' ');
+                value
+            }
+            Argument::Named { name, value, .. } => {
+                out.push_str(&format!(" -{}", name));
+                // A switch is written without its value unless a positional
+                // argument follows, which would be taken as the value
+                if value.kind == ExpressionKind::Literal(Literal::Boolean(true))
+                    && !next_is_positional
+                {
+                    continue;
+                }
+                out.push(' ');
+                value
+            }
+            // Everything after --% is kept as written
+            Argument::StopParsing { text, .. } => {
+                out.push_str(" --%");
+                if !text.is_empty() {
+                    out.push(' ');
+                    out.push_str(text);
+                }
+                continue;
+            }
+        };
+
+        out.push_str(&command_argument(
+            value,
+            argument,
+            next_is_positional,
+            level,
+        ));
+    }
+    out
+}
+
+/// Print a command argument value. Arguments are primary expressions, so anything
+/// else is parenthesized; an array of two or more items is a comma-separated list.
+fn command_argument(
+    value: &Expression,
+    argument: &Argument,
+    next_is_positional: bool,
+    level: usize,
+) -> String {
+    match &value.kind {
+        ExpressionKind::Literal(Literal::String(word)) if is_bare_word(word) => word.clone(),
+        ExpressionKind::Array(items) if items.len() > 1 => items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| match &item.kind {
+                // A list inside the list keeps its @( )
+                ExpressionKind::Array(_) => expression(item, level),
+                _ => {
+                    let is_last = i + 1 == items.len();
+                    command_argument(item, argument, is_last && next_is_positional, level)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", "),
+        // [T] followed by an operand would parse as a cast
+        ExpressionKind::TypeLiteral(_) if next_is_positional => {
+            format!("({})", expression(value, level))
+        }
+        ExpressionKind::TypeLiteral(_) => expression(value, level),
+        // A positional -x is parenthesized, so it can't read as a parameter
+        ExpressionKind::UnaryOp {
+            operator: UnaryOperator::Negate,
+            ..
+        } if matches!(argument, Argument::Named { .. }) => expression(value, level),
+        ExpressionKind::Literal(_)
+        | ExpressionKind::Variable(_)
+        | ExpressionKind::UnaryOp {
+            operator: UnaryOperator::Not,
+            ..
+        }
+        | ExpressionKind::Cast { .. }
+        | ExpressionKind::StaticMember { .. }
+        | ExpressionKind::StaticMethodCall { .. }
+        | ExpressionKind::ScriptBlock(_)
+        | ExpressionKind::Hashtable { .. }
+        | ExpressionKind::Array(_)
+        | ExpressionKind::Pipeline(_) => expression(value, level),
+        _ => format!("({})", expression(value, level)),
+    }
+}
+
+/// Whether a string can be written as a bare word argument: Select-Object Name
+fn is_bare_word(word: &str) -> bool {
+    match Lexer::new(word).tokenize() {
+        Ok(tokens) => {
+            matches!(
+                tokens.iter().map(|t| &t.token).collect::<Vec<_>>().as_slice(),
+                [Token::Identifier(name), Token::Eof] if name == word
+            )
+        }
+        Err(_) => false,
+    }
+}
+
+/// A hashtable, kept on one line when it is short and has no comments;
+/// otherwise one entry per line with its comments
+fn hashtable(pairs: &[(String, Expression)], trivia: &[Trivia], level: usize) -> String {
+    let has_comments = trivia
+        .iter()
+        .any(|t| !matches!(t.kind, TriviaKind::BlankLine));
+    if pairs.is_empty() && !has_comments {
+        return "@{}".to_string();
+    }
+
+    let entries: Vec<(String, bool)> = pairs
+        .iter()
+        .map(|(key, value)| {
+            let entry = format!("{} = {}", key, expression(value, level + 1));
+            (entry, false)
+        })
+        .collect();
+    if !has_comments {
+        let inline: Vec<&str> = entries.iter().map(|(entry, _)| entry.as_str()).collect();
+        let inline = format!("@{{ {} }}", inline.join("; "));
+        if !inline.contains('\n') && inline.chars().count() <= INLINE_WIDTH {
+            return inline;
+        }
+    }
+
+    let body = commented_lines(&entries, trivia, level + 1);
+    format!("@{{\n{}\n{}}}", body, indent(level))
+}
+
+fn literal(literal: &Literal) -> String {
+    match literal {
+        Literal::String(s) => format!("'{}'", escape(s, '\'')),
+        Literal::InterpolatedString(parts) => {
+            let mut out = String::from("\"");
+            for (i, part) in parts.iter().enumerate() {
+                match part {
+                    StringPart::Literal(s) => out.push_str(&escape(s, '"')),
+                    StringPart::Variable(name) => {
+                        // Text that would run on into the name needs braces
+                        let runs_on = match parts.get(i + 1) {
+                            Some(StringPart::Literal(next)) => next
+                                .starts_with(|c: char| c.is_alphanumeric() || c == '_' || c == ':'),
+                            _ => false,
+                        };
+                        out.push_str(&variable(name, runs_on));
+                    }
+                }
+            }
+            out.push('"');
+            out
+        }
+        Literal::Number(n) => n.to_string(),
+        Literal::Boolean(b) => format!("${}", b),
+        Literal::Null => "$null".to_string(),
+    }
+}
+
+/// A variable reference, braced when the lexer wouldn't read the name back
+/// plainly (${my var}, ${env:ProgramFiles(x86)}) or when `braced` asks for it
+fn variable(name: &str, braced: bool) -> String {
+    let is_name = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_alphanumeric() || c == '_');
+    let plain = match name.split_once(':') {
+        Some((qualifier, rest)) => is_name(qualifier) && is_name(rest),
+        None => is_name(name),
+    };
+    if plain && !braced {
+        format!("${}", name)
+    } else {
+        format!("${{{}}}", name.replace('`', "``").replace('}', "`}"))
+    }
+}
+
+/// Escape string contents for a literal quoted with `quote`. Backslashes are
+/// only doubled where the lexer would otherwise read an escape sequence.
+fn escape(s: &str, quote: char) -> String {
+    let mut out = String::new();
+    let mut chars = s.chars().peekable();
+    while let Some(ch) = chars.next() {
+        let next = chars.peek().copied();
+        match ch {
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\\' => {
+                let escapes_next =
+                    next.is_none_or(|c| matches!(c, 'n' | 'r' | 't' | '\\' | '"' | '\'' | '$'));
+                out.push_str(if escapes_next { "\\\\" } else { "\\" });
+            }
+            c if c == quote => {
+                out.push('\\');
+                out.push(c);
+            }
+            // "$name" would interpolate
+            '$' if quote == '"' && next.is_some_and(|c| c.is_alphanumeric() || c == '_') => {
+                out.push_str("\\$")
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pwsh_parser::Parser;
+
+    fn format(source: &str) -> String {
+        let tokens = Lexer::new(source).tokenize().unwrap();
+        format_program(&Parser::new(tokens).parse().unwrap())
+    }
+
+    #[test]
+    fn test_blocks_are_indented() {
+        assert_eq!(
+            format("if ($x -gt 1) { 'big' } else { if ($x) {'one'} }"),
+            "if ($x -gt 1) {\n    'big'\n} else {\n    if ($x) {\n        'one'\n    }\n}\n"
+        );
+        assert_eq!(
+            format("function Get-Twice([int]$n=2){return $n*2}\n$y=1"),
+            "function Get-Twice([int]$n = 2) {\n    return $n * 2\n}\n\n$y = 1\n"
+        );
+    }
+
+    #[test]
+    fn test_parentheses_follow_precedence() {
+        assert_eq!(format("(1+2)*3 - (4-5)"), "(1 + 2) * 3 - (4 - 5)\n");
+        assert_eq!(format("-($x.Count) + [int]'5'"), "-($x.Count) + [int]'5'\n");
+        assert_eq!(format("(Get-Date).Year"), "(Get-Date).Year\n");
+    }
+
+    #[test]
+    fn test_command_arguments() {
+        assert_eq!(
+            format("Get-ChildItem -Recurse|Select-Object Name,Length -First 2"),
+            "Get-ChildItem -Recurse | Select-Object Name, Length -First 2\n"
+        );
+        assert_eq!(
+            format("Write-Output (1 + 2) 'two words' (-1)"),
+            "Write-Output (1 + 2) 'two words' (-1)\n"
+        );
+    }
+
+    #[test]
+    fn test_strings_are_escaped() {
+        assert_eq!(
+            format(r"'it\'s' + 'C:\dir\\'"),
+            "'it\\'s' + 'C:\\dir\\\\'\n"
+        );
+        assert_eq!(
+            format(r#""Hi $name \$5 \"q\"""#),
+            "\"Hi $name \\$5 \\\"q\\\"\"\n"
+        );
+    }
+
+    #[test]
+    fn test_long_pipelines_wrap() {
+        let source = format!(
+            "Get-ChildItem | Where-Object {{ $_.Name -eq '{}' }} | Sort-Object Length",
+            "x".repeat(40)
+        );
+        let formatted = format(&source);
+        assert!(formatted.starts_with("Get-ChildItem |\n    Where-Object {"));
+        assert!(formatted.ends_with("} |\n    Sort-Object Length\n"));
+    }
+}
@@ -0,0 +1,142 @@
+// Formatting must preserve meaning: the output parses back to the same AST,
+// keeps every comment, and formatting it again changes nothing
+use pwsh_fmt::format_program;
+use pwsh_lexer::{Lexer, LocatedToken, Token, Trivia};
+use pwsh_parser::{Parser, Program};
+use std::fs;
+use std::path::Path;
+
+fn tokenize(source: &str) -> Vec<LocatedToken> {
+    Lexer::new(source)
+        .tokenize()
+        .unwrap_or_else(|e| panic!("{}\n{}", e, source))
+}
+
+fn parse_tokens(tokens: Vec<LocatedToken>, source: &str) -> Program {
+    Parser::new(tokens)
+        .parse()
+        .unwrap_or_else(|e| panic!("{}\n{}", e, source))
+}
+
+/// Parse without comments or blank lines, so layout doesn't affect the AST
+fn parse_code(source: &str) -> Program {
+    let mut tokens = tokenize(source);
+    tokens.dedup_by(|next, prev| prev.token == Token::Newline && next.token == Token::Newline);
+    for token in &mut tokens {
+        token.trivia.clear();
+    }
+    parse_tokens(tokens, source)
+}
+
+fn comments(source: &str) -> Vec<String> {
+    let mut comments: Vec<String> = tokenize(source)
+        .into_iter()
+        .flat_map(|token| token.trivia)
+        .filter_map(|trivia| match trivia {
+            Trivia::Comment { text, .. } => Some(text),
+            Trivia::Whitespace(_) => None,
+        })
+        .collect();
+    // Comments inside a statement move to its start or end
+    comments.sort();
+    comments
+}
+
+fn assert_round_trip(source: &str) -> String {
+    let formatted = format_program(&parse_tokens(tokenize(source), source));
+    assert_eq!(
+        parse_code(&formatted),
+        parse_code(source),
+        "AST changed:\n{}",
+        formatted
+    );
+    assert_eq!(comments(&formatted), comments(source));
+    assert_eq!(
+        format_program(&parse_tokens(tokenize(&formatted), &formatted)),
+        formatted
+    );
+    formatted
+}
+
+#[test]
+fn test_round_trip_statements() {
+    assert_round_trip("$a, $b, $rest = 1, 2, 3, 4; $arr[0] = 5; $obj.Name.First = 'x'");
+    assert_round_trip("$files = Get-ChildItem -Filter '*.rs' | Sort-Object Length -Descending");
+    assert_round_trip("foreach ($n in @(1, 2)) { if (-not $n -eq 1) { return } else { $n } }");
+    assert_round_trip("try { Get-Item $p -ErrorAction Stop }\ncatch { $_.Exception.Message }");
+    assert_round_trip("try { throw 'bad' } catch { if ($_ -eq 'x') { throw } }\nthrow $e");
+    assert_round_trip(
+        "try { $f = Open-Thing } finally { Close-Thing }\ntry { 1 } catch { 2 } finally { 3 }",
+    );
+    assert_round_trip("foreach ($i in $items) { if ($i) { continue }\n    break }");
+    assert_round_trip(
+        "function Show { begin { $t = 0 } process { $t = $t + $_ } end { $t } }\n\
+         filter Double { $_ * 2 }\n\
+         function Get-Area([int]$w = 1, $h = (Get-Size 2)) { $w * $h }",
+    );
+    assert_round_trip(
+        "class Person { [string]$Name; [int]$Age = 30\n\
+         Person([string]$n) { $this.Name = $n }\n\
+         [string] Greet() { return \"Hi $($this.Name)\" }\n\
+         static [Person] Create() { return [Person]::new('x') } }\n\
+         enum Color { Red; Green = 5; Blue = -1 }",
+    );
+}
+
+#[test]
+fn test_round_trip_expressions() {
+    assert_round_trip("Write-Output (1 - (2 - 3)) (-$x) (Get-Date) [int]$y z, 'w' -Count -5");
+    assert_round_trip("'{0} is {1}' -f 'a', (1 + 2); 1 + 2 * 3 % 4 / 5 -ge -(6) -f 1");
+    assert_round_trip("$m = 'abc' -match '^a(b)'; $n = $s -notmatch \"\\d+\"");
+    assert_round_trip("$l = 'abc' -like 'a*'; $s -notlike '[x-z]?' -clike 'A' -cnotlike 'b`*'");
+    assert_round_trip("[int]$x.Length + [string][int]'5'; [Math]::Max(1, 2); [Color]::Red");
+    assert_round_trip("$h = [ordered]@{ A = 1; B = @{ C = @(1, @()) }; D = { $_ } }; @{}.Count");
+    assert_round_trip("(@(2, 1) | Sort-Object).Count; @(1, 2)[0]; ('a').Length; $x.ToUpper()");
+    assert_round_trip("Get-Process | Where-Object { $_.CPU -gt 10 } | Select -First 2");
+    assert_round_trip("icacls $dir --% /grant Users:(F) \"%HOME%\" | Select -First 1");
+    assert_round_trip("'it\\'s \\n' + \"tab\\t $x-y \\$z\"; $null; true; -not false");
+    assert_round_trip("-split $s -join '+'; -join @(1, 2); $l -split ',\\s*'; +$n + +(1).Count");
+    assert_round_trip("${my var} = ${env:PROGRAM FILES}; \"${a}b ${x:y}: ${odd`}name}\"");
+    assert_round_trip(
+        "& 'C:/Program Files/tool.exe' -v $x; & $cmd | & { $_ }; $a = (& Get-Date), 1",
+    );
+}
+
+#[test]
+fn test_round_trip_comments() {
+    assert_round_trip(
+        "# Totals\n$sum = 0 # start\nforeach ($n in @(1, 2)) { # each\n  $sum = $sum + $n\n\n  # keep going\n}\n",
+    );
+    assert_round_trip(
+        "class Point {\n    # Coordinates\n    [int]$X\n}\n$h = @{\n    A = 1 # first\n}\nGet-Item |\n    # filter\n    Where-Object { $_ }",
+    );
+    let formatted =
+        assert_round_trip("<#\n.SYNOPSIS\nGreets\n#>\nfunction Greet { 'hi' } <# inline #>");
+    assert!(formatted.starts_with("<#\n.SYNOPSIS\nGreets\n#>\nfunction Greet {"));
+
+    // Comments inside a hashtable stay with the entries they are written at
+    let source = "$h = @{\n    # key comment\n    a = 1 # after a\n\n    b = @{ c = 2 } # inner\n    # last\n}\n$e = @{ <# none #> }\n";
+    assert_eq!(
+        assert_round_trip(source),
+        "$h = @{\n    # key comment\n    a = 1 # after a\n\n    b = @{ c = 2 } # inner\n    # last\n}\n$e = @{\n    <# none #>\n}\n"
+    );
+}
+
+#[test]
+fn test_round_trip_examples() {
+    let examples = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../examples");
+    let mut formatted = 0;
+    for entry in fs::read_dir(examples).unwrap() {
+        let path = entry.unwrap().path();
+        let source = fs::read_to_string(&path).unwrap();
+        // Only scripts this parser understands can be formatted
+        let Ok(tokens) = Lexer::new(&source).tokenize() else {
+            continue;
+        };
+        if Parser::new(tokens).parse().is_ok() {
+            assert_round_trip(&source);
+            formatted += 1;
+        }
+    }
+    assert!(formatted > 10, "only {} examples parsed", formatted);
+}
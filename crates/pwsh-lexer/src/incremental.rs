@@ -0,0 +1,230 @@
+/// Incremental lexing: keeping a document's tokens up to date as lines change
+use crate::lexer::{LexError, Lexer};
+use crate::token::{LocatedToken, Position, Token, Trivia};
+
+/// The tokens of a document that is edited a few lines at a time, as in an
+/// editor or the REPL. After an edit only the changed lines are lexed again:
+/// lexing restarts just before them and stops as soon as a token lines up
+/// with the old one at the same place in the unchanged text after them, from
+/// where the old tokens are kept (moved by the number of lines added or
+/// removed).
+#[derive(Debug)]
+pub struct IncrementalLexer {
+    /// The document's lines, without their line breaks
+    lines: Vec<String>,
+    tokens: Result<Vec<LocatedToken>, LexError>,
+}
+
+impl IncrementalLexer {
+    /// Lex a whole document
+    pub fn new(source: &str) -> Self {
+        IncrementalLexer {
+            lines: source.split('\n').map(str::to_string).collect(),
+            tokens: Lexer::new(source).tokenize(),
+        }
+    }
+
+    /// The document's text
+    pub fn source(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    /// The document's tokens, ending with Eof, or the error lexing it
+    pub fn tokens(&self) -> Result<&[LocatedToken], &LexError> {
+        self.tokens.as_deref()
+    }
+
+    /// Replace `count` lines starting at line `first` (1-based, as in
+    /// `Position`) with `lines`; a count of 0 inserts them before `first`.
+    /// Returns how many tokens were lexed again.
+    pub fn replace_lines(
+        &mut self,
+        first: usize,
+        count: usize,
+        lines: &[&str],
+    ) -> Result<usize, LexError> {
+        let start = first.saturating_sub(1).min(self.lines.len());
+        let end = (start + count).min(self.lines.len());
+        self.lines
+            .splice(start..end, lines.iter().map(|line| line.to_string()));
+        let source = self.source();
+
+        // After an error there is nothing to reuse
+        let old = match std::mem::replace(&mut self.tokens, Ok(Vec::new())) {
+            Ok(tokens) => tokens,
+            Err(_) => {
+                self.tokens = Lexer::new(&source).tokenize();
+                return self.tokens.as_ref().map(Vec::len).map_err(Clone::clone);
+            }
+        };
+
+        // Tokens ending before the line above the change are kept. The line
+        // above is lexed again too: a token at its end may have looked past
+        // the line break.
+        let first = start + 1;
+        let kept = old
+            .iter()
+            .take_while(|token| token.end.line + 1 < first)
+            .count();
+        let restart = match kept {
+            0 => Position::new(1, 1),
+            _ => old[kept - 1].end,
+        };
+
+        let shift = lines.len() as isize - (end - start) as isize;
+        let after_change = Position::new(first + lines.len(), 1);
+        let chars: Vec<char> = source.chars().collect();
+        let offset = char_offset(&self.lines, restart);
+        let mut lexer = Lexer::starting_at(chars, offset, restart);
+
+        let mut tokens: Vec<LocatedToken> = old[..kept].to_vec();
+        let mut relexed = 0;
+        let mut next_old = kept;
+        let result = loop {
+            let token = match lexer.next_token() {
+                Ok(token) => token,
+                Err(error) => break Err(error),
+            };
+            relexed += 1;
+            let is_eof = token.token == Token::Eof;
+
+            // In the unchanged text after the edit, an old token at the same
+            // place means the rest would come out as before
+            if token.position >= after_change {
+                let was_at = moved(token.position, -shift);
+                while next_old < old.len() && old[next_old].position < was_at {
+                    next_old += 1;
+                }
+                if let Some(previous) = old.get(next_old) {
+                    if *previous == moved_token(&token, -shift) {
+                        tokens.push(token);
+                        tokens.extend(
+                            old[next_old + 1..]
+                                .iter()
+                                .map(|token| moved_token(token, shift)),
+                        );
+                        break Ok(relexed);
+                    }
+                }
+            }
+
+            tokens.push(token);
+            if is_eof {
+                break Ok(relexed);
+            }
+        };
+
+        match result {
+            Ok(relexed) => {
+                self.tokens = Ok(tokens);
+                Ok(relexed)
+            }
+            Err(error) => {
+                self.tokens = Err(error.clone());
+                Err(error)
+            }
+        }
+    }
+}
+
+/// The character offset of `position` in the document made of `lines`
+fn char_offset(lines: &[String], position: Position) -> usize {
+    let before: usize = lines
+        .iter()
+        .take(position.line.saturating_sub(1))
+        .map(|line| line.chars().count() + 1)
+        .sum();
+    before + position.column.saturating_sub(1)
+}
+
+/// A position `shift` lines further down (or up, when negative)
+fn moved(position: Position, shift: isize) -> Position {
+    Position::new(position.line.saturating_add_signed(shift), position.column)
+}
+
+/// A token, with its trivia, `shift` lines further down (or up)
+fn moved_token(token: &LocatedToken, shift: isize) -> LocatedToken {
+    LocatedToken {
+        token: token.token.clone(),
+        position: moved(token.position, shift),
+        end: moved(token.end, shift),
+        trivia: token
+            .trivia
+            .iter()
+            .map(|trivia| match trivia {
+                Trivia::Comment { text, position } => Trivia::Comment {
+                    text: text.clone(),
+                    position: moved(*position, shift),
+                },
+                other => other.clone(),
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Apply an edit both ways and check the incremental tokens match
+    /// lexing the edited document from scratch
+    fn assert_edit(source: &str, first: usize, count: usize, lines: &[&str]) -> usize {
+        let mut incremental = IncrementalLexer::new(source);
+        let relexed = incremental.replace_lines(first, count, lines).unwrap();
+        let expected = Lexer::new(&incremental.source()).tokenize().unwrap();
+        assert_eq!(incremental.tokens().unwrap(), expected.as_slice());
+        relexed
+    }
+
+    fn document(lines: usize) -> String {
+        (1..=lines)
+            .map(|n| format!("$v{} = {} # line {}", n, n, n))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_only_changed_lines_are_lexed_again() {
+        let source = document(200);
+        // Each line is 4 tokens with its line break; an edit in the middle
+        // lexes about the changed line and its neighbours, not the 800 tokens
+        let relexed = assert_edit(&source, 100, 1, &["$changed = 'x'"]);
+        assert!(relexed < 20, "lexed {} tokens again", relexed);
+
+        let relexed = assert_edit(&source, 50, 0, &["function f {", "  1", "}"]);
+        assert!(relexed < 25, "lexed {} tokens again", relexed);
+
+        let relexed = assert_edit(&source, 10, 5, &[]);
+        assert!(relexed < 20, "lexed {} tokens again", relexed);
+    }
+
+    #[test]
+    fn test_edits_at_the_edges_and_across_lines() {
+        let source = document(5);
+        assert_edit(&source, 1, 1, &["# new first line"]);
+        assert_edit(&source, 5, 1, &["$last = 1", "$after = 2"]);
+        assert_edit(&source, 6, 0, &["'appended'"]);
+        assert_edit(&source, 1, 5, &[]);
+
+        // Opening a block comment or string takes in the lines after it
+        assert_edit(
+            "$a = 1\n$b = 2\n$c = 3\n# end #>\n$d = 4",
+            2,
+            1,
+            &["<# open"],
+        );
+        assert_edit("'one\ntwo'\n$x", 1, 1, &["'one"]);
+        assert_edit("$a = \"x\n$b\n\"", 2, 1, &["changed"]);
+    }
+
+    #[test]
+    fn test_lex_errors_are_kept_until_fixed() {
+        let mut incremental = IncrementalLexer::new(&document(3));
+        assert!(incremental.replace_lines(2, 1, &["'unclosed"]).is_err());
+        assert!(incremental.tokens().is_err());
+
+        incremental.replace_lines(2, 1, &["'closed'"]).unwrap();
+        let expected = Lexer::new(&incremental.source()).tokenize().unwrap();
+        assert_eq!(incremental.tokens().unwrap(), expected.as_slice());
+    }
+}
@@ -1,10 +1,11 @@
-use crate::token::{LocatedToken, Position, StringPart, Token};
+use crate::token::{LocatedToken, Position, StringPart, Token, Trivia};
 
 /// Lexer errors
 #[derive(Debug, Clone, PartialEq)]
 pub enum LexError {
     UnexpectedCharacter { ch: char, position: Position },
     UnterminatedString { position: Position },
+    UnterminatedComment { position: Position },
     InvalidNumber { text: String, position: Position },
     InvalidToken { text: String, position: Position },
 }
@@ -26,6 +27,13 @@ impl std::fmt::Display for LexError {
                     position.line, position.column
                 )
             }
+            LexError::UnterminatedComment { position } => {
+                write!(
+                    f,
+                    "Unterminated comment at line {}, column {}",
+                    position.line, position.column
+                )
+            }
             LexError::InvalidNumber { text, position } => {
                 write!(
                     f,
@@ -46,22 +54,52 @@ impl std::fmt::Display for LexError {
 
 impl std::error::Error for LexError {}
 
+impl LexError {
+    /// Where the error was found
+    pub fn position(&self) -> Position {
+        match self {
+            LexError::UnexpectedCharacter { position, .. }
+            | LexError::UnterminatedString { position }
+            | LexError::UnterminatedComment { position }
+            | LexError::InvalidNumber { position, .. }
+            | LexError::InvalidToken { position, .. } => *position,
+        }
+    }
+}
+
 /// PowerShell lexer/tokenizer
+///
+/// `tokenize` lexes the whole input at once. The lexer is also an iterator
+/// over the tokens, so callers can stop early or look ahead with
+/// `.peekable()`.
 pub struct Lexer {
     input: Vec<char>,
     position: usize,
     line: usize,
     column: usize,
+    /// Whether the iterator has yielded Eof or an error
+    finished: bool,
+    /// Whether the last token was a word or a value, after which a word is
+    /// an argument (`Write-Output class`) rather than a keyword
+    after_operand: bool,
 }
 
 impl Lexer {
     /// Create a new lexer from input string
     pub fn new(input: &str) -> Self {
+        Self::starting_at(input.chars().collect(), 0, Position::new(1, 1))
+    }
+
+    /// A lexer that starts part way through `input`, at character `offset`,
+    /// which is `at` in the source
+    pub(crate) fn starting_at(input: Vec<char>, offset: usize, at: Position) -> Self {
         Lexer {
-            input: input.chars().collect(),
-            position: 0,
-            line: 1,
-            column: 1,
+            input,
+            position: offset,
+            line: at.line,
+            column: at.column,
+            finished: false,
+            after_operand: false,
         }
     }
 
@@ -119,16 +157,56 @@ impl Lexer {
         }
     }
 
-    /// Skip comments (# to end of line)
-    fn skip_comment(&mut self) {
-        if self.peek() == Some('#') {
-            while let Some(ch) = self.peek() {
-                if ch == '\n' {
-                    break;
-                }
-                self.advance();
+    /// Read the whitespace and comments before the next token
+    fn read_trivia(&mut self) -> Result<Vec<Trivia>, LexError> {
+        let mut trivia = Vec::new();
+        loop {
+            let start = self.position;
+            self.skip_whitespace();
+            if self.position > start {
+                let text = self.input[start..self.position].iter().collect();
+                trivia.push(Trivia::Whitespace(text));
+            }
+
+            let position = self.current_position();
+            let text = match self.peek() {
+                Some('#') => self.read_line_comment(),
+                Some('<') if self.peek_ahead(1) == Some('#') => self.read_block_comment()?,
+                _ => return Ok(trivia),
+            };
+            trivia.push(Trivia::Comment { text, position });
+        }
+    }
+
+    /// Read a comment from # to the end of the line
+    fn read_line_comment(&mut self) -> String {
+        let mut text = String::new();
+        while let Some(ch) = self.peek() {
+            if ch == '\n' || ch == '\r' {
+                break;
+            }
+            text.push(ch);
+            self.advance();
+        }
+        text
+    }
+
+    /// Read a block comment: <# ... #>
+    fn read_block_comment(&mut self) -> Result<String, LexError> {
+        let start_pos = self.current_position();
+        let mut text = String::new();
+
+        while let Some(ch) = self.advance() {
+            text.push(ch);
+            // The # of the opening <# can't also close the comment: <#>
+            if ch == '>' && text.len() >= 4 && text.ends_with("#>") {
+                return Ok(text);
             }
         }
+
+        Err(LexError::UnterminatedComment {
+            position: start_pos,
+        })
     }
 
     /// Process an escape sequence and return the resulting character(s)
@@ -152,7 +230,7 @@ impl Lexer {
         self.peek() == Some('$')
             && self
                 .peek_ahead(1)
-                .map(|c| c.is_alphanumeric() || c == '_')
+                .map(|c| c.is_alphanumeric() || c == '_' || c == '{')
                 .unwrap_or(false)
     }
 
@@ -207,7 +285,7 @@ impl Lexer {
                 }
 
                 self.advance(); // consume $
-                let var_name = self.read_identifier();
+                let var_name = self.read_variable_name()?;
                 parts.push(StringPart::Variable(var_name));
             } else if ch == '\\' && self.peek_ahead(1).is_some() {
                 // Handle escape sequences
@@ -267,16 +345,7 @@ impl Lexer {
         let start_pos = self.current_position();
         self.advance(); // consume $
 
-        let mut var_name = String::new();
-
-        while let Some(ch) = self.peek() {
-            if ch.is_alphanumeric() || ch == '_' || ch == ':' {
-                var_name.push(ch);
-                self.advance();
-            } else {
-                break;
-            }
-        }
+        let var_name = self.read_variable_name()?;
 
         if var_name.is_empty() {
             Err(LexError::InvalidToken {
@@ -288,6 +357,76 @@ impl Lexer {
         }
     }
 
+    /// Read the name part of a variable: letters, digits and underscores,
+    /// with at most one scope or drive qualifier ($global:x, $env:PATH). A
+    /// colon not followed by a name is left alone, as in "$name: done".
+    /// A braced name (${my var}, ${env:PROGRAM FILES}) runs to the closing
+    /// brace and may hold any character; a backtick escapes the next one.
+    fn read_variable_name(&mut self) -> Result<String, LexError> {
+        if self.peek() == Some('{') {
+            return self.read_braced_variable_name();
+        }
+
+        let is_name_char = |c: char| c.is_alphanumeric() || c == '_';
+        let mut name = String::new();
+        let mut qualified = false;
+
+        while let Some(ch) = self.peek() {
+            if is_name_char(ch) {
+                name.push(ch);
+                self.advance();
+            } else if ch == ':'
+                && !qualified
+                && !name.is_empty()
+                && self.peek_ahead(1).is_some_and(is_name_char)
+            {
+                qualified = true;
+                name.push(ch);
+                self.advance();
+            } else {
+                break;
+            }
+        }
+
+        Ok(name)
+    }
+
+    /// Read a variable name between braces, from the opening brace
+    fn read_braced_variable_name(&mut self) -> Result<String, LexError> {
+        let start_pos = self.current_position();
+        self.advance(); // consume {
+
+        let mut name = String::new();
+        while let Some(ch) = self.peek() {
+            self.advance();
+            match ch {
+                '}' if name.is_empty() => break,
+                '}' => return Ok(name),
+                '`' => match self.peek() {
+                    Some(escaped) => {
+                        name.push(escaped);
+                        self.advance();
+                    }
+                    None => break,
+                },
+                _ => name.push(ch),
+            }
+        }
+
+        Err(LexError::InvalidToken {
+            text: format!("${{{}", name),
+            position: start_pos,
+        })
+    }
+
+    /// Whether the next character after any spaces is `=`
+    fn before_assignment(&self) -> bool {
+        self.input[self.position..]
+            .iter()
+            .find(|ch| !matches!(ch, ' ' | '\t'))
+            .is_some_and(|ch| *ch == '=')
+    }
+
     /// Check if identifier is a keyword
     fn keyword_or_identifier(&self, ident: &str) -> Token {
         match ident.to_lowercase().as_str() {
@@ -295,13 +434,38 @@ impl Lexer {
             "else" => Token::Else,
             "elseif" => Token::ElseIf,
             "function" => Token::Function,
+            "filter" => Token::Filter,
+            "class" => Token::Class,
+            "enum" => Token::Enum,
+            "foreach" => Token::Foreach,
+            "try" => Token::Try,
+            "catch" => Token::Catch,
+            "throw" => Token::Throw,
+            "finally" => Token::Finally,
+            "break" => Token::Break,
+            "continue" => Token::Continue,
             "return" => Token::Return,
-            "true" => Token::Boolean(true),
-            "false" => Token::Boolean(false),
             _ => Token::Identifier(ident.to_string()),
         }
     }
 
+    /// Read the stop-parsing sign `--%` and the rest of the line after it,
+    /// up to a newline or `|`, with no escapes, quotes or variables
+    fn read_stop_parsing(&mut self) -> String {
+        for _ in 0..3 {
+            self.advance();
+        }
+        let mut text = String::new();
+        while let Some(ch) = self.peek() {
+            if matches!(ch, '\n' | '|') {
+                break;
+            }
+            text.push(ch);
+            self.advance();
+        }
+        text.trim().to_string()
+    }
+
     /// Read operator starting with '-'
     fn read_operator(&mut self) -> Result<Token, LexError> {
         let saved_position = self.position;
@@ -319,6 +483,16 @@ impl Lexer {
             "lt" => Ok(Token::Less),
             "ge" => Ok(Token::GreaterOrEqual),
             "le" => Ok(Token::LessOrEqual),
+            "not" => Ok(Token::Not),
+            "f" => Ok(Token::Format),
+            "match" => Ok(Token::Match),
+            "notmatch" => Ok(Token::NotMatch),
+            "like" => Ok(Token::Like),
+            "notlike" => Ok(Token::NotLike),
+            "clike" => Ok(Token::CLike),
+            "cnotlike" => Ok(Token::CNotLike),
+            "join" => Ok(Token::Join),
+            "split" => Ok(Token::Split),
             _ => {
                 // Not a known operator, restore position and return Minus
                 // This allows -First, -Name, etc. to be parsed as minus + identifier
@@ -331,16 +505,28 @@ impl Lexer {
         }
     }
 
-    /// Get next token
+    /// Get next token, with the whitespace and comments before it as trivia
     pub fn next_token(&mut self) -> Result<LocatedToken, LexError> {
-        self.skip_whitespace();
-
-        // Skip comments
-        if self.peek() == Some('#') {
-            self.skip_comment();
-            self.skip_whitespace();
-        }
+        let trivia = self.read_trivia()?;
+        let mut token = self.read_token()?;
+        token.end = self.current_position();
+        token.trivia = trivia;
+        self.after_operand = matches!(
+            token.token,
+            Token::Identifier(_)
+                | Token::Variable(_)
+                | Token::Number(_)
+                | Token::String(_)
+                | Token::InterpolatedString(_)
+                | Token::RightParen
+                | Token::RightBracket
+                | Token::Comma
+        );
+        Ok(token)
+    }
 
+    /// Read the token at the current position, after any trivia
+    fn read_token(&mut self) -> Result<LocatedToken, LexError> {
         let position = self.current_position();
 
         match self.peek() {
@@ -385,6 +571,15 @@ impl Lexer {
                 self.advance();
                 Ok(LocatedToken::new(Token::Dot, position))
             }
+            Some(':') if self.peek_ahead(1) == Some(':') => {
+                self.advance();
+                self.advance();
+                Ok(LocatedToken::new(Token::DoubleColon, position))
+            }
+            Some(':') => {
+                self.advance();
+                Ok(LocatedToken::new(Token::Colon, position))
+            }
             Some('|') => {
                 self.advance();
                 Ok(LocatedToken::new(Token::Pipeline, position))
@@ -397,6 +592,10 @@ impl Lexer {
                 self.advance();
                 Ok(LocatedToken::new(Token::At, position))
             }
+            Some('&') => {
+                self.advance();
+                Ok(LocatedToken::new(Token::Ampersand, position))
+            }
             Some('+') => {
                 self.advance();
                 Ok(LocatedToken::new(Token::Plus, position))
@@ -413,6 +612,14 @@ impl Lexer {
                 self.advance();
                 Ok(LocatedToken::new(Token::Modulo, position))
             }
+            Some('!') => {
+                self.advance();
+                Ok(LocatedToken::new(Token::Not, position))
+            }
+            Some('-') if self.peek_ahead(1) == Some('-') && self.peek_ahead(2) == Some('%') => {
+                let text = self.read_stop_parsing();
+                Ok(LocatedToken::new(Token::StopParsing(text), position))
+            }
             Some('-') => {
                 // Could be minus or an operator like -eq
                 if let Some(next) = self.peek_ahead(1) {
@@ -471,8 +678,18 @@ impl Lexer {
                 Ok(LocatedToken::new(Token::Number(num), position))
             }
             Some(ch) if ch.is_alphabetic() => {
+                // A word directly after '-' is a parameter name (-Filter), a word
+                // directly after '.' or '::' is a member name ($x.Class), a word
+                // before '=' is a hashtable key (@{class = 1}) and a word after
+                // another word or value is an argument: none is a keyword
+                let is_name_position =
+                    self.position > 0 && matches!(self.input[self.position - 1], '-' | '.' | ':');
                 let ident = self.read_identifier();
-                let token = self.keyword_or_identifier(&ident);
+                let token = if is_name_position || self.after_operand || self.before_assignment() {
+                    Token::Identifier(ident)
+                } else {
+                    self.keyword_or_identifier(&ident)
+                };
                 Ok(LocatedToken::new(token, position))
             }
             Some(ch) => {
@@ -499,3 +716,17 @@ impl Lexer {
         Ok(tokens)
     }
 }
+
+/// The tokens one at a time, ending with Eof or at the first error
+impl Iterator for Lexer {
+    type Item = Result<LocatedToken, LexError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+        let result = self.next_token();
+        self.finished = !matches!(&result, Ok(token) if token.token != Token::Eof);
+        Some(result)
+    }
+}
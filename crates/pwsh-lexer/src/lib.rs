@@ -1,5 +1,7 @@
+pub mod incremental;
 pub mod lexer;
 pub mod token;
 
+pub use incremental::IncrementalLexer;
 pub use lexer::{LexError, Lexer};
-pub use token::{LocatedToken, Position, StringPart, Token};
+pub use token::{LocatedToken, Position, Span, StringPart, Token, Trivia};
@@ -1,16 +1,21 @@
 /// Token types for PowerShell lexer
-#[derive(Debug, Clone, PartialEq)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Token {
     // Literals
     String(String),
     InterpolatedString(Vec<StringPart>), // "Hello $name" - will be expanded later
     Number(f64),
-    Boolean(bool),
 
     // Identifiers and Variables
     Identifier(String),
     Variable(String), // $varName
 
+    /// The stop-parsing sign `--%` and the text after it, up to the end of
+    /// the line or a `|`, taken as it is
+    StopParsing(String),
+
     // Operators - Arithmetic
     Plus,     // +
     Minus,    // -
@@ -26,12 +31,36 @@ pub enum Token {
     GreaterOrEqual, // -ge
     LessOrEqual,    // -le
 
+    // Operators - Logical
+    Not, // -not, !
+
+    // Operators - String
+    Format,   // -f
+    Match,    // -match
+    NotMatch, // -notmatch
+    Like,     // -like
+    NotLike,  // -notlike
+    CLike,    // -clike
+    CNotLike, // -cnotlike
+    Join,     // -join
+    Split,    // -split
+
     // Keywords
     If,
     Else,
     ElseIf,
     Function,
+    Filter,
     Return,
+    Class,
+    Enum,
+    Foreach,
+    Try,
+    Catch,
+    Throw,
+    Finally,
+    Break,
+    Continue,
 
     // Syntax
     LeftParen,    // (
@@ -42,10 +71,13 @@ pub enum Token {
     RightBracket, // ]
     Comma,        // ,
     Dot,          // .
+    Colon,        // : (-Name:value)
+    DoubleColon,  // ::
     Pipeline,     // |
     Assignment,   // =
     Semicolon,    // ;
     At,           // @
+    Ampersand,    // & (call operator)
     Newline,
 
     // Special
@@ -53,7 +85,7 @@ pub enum Token {
 }
 
 /// Parts of an interpolated string
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum StringPart {
     Literal(String),
     Variable(String),
@@ -77,9 +109,9 @@ impl std::fmt::Display for Token {
                 write!(f, ")")
             }
             Token::Number(n) => write!(f, "Number({})", n),
-            Token::Boolean(b) => write!(f, "Boolean({})", b),
             Token::Identifier(id) => write!(f, "Identifier({})", id),
             Token::Variable(var) => write!(f, "Variable(${})", var),
+            Token::StopParsing(text) => write!(f, "StopParsing(--% {})", text),
             Token::Plus => write!(f, "Plus"),
             Token::Minus => write!(f, "Minus"),
             Token::Multiply => write!(f, "Multiply"),
@@ -91,11 +123,31 @@ impl std::fmt::Display for Token {
             Token::Less => write!(f, "Less(-lt)"),
             Token::GreaterOrEqual => write!(f, "GreaterOrEqual(-ge)"),
             Token::LessOrEqual => write!(f, "LessOrEqual(-le)"),
+            Token::Not => write!(f, "Not(-not)"),
+            Token::Format => write!(f, "Format(-f)"),
+            Token::Match => write!(f, "Match(-match)"),
+            Token::NotMatch => write!(f, "NotMatch(-notmatch)"),
+            Token::Like => write!(f, "Like(-like)"),
+            Token::NotLike => write!(f, "NotLike(-notlike)"),
+            Token::CLike => write!(f, "CLike(-clike)"),
+            Token::CNotLike => write!(f, "CNotLike(-cnotlike)"),
+            Token::Join => write!(f, "Join(-join)"),
+            Token::Split => write!(f, "Split(-split)"),
             Token::If => write!(f, "If"),
             Token::Else => write!(f, "Else"),
             Token::ElseIf => write!(f, "ElseIf"),
             Token::Function => write!(f, "Function"),
+            Token::Filter => write!(f, "Filter"),
             Token::Return => write!(f, "Return"),
+            Token::Class => write!(f, "Class"),
+            Token::Enum => write!(f, "Enum"),
+            Token::Foreach => write!(f, "Foreach"),
+            Token::Try => write!(f, "Try"),
+            Token::Catch => write!(f, "Catch"),
+            Token::Throw => write!(f, "Throw"),
+            Token::Finally => write!(f, "Finally"),
+            Token::Break => write!(f, "Break"),
+            Token::Continue => write!(f, "Continue"),
             Token::LeftParen => write!(f, "LeftParen"),
             Token::RightParen => write!(f, "RightParen"),
             Token::LeftBrace => write!(f, "LeftBrace"),
@@ -104,10 +156,13 @@ impl std::fmt::Display for Token {
             Token::RightBracket => write!(f, "RightBracket"),
             Token::Comma => write!(f, "Comma"),
             Token::Dot => write!(f, "Dot"),
+            Token::Colon => write!(f, "Colon(:)"),
+            Token::DoubleColon => write!(f, "DoubleColon(::)"),
             Token::Pipeline => write!(f, "Pipeline"),
             Token::Assignment => write!(f, "Assignment"),
             Token::Semicolon => write!(f, "Semicolon"),
             Token::At => write!(f, "At"),
+            Token::Ampersand => write!(f, "Ampersand"),
             Token::Newline => write!(f, "Newline"),
             Token::Eof => write!(f, "Eof"),
         }
@@ -115,7 +170,7 @@ impl std::fmt::Display for Token {
 }
 
 /// Position in source code for error reporting
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize, Deserialize)]
 pub struct Position {
     pub line: usize,
     pub column: usize,
@@ -127,15 +182,69 @@ impl Position {
     }
 }
 
+/// A range of source code: from `start` up to (not including) `end`
+/// The default span (0:0..0:0) marks nodes that weren't parsed from source.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Span {
+    pub start: Position,
+    pub end: Position,
+}
+
+impl Span {
+    pub fn new(start: Position, end: Position) -> Self {
+        Span { start, end }
+    }
+}
+
+impl std::fmt::Debug for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{}:{}..{}:{}",
+            self.start.line, self.start.column, self.end.line, self.end.column
+        )
+    }
+}
+
+/// Source text between tokens that doesn't affect parsing
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Trivia {
+    /// Spaces, tabs and carriage returns
+    Whitespace(String),
+    /// A line comment (`# note`) or block comment (`<# help #>`), including its delimiters
+    Comment { text: String, position: Position },
+}
+
 /// Token with position information
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct LocatedToken {
     pub token: Token,
     pub position: Position,
+    /// Position just past the token's last character
+    pub end: Position,
+    /// Whitespace and comments between the previous token and this one
+    pub trivia: Vec<Trivia>,
 }
 
 impl LocatedToken {
     pub fn new(token: Token, position: Position) -> Self {
-        LocatedToken { token, position }
+        LocatedToken {
+            token,
+            position,
+            end: position,
+            trivia: Vec::new(),
+        }
+    }
+
+    /// The source range the token covers
+    pub fn span(&self) -> Span {
+        Span::new(self.position, self.end)
+    }
+
+    /// Whether a comment precedes the token
+    pub fn has_comment(&self) -> bool {
+        self.trivia
+            .iter()
+            .any(|t| matches!(t, Trivia::Comment { .. }))
     }
 }
@@ -1,4 +1,4 @@
-use pwsh_lexer::{LexError, Lexer, StringPart, Token};
+use pwsh_lexer::{LexError, Lexer, LocatedToken, Position, Span, StringPart, Token, Trivia};
 
 #[test]
 fn test_tokenize_variable() {
@@ -122,23 +122,154 @@ fn test_tokenize_all_comparison_operators() {
     assert_eq!(tokens[5].token, Token::LessOrEqual);
 }
 
+#[test]
+fn test_tokenize_not_operators() {
+    let mut lexer = Lexer::new("-not $found !$x -NOT");
+    let tokens = lexer.tokenize().unwrap();
+    assert_eq!(tokens[0].token, Token::Not);
+    assert_eq!(tokens[1].token, Token::Variable("found".to_string()));
+    assert_eq!(tokens[2].token, Token::Not);
+    assert_eq!(tokens[3].token, Token::Variable("x".to_string()));
+    assert_eq!(tokens[4].token, Token::Not);
+}
+
+#[test]
+fn test_tokenize_format_operator() {
+    let mut lexer = Lexer::new("'{0}' -f $name -F 1");
+    let tokens = lexer.tokenize().unwrap();
+    assert_eq!(tokens[1].token, Token::Format);
+    assert_eq!(tokens[2].token, Token::Variable("name".to_string()));
+    assert_eq!(tokens[3].token, Token::Format);
+}
+
+#[test]
+fn test_tokenize_match_operators() {
+    let mut lexer = Lexer::new("$s -match '\\d' -NotMatch 'x'");
+    let tokens = lexer.tokenize().unwrap();
+    assert_eq!(tokens[1].token, Token::Match);
+    assert_eq!(tokens[2].token, Token::String("\\d".to_string()));
+    assert_eq!(tokens[3].token, Token::NotMatch);
+}
+
+#[test]
+fn test_tokenize_like_operators() {
+    let mut lexer = Lexer::new("$s -like 'a*' -NotLike 'b' -clike 'C' -cnotlike 'd'");
+    let tokens = lexer.tokenize().unwrap();
+    assert_eq!(tokens[1].token, Token::Like);
+    assert_eq!(tokens[3].token, Token::NotLike);
+    assert_eq!(tokens[5].token, Token::CLike);
+    assert_eq!(tokens[7].token, Token::CNotLike);
+}
+
+#[test]
+fn test_tokenize_join_and_split() {
+    let tokens = Lexer::new("-split $s -join ','").tokenize().unwrap();
+    assert_eq!(tokens[0].token, Token::Split);
+    assert_eq!(tokens[2].token, Token::Join);
+}
+
 #[test]
 fn test_tokenize_keywords() {
-    let mut lexer = Lexer::new("if else elseif function return");
+    let mut lexer =
+        Lexer::new("if else elseif function return filter class enum foreach try catch throw finally break continue");
     let tokens = lexer.tokenize().unwrap();
     assert_eq!(tokens[0].token, Token::If);
     assert_eq!(tokens[1].token, Token::Else);
     assert_eq!(tokens[2].token, Token::ElseIf);
     assert_eq!(tokens[3].token, Token::Function);
     assert_eq!(tokens[4].token, Token::Return);
+    assert_eq!(tokens[5].token, Token::Filter);
+    assert_eq!(tokens[6].token, Token::Class);
+    assert_eq!(tokens[7].token, Token::Enum);
+    assert_eq!(tokens[8].token, Token::Foreach);
+    assert_eq!(tokens[9].token, Token::Try);
+    assert_eq!(tokens[10].token, Token::Catch);
+    assert_eq!(tokens[11].token, Token::Throw);
+    assert_eq!(tokens[12].token, Token::Finally);
+    assert_eq!(tokens[13].token, Token::Break);
+    assert_eq!(tokens[14].token, Token::Continue);
+}
+
+#[test]
+fn test_tokenize_static_member_access() {
+    let mut lexer = Lexer::new("[Color]::Red $p.class");
+    let tokens = lexer.tokenize().unwrap();
+    assert_eq!(tokens[0].token, Token::LeftBracket);
+    assert_eq!(tokens[1].token, Token::Identifier("Color".to_string()));
+    assert_eq!(tokens[2].token, Token::RightBracket);
+    assert_eq!(tokens[3].token, Token::DoubleColon);
+    assert_eq!(tokens[4].token, Token::Identifier("Red".to_string()));
+    assert_eq!(tokens[7].token, Token::Identifier("class".to_string()));
 }
 
 #[test]
-fn test_tokenize_boolean_literals() {
-    let mut lexer = Lexer::new("true false");
+fn test_tokenize_keyword_as_parameter_name() {
+    let mut lexer = Lexer::new("Get-ChildItem -Filter '*.txt' -function");
     let tokens = lexer.tokenize().unwrap();
-    assert_eq!(tokens[0].token, Token::Boolean(true));
-    assert_eq!(tokens[1].token, Token::Boolean(false));
+    assert_eq!(tokens[1].token, Token::Minus);
+    assert_eq!(tokens[2].token, Token::Identifier("Filter".to_string()));
+    assert_eq!(tokens[5].token, Token::Identifier("function".to_string()));
+}
+
+#[test]
+fn test_tokenize_keyword_as_argument_or_key() {
+    let words = |input: &str| -> Vec<Token> {
+        Lexer::new(input)
+            .tokenize()
+            .unwrap()
+            .into_iter()
+            .map(|t| t.token)
+            .filter(|t| !matches!(t, Token::Eof))
+            .collect()
+    };
+    let ident = |name: &str| Token::Identifier(name.to_string());
+
+    assert_eq!(
+        words("Write-Output Class enum, filter"),
+        vec![
+            ident("Write-Output"),
+            ident("Class"),
+            ident("enum"),
+            Token::Comma,
+            ident("filter")
+        ]
+    );
+    assert_eq!(
+        words("@{try = 1; Catch=2}"),
+        vec![
+            Token::At,
+            Token::LeftBrace,
+            ident("try"),
+            Token::Assignment,
+            Token::Number(1.0),
+            Token::Semicolon,
+            ident("Catch"),
+            Token::Assignment,
+            Token::Number(2.0),
+            Token::RightBrace
+        ]
+    );
+    // Where a statement can start, the word is still a keyword
+    assert_eq!(
+        words("} else { throw }"),
+        vec![
+            Token::RightBrace,
+            Token::Else,
+            Token::LeftBrace,
+            Token::Throw,
+            Token::RightBrace
+        ]
+    );
+}
+
+#[test]
+fn test_tokenize_boolean_constants() {
+    // Booleans are the variables $true and $false; bare words are names
+    let mut lexer = Lexer::new("$true $False true");
+    let tokens = lexer.tokenize().unwrap();
+    assert_eq!(tokens[0].token, Token::Variable("true".to_string()));
+    assert_eq!(tokens[1].token, Token::Variable("False".to_string()));
+    assert_eq!(tokens[2].token, Token::Identifier("true".to_string()));
 }
 
 #[test]
@@ -163,6 +294,42 @@ fn test_tokenize_comment() {
     assert_eq!(tokens[2].token, Token::Number(5.0));
 }
 
+#[test]
+fn test_comments_are_kept_as_trivia() {
+    let mut lexer = Lexer::new("$x = 5  # five\r\n<# block\ncomment #>  $y");
+    let tokens = lexer.tokenize().unwrap();
+    assert_eq!(tokens[3].token, Token::Newline);
+    assert_eq!(
+        tokens[3].trivia,
+        vec![
+            Trivia::Whitespace("  ".to_string()),
+            Trivia::Comment {
+                text: "# five".to_string(),
+                position: Position::new(1, 9),
+            },
+            Trivia::Whitespace("\r".to_string()),
+        ]
+    );
+    assert_eq!(tokens[4].token, Token::Variable("y".to_string()));
+    assert_eq!(
+        tokens[4].trivia[0],
+        Trivia::Comment {
+            text: "<# block\ncomment #>".to_string(),
+            position: Position::new(2, 1),
+        }
+    );
+    assert_eq!(tokens[4].position, Position::new(3, 13));
+}
+
+#[test]
+fn test_unterminated_block_comment() {
+    let mut lexer = Lexer::new("$x <#> 1");
+    assert!(matches!(
+        lexer.tokenize(),
+        Err(LexError::UnterminatedComment { .. })
+    ));
+}
+
 #[test]
 fn test_tokenize_newline() {
     let mut lexer = Lexer::new("$x = 5\n$y = 10");
@@ -224,6 +391,29 @@ fn test_unterminated_string() {
     }
 }
 
+#[test]
+fn test_lexer_is_an_iterator() {
+    let mut tokens = Lexer::new("$x | Sort-Object").peekable();
+    assert!(matches!(
+        tokens.peek(),
+        Some(Ok(LocatedToken {
+            token: Token::Variable(_),
+            ..
+        }))
+    ));
+    let kinds: Vec<Token> = tokens.map(|token| token.unwrap().token).collect();
+    assert_eq!(kinds.len(), 4);
+    assert_eq!(kinds[3], Token::Eof);
+
+    // The first error ends the tokens
+    let results: Vec<_> = Lexer::new("$x 'open").collect();
+    assert_eq!(results.len(), 2);
+    assert!(matches!(
+        results[1],
+        Err(LexError::UnterminatedString { .. })
+    ));
+}
+
 #[test]
 fn test_invalid_variable() {
     let mut lexer = Lexer::new("$ ");
@@ -244,6 +434,35 @@ fn test_position_tracking() {
     assert_eq!(tokens[4].position.line, 2);
 }
 
+#[test]
+fn test_tokens_end_after_their_last_character() {
+    let tokens = Lexer::new("$name = 'a b' # note\nGet-Item")
+        .tokenize()
+        .unwrap();
+
+    assert_eq!(
+        tokens[0].span(),
+        Span::new(Position::new(1, 1), Position::new(1, 6))
+    );
+    assert_eq!(tokens[2].position, Position::new(1, 9));
+    assert_eq!(tokens[2].end, Position::new(1, 14));
+    // Trivia before a token is not part of it
+    assert_eq!(tokens[3].position, Position::new(1, 21));
+    assert_eq!(tokens[4].span().end, Position::new(2, 9));
+}
+
+#[test]
+fn test_tokens_round_trip_through_json() {
+    let tokens = Lexer::new("# greet\n$name = \"Hi $who\"; 4.5")
+        .tokenize()
+        .unwrap();
+
+    let json = serde_json::to_string(&tokens).unwrap();
+    let back: Vec<LocatedToken> = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, tokens);
+    assert!(json.contains(r#"{"Variable":"name"}"#), "{}", json);
+}
+
 #[test]
 fn test_escape_sequences() {
     let mut lexer = Lexer::new("\"hello\\nworld\"");
@@ -403,6 +622,57 @@ fn test_tokenize_scope_qualified_assignment() {
     assert_eq!(tokens[2].token, Token::Number(100.0));
 }
 
+#[test]
+fn test_tokenize_qualified_variables_in_strings() {
+    let tokens = Lexer::new("$env:HOME\n\"$global:x and $env:USER_NAME\"")
+        .tokenize()
+        .unwrap();
+    assert_eq!(tokens[0].token, Token::Variable("env:HOME".to_string()));
+    assert_eq!(
+        tokens[2].token,
+        Token::InterpolatedString(vec![
+            StringPart::Variable("global:x".to_string()),
+            StringPart::Literal(" and ".to_string()),
+            StringPart::Variable("env:USER_NAME".to_string()),
+        ])
+    );
+
+    // A colon or dash that doesn't continue the name stays text
+    let tokens = Lexer::new("\"$name: $a-b\"").tokenize().unwrap();
+    assert_eq!(
+        tokens[0].token,
+        Token::InterpolatedString(vec![
+            StringPart::Variable("name".to_string()),
+            StringPart::Literal(": ".to_string()),
+            StringPart::Variable("a".to_string()),
+            StringPart::Literal("-b".to_string()),
+        ])
+    );
+}
+
+#[test]
+fn test_tokenize_braced_variables() {
+    let tokens = Lexer::new("${my var} ${env:PROGRAM FILES} ${a`}b}\n\"${name}s\"")
+        .tokenize()
+        .unwrap();
+    assert_eq!(tokens[0].token, Token::Variable("my var".to_string()));
+    assert_eq!(
+        tokens[1].token,
+        Token::Variable("env:PROGRAM FILES".to_string())
+    );
+    assert_eq!(tokens[2].token, Token::Variable("a}b".to_string()));
+    assert_eq!(
+        tokens[4].token,
+        Token::InterpolatedString(vec![
+            StringPart::Variable("name".to_string()),
+            StringPart::Literal("s".to_string()),
+        ])
+    );
+
+    assert!(Lexer::new("${unclosed").tokenize().is_err());
+    assert!(Lexer::new("${}").tokenize().is_err());
+}
+
 #[test]
 fn test_tokenize_scope_qualified_in_expression() {
     let mut lexer = Lexer::new("$global:x + $local:y");
@@ -460,3 +730,39 @@ fn test_tokenize_hashtable_multiple_pairs() {
     assert_eq!(tokens[8].token, Token::Number(30.0));
     assert_eq!(tokens[9].token, Token::RightBrace);
 }
+
+#[test]
+fn test_tokenize_stop_parsing_takes_the_rest_of_the_line() {
+    let mut lexer = Lexer::new("icacls x --% /grant $me:(F) \"a b\"; 'c' | more\nnext");
+    let tokens: Vec<Token> = lexer
+        .tokenize()
+        .unwrap()
+        .into_iter()
+        .map(|t| t.token)
+        .collect();
+    assert_eq!(
+        tokens[2],
+        Token::StopParsing("/grant $me:(F) \"a b\"; 'c'".to_string())
+    );
+    assert_eq!(tokens[3], Token::Pipeline);
+    assert_eq!(tokens[5], Token::Newline);
+}
+
+#[test]
+fn test_tokenize_call_operator() {
+    let tokens = Lexer::new("& 'tool' -x;&$cmd").tokenize().unwrap();
+    let kinds: Vec<_> = tokens.iter().map(|t| t.token.clone()).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            Token::Ampersand,
+            Token::String("tool".to_string()),
+            Token::Minus,
+            Token::Identifier("x".to_string()),
+            Token::Semicolon,
+            Token::Ampersand,
+            Token::Variable("cmd".to_string()),
+            Token::Eof,
+        ]
+    );
+}
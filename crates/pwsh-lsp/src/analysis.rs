@@ -0,0 +1,438 @@
+/// Editor features computed from a document's text: diagnostics, document symbols,
+/// completion and hover. These are pure functions so they can be tested without a client.
+use lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionTextEdit, Diagnostic, DiagnosticSeverity,
+    DocumentSymbol, Hover, HoverContents, MarkupContent, MarkupKind, Position, Range, SymbolKind,
+    TextEdit,
+};
+use pwsh_lexer::{Lexer, LocatedToken, Span, Token};
+use pwsh_parser::{Parser, StatementKind};
+use pwsh_runtime::{CmdletRegistry, CompletionEngine, CompletionKind};
+
+/// Source name reported with diagnostics
+const DIAGNOSTIC_SOURCE: &str = "pwsh";
+
+/// Lexer and parser errors in a document: a lexer error, or every statement's parse error
+pub fn diagnostics(source: &str) -> Vec<Diagnostic> {
+    let errors = match Lexer::new(source).tokenize() {
+        Err(e) => vec![(e.to_string(), Some(e.position()))],
+        Ok(tokens) => {
+            let (_, errors) = Parser::new(tokens).parse_with_recovery();
+            errors
+                .into_iter()
+                .map(|e| (e.to_string(), e.position()))
+                .collect()
+        }
+    };
+
+    errors
+        .into_iter()
+        .map(|(message, position)| {
+            // Errors at the end of input have no position: point at the end of the document
+            let range = match position {
+                Some(position) => Range::new(
+                    source_position(source, position),
+                    source_position(source, advance(position)),
+                ),
+                None => {
+                    let end = offset_to_position(source, source.len());
+                    Range::new(end, end)
+                }
+            };
+            Diagnostic {
+                range,
+                severity: Some(DiagnosticSeverity::ERROR),
+                source: Some(DIAGNOSTIC_SOURCE.to_string()),
+                message,
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+/// Functions and filters defined in a document
+pub fn document_symbols(source: &str) -> Vec<DocumentSymbol> {
+    let tokens = tokenize(source);
+
+    function_definitions(&tokens)
+        .into_iter()
+        .map(|definition| {
+            let name_start = source_position(source, definition.name_span.start);
+            let name_end = source_position(source, definition.name_span.end);
+            let end = definition
+                .end
+                .map_or(name_end, |end| source_position(source, end));
+
+            #[allow(deprecated)]
+            DocumentSymbol {
+                name: definition.name,
+                detail: definition.is_filter.then(|| "filter".to_string()),
+                kind: SymbolKind::FUNCTION,
+                tags: None,
+                deprecated: None,
+                range: Range::new(source_position(source, definition.keyword), end),
+                selection_range: Range::new(name_start, name_end),
+                children: None,
+            }
+        })
+        .collect()
+}
+
+/// Completion items at a position, from the base engine (cmdlets) plus the
+/// functions and variables defined in the document
+pub fn completions(
+    base: &CompletionEngine,
+    source: &str,
+    position: Position,
+) -> Vec<CompletionItem> {
+    let mut engine = base.clone();
+    for (name, parameters) in document_functions(source) {
+        let parameters: Vec<&str> = parameters.iter().map(String::as_str).collect();
+        engine.add_command(&name, &parameters);
+    }
+    let cursor = position_to_offset(source, position);
+    engine.set_variables(document_variables(source, cursor));
+    engine
+        .complete(source, cursor)
+        .into_iter()
+        .map(|completion| {
+            let kind = match completion.kind {
+                CompletionKind::Command => CompletionItemKind::FUNCTION,
+                CompletionKind::Parameter => CompletionItemKind::PROPERTY,
+                CompletionKind::Variable => CompletionItemKind::VARIABLE,
+                CompletionKind::Path if completion.text.ends_with(['/', '\\']) => {
+                    CompletionItemKind::FOLDER
+                }
+                CompletionKind::Path => CompletionItemKind::FILE,
+            };
+            let range = Range::new(
+                offset_to_position(source, completion.replace.start),
+                offset_to_position(source, completion.replace.end),
+            );
+            CompletionItem {
+                label: completion.text.clone(),
+                kind: Some(kind),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit::new(
+                    range,
+                    completion.text,
+                ))),
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+/// Syntax of the cmdlet under the cursor
+pub fn hover(registry: &CmdletRegistry, source: &str, position: Position) -> Option<Hover> {
+    let cursor = position_to_offset(source, position);
+    let is_word = |c: char| c.is_alphanumeric() || c == '-' || c == '_';
+    let start = source[..cursor]
+        .rfind(|c: char| !is_word(c))
+        .map_or(0, |i| i + 1);
+    let end = source[cursor..]
+        .find(|c: char| !is_word(c))
+        .map_or(source.len(), |i| cursor + i);
+
+    let cmdlet = registry.get(&source[start..end])?;
+    let syntax: String = std::iter::once(cmdlet.name().to_string())
+        .chain(cmdlet.parameters().iter().map(|p| format!("[-{}]", p)))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    Some(Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: format!("```powershell\n{}\n```", syntax),
+        }),
+        range: Some(Range::new(
+            offset_to_position(source, start),
+            offset_to_position(source, end),
+        )),
+    })
+}
+
+/// A `function Name { ... }` or `filter Name { ... }` found in the token stream
+struct FunctionDefinition {
+    keyword: pwsh_lexer::Position,
+    name: String,
+    name_span: Span,
+    is_filter: bool,
+    /// Where the closing brace of the body ends, if the body is complete
+    end: Option<pwsh_lexer::Position>,
+}
+
+/// Find function definitions by scanning tokens, so symbols are still reported
+/// while the document has parse errors
+fn function_definitions(tokens: &[LocatedToken]) -> Vec<FunctionDefinition> {
+    let mut definitions = Vec::new();
+    for (i, keyword) in tokens.iter().enumerate() {
+        let is_filter = match keyword.token {
+            Token::Function => false,
+            Token::Filter => true,
+            _ => continue,
+        };
+        let Some(name_token) = tokens.get(i + 1) else {
+            continue;
+        };
+        let Token::Identifier(name) = &name_token.token else {
+            continue;
+        };
+
+        // The body ends at the brace matching the first one after the name
+        let mut depth = 0;
+        let mut end = None;
+        for token in &tokens[i + 2..] {
+            match token.token {
+                Token::LeftBrace => depth += 1,
+                Token::RightBrace if depth == 1 => {
+                    end = Some(token.end);
+                    break;
+                }
+                Token::RightBrace => depth -= 1,
+                _ => {}
+            }
+        }
+
+        definitions.push(FunctionDefinition {
+            keyword: keyword.position,
+            name: name.clone(),
+            name_span: name_token.span(),
+            is_filter,
+            end,
+        });
+    }
+    definitions
+}
+
+/// Names and parameters of the functions defined in a document. Parameters are
+/// only known when the document parses; otherwise just the names are returned.
+fn document_functions(source: &str) -> Vec<(String, Vec<String>)> {
+    let tokens = tokenize(source);
+
+    if let Ok(program) = Parser::new(tokens.clone()).parse() {
+        return program
+            .statements
+            .into_iter()
+            .filter_map(|statement| match statement.kind {
+                StatementKind::FunctionDef {
+                    name, parameters, ..
+                } => Some((name, parameters.into_iter().map(|p| p.name).collect())),
+                _ => None,
+            })
+            .collect();
+    }
+
+    function_definitions(&tokens)
+        .into_iter()
+        .map(|definition| (definition.name, Vec::new()))
+        .collect()
+}
+
+/// Names of the variables used in a document, except the one being typed at `cursor`
+fn document_variables(source: &str, cursor: usize) -> Vec<String> {
+    let tokens = tokenize(source);
+
+    let before = &source[..cursor];
+    let line = before.matches('\n').count() + 1;
+    let column = before[before.rfind('\n').map_or(0, |i| i + 1)..]
+        .chars()
+        .count()
+        + 1;
+    let cursor = pwsh_lexer::Position::new(line, column);
+    let mut names: Vec<String> = tokens
+        .into_iter()
+        .filter_map(|token| match token.token {
+            Token::Variable(name) if token.end != cursor => Some(name),
+            _ => None,
+        })
+        .collect();
+    names.sort_by_key(|name| name.to_lowercase());
+    names.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+    names
+}
+
+/// Tokens of a document. If lexing fails, the tokens before the error are returned
+/// so symbols and completion keep working while e.g. a string is still open.
+fn tokenize(source: &str) -> Vec<LocatedToken> {
+    match Lexer::new(source).tokenize() {
+        Ok(tokens) => tokens,
+        Err(e) => {
+            let end = position_to_offset(source, source_position(source, e.position()));
+            Lexer::new(&source[..end]).tokenize().unwrap_or_default()
+        }
+    }
+}
+
+/// The source position one character to the right
+fn advance(position: pwsh_lexer::Position) -> pwsh_lexer::Position {
+    pwsh_lexer::Position::new(position.line, position.column + 1)
+}
+
+/// Convert a lexer position (1-based line and character column) to an LSP
+/// position (0-based line, UTF-16 offset)
+fn source_position(source: &str, position: pwsh_lexer::Position) -> Position {
+    let line = position.line.saturating_sub(1);
+    let text = source.split('\n').nth(line).unwrap_or("");
+    let character: usize = text
+        .chars()
+        .take(position.column.saturating_sub(1))
+        .map(char::len_utf16)
+        .sum();
+    Position::new(line as u32, character as u32)
+}
+
+/// Convert a byte offset in the source to an LSP position
+pub fn offset_to_position(source: &str, offset: usize) -> Position {
+    let before = &source[..offset.min(source.len())];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    let line = before.matches('\n').count();
+    let character: usize = before[line_start..].chars().map(char::len_utf16).sum();
+    Position::new(line as u32, character as u32)
+}
+
+/// Convert an LSP position to a byte offset in the source, clamped to the line's end
+pub fn position_to_offset(source: &str, position: Position) -> usize {
+    let mut line_start = 0;
+    for _ in 0..position.line {
+        match source[line_start..].find('\n') {
+            Some(i) => line_start += i + 1,
+            None => return source.len(),
+        }
+    }
+
+    let line = &source[line_start..];
+    let line = &line[..line.find('\n').unwrap_or(line.len())];
+    let mut units = 0;
+    for (i, c) in line.char_indices() {
+        if units >= position.character as usize {
+            return line_start + i;
+        }
+        units += c.len_utf16();
+    }
+    line_start + line.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry() -> CmdletRegistry {
+        let mut registry = CmdletRegistry::new();
+        pwsh_cmdlets::register_all(&mut registry);
+        registry
+    }
+
+    #[test]
+    fn test_diagnostics_report_parse_errors_with_positions() {
+        assert!(diagnostics("$x = 1\nWrite-Output $x").is_empty());
+
+        let found = diagnostics("$x = 1\n$y = 'open");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].range.start, Position::new(1, 5));
+        assert_eq!(found[0].severity, Some(DiagnosticSeverity::ERROR));
+        assert!(found[0].message.contains("Unterminated string"));
+
+        // A missing closing brace is reported at the end of the document
+        let found = diagnostics("function Get-Thing {\n  1");
+        assert_eq!(found[0].range.start, Position::new(1, 3));
+
+        // Every statement with an error gets its own diagnostic
+        let found = diagnostics("$a = )\n$b = 2\n$c = ]");
+        let lines: Vec<_> = found.iter().map(|d| d.range.start.line).collect();
+        assert_eq!(lines, vec![0, 2]);
+    }
+
+    #[test]
+    fn test_document_symbols_list_functions_and_filters() {
+        let source = "function Get-Total($a) {\n  if ($a) { $a }\n}\nfilter Double { $_ * 2 }";
+        let symbols = document_symbols(source);
+
+        assert_eq!(symbols.len(), 2);
+        assert_eq!(symbols[0].name, "Get-Total");
+        assert_eq!(symbols[0].kind, SymbolKind::FUNCTION);
+        assert_eq!(
+            symbols[0].range,
+            Range::new(Position::new(0, 0), Position::new(2, 1))
+        );
+        assert_eq!(
+            symbols[0].selection_range,
+            Range::new(Position::new(0, 9), Position::new(0, 18))
+        );
+        assert_eq!(symbols[1].name, "Double");
+        assert_eq!(symbols[1].detail.as_deref(), Some("filter"));
+
+        // Symbols are still found while the document doesn't parse
+        assert_eq!(
+            document_symbols("function Broken {\n  $x = ")[0].name,
+            "Broken"
+        );
+    }
+
+    #[test]
+    fn test_completions_include_document_functions_and_variables() {
+        let mut base = CompletionEngine::new();
+        base.add_command("Get-ChildItem", &["Path", "Recurse"]);
+        let source = "function Get-Total($Items) { $Items }\n$total = 1\nGet-T";
+
+        let items = completions(&base, source, Position::new(2, 5));
+        let labels: Vec<&str> = items.iter().map(|i| i.label.as_str()).collect();
+        assert_eq!(labels, vec!["Get-Total"]);
+        assert_eq!(items[0].kind, Some(CompletionItemKind::FUNCTION));
+        assert_eq!(
+            items[0].text_edit,
+            Some(CompletionTextEdit::Edit(TextEdit::new(
+                Range::new(Position::new(2, 0), Position::new(2, 5)),
+                "Get-Total".to_string()
+            )))
+        );
+
+        let source = "function Get-Total($Items) { $Items }\nGet-Total -i";
+        let items = completions(&base, source, Position::new(1, 12));
+        assert_eq!(items[0].label, "-Items");
+
+        let source = "$total = 1\nWrite-Output $to";
+        let items = completions(&base, source, Position::new(1, 16));
+        assert_eq!(items[0].label, "$total");
+        assert_eq!(items[0].kind, Some(CompletionItemKind::VARIABLE));
+    }
+
+    #[test]
+    fn test_hover_shows_cmdlet_syntax() {
+        let registry = registry();
+        let source = "Get-Content log.txt | Select-Object -First 5";
+
+        let found = hover(&registry, source, Position::new(0, 25)).unwrap();
+        let HoverContents::Markup(content) = found.contents else {
+            panic!("Expected markdown hover");
+        };
+        assert!(content
+            .value
+            .contains("Select-Object [-Property] [-First] [-Last] [-Skip]"));
+        assert_eq!(
+            found.range,
+            Some(Range::new(Position::new(0, 22), Position::new(0, 35)))
+        );
+
+        assert!(hover(&registry, source, Position::new(0, 14)).is_none());
+    }
+
+    #[test]
+    fn test_position_conversions_use_utf16() {
+        let source = "$s = 'é😀'\nGet-Item";
+        assert_eq!(
+            offset_to_position(source, source.len()),
+            Position::new(1, 8)
+        );
+        let emoji_end = source.find('\'').unwrap() + 1 + 'é'.len_utf8() + '😀'.len_utf8();
+        assert_eq!(offset_to_position(source, emoji_end), Position::new(0, 9));
+        assert_eq!(position_to_offset(source, Position::new(0, 9)), emoji_end);
+        assert_eq!(
+            position_to_offset(source, Position::new(0, 99)),
+            emoji_end + 1
+        );
+        assert_eq!(
+            position_to_offset(source, Position::new(5, 0)),
+            source.len()
+        );
+    }
+}
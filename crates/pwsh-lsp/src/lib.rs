@@ -0,0 +1,10 @@
+/// PowerShell Language Server
+///
+/// This crate provides editor support for PowerShell scripts over the Language
+/// Server Protocol: diagnostics for lexer and parser errors, document symbols for
+/// functions, completion through the runtime's completion engine, and hover
+/// showing cmdlet syntax.
+pub mod analysis;
+mod server;
+
+pub use server::{capabilities, run, ServerError};
@@ -0,0 +1,14 @@
+use lsp_server::Connection;
+use pwsh_lsp::ServerError;
+
+fn main() -> Result<(), ServerError> {
+    // stdout carries the protocol, so log to stderr
+    eprintln!("pwsh-lsp: starting");
+
+    let (connection, io_threads) = Connection::stdio();
+    pwsh_lsp::run(connection)?;
+    io_threads.join()?;
+
+    eprintln!("pwsh-lsp: shutting down");
+    Ok(())
+}
@@ -0,0 +1,190 @@
+/// Language server main loop: tracks open documents and answers requests
+use crate::analysis;
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument,
+    Notification as NotificationTrait, PublishDiagnostics,
+};
+use lsp_types::request::{
+    Completion, DocumentSymbolRequest, HoverRequest, Request as RequestTrait,
+};
+use lsp_types::{
+    CompletionOptions, CompletionParams, CompletionResponse, DocumentSymbolParams,
+    DocumentSymbolResponse, HoverParams, HoverProviderCapability, OneOf, PublishDiagnosticsParams,
+    ServerCapabilities, TextDocumentSyncCapability, TextDocumentSyncKind, Url,
+};
+use pwsh_runtime::{CompletionEngine, Evaluator};
+use std::collections::HashMap;
+use std::error::Error;
+
+/// Error type for the server loop
+pub type ServerError = Box<dyn Error + Send + Sync>;
+
+/// Features advertised to the client during initialization
+pub fn capabilities() -> ServerCapabilities {
+    ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        completion_provider: Some(CompletionOptions {
+            trigger_characters: Some(vec!["-".to_string(), "$".to_string()]),
+            ..Default::default()
+        }),
+        hover_provider: Some(HoverProviderCapability::Simple(true)),
+        document_symbol_provider: Some(OneOf::Left(true)),
+        ..Default::default()
+    }
+}
+
+/// Initialize the connection and serve requests until the client shuts down
+pub fn run(connection: Connection) -> Result<(), ServerError> {
+    connection.initialize(serde_json::to_value(capabilities())?)?;
+    Server::new(connection).main_loop()
+}
+
+struct Server {
+    connection: Connection,
+    /// Open documents by URI, with their full text
+    documents: HashMap<Url, String>,
+    /// Provides the built-in cmdlets for completion and hover
+    evaluator: Evaluator,
+    completions: CompletionEngine,
+}
+
+impl Server {
+    fn new(connection: Connection) -> Self {
+        let mut evaluator = Evaluator::new();
+        pwsh_cmdlets::register_all(evaluator.registry_mut());
+        let completions = CompletionEngine::from_evaluator(&evaluator);
+
+        Server {
+            connection,
+            documents: HashMap::new(),
+            evaluator,
+            completions,
+        }
+    }
+
+    fn main_loop(&mut self) -> Result<(), ServerError> {
+        while let Ok(message) = self.connection.receiver.recv() {
+            match message {
+                Message::Request(request) => {
+                    if self.connection.handle_shutdown(&request)? {
+                        return Ok(());
+                    }
+                    self.handle_request(request)?;
+                }
+                Message::Notification(notification) => self.handle_notification(notification)?,
+                Message::Response(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn handle_request(&mut self, request: Request) -> Result<(), ServerError> {
+        let Request { id, method, params } = request;
+        let result = match method.as_str() {
+            Completion::METHOD => self.completion(params),
+            HoverRequest::METHOD => self.hover(params),
+            DocumentSymbolRequest::METHOD => self.document_symbols(params),
+            _ => {
+                return self.send(Response::new_err(
+                    id,
+                    ErrorCode::MethodNotFound as i32,
+                    format!("Unsupported request: {}", method),
+                ))
+            }
+        };
+
+        let response = match result {
+            Ok(value) => Response::new_ok(id, value),
+            Err(error) => Response::new_err(id, ErrorCode::InvalidParams as i32, error.to_string()),
+        };
+        self.send(response)
+    }
+
+    fn completion(&self, params: serde_json::Value) -> serde_json::Result<serde_json::Value> {
+        let params: CompletionParams = serde_json::from_value(params)?;
+        let position = params.text_document_position;
+        let items = self
+            .document(&position.text_document.uri)
+            .map(|source| analysis::completions(&self.completions, source, position.position))
+            .unwrap_or_default();
+        serde_json::to_value(CompletionResponse::Array(items))
+    }
+
+    fn hover(&self, params: serde_json::Value) -> serde_json::Result<serde_json::Value> {
+        let params: HoverParams = serde_json::from_value(params)?;
+        let position = params.text_document_position_params;
+        let hover = self
+            .document(&position.text_document.uri)
+            .and_then(|source| {
+                analysis::hover(self.evaluator.registry(), source, position.position)
+            });
+        serde_json::to_value(hover)
+    }
+
+    fn document_symbols(&self, params: serde_json::Value) -> serde_json::Result<serde_json::Value> {
+        let params: DocumentSymbolParams = serde_json::from_value(params)?;
+        let symbols = self
+            .document(&params.text_document.uri)
+            .map(analysis::document_symbols)
+            .unwrap_or_default();
+        serde_json::to_value(DocumentSymbolResponse::Nested(symbols))
+    }
+
+    fn handle_notification(&mut self, notification: Notification) -> Result<(), ServerError> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params: lsp_types::DidOpenTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                let document = params.text_document;
+                self.update_document(document.uri, document.text)
+            }
+            DidChangeTextDocument::METHOD => {
+                let params: lsp_types::DidChangeTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                // Full sync: the last change holds the whole document
+                match params.content_changes.into_iter().last() {
+                    Some(change) => self.update_document(params.text_document.uri, change.text),
+                    None => Ok(()),
+                }
+            }
+            DidCloseTextDocument::METHOD => {
+                let params: lsp_types::DidCloseTextDocumentParams =
+                    serde_json::from_value(notification.params)?;
+                self.documents.remove(&params.text_document.uri);
+                // Clear the closed document's diagnostics
+                self.publish_diagnostics(params.text_document.uri, Vec::new())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Store a document's new text and publish its diagnostics
+    fn update_document(&mut self, uri: Url, text: String) -> Result<(), ServerError> {
+        let diagnostics = analysis::diagnostics(&text);
+        self.documents.insert(uri.clone(), text);
+        self.publish_diagnostics(uri, diagnostics)
+    }
+
+    fn publish_diagnostics(
+        &self,
+        uri: Url,
+        diagnostics: Vec<lsp_types::Diagnostic>,
+    ) -> Result<(), ServerError> {
+        let params = PublishDiagnosticsParams::new(uri, diagnostics, None);
+        let notification = Notification::new(PublishDiagnostics::METHOD.to_string(), params);
+        self.connection
+            .sender
+            .send(Message::Notification(notification))?;
+        Ok(())
+    }
+
+    fn document(&self, uri: &Url) -> Option<&str> {
+        self.documents.get(uri).map(String::as_str)
+    }
+
+    fn send(&self, response: Response) -> Result<(), ServerError> {
+        self.connection.sender.send(Message::Response(response))?;
+        Ok(())
+    }
+}
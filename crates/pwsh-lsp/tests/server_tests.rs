@@ -0,0 +1,80 @@
+// Drive the language server over an in-memory connection, as an editor would
+use lsp_server::{Connection, Message, Notification, Request, RequestId};
+use lsp_types::{
+    DidOpenTextDocumentParams, DocumentSymbolParams, InitializeParams, PublishDiagnosticsParams,
+    TextDocumentIdentifier, TextDocumentItem, Url,
+};
+use std::thread;
+
+fn request(client: &Connection, id: i32, method: &str, params: impl serde::Serialize) {
+    let request = Request::new(RequestId::from(id), method.to_string(), params);
+    client.sender.send(Message::Request(request)).unwrap();
+}
+
+fn notify(client: &Connection, method: &str, params: impl serde::Serialize) {
+    let notification = Notification::new(method.to_string(), params);
+    client
+        .sender
+        .send(Message::Notification(notification))
+        .unwrap();
+}
+
+fn response(client: &Connection) -> serde_json::Value {
+    match client.receiver.recv().unwrap() {
+        Message::Response(response) => {
+            assert!(response.error.is_none(), "{:?}", response.error);
+            response.result.unwrap()
+        }
+        other => panic!("Expected a response, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_server_publishes_diagnostics_and_answers_requests() {
+    let (server, client) = Connection::memory();
+    let server_thread = thread::spawn(move || pwsh_lsp::run(server).unwrap());
+
+    request(&client, 1, "initialize", InitializeParams::default());
+    let initialized = response(&client);
+    assert_eq!(initialized["capabilities"]["hoverProvider"], true);
+    notify(&client, "initialized", serde_json::json!({}));
+
+    let uri = Url::parse("file:///scripts/build.ps1").unwrap();
+    notify(
+        &client,
+        "textDocument/didOpen",
+        DidOpenTextDocumentParams {
+            text_document: TextDocumentItem::new(
+                uri.clone(),
+                "powershell".to_string(),
+                1,
+                "function Invoke-Build { 'ok' }\n$name = 'open".to_string(),
+            ),
+        },
+    );
+    let Message::Notification(published) = client.receiver.recv().unwrap() else {
+        panic!("Expected diagnostics");
+    };
+    let diagnostics: PublishDiagnosticsParams = serde_json::from_value(published.params).unwrap();
+    assert_eq!(diagnostics.uri, uri);
+    assert_eq!(diagnostics.diagnostics.len(), 1);
+    assert_eq!(diagnostics.diagnostics[0].range.start.line, 1);
+
+    request(
+        &client,
+        2,
+        "textDocument/documentSymbol",
+        DocumentSymbolParams {
+            text_document: TextDocumentIdentifier::new(uri),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        },
+    );
+    let symbols = response(&client);
+    assert_eq!(symbols[0]["name"], "Invoke-Build");
+
+    request(&client, 3, "shutdown", ());
+    response(&client);
+    notify(&client, "exit", ());
+    server_thread.join().unwrap();
+}
@@ -1,22 +1,102 @@
 /// Abstract Syntax Tree definitions for PowerShell
+use crate::help::CommentHelp;
+use crate::requires::Requirements;
+use pwsh_lexer::Position;
+use serde::{Deserialize, Serialize};
+
+pub use pwsh_lexer::Span;
+
 /// A complete PowerShell program
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Program {
     pub statements: Vec<Statement>,
+    /// Comments and blank lines between the statements
+    pub trivia: Vec<Trivia>,
+    /// What the script's `#Requires` statements ask for
+    pub requires: Requirements,
+}
+
+/// A comment or blank line kept from the source, attached to a statement list
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Trivia {
+    /// Index of the statement this belongs to; the number of statements
+    /// for trivia after the last one
+    pub statement: usize,
+    pub kind: TriviaKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TriviaKind {
+    /// One or more empty lines before the statement
+    BlankLine,
+    /// A comment on its own line before the statement
+    Comment { text: String, position: Position },
+    /// A comment after the statement, on its last line: $x = 1 # note
+    TrailingComment { text: String, position: Position },
+}
+
+/// A statement and the source it was parsed from
+/// Equality ignores spans, like for every node: the same code parsed from
+/// different places, or built by hand, compares equal.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Statement {
+    pub kind: StatementKind,
+    pub span: Span,
+}
+
+impl Statement {
+    pub fn new(kind: StatementKind, span: Span) -> Self {
+        Statement { kind, span }
+    }
+}
+
+impl PartialEq for Statement {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind
+    }
+}
+
+/// A statement built in code rather than parsed has no span
+impl From<StatementKind> for Statement {
+    fn from(kind: StatementKind) -> Self {
+        Statement::new(kind, Span::default())
+    }
 }
 
 /// A statement in PowerShell
-#[derive(Debug, Clone, PartialEq)]
-pub enum Statement {
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum StatementKind {
     /// An expression statement
     Expression(Expression),
     /// Variable assignment: $x = value
     Assignment { variable: String, value: Expression },
+    /// Multiple assignment: $a, $b = 1, 2
+    /// The last variable captures any remaining values as an array.
+    MultipleAssignment {
+        variables: Vec<String>,
+        value: Expression,
+    },
+    /// Element or property assignment: $arr[0] = value, $obj.Prop = value
+    /// The target is an Index or MemberAccess chain rooted at a variable.
+    TargetAssignment {
+        target: Expression,
+        value: Expression,
+    },
     /// Function definition
+    /// A filter (`filter Name { ... }`) runs its body once per pipeline item with $_ bound.
+    /// For functions with named blocks, `body` holds the `end` block.
     FunctionDef {
         name: String,
         parameters: Vec<Parameter>,
         body: Block,
+        is_filter: bool,
+        /// `begin { ... }`: runs once before any pipeline input
+        begin: Option<Block>,
+        /// `process { ... }`: runs once per pipeline item with $_ bound
+        process: Option<Block>,
+        /// Comment-based help written before the function or at the start
+        /// or end of its body
+        help: Option<Box<CommentHelp>>,
     },
     /// If/else conditional
     If {
@@ -24,28 +104,157 @@ pub enum Statement {
         then_branch: Block,
         else_branch: Option<Block>,
     },
+    /// Foreach loop: foreach ($item in collection) { body }
+    Foreach {
+        variable: String,
+        collection: Expression,
+        body: Block,
+    },
+    /// Try statement: try { body } catch { handler } finally { cleanup }.
+    /// A terminating error in the body runs the catch block with the error
+    /// record in $_. The finally block runs however the body and catch
+    /// block end: normally, with an error, or by return, break or continue.
+    /// At least one of catch and finally is present.
+    Try {
+        body: Block,
+        catch: Option<Block>,
+        finally: Option<Block>,
+    },
+    /// Class definition: class Name { [type]$Prop; Name() { } [type] Method() { } }
+    ClassDef(ClassDef),
+    /// Enum definition: enum Name { A; B = 5 }
+    EnumDef(EnumDef),
     /// Return statement
     Return(Option<Expression>),
+    /// Break out of the innermost loop
+    Break,
+    /// Go on with the next iteration of the innermost loop
+    Continue,
+    /// Throw statement: `throw "message"`, `throw $record`, or a bare
+    /// `throw` in a catch block to throw the caught error again
+    Throw(Option<Expression>),
     /// Pipeline expression
     Pipeline(Pipeline),
 }
 
 /// Function parameter
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Parameter {
     pub name: String,
+    /// Type constraint: [string]$name
+    pub type_name: Option<String>,
+    pub default_value: Option<Expression>,
+    pub span: Span,
+}
+
+impl PartialEq for Parameter {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.type_name == other.type_name
+            && self.default_value == other.default_value
+    }
+}
+
+/// Class definition
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClassDef {
+    pub name: String,
+    pub properties: Vec<ClassProperty>,
+    /// Constructors are methods named after the class
+    pub constructors: Vec<ClassMethod>,
+    pub methods: Vec<ClassMethod>,
+}
+
+/// Class property: [type]$Name = default
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClassProperty {
+    pub name: String,
+    pub type_name: Option<String>,
     pub default_value: Option<Expression>,
+    pub span: Span,
+}
+
+impl PartialEq for ClassProperty {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.type_name == other.type_name
+            && self.default_value == other.default_value
+    }
+}
+
+/// Class method or constructor: [type] Name($a, $b) { body }
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClassMethod {
+    pub name: String,
+    pub return_type: Option<String>,
+    pub parameters: Vec<Parameter>,
+    pub body: Block,
+    pub is_static: bool,
+    pub span: Span,
+}
+
+impl PartialEq for ClassMethod {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.return_type == other.return_type
+            && self.parameters == other.parameters
+            && self.body == other.body
+            && self.is_static == other.is_static
+    }
+}
+
+/// Enum definition; members without an explicit value continue from the previous one
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnumDef {
+    pub name: String,
+    pub members: Vec<(String, Option<i64>)>,
 }
 
 /// A block of statements
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Block {
     pub statements: Vec<Statement>,
+    /// Comments and blank lines between the statements
+    pub trivia: Vec<Trivia>,
+    /// From the opening brace through the closing one
+    pub span: Span,
+}
+
+impl PartialEq for Block {
+    fn eq(&self, other: &Self) -> bool {
+        self.statements == other.statements && self.trivia == other.trivia
+    }
+}
+
+/// An expression and the source it was parsed from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Expression {
+    pub kind: ExpressionKind,
+    pub span: Span,
+}
+
+impl Expression {
+    pub fn new(kind: ExpressionKind, span: Span) -> Self {
+        Expression { kind, span }
+    }
+}
+
+impl PartialEq for Expression {
+    fn eq(&self, other: &Self) -> bool {
+        self.kind == other.kind
+    }
+}
+
+/// An expression built in code rather than parsed has no span
+impl From<ExpressionKind> for Expression {
+    fn from(kind: ExpressionKind) -> Self {
+        Expression::new(kind, Span::default())
+    }
 }
 
 /// An expression in PowerShell
-#[derive(Debug, Clone, PartialEq)]
-pub enum Expression {
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ExpressionKind {
     /// Literal value
     Literal(Literal),
     /// Variable reference: $varName
@@ -66,15 +275,52 @@ pub enum Expression {
         name: String,
         arguments: Vec<Argument>,
     },
+    /// Call operator: & $command arguments, running the command its operand
+    /// names or the script block it holds
+    Invoke {
+        command: Box<Expression>,
+        arguments: Vec<Argument>,
+    },
     /// Member access: object.member
     MemberAccess {
         object: Box<Expression>,
         member: String,
     },
+    /// Index access: object[index]
+    Index {
+        object: Box<Expression>,
+        index: Box<Expression>,
+    },
+    /// Method invocation: object.Method(arg1, arg2)
+    MethodCall {
+        object: Box<Expression>,
+        method: String,
+        arguments: Vec<Expression>,
+    },
+    /// Type literal: [TypeName]
+    TypeLiteral(String),
+    /// Type cast: [int]$x, [ordered]@{a = 1}
+    Cast {
+        type_name: String,
+        expression: Box<Expression>,
+    },
+    /// Static member access: [TypeName]::Member
+    StaticMember { type_name: String, member: String },
+    /// Static method invocation: [TypeName]::Method(arg1, arg2)
+    StaticMethodCall {
+        type_name: String,
+        method: String,
+        arguments: Vec<Expression>,
+    },
     /// Script block: { statements }
     ScriptBlock(Block),
     /// Hashtable: @{key1=value1; key2=value2}
-    Hashtable(Vec<(String, Expression)>),
+    Hashtable {
+        entries: Vec<(String, Expression)>,
+        /// Comments and blank lines between the entries, each entry
+        /// counting as a statement
+        trivia: Vec<Trivia>,
+    },
     /// Array: @(item1, item2, ...)
     Array(Vec<Expression>),
     /// Pipeline: expr | expr | expr
@@ -82,7 +328,7 @@ pub enum Expression {
 }
 
 /// Literal values
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Literal {
     String(String),
     InterpolatedString(Vec<StringPart>),
@@ -92,14 +338,14 @@ pub enum Literal {
 }
 
 /// Parts of an interpolated string
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum StringPart {
     Literal(String),
     Variable(String),
 }
 
 /// Binary operators
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum BinaryOperator {
     // Arithmetic
     Add,      // +
@@ -115,26 +361,78 @@ pub enum BinaryOperator {
     Less,           // -lt
     GreaterOrEqual, // -ge
     LessOrEqual,    // -le
+
+    // String
+    Format,   // -f
+    Match,    // -match
+    NotMatch, // -notmatch
+    Like,     // -like
+    NotLike,  // -notlike
+    CLike,    // -clike
+    CNotLike, // -cnotlike
+    Join,     // -join
+    Split,    // -split
 }
 
 /// Unary operators
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum UnaryOperator {
     Negate, // -
+    Plus,   // +
     Not,    // !
+    Join,   // -join
+    Split,  // -split
 }
 
 /// Function/cmdlet argument
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum Argument {
     /// Positional argument
     Positional(Expression),
     /// Named parameter: -Name value
-    Named { name: String, value: Expression },
+    Named {
+        name: String,
+        value: Expression,
+        /// From the dash through the value
+        span: Span,
+    },
+    /// The text after the stop-parsing sign: `--% /grant user:(F)`, passed
+    /// on as it is written
+    StopParsing { text: String, span: Span },
+}
+
+impl Argument {
+    /// The source range of the whole argument
+    pub fn span(&self) -> Span {
+        match self {
+            Argument::Positional(value) => value.span,
+            Argument::Named { span, .. } | Argument::StopParsing { span, .. } => *span,
+        }
+    }
+}
+
+impl PartialEq for Argument {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Argument::Positional(a), Argument::Positional(b)) => a == b,
+            (
+                Argument::Named { name, value, .. },
+                Argument::Named {
+                    name: other_name,
+                    value: other_value,
+                    ..
+                },
+            ) => name == other_name && value == other_value,
+            (Argument::StopParsing { text, .. }, Argument::StopParsing { text: other, .. }) => {
+                text == other
+            }
+            _ => false,
+        }
+    }
 }
 
 /// Pipeline of commands
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Pipeline {
     pub stages: Vec<Expression>,
 }
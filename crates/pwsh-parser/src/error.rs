@@ -62,3 +62,16 @@ impl std::fmt::Display for ParseError {
 }
 
 impl std::error::Error for ParseError {}
+
+impl ParseError {
+    /// Where the error was found; None at end of input
+    pub fn position(&self) -> Option<Position> {
+        match self {
+            ParseError::UnexpectedToken { position, .. }
+            | ParseError::InvalidExpression { position, .. }
+            | ParseError::InvalidStatement { position, .. }
+            | ParseError::InvalidOperator { position, .. } => Some(*position),
+            ParseError::UnexpectedEof { .. } => None,
+        }
+    }
+}
@@ -0,0 +1,202 @@
+/// Comment-based help: `<# .SYNOPSIS ... #>` blocks documenting a function
+use serde::{Deserialize, Serialize};
+
+/// The help written in comments before or inside a function
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CommentHelp {
+    pub synopsis: Option<String>,
+    pub description: Option<String>,
+    /// `.PARAMETER name` sections, in the order written
+    pub parameters: Vec<ParameterHelp>,
+    /// One entry per `.EXAMPLE` section
+    pub examples: Vec<String>,
+    pub inputs: Option<String>,
+    pub outputs: Option<String>,
+    pub notes: Option<String>,
+    /// One entry per `.LINK` section
+    pub links: Vec<String>,
+}
+
+/// The text of a `.PARAMETER name` section
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParameterHelp {
+    pub name: String,
+    pub description: String,
+}
+
+impl CommentHelp {
+    /// The help for parameter `name`, ignoring case
+    pub fn parameter(&self, name: &str) -> Option<&ParameterHelp> {
+        self.parameters
+            .iter()
+            .find(|parameter| parameter.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Read help from comments as the lexer keeps them, delimiters included:
+    /// one block comment or a run of line comments. None unless the text
+    /// has at least one section and every `.KEYWORD` line is a known one,
+    /// so an ordinary comment is never taken for help.
+    pub fn parse(comments: &[&str]) -> Option<CommentHelp> {
+        let lines: Vec<&str> = comments
+            .iter()
+            .flat_map(|comment| comment_lines(comment))
+            .collect();
+
+        let mut help = CommentHelp::default();
+        let mut section: Option<Section> = None;
+        let mut text: Vec<&str> = Vec::new();
+        for line in lines {
+            match keyword(line) {
+                Some(Ok(next)) => {
+                    if let Some((name, argument)) = section.take() {
+                        help.add(&name, argument, &text);
+                    }
+                    section = Some(next);
+                    text.clear();
+                }
+                Some(Err(())) => return None,
+                None if section.is_some() => text.push(line),
+                // Text before the first keyword is not part of any section
+                None => {}
+            }
+        }
+        let (name, argument) = section?;
+        help.add(&name, argument, &text);
+        Some(help)
+    }
+
+    /// Store a finished section
+    fn add(&mut self, keyword: &str, argument: Option<String>, lines: &[&str]) {
+        let text = section_text(lines);
+        match keyword {
+            "SYNOPSIS" => self.synopsis = Some(text),
+            "DESCRIPTION" => self.description = Some(text),
+            "PARAMETER" => self.parameters.push(ParameterHelp {
+                name: argument.unwrap_or_default(),
+                description: text,
+            }),
+            "EXAMPLE" => self.examples.push(text),
+            "INPUTS" => self.inputs = Some(text),
+            "OUTPUTS" => self.outputs = Some(text),
+            "NOTES" => self.notes = Some(text),
+            "LINK" => self.links.push(text),
+            // Recognised, but not kept
+            _ => {}
+        }
+    }
+}
+
+/// Keywords PowerShell accepts in comment-based help
+const KEYWORDS: &[&str] = &[
+    "SYNOPSIS",
+    "DESCRIPTION",
+    "PARAMETER",
+    "EXAMPLE",
+    "INPUTS",
+    "OUTPUTS",
+    "NOTES",
+    "LINK",
+    "COMPONENT",
+    "ROLE",
+    "FUNCTIONALITY",
+    "FORWARDHELPTARGETNAME",
+    "FORWARDHELPCATEGORY",
+    "REMOTEHELPRUNSPACE",
+    "EXTERNALHELP",
+];
+
+/// The lines of a comment without its delimiters
+fn comment_lines(comment: &str) -> Vec<&str> {
+    if let Some(inner) = comment.strip_prefix("<#") {
+        inner.strip_suffix("#>").unwrap_or(inner).lines().collect()
+    } else {
+        vec![comment.strip_prefix('#').unwrap_or(comment)]
+    }
+}
+
+/// A help keyword, upper-cased, and the argument after it
+type Section = (String, Option<String>);
+
+/// A `.KEYWORD [argument]` line: Ok with the keyword and its argument, Err
+/// for an unknown keyword, None for any other line
+fn keyword(line: &str) -> Option<Result<Section, ()>> {
+    let rest = line.trim().strip_prefix('.')?;
+    let mut words = rest.splitn(2, char::is_whitespace);
+    let name = words.next()?.to_ascii_uppercase();
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    if !KEYWORDS.contains(&name.as_str()) {
+        return Some(Err(()));
+    }
+    let argument = words
+        .next()
+        .map(str::trim)
+        .filter(|argument| !argument.is_empty())
+        .map(str::to_string);
+    Some(Ok((name, argument)))
+}
+
+/// A section's lines, outdented by the indentation they share and without
+/// the blank lines around them
+fn section_text(lines: &[&str]) -> String {
+    let indent = lines
+        .iter()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    let lines: Vec<&str> = lines
+        .iter()
+        .map(|line| line.get(indent..).unwrap_or("").trim_end())
+        .collect();
+    lines.join("\n").trim_matches('\n').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_block_comment_help() {
+        let help = CommentHelp::parse(&["<#
+    .SYNOPSIS
+        Greets someone.
+    .DESCRIPTION
+        Writes a greeting.
+
+        Twice, if asked.
+    .PARAMETER Name
+        Who to greet.
+    .EXAMPLE
+        Greet -Name World
+    .EXAMPLE
+        Greet Bob
+    #>"])
+        .unwrap();
+        assert_eq!(help.synopsis.as_deref(), Some("Greets someone."));
+        assert_eq!(
+            help.description.as_deref(),
+            Some("Writes a greeting.\n\nTwice, if asked.")
+        );
+        assert_eq!(
+            help.parameter("name").map(|p| p.description.as_str()),
+            Some("Who to greet.")
+        );
+        assert_eq!(help.examples, vec!["Greet -Name World", "Greet Bob"]);
+    }
+
+    #[test]
+    fn test_parse_line_comment_help() {
+        let help =
+            CommentHelp::parse(&["# .synopsis", "#   Adds one.", "# .notes", "#   None."]).unwrap();
+        assert_eq!(help.synopsis.as_deref(), Some("Adds one."));
+        assert_eq!(help.notes.as_deref(), Some("None."));
+    }
+
+    #[test]
+    fn test_ordinary_comments_are_not_help() {
+        assert_eq!(CommentHelp::parse(&["# just a note"]), None);
+        assert_eq!(CommentHelp::parse(&["<# .SYNOPSIS x\n.BOGUS y #>"]), None);
+    }
+}
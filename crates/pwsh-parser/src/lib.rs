@@ -1,7 +1,11 @@
 pub mod ast;
 pub mod error;
+pub mod help;
 pub mod parser;
+pub mod requires;
 
 pub use ast::*;
 pub use error::ParseError;
+pub use help::{CommentHelp, ParameterHelp};
 pub use parser::Parser;
+pub use requires::Requirements;
@@ -1,22 +1,48 @@
 /// Parser implementation using recursive descent with Pratt parsing for expressions
 use crate::ast::*;
 use crate::error::ParseError;
-use pwsh_lexer::{LocatedToken, Token};
+use crate::help::CommentHelp;
+use crate::requires::Requirements;
+use pwsh_lexer::{LocatedToken, Position, Token};
+
+/// Precedence used for unary operand parsing (higher than every binary operator)
+const UNARY_PRECEDENCE: u8 = 5;
 
 /// Parser for PowerShell code
 pub struct Parser {
     tokens: Vec<LocatedToken>,
     current: usize,
+    /// Trivia for each statement list being parsed, innermost last
+    trivia_frames: Vec<TriviaFrame>,
+    /// The errors recovered from so far, when parsing with recovery
+    errors: Option<Vec<ParseError>>,
+}
+
+/// Comments and blank lines collected while parsing a statement list
+#[derive(Default)]
+struct TriviaFrame {
+    /// Collected trivia and the index of the token it was found before
+    trivia: Vec<(usize, Trivia)>,
+    /// Number of statements completed so far
+    statements: usize,
+    /// Whether a statement is being parsed
+    in_statement: bool,
 }
 
 impl Parser {
     /// Create a new parser from a token stream
     pub fn new(tokens: Vec<LocatedToken>) -> Self {
-        Parser { tokens, current: 0 }
+        Parser {
+            tokens,
+            current: 0,
+            trivia_frames: Vec::new(),
+            errors: None,
+        }
     }
 
     /// Parse the token stream into a program
     pub fn parse(&mut self) -> Result<Program, ParseError> {
+        self.trivia_frames.push(TriviaFrame::default());
         let mut statements = Vec::new();
 
         while !self.is_at_end() {
@@ -26,10 +52,220 @@ impl Parser {
                 continue;
             }
 
-            statements.push(self.parse_statement()?);
+            self.parse_recoverable_statement(&mut statements, false)?;
+        }
+
+        // Comments at the end of the input come before the Eof token
+        if self.current < self.tokens.len() {
+            self.collect_trivia(self.current);
         }
 
-        Ok(Program { statements })
+        let trivia = self.pop_trivia_frame();
+        let requires = match Requirements::from_trivia(&trivia) {
+            Ok(requires) => requires,
+            Err(error) => match &mut self.errors {
+                Some(errors) => {
+                    errors.push(error);
+                    Requirements::default()
+                }
+                None => return Err(error),
+            },
+        };
+        Ok(Program {
+            statements,
+            trivia,
+            requires,
+        })
+    }
+
+    /// Parse the token stream into a program, carrying on past errors: a
+    /// statement with an error is left out and parsing picks up again at the
+    /// next statement, so every error in the script is found at once. Returns
+    /// the statements that parsed and the errors, in the order found.
+    pub fn parse_with_recovery(&mut self) -> (Program, Vec<ParseError>) {
+        self.errors = Some(Vec::new());
+        let program = self.parse();
+        let mut errors = self.errors.take().unwrap_or_default();
+        // Every statement's errors are recovered from, so this is not expected to fail
+        let program = program.unwrap_or_else(|error| {
+            errors.push(error);
+            Program {
+                statements: Vec::new(),
+                trivia: Vec::new(),
+                requires: Requirements::default(),
+            }
+        });
+        (program, errors)
+    }
+
+    /// Parse a statement into a statement list. When recovering, an error
+    /// is recorded and the statement skipped instead of returned.
+    fn parse_recoverable_statement(
+        &mut self,
+        statements: &mut Vec<Statement>,
+        in_block: bool,
+    ) -> Result<(), ParseError> {
+        let start = self.current;
+        let frames = self.trivia_frames.len();
+        let error = match self.parse_listed_statement() {
+            Ok(statement) => {
+                statements.push(statement);
+                return Ok(());
+            }
+            Err(error) => error,
+        };
+        let Some(errors) = self.errors.as_mut() else {
+            return Err(error);
+        };
+        // Blocks can report the same error again after the parser backs up
+        if !errors.contains(&error) {
+            errors.push(error);
+        }
+
+        // Drop what the failed statement left behind: the trivia frames of
+        // blocks inside it and the trivia collected in it
+        self.trivia_frames.truncate(frames);
+        if let Some(frame) = self.trivia_frames.last_mut() {
+            frame.in_statement = false;
+        }
+        self.rewind(start);
+        self.skip_statement(in_block);
+        Ok(())
+    }
+
+    /// Skip the tokens of a statement that failed to parse, up to the line
+    /// break or semicolon after it outside any brackets, or the `}` ending
+    /// the block it is in. A statement carries on over line breaks before
+    /// `else`, `elseif`, `catch` and `finally`, and after a `|`.
+    fn skip_statement(&mut self, in_block: bool) {
+        let mut depth = 0usize;
+        let mut first = true;
+        while !self.is_at_end() {
+            let Some(token) = self.peek().cloned() else {
+                break;
+            };
+            if depth == 0 && !first {
+                match token {
+                    Token::Semicolon => break,
+                    Token::RightBrace if in_block => break,
+                    Token::Newline if !self.statement_continues() => break,
+                    _ => {}
+                }
+            }
+            match token {
+                Token::LeftParen | Token::LeftBrace | Token::LeftBracket => depth += 1,
+                Token::RightParen | Token::RightBrace | Token::RightBracket => {
+                    depth = depth.saturating_sub(1)
+                }
+                _ => {}
+            }
+            self.advance();
+            first = false;
+        }
+    }
+
+    /// Whether the statement before the line break at the current token
+    /// goes on after it
+    fn statement_continues(&self) -> bool {
+        let after_pipe = self.current > 0 && self.tokens[self.current - 1].token == Token::Pipeline;
+        let next = self.tokens[self.current..]
+            .iter()
+            .find(|token| token.token != Token::Newline)
+            .map(|token| &token.token);
+        after_pipe
+            || matches!(
+                next,
+                Some(Token::Else | Token::ElseIf | Token::Catch | Token::Finally)
+            )
+    }
+
+    /// Parse a statement of a program or block, tracking which statement
+    /// comments and blank lines belong to
+    fn parse_listed_statement(&mut self) -> Result<Statement, ParseError> {
+        if let Some(frame) = self.trivia_frames.last_mut() {
+            frame.in_statement = true;
+        }
+        let statement = self.parse_statement()?;
+        self.finish_statement();
+        Ok(statement)
+    }
+
+    /// Mark the current statement complete, so trivia after it goes to the next one
+    fn finish_statement(&mut self) {
+        if let Some(frame) = self.trivia_frames.last_mut() {
+            if frame.in_statement {
+                frame.in_statement = false;
+                frame.statements += 1;
+            }
+        }
+    }
+
+    fn pop_trivia_frame(&mut self) -> Vec<Trivia> {
+        self.trivia_frames
+            .pop()
+            .map(|frame| frame.trivia.into_iter().map(|(_, t)| t).collect())
+            .unwrap_or_default()
+    }
+
+    /// Attach the comments before a consumed token, and the blank line it may
+    /// end, to the statement list being parsed
+    fn collect_trivia(&mut self, index: usize) {
+        let Some(frame) = self.trivia_frames.last_mut() else {
+            return;
+        };
+        let token = &self.tokens[index];
+        // Trivia after other tokens on the same line trails their statement
+        let follows_content = index > 0 && self.tokens[index - 1].token != Token::Newline;
+
+        // A second newline in a row ends an empty line; ones inside a statement are dropped
+        if token.token == Token::Newline
+            && index > 0
+            && !follows_content
+            && !token.has_comment()
+            && !frame.in_statement
+        {
+            let statement = frame.statements;
+            let repeated = matches!(
+                frame.trivia.last(),
+                Some((_, Trivia { statement: s, kind: TriviaKind::BlankLine })) if *s == statement
+            );
+            if !repeated {
+                frame.trivia.push((
+                    index,
+                    Trivia {
+                        statement,
+                        kind: TriviaKind::BlankLine,
+                    },
+                ));
+            }
+        }
+
+        for trivia in &token.trivia {
+            let pwsh_lexer::Trivia::Comment { text, position } = trivia else {
+                continue;
+            };
+            let (text, position) = (text.clone(), *position);
+            let (statement, kind) = match (frame.in_statement, follows_content) {
+                (true, true) => (
+                    frame.statements,
+                    TriviaKind::TrailingComment { text, position },
+                ),
+                (false, true) if frame.statements > 0 => (
+                    frame.statements - 1,
+                    TriviaKind::TrailingComment { text, position },
+                ),
+                _ => (frame.statements, TriviaKind::Comment { text, position }),
+            };
+            frame.trivia.push((index, Trivia { statement, kind }));
+        }
+    }
+
+    /// Go back to an earlier token, dropping the trivia collected after it
+    fn rewind(&mut self, index: usize) {
+        self.current = index;
+        if let Some(frame) = self.trivia_frames.last_mut() {
+            frame.trivia.retain(|(i, _)| *i < index);
+        }
     }
 
     /// Parse a single statement
@@ -39,39 +275,86 @@ impl Parser {
             self.advance();
         }
 
+        let start = self.start_position();
+        let kind = self.parse_statement_kind()?;
+        Ok(Statement::new(kind, self.span_from(start)))
+    }
+
+    fn parse_statement_kind(&mut self) -> Result<StatementKind, ParseError> {
         // Check for keywords first
         if self.check(&Token::If) {
             return self.parse_if_statement();
         }
 
-        if self.check(&Token::Function) {
+        if self.check(&Token::Foreach) {
+            return self.parse_foreach_statement();
+        }
+
+        if self.check(&Token::Try) {
+            return self.parse_try_statement();
+        }
+
+        if self.check(&Token::Function) || self.check(&Token::Filter) {
             return self.parse_function_def();
         }
 
+        if self.check(&Token::Class) {
+            return self.parse_class_def();
+        }
+
+        if self.check(&Token::Enum) {
+            return self.parse_enum_def();
+        }
+
         if self.check(&Token::Return) {
             return self.parse_return_statement();
         }
 
+        if self.check(&Token::Throw) {
+            return self.parse_throw_statement();
+        }
+
+        if self.check(&Token::Break) || self.check(&Token::Continue) {
+            let kind = if self.check(&Token::Break) {
+                StatementKind::Break
+            } else {
+                StatementKind::Continue
+            };
+            self.advance();
+            self.consume_statement_terminator();
+            return Ok(kind);
+        }
+
         // Check for variable assignment
         if self.check_ahead_for_assignment() {
             return self.parse_assignment();
         }
 
+        // Check for multiple assignment: $a, $b = 1, 2
+        if self.check_ahead_for_multiple_assignment() {
+            return self.parse_multiple_assignment();
+        }
+
+        // Check for element/property assignment: $arr[0] = v, $obj.Prop = v
+        if let Some(statement) = self.try_parse_target_assignment()? {
+            return Ok(statement);
+        }
+
         // Check for pipeline (contains |)
         if self.contains_pipeline() {
             let pipeline = self.parse_pipeline()?;
             self.consume_statement_terminator();
-            return Ok(Statement::Pipeline(pipeline));
+            return Ok(StatementKind::Pipeline(pipeline));
         }
 
         // Otherwise, parse as expression statement
         let expr = self.parse_expression()?;
         self.consume_statement_terminator();
-        Ok(Statement::Expression(expr))
+        Ok(StatementKind::Expression(expr))
     }
 
     /// Parse an assignment statement: $var = expr
-    fn parse_assignment(&mut self) -> Result<Statement, ParseError> {
+    fn parse_assignment(&mut self) -> Result<StatementKind, ParseError> {
         let var_token = self.advance();
         let variable = match &var_token.token {
             Token::Variable(name) => name.clone(),
@@ -85,22 +368,98 @@ impl Parser {
         };
 
         self.consume(&Token::Assignment, "=")?;
+        let value = self.parse_assignment_value()?;
 
+        Ok(StatementKind::Assignment { variable, value })
+    }
+
+    /// Parse a multiple assignment statement: $a, $b, $rest = expr
+    fn parse_multiple_assignment(&mut self) -> Result<StatementKind, ParseError> {
+        let mut variables = Vec::new();
+
+        loop {
+            let var_token = self.advance();
+            match &var_token.token {
+                Token::Variable(name) => variables.push(name.clone()),
+                _ => {
+                    return Err(ParseError::UnexpectedToken {
+                        expected: "variable".to_string(),
+                        found: var_token.token.clone(),
+                        position: var_token.position,
+                    })
+                }
+            }
+
+            if !self.check(&Token::Comma) {
+                break;
+            }
+            self.advance(); // consume ,
+        }
+
+        self.consume(&Token::Assignment, "=")?;
+        let value = self.parse_assignment_value()?;
+
+        Ok(StatementKind::MultipleAssignment { variables, value })
+    }
+
+    /// Parse the right-hand side of an assignment, which may be a pipeline
+    /// or a comma-separated list of values ($x = 1, 2, 3)
+    fn parse_assignment_value(&mut self) -> Result<Expression, ParseError> {
+        let start = self.start_position();
         // Check if the value contains a pipeline
         let value = if self.contains_pipeline() {
-            // Parse as pipeline and wrap in Expression::Pipeline
+            // Parse as pipeline and wrap in ExpressionKind::Pipeline
             let pipeline = self.parse_pipeline()?;
-            Expression::Pipeline(pipeline)
+            Expression::new(ExpressionKind::Pipeline(pipeline), self.span_from(start))
         } else {
-            self.parse_expression()?
+            let first = self.parse_expression()?;
+            if self.check(&Token::Comma) {
+                let mut items = vec![first];
+                while self.check(&Token::Comma) {
+                    self.advance();
+                    items.push(self.parse_expression()?);
+                }
+                Expression::new(ExpressionKind::Array(items), self.span_from(start))
+            } else {
+                first
+            }
         };
         self.consume_statement_terminator();
+        Ok(value)
+    }
+
+    /// Try to parse an element or property assignment: $arr[0] = v, $obj.Prop = v
+    /// Returns None (without consuming tokens) when the statement is not an assignment.
+    fn try_parse_target_assignment(&mut self) -> Result<Option<StatementKind>, ParseError> {
+        let starts_with_target = matches!(self.peek(), Some(Token::Variable(_)))
+            && matches!(
+                self.tokens.get(self.current + 1).map(|t| &t.token),
+                Some(Token::Dot) | Some(Token::LeftBracket)
+            );
+        if !starts_with_target {
+            return Ok(None);
+        }
+
+        let start = self.current;
+        // Only the variable and its member/index chain make up the target
+        let target = self.parse_expression_with_precedence(UNARY_PRECEDENCE)?;
+        if !self.check(&Token::Assignment)
+            || !matches!(
+                target.kind,
+                ExpressionKind::Index { .. } | ExpressionKind::MemberAccess { .. }
+            )
+        {
+            self.rewind(start);
+            return Ok(None);
+        }
 
-        Ok(Statement::Assignment { variable, value })
+        self.advance(); // consume =
+        let value = self.parse_assignment_value()?;
+        Ok(Some(StatementKind::TargetAssignment { target, value }))
     }
 
     /// Parse an if statement
-    fn parse_if_statement(&mut self) -> Result<Statement, ParseError> {
+    fn parse_if_statement(&mut self) -> Result<StatementKind, ParseError> {
         self.consume(&Token::If, "if")?;
         self.consume(&Token::LeftParen, "(")?;
         let condition = self.parse_expression()?;
@@ -115,16 +474,114 @@ impl Parser {
             None
         };
 
-        Ok(Statement::If {
+        Ok(StatementKind::If {
             condition,
             then_branch,
             else_branch,
         })
     }
 
-    /// Parse a function definition
-    fn parse_function_def(&mut self) -> Result<Statement, ParseError> {
-        self.consume(&Token::Function, "function")?;
+    /// Parse a foreach loop: foreach ($item in $collection) { ... }
+    fn parse_foreach_statement(&mut self) -> Result<StatementKind, ParseError> {
+        self.consume(&Token::Foreach, "foreach")?;
+        self.consume(&Token::LeftParen, "(")?;
+
+        let var_token = self.advance();
+        let variable = match &var_token.token {
+            Token::Variable(name) => name.clone(),
+            _ => {
+                return Err(ParseError::UnexpectedToken {
+                    expected: "loop variable".to_string(),
+                    found: var_token.token.clone(),
+                    position: var_token.position,
+                })
+            }
+        };
+
+        let in_token = self.advance();
+        if !matches!(&in_token.token, Token::Identifier(word) if word.eq_ignore_ascii_case("in")) {
+            return Err(ParseError::UnexpectedToken {
+                expected: "in".to_string(),
+                found: in_token.token.clone(),
+                position: in_token.position,
+            });
+        }
+
+        let collection = self.parse_expression()?;
+        self.consume(&Token::RightParen, ")")?;
+        let body = self.parse_block()?;
+
+        Ok(StatementKind::Foreach {
+            variable,
+            collection,
+            body,
+        })
+    }
+
+    /// Parse a try statement: try { ... } catch { ... } finally { ... },
+    /// with a catch, a finally or both, each of which may start on a line
+    /// of its own
+    fn parse_try_statement(&mut self) -> Result<StatementKind, ParseError> {
+        let start = self.start_position();
+        self.consume(&Token::Try, "try")?;
+        let body = self.parse_block()?;
+
+        let catch = if self.skip_newlines_before(&Token::Catch) {
+            self.advance();
+            if self.check(&Token::LeftBracket) {
+                return Err(ParseError::InvalidStatement {
+                    message: "Catch blocks for specific exception types are not supported"
+                        .to_string(),
+                    position: self.start_position(),
+                });
+            }
+            Some(self.parse_block()?)
+        } else {
+            None
+        };
+        let finally = if self.skip_newlines_before(&Token::Finally) {
+            self.advance();
+            Some(self.parse_block()?)
+        } else {
+            None
+        };
+
+        if catch.is_none() && finally.is_none() {
+            return Err(ParseError::InvalidStatement {
+                message: "The try statement is missing its catch or finally block".to_string(),
+                position: start,
+            });
+        }
+        Ok(StatementKind::Try {
+            body,
+            catch,
+            finally,
+        })
+    }
+
+    /// Skip the newlines before `token` if it comes next, so a block can
+    /// start on a line of its own; otherwise leave them
+    fn skip_newlines_before(&mut self, token: &Token) -> bool {
+        let before = self.current;
+        while self.check(&Token::Newline) {
+            self.advance();
+        }
+        if self.check(token) {
+            return true;
+        }
+        self.rewind(before);
+        false
+    }
+
+    /// Parse a function or filter definition
+    fn parse_function_def(&mut self) -> Result<StatementKind, ParseError> {
+        let keyword = self.current;
+        let is_filter = self.check(&Token::Filter);
+        if is_filter {
+            self.consume(&Token::Filter, "filter")?;
+        } else {
+            self.consume(&Token::Function, "function")?;
+        }
 
         let name_token = self.advance();
         let name = match &name_token.token {
@@ -145,47 +602,429 @@ impl Parser {
             Vec::new()
         };
 
-        let body = self.parse_block()?;
+        let open = self.current;
+        let (begin, process, body) = if !is_filter && self.check_ahead_for_named_blocks() {
+            self.parse_named_blocks()?
+        } else {
+            (None, None, self.parse_block()?)
+        };
+        let close = self.current - 1;
+
+        // Help goes before the function, or first or last in its body
+        let help = self
+            .help_before(keyword)
+            .or_else(|| self.help_after(open))
+            .or_else(|| self.help_before(close))
+            .map(Box::new);
+
+        Ok(StatementKind::FunctionDef {
+            name,
+            parameters,
+            body,
+            is_filter,
+            begin,
+            process,
+            help,
+        })
+    }
+
+    /// Comment-based help in the comments just before token `index`: a
+    /// block comment or a run of line comments, at most one blank line away
+    fn help_before(&self, index: usize) -> Option<CommentHelp> {
+        let mut comments = Vec::new();
+        let mut blank_lines = 0;
+        let mut index = index;
+        loop {
+            let token = &self.tokens[index];
+            let found: Vec<&str> = Self::comments_on(token).collect();
+            if found.is_empty() {
+                // A comment-less line break ends a blank line (or the line
+                // of the keyword itself)
+                if !comments.is_empty() || (token.token == Token::Newline && blank_lines == 1) {
+                    break;
+                }
+                if token.token == Token::Newline {
+                    blank_lines += 1;
+                }
+            } else {
+                comments.splice(0..0, found);
+            }
+            if index == 0 || self.tokens[index - 1].token != Token::Newline {
+                break;
+            }
+            index -= 1;
+        }
+        CommentHelp::parse(&comments)
+    }
+
+    /// Comment-based help in the comments just after token `index`, before
+    /// anything else on the lines that follow it
+    fn help_after(&self, index: usize) -> Option<CommentHelp> {
+        let mut comments = Vec::new();
+        for token in &self.tokens[index + 1..] {
+            let found: Vec<&str> = Self::comments_on(token).collect();
+            if found.is_empty() && !comments.is_empty() {
+                break;
+            }
+            comments.extend(found);
+            if token.token != Token::Newline {
+                break;
+            }
+        }
+        CommentHelp::parse(&comments)
+    }
+
+    /// The text of the comments kept before a token
+    fn comments_on(token: &LocatedToken) -> impl Iterator<Item = &str> {
+        token.trivia.iter().filter_map(|trivia| match trivia {
+            pwsh_lexer::Trivia::Comment { text, .. } => Some(text.as_str()),
+            _ => None,
+        })
+    }
+
+    /// Check if a function body starts with a named block: { begin { ... } ... }
+    fn check_ahead_for_named_blocks(&self) -> bool {
+        if !self.check(&Token::LeftBrace) {
+            return false;
+        }
+
+        let mut idx = self.current + 1;
+        while idx < self.tokens.len()
+            && matches!(self.tokens[idx].token, Token::Newline | Token::Semicolon)
+        {
+            idx += 1;
+        }
+
+        match (self.tokens.get(idx), self.tokens.get(idx + 1)) {
+            (Some(name), Some(next)) => {
+                matches!(&name.token, Token::Identifier(n) if Self::is_named_block(n))
+                    && next.token == Token::LeftBrace
+            }
+            _ => false,
+        }
+    }
+
+    fn is_named_block(name: &str) -> bool {
+        matches!(name.to_lowercase().as_str(), "begin" | "process" | "end")
+    }
+
+    /// Parse a function body made of named blocks: { begin { } process { } end { } }
+    /// Returns (begin, process, end); a missing end block is empty.
+    fn parse_named_blocks(&mut self) -> Result<(Option<Block>, Option<Block>, Block), ParseError> {
+        self.consume(&Token::LeftBrace, "{")?;
+
+        let mut begin = None;
+        let mut process = None;
+        let mut end = None;
+
+        while !self.check(&Token::RightBrace) && !self.is_at_end() {
+            if self.check(&Token::Newline) || self.check(&Token::Semicolon) {
+                self.advance();
+                continue;
+            }
+
+            let name_token = self.advance();
+            let slot = match &name_token.token {
+                Token::Identifier(n) if n.eq_ignore_ascii_case("begin") => &mut begin,
+                Token::Identifier(n) if n.eq_ignore_ascii_case("process") => &mut process,
+                Token::Identifier(n) if n.eq_ignore_ascii_case("end") => &mut end,
+                _ => {
+                    return Err(ParseError::UnexpectedToken {
+                        expected: "begin, process, or end block".to_string(),
+                        found: name_token.token,
+                        position: name_token.position,
+                    })
+                }
+            };
+            if slot.is_some() {
+                return Err(ParseError::InvalidStatement {
+                    message: "Duplicate named block in function body".to_string(),
+                    position: name_token.position,
+                });
+            }
+            *slot = Some(self.parse_block()?);
+        }
+
+        self.consume(&Token::RightBrace, "}")?;
+
+        let end = end.unwrap_or(Block {
+            statements: Vec::new(),
+            trivia: Vec::new(),
+            span: Span::default(),
+        });
+        Ok((begin, process, end))
+    }
+
+    /// Parse function parameters
+    fn parse_parameters(&mut self) -> Result<Vec<Parameter>, ParseError> {
+        self.consume(&Token::LeftParen, "(")?;
+        let mut parameters = Vec::new();
+
+        if !self.check(&Token::RightParen) {
+            loop {
+                let start = self.start_position();
+                // Optional type constraint: [string]$name
+                let type_name = if self.check(&Token::LeftBracket) {
+                    Some(self.parse_type_name()?)
+                } else {
+                    None
+                };
+
+                let param_token = self.advance();
+                let name = match &param_token.token {
+                    Token::Variable(n) => n.clone(),
+                    _ => {
+                        return Err(ParseError::UnexpectedToken {
+                            expected: "parameter name".to_string(),
+                            found: param_token.token.clone(),
+                            position: param_token.position,
+                        })
+                    }
+                };
+
+                // Check for default value
+                let default_value = if self.check(&Token::Assignment) {
+                    self.advance();
+                    Some(self.parse_expression()?)
+                } else {
+                    None
+                };
+
+                parameters.push(Parameter {
+                    name,
+                    type_name,
+                    default_value,
+                    span: self.span_from(start),
+                });
+
+                if !self.check(&Token::Comma) {
+                    break;
+                }
+                self.advance();
+            }
+        }
+
+        self.consume(&Token::RightParen, ")")?;
+        Ok(parameters)
+    }
+
+    /// Parse a class definition:
+    /// class Name { [type]$Prop = value; Name($a) { } [type] Method($b) { } static [type] M() { } }
+    fn parse_class_def(&mut self) -> Result<StatementKind, ParseError> {
+        self.consume(&Token::Class, "class")?;
+        let name = self.parse_identifier("class name")?;
+        self.consume(&Token::LeftBrace, "{")?;
+
+        let mut class = ClassDef {
+            name,
+            properties: Vec::new(),
+            constructors: Vec::new(),
+            methods: Vec::new(),
+        };
+
+        loop {
+            while self.check(&Token::Newline) || self.check(&Token::Semicolon) {
+                self.advance();
+            }
+            if self.check(&Token::RightBrace) || self.is_at_end() {
+                break;
+            }
+
+            let start = self.start_position();
+            let is_static = matches!(self.peek(), Some(Token::Identifier(w)) if w.eq_ignore_ascii_case("static"));
+            if is_static {
+                self.advance();
+            }
+
+            let type_name = if self.check(&Token::LeftBracket) {
+                Some(self.parse_type_name()?)
+            } else {
+                None
+            };
+
+            let member_token = self.advance();
+            match member_token.token {
+                Token::Variable(prop_name) => {
+                    if is_static {
+                        return Err(ParseError::InvalidStatement {
+                            message: "Static properties are not supported".to_string(),
+                            position: member_token.position,
+                        });
+                    }
+                    let default_value = if self.check(&Token::Assignment) {
+                        self.advance();
+                        Some(self.parse_expression()?)
+                    } else {
+                        None
+                    };
+                    class.properties.push(ClassProperty {
+                        name: prop_name,
+                        type_name,
+                        default_value,
+                        span: self.span_from(start),
+                    });
+                }
+                Token::Identifier(method_name) => {
+                    let parameters = self.parse_parameters()?;
+                    let body = self.parse_block()?;
+                    let is_constructor = method_name.eq_ignore_ascii_case(&class.name);
+                    let method = ClassMethod {
+                        name: method_name,
+                        return_type: type_name,
+                        parameters,
+                        body,
+                        is_static,
+                        span: self.span_from(start),
+                    };
+                    if is_constructor {
+                        class.constructors.push(method);
+                    } else {
+                        class.methods.push(method);
+                    }
+                }
+                other => {
+                    return Err(ParseError::UnexpectedToken {
+                        expected: "property or method definition".to_string(),
+                        found: other,
+                        position: member_token.position,
+                    })
+                }
+            }
+        }
+
+        self.consume(&Token::RightBrace, "}")?;
+        Ok(StatementKind::ClassDef(class))
+    }
+
+    /// Parse an enum definition: enum Name { A; B = 5 }
+    fn parse_enum_def(&mut self) -> Result<StatementKind, ParseError> {
+        self.consume(&Token::Enum, "enum")?;
+        let name = self.parse_identifier("enum name")?;
+        self.consume(&Token::LeftBrace, "{")?;
+
+        let mut members = Vec::new();
+        loop {
+            while self.check(&Token::Newline) || self.check(&Token::Semicolon) {
+                self.advance();
+            }
+            if self.check(&Token::RightBrace) || self.is_at_end() {
+                break;
+            }
+
+            let member = self.parse_identifier("enum member name")?;
+            let value = if self.check(&Token::Assignment) {
+                self.advance();
+                let negative = self.check(&Token::Minus);
+                if negative {
+                    self.advance();
+                }
+                let value_token = self.advance();
+                match value_token.token {
+                    Token::Number(n) if n.fract() == 0.0 => {
+                        Some(if negative { -(n as i64) } else { n as i64 })
+                    }
+                    other => {
+                        return Err(ParseError::UnexpectedToken {
+                            expected: "integer enum value".to_string(),
+                            found: other,
+                            position: value_token.position,
+                        })
+                    }
+                }
+            } else {
+                None
+            };
+            members.push((member, value));
+        }
+
+        self.consume(&Token::RightBrace, "}")?;
+        Ok(StatementKind::EnumDef(EnumDef { name, members }))
+    }
+
+    /// Parse a type name in brackets: [string], [System.IO.Path], [int[]]
+    fn parse_type_name(&mut self) -> Result<String, ParseError> {
+        self.consume(&Token::LeftBracket, "[")?;
+        let mut name = self.parse_identifier("type name")?;
+
+        while self.check(&Token::Dot) {
+            self.advance();
+            name.push('.');
+            name.push_str(&self.parse_identifier("type name")?);
+        }
+
+        // Array type suffix: [int[]]
+        while self.check(&Token::LeftBracket) {
+            self.advance();
+            self.consume(&Token::RightBracket, "]")?;
+            name.push_str("[]");
+        }
+
+        self.consume(&Token::RightBracket, "]")?;
+        Ok(name)
+    }
+
+    /// Check if the token after a type literal starts the operand of a cast
+    fn starts_cast_operand(&self) -> bool {
+        matches!(
+            self.peek(),
+            Some(
+                Token::At
+                    | Token::Variable(_)
+                    | Token::LeftParen
+                    | Token::LeftBracket
+                    | Token::Number(_)
+                    | Token::String(_)
+                    | Token::InterpolatedString(_)
+            )
+        )
+    }
+
+    /// Parse the operand of a cast; like unary operators it binds tighter
+    /// than any binary operator: [int]$x + 1 casts only $x
+    fn parse_cast(
+        &mut self,
+        type_name: String,
+        position: Position,
+    ) -> Result<ExpressionKind, ParseError> {
+        let expression = self.parse_expression_with_precedence(UNARY_PRECEDENCE)?;
 
-        Ok(Statement::FunctionDef {
-            name,
-            parameters,
-            body,
+        if type_name.eq_ignore_ascii_case("ordered")
+            && !matches!(expression.kind, ExpressionKind::Hashtable { .. })
+        {
+            return Err(ParseError::InvalidExpression {
+                message: "The ordered attribute can be specified only on a hashtable literal"
+                    .to_string(),
+                position,
+            });
+        }
+
+        Ok(ExpressionKind::Cast {
+            type_name,
+            expression: Box::new(expression),
         })
     }
 
-    /// Parse function parameters
-    fn parse_parameters(&mut self) -> Result<Vec<Parameter>, ParseError> {
+    /// Parse a single identifier, e.g. a class or member name
+    fn parse_identifier(&mut self, expected: &str) -> Result<String, ParseError> {
+        let token = self.advance();
+        match token.token {
+            Token::Identifier(name) => Ok(name),
+            other => Err(ParseError::UnexpectedToken {
+                expected: expected.to_string(),
+                found: other,
+                position: token.position,
+            }),
+        }
+    }
+
+    /// Parse parenthesized method arguments: (arg1, arg2)
+    fn parse_method_arguments(&mut self) -> Result<Vec<Expression>, ParseError> {
         self.consume(&Token::LeftParen, "(")?;
-        let mut parameters = Vec::new();
+        let mut arguments = Vec::new();
 
         if !self.check(&Token::RightParen) {
             loop {
-                let param_token = self.advance();
-                let name = match &param_token.token {
-                    Token::Variable(n) => n.clone(),
-                    _ => {
-                        return Err(ParseError::UnexpectedToken {
-                            expected: "parameter name".to_string(),
-                            found: param_token.token.clone(),
-                            position: param_token.position,
-                        })
-                    }
-                };
-
-                // Check for default value
-                let default_value = if self.check(&Token::Assignment) {
-                    self.advance();
-                    Some(self.parse_expression()?)
-                } else {
-                    None
-                };
-
-                parameters.push(Parameter {
-                    name,
-                    default_value,
-                });
-
+                arguments.push(self.parse_expression()?);
                 if !self.check(&Token::Comma) {
                     break;
                 }
@@ -194,31 +1033,44 @@ impl Parser {
         }
 
         self.consume(&Token::RightParen, ")")?;
-        Ok(parameters)
+        Ok(arguments)
     }
 
     /// Parse a return statement
-    fn parse_return_statement(&mut self) -> Result<Statement, ParseError> {
+    fn parse_return_statement(&mut self) -> Result<StatementKind, ParseError> {
         self.consume(&Token::Return, "return")?;
+        let value = self.parse_optional_statement_value()?;
+        self.consume_statement_terminator();
+        Ok(StatementKind::Return(value))
+    }
+
+    /// Parse a throw statement: throw, throw "message", throw $error
+    fn parse_throw_statement(&mut self) -> Result<StatementKind, ParseError> {
+        self.consume(&Token::Throw, "throw")?;
+        let value = self.parse_optional_statement_value()?;
+        self.consume_statement_terminator();
+        Ok(StatementKind::Throw(value))
+    }
 
-        // Return can have an optional expression
-        let value = if self.check(&Token::Newline)
+    /// The expression after `return` or `throw`, if the statement doesn't
+    /// end with the keyword
+    fn parse_optional_statement_value(&mut self) -> Result<Option<Expression>, ParseError> {
+        if self.check(&Token::Newline)
             || self.check(&Token::Semicolon)
             || self.check(&Token::RightBrace)
             || self.is_at_end()
         {
-            None
+            Ok(None)
         } else {
-            Some(self.parse_expression()?)
-        };
-
-        self.consume_statement_terminator();
-        Ok(Statement::Return(value))
+            Ok(Some(self.parse_expression()?))
+        }
     }
 
     /// Parse a block: { statements }
     fn parse_block(&mut self) -> Result<Block, ParseError> {
+        let start = self.start_position();
         self.consume(&Token::LeftBrace, "{")?;
+        self.trivia_frames.push(TriviaFrame::default());
 
         let mut statements = Vec::new();
 
@@ -229,12 +1081,16 @@ impl Parser {
                 continue;
             }
 
-            statements.push(self.parse_statement()?);
+            self.parse_recoverable_statement(&mut statements, true)?;
         }
 
         self.consume(&Token::RightBrace, "}")?;
 
-        Ok(Block { statements })
+        Ok(Block {
+            statements,
+            trivia: self.pop_trivia_frame(),
+            span: self.span_from(start),
+        })
     }
 
     /// Parse a pipeline: expr | expr | expr
@@ -245,6 +1101,10 @@ impl Parser {
 
         while self.check(&Token::Pipeline) {
             self.advance();
+            // A trailing | continues the pipeline on the next line
+            while self.check(&Token::Newline) {
+                self.advance();
+            }
             stages.push(self.parse_expression()?);
         }
 
@@ -261,10 +1121,50 @@ impl Parser {
         &mut self,
         min_precedence: u8,
     ) -> Result<Expression, ParseError> {
-        let mut left = self.parse_primary()?;
+        let start = self.start_position();
+        let primary = self.parse_primary()?;
+        let mut left = self.parse_accessors(start, primary, false)?;
+
+        // Binary operators, binding by precedence
+        while let Some((precedence, operator)) = self.get_binary_operator() {
+            if precedence < min_precedence {
+                break;
+            }
+
+            self.advance(); // consume operator
+            let right = if operator == BinaryOperator::Format {
+                self.parse_format_arguments(precedence + 1)?
+            } else {
+                self.parse_expression_with_precedence(precedence + 1)?
+            };
 
+            let kind = ExpressionKind::BinaryOp {
+                left: Box::new(left),
+                operator,
+                right: Box::new(right),
+            };
+            left = Expression::new(kind, self.span_from(start));
+        }
+
+        Ok(left)
+    }
+
+    /// Parse the member accesses, method calls and indexes after an
+    /// expression. They bind tighter than any operator. In argument mode
+    /// only ones written right after it count: `$x.Name` is one argument,
+    /// `$x .Name` two.
+    fn parse_accessors(
+        &mut self,
+        start: Position,
+        mut left: Expression,
+        adjacent_only: bool,
+    ) -> Result<Expression, ParseError> {
         loop {
-            // Check for member access first (highest precedence)
+            if adjacent_only && !self.follows_adjacently() {
+                break;
+            }
+
+            // Member access: $x.Name, $x.Method(1)
             if self.check(&Token::Dot) {
                 self.advance();
                 let member_token = self.advance();
@@ -279,35 +1179,64 @@ impl Parser {
                     }
                 };
 
-                left = Expression::MemberAccess {
-                    object: Box::new(left),
-                    member,
+                let is_call =
+                    self.check(&Token::LeftParen) && (!adjacent_only || self.follows_adjacently());
+                let kind = if is_call {
+                    ExpressionKind::MethodCall {
+                        object: Box::new(left),
+                        method: member,
+                        arguments: self.parse_method_arguments()?,
+                    }
+                } else {
+                    ExpressionKind::MemberAccess {
+                        object: Box::new(left),
+                        member,
+                    }
                 };
+                left = Expression::new(kind, self.span_from(start));
                 continue;
             }
 
-            // Check for binary operators
-            if let Some((precedence, operator)) = self.get_binary_operator() {
-                if precedence < min_precedence {
-                    break;
-                }
-
-                self.advance(); // consume operator
-                let right = self.parse_expression_with_precedence(precedence + 1)?;
+            // Index access binds as tightly as member access: $arr[0], (Get-Content f)[1]
+            if self.check(&Token::LeftBracket) {
+                self.advance();
+                let index = self.parse_expression()?;
+                self.consume(&Token::RightBracket, "]")?;
 
-                left = Expression::BinaryOp {
-                    left: Box::new(left),
-                    operator,
-                    right: Box::new(right),
+                let kind = ExpressionKind::Index {
+                    object: Box::new(left),
+                    index: Box::new(index),
                 };
-            } else {
-                break;
+                left = Expression::new(kind, self.span_from(start));
+                continue;
             }
+
+            break;
         }
 
         Ok(left)
     }
 
+    /// Parse the right operand of -f, where a comma-separated list binds
+    /// tighter than the operator: "{0} {1}" -f $a, $b
+    fn parse_format_arguments(&mut self, precedence: u8) -> Result<Expression, ParseError> {
+        let start = self.start_position();
+        let first = self.parse_expression_with_precedence(precedence)?;
+        if !self.check(&Token::Comma) {
+            return Ok(first);
+        }
+
+        let mut items = vec![first];
+        while self.check(&Token::Comma) {
+            self.advance();
+            items.push(self.parse_expression_with_precedence(precedence)?);
+        }
+        Ok(Expression::new(
+            ExpressionKind::Array(items),
+            self.span_from(start),
+        ))
+    }
+
     /// Get binary operator and its precedence
     fn get_binary_operator(&self) -> Option<(u8, BinaryOperator)> {
         let token = self.peek()?;
@@ -320,22 +1249,39 @@ impl Parser {
             Token::Less => Some((1, BinaryOperator::Less)),
             Token::GreaterOrEqual => Some((1, BinaryOperator::GreaterOrEqual)),
             Token::LessOrEqual => Some((1, BinaryOperator::LessOrEqual)),
+            Token::Match => Some((1, BinaryOperator::Match)),
+            Token::NotMatch => Some((1, BinaryOperator::NotMatch)),
+            Token::Like => Some((1, BinaryOperator::Like)),
+            Token::NotLike => Some((1, BinaryOperator::NotLike)),
+            Token::CLike => Some((1, BinaryOperator::CLike)),
+            Token::CNotLike => Some((1, BinaryOperator::CNotLike)),
+            Token::Join => Some((1, BinaryOperator::Join)),
+            Token::Split => Some((1, BinaryOperator::Split)),
 
             // Additive operators
             Token::Plus => Some((2, BinaryOperator::Add)),
             Token::Minus => Some((2, BinaryOperator::Subtract)),
 
-            // Multiplicative operators (highest precedence)
+            // Multiplicative operators
             Token::Multiply => Some((3, BinaryOperator::Multiply)),
             Token::Divide => Some((3, BinaryOperator::Divide)),
             Token::Modulo => Some((3, BinaryOperator::Modulo)),
 
+            // Format operator (highest precedence): "{0}" -f $a, $b
+            Token::Format => Some((4, BinaryOperator::Format)),
+
             _ => None,
         }
     }
 
     /// Parse a primary expression (literals, variables, calls, etc.)
     fn parse_primary(&mut self) -> Result<Expression, ParseError> {
+        let start = self.start_position();
+        let kind = self.parse_primary_kind()?;
+        Ok(Expression::new(kind, self.span_from(start)))
+    }
+
+    fn parse_primary_kind(&mut self) -> Result<ExpressionKind, ParseError> {
         let token = self.peek();
 
         if token.is_none() {
@@ -349,12 +1295,12 @@ impl Parser {
             Token::Number(n) => {
                 let num = *n;
                 self.advance();
-                Ok(Expression::Literal(Literal::Number(num)))
+                Ok(ExpressionKind::Literal(Literal::Number(num)))
             }
             Token::String(s) => {
                 let str_val = s.clone();
                 self.advance();
-                Ok(Expression::Literal(Literal::String(str_val)))
+                Ok(ExpressionKind::Literal(Literal::String(str_val)))
             }
             Token::InterpolatedString(parts) => {
                 let string_parts: Vec<StringPart> = parts
@@ -365,79 +1311,151 @@ impl Parser {
                     })
                     .collect();
                 self.advance();
-                Ok(Expression::Literal(Literal::InterpolatedString(
+                Ok(ExpressionKind::Literal(Literal::InterpolatedString(
                     string_parts,
                 )))
             }
-            Token::Boolean(b) => {
-                let bool_val = *b;
-                self.advance();
-                Ok(Expression::Literal(Literal::Boolean(bool_val)))
-            }
-
-            // Variable
+            // Variable ($true, $false and $null are automatic constants
+            // the runtime resolves)
             Token::Variable(name) => {
                 let var_name = name.clone();
                 self.advance();
-                Ok(Expression::Variable(var_name))
+                Ok(ExpressionKind::Variable(var_name))
             }
 
-            // Identifier (function/cmdlet call or bare identifier)
+            // Identifier: a command call, whose arguments are parsed in
+            // argument mode up to the end of the statement, a `|`, or the
+            // `)` or `}` around it. Operators after the name are arguments
+            // too: `Get-Thing -eq 1` passes -eq as a parameter.
             Token::Identifier(name) => {
                 let func_name = name.clone();
                 self.advance();
 
-                // Check if this looks like a function call (has arguments following)
-                // Don't parse arguments if we see comma, pipeline, statement terminators, etc.
-                // Special case: -Identifier could be a named parameter, not subtraction
-                let is_named_param_following = self.check(&Token::Minus)
-                    && self.current + 1 < self.tokens.len()
-                    && matches!(&self.tokens[self.current + 1].token, Token::Identifier(_));
-
-                let should_parse_args = is_named_param_following
-                    || (!self.check(&Token::Comma)
-                        && !self.check(&Token::Pipeline)
-                        && !self.is_statement_terminator()
-                        && !self.check(&Token::RightParen)
-                        && !self.check(&Token::RightBrace)
-                        && !self.is_binary_operator());
-
-                let arguments = if should_parse_args {
-                    self.parse_call_arguments()?
-                } else {
+                // A comma makes the call an item of an array: Get-A, Get-B
+                let arguments = if self.check(&Token::Comma) {
                     Vec::new()
+                } else {
+                    self.parse_call_arguments()?
                 };
 
-                Ok(Expression::Call {
+                Ok(ExpressionKind::Call {
                     name: func_name,
                     arguments,
                 })
             }
 
-            // Parenthesized expression
+            // Call operator: & 'C:\Program Files\tool.exe' arg, & $command,
+            // & { ... }. The command is read like an argument, so a bare
+            // word is its name.
+            Token::Ampersand => {
+                self.advance();
+                if self.is_at_end()
+                    || self.is_statement_terminator()
+                    || self.check(&Token::Pipeline)
+                {
+                    let token = self.advance();
+                    return Err(ParseError::InvalidExpression {
+                        message: "Missing the command after the call operator &".to_string(),
+                        position: token.position,
+                    });
+                }
+                let command = self.parse_argument()?;
+                let arguments = if self.check(&Token::Comma) {
+                    Vec::new()
+                } else {
+                    self.parse_call_arguments()?
+                };
+                Ok(ExpressionKind::Invoke {
+                    command: Box::new(command),
+                    arguments,
+                })
+            }
+
+            // Parenthesized expression or pipeline: (1 + 2), (Get-ChildItem | Sort-Object)
             Token::LeftParen => {
                 self.advance();
-                let expr = self.parse_expression()?;
+                let mut pipeline = self.parse_pipeline()?;
                 self.consume(&Token::RightParen, ")")?;
-                Ok(expr)
+                // The parentheses become part of the inner expression's span
+                if pipeline.stages.len() == 1 {
+                    Ok(pipeline.stages.remove(0).kind)
+                } else {
+                    Ok(ExpressionKind::Pipeline(pipeline))
+                }
             }
 
             // Script block
             Token::LeftBrace => {
                 let block = self.parse_block()?;
-                Ok(Expression::ScriptBlock(block))
+                Ok(ExpressionKind::ScriptBlock(block))
             }
 
-            // Unary minus
-            Token::Minus => {
+            // Unary minus and plus
+            Token::Minus | Token::Plus => {
+                let operator = if self.check(&Token::Minus) {
+                    UnaryOperator::Negate
+                } else {
+                    UnaryOperator::Plus
+                };
                 self.advance();
                 let operand = self.parse_primary()?;
-                Ok(Expression::UnaryOp {
-                    operator: UnaryOperator::Negate,
+                Ok(ExpressionKind::UnaryOp {
+                    operator,
+                    operand: Box::new(operand),
+                })
+            }
+
+            // Unary -join and -split bind like -not: -join $parts, -split $line
+            Token::Join | Token::Split => {
+                let operator = if self.check(&Token::Join) {
+                    UnaryOperator::Join
+                } else {
+                    UnaryOperator::Split
+                };
+                self.advance();
+                let operand = self.parse_expression_with_precedence(UNARY_PRECEDENCE)?;
+                Ok(ExpressionKind::UnaryOp {
+                    operator,
+                    operand: Box::new(operand),
+                })
+            }
+
+            // Logical not: -not $x or !$x
+            // The operand binds tighter than any binary operator but still
+            // includes member access, so -not $x.Enabled negates the property.
+            Token::Not => {
+                self.advance();
+                let operand = self.parse_expression_with_precedence(UNARY_PRECEDENCE)?;
+                Ok(ExpressionKind::UnaryOp {
+                    operator: UnaryOperator::Not,
                     operand: Box::new(operand),
                 })
             }
 
+            // Type literal, cast, or static member: [Foo], [int]$x, [Foo]::new(1), [Color]::Red
+            Token::LeftBracket => {
+                let position = self.tokens[self.current].position;
+                let type_name = self.parse_type_name()?;
+                if self.starts_cast_operand() {
+                    return self.parse_cast(type_name, position);
+                }
+                if !self.check(&Token::DoubleColon) {
+                    return Ok(ExpressionKind::TypeLiteral(type_name));
+                }
+                self.advance();
+
+                let member = self.parse_identifier("static member name")?;
+                if self.check(&Token::LeftParen) {
+                    Ok(ExpressionKind::StaticMethodCall {
+                        type_name,
+                        method: member,
+                        arguments: self.parse_method_arguments()?,
+                    })
+                } else {
+                    Ok(ExpressionKind::StaticMember { type_name, member })
+                }
+            }
+
             // Hashtable: @{key=value; ...} or Array: @(item1, item2, ...)
             Token::At => {
                 self.advance(); // consume @
@@ -446,15 +1464,19 @@ impl Parser {
                 if self.check(&Token::LeftBrace) {
                     // Hashtable: @{key=value; ...}
                     self.advance(); // consume {
-                    let pairs = self.parse_hashtable_pairs()?;
+                    self.trivia_frames.push(TriviaFrame::default());
+                    let entries = self.parse_hashtable_pairs()?;
                     self.consume(&Token::RightBrace, "}")?;
-                    Ok(Expression::Hashtable(pairs))
+                    Ok(ExpressionKind::Hashtable {
+                        entries,
+                        trivia: self.pop_trivia_frame(),
+                    })
                 } else if self.check(&Token::LeftParen) {
                     // Array: @(item1, item2, ...)
                     self.advance(); // consume (
                     let items = self.parse_array_items()?;
                     self.consume(&Token::RightParen, ")")?;
-                    Ok(Expression::Array(items))
+                    Ok(ExpressionKind::Array(items))
                 } else {
                     let tok = self.peek().unwrap();
                     Err(ParseError::UnexpectedToken {
@@ -491,131 +1513,295 @@ impl Parser {
             && !self.check(&Token::RightParen)
             && !self.check(&Token::RightBrace)
         {
-            // Check for named parameter (-Identifier pattern)
-            let is_named_param = self.check(&Token::Minus)
-                && self.current + 1 < self.tokens.len()
-                && matches!(&self.tokens[self.current + 1].token, Token::Identifier(_));
-
-            if is_named_param {
-                self.advance(); // consume minus
-                let name_token = self.advance(); // consume identifier
-                let name = match &name_token.token {
-                    Token::Identifier(n) => n.clone(),
-                    _ => {
-                        return Err(ParseError::UnexpectedToken {
-                            expected: "identifier after -".to_string(),
-                            found: name_token.token.clone(),
-                            position: name_token.position,
-                        })
-                    }
-                };
+            if let Some(Token::StopParsing(text)) = self.peek() {
+                let text = text.clone();
+                let span = self.advance().span();
+                arguments.push(Argument::StopParsing { text, span });
+                continue;
+            }
+
+            let start = self.start_position();
+            if let Some(name) = self.parameter_name() {
+                // -Name:value always takes the value, even for a switch: -Recurse:$false
+                let has_colon = self.check(&Token::Colon);
+                if has_colon {
+                    self.advance();
+                }
 
                 // Support switch-style parameters like: -Recurse
                 // If no value follows (end of statement / pipeline / comma / etc), treat as $true.
-                let value = if self.is_at_end()
-                    || self.is_statement_terminator()
-                    || self.check(&Token::Comma)
-                    || self.check(&Token::Pipeline)
-                    || self.check(&Token::RightParen)
-                    || self.check(&Token::RightBrace)
+                // A dash starts another parameter only right before a name,
+                // so -Depth -1 and -Offset -$x take negative values.
+                let value = if !has_colon
+                    && (self.is_at_end()
+                        || self.is_statement_terminator()
+                        || self.check(&Token::Comma)
+                        || self.check(&Token::Pipeline)
+                        || self.check(&Token::RightParen)
+                        || self.check(&Token::RightBrace)
+                        || matches!(self.peek(), Some(Token::StopParsing(_)))
+                    // Another parameter follows: -Recurse -Force, -Property -eq
+                    || self.at_parameter())
                 {
-                    Expression::Literal(Literal::Boolean(true))
-                } else if let Some(Token::Identifier(id)) = self.peek() {
-                    // In PowerShell, bare words in argument position are strings.
-                    // This includes named parameter values like: Sort-Object -Property CPU
-                    // If the identifier is followed by a token that clearly ends the value,
-                    // treat it as a string literal rather than a cmdlet/function call.
-
-                    let next_idx = self.current + 1;
-                    let is_bare_word_value = if next_idx < self.tokens.len() {
-                        matches!(
-                            &self.tokens[next_idx].token,
-                            Token::Comma
-                                | Token::Minus
-                                | Token::Pipeline
-                                | Token::RightParen
-                                | Token::RightBrace
-                                | Token::Newline
-                                | Token::Semicolon
-                                | Token::Eof
-                        )
-                    } else {
-                        true
-                    };
-
-                    if is_bare_word_value {
-                        let identifier = id.clone();
-                        self.advance();
-                        Expression::Literal(Literal::String(identifier))
-                    } else {
-                        self.parse_primary()?
-                    }
+                    // A switch's value spans its name
+                    Expression::new(
+                        ExpressionKind::Literal(Literal::Boolean(true)),
+                        self.span_from(start),
+                    )
                 } else {
-                    self.parse_primary()?
+                    self.parse_argument_list(Self::parse_argument)?
                 };
-                arguments.push(Argument::Named { name, value });
-
-                // Skip optional comma
-                if self.check(&Token::Comma) {
-                    self.advance();
-                }
+                arguments.push(Argument::Named {
+                    name,
+                    value,
+                    span: self.span_from(start),
+                });
                 continue;
             }
 
-            // Check if we hit a binary operator (but not named param pattern)
-            if self.is_binary_operator() {
-                break;
+            let arg = self.parse_argument_list(Self::parse_argument)?;
+            arguments.push(Argument::Positional(arg));
+        }
+
+        Ok(arguments)
+    }
+
+    /// The name of the parameter at the current token, consumed: `-Name`, or
+    /// an operator, which in argument mode names a parameter (`-eq`, `-f`)
+    fn parameter_name(&mut self) -> Option<String> {
+        if self.at_named_parameter() {
+            self.advance(); // consume minus
+            return match self.advance().token {
+                Token::Identifier(name) => Some(name),
+                _ => None,
+            };
+        }
+        let name = operator_parameter(self.peek()?)?;
+        self.advance();
+        Some(name.to_string())
+    }
+
+    /// Whether a parameter starts here, named or an operator
+    fn at_parameter(&self) -> bool {
+        self.at_named_parameter() || self.peek().and_then(operator_parameter).is_some()
+    }
+
+    /// Parse a command argument that may be a comma-separated list, which
+    /// is one array argument: `Select-Object Name, CPU`. A line may end
+    /// after a comma.
+    fn parse_argument_list(
+        &mut self,
+        parse_item: fn(&mut Self) -> Result<Expression, ParseError>,
+    ) -> Result<Expression, ParseError> {
+        let start = self.start_position();
+        let first = parse_item(self)?;
+        if !self.check(&Token::Comma) {
+            return Ok(first);
+        }
+
+        let mut items = vec![first];
+        while self.check(&Token::Comma) {
+            self.advance();
+            while self.check(&Token::Newline) {
+                self.advance();
             }
+            items.push(parse_item(self)?);
+        }
+        Ok(Expression::new(
+            ExpressionKind::Array(items),
+            self.span_from(start),
+        ))
+    }
 
-            // Special case: bare identifier as argument (should be treated as string)
-            // In PowerShell, bare words in argument position are strings
-            // e.g., Select-Object Name, CPU -> Name and CPU are strings
-            if let Some(Token::Identifier(name)) = self.peek() {
-                // Check what comes after the identifier
-                let next_idx = self.current + 1;
-                let is_bare_word = if next_idx < self.tokens.len() {
-                    matches!(
-                        &self.tokens[next_idx].token,
-                        Token::Comma
-                            | Token::Minus  // Could be a named parameter for the parent cmdlet
-                            | Token::Pipeline
-                            | Token::RightParen
-                            | Token::RightBrace
-                            | Token::Newline
-                            | Token::Semicolon
-                            | Token::Eof
-                    )
-                } else {
-                    true // End of tokens, it's a bare word
-                };
+    /// Parse a command argument or named parameter value in argument mode:
+    /// a bare word is a string, and operators are not applied, so
+    /// `Write-Output a + b` passes `a`, `+` and `b`. Other arguments are a
+    /// value with the member accesses and indexes written right after it:
+    /// `$x.Name`, `(Get-Date).Year`, `@(1)[0]`. Operators apply inside
+    /// parentheses: `-First ($n * 2)`.
+    fn parse_argument(&mut self) -> Result<Expression, ParseError> {
+        if let Some(word) = self
+            .wildcard_word()
+            .or_else(|| self.drive_qualified_word())
+            .or_else(|| self.bare_word())
+        {
+            return Ok(word);
+        }
 
-                if is_bare_word {
-                    // Treat as string literal
-                    let identifier = name.clone();
-                    self.advance();
-                    arguments.push(Argument::Positional(Expression::Literal(Literal::String(
-                        identifier,
-                    ))));
+        // An arithmetic operator on its own is a word too; one written
+        // right before a value is a sign (-1, +$x) or !$x
+        let symbol = match self.peek() {
+            Some(Token::Plus) => Some("+"),
+            Some(Token::Minus) => Some("-"),
+            Some(Token::Multiply) => Some("*"),
+            Some(Token::Divide) => Some("/"),
+            Some(Token::Modulo) => Some("%"),
+            _ => None,
+        };
+        if let Some(symbol) = symbol {
+            if !self.next_follows_adjacently() {
+                let token = self.advance();
+                return Ok(Expression::new(
+                    ExpressionKind::Literal(Literal::String(symbol.to_string())),
+                    token.span(),
+                ));
+            }
+        }
 
-                    // Skip optional comma
-                    if self.check(&Token::Comma) {
-                        self.advance();
-                    }
-                    continue;
+        let start = self.start_position();
+        let value = self.parse_primary()?;
+        self.parse_accessors(start, value, true)
+    }
+
+    /// A bare word in argument position, read as a string: a word, or a
+    /// path starting with `.` or `/`, or a word after `+`, and the words,
+    /// dots, slashes, dashes and numbers written right after it, such as
+    /// `Name`, `notes.txt`, `utf-8`, `./examples` or `+acc`
+    fn bare_word(&mut self) -> Option<Expression> {
+        let next = self.tokens.get(self.current + 1).map(|t| &t.token);
+        let starts_path = matches!(self.peek(), Some(Token::Dot | Token::Divide))
+            && self.next_follows_adjacently()
+            && matches!(
+                next,
+                Some(Token::Identifier(_) | Token::Dot | Token::Divide)
+            );
+        let starts_with_plus = self.check(&Token::Plus)
+            && self.next_follows_adjacently()
+            && matches!(next, Some(Token::Identifier(_)));
+        if !starts_path && !starts_with_plus && !matches!(self.peek(), Some(Token::Identifier(_))) {
+            return None;
+        }
+        let mut word = String::new();
+        let start = self.start_position();
+        loop {
+            match self.peek() {
+                Some(Token::Identifier(name)) => word.push_str(name),
+                Some(Token::Dot) => word.push('.'),
+                Some(Token::Divide) => word.push('/'),
+                Some(Token::Minus) => word.push('-'),
+                Some(Token::Plus) => word.push('+'),
+                Some(Token::Number(n)) if n.fract() == 0.0 && *n >= 0.0 => {
+                    word.push_str(&n.to_string())
                 }
+                _ => break,
+            }
+            self.advance();
+            if !self.follows_adjacently() {
+                break;
             }
+        }
+        Some(Expression::new(
+            ExpressionKind::Literal(Literal::String(word)),
+            self.span_from(start),
+        ))
+    }
 
-            // Otherwise, parse as positional argument normally
-            let arg = self.parse_primary()?;
-            arguments.push(Argument::Positional(arg));
+    /// Whether the current token is written right after the one before it,
+    /// with no space between them
+    fn follows_adjacently(&self) -> bool {
+        self.current > 0
+            && self
+                .tokens
+                .get(self.current)
+                .is_some_and(|token| token.position == self.tokens[self.current - 1].end)
+    }
 
-            // Skip optional comma
-            if self.check(&Token::Comma) {
-                self.advance();
+    /// Whether the token after the current one is written right after it
+    fn next_follows_adjacently(&self) -> bool {
+        match (
+            self.tokens.get(self.current),
+            self.tokens.get(self.current + 1),
+        ) {
+            (Some(token), Some(next)) => next.position == token.end && next.token != Token::Eof,
+            _ => false,
+        }
+    }
+
+    /// A drive-qualified bare word in argument position, such as `Variable:`
+    /// or `Function:Get-Size`, read as a string
+    fn drive_qualified_word(&mut self) -> Option<Expression> {
+        let token_at = |offset: usize| self.tokens.get(self.current + offset).map(|t| &t.token);
+        let drive = match token_at(0) {
+            Some(Token::Identifier(drive)) => drive.clone(),
+            Some(Token::Function) => "Function".to_string(),
+            _ => return None,
+        };
+        if token_at(1) != Some(&Token::Colon) {
+            return None;
+        }
+        let name = match token_at(2) {
+            Some(Token::Identifier(name)) => Some(name.clone()),
+            _ => None,
+        };
+        let end = if name.is_some() { 3 } else { 2 };
+        let ends_argument = matches!(
+            token_at(end),
+            None | Some(Token::Comma)
+                | Some(Token::Minus)
+                | Some(Token::Pipeline)
+                | Some(Token::RightParen)
+                | Some(Token::RightBrace)
+                | Some(Token::Newline)
+                | Some(Token::Semicolon)
+                | Some(Token::Eof)
+        );
+        if !ends_argument {
+            return None;
+        }
+
+        let word = format!("{}:{}", drive, name.unwrap_or_default());
+        let start = self.start_position();
+        for _ in 0..end {
+            self.advance();
+        }
+        Some(Expression::new(
+            ExpressionKind::Literal(Literal::String(word)),
+            self.span_from(start),
+        ))
+    }
+
+    /// A bare word with wildcards in argument position, such as `*`, `*Time`
+    /// or `Name*`, read as a string: words and stars with no space between
+    /// them, at least one of them a star
+    fn wildcard_word(&mut self) -> Option<Expression> {
+        let mut word = String::new();
+        let mut has_star = false;
+        let mut end = 0;
+        while let Some(token) = self.tokens.get(self.current + end) {
+            let adjacent = end == 0 || self.tokens[self.current + end - 1].end == token.position;
+            match &token.token {
+                Token::Multiply if adjacent => {
+                    word.push('*');
+                    has_star = true;
+                }
+                Token::Identifier(name) if adjacent => word.push_str(name),
+                _ => break,
             }
+            end += 1;
+        }
+        let ends_argument = matches!(
+            self.tokens.get(self.current + end).map(|t| &t.token),
+            None | Some(Token::Comma)
+                | Some(Token::Minus)
+                | Some(Token::Pipeline)
+                | Some(Token::RightParen)
+                | Some(Token::RightBrace)
+                | Some(Token::Newline)
+                | Some(Token::Semicolon)
+                | Some(Token::Eof)
+        );
+        if !has_star || !ends_argument {
+            return None;
         }
 
-        Ok(arguments)
+        let start = self.start_position();
+        for _ in 0..end {
+            self.advance();
+        }
+        Some(Expression::new(
+            ExpressionKind::Literal(Literal::String(word)),
+            self.span_from(start),
+        ))
     }
 
     /// Parse hashtable key-value pairs: key1=value1; key2=value2
@@ -646,6 +1832,11 @@ impl Parser {
                 }
             };
 
+            // Comments from here to the end of the entry's line trail it
+            if let Some(frame) = self.trivia_frames.last_mut() {
+                frame.in_statement = true;
+            }
+
             // Expect =
             self.consume(&Token::Assignment, "=")?;
 
@@ -653,6 +1844,7 @@ impl Parser {
             let value = self.parse_expression()?;
 
             pairs.push((key, value));
+            self.finish_statement();
 
             // Skip optional semicolons and newlines
             while self.check(&Token::Semicolon) || self.check(&Token::Newline) {
@@ -724,6 +1916,23 @@ impl Parser {
         false
     }
 
+    /// Check ahead for multiple assignment pattern: $a, $b = ...
+    fn check_ahead_for_multiple_assignment(&self) -> bool {
+        let mut i = self.current;
+        let mut count = 0;
+
+        while let Some(Token::Variable(_)) = self.tokens.get(i).map(|t| &t.token) {
+            count += 1;
+            match self.tokens.get(i + 1).map(|t| &t.token) {
+                Some(Token::Comma) => i += 2,
+                Some(Token::Assignment) => return count > 1,
+                _ => return false,
+            }
+        }
+
+        false
+    }
+
     /// Check if the upcoming tokens contain a pipeline operator
     fn contains_pipeline(&self) -> bool {
         let mut i = self.current;
@@ -732,7 +1941,13 @@ impl Parser {
         while i < self.tokens.len() {
             match &self.tokens[i].token {
                 Token::LeftParen | Token::LeftBrace | Token::LeftBracket => depth += 1,
-                Token::RightParen | Token::RightBrace | Token::RightBracket => depth -= 1,
+                Token::RightParen | Token::RightBrace | Token::RightBracket => {
+                    // Closing a group we did not open ends the current statement
+                    if depth == 0 {
+                        return false;
+                    }
+                    depth -= 1;
+                }
                 Token::Pipeline if depth == 0 => return true,
                 Token::Newline | Token::Semicolon | Token::Eof if depth == 0 => return false,
                 _ => {}
@@ -743,6 +1958,26 @@ impl Parser {
         false
     }
 
+    /// Where the next token starts; the start of a node about to be parsed
+    fn start_position(&self) -> Position {
+        self.tokens
+            .get(self.current)
+            .or(self.tokens.last())
+            .map(|t| t.position)
+            .unwrap_or(Position::new(1, 1))
+    }
+
+    /// The span from `start` through the last consumed token, leaving out
+    /// statement terminators consumed after it
+    fn span_from(&self, start: Position) -> Span {
+        let end = self.tokens[..self.current.min(self.tokens.len())]
+            .iter()
+            .rev()
+            .find(|t| !matches!(t.token, Token::Newline | Token::Semicolon))
+            .map_or(start, |t| t.end);
+        Span::new(start, end.max(start))
+    }
+
     /// Peek at current token without consuming
     fn peek(&self) -> Option<&Token> {
         if self.current < self.tokens.len() {
@@ -755,6 +1990,7 @@ impl Parser {
     /// Advance to next token and return current
     fn advance(&mut self) -> LocatedToken {
         if self.current < self.tokens.len() {
+            self.collect_trivia(self.current);
             let token = self.tokens[self.current].clone();
             self.current += 1;
             token
@@ -778,6 +2014,17 @@ impl Parser {
             )
     }
 
+    /// Whether a named parameter starts here: -Identifier, with no space
+    /// after the dash
+    fn at_named_parameter(&self) -> bool {
+        self.check(&Token::Minus)
+            && self.next_follows_adjacently()
+            && matches!(
+                self.tokens.get(self.current + 1).map(|t| &t.token),
+                Some(Token::Identifier(_))
+            )
+    }
+
     /// Check if current token is a statement terminator
     fn is_statement_terminator(&self) -> bool {
         matches!(
@@ -786,11 +2033,6 @@ impl Parser {
         )
     }
 
-    /// Check if current token is a binary operator
-    fn is_binary_operator(&self) -> bool {
-        self.get_binary_operator().is_some()
-    }
-
     /// Consume expected token or return error
     fn consume(&mut self, expected: &Token, description: &str) -> Result<(), ParseError> {
         if self.check(expected) {
@@ -806,10 +2048,35 @@ impl Parser {
         }
     }
 
-    /// Consume optional statement terminator (newline, semicolon)
+    /// Consume optional statement terminator (newline, semicolon), ending the statement
     fn consume_statement_terminator(&mut self) {
+        self.finish_statement();
         while self.check(&Token::Newline) || self.check(&Token::Semicolon) {
             self.advance();
         }
     }
 }
+
+/// The parameter an operator names in argument mode: `Get-Thing -eq 1`
+/// passes -eq
+fn operator_parameter(token: &Token) -> Option<&'static str> {
+    let name = match token {
+        Token::Equal => "eq",
+        Token::NotEqual => "ne",
+        Token::Greater => "gt",
+        Token::Less => "lt",
+        Token::GreaterOrEqual => "ge",
+        Token::LessOrEqual => "le",
+        Token::Match => "match",
+        Token::NotMatch => "notmatch",
+        Token::Like => "like",
+        Token::NotLike => "notlike",
+        Token::CLike => "clike",
+        Token::CNotLike => "cnotlike",
+        Token::Join => "join",
+        Token::Split => "split",
+        Token::Format => "f",
+        _ => return None,
+    };
+    Some(name)
+}
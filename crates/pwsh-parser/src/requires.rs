@@ -0,0 +1,233 @@
+/// `#Requires` statements: what a script needs before it can run
+use crate::ast::{Trivia, TriviaKind};
+use crate::error::ParseError;
+use pwsh_lexer::Position;
+use serde::{Deserialize, Serialize};
+
+/// What a script's `#Requires` statements ask for, all of them together
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Requirements {
+    /// `-Version`: the lowest PowerShell version the script runs on, as its
+    /// dot-separated numbers (the highest asked for, when there are several)
+    pub version: Option<Vec<u32>>,
+    /// `-PSEdition`: `Core` or `Desktop`
+    pub edition: Option<String>,
+    /// `-Modules`: the modules the script needs, by name
+    pub modules: Vec<String>,
+}
+
+impl Requirements {
+    /// Whether the script asks for nothing
+    pub fn is_empty(&self) -> bool {
+        self.version.is_none() && self.edition.is_none() && self.modules.is_empty()
+    }
+
+    /// Read the `#Requires` statements among a program's comments. Only a
+    /// comment on a line of its own counts, as in PowerShell.
+    pub(crate) fn from_trivia(trivia: &[Trivia]) -> Result<Requirements, ParseError> {
+        let mut requirements = Requirements::default();
+        for trivia in trivia {
+            if let TriviaKind::Comment { text, position } = &trivia.kind {
+                if let Some(rest) = requires_text(text) {
+                    requirements.add(rest, *position)?;
+                }
+            }
+        }
+        Ok(requirements)
+    }
+
+    /// Add what one `#Requires` statement asks for
+    fn add(&mut self, text: &str, position: Position) -> Result<(), ParseError> {
+        let invalid = |message: String| ParseError::InvalidStatement {
+            message: format!("Cannot process the #requires statement: {}", message),
+            position,
+        };
+
+        let parameters = split_parameters(text);
+        if parameters.is_empty() {
+            return Err(invalid(
+                "expected -Version, -PSEdition or -Modules".to_string(),
+            ));
+        }
+        for (name, value) in parameters {
+            let value = value.trim();
+            let lower = name.to_ascii_lowercase();
+            match lower.as_str() {
+                "version" => {
+                    let version = parse_version(value)
+                        .ok_or_else(|| invalid(format!("'{}' is not a valid version", value)))?;
+                    if self.version.as_ref().is_none_or(|v| *v < version) {
+                        self.version = Some(version);
+                    }
+                }
+                "psedition" => {
+                    let edition = unquote(value);
+                    if edition.eq_ignore_ascii_case("Core") {
+                        self.edition = Some("Core".to_string());
+                    } else if edition.eq_ignore_ascii_case("Desktop") {
+                        self.edition = Some("Desktop".to_string());
+                    } else {
+                        return Err(invalid(format!(
+                            "'{}' is not an edition of PowerShell; expected Core or Desktop",
+                            edition
+                        )));
+                    }
+                }
+                "modules" => {
+                    for module in split_list(value) {
+                        let name = module_name(module).ok_or_else(|| {
+                            invalid(format!("'{}' is not a module name", module.trim()))
+                        })?;
+                        self.modules.push(name);
+                    }
+                }
+                "runasadministrator" | "pssnapin" | "shellid" | "assembly" => {
+                    return Err(invalid(format!("-{} is not supported", name)));
+                }
+                _ => return Err(invalid(format!("unknown parameter -{}", name))),
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The text after `#Requires`, or None for any other comment
+fn requires_text(comment: &str) -> Option<&str> {
+    let keyword = comment.get(..9)?;
+    let rest = &comment[9..];
+    (keyword.eq_ignore_ascii_case("#requires")
+        && (rest.is_empty() || rest.starts_with(char::is_whitespace)))
+    .then_some(rest)
+}
+
+/// Split `-Version 5.1 -Modules A, B` into its parameters and their values.
+/// A dash only starts a parameter after whitespace, so `My-Module` is a value.
+fn split_parameters(text: &str) -> Vec<(&str, &str)> {
+    let mut starts = Vec::new();
+    let mut previous = ' ';
+    for (index, c) in text.char_indices() {
+        if c == '-' && previous.is_whitespace() {
+            starts.push(index);
+        }
+        previous = c;
+    }
+    if text.trim().is_empty() || !text.trim_start().starts_with('-') {
+        return Vec::new();
+    }
+
+    let mut parameters = Vec::new();
+    for (i, start) in starts.iter().enumerate() {
+        let end = starts.get(i + 1).copied().unwrap_or(text.len());
+        let parameter = &text[start + 1..end];
+        let name_end = parameter
+            .find(char::is_whitespace)
+            .unwrap_or(parameter.len());
+        parameters.push((&parameter[..name_end], &parameter[name_end..]));
+    }
+    parameters
+}
+
+/// `5`, `5.1` or `7.2.0`
+fn parse_version(text: &str) -> Option<Vec<u32>> {
+    let parts: Vec<u32> = unquote(text)
+        .split('.')
+        .map(|part| part.parse().ok())
+        .collect::<Option<_>>()?;
+    (1..=4).contains(&parts.len()).then_some(parts)
+}
+
+/// Split a comma list, leaving the commas inside `@{ ... }` alone
+fn split_list(text: &str) -> Vec<&str> {
+    let mut items = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (index, c) in text.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            ',' if depth == 0 => {
+                items.push(&text[start..index]);
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    items.push(&text[start..]);
+    items
+}
+
+/// A module's name: a bare or quoted name, or the `ModuleName` entry of a
+/// `@{ModuleName = ...; ModuleVersion = ...}` specification
+fn module_name(text: &str) -> Option<String> {
+    let text = text.trim();
+    let name = match text.strip_prefix("@{") {
+        Some(entries) => entries
+            .strip_suffix('}')?
+            .split(';')
+            .filter_map(|entry| entry.split_once('='))
+            .find(|(key, _)| key.trim().eq_ignore_ascii_case("ModuleName"))
+            .map(|(_, value)| unquote(value))?,
+        None => unquote(text),
+    };
+    (!name.is_empty() && !name.contains(char::is_whitespace)).then(|| name.to_string())
+}
+
+fn unquote(text: &str) -> &str {
+    let text = text.trim();
+    for quote in ['\'', '"'] {
+        if let Some(inner) = text
+            .strip_prefix(quote)
+            .and_then(|rest| rest.strip_suffix(quote))
+        {
+            return inner;
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn requirements(lines: &[&str]) -> Result<Requirements, ParseError> {
+        let trivia: Vec<Trivia> = lines
+            .iter()
+            .map(|line| Trivia {
+                statement: 0,
+                kind: TriviaKind::Comment {
+                    text: line.to_string(),
+                    position: Position::new(1, 1),
+                },
+            })
+            .collect();
+        Requirements::from_trivia(&trivia)
+    }
+
+    #[test]
+    fn test_read_requires_statements() {
+        let found = requirements(&[
+            "#Requires -Version 5.1",
+            "#requires -Modules Azure, 'My-Tools', @{ModuleName = 'Az.Accounts'; ModuleVersion = '2.0'}",
+            "#REQUIRES -PSEdition core -Version 7",
+            "# Requires nothing, just a comment",
+        ])
+        .unwrap();
+        assert_eq!(found.version, Some(vec![7]));
+        assert_eq!(found.edition.as_deref(), Some("Core"));
+        assert_eq!(found.modules, vec!["Azure", "My-Tools", "Az.Accounts"]);
+    }
+
+    #[test]
+    fn test_invalid_requires_statements() {
+        for line in [
+            "#Requires",
+            "#Requires -Version five",
+            "#Requires -PSEdition Server",
+            "#Requires -Bogus",
+            "#Requires -RunAsAdministrator",
+            "#Requires Version 5",
+        ] {
+            assert!(requirements(&[line]).is_err(), "{}", line);
+        }
+    }
+}
@@ -9,13 +9,21 @@ fn parse_str(input: &str) -> Result<Program, ParseError> {
     parser.parse()
 }
 
+// The expression of an expression statement
+fn expression_kind(statement: &Statement) -> &ExpressionKind {
+    match &statement.kind {
+        StatementKind::Expression(expr) => &expr.kind,
+        other => panic!("Expected expression statement, got {:?}", other),
+    }
+}
+
 #[test]
 fn test_parse_number_literal() {
     let program = parse_str("42").unwrap();
     assert_eq!(program.statements.len(), 1);
 
-    match &program.statements[0] {
-        Statement::Expression(Expression::Literal(Literal::Number(n))) => {
+    match expression_kind(&program.statements[0]) {
+        ExpressionKind::Literal(Literal::Number(n)) => {
             assert_eq!(*n, 42.0);
         }
         _ => panic!("Expected number literal"),
@@ -27,8 +35,8 @@ fn test_parse_string_literal() {
     let program = parse_str("\"hello\"").unwrap();
     assert_eq!(program.statements.len(), 1);
 
-    match &program.statements[0] {
-        Statement::Expression(Expression::Literal(Literal::String(s))) => {
+    match expression_kind(&program.statements[0]) {
+        ExpressionKind::Literal(Literal::String(s)) => {
             assert_eq!(s, "hello");
         }
         _ => panic!("Expected string literal"),
@@ -36,16 +44,19 @@ fn test_parse_string_literal() {
 }
 
 #[test]
-fn test_parse_boolean_literal() {
-    let program = parse_str("true").unwrap();
-    assert_eq!(program.statements.len(), 1);
-
-    match &program.statements[0] {
-        Statement::Expression(Expression::Literal(Literal::Boolean(b))) => {
-            assert!(*b);
-        }
-        _ => panic!("Expected boolean literal"),
-    }
+fn test_parse_boolean_constants() {
+    // $true is an automatic variable; a bare `true` is a command name
+    let program = parse_str("$true\ntrue").unwrap();
+    assert_eq!(program.statements.len(), 2);
+
+    assert_eq!(
+        *expression_kind(&program.statements[0]),
+        ExpressionKind::Variable("true".to_string())
+    );
+    assert!(matches!(
+        expression_kind(&program.statements[1]),
+        ExpressionKind::Call { name, .. } if name == "true"
+    ));
 }
 
 #[test]
@@ -53,8 +64,8 @@ fn test_parse_variable() {
     let program = parse_str("$x").unwrap();
     assert_eq!(program.statements.len(), 1);
 
-    match &program.statements[0] {
-        Statement::Expression(Expression::Variable(name)) => {
+    match expression_kind(&program.statements[0]) {
+        ExpressionKind::Variable(name) => {
             assert_eq!(name, "x");
         }
         _ => panic!("Expected variable"),
@@ -66,11 +77,11 @@ fn test_parse_assignment() {
     let program = parse_str("$x = 5").unwrap();
     assert_eq!(program.statements.len(), 1);
 
-    match &program.statements[0] {
-        Statement::Assignment { variable, value } => {
+    match &program.statements[0].kind {
+        StatementKind::Assignment { variable, value } => {
             assert_eq!(variable, "x");
-            match value {
-                Expression::Literal(Literal::Number(n)) => assert_eq!(*n, 5.0),
+            match &value.kind {
+                ExpressionKind::Literal(Literal::Number(n)) => assert_eq!(*n, 5.0),
                 _ => panic!("Expected number literal"),
             }
         }
@@ -83,19 +94,19 @@ fn test_parse_binary_addition() {
     let program = parse_str("5 + 3").unwrap();
     assert_eq!(program.statements.len(), 1);
 
-    match &program.statements[0] {
-        Statement::Expression(Expression::BinaryOp {
+    match expression_kind(&program.statements[0]) {
+        ExpressionKind::BinaryOp {
             left,
             operator,
             right,
-        }) => {
+        } => {
             assert_eq!(*operator, BinaryOperator::Add);
-            match **left {
-                Expression::Literal(Literal::Number(n)) => assert_eq!(n, 5.0),
+            match left.kind {
+                ExpressionKind::Literal(Literal::Number(n)) => assert_eq!(n, 5.0),
                 _ => panic!("Expected number literal"),
             }
-            match **right {
-                Expression::Literal(Literal::Number(n)) => assert_eq!(n, 3.0),
+            match right.kind {
+                ExpressionKind::Literal(Literal::Number(n)) => assert_eq!(n, 3.0),
                 _ => panic!("Expected number literal"),
             }
         }
@@ -107,8 +118,8 @@ fn test_parse_binary_addition() {
 fn test_parse_binary_multiplication() {
     let program = parse_str("10 * 2").unwrap();
 
-    match &program.statements[0] {
-        Statement::Expression(Expression::BinaryOp { operator, .. }) => {
+    match expression_kind(&program.statements[0]) {
+        ExpressionKind::BinaryOp { operator, .. } => {
             assert_eq!(*operator, BinaryOperator::Multiply);
         }
         _ => panic!("Expected binary operation"),
@@ -119,8 +130,8 @@ fn test_parse_binary_multiplication() {
 fn test_parse_comparison_equal() {
     let program = parse_str("$x -eq 5").unwrap();
 
-    match &program.statements[0] {
-        Statement::Expression(Expression::BinaryOp { operator, .. }) => {
+    match expression_kind(&program.statements[0]) {
+        ExpressionKind::BinaryOp { operator, .. } => {
             assert_eq!(*operator, BinaryOperator::Equal);
         }
         _ => panic!("Expected binary operation"),
@@ -131,8 +142,8 @@ fn test_parse_comparison_equal() {
 fn test_parse_comparison_greater() {
     let program = parse_str("$x -gt 5").unwrap();
 
-    match &program.statements[0] {
-        Statement::Expression(Expression::BinaryOp { operator, .. }) => {
+    match expression_kind(&program.statements[0]) {
+        ExpressionKind::BinaryOp { operator, .. } => {
             assert_eq!(*operator, BinaryOperator::Greater);
         }
         _ => panic!("Expected binary operation"),
@@ -144,23 +155,23 @@ fn test_parse_operator_precedence() {
     // 10 + 20 * 2 should parse as 10 + (20 * 2)
     let program = parse_str("10 + 20 * 2").unwrap();
 
-    match &program.statements[0] {
-        Statement::Expression(Expression::BinaryOp {
+    match expression_kind(&program.statements[0]) {
+        ExpressionKind::BinaryOp {
             left,
             operator,
             right,
-        }) => {
+        } => {
             assert_eq!(*operator, BinaryOperator::Add);
 
             // Left should be 10
-            match **left {
-                Expression::Literal(Literal::Number(n)) => assert_eq!(n, 10.0),
+            match left.kind {
+                ExpressionKind::Literal(Literal::Number(n)) => assert_eq!(n, 10.0),
                 _ => panic!("Expected number literal"),
             }
 
             // Right should be 20 * 2
-            match &**right {
-                Expression::BinaryOp { operator: op, .. } => {
+            match &right.kind {
+                ExpressionKind::BinaryOp { operator: op, .. } => {
                     assert_eq!(*op, BinaryOperator::Multiply);
                 }
                 _ => panic!("Expected multiplication"),
@@ -174,25 +185,25 @@ fn test_parse_operator_precedence() {
 fn test_parse_parenthesized_expression() {
     let program = parse_str("(10 + 20) * 2").unwrap();
 
-    match &program.statements[0] {
-        Statement::Expression(Expression::BinaryOp {
+    match expression_kind(&program.statements[0]) {
+        ExpressionKind::BinaryOp {
             left,
             operator,
             right,
-        }) => {
+        } => {
             assert_eq!(*operator, BinaryOperator::Multiply);
 
             // Left should be (10 + 20)
-            match &**left {
-                Expression::BinaryOp { operator: op, .. } => {
+            match &left.kind {
+                ExpressionKind::BinaryOp { operator: op, .. } => {
                     assert_eq!(*op, BinaryOperator::Add);
                 }
                 _ => panic!("Expected addition"),
             }
 
             // Right should be 2
-            match **right {
-                Expression::Literal(Literal::Number(n)) => assert_eq!(n, 2.0),
+            match right.kind {
+                ExpressionKind::Literal(Literal::Number(n)) => assert_eq!(n, 2.0),
                 _ => panic!("Expected number literal"),
             }
         }
@@ -204,11 +215,11 @@ fn test_parse_parenthesized_expression() {
 fn test_parse_unary_minus() {
     let program = parse_str("-5").unwrap();
 
-    match &program.statements[0] {
-        Statement::Expression(Expression::UnaryOp { operator, operand }) => {
+    match expression_kind(&program.statements[0]) {
+        ExpressionKind::UnaryOp { operator, operand } => {
             assert_eq!(*operator, UnaryOperator::Negate);
-            match **operand {
-                Expression::Literal(Literal::Number(n)) => assert_eq!(n, 5.0),
+            match operand.kind {
+                ExpressionKind::Literal(Literal::Number(n)) => assert_eq!(n, 5.0),
                 _ => panic!("Expected number literal"),
             }
         }
@@ -216,12 +227,181 @@ fn test_parse_unary_minus() {
     }
 }
 
+#[test]
+fn test_parse_not_operator() {
+    for input in ["-not $found", "!$found"] {
+        let program = parse_str(input).unwrap();
+
+        match expression_kind(&program.statements[0]) {
+            ExpressionKind::UnaryOp { operator, operand } => {
+                assert_eq!(*operator, UnaryOperator::Not);
+                assert_eq!(operand.kind, ExpressionKind::Variable("found".to_string()));
+            }
+            _ => panic!("Expected unary operation for {}", input),
+        }
+    }
+}
+
+#[test]
+fn test_parse_null_and_match() {
+    let program = parse_str("$s -match 'x' -eq $NULL").unwrap();
+
+    assert_eq!(
+        *expression_kind(&program.statements[0]),
+        ExpressionKind::BinaryOp {
+            left: Box::new(
+                ExpressionKind::BinaryOp {
+                    left: Box::new(ExpressionKind::Variable("s".to_string()).into()),
+                    operator: BinaryOperator::Match,
+                    right: Box::new(
+                        ExpressionKind::Literal(Literal::String("x".to_string())).into()
+                    ),
+                }
+                .into()
+            ),
+            operator: BinaryOperator::Equal,
+            right: Box::new(ExpressionKind::Variable("NULL".to_string()).into()),
+        }
+    );
+}
+
+#[test]
+fn test_parse_format_operator_with_argument_list() {
+    let program = parse_str("'{0} is {1:N2}' -f $name, $value").unwrap();
+
+    match expression_kind(&program.statements[0]) {
+        ExpressionKind::BinaryOp {
+            left,
+            operator,
+            right,
+        } => {
+            assert_eq!(*operator, BinaryOperator::Format);
+            assert_eq!(
+                left.kind,
+                ExpressionKind::Literal(Literal::String("{0} is {1:N2}".to_string()))
+            );
+            assert_eq!(
+                right.kind,
+                ExpressionKind::Array(vec![
+                    ExpressionKind::Variable("name".to_string()).into(),
+                    ExpressionKind::Variable("value".to_string()).into(),
+                ])
+            );
+        }
+        _ => panic!("Expected format operation"),
+    }
+}
+
+#[test]
+fn test_parse_format_binds_tighter_than_arithmetic() {
+    // '{0}' -f 1 + 2 parses as ('{0}' -f 1) + 2
+    let program = parse_str("'{0}' -f 1 + 2").unwrap();
+
+    match expression_kind(&program.statements[0]) {
+        ExpressionKind::BinaryOp { left, operator, .. } => {
+            assert_eq!(*operator, BinaryOperator::Add);
+            assert!(matches!(
+                left.kind,
+                ExpressionKind::BinaryOp {
+                    operator: BinaryOperator::Format,
+                    ..
+                }
+            ));
+        }
+        _ => panic!("Expected binary operation"),
+    }
+}
+
+#[test]
+fn test_parse_not_binds_tighter_than_comparison() {
+    // -not $a -eq $b parses as (-not $a) -eq $b
+    let program = parse_str("-not $a -eq $b").unwrap();
+
+    match expression_kind(&program.statements[0]) {
+        ExpressionKind::BinaryOp { left, operator, .. } => {
+            assert_eq!(*operator, BinaryOperator::Equal);
+            assert!(matches!(
+                left.kind,
+                ExpressionKind::UnaryOp {
+                    operator: UnaryOperator::Not,
+                    ..
+                }
+            ));
+        }
+        _ => panic!("Expected binary operation"),
+    }
+}
+
+#[test]
+fn test_parse_join_split_and_unary_plus() {
+    // Unary -split binds tighter than binary -join: (-split $s) -join '+'
+    let program = parse_str("-split $s -join '+'").unwrap();
+    match expression_kind(&program.statements[0]) {
+        ExpressionKind::BinaryOp { left, operator, .. } => {
+            assert_eq!(*operator, BinaryOperator::Join);
+            assert!(matches!(
+                left.kind,
+                ExpressionKind::UnaryOp {
+                    operator: UnaryOperator::Split,
+                    ..
+                }
+            ));
+        }
+        _ => panic!("Expected binary operation"),
+    }
+
+    let program = parse_str("-join $parts; $line -split ','; +$n").unwrap();
+    assert!(matches!(
+        expression_kind(&program.statements[0]),
+        ExpressionKind::UnaryOp {
+            operator: UnaryOperator::Join,
+            ..
+        }
+    ));
+    assert!(matches!(
+        expression_kind(&program.statements[1]),
+        ExpressionKind::BinaryOp {
+            operator: BinaryOperator::Split,
+            ..
+        }
+    ));
+    assert!(matches!(
+        expression_kind(&program.statements[2]),
+        ExpressionKind::UnaryOp {
+            operator: UnaryOperator::Plus,
+            ..
+        }
+    ));
+}
+
+#[test]
+fn test_parse_not_with_parenthesized_command() {
+    let program = parse_str("if (!(Test-Path $p)) { 1 }").unwrap();
+
+    match &program.statements[0].kind {
+        StatementKind::If { condition, .. } => match &condition.kind {
+            ExpressionKind::UnaryOp { operator, operand } => {
+                assert_eq!(*operator, UnaryOperator::Not);
+                match &operand.kind {
+                    ExpressionKind::Call { name, arguments } => {
+                        assert_eq!(name, "Test-Path");
+                        assert_eq!(arguments.len(), 1);
+                    }
+                    _ => panic!("Expected Test-Path call"),
+                }
+            }
+            _ => panic!("Expected unary operation"),
+        },
+        _ => panic!("Expected if statement"),
+    }
+}
+
 #[test]
 fn test_parse_function_call_no_args() {
     let program = parse_str("Get-Process").unwrap();
 
-    match &program.statements[0] {
-        Statement::Expression(Expression::Call { name, arguments }) => {
+    match expression_kind(&program.statements[0]) {
+        ExpressionKind::Call { name, arguments } => {
             assert_eq!(name, "Get-Process");
             assert_eq!(arguments.len(), 0);
         }
@@ -233,13 +413,16 @@ fn test_parse_function_call_no_args() {
 fn test_parse_function_call_with_args() {
     let program = parse_str("Write-Output \"hello\"").unwrap();
 
-    match &program.statements[0] {
-        Statement::Expression(Expression::Call { name, arguments }) => {
+    match expression_kind(&program.statements[0]) {
+        ExpressionKind::Call { name, arguments } => {
             assert_eq!(name, "Write-Output");
             assert_eq!(arguments.len(), 1);
 
             match &arguments[0] {
-                Argument::Positional(Expression::Literal(Literal::String(s))) => {
+                Argument::Positional(Expression {
+                    kind: ExpressionKind::Literal(Literal::String(s)),
+                    ..
+                }) => {
                     assert_eq!(s, "hello");
                 }
                 _ => panic!("Expected positional string argument"),
@@ -253,8 +436,8 @@ fn test_parse_function_call_with_args() {
 fn test_parse_function_call_multiple_args() {
     let program = parse_str("Add 5 10").unwrap();
 
-    match &program.statements[0] {
-        Statement::Expression(Expression::Call { name, arguments }) => {
+    match expression_kind(&program.statements[0]) {
+        ExpressionKind::Call { name, arguments } => {
             assert_eq!(name, "Add");
             assert_eq!(arguments.len(), 2);
         }
@@ -266,10 +449,10 @@ fn test_parse_function_call_multiple_args() {
 fn test_parse_member_access() {
     let program = parse_str("$obj.Property").unwrap();
 
-    match &program.statements[0] {
-        Statement::Expression(Expression::MemberAccess { object, member }) => {
-            match **object {
-                Expression::Variable(ref name) => assert_eq!(name, "obj"),
+    match expression_kind(&program.statements[0]) {
+        ExpressionKind::MemberAccess { object, member } => {
+            match object.kind {
+                ExpressionKind::Variable(ref name) => assert_eq!(name, "obj"),
                 _ => panic!("Expected variable"),
             }
             assert_eq!(member, "Property");
@@ -278,12 +461,56 @@ fn test_parse_member_access() {
     }
 }
 
+#[test]
+fn test_parse_index_access() {
+    let program = parse_str("$arr[0]").unwrap();
+
+    match expression_kind(&program.statements[0]) {
+        ExpressionKind::Index { object, index } => {
+            assert_eq!(object.kind, ExpressionKind::Variable("arr".to_string()));
+            assert_eq!(index.kind, ExpressionKind::Literal(Literal::Number(0.0)));
+        }
+        _ => panic!("Expected index expression"),
+    }
+}
+
+#[test]
+fn test_parse_parenthesized_pipeline_member_access() {
+    let program = parse_str("(Get-Process | Where-Object { $_.CPU -gt 10 }).Count").unwrap();
+    assert_eq!(program.statements.len(), 1);
+
+    match expression_kind(&program.statements[0]) {
+        ExpressionKind::MemberAccess { object, member } => {
+            assert_eq!(member, "Count");
+            match &object.kind {
+                ExpressionKind::Pipeline(pipeline) => assert_eq!(pipeline.stages.len(), 2),
+                _ => panic!("Expected pipeline inside parentheses"),
+            }
+        }
+        _ => panic!("Expected member access"),
+    }
+}
+
+#[test]
+fn test_parse_parenthesized_command_index() {
+    let program = parse_str("(Get-Content 'f.txt')[0]").unwrap();
+
+    match expression_kind(&program.statements[0]) {
+        ExpressionKind::Index { object, .. } => {
+            assert!(
+                matches!(&object.kind, ExpressionKind::Call { name, .. } if name == "Get-Content")
+            );
+        }
+        _ => panic!("Expected index expression"),
+    }
+}
+
 #[test]
 fn test_parse_script_block() {
     let program = parse_str("{ $x = 5 }").unwrap();
 
-    match &program.statements[0] {
-        Statement::Expression(Expression::ScriptBlock(block)) => {
+    match expression_kind(&program.statements[0]) {
+        ExpressionKind::ScriptBlock(block) => {
             assert_eq!(block.statements.len(), 1);
         }
         _ => panic!("Expected script block"),
@@ -294,15 +521,15 @@ fn test_parse_script_block() {
 fn test_parse_if_statement() {
     let program = parse_str("if ($x -eq 5) { Write-Output \"Five\" }").unwrap();
 
-    match &program.statements[0] {
-        Statement::If {
+    match &program.statements[0].kind {
+        StatementKind::If {
             condition,
             then_branch,
             else_branch,
         } => {
             // Check condition is a comparison
-            match condition {
-                Expression::BinaryOp { operator, .. } => {
+            match &condition.kind {
+                ExpressionKind::BinaryOp { operator, .. } => {
                     assert_eq!(*operator, BinaryOperator::Equal);
                 }
                 _ => panic!("Expected binary operation"),
@@ -322,8 +549,8 @@ fn test_parse_if_statement() {
 fn test_parse_if_else_statement() {
     let program = parse_str("if ($x -eq 5) { $y = 1 } else { $y = 2 }").unwrap();
 
-    match &program.statements[0] {
-        Statement::If { else_branch, .. } => {
+    match &program.statements[0].kind {
+        StatementKind::If { else_branch, .. } => {
             assert!(else_branch.is_some());
         }
         _ => panic!("Expected if statement"),
@@ -334,12 +561,15 @@ fn test_parse_if_else_statement() {
 fn test_parse_function_def() {
     let program = parse_str("function Test { Write-Output \"Hello\" }").unwrap();
 
-    match &program.statements[0] {
-        Statement::FunctionDef {
+    match &program.statements[0].kind {
+        StatementKind::FunctionDef {
             name,
             parameters,
             body,
+            is_filter,
+            ..
         } => {
+            assert!(!is_filter);
             assert_eq!(name, "Test");
             assert_eq!(parameters.len(), 0);
             assert!(!body.statements.is_empty());
@@ -348,16 +578,92 @@ fn test_parse_function_def() {
     }
 }
 
+#[test]
+fn test_parse_filter_def() {
+    let program = parse_str("filter Double { $_ * 2 }").unwrap();
+
+    match &program.statements[0].kind {
+        StatementKind::FunctionDef {
+            name,
+            parameters,
+            body,
+            is_filter,
+            ..
+        } => {
+            assert_eq!(name, "Double");
+            assert!(parameters.is_empty());
+            assert_eq!(body.statements.len(), 1);
+            assert!(is_filter);
+        }
+        _ => panic!("Expected filter definition"),
+    }
+}
+
+#[test]
+fn test_parse_function_with_named_blocks() {
+    let code = "function Sum {\n  begin { $total = 0 }\n  process { $total = $total + $_ }\n  end { $total }\n}";
+    let program = parse_str(code).unwrap();
+
+    match &program.statements[0].kind {
+        StatementKind::FunctionDef {
+            name,
+            body,
+            begin,
+            process,
+            ..
+        } => {
+            assert_eq!(name, "Sum");
+            assert_eq!(begin.as_ref().unwrap().statements.len(), 1);
+            assert_eq!(process.as_ref().unwrap().statements.len(), 1);
+            assert_eq!(
+                body.statements,
+                vec![StatementKind::Expression(
+                    ExpressionKind::Variable("total".to_string()).into()
+                )
+                .into()]
+            );
+        }
+        _ => panic!("Expected function definition"),
+    }
+}
+
+#[test]
+fn test_parse_function_with_only_process_block() {
+    let program = parse_str("function Up { process { $_ } }").unwrap();
+
+    match &program.statements[0].kind {
+        StatementKind::FunctionDef {
+            body,
+            begin,
+            process,
+            ..
+        } => {
+            assert!(begin.is_none());
+            assert!(process.is_some());
+            assert!(body.statements.is_empty());
+        }
+        _ => panic!("Expected function definition"),
+    }
+}
+
+#[test]
+fn test_parse_function_duplicate_named_block_is_error() {
+    assert!(parse_str("function F { process { 1 } process { 2 } }").is_err());
+}
+
 #[test]
 fn test_parse_function_def_with_params() {
     let program = parse_str("function Add($a, $b) { $a + $b }").unwrap();
 
-    match &program.statements[0] {
-        Statement::FunctionDef {
+    match &program.statements[0].kind {
+        StatementKind::FunctionDef {
             name,
             parameters,
             body,
+            is_filter,
+            ..
         } => {
+            assert!(!is_filter);
             assert_eq!(name, "Add");
             assert_eq!(parameters.len(), 2);
             assert_eq!(parameters[0].name, "a");
@@ -372,8 +678,11 @@ fn test_parse_function_def_with_params() {
 fn test_parse_return_statement() {
     let program = parse_str("return 42").unwrap();
 
-    match &program.statements[0] {
-        Statement::Return(Some(Expression::Literal(Literal::Number(n)))) => {
+    match &program.statements[0].kind {
+        StatementKind::Return(Some(Expression {
+            kind: ExpressionKind::Literal(Literal::Number(n)),
+            ..
+        })) => {
             assert_eq!(*n, 42.0);
         }
         _ => panic!("Expected return statement"),
@@ -384,8 +693,8 @@ fn test_parse_return_statement() {
 fn test_parse_return_statement_no_value() {
     let program = parse_str("return").unwrap();
 
-    match &program.statements[0] {
-        Statement::Return(None) => {}
+    match &program.statements[0].kind {
+        StatementKind::Return(None) => {}
         _ => panic!("Expected return statement with no value"),
     }
 }
@@ -394,19 +703,19 @@ fn test_parse_return_statement_no_value() {
 fn test_parse_pipeline_two_stages() {
     let program = parse_str("Get-Process | Where-Object").unwrap();
 
-    match &program.statements[0] {
-        Statement::Pipeline(pipeline) => {
+    match &program.statements[0].kind {
+        StatementKind::Pipeline(pipeline) => {
             assert_eq!(pipeline.stages.len(), 2);
 
-            match &pipeline.stages[0] {
-                Expression::Call { name, .. } => {
+            match &pipeline.stages[0].kind {
+                ExpressionKind::Call { name, .. } => {
                     assert_eq!(name, "Get-Process");
                 }
                 _ => panic!("Expected function call"),
             }
 
-            match &pipeline.stages[1] {
-                Expression::Call { name, .. } => {
+            match &pipeline.stages[1].kind {
+                ExpressionKind::Call { name, .. } => {
                     assert_eq!(name, "Where-Object");
                 }
                 _ => panic!("Expected function call"),
@@ -420,17 +729,20 @@ fn test_parse_pipeline_two_stages() {
 fn test_parse_pipeline_with_scriptblock() {
     let program = parse_str("Get-Process | Where-Object { $_.CPU -gt 10 }").unwrap();
 
-    match &program.statements[0] {
-        Statement::Pipeline(pipeline) => {
+    match &program.statements[0].kind {
+        StatementKind::Pipeline(pipeline) => {
             assert_eq!(pipeline.stages.len(), 2);
 
-            match &pipeline.stages[1] {
-                Expression::Call { name, arguments } => {
+            match &pipeline.stages[1].kind {
+                ExpressionKind::Call { name, arguments } => {
                     assert_eq!(name, "Where-Object");
                     assert_eq!(arguments.len(), 1);
 
                     match &arguments[0] {
-                        Argument::Positional(Expression::ScriptBlock(_)) => {}
+                        Argument::Positional(Expression {
+                            kind: ExpressionKind::ScriptBlock(_),
+                            ..
+                        }) => {}
                         _ => panic!("Expected script block argument"),
                     }
                 }
@@ -448,8 +760,8 @@ fn test_parse_multiple_statements() {
 
     // Verify all are assignments
     for stmt in &program.statements {
-        match stmt {
-            Statement::Assignment { .. } => {}
+        match &stmt.kind {
+            StatementKind::Assignment { .. } => {}
             _ => panic!("Expected assignment"),
         }
     }
@@ -465,12 +777,12 @@ fn test_parse_multiple_statements_with_semicolon() {
 fn test_parse_complex_expression() {
     let program = parse_str("$x = ($a + $b) * 2 - $c / 3").unwrap();
 
-    match &program.statements[0] {
-        Statement::Assignment { variable, value } => {
+    match &program.statements[0].kind {
+        StatementKind::Assignment { variable, value } => {
             assert_eq!(variable, "x");
             // Just verify it parses without error
-            match value {
-                Expression::BinaryOp { .. } => {}
+            match &value.kind {
+                ExpressionKind::BinaryOp { .. } => {}
                 _ => panic!("Expected binary operation"),
             }
         }
@@ -482,8 +794,8 @@ fn test_parse_complex_expression() {
 fn test_parse_interpolated_string() {
     let program = parse_str("\"Hello $name\"").unwrap();
 
-    match &program.statements[0] {
-        Statement::Expression(Expression::Literal(Literal::InterpolatedString(parts))) => {
+    match expression_kind(&program.statements[0]) {
+        ExpressionKind::Literal(Literal::InterpolatedString(parts)) => {
             assert_eq!(parts.len(), 2);
             assert_eq!(parts[0], StringPart::Literal("Hello ".to_string()));
             assert_eq!(parts[1], StringPart::Variable("name".to_string()));
@@ -510,20 +822,79 @@ fn test_parse_comments_ignored() {
     assert_eq!(program.statements.len(), 1);
 }
 
+#[test]
+fn test_parse_keeps_comments_and_blank_lines() {
+    let program =
+        parse_str("# first\n$x = 5 # five\n\n\nif ($x) {\n    # inside\n}\n# last").unwrap();
+    let trivia: Vec<(usize, &TriviaKind)> = program
+        .trivia
+        .iter()
+        .map(|t| (t.statement, &t.kind))
+        .collect();
+    assert!(matches!(
+        trivia.as_slice(),
+        [
+            (0, TriviaKind::Comment { text: first, .. }),
+            (0, TriviaKind::TrailingComment { text: five, .. }),
+            (1, TriviaKind::BlankLine),
+            (2, TriviaKind::Comment { text: last, .. }),
+        ] if first == "# first" && five == "# five" && last == "# last"
+    ));
+
+    match &program.statements[1].kind {
+        StatementKind::If { then_branch, .. } => {
+            assert!(then_branch.statements.is_empty());
+            assert!(matches!(
+                &then_branch.trivia[..],
+                [Trivia { statement: 0, kind: TriviaKind::Comment { text, .. } }] if text == "# inside"
+            ));
+        }
+        _ => panic!("Expected if statement"),
+    }
+
+    // Each hashtable entry counts as a statement for the comments in it
+    let program = parse_str("$h = @{\n    # key\n    a = 1 # one\n    b = 2\n}").unwrap();
+    assert!(program.trivia.is_empty());
+    match &program.statements[0].kind {
+        StatementKind::Assignment { value, .. } => match &value.kind {
+            ExpressionKind::Hashtable { trivia, .. } => {
+                let trivia: Vec<(usize, &TriviaKind)> =
+                    trivia.iter().map(|t| (t.statement, &t.kind)).collect();
+                assert!(matches!(
+                    trivia.as_slice(),
+                    [
+                        (0, TriviaKind::Comment { text: key, .. }),
+                        (0, TriviaKind::TrailingComment { text: one, .. }),
+                    ] if key == "# key" && one == "# one"
+                ));
+            }
+            _ => panic!("Expected hashtable expression"),
+        },
+        _ => panic!("Expected assignment"),
+    }
+}
+
+#[test]
+fn test_comments_are_not_duplicated_by_backtracking() {
+    // $a.b is first tried as an assignment target, then reparsed as an expression
+    let program = parse_str("$a.b # note").unwrap();
+    assert_eq!(program.trivia.len(), 1);
+}
+
 #[test]
 fn test_parse_cmdlet_with_named_param() {
     let program = parse_str("Select-Object -First 5").unwrap();
 
-    match &program.statements[0] {
-        Statement::Expression(Expression::Call { name, arguments }) => {
+    match expression_kind(&program.statements[0]) {
+        ExpressionKind::Call { name, arguments } => {
             assert_eq!(name, "Select-Object");
             assert_eq!(arguments.len(), 1);
 
             match &arguments[0] {
-                Argument::Named { name, value } => {
+                Argument::Named { name, value, .. } => {
                     assert_eq!(name, "First");
-                    match value {
-                        Expression::Literal(Literal::Number(n)) => assert_eq!(*n, 5.0),
+                    match &value.kind {
+                        ExpressionKind::Literal(Literal::Number(n)) => assert_eq!(*n, 5.0),
                         _ => panic!("Expected number"),
                     }
                 }
@@ -537,7 +908,7 @@ fn test_parse_cmdlet_with_named_param() {
 #[test]
 fn test_parse_cmdlet_with_switch_param_implicit_true() {
     use pwsh_lexer::Lexer;
-    use pwsh_parser::{Argument, Expression, Literal, Parser, Statement};
+    use pwsh_parser::{Argument, ExpressionKind, Literal, Parser};
 
     let mut lexer = Lexer::new("Get-ChildItem -Recurse");
     let tokens = lexer.tokenize().unwrap();
@@ -545,14 +916,14 @@ fn test_parse_cmdlet_with_switch_param_implicit_true() {
     let program = parser.parse().unwrap();
 
     assert_eq!(program.statements.len(), 1);
-    match &program.statements[0] {
-        Statement::Expression(Expression::Call { name, arguments }) => {
+    match expression_kind(&program.statements[0]) {
+        ExpressionKind::Call { name, arguments } => {
             assert_eq!(name, "Get-ChildItem");
             assert_eq!(arguments.len(), 1);
             match &arguments[0] {
-                Argument::Named { name, value } => {
+                Argument::Named { name, value, .. } => {
                     assert_eq!(name, "Recurse");
-                    assert_eq!(value, &Expression::Literal(Literal::Boolean(true)));
+                    assert_eq!(value.kind, ExpressionKind::Literal(Literal::Boolean(true)));
                 }
                 other => panic!("Expected named argument, got {:?}", other),
             }
@@ -561,14 +932,189 @@ fn test_parse_cmdlet_with_switch_param_implicit_true() {
     }
 }
 
+#[test]
+fn test_parse_cmdlet_with_colon_param_value() {
+    let program = parse_str("Sort-Object -Parallel:$false -Property:Name").unwrap();
+
+    match expression_kind(&program.statements[0]) {
+        ExpressionKind::Call { arguments, .. } => match &arguments[..] {
+            [Argument::Named {
+                name: parallel,
+                value: off,
+                ..
+            }, Argument::Named {
+                name: property,
+                value: by,
+                ..
+            }] => {
+                assert_eq!(parallel, "Parallel");
+                assert_eq!(off.kind, ExpressionKind::Variable("false".to_string()));
+                assert_eq!(property, "Property");
+                assert_eq!(
+                    by.kind,
+                    ExpressionKind::Literal(Literal::String("Name".to_string()))
+                );
+            }
+            other => panic!("Expected two named arguments, got {:?}", other),
+        },
+        other => panic!("Expected call expression, got {:?}", other),
+    }
+    assert!(parse_str("Get-ChildItem -Recurse:").is_err());
+}
+
+#[test]
+fn test_parse_drive_qualified_bare_words() {
+    let program =
+        parse_str("Get-ChildItem Variable:\nGet-ChildItem -Path function:Get-Size").unwrap();
+
+    let argument_value = |statement: &Statement| match expression_kind(statement) {
+        ExpressionKind::Call { arguments, .. } => match &arguments[..] {
+            [Argument::Positional(value)] | [Argument::Named { value, .. }] => value.kind.clone(),
+            other => panic!("Expected one argument, got {:?}", other),
+        },
+        other => panic!("Expected call expression, got {:?}", other),
+    };
+    assert_eq!(
+        argument_value(&program.statements[0]),
+        ExpressionKind::Literal(Literal::String("Variable:".to_string()))
+    );
+    assert_eq!(
+        argument_value(&program.statements[1]),
+        ExpressionKind::Literal(Literal::String("function:Get-Size".to_string()))
+    );
+}
+
+#[test]
+fn test_parse_wildcard_bare_words() {
+    let program = parse_str("Select-Object Name, *Time, *\nGet-ChildItem -Filter Get*").unwrap();
+
+    let argument_values = |statement: &Statement| match expression_kind(statement) {
+        ExpressionKind::Call { arguments, .. } => arguments
+            .iter()
+            .map(|argument| match argument {
+                Argument::Positional(value) | Argument::Named { value, .. } => value.kind.clone(),
+                other => panic!("Expected a value argument, got {:?}", other),
+            })
+            .collect::<Vec<_>>(),
+        other => panic!("Expected call expression, got {:?}", other),
+    };
+    let string = |text: &str| ExpressionKind::Literal(Literal::String(text.to_string()));
+    match &argument_values(&program.statements[0])[..] {
+        [ExpressionKind::Array(items)] => assert_eq!(
+            items
+                .iter()
+                .map(|item| item.kind.clone())
+                .collect::<Vec<_>>(),
+            vec![string("Name"), string("*Time"), string("*")]
+        ),
+        other => panic!("Expected one array argument, got {:?}", other),
+    }
+    assert_eq!(
+        argument_values(&program.statements[1]),
+        vec![string("Get*")]
+    );
+
+    // Spaced out, a star is still multiplication
+    let program = parse_str("$x = 2 * 3").unwrap();
+    assert!(matches!(
+        &program.statements[0].kind,
+        StatementKind::Assignment { .. }
+    ));
+}
+
+#[test]
+fn test_parse_switch_followed_by_another_param() {
+    let program = parse_str("Get-ChildItem -Recurse -Force -Depth -1").unwrap();
+
+    match expression_kind(&program.statements[0]) {
+        ExpressionKind::Call { arguments, .. } => {
+            let named: Vec<(&str, &ExpressionKind)> = arguments
+                .iter()
+                .map(|argument| match argument {
+                    Argument::Named { name, value, .. } => (name.as_str(), &value.kind),
+                    other => panic!("Expected named argument, got {:?}", other),
+                })
+                .collect();
+            assert_eq!(named.len(), 3);
+            assert_eq!(
+                named[0],
+                ("Recurse", &ExpressionKind::Literal(Literal::Boolean(true)))
+            );
+            assert_eq!(
+                named[1],
+                ("Force", &ExpressionKind::Literal(Literal::Boolean(true)))
+            );
+            assert_eq!(named[2].0, "Depth");
+            assert!(matches!(named[2].1, ExpressionKind::UnaryOp { .. }));
+        }
+        other => panic!("Expected call expression, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_parameter_or_value_after_a_dash() {
+    let program = parse_str(
+        "Get-Item -Depth -1.5 -Offset -$n -Path $item.FullName -Force -Filter -eq\nGet-Item -Name - Value",
+    )
+    .unwrap();
+    assert_eq!(program.statements.len(), 2);
+
+    let arguments = |statement: &Statement| match expression_kind(statement) {
+        ExpressionKind::Call { arguments, .. } => arguments
+            .iter()
+            .map(|argument| match argument {
+                Argument::Named { name, value, .. } => (format!("-{}", name), value.kind.clone()),
+                Argument::Positional(value) => (String::new(), value.kind.clone()),
+                other => panic!("Expected a value argument, got {:?}", other),
+            })
+            .collect::<Vec<_>>(),
+        other => panic!("Expected call expression, got {:?}", other),
+    };
+    let negated = |kind: &ExpressionKind| {
+        matches!(
+            kind,
+            ExpressionKind::UnaryOp {
+                operator: UnaryOperator::Negate,
+                ..
+            }
+        )
+    };
+    let switch = ExpressionKind::Literal(Literal::Boolean(true));
+
+    // A dash right before a number or variable makes a negative value; one
+    // right before a name, or an operator, starts the next parameter
+    let first = arguments(&program.statements[0]);
+    let names: Vec<_> = first.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(
+        names,
+        vec!["-Depth", "-Offset", "-Path", "-Force", "-Filter", "-eq"]
+    );
+    assert!(negated(&first[0].1));
+    assert!(negated(&first[1].1));
+    assert!(matches!(first[2].1, ExpressionKind::MemberAccess { .. }));
+    assert_eq!(first[3].1, switch);
+    assert_eq!(first[4].1, switch);
+    assert_eq!(first[5].1, switch);
+
+    // A dash with a space after it is a word, not a parameter
+    let string = |text: &str| ExpressionKind::Literal(Literal::String(text.to_string()));
+    assert_eq!(
+        arguments(&program.statements[1]),
+        vec![
+            ("-Name".to_string(), string("-")),
+            (String::new(), string("Value"))
+        ]
+    );
+}
+
 #[test]
 fn test_parse_complex_pipeline() {
     let program =
         parse_str("Get-Process | Where-Object { $_.CPU -gt 10 } | Select-Object Name, CPU")
             .unwrap();
 
-    match &program.statements[0] {
-        Statement::Pipeline(pipeline) => {
+    match &program.statements[0].kind {
+        StatementKind::Pipeline(pipeline) => {
             assert_eq!(pipeline.stages.len(), 3);
         }
         _ => panic!("Expected pipeline"),
@@ -579,12 +1125,12 @@ fn test_parse_complex_pipeline() {
 fn test_parse_nested_blocks() {
     let program = parse_str("if ($x -eq 5) { if ($y -eq 10) { $z = 1 } }").unwrap();
 
-    match &program.statements[0] {
-        Statement::If { then_branch, .. } => {
+    match &program.statements[0].kind {
+        StatementKind::If { then_branch, .. } => {
             assert_eq!(then_branch.statements.len(), 1);
 
-            match &then_branch.statements[0] {
-                Statement::If { .. } => {}
+            match &then_branch.statements[0].kind {
+                StatementKind::If { .. } => {}
                 _ => panic!("Expected nested if statement"),
             }
         }
@@ -598,8 +1144,8 @@ fn test_parse_empty_hashtable() {
     let program = parse_str("@{}").unwrap();
     assert_eq!(program.statements.len(), 1);
 
-    match &program.statements[0] {
-        Statement::Expression(Expression::Hashtable(pairs)) => {
+    match expression_kind(&program.statements[0]) {
+        ExpressionKind::Hashtable { entries: pairs, .. } => {
             assert_eq!(pairs.len(), 0);
         }
         _ => panic!("Expected hashtable expression"),
@@ -611,12 +1157,12 @@ fn test_parse_hashtable_single_pair() {
     let program = parse_str("@{Name=\"John\"}").unwrap();
     assert_eq!(program.statements.len(), 1);
 
-    match &program.statements[0] {
-        Statement::Expression(Expression::Hashtable(pairs)) => {
+    match expression_kind(&program.statements[0]) {
+        ExpressionKind::Hashtable { entries: pairs, .. } => {
             assert_eq!(pairs.len(), 1);
             assert_eq!(pairs[0].0, "Name");
-            match &pairs[0].1 {
-                Expression::Literal(Literal::String(s)) => assert_eq!(s, "John"),
+            match &pairs[0].1.kind {
+                ExpressionKind::Literal(Literal::String(s)) => assert_eq!(s, "John"),
                 _ => panic!("Expected string literal"),
             }
         }
@@ -629,13 +1175,13 @@ fn test_parse_hashtable_multiple_pairs() {
     let program = parse_str("@{Name=\"John\"; Age=30}").unwrap();
     assert_eq!(program.statements.len(), 1);
 
-    match &program.statements[0] {
-        Statement::Expression(Expression::Hashtable(pairs)) => {
+    match expression_kind(&program.statements[0]) {
+        ExpressionKind::Hashtable { entries: pairs, .. } => {
             assert_eq!(pairs.len(), 2);
             assert_eq!(pairs[0].0, "Name");
             assert_eq!(pairs[1].0, "Age");
-            match &pairs[1].1 {
-                Expression::Literal(Literal::Number(n)) => assert_eq!(*n, 30.0),
+            match &pairs[1].1.kind {
+                ExpressionKind::Literal(Literal::Number(n)) => assert_eq!(*n, 30.0),
                 _ => panic!("Expected number literal"),
             }
         }
@@ -648,11 +1194,11 @@ fn test_parse_hashtable_assignment() {
     let program = parse_str("$obj = @{X=5; Y=10}").unwrap();
     assert_eq!(program.statements.len(), 1);
 
-    match &program.statements[0] {
-        Statement::Assignment { variable, value } => {
+    match &program.statements[0].kind {
+        StatementKind::Assignment { variable, value } => {
             assert_eq!(variable, "obj");
-            match value {
-                Expression::Hashtable(pairs) => {
+            match &value.kind {
+                ExpressionKind::Hashtable { entries: pairs, .. } => {
                     assert_eq!(pairs.len(), 2);
                     assert_eq!(pairs[0].0, "X");
                     assert_eq!(pairs[1].0, "Y");
@@ -669,8 +1215,8 @@ fn test_parse_empty_array() {
     let program = parse_str("@()").unwrap();
     assert_eq!(program.statements.len(), 1);
 
-    match &program.statements[0] {
-        Statement::Expression(Expression::Array(items)) => {
+    match expression_kind(&program.statements[0]) {
+        ExpressionKind::Array(items) => {
             assert_eq!(items.len(), 0);
         }
         _ => panic!("Expected array expression"),
@@ -682,11 +1228,11 @@ fn test_parse_array_single_item() {
     let program = parse_str("@(1)").unwrap();
     assert_eq!(program.statements.len(), 1);
 
-    match &program.statements[0] {
-        Statement::Expression(Expression::Array(items)) => {
+    match expression_kind(&program.statements[0]) {
+        ExpressionKind::Array(items) => {
             assert_eq!(items.len(), 1);
-            match &items[0] {
-                Expression::Literal(Literal::Number(n)) => assert_eq!(*n, 1.0),
+            match &items[0].kind {
+                ExpressionKind::Literal(Literal::Number(n)) => assert_eq!(*n, 1.0),
                 _ => panic!("Expected number literal"),
             }
         }
@@ -699,12 +1245,12 @@ fn test_parse_array_multiple_items() {
     let program = parse_str("@(1, 2, 3)").unwrap();
     assert_eq!(program.statements.len(), 1);
 
-    match &program.statements[0] {
-        Statement::Expression(Expression::Array(items)) => {
+    match expression_kind(&program.statements[0]) {
+        ExpressionKind::Array(items) => {
             assert_eq!(items.len(), 3);
             for (i, item) in items.iter().enumerate() {
-                match item {
-                    Expression::Literal(Literal::Number(n)) => {
+                match &item.kind {
+                    ExpressionKind::Literal(Literal::Number(n)) => {
                         assert_eq!(*n, (i + 1) as f64);
                     }
                     _ => panic!("Expected number literal"),
@@ -720,11 +1266,11 @@ fn test_parse_array_assignment() {
     let program = parse_str("$arr = @(1, 2, 3, 4, 5)").unwrap();
     assert_eq!(program.statements.len(), 1);
 
-    match &program.statements[0] {
-        Statement::Assignment { variable, value } => {
+    match &program.statements[0].kind {
+        StatementKind::Assignment { variable, value } => {
             assert_eq!(variable, "arr");
-            match value {
-                Expression::Array(items) => {
+            match &value.kind {
+                ExpressionKind::Array(items) => {
                     assert_eq!(items.len(), 5);
                 }
                 _ => panic!("Expected array expression"),
@@ -739,11 +1285,11 @@ fn test_parse_array_in_pipeline() {
     let program = parse_str("@(1, 2, 3) | Write-Output").unwrap();
     assert_eq!(program.statements.len(), 1);
 
-    match &program.statements[0] {
-        Statement::Pipeline(pipeline) => {
+    match &program.statements[0].kind {
+        StatementKind::Pipeline(pipeline) => {
             assert_eq!(pipeline.stages.len(), 2);
-            match &pipeline.stages[0] {
-                Expression::Array(items) => {
+            match &pipeline.stages[0].kind {
+                ExpressionKind::Array(items) => {
                     assert_eq!(items.len(), 3);
                 }
                 _ => panic!("Expected array expression"),
@@ -758,24 +1304,31 @@ fn test_parse_assignment_with_pipeline() {
     let program = parse_str("$selected = $processes | Select-Object Name, CPU").unwrap();
     assert_eq!(program.statements.len(), 1);
 
-    match &program.statements[0] {
-        Statement::Assignment { variable, value } => {
+    match &program.statements[0].kind {
+        StatementKind::Assignment { variable, value } => {
             assert_eq!(variable, "selected");
-            match value {
-                Expression::Pipeline(pipeline) => {
+            match &value.kind {
+                ExpressionKind::Pipeline(pipeline) => {
                     assert_eq!(pipeline.stages.len(), 2);
                     // First stage should be a variable
-                    match &pipeline.stages[0] {
-                        Expression::Variable(name) => {
+                    match &pipeline.stages[0].kind {
+                        ExpressionKind::Variable(name) => {
                             assert_eq!(name, "processes");
                         }
                         _ => panic!("Expected variable"),
                     }
                     // Second stage should be a call to Select-Object
-                    match &pipeline.stages[1] {
-                        Expression::Call { name, arguments } => {
+                    match &pipeline.stages[1].kind {
+                        ExpressionKind::Call { name, arguments } => {
                             assert_eq!(name, "Select-Object");
-                            assert_eq!(arguments.len(), 2);
+                            // Name, CPU is one argument, an array of two words
+                            match &arguments[..] {
+                                [Argument::Positional(Expression {
+                                    kind: ExpressionKind::Array(items),
+                                    ..
+                                })] => assert_eq!(items.len(), 2),
+                                other => panic!("Expected one array argument, got {:?}", other),
+                            }
                         }
                         _ => panic!("Expected function call"),
                     }
@@ -786,3 +1339,775 @@ fn test_parse_assignment_with_pipeline() {
         _ => panic!("Expected assignment"),
     }
 }
+
+#[test]
+fn test_parse_assignment_with_multiline_pipeline() {
+    let program = parse_str("$files = Get-ChildItem |\n    Sort-Object Name\n$files").unwrap();
+    assert_eq!(program.statements.len(), 2);
+
+    match &program.statements[0].kind {
+        StatementKind::Assignment { variable, value } => {
+            assert_eq!(variable, "files");
+            match &value.kind {
+                ExpressionKind::Pipeline(pipeline) => {
+                    assert_eq!(pipeline.stages.len(), 2);
+                    assert!(matches!(
+                        &pipeline.stages[0].kind,
+                        ExpressionKind::Call { name, arguments } if name == "Get-ChildItem" && arguments.is_empty()
+                    ));
+                }
+                _ => panic!("Expected pipeline expression"),
+            }
+        }
+        _ => panic!("Expected assignment"),
+    }
+}
+
+#[test]
+fn test_parse_assignment_in_block_does_not_consume_following_pipeline() {
+    let program = parse_str("if (true) { $x = 5 }\n(1 | Write-Output)").unwrap();
+    assert_eq!(program.statements.len(), 2);
+
+    match &program.statements[0].kind {
+        StatementKind::If { then_branch, .. } => match &then_branch.statements[0].kind {
+            StatementKind::Assignment { value, .. } => {
+                assert_eq!(value.kind, ExpressionKind::Literal(Literal::Number(5.0)));
+            }
+            _ => panic!("Expected assignment"),
+        },
+        _ => panic!("Expected if statement"),
+    }
+}
+
+#[test]
+fn test_parse_index_assignment() {
+    let program = parse_str("$arr[0] = 5").unwrap();
+
+    match &program.statements[0].kind {
+        StatementKind::TargetAssignment { target, value } => {
+            assert!(matches!(target.kind, ExpressionKind::Index { .. }));
+            assert_eq!(value.kind, ExpressionKind::Literal(Literal::Number(5.0)));
+        }
+        _ => panic!("Expected target assignment"),
+    }
+}
+
+#[test]
+fn test_parse_property_assignment() {
+    let program = parse_str("$obj.Prop = 10").unwrap();
+
+    match &program.statements[0].kind {
+        StatementKind::TargetAssignment { target, .. } => match &target.kind {
+            ExpressionKind::MemberAccess { object, member } => {
+                assert_eq!(object.kind, ExpressionKind::Variable("obj".to_string()));
+                assert_eq!(member, "Prop");
+            }
+            _ => panic!("Expected member access target"),
+        },
+        _ => panic!("Expected target assignment"),
+    }
+}
+
+#[test]
+fn test_parse_member_access_without_assignment_is_expression() {
+    let program = parse_str("$obj.Prop -eq 10").unwrap();
+    assert!(matches!(
+        expression_kind(&program.statements[0]),
+        ExpressionKind::BinaryOp { .. }
+    ));
+}
+
+#[test]
+fn test_parse_multiple_assignment() {
+    let program = parse_str("$a, $b = 1, 2").unwrap();
+    assert_eq!(program.statements.len(), 1);
+
+    match &program.statements[0].kind {
+        StatementKind::MultipleAssignment { variables, value } => {
+            assert_eq!(variables, &vec!["a".to_string(), "b".to_string()]);
+            match &value.kind {
+                ExpressionKind::Array(items) => assert_eq!(items.len(), 2),
+                _ => panic!("Expected array value"),
+            }
+        }
+        _ => panic!("Expected multiple assignment"),
+    }
+}
+
+#[test]
+fn test_parse_assignment_comma_list() {
+    let program = parse_str("$x = 1, 2, 3").unwrap();
+    assert_eq!(program.statements.len(), 1);
+
+    match &program.statements[0].kind {
+        StatementKind::Assignment { variable, value } => {
+            assert_eq!(variable, "x");
+            assert!(matches!(&value.kind, ExpressionKind::Array(items) if items.len() == 3));
+        }
+        _ => panic!("Expected assignment"),
+    }
+}
+
+#[test]
+fn test_parse_class_def() {
+    let code = "class Person {\n  [string]$Name\n  [int]$Age = 1\n  Person([string]$n) { $this.Name = $n }\n  [string] Greet() { return $this.Name }\n  static [int] Zero() { return 0 }\n}";
+    let program = parse_str(code).unwrap();
+
+    match &program.statements[0].kind {
+        StatementKind::ClassDef(class) => {
+            assert_eq!(class.name, "Person");
+            assert_eq!(class.properties.len(), 2);
+            assert_eq!(class.properties[0].type_name.as_deref(), Some("string"));
+            assert!(class.properties[1].default_value.is_some());
+            assert_eq!(class.constructors.len(), 1);
+            assert_eq!(
+                class.constructors[0].parameters[0].type_name.as_deref(),
+                Some("string")
+            );
+            assert_eq!(class.methods.len(), 2);
+            assert_eq!(class.methods[0].return_type.as_deref(), Some("string"));
+            assert!(!class.methods[0].is_static);
+            assert!(class.methods[1].is_static);
+        }
+        _ => panic!("Expected class definition"),
+    }
+}
+
+#[test]
+fn test_parse_enum_def() {
+    let program = parse_str("enum Color { Red; Green = 5\n Blue }").unwrap();
+
+    match &program.statements[0].kind {
+        StatementKind::EnumDef(def) => {
+            assert_eq!(def.name, "Color");
+            assert_eq!(
+                def.members,
+                vec![
+                    ("Red".to_string(), None),
+                    ("Green".to_string(), Some(5)),
+                    ("Blue".to_string(), None)
+                ]
+            );
+        }
+        _ => panic!("Expected enum definition"),
+    }
+}
+
+#[test]
+fn test_parse_static_member_and_method_call() {
+    let program = parse_str("[Color]::Red\n[Person]::new('Ann')").unwrap();
+
+    assert_eq!(
+        *expression_kind(&program.statements[0]),
+        ExpressionKind::StaticMember {
+            type_name: "Color".to_string(),
+            member: "Red".to_string(),
+        }
+    );
+    assert_eq!(
+        *expression_kind(&program.statements[1]),
+        ExpressionKind::StaticMethodCall {
+            type_name: "Person".to_string(),
+            method: "new".to_string(),
+            arguments: vec![ExpressionKind::Literal(Literal::String("Ann".to_string())).into()],
+        }
+    );
+}
+
+#[test]
+fn test_parse_method_call() {
+    let program = parse_str("$p.Greet('hi', 2)").unwrap();
+
+    assert_eq!(
+        *expression_kind(&program.statements[0]),
+        ExpressionKind::MethodCall {
+            object: Box::new(ExpressionKind::Variable("p".to_string()).into()),
+            method: "Greet".to_string(),
+            arguments: vec![
+                ExpressionKind::Literal(Literal::String("hi".to_string())).into(),
+                ExpressionKind::Literal(Literal::Number(2.0)).into(),
+            ],
+        }
+    );
+}
+
+#[test]
+fn test_parse_foreach_statement() {
+    let program = parse_str("foreach ($kv in $h.GetEnumerator()) { $kv.Key }").unwrap();
+
+    match &program.statements[0].kind {
+        StatementKind::Foreach {
+            variable,
+            collection,
+            body,
+        } => {
+            assert_eq!(variable, "kv");
+            assert!(
+                matches!(&collection.kind, ExpressionKind::MethodCall { method, .. } if method == "GetEnumerator")
+            );
+            assert_eq!(body.statements.len(), 1);
+        }
+        _ => panic!("Expected foreach statement"),
+    }
+}
+
+#[test]
+fn test_parse_foreach_requires_in() {
+    assert!(parse_str("foreach ($x of $items) { $x }").is_err());
+}
+
+#[test]
+fn test_parse_ordered_hashtable_cast() {
+    let program = parse_str("[ordered]@{b = 1; a = 2}").unwrap();
+
+    match expression_kind(&program.statements[0]) {
+        ExpressionKind::Cast {
+            type_name,
+            expression,
+        } => {
+            assert_eq!(type_name, "ordered");
+            assert!(
+                matches!(expression.kind, ExpressionKind::Hashtable { entries: ref pairs, .. } if pairs.len() == 2)
+            );
+        }
+        _ => panic!("Expected cast expression"),
+    }
+}
+
+#[test]
+fn test_parse_cast_binds_tighter_than_binary_operators() {
+    let program = parse_str("[int]$x + 1").unwrap();
+
+    match expression_kind(&program.statements[0]) {
+        ExpressionKind::BinaryOp { left, .. } => {
+            assert_eq!(
+                left.kind,
+                ExpressionKind::Cast {
+                    type_name: "int".to_string(),
+                    expression: Box::new(ExpressionKind::Variable("x".to_string()).into()),
+                }
+            );
+        }
+        _ => panic!("Expected binary operation"),
+    }
+}
+
+#[test]
+fn test_parse_ordered_requires_hashtable_literal() {
+    assert!(matches!(
+        parse_str("[ordered]$h"),
+        Err(ParseError::InvalidExpression { .. })
+    ));
+}
+
+#[test]
+fn test_parse_records_spans() {
+    let source = "$total = (1 + 2) * 3;\nfunction F($a = 1) {\n    Get-Item -Path $a\n}";
+    let program = parse_str(source).unwrap();
+    // Spans print as start..end, where end is just past the last character
+    let span = |span: Span| format!("{:?}", span);
+
+    assert_eq!(span(program.statements[0].span), "1:1..1:21");
+    match &program.statements[0].kind {
+        StatementKind::Assignment { value, .. } => {
+            assert_eq!(span(value.span), "1:10..1:21");
+            match &value.kind {
+                ExpressionKind::BinaryOp { left, right, .. } => {
+                    // Parentheses belong to the expression they enclose
+                    assert_eq!(span(left.span), "1:10..1:17");
+                    assert_eq!(span(right.span), "1:20..1:21");
+                }
+                _ => panic!("Expected binary operation"),
+            }
+        }
+        _ => panic!("Expected assignment"),
+    }
+
+    assert_eq!(span(program.statements[1].span), "2:1..4:2");
+    match &program.statements[1].kind {
+        StatementKind::FunctionDef {
+            parameters, body, ..
+        } => {
+            assert_eq!(span(parameters[0].span), "2:12..2:18");
+            assert_eq!(span(body.span), "2:20..4:2");
+            assert_eq!(span(body.statements[0].span), "3:5..3:22");
+            match expression_kind(&body.statements[0]) {
+                ExpressionKind::Call { arguments, .. } => {
+                    assert_eq!(span(arguments[0].span()), "3:14..3:22");
+                }
+                _ => panic!("Expected call"),
+            }
+        }
+        _ => panic!("Expected function definition"),
+    }
+}
+
+#[test]
+fn test_equal_nodes_ignore_spans() {
+    let a = parse_str("$x = 1 + 2").unwrap();
+    let b = parse_str("\n\n  $x=1+2").unwrap();
+    assert_ne!(a.statements[0].span, b.statements[0].span);
+    assert_eq!(a.statements, b.statements);
+}
+
+#[test]
+fn test_ast_round_trips_through_json() {
+    let program = parse_str(
+        "# total\nfunction Add([int]$a, $b = 2) { $a + $b }\nGet-Item -Path 'x' | Sort-Object",
+    )
+    .unwrap();
+
+    let json = serde_json::to_string(&program).unwrap();
+    let back: Program = serde_json::from_str(&json).unwrap();
+    assert_eq!(back, program);
+    // Equality ignores spans, so check they survive too
+    assert_eq!(back.statements[1].span, program.statements[1].span);
+    assert_eq!(back.trivia, program.trivia);
+}
+
+#[test]
+fn test_parse_stop_parsing_argument() {
+    let program =
+        parse_str("git -C $dir --% log --format=\"%h $x\" | Select-Object -First 1").unwrap();
+
+    let StatementKind::Pipeline(pipeline) = &program.statements[0].kind else {
+        panic!("Expected pipeline, got {:?}", program.statements[0].kind);
+    };
+    match &pipeline.stages[0].kind {
+        ExpressionKind::Call { name, arguments } => {
+            assert_eq!(name, "git");
+            assert_eq!(arguments.len(), 2);
+            assert_eq!(
+                arguments[1],
+                Argument::StopParsing {
+                    text: "log --format=\"%h $x\"".to_string(),
+                    span: Span::default(),
+                }
+            );
+        }
+        other => panic!("Expected call, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_try_catch() {
+    let program = parse_str("try { Get-Item 'x' }\ncatch { $_ }\n'next'").unwrap();
+    assert_eq!(program.statements.len(), 2);
+    match &program.statements[0].kind {
+        StatementKind::Try {
+            body,
+            catch: Some(catch),
+            finally: None,
+        } => {
+            assert_eq!(body.statements.len(), 1);
+            assert_eq!(catch.statements.len(), 1);
+        }
+        other => panic!("Expected try statement, got {:?}", other),
+    }
+
+    assert!(parse_str("try { 1 }").is_err());
+    assert!(matches!(
+        parse_str("try { 1 } catch [System.IO.IOException] { 2 }"),
+        Err(ParseError::InvalidStatement { .. })
+    ));
+}
+
+#[test]
+fn test_parse_try_finally_and_loop_control() {
+    let program =
+        parse_str("try { 1 }\nfinally { 2 }\ntry { 1 } catch { 2 }\nfinally { 3 }").unwrap();
+    assert_eq!(program.statements.len(), 2);
+    assert!(matches!(
+        &program.statements[0].kind,
+        StatementKind::Try {
+            catch: None,
+            finally: Some(_),
+            ..
+        }
+    ));
+    assert!(matches!(
+        &program.statements[1].kind,
+        StatementKind::Try {
+            catch: Some(_),
+            finally: Some(_),
+            ..
+        }
+    ));
+
+    let program = parse_str("foreach ($i in $items) { if ($i) { continue }; break }").unwrap();
+    match &program.statements[0].kind {
+        StatementKind::Foreach { body, .. } => {
+            assert!(matches!(body.statements[1].kind, StatementKind::Break));
+        }
+        other => panic!("Expected foreach statement, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_parse_throw() {
+    let program = parse_str("throw 'bad'\ntry { 1 } catch { throw }").unwrap();
+    assert!(matches!(
+        &program.statements[0].kind,
+        StatementKind::Throw(Some(_))
+    ));
+    match &program.statements[1].kind {
+        StatementKind::Try {
+            catch: Some(catch), ..
+        } => {
+            assert!(matches!(
+                catch.statements[0].kind,
+                StatementKind::Throw(None)
+            ));
+        }
+        other => panic!("Expected try statement, got {:?}", other),
+    }
+}
+
+// Parse a string, carrying on past errors
+fn parse_recovering(input: &str) -> (Program, Vec<ParseError>) {
+    let tokens = Lexer::new(input).tokenize().unwrap();
+    Parser::new(tokens).parse_with_recovery()
+}
+
+#[test]
+fn test_recovery_reports_every_error() {
+    let (program, errors) = parse_recovering("$a = 1\n$b = )\n$c = 3; $d = (4 +]\n$e = 5");
+    let lines: Vec<_> = errors
+        .iter()
+        .map(|error| error.position().unwrap().line)
+        .collect();
+    assert_eq!(lines, vec![2, 3]);
+
+    // The statements without errors are all there
+    let names: Vec<_> = program
+        .statements
+        .iter()
+        .map(|statement| match &statement.kind {
+            StatementKind::Assignment { variable, .. } => variable.as_str(),
+            other => panic!("Expected assignment, got {:?}", other),
+        })
+        .collect();
+    assert_eq!(names, vec!["a", "c", "e"]);
+
+    // Without recovery the first error is returned
+    assert_eq!(parse_str("$a = 1\n$b = )\n$c = )").unwrap_err(), errors[0]);
+}
+
+#[test]
+fn test_recovery_inside_blocks() {
+    let source =
+        "function f {\n  $x = )\n  $y = 2\n}\nif ($true) {\n  1\n} else {\n  $z = ]\n}\n$after = 1";
+    let (program, errors) = parse_recovering(source);
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].position().unwrap().line, 2);
+    assert_eq!(errors[1].position().unwrap().line, 8);
+
+    assert_eq!(program.statements.len(), 3);
+    match &program.statements[0].kind {
+        StatementKind::FunctionDef { body, .. } => assert_eq!(body.statements.len(), 1),
+        other => panic!("Expected function definition, got {:?}", other),
+    }
+    match &program.statements[1].kind {
+        StatementKind::If {
+            else_branch: Some(else_block),
+            ..
+        } => assert!(else_block.statements.is_empty()),
+        other => panic!("Expected if statement, got {:?}", other),
+    }
+
+    // An unclosed block takes the rest of the script with it
+    let (program, errors) = parse_recovering("$a = 1\nif ($a) {\n  2\n");
+    assert_eq!(program.statements.len(), 1);
+    assert!(matches!(
+        &errors[..],
+        [ParseError::UnexpectedToken {
+            found: pwsh_lexer::Token::Eof,
+            ..
+        }]
+    ));
+
+    // A stray closing brace is skipped
+    let (program, errors) = parse_recovering("}\n$a = 1");
+    assert_eq!((program.statements.len(), errors.len()), (1, 1));
+}
+
+// The values of a call statement's arguments, with named parameters as "-name"
+fn argument_texts(statement: &Statement) -> Vec<String> {
+    match expression_kind(statement) {
+        ExpressionKind::Call { arguments, .. } => arguments
+            .iter()
+            .map(|argument| match argument {
+                Argument::Positional(value) => match &value.kind {
+                    ExpressionKind::Literal(Literal::String(text)) => text.clone(),
+                    other => format!("{:?}", other),
+                },
+                Argument::Named { name, .. } => format!("-{}", name),
+                other => panic!("Expected a value argument, got {:?}", other),
+            })
+            .collect(),
+        other => panic!("Expected call expression, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_arguments_are_parsed_in_argument_mode() {
+    // Bare words are strings, whatever follows them
+    let program =
+        parse_str("Write-Output hello world\nWrite-Output notes.txt ./examples utf-8").unwrap();
+    assert_eq!(
+        argument_texts(&program.statements[0]),
+        vec!["hello", "world"]
+    );
+    assert_eq!(
+        argument_texts(&program.statements[1]),
+        vec!["notes.txt", "./examples", "utf-8"]
+    );
+
+    // A word after `+` is a word too: -OutVariable +acc adds to $acc
+    let program = parse_str("Write-Output 1 -OutVariable +acc").unwrap();
+    match expression_kind(&program.statements[0]) {
+        ExpressionKind::Call { arguments, .. } => assert!(matches!(
+            &arguments[1],
+            Argument::Named { name, value, .. } if name == "OutVariable"
+                && value.kind == ExpressionKind::Literal(Literal::String("+acc".to_string()))
+        )),
+        other => panic!("Expected call expression, got {:?}", other),
+    }
+
+    // Operators are not applied to a command: a spaced-out arithmetic
+    // operator is a word and a comparison operator names a parameter
+    let program = parse_str("Write-Output 1 + 2\nGet-Thing -eq 3").unwrap();
+    assert_eq!(program.statements.len(), 2);
+    let texts = argument_texts(&program.statements[0]);
+    assert_eq!(texts[1], "+");
+    assert_eq!(argument_texts(&program.statements[1]), vec!["-eq"]);
+
+    // Accessors written right after a value belong to it; after a space
+    // they start another argument
+    let program = parse_str("Write-Output $x.Name $y .Name (Get-Date).Year -5").unwrap();
+    match expression_kind(&program.statements[0]) {
+        ExpressionKind::Call { arguments, .. } => {
+            let kinds: Vec<_> = arguments
+                .iter()
+                .map(|argument| match argument {
+                    Argument::Positional(value) => &value.kind,
+                    other => panic!("Expected a positional argument, got {:?}", other),
+                })
+                .collect();
+            assert_eq!(kinds.len(), 5);
+            assert!(matches!(kinds[0], ExpressionKind::MemberAccess { .. }));
+            assert!(matches!(kinds[1], ExpressionKind::Variable(_)));
+            assert_eq!(
+                kinds[2],
+                &ExpressionKind::Literal(Literal::String(".Name".to_string()))
+            );
+            assert!(matches!(kinds[3], ExpressionKind::MemberAccess { .. }));
+            assert!(matches!(
+                kinds[4],
+                ExpressionKind::UnaryOp {
+                    operator: UnaryOperator::Negate,
+                    ..
+                }
+            ));
+        }
+        other => panic!("Expected call expression, got {:?}", other),
+    }
+
+    // Inside parentheses an expression is in expression mode again
+    let program = parse_str("Write-Output (1 + 2)").unwrap();
+    match expression_kind(&program.statements[0]) {
+        ExpressionKind::Call { arguments, .. } => assert!(matches!(
+            &arguments[..],
+            [Argument::Positional(Expression {
+                kind: ExpressionKind::BinaryOp { .. },
+                ..
+            })]
+        )),
+        other => panic!("Expected call expression, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_bare_words_are_never_calls() {
+    let program = parse_str(
+        "Select-Object -Property Name Id\nSort-Object -Property CPU -eq 1\nSelect-Object Name,\n  CPU -First 1",
+    )
+    .unwrap();
+    assert_eq!(program.statements.len(), 3);
+
+    let values = |statement: &Statement| match expression_kind(statement) {
+        ExpressionKind::Call { arguments, .. } => arguments
+            .iter()
+            .map(|argument| match argument {
+                Argument::Positional(value) | Argument::Named { value, .. } => value.kind.clone(),
+                other => panic!("Expected a value argument, got {:?}", other),
+            })
+            .collect::<Vec<_>>(),
+        other => panic!("Expected call expression, got {:?}", other),
+    };
+    let string = |text: &str| ExpressionKind::Literal(Literal::String(text.to_string()));
+
+    assert_eq!(
+        values(&program.statements[0]),
+        vec![string("Name"), string("Id")]
+    );
+    assert_eq!(
+        values(&program.statements[1]),
+        vec![string("CPU"), ExpressionKind::Literal(Literal::Number(1.0))]
+    );
+    match &values(&program.statements[2])[..] {
+        [ExpressionKind::Array(items), ExpressionKind::Literal(Literal::Number(_))] => {
+            assert_eq!(
+                items
+                    .iter()
+                    .map(|item| item.kind.clone())
+                    .collect::<Vec<_>>(),
+                vec![string("Name"), string("CPU")]
+            );
+        }
+        other => panic!("Expected a list and -First, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_named_parameter_values_are_full_arguments() {
+    let program = parse_str(
+        "Get-Item -Path ($dir + '/sub') -First ($n * 2)\nGet-Item -Path $item.FullName -Index $list[0] -Name (Get-Date).Year",
+    )
+    .unwrap();
+    assert_eq!(program.statements.len(), 2);
+
+    let named = |statement: &Statement| match expression_kind(statement) {
+        ExpressionKind::Call { arguments, .. } => arguments
+            .iter()
+            .map(|argument| match argument {
+                Argument::Named { name, value, .. } => (name.clone(), value.kind.clone()),
+                other => panic!("Expected a named argument, got {:?}", other),
+            })
+            .collect::<Vec<_>>(),
+        other => panic!("Expected call expression, got {:?}", other),
+    };
+
+    let first = named(&program.statements[0]);
+    assert_eq!(first.len(), 2);
+    for (_, value) in &first {
+        assert!(
+            matches!(value, ExpressionKind::BinaryOp { .. }),
+            "{:?}",
+            value
+        );
+    }
+
+    let second = named(&program.statements[1]);
+    let names: Vec<_> = second.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, vec!["Path", "Index", "Name"]);
+    assert!(matches!(second[0].1, ExpressionKind::MemberAccess { .. }));
+    assert!(matches!(second[1].1, ExpressionKind::Index { .. }));
+    assert!(matches!(second[2].1, ExpressionKind::MemberAccess { .. }));
+}
+
+#[test]
+fn test_parse_call_operator() {
+    let program =
+        parse_str("& 'C:/Program Files/tool.exe' arg -v\n$x | & $cmd\n$a = & { 1 }, 2").unwrap();
+    assert_eq!(program.statements.len(), 3);
+
+    match expression_kind(&program.statements[0]) {
+        ExpressionKind::Invoke { command, arguments } => {
+            assert_eq!(
+                command.kind,
+                ExpressionKind::Literal(Literal::String("C:/Program Files/tool.exe".to_string()))
+            );
+            assert_eq!(arguments.len(), 2);
+        }
+        other => panic!("Expected call operator, got {:?}", other),
+    }
+    match &program.statements[1].kind {
+        StatementKind::Pipeline(pipeline) => assert!(matches!(
+            &pipeline.stages[1].kind,
+            ExpressionKind::Invoke { command, arguments }
+                if matches!(command.kind, ExpressionKind::Variable(_)) && arguments.is_empty()
+        )),
+        other => panic!("Expected pipeline, got {:?}", other),
+    }
+    // A comma after the command ends it, as after a command name
+    match &program.statements[2].kind {
+        StatementKind::Assignment { value, .. } => assert!(matches!(
+            &value.kind,
+            ExpressionKind::Array(items) if matches!(items[0].kind, ExpressionKind::Invoke { .. })
+        )),
+        other => panic!("Expected assignment, got {:?}", other),
+    }
+
+    assert!(parse_str("&").is_err());
+    assert!(parse_str("$x | &").is_err());
+}
+
+/// The comment-based help of the function defined by each statement
+fn function_help(source: &str) -> Vec<Option<Box<CommentHelp>>> {
+    parse_str(source)
+        .unwrap()
+        .statements
+        .into_iter()
+        .filter_map(|statement| match statement.kind {
+            StatementKind::FunctionDef { help, .. } => Some(help),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_comment_based_help() {
+    let helps = function_help(
+        "<#\n.SYNOPSIS\n  Before.\n.PARAMETER Name\n  Who.\n#>\n\nfunction Before($Name) { $Name }\n\
+         function First {\n  # .SYNOPSIS\n  #   First in the body.\n  1\n}\n\
+         function Last {\n  1\n  <# .NOTES\n  Last in the body. #>\n}\n\
+         # .SYNOPSIS\n#   Two blank lines away.\n\n\nfunction TooFar { 1 }\n\
+         # an ordinary comment\nfunction Plain { 1 }",
+    );
+    let synopses: Vec<Option<&str>> = helps
+        .iter()
+        .map(|help| help.as_ref().and_then(|h| h.synopsis.as_deref()))
+        .collect();
+    assert_eq!(
+        synopses,
+        vec![
+            Some("Before."),
+            Some("First in the body."),
+            None,
+            None,
+            None
+        ]
+    );
+    assert_eq!(
+        helps[0]
+            .as_ref()
+            .unwrap()
+            .parameter("name")
+            .unwrap()
+            .description,
+        "Who."
+    );
+    assert_eq!(
+        helps[2].as_ref().unwrap().notes.as_deref(),
+        Some("Last in the body.")
+    );
+    assert!(helps[3].is_none() && helps[4].is_none());
+}
+
+#[test]
+fn test_requires_statements() {
+    let program = parse_str(
+        "#Requires -Version 7.2\n#Requires -Modules Azure, @{ModuleName='Tools'}\n$x = 1 #Requires -Version 9\n",
+    )
+    .unwrap();
+    // A #Requires after code on its line is just a comment
+    assert_eq!(program.requires.version, Some(vec![7, 2]));
+    assert_eq!(program.requires.modules, vec!["Azure", "Tools"]);
+    assert!(parse_str("$x = 1").unwrap().requires.is_empty());
+
+    match parse_str("$x = 1\n#Requires -Version x") {
+        Err(ParseError::InvalidStatement { position, .. }) => assert_eq!(position.line, 2),
+        other => panic!("Expected an invalid #Requires, got {:?}", other),
+    }
+}
@@ -0,0 +1,153 @@
+/// ANSI styling in output: whether the console's output keeps it, as `$OutputRendering` says
+use crate::error::{ErrorCategory, ErrorRecord, RuntimeError};
+use crate::value::Value;
+use std::ffi::OsString;
+
+/// Set to anything but an empty string, this environment variable turns
+/// styling off, as https://no-color.org asks
+pub const NO_COLOR_VARIABLE: &str = "NO_COLOR";
+
+/// What happens to ANSI escape sequences in the console's output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputRendering {
+    /// Keep them when the host shows them as styling, as a terminal does,
+    /// and take them out when output goes to a file or a pipe
+    #[default]
+    Host,
+    /// Always keep them
+    Ansi,
+    /// Always take them out
+    PlainText,
+}
+
+impl OutputRendering {
+    /// Parse a rendering name, ignoring case: `$OutputRendering = 'PlainText'`
+    pub fn from_value(value: &Value) -> Result<Self, RuntimeError> {
+        let name = value.to_string();
+        match name.to_ascii_lowercase().as_str() {
+            "host" => Ok(OutputRendering::Host),
+            "ansi" => Ok(OutputRendering::Ansi),
+            "plaintext" => Ok(OutputRendering::PlainText),
+            _ => Err(ErrorRecord::new(
+                ErrorCategory::InvalidArgument,
+                "InvalidOutputRendering",
+                format!(
+                    "Cannot convert '{}' to an output rendering. Use one of: Host, Ansi, PlainText",
+                    name
+                ),
+            )
+            .into()),
+        }
+    }
+
+    /// The rendering a session starts with: PlainText when `$env:NO_COLOR`
+    /// is set, otherwise Host
+    pub fn from_environment() -> Self {
+        Self::for_no_color(std::env::var_os(NO_COLOR_VARIABLE))
+    }
+
+    fn for_no_color(no_color: Option<OsString>) -> Self {
+        match no_color {
+            Some(value) if !value.is_empty() => OutputRendering::PlainText,
+            _ => OutputRendering::Host,
+        }
+    }
+
+    /// The rendering's name, as `$PSStyle.OutputRendering` shows it
+    pub fn name(self) -> &'static str {
+        match self {
+            OutputRendering::Host => "Host",
+            OutputRendering::Ansi => "Ansi",
+            OutputRendering::PlainText => "PlainText",
+        }
+    }
+
+    /// Whether output keeps its styling on a host that does, or doesn't,
+    /// show ANSI escape sequences as styling
+    pub fn keeps_ansi(self, host_supports_ansi: bool) -> bool {
+        match self {
+            OutputRendering::Host => host_supports_ansi,
+            OutputRendering::Ansi => true,
+            OutputRendering::PlainText => false,
+        }
+    }
+}
+
+/// `text` without its ANSI escape sequences: the `ESC [ ... m` styling and
+/// other control sequences, and `ESC ] ... BEL` operating system commands
+/// such as hyperlinks
+pub fn strip_ansi(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            plain.push(c);
+            continue;
+        }
+        match chars.next() {
+            // Control sequence: parameters, then a final letter
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if ('\x40'..='\x7e').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            // Operating system command: up to BEL or ESC \
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\x07' {
+                        break;
+                    }
+                    if c == '\x1b' && chars.peek() == Some(&'\\') {
+                        chars.next();
+                        break;
+                    }
+                }
+            }
+            // Other escapes are two characters long
+            _ => {}
+        }
+    }
+    plain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_ansi_keeps_the_text() {
+        assert_eq!(strip_ansi("\x1b[1;34msrc\x1b[0m"), "src");
+        assert_eq!(strip_ansi("a\x1b[38;2;255;0;0mb\x1b[m c"), "ab c");
+        assert_eq!(
+            strip_ansi("\x1b]8;;https://example.com\x07link\x1b]8;;\x1b\\"),
+            "link"
+        );
+        assert_eq!(strip_ansi("plain — text"), "plain — text");
+    }
+
+    #[test]
+    fn test_output_rendering() {
+        let parse = |name: &str| OutputRendering::from_value(&Value::String(name.into()));
+        assert_eq!(parse("plaintext").unwrap(), OutputRendering::PlainText);
+        assert_eq!(parse("ANSI").unwrap(), OutputRendering::Ansi);
+        assert_eq!(
+            parse("Color").unwrap_err().error_id(),
+            "InvalidOutputRendering"
+        );
+        assert!(OutputRendering::Host.keeps_ansi(true));
+        assert!(!OutputRendering::Host.keeps_ansi(false));
+        assert!(OutputRendering::Ansi.keeps_ansi(false));
+        assert!(!OutputRendering::PlainText.keeps_ansi(true));
+    }
+
+    #[test]
+    fn test_no_color_starts_plain_text() {
+        let start = |value: Option<&str>| OutputRendering::for_no_color(value.map(OsString::from));
+        assert_eq!(start(None), OutputRendering::Host);
+        assert_eq!(start(Some("")), OutputRendering::Host);
+        assert_eq!(start(Some("1")), OutputRendering::PlainText);
+        assert_eq!(OutputRendering::PlainText.name(), "PlainText");
+    }
+}
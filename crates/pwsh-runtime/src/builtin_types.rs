@@ -0,0 +1,454 @@
+use crate::clock::Clock;
+/// Static members of built-in .NET types: [math]::Round(3.7), [datetime]::Now,
+/// [string]::IsNullOrEmpty($s), [guid]::NewGuid(), [environment]::MachineName
+use crate::culture::Culture;
+use crate::error::RuntimeError;
+use crate::format;
+use crate::value::Value;
+use chrono::NaiveDate;
+
+/// A built-in type with static members
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BuiltinType {
+    Math,
+    DateTime,
+    String,
+    Guid,
+    Environment,
+}
+
+impl BuiltinType {
+    /// Resolve a type name, with or without the System. namespace (case-insensitive)
+    pub fn from_name(name: &str) -> Option<Self> {
+        let lower = name.to_lowercase();
+        match lower.strip_prefix("system.").unwrap_or(&lower) {
+            "math" => Some(BuiltinType::Math),
+            "datetime" => Some(BuiltinType::DateTime),
+            "string" => Some(BuiltinType::String),
+            "guid" => Some(BuiltinType::Guid),
+            "environment" => Some(BuiltinType::Environment),
+            _ => None,
+        }
+    }
+
+    /// Full .NET name of the type
+    pub fn full_name(&self) -> &'static str {
+        match self {
+            BuiltinType::Math => "System.Math",
+            BuiltinType::DateTime => "System.DateTime",
+            BuiltinType::String => "System.String",
+            BuiltinType::Guid => "System.Guid",
+            BuiltinType::Environment => "System.Environment",
+        }
+    }
+
+    /// Get a static property: [math]::PI, [datetime]::Now (read from `clock`)
+    pub fn property(&self, member: &str, clock: &dyn Clock) -> Result<Value, RuntimeError> {
+        let value = match (self, member.to_lowercase().as_str()) {
+            (BuiltinType::Math, "pi") => Value::Number(std::f64::consts::PI),
+            (BuiltinType::Math, "e") => Value::Number(std::f64::consts::E),
+            (BuiltinType::DateTime, "now") => Value::DateTime(clock.now()),
+            (BuiltinType::DateTime, "utcnow") => Value::DateTime(clock.utc_now()),
+            (BuiltinType::DateTime, "today") => {
+                Value::DateTime(clock.now().date().and_time(Default::default()))
+            }
+            (BuiltinType::String, "empty") => Value::String("".into()),
+            (BuiltinType::Guid, "empty") => Value::String(uuid::Uuid::nil().to_string().into()),
+            (BuiltinType::Environment, "machinename") => Value::String(machine_name().into()),
+            (BuiltinType::Environment, "username") => Value::String(
+                std::env::var("USERNAME")
+                    .or_else(|_| std::env::var("USER"))
+                    .unwrap_or_default()
+                    .into(),
+            ),
+            (BuiltinType::Environment, "newline") => {
+                Value::String(if cfg!(windows) { "\r\n" } else { "\n" }.into())
+            }
+            (BuiltinType::Environment, "currentdirectory") => Value::String(
+                std::env::current_dir()
+                    .map(|dir| dir.display().to_string())
+                    .unwrap_or_default()
+                    .into(),
+            ),
+            (BuiltinType::Environment, "processorcount") => {
+                Value::Number(std::thread::available_parallelism().map_or(1, |n| n.get()) as f64)
+            }
+            _ => {
+                return Err(RuntimeError::InvalidPropertyAccess(format!(
+                    "Property '{}' not found on type [{}]",
+                    member,
+                    self.full_name()
+                )))
+            }
+        };
+        Ok(value)
+    }
+
+    /// Call a static method: [math]::Round(3.7), [string]::Join(',', $items).
+    /// [string]::Format formats numbers and dates for `culture`.
+    pub fn call(
+        &self,
+        method: &str,
+        args: &[Value],
+        culture: &Culture,
+    ) -> Result<Value, RuntimeError> {
+        let name = method.to_lowercase();
+
+        match (self, name.as_str(), args) {
+            // [math]
+            (BuiltinType::Math, "round", [x]) => Ok(Value::Number(number(x)?.round_ties_even())),
+            (BuiltinType::Math, "round", [x, digits]) => {
+                let factor = 10f64.powi(number(digits)? as i32);
+                Ok(Value::Number(
+                    (number(x)? * factor).round_ties_even() / factor,
+                ))
+            }
+            (BuiltinType::Math, "log", [x, base]) => {
+                Ok(Value::Number(number(x)?.log(number(base)?)))
+            }
+            (BuiltinType::Math, _, [x]) => {
+                let x = number(x)?;
+                let result = match name.as_str() {
+                    "abs" => x.abs(),
+                    "floor" => x.floor(),
+                    "ceiling" => x.ceil(),
+                    "truncate" => x.trunc(),
+                    "sqrt" => x.sqrt(),
+                    "sign" => {
+                        if x == 0.0 {
+                            0.0
+                        } else {
+                            x.signum()
+                        }
+                    }
+                    "exp" => x.exp(),
+                    "log" => x.ln(),
+                    "log10" => x.log10(),
+                    "sin" => x.sin(),
+                    "cos" => x.cos(),
+                    "tan" => x.tan(),
+                    _ => return Err(self.missing_method(method, args)),
+                };
+                Ok(Value::Number(result))
+            }
+            (BuiltinType::Math, _, [a, b]) => {
+                let (a, b) = (number(a)?, number(b)?);
+                let result = match name.as_str() {
+                    "pow" => a.powf(b),
+                    "max" => a.max(b),
+                    "min" => a.min(b),
+                    "atan2" => a.atan2(b),
+                    _ => return Err(self.missing_method(method, args)),
+                };
+                Ok(Value::Number(result))
+            }
+
+            // [datetime]
+            (BuiltinType::DateTime, "parse", [s]) => {
+                s.to_datetime().map(Value::DateTime).ok_or_else(|| {
+                    RuntimeError::InvalidOperation(format!(
+                        "String '{}' was not recognized as a valid DateTime",
+                        s
+                    ))
+                })
+            }
+            (BuiltinType::DateTime, "isleapyear", [year]) => {
+                let year = number(year)? as i32;
+                Ok(Value::Boolean(
+                    NaiveDate::from_ymd_opt(year, 2, 29).is_some(),
+                ))
+            }
+            (BuiltinType::DateTime, "daysinmonth", [year, month]) => {
+                let (year, month) = (number(year)? as i32, number(month)? as u32);
+                let first = NaiveDate::from_ymd_opt(year, month, 1).ok_or_else(|| {
+                    RuntimeError::InvalidOperation(format!("Month '{}' is out of range", month))
+                })?;
+                let next = if month == 12 {
+                    NaiveDate::from_ymd_opt(year + 1, 1, 1)
+                } else {
+                    NaiveDate::from_ymd_opt(year, month + 1, 1)
+                };
+                let days = next.map_or(31, |next| (next - first).num_days());
+                Ok(Value::Number(days as f64))
+            }
+
+            // [string]
+            (BuiltinType::String, "isnullorempty", [s]) => Ok(Value::Boolean(match s {
+                Value::Null => true,
+                Value::String(s) => s.is_empty(),
+                _ => false,
+            })),
+            (BuiltinType::String, "isnullorwhitespace", [s]) => Ok(Value::Boolean(match s {
+                Value::Null => true,
+                Value::String(s) => s.trim().is_empty(),
+                _ => false,
+            })),
+            (BuiltinType::String, "join", [separator, values @ ..]) if !values.is_empty() => {
+                let parts: Vec<String> = flatten(values).iter().map(|v| v.to_string()).collect();
+                Ok(Value::String(parts.join(&separator.to_string()).into()))
+            }
+            (BuiltinType::String, "concat", values) if !values.is_empty() => Ok(Value::String(
+                flatten(values)
+                    .iter()
+                    .map(|v| v.to_string())
+                    .collect::<String>()
+                    .into(),
+            )),
+            (BuiltinType::String, "format", [format_string, values @ ..]) => Ok(Value::String(
+                format::format_composite(&format_string.to_string(), &flatten(values), culture)?
+                    .into(),
+            )),
+
+            // [guid]
+            (BuiltinType::Guid, "newguid", []) => {
+                Ok(Value::String(uuid::Uuid::new_v4().to_string().into()))
+            }
+            (BuiltinType::Guid, "parse", [s]) => uuid::Uuid::parse_str(&s.to_string())
+                .map(|guid| Value::String(guid.to_string().into()))
+                .map_err(|_| {
+                    RuntimeError::InvalidOperation(format!("Unrecognized Guid format: '{}'", s))
+                }),
+
+            // [environment]
+            (BuiltinType::Environment, "getenvironmentvariable", [variable]) => {
+                Ok(std::env::var(variable.to_string())
+                    .map_or(Value::Null, |s| Value::String(s.into())))
+            }
+
+            _ => Err(self.missing_method(method, args)),
+        }
+    }
+
+    /// Whether the type has a static method with this (lowercase) name
+    fn has_method(&self, name: &str) -> bool {
+        let methods: &[&str] = match self {
+            BuiltinType::Math => &[
+                "abs", "floor", "ceiling", "truncate", "sqrt", "sign", "exp", "log", "log10",
+                "sin", "cos", "tan", "pow", "max", "min", "atan2", "round",
+            ],
+            BuiltinType::DateTime => &["parse", "isleapyear", "daysinmonth"],
+            BuiltinType::String => &[
+                "isnullorempty",
+                "isnullorwhitespace",
+                "join",
+                "concat",
+                "format",
+            ],
+            BuiltinType::Guid => &["newguid", "parse"],
+            BuiltinType::Environment => &["getenvironmentvariable"],
+        };
+        methods.contains(&name)
+    }
+
+    fn missing_method(&self, method: &str, args: &[Value]) -> RuntimeError {
+        if self.has_method(&method.to_lowercase()) {
+            return RuntimeError::InvalidOperation(format!(
+                "Cannot find an overload for \"{}\" and the argument count: \"{}\"",
+                method,
+                args.len()
+            ));
+        }
+        RuntimeError::InvalidOperation(format!(
+            "Method invocation failed because [{}] does not contain a method named '{}'",
+            self.full_name(),
+            method
+        ))
+    }
+}
+
+/// Convert a method argument to a number
+fn number(value: &Value) -> Result<f64, RuntimeError> {
+    value.to_number().ok_or_else(|| RuntimeError::TypeMismatch {
+        expected: "number".to_string(),
+        got: format!("{:?}", value),
+        operation: "static method call".to_string(),
+    })
+}
+
+/// Expand array arguments into their items: [string]::Join(',', @(1, 2))
+fn flatten(values: &[Value]) -> Vec<Value> {
+    values
+        .iter()
+        .flat_map(|v| match v {
+            Value::Array(items) => items.read().to_vec(),
+            other => vec![other.clone()],
+        })
+        .collect()
+}
+
+/// Host name of this machine
+fn machine_name() -> String {
+    std::env::var("COMPUTERNAME")
+        .or_else(|_| std::env::var("HOSTNAME"))
+        .ok()
+        .or_else(|| {
+            std::fs::read_to_string("/etc/hostname")
+                .ok()
+                .map(|name| name.trim().to_string())
+        })
+        .filter(|name| !name.is_empty())
+        .unwrap_or_else(|| "localhost".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::{FixedClock, SystemClock};
+
+    fn call(type_name: &str, method: &str, args: Vec<Value>) -> Result<Value, RuntimeError> {
+        BuiltinType::from_name(type_name)
+            .unwrap()
+            .call(method, &args, &Culture::default())
+    }
+
+    #[test]
+    fn test_type_names_resolve_with_or_without_namespace() {
+        assert_eq!(BuiltinType::from_name("Math"), Some(BuiltinType::Math));
+        assert_eq!(
+            BuiltinType::from_name("System.DateTime"),
+            Some(BuiltinType::DateTime)
+        );
+        assert_eq!(BuiltinType::from_name("Person"), None);
+    }
+
+    #[test]
+    fn test_math_round_uses_bankers_rounding() {
+        let round = |n: f64| call("math", "Round", vec![Value::Number(n)]).unwrap();
+        assert_eq!(round(3.7), Value::Number(4.0));
+        assert_eq!(round(2.5), Value::Number(2.0));
+        assert_eq!(round(3.5), Value::Number(4.0));
+        assert_eq!(
+            call(
+                "math",
+                "round",
+                vec![Value::Number(1.2345), Value::Number(2.0)]
+            ),
+            Ok(Value::Number(1.23))
+        );
+    }
+
+    #[test]
+    fn test_math_functions() {
+        assert_eq!(
+            call("math", "Pow", vec![Value::Number(2.0), Value::Number(10.0)]),
+            Ok(Value::Number(1024.0))
+        );
+        assert_eq!(
+            call("math", "Max", vec![Value::Number(2.0), Value::Number(7.0)]),
+            Ok(Value::Number(7.0))
+        );
+        assert_eq!(
+            call("math", "Floor", vec![Value::Number(-1.5)]),
+            Ok(Value::Number(-2.0))
+        );
+        assert_eq!(
+            BuiltinType::Math.property("PI", &SystemClock),
+            Ok(Value::Number(std::f64::consts::PI))
+        );
+    }
+
+    #[test]
+    fn test_string_methods() {
+        assert_eq!(
+            call("string", "IsNullOrEmpty", vec![Value::String("".into())]),
+            Ok(Value::Boolean(true))
+        );
+        assert_eq!(
+            call(
+                "string",
+                "IsNullOrWhiteSpace",
+                vec![Value::String("x".into())]
+            ),
+            Ok(Value::Boolean(false))
+        );
+        assert_eq!(
+            call(
+                "string",
+                "Join",
+                vec![
+                    Value::String(", ".into()),
+                    Value::Array(vec![Value::Number(1.0), Value::Number(2.0)].into())
+                ]
+            ),
+            Ok(Value::String("1, 2".into()))
+        );
+        assert_eq!(
+            call(
+                "string",
+                "Format",
+                vec![
+                    Value::String("{0}-{1}".into()),
+                    Value::Number(1.0),
+                    Value::Number(2.0)
+                ]
+            ),
+            Ok(Value::String("1-2".into()))
+        );
+    }
+
+    #[test]
+    fn test_datetime_methods() {
+        assert_eq!(
+            call("datetime", "IsLeapYear", vec![Value::Number(2024.0)]),
+            Ok(Value::Boolean(true))
+        );
+        assert_eq!(
+            call(
+                "datetime",
+                "DaysInMonth",
+                vec![Value::Number(2023.0), Value::Number(2.0)]
+            ),
+            Ok(Value::Number(28.0))
+        );
+        assert_eq!(
+            call(
+                "datetime",
+                "DaysInMonth",
+                vec![Value::Number(2023.0), Value::Number(12.0)]
+            ),
+            Ok(Value::Number(31.0))
+        );
+        assert!(matches!(
+            BuiltinType::DateTime.property("Now", &SystemClock),
+            Ok(Value::DateTime(_))
+        ));
+
+        let now = NaiveDate::from_ymd_opt(2024, 3, 5)
+            .unwrap()
+            .and_hms_opt(14, 7, 9)
+            .unwrap();
+        let clock = FixedClock::new(now);
+        assert_eq!(
+            BuiltinType::DateTime.property("Now", &clock),
+            Ok(Value::DateTime(now))
+        );
+        assert_eq!(
+            BuiltinType::DateTime.property("today", &clock),
+            Ok(Value::DateTime(now.date().and_hms_opt(0, 0, 0).unwrap()))
+        );
+    }
+
+    #[test]
+    fn test_guid_new_guid_is_unique() {
+        let a = call("guid", "NewGuid", vec![]).unwrap().to_string();
+        let b = call("guid", "NewGuid", vec![]).unwrap().to_string();
+        assert_eq!(a.len(), 36);
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_unknown_members() {
+        assert!(matches!(
+            call("math", "Frobnicate", vec![]),
+            Err(RuntimeError::InvalidOperation(msg))
+                if msg.contains("[System.Math] does not contain a method named 'Frobnicate'")
+        ));
+        assert!(matches!(
+            call("math", "Round", vec![]),
+            Err(RuntimeError::InvalidOperation(msg)) if msg.contains("argument count: \"0\"")
+        ));
+        assert!(BuiltinType::Environment
+            .property("Nope", &SystemClock)
+            .is_err());
+    }
+}
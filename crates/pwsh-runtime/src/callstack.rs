@@ -0,0 +1,231 @@
+/// The call stack: the scripts, functions and script blocks running, and where each is
+use crate::display::DisplayLimits;
+use crate::property_map::PropertyMap;
+use crate::value::Value;
+use pwsh_parser::Span;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// The type name of the objects Get-PSCallStack writes
+pub const CALL_STACK_FRAME_TYPE: &str = "System.Management.Automation.CallStackFrame";
+
+/// The name a frame without a function has, as in PowerShell: a script,
+/// a script module being loaded or a script block
+pub const SCRIPT_BLOCK_COMMAND: &str = "<ScriptBlock>";
+
+/// The text of a script, and the file it was read from when it was
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ScriptSource {
+    pub path: Option<PathBuf>,
+    pub text: String,
+}
+
+impl ScriptSource {
+    pub fn new(path: Option<&Path>, text: impl Into<String>) -> Self {
+        ScriptSource {
+            path: path.map(Path::to_path_buf),
+            text: text.into(),
+        }
+    }
+
+    /// Line `line` of the text, counting from 1
+    pub fn line(&self, line: usize) -> &str {
+        self.text
+            .lines()
+            .nth(line.saturating_sub(1))
+            .unwrap_or_default()
+    }
+
+    /// The text at `span`, up to the end of its first line
+    pub fn text_at(&self, span: Span) -> String {
+        let length = if span.end.line == span.start.line {
+            span.end.column.saturating_sub(span.start.column)
+        } else {
+            usize::MAX
+        };
+        self.line(span.start.line)
+            .chars()
+            .skip(span.start.column.saturating_sub(1))
+            .take(length)
+            .collect()
+    }
+
+    /// Point at `span` in the script, as PowerShell does:
+    /// ```text
+    /// At /scripts/deploy.ps1:12 char:5
+    /// +     $total = $count / 0
+    /// +              ~~~~~~~~~~
+    /// ```
+    /// A script without a file says `At line:12 char:5`. Spans over
+    /// several lines are underlined to the end of the first.
+    pub fn position_message(&self, span: Span) -> String {
+        let start = span.start;
+        let line = self.line(start.line);
+        let line_length = line.chars().count();
+        let end = if span.end.line == start.line {
+            span.end.column
+        } else {
+            line_length + 1
+        };
+        let indent = start.column.saturating_sub(1).min(line_length);
+        let width = end.saturating_sub(start.column).max(1);
+        let at = match &self.path {
+            Some(path) => format!("{}:{}", path.display(), start.line),
+            None => format!("line:{}", start.line),
+        };
+        format!(
+            "At {} char:{}\n+ {}\n+ {}{}",
+            at,
+            start.column,
+            line,
+            " ".repeat(indent),
+            "~".repeat(width)
+        )
+    }
+}
+
+/// One running call: the command, the script its code came from and
+/// where in that script it is now
+#[derive(Debug, Clone, PartialEq)]
+pub struct CallFrame {
+    /// The function or method's name, or `<ScriptBlock>`
+    pub command: String,
+    /// The parameters the call was given values for, in order
+    pub arguments: Vec<(String, Value)>,
+    /// The script the code came from, when it is known
+    pub source: Option<Arc<ScriptSource>>,
+    /// The statement or command running in this frame; for every frame but
+    /// the innermost, the call to the next one. The default span until the
+    /// first one starts.
+    pub position: Span,
+}
+
+impl CallFrame {
+    pub fn new(command: impl Into<String>, source: Option<Arc<ScriptSource>>) -> Self {
+        CallFrame {
+            command: command.into(),
+            arguments: Vec::new(),
+            source,
+            position: Span::default(),
+        }
+    }
+
+    /// The file the frame's script came from, if any
+    pub fn script_name(&self) -> Option<&Path> {
+        self.source.as_ref()?.path.as_deref()
+    }
+
+    /// Where the frame is, as Get-PSCallStack shows it: the script's file
+    /// name and the line, `deploy.ps1: line 12`
+    pub fn location(&self) -> String {
+        let script = self.script_name().and_then(Path::file_name).map_or_else(
+            || "<No file>".to_string(),
+            |name| name.to_string_lossy().into_owned(),
+        );
+        format!("{}: line {}", script, self.position.start.line)
+    }
+
+    /// The frame as Get-PSCallStack writes it: its Command, Arguments
+    /// (`{Name=value, ...}`), Location, ScriptName and ScriptLineNumber
+    pub fn to_value(&self) -> Value {
+        let limits = DisplayLimits::default();
+        let arguments: Vec<String> = self
+            .arguments
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, limits.inline(value)))
+            .collect();
+        let mut frame = PropertyMap::from([
+            ("Command", Value::String(self.command.as_str().into())),
+            (
+                "Arguments",
+                Value::String(format!("{{{}}}", arguments.join(", ")).into()),
+            ),
+            ("Location", Value::String(self.location().into())),
+            (
+                "ScriptName",
+                self.script_name().map_or(Value::Null, |path| {
+                    Value::String(path.display().to_string().into())
+                }),
+            ),
+            (
+                "ScriptLineNumber",
+                Value::Number(self.position.start.line as f64),
+            ),
+        ]);
+        frame.insert_type_name(CALL_STACK_FRAME_TYPE);
+        Value::Object(frame.into())
+    }
+
+    /// The frame's line in a stack trace, as PowerShell writes
+    /// ScriptStackTrace: `at Get-Total, /scripts/deploy.ps1: line 12`
+    pub fn trace_line(&self) -> String {
+        let script = match self.script_name() {
+            Some(path) => path.display().to_string(),
+            None => "<No file>".to_string(),
+        };
+        format!(
+            "at {}, {}: line {}",
+            self.command, script, self.position.start.line
+        )
+    }
+}
+
+/// A stack trace of `frames`, innermost first, one line per frame
+pub fn stack_trace(frames: &[CallFrame]) -> String {
+    frames
+        .iter()
+        .rev()
+        .map(CallFrame::trace_line)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pwsh_lexer::Position;
+
+    fn span(line: usize, start: usize, end: usize) -> Span {
+        Span::new(Position::new(line, start), Position::new(line, end))
+    }
+
+    #[test]
+    fn test_position_message_names_the_file_and_underlines_the_span() {
+        let source = ScriptSource::new(None, "$a = 1\n$b = $a / 0 + 2");
+        assert_eq!(
+            source.position_message(span(2, 6, 12)),
+            "At line:2 char:6\n+ $b = $a / 0 + 2\n+      ~~~~~~"
+        );
+
+        let source = ScriptSource::new(Some(Path::new("/tmp/deploy.ps1")), "throw 'x'");
+        assert_eq!(
+            source.position_message(span(1, 1, 10)),
+            "At /tmp/deploy.ps1:1 char:1\n+ throw 'x'\n+ ~~~~~~~~~"
+        );
+    }
+
+    #[test]
+    fn test_text_at_stops_at_the_end_of_the_first_line() {
+        let source = ScriptSource::new(None, "$a = 1; $b = 2\nif ($a) {\n  $b\n}");
+        assert_eq!(source.text_at(span(1, 9, 15)), "$b = 2");
+        let span = Span::new(Position::new(2, 1), Position::new(4, 2));
+        assert_eq!(source.text_at(span), "if ($a) {");
+    }
+
+    #[test]
+    fn test_stack_trace_lists_the_innermost_frame_first() {
+        let script = Arc::new(ScriptSource::new(Some(Path::new("/tmp/s.ps1")), ""));
+        let mut outer = CallFrame::new(SCRIPT_BLOCK_COMMAND, Some(script.clone()));
+        outer.position = span(9, 1, 6);
+        let mut inner = CallFrame::new("Get-Total", Some(script));
+        inner.position = span(3, 5, 10);
+        assert_eq!(
+            stack_trace(&[outer, inner]),
+            "at Get-Total, /tmp/s.ps1: line 3\nat <ScriptBlock>, /tmp/s.ps1: line 9"
+        );
+        assert_eq!(
+            CallFrame::new("Get-Total", None).trace_line(),
+            "at Get-Total, <No file>: line 0"
+        );
+    }
+}
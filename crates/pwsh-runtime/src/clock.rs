@@ -0,0 +1,109 @@
+/// Clocks: where a session gets the current date and time from
+use chrono::{Duration, Local, NaiveDateTime, Utc};
+use std::sync::{Arc, Mutex};
+
+/// The source of the current time for Get-Date and `[datetime]::Now`.
+/// Sessions use the system clock; tests and embedders can swap in a
+/// `FixedClock` to freeze or advance time.
+pub trait Clock {
+    /// The local date and time
+    fn now(&self) -> NaiveDateTime;
+
+    /// The date and time in UTC
+    fn utc_now(&self) -> NaiveDateTime;
+}
+
+/// The computer's clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> NaiveDateTime {
+        Local::now().naive_local()
+    }
+
+    fn utc_now(&self) -> NaiveDateTime {
+        Utc::now().naive_utc()
+    }
+}
+
+/// A clock that stands still until it is set or advanced. Clones share the
+/// time, so a test can keep one to move the clock of the session it built:
+///
+/// ```
+/// use pwsh_runtime::{Clock, FixedClock};
+///
+/// let start = chrono::NaiveDate::from_ymd_opt(2024, 3, 5)
+///     .unwrap()
+///     .and_hms_opt(9, 0, 0)
+///     .unwrap();
+/// let clock = FixedClock::new(start);
+/// let session_clock = clock.clone();
+/// clock.advance(chrono::Duration::minutes(90));
+/// assert_eq!(session_clock.now(), start + chrono::Duration::minutes(90));
+/// ```
+#[derive(Debug, Clone)]
+pub struct FixedClock {
+    now: Arc<Mutex<NaiveDateTime>>,
+}
+
+impl FixedClock {
+    /// A clock showing `now`, in local time, in a zone with no UTC offset
+    pub fn new(now: NaiveDateTime) -> Self {
+        FixedClock {
+            now: Arc::new(Mutex::new(now)),
+        }
+    }
+
+    /// Move the clock to `now`
+    pub fn set(&self, now: NaiveDateTime) {
+        *self.lock() = now;
+    }
+
+    /// Move the clock forward (or back, for a negative duration)
+    pub fn advance(&self, by: Duration) {
+        let mut now = self.lock();
+        *now += by;
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, NaiveDateTime> {
+        // The time is a plain value, so a panic while it was locked can't have
+        // left it half-written
+        self.now
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> NaiveDateTime {
+        *self.lock()
+    }
+
+    fn utc_now(&self) -> NaiveDateTime {
+        *self.lock()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fixed_clock_is_shared_between_clones() {
+        let start = chrono::NaiveDate::from_ymd_opt(2024, 3, 5)
+            .unwrap()
+            .and_hms_opt(23, 30, 0)
+            .unwrap();
+        let clock = FixedClock::new(start);
+        let copy = clock.clone();
+        assert_eq!(copy.now(), start);
+
+        clock.advance(Duration::hours(1));
+        assert_eq!(copy.now().to_string(), "2024-03-06 00:30:00");
+        assert_eq!(copy.utc_now(), copy.now());
+
+        clock.set(start);
+        assert_eq!(copy.now(), start);
+    }
+}
@@ -1,16 +1,27 @@
-use crate::error::RuntimeError;
+use crate::error::{ActionPreference, ErrorRecord, RuntimeError};
+use crate::limits::Capability;
 /// Cmdlet trait and execution infrastructure
+use crate::property_map::PropertyMap;
 use crate::value::Value;
+use crate::wildcard::WildcardPattern;
 use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Parameters the evaluator handles for every cmdlet, which cmdlets never see
+pub const COMMON_PARAMETERS: &[&str] = &["ErrorAction", "OutVariable", "ErrorVariable"];
 
 /// Context provided to cmdlets during execution
 pub struct CmdletContext {
     /// Input from pipeline (if any)
     pub pipeline_input: Vec<Value>,
-    /// Named parameters passed to the cmdlet
-    pub parameters: HashMap<String, Value>,
+    /// Named parameters passed to the cmdlet, matched case-insensitively
+    pub parameters: PropertyMap,
     /// Positional arguments passed to the cmdlet
     pub arguments: Vec<Value>,
+    /// What to do with non-terminating errors: -ErrorAction if the cmdlet
+    /// was called with it, otherwise $ErrorActionPreference as it was when
+    /// the cmdlet was invoked
+    pub error_action: ActionPreference,
 }
 
 impl Default for CmdletContext {
@@ -24,8 +35,9 @@ impl CmdletContext {
     pub fn new() -> Self {
         Self {
             pipeline_input: Vec::new(),
-            parameters: HashMap::new(),
+            parameters: PropertyMap::new(),
             arguments: Vec::new(),
+            error_action: ActionPreference::default(),
         }
     }
 
@@ -33,8 +45,7 @@ impl CmdletContext {
     pub fn with_input(input: Vec<Value>) -> Self {
         Self {
             pipeline_input: input,
-            parameters: HashMap::new(),
-            arguments: Vec::new(),
+            ..Self::new()
         }
     }
 
@@ -50,22 +61,114 @@ impl CmdletContext {
         self
     }
 
-    /// Get a named parameter
+    /// Get a named parameter, ignoring the case of its name
     pub fn get_parameter(&self, name: &str) -> Option<&Value> {
         self.parameters.get(name)
     }
 
+    /// Set how non-terminating errors are handled
+    pub fn with_error_action(mut self, action: ActionPreference) -> Self {
+        self.error_action = action;
+        self
+    }
+
     /// Get a positional argument by index
     pub fn get_argument(&self, index: usize) -> Option<&Value> {
         self.arguments.get(index)
     }
 }
 
+/// Whether a switch parameter such as `-Recurse` is on: off when it isn't
+/// given. `command` names the cmdlet in the error for a value that isn't a
+/// boolean.
+pub fn parse_switch(value: Option<&Value>, command: &str) -> Result<bool, RuntimeError> {
+    let invalid = |value: &dyn std::fmt::Display| {
+        ErrorRecord::invalid_argument(
+            &format!("InvalidBoolean,{}", command),
+            format!("Invalid boolean value: {}", value),
+        )
+        .into()
+    };
+    match value {
+        None | Some(Value::Null) => Ok(false),
+        Some(Value::Boolean(b)) => Ok(*b),
+        Some(Value::Number(n)) => Ok(*n != 0.0),
+        Some(Value::String(s)) => match s.trim().to_ascii_lowercase().as_str() {
+            "true" | "t" | "1" | "yes" | "y" => Ok(true),
+            "false" | "f" | "0" | "no" | "n" => Ok(false),
+            _ => Err(invalid(s)),
+        },
+        Some(other) => Err(invalid(other)),
+    }
+}
+
+/// The names given to a parameter such as `-Property`: one name, or the
+/// strings in a list of them
+pub fn parse_property_list(value: Option<&Value>) -> Vec<String> {
+    match value {
+        None => vec![],
+        Some(Value::String(s)) => vec![s.to_string()],
+        Some(Value::Array(items)) => items
+            .read()
+            .iter()
+            .filter_map(|v| match v {
+                Value::String(s) => Some(s.to_string()),
+                _ => None,
+            })
+            .collect(),
+        Some(other) => vec![other.to_string()],
+    }
+}
+
+/// The items of the given values, with each array replaced by its items
+pub fn unroll_to_items(values: Vec<Value>) -> Vec<Value> {
+    let mut out = Vec::new();
+    for v in values {
+        if let Value::Array(items) = v {
+            out.extend(items.unwrap_or_clone());
+        } else {
+            out.push(v);
+        }
+    }
+    out
+}
+
+/// The properties of `item` that `patterns` match, in the object's order.
+/// Values without properties are kept as they are.
+pub fn select_properties(item: Value, patterns: &[WildcardPattern]) -> Value {
+    let Some(properties) = item.properties() else {
+        return item;
+    };
+    let selected: PropertyMap = properties
+        .iter()
+        .filter(|(name, _)| patterns.iter().any(|p| p.is_match(name.as_str())))
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect();
+    Value::Object(selected.into())
+}
+
 /// Trait that all cmdlets must implement
 pub trait Cmdlet: Send + Sync {
     /// Get the name of the cmdlet (e.g., "Write-Output")
     fn name(&self) -> &str;
 
+    /// Named parameters the cmdlet accepts, offered by tab completion
+    fn parameters(&self) -> &[&str] {
+        &[]
+    }
+
+    /// Other names the cmdlet runs by, such as `r` for Invoke-History; a
+    /// function of the same name still comes first
+    fn aliases(&self) -> &[&str] {
+        &[]
+    }
+
+    /// What the cmdlet reaches outside the interpreter, such as the file
+    /// system; a sandboxed session won't run it if any is denied
+    fn capabilities(&self) -> &[Capability] {
+        &[]
+    }
+
     /// Execute the cmdlet with the given context
     /// Returns a Vec of output values (for pipeline)
     /// The evaluator parameter allows cmdlets to execute script blocks
@@ -77,8 +180,11 @@ pub trait Cmdlet: Send + Sync {
 }
 
 /// Registry for managing cmdlets
+/// Cmdlets are shared so one can run while it registers others (Import-Module).
 pub struct CmdletRegistry {
-    cmdlets: HashMap<String, Box<dyn Cmdlet>>,
+    cmdlets: HashMap<String, Arc<dyn Cmdlet>>,
+    /// The cmdlet each alias names, both in lower case
+    aliases: HashMap<String, String>,
 }
 
 impl CmdletRegistry {
@@ -86,23 +192,45 @@ impl CmdletRegistry {
     pub fn new() -> Self {
         Self {
             cmdlets: HashMap::new(),
+            aliases: HashMap::new(),
         }
     }
 
-    /// Register a cmdlet
+    /// Register a cmdlet, and its aliases
     pub fn register(&mut self, cmdlet: Box<dyn Cmdlet>) {
         let name = cmdlet.name().to_lowercase();
-        self.cmdlets.insert(name, cmdlet);
+        for alias in cmdlet.aliases() {
+            self.aliases.insert(alias.to_lowercase(), name.clone());
+        }
+        self.cmdlets.insert(name, Arc::from(cmdlet));
+    }
+
+    /// The key of the cmdlet `name` names, itself or by an alias
+    fn key(&self, name: &str) -> String {
+        let name = name.to_lowercase();
+        self.aliases.get(&name).cloned().unwrap_or(name)
     }
 
-    /// Get a cmdlet by name (case-insensitive)
+    /// Get a cmdlet by name or alias (case-insensitive)
     pub fn get(&self, name: &str) -> Option<&dyn Cmdlet> {
-        self.cmdlets.get(&name.to_lowercase()).map(|b| &**b)
+        self.cmdlets.get(&self.key(name)).map(|b| &**b)
+    }
+
+    /// Get a shared handle to a cmdlet, to run it without borrowing the registry
+    pub(crate) fn get_shared(&self, name: &str) -> Option<Arc<dyn Cmdlet>> {
+        self.cmdlets.get(&self.key(name)).cloned()
     }
 
-    /// Check if a cmdlet is registered
+    /// Check if a cmdlet is registered by this name or alias
     pub fn contains(&self, name: &str) -> bool {
-        self.cmdlets.contains_key(&name.to_lowercase())
+        self.cmdlets.contains_key(&self.key(name))
+    }
+
+    /// Names of all registered cmdlets, as declared by the cmdlets, sorted
+    pub fn names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.cmdlets.values().map(|c| c.name()).collect();
+        names.sort_unstable();
+        names
     }
 }
 
@@ -124,6 +252,10 @@ mod tests {
             "Test-Cmdlet"
         }
 
+        fn aliases(&self) -> &[&str] {
+            &["tc"]
+        }
+
         fn execute(
             &self,
             context: CmdletContext,
@@ -148,6 +280,14 @@ mod tests {
         assert_eq!(ctx.pipeline_input, input);
     }
 
+    #[test]
+    fn test_cmdlet_context_parameters_ignore_case() {
+        let ctx = CmdletContext::new().with_parameter("Path".to_string(), Value::Number(1.0));
+        assert_eq!(ctx.get_parameter("path"), Some(&Value::Number(1.0)));
+        assert_eq!(ctx.get_parameter("PATH"), Some(&Value::Number(1.0)));
+        assert_eq!(ctx.get_parameter("Paths"), None);
+    }
+
     #[test]
     fn test_cmdlet_registry() {
         let mut registry = CmdletRegistry::new();
@@ -159,6 +299,10 @@ mod tests {
 
         let cmdlet = registry.get("test-cmdlet");
         assert!(cmdlet.is_some());
+
+        // An alias finds the cmdlet, but isn't listed as a name of its own
+        assert_eq!(registry.get("TC").map(|c| c.name()), Some("Test-Cmdlet"));
+        assert_eq!(registry.names(), ["Test-Cmdlet"]);
     }
 
     #[test]
@@ -171,4 +315,49 @@ mod tests {
         let result = cmdlet.execute(ctx, &mut evaluator).unwrap();
         assert_eq!(result, input);
     }
+
+    #[test]
+    fn test_parse_switch() {
+        assert!(!parse_switch(None, "Test-Cmdlet").unwrap());
+        assert!(parse_switch(Some(&Value::Boolean(true)), "Test-Cmdlet").unwrap());
+        let error = parse_switch(Some(&Value::String("maybe".into())), "Test-Cmdlet").unwrap_err();
+        assert_eq!(error.error_id(), "InvalidBoolean,Test-Cmdlet");
+        assert_eq!(
+            error.category(),
+            crate::error::ErrorCategory::InvalidArgument
+        );
+    }
+
+    #[test]
+    fn test_parameter_helpers() {
+        let names = Value::Array(vec![Value::String("Name".into()), Value::Number(1.0)].into());
+        assert_eq!(parse_property_list(Some(&names)), ["Name"]);
+        assert_eq!(parse_property_list(Some(&Value::Number(2.0))), ["2"]);
+        assert!(parse_property_list(None).is_empty());
+
+        let items = unroll_to_items(vec![names, Value::Boolean(true)]);
+        assert_eq!(
+            items,
+            [
+                Value::String("Name".into()),
+                Value::Number(1.0),
+                Value::Boolean(true)
+            ]
+        );
+
+        let mut props = PropertyMap::new();
+        props.insert("Name".to_string(), Value::String("a".into()));
+        props.insert("Size".to_string(), Value::Number(3.0));
+        let patterns = [WildcardPattern::new("n*").unwrap()];
+        let selected = select_properties(Value::Object(props.into()), &patterns);
+        assert_eq!(
+            selected.get_property("Name"),
+            Some(Value::String("a".into()))
+        );
+        assert_eq!(selected.get_property("Size"), None);
+        assert_eq!(
+            select_properties(Value::Number(1.0), &patterns),
+            Value::Number(1.0)
+        );
+    }
 }
@@ -0,0 +1,363 @@
+/// Compilation of blocks into a flat instruction list
+///
+/// Loop bodies and script blocks run once per item, so instead of walking
+/// their syntax tree every time the evaluator lowers them once into `Op`s for
+/// a small stack machine (`Evaluator::run_compiled`). Statements and
+/// expressions without an instruction of their own (pipelines, calls, method
+/// calls, function and class definitions...) are kept as tree nodes that the
+/// machine hands back to the tree-walker.
+use crate::value::{ScriptBlock, Value};
+use pwsh_parser::{
+    BinaryOperator, Block, Expression, ExpressionKind, Literal, Span, Statement, StatementKind,
+    StringPart, UnaryOperator,
+};
+
+/// One instruction; operands are popped from the value stack and results pushed
+#[derive(Debug)]
+pub(crate) enum Op {
+    /// Push a constant
+    Constant(Value),
+    /// Push a variable's value
+    Load(String),
+    /// Pop a value into a variable ($null discards it)
+    Store(String),
+    /// Pop the values of this many statements and push their combined output
+    Output(usize),
+    /// Pop the right then the left operand and apply the operator
+    Binary(BinaryOperator, Span),
+    Unary(UnaryOperator, Span),
+    /// Pop an object and push one of its members
+    Member(String, Span),
+    /// Pop an index then an object and push the element
+    Index(Span),
+    /// Pop a value and convert it: [int]$x
+    Cast(String, Span),
+    /// Push an expandable string with its variables filled in
+    Interpolate(Vec<StringPart>),
+    /// Pop this many values into an array
+    MakeArray(usize),
+    /// Pop one value per key into a hashtable
+    MakeHashtable(Vec<String>),
+    Jump(usize),
+    /// Pop a value and jump if it is false
+    JumpIfFalse(usize),
+    PushScope,
+    PopScope,
+    /// Pop the collection of a foreach loop and start iterating it
+    ForeachStart,
+    /// Set the loop variable to the next item, or jump to `exit` when done
+    ForeachNext {
+        variable: String,
+        exit: usize,
+    },
+    /// Pop the result of one iteration, keeping it unless it is null
+    ForeachCollect,
+    /// Finish the innermost loop and push its results
+    ForeachEnd,
+    /// Pop a value and return it from the enclosing function
+    Return,
+    /// Evaluate an expression with the tree-walker
+    Expression(Box<Expression>),
+    /// Run a statement with the tree-walker
+    Statement(Box<Statement>),
+}
+
+/// A block's statements as instructions; running them leaves everything the
+/// statements output, like `Evaluator::eval_block` without its scope
+#[derive(Debug, Default)]
+pub(crate) struct CompiledBlock {
+    ops: Vec<Op>,
+}
+
+impl CompiledBlock {
+    /// Lower a block's statements
+    pub fn new(block: &Block) -> Self {
+        let mut compiler = Compiler { ops: Vec::new() };
+        compiler.statements(&block.statements);
+        CompiledBlock { ops: compiler.ops }
+    }
+
+    pub(crate) fn ops(&self) -> &[Op] {
+        &self.ops
+    }
+}
+
+struct Compiler {
+    ops: Vec<Op>,
+}
+
+impl Compiler {
+    fn emit(&mut self, op: Op) -> usize {
+        self.ops.push(op);
+        self.ops.len() - 1
+    }
+
+    /// Point the jump at `at` to the next instruction
+    fn patch(&mut self, at: usize) {
+        let target = self.ops.len();
+        match &mut self.ops[at] {
+            Op::Jump(to) | Op::JumpIfFalse(to) | Op::ForeachNext { exit: to, .. } => *to = target,
+            other => unreachable!("not a jump: {:?}", other),
+        }
+    }
+
+    /// Leave the statements' combined output (null for none)
+    fn statements(&mut self, statements: &[Statement]) {
+        if statements.is_empty() {
+            self.emit(Op::Constant(Value::Null));
+            return;
+        }
+        for statement in statements {
+            self.statement(statement);
+        }
+        if statements.len() > 1 {
+            self.emit(Op::Output(statements.len()));
+        }
+    }
+
+    /// A nested block gets its own scope, as in `Evaluator::eval_block`
+    fn scoped_block(&mut self, block: &Block) {
+        self.emit(Op::PushScope);
+        self.statements(&block.statements);
+        self.emit(Op::PopScope);
+    }
+
+    fn statement(&mut self, statement: &Statement) {
+        match &statement.kind {
+            StatementKind::Expression(expr) => self.expression(expr),
+
+            StatementKind::Assignment { variable, value } => {
+                self.expression(value);
+                self.emit(Op::Store(variable.clone()));
+                self.emit(Op::Constant(Value::Null));
+            }
+
+            StatementKind::If {
+                condition,
+                then_branch,
+                else_branch,
+            } => {
+                self.expression(condition);
+                let to_else = self.emit(Op::JumpIfFalse(0));
+                self.scoped_block(then_branch);
+                let to_end = self.emit(Op::Jump(0));
+                self.patch(to_else);
+                match else_branch {
+                    Some(else_block) => self.scoped_block(else_block),
+                    None => {
+                        self.emit(Op::Constant(Value::Null));
+                    }
+                }
+                self.patch(to_end);
+            }
+
+            StatementKind::Foreach {
+                variable,
+                collection,
+                body,
+            } => {
+                self.expression(collection);
+                self.emit(Op::ForeachStart);
+                let next = self.emit(Op::ForeachNext {
+                    variable: variable.clone(),
+                    exit: 0,
+                });
+                self.scoped_block(body);
+                self.emit(Op::ForeachCollect);
+                self.emit(Op::Jump(next));
+                self.patch(next);
+                self.emit(Op::ForeachEnd);
+            }
+
+            StatementKind::Return(expr) => {
+                match expr {
+                    Some(expr) => self.expression(expr),
+                    None => {
+                        self.emit(Op::Constant(Value::Null));
+                    }
+                }
+                self.emit(Op::Return);
+            }
+
+            _ => {
+                self.emit(Op::Statement(Box::new(statement.clone())));
+            }
+        }
+    }
+
+    fn expression(&mut self, expr: &Expression) {
+        let span = expr.span;
+        match &expr.kind {
+            ExpressionKind::Literal(Literal::InterpolatedString(parts)) => {
+                self.emit(Op::Interpolate(parts.clone()));
+            }
+            ExpressionKind::Literal(literal) => {
+                let value = match literal {
+                    Literal::Number(n) => Value::Number(*n),
+                    Literal::String(s) => Value::String(s.as_str().into()),
+                    Literal::Boolean(b) => Value::Boolean(*b),
+                    Literal::Null | Literal::InterpolatedString(_) => Value::Null,
+                };
+                self.emit(Op::Constant(value));
+            }
+
+            ExpressionKind::Variable(name) => {
+                self.emit(Op::Load(name.clone()));
+            }
+
+            ExpressionKind::BinaryOp {
+                left,
+                operator,
+                right,
+            } => {
+                self.expression(left);
+                self.expression(right);
+                self.emit(Op::Binary(operator.clone(), span));
+            }
+
+            ExpressionKind::UnaryOp { operator, operand } => {
+                self.expression(operand);
+                self.emit(Op::Unary(operator.clone(), span));
+            }
+
+            ExpressionKind::MemberAccess { object, member } => {
+                self.expression(object);
+                self.emit(Op::Member(member.clone(), span));
+            }
+
+            ExpressionKind::Index { object, index } => {
+                self.expression(object);
+                self.expression(index);
+                self.emit(Op::Index(span));
+            }
+
+            ExpressionKind::Cast {
+                type_name,
+                expression,
+            } => {
+                self.expression(expression);
+                self.emit(Op::Cast(type_name.clone(), span));
+            }
+
+            ExpressionKind::TypeLiteral(name) => {
+                self.emit(Op::Constant(Value::String(name.as_str().into())));
+            }
+
+            // Built once, so every run shares the inner block's compiled code
+            ExpressionKind::ScriptBlock(block) => {
+                self.emit(Op::Constant(Value::ScriptBlock(ScriptBlock::new(
+                    block.clone(),
+                ))));
+            }
+
+            ExpressionKind::Array(items) => {
+                for item in items {
+                    self.expression(item);
+                }
+                self.emit(Op::MakeArray(items.len()));
+            }
+
+            ExpressionKind::Hashtable { entries: pairs, .. } => {
+                for (_, value) in pairs {
+                    self.expression(value);
+                }
+                let keys = pairs.iter().map(|(key, _)| key.clone()).collect();
+                self.emit(Op::MakeHashtable(keys));
+            }
+
+            _ => {
+                self.emit(Op::Expression(Box::new(expr.clone())));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Evaluator;
+    use pwsh_lexer::Lexer;
+    use pwsh_parser::Parser;
+
+    fn compile(input: &str) -> CompiledBlock {
+        let tokens = Lexer::new(input).tokenize().unwrap();
+        let program = Parser::new(tokens).parse().unwrap();
+        CompiledBlock::new(&Block {
+            statements: program.statements,
+            trivia: Vec::new(),
+            span: Span::default(),
+        })
+    }
+
+    fn eval_with(compile: bool, input: &str) -> (Result<Value, String>, Option<Span>) {
+        let tokens = Lexer::new(input).tokenize().unwrap();
+        let program = Parser::new(tokens).parse().unwrap();
+        let mut evaluator = Evaluator::new();
+        evaluator.set_compilation(compile);
+        let result = evaluator.eval(program).map_err(|e| e.to_string());
+        (result, evaluator.error_span())
+    }
+
+    #[test]
+    fn test_expressions_lower_to_instructions() {
+        let code = compile("$x = 1 + $y; $x");
+        assert!(matches!(
+            code.ops(),
+            [
+                Op::Constant(Value::Number(_)),
+                Op::Load(y),
+                Op::Binary(BinaryOperator::Add, _),
+                Op::Store(x),
+                Op::Constant(Value::Null),
+                Op::Load(x2),
+                Op::Output(2),
+            ] if y == "y" && x == "x" && x2 == "x"
+        ));
+    }
+
+    #[test]
+    fn test_unsupported_nodes_fall_back_to_the_tree() {
+        let code = compile("function f { 1 }; $s.Trim()");
+        assert!(matches!(
+            code.ops(),
+            [Op::Statement(_), Op::Expression(_), Op::Output(2)]
+        ));
+    }
+
+    #[test]
+    fn test_compiled_code_matches_the_tree_walker() {
+        let scripts = [
+            "$total = 0; foreach ($n in @(1, 2, 3)) { $total = $total + $n }; $total",
+            "foreach ($n in @(1, 2, 3, 4)) { if ($n % 2 -eq 0) { $n * 10 } else { \"odd $n\" } }",
+            "foreach ($p in @(@{Name = 'a'; Size = 2}, @{Name = 'b'; Size = 5})) { $p.Name + $p['Size'] }",
+            "@(3, 1, 2) | { [string]($_ * 2) }",
+            "@('x', 'y') | { foreach ($i in @(1, 2)) { $_ + $i } }",
+            "foreach ($w in @('ab', 'cd')) { if ($w -match 'c(.)') { $matches[1] } }",
+            "function f { foreach ($n in @(1, 2, 3)) { if ($n -eq 2) { return $n * 100 } } }; f",
+            "$x = 5; foreach ($i in $null) { $x = 0 }; $x",
+            "@(1, 2) | { $_; $_ * 10 }",
+            "foreach ($n in @(1, 2)) { $n; @($n, 0) }",
+            "@(1, 2) | { 'a'; foreach ($i in @(1, 2)) { $i; if ($i -eq 2) { return 'r' } }; 'never' }",
+            "@(1) | { foreach ($i in @(1, 2, 3, 4)) { if ($i -eq 2) { continue }; if ($i -eq 4) { break }; $i } }",
+            "@(1) | { foreach ($i in @(1, 2)) { foreach ($j in @(1, 2)) { $j; break; 'never' }; $i } }",
+            "@(1) | { foreach ($i in @(1, 2)) { try { $i; continue } finally { 'f' } } }",
+        ];
+        for script in scripts {
+            assert_eq!(
+                eval_with(true, script),
+                eval_with(false, script),
+                "{}",
+                script
+            );
+        }
+    }
+
+    #[test]
+    fn test_compiled_errors_point_at_the_failing_expression() {
+        let script = "foreach ($n in @(1, 0)) {\n    $x = 10 / $n\n}";
+        let (result, span) = eval_with(true, script);
+        assert!(result.is_err());
+        assert_eq!(span.unwrap().start.line, 2);
+        assert_eq!((result, span), eval_with(false, script));
+    }
+}
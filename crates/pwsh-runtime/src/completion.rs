@@ -0,0 +1,308 @@
+/// Tab completion for commands, parameters, variables and file paths
+/// The engine works on raw source text, so the REPL, editors and embedders can share it.
+use crate::cmdlet::COMMON_PARAMETERS;
+use crate::evaluator::Evaluator;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+/// Characters that end the word being completed
+const WORD_BOUNDARIES: &[char] = &['|', ';', '(', ')', '{', '}', ',', '='];
+
+/// Characters after which a new command starts
+const COMMAND_BOUNDARIES: &[char] = &['|', ';', '(', '{', '=', '\n'];
+
+/// What a completion candidate refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompletionKind {
+    Command,
+    Parameter,
+    Variable,
+    Path,
+}
+
+/// A completion candidate and the part of the source it replaces
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    /// Text to insert, e.g. "Get-ChildItem", "-Recurse", "$name"
+    pub text: String,
+    pub kind: CompletionKind,
+    /// Byte range of the source replaced by `text`
+    pub replace: Range<usize>,
+}
+
+/// A command known to the engine and its named parameters
+#[derive(Debug, Clone)]
+struct CommandInfo {
+    name: String,
+    parameters: Vec<String>,
+}
+
+/// Completes commands, parameters, variables and file paths at a cursor position
+#[derive(Debug, Clone, Default)]
+pub struct CompletionEngine {
+    commands: Vec<CommandInfo>,
+    variables: Vec<String>,
+    /// Directory relative paths are completed against (the process's by default)
+    current_dir: Option<PathBuf>,
+}
+
+impl CompletionEngine {
+    /// Create an engine that knows no commands or variables
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an engine from an evaluator's cmdlets, functions and variables
+    pub fn from_evaluator(evaluator: &Evaluator) -> Self {
+        let mut engine = Self::new();
+
+        let registry = evaluator.registry();
+        for name in registry.names() {
+            let mut parameters = registry
+                .get(name)
+                .map_or(vec![], |c| c.parameters().to_vec());
+            parameters.extend_from_slice(COMMON_PARAMETERS);
+            engine.add_command(name, &parameters);
+        }
+        for func in evaluator.functions() {
+            let parameters: Vec<&str> = func.parameters.iter().map(|p| p.name.as_str()).collect();
+            engine.add_command(&func.name, &parameters);
+        }
+        engine.set_variables(evaluator.variable_names());
+
+        engine
+    }
+
+    /// Add a command (or replace one with the same name) and its parameters
+    pub fn add_command(&mut self, name: &str, parameters: &[&str]) {
+        self.commands
+            .retain(|command| !command.name.eq_ignore_ascii_case(name));
+        self.commands.push(CommandInfo {
+            name: name.to_string(),
+            parameters: parameters.iter().map(|p| p.to_string()).collect(),
+        });
+    }
+
+    /// Replace the known variable names (without the leading $)
+    pub fn set_variables(&mut self, names: impl IntoIterator<Item = String>) {
+        self.variables = names.into_iter().collect();
+    }
+
+    /// Complete relative paths against `dir` instead of the process's directory
+    pub fn with_current_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.current_dir = Some(dir.into());
+        self
+    }
+
+    /// Completion candidates for the word ending at `cursor_pos` (a byte offset),
+    /// sorted case-insensitively
+    pub fn complete(&self, source: &str, cursor_pos: usize) -> Vec<Completion> {
+        let mut cursor = cursor_pos.min(source.len());
+        while !source.is_char_boundary(cursor) {
+            cursor -= 1;
+        }
+
+        let before = &source[..cursor];
+        let start = before
+            .rfind(|c: char| c.is_whitespace() || WORD_BOUNDARIES.contains(&c))
+            .map_or(0, |i| {
+                i + before[i..].chars().next().map_or(1, char::len_utf8)
+            });
+        let word = &source[start..cursor];
+
+        // The command this word belongs to: everything since the last command boundary
+        let segment_start = source[..start]
+            .rfind(COMMAND_BOUNDARIES)
+            .map_or(0, |i| i + 1);
+        let command = source[segment_start..start].split_whitespace().next();
+
+        let mut candidates: Vec<(String, CompletionKind)> =
+            if let Some(name) = word.strip_prefix('$') {
+                self.complete_variables(name)
+            } else if let (Some(name), Some(command)) = (word.strip_prefix('-'), command) {
+                self.complete_parameters(command, name)
+            } else if command.is_none() {
+                let mut candidates = self.complete_commands(word);
+                // Scripts can be run by path: ./build.ps1
+                if word.contains(['/', '\\']) || word.starts_with('.') {
+                    candidates.extend(self.complete_paths(word));
+                }
+                candidates
+            } else {
+                self.complete_paths(word)
+            };
+
+        // Quoted paths sort by their name, not the quote
+        candidates.sort_by_key(|(text, _)| text.trim_start_matches('\'').to_lowercase());
+        candidates.dedup_by(|a, b| a.0.eq_ignore_ascii_case(&b.0));
+        candidates
+            .into_iter()
+            .map(|(text, kind)| Completion {
+                text,
+                kind,
+                replace: start..cursor,
+            })
+            .collect()
+    }
+
+    fn complete_commands(&self, prefix: &str) -> Vec<(String, CompletionKind)> {
+        self.commands
+            .iter()
+            .filter(|command| starts_with_ignore_case(&command.name, prefix))
+            .map(|command| (command.name.clone(), CompletionKind::Command))
+            .collect()
+    }
+
+    fn complete_parameters(&self, command: &str, prefix: &str) -> Vec<(String, CompletionKind)> {
+        self.commands
+            .iter()
+            .find(|info| info.name.eq_ignore_ascii_case(command))
+            .map(|info| {
+                info.parameters
+                    .iter()
+                    .filter(|parameter| starts_with_ignore_case(parameter, prefix))
+                    .map(|parameter| (format!("-{}", parameter), CompletionKind::Parameter))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn complete_variables(&self, prefix: &str) -> Vec<(String, CompletionKind)> {
+        self.variables
+            .iter()
+            .filter(|name| starts_with_ignore_case(name, prefix))
+            .map(|name| (format!("${}", name), CompletionKind::Variable))
+            .collect()
+    }
+
+    /// Complete the last component of a path; directories end with a separator
+    /// and names containing spaces are quoted
+    fn complete_paths(&self, word: &str) -> Vec<(String, CompletionKind)> {
+        let split = word.rfind(['/', '\\']).map_or(0, |i| i + 1);
+        let (dir, prefix) = word.split_at(split);
+        let separator = dir.chars().last().unwrap_or(std::path::MAIN_SEPARATOR);
+
+        let base = self.current_dir.as_deref().unwrap_or(Path::new("."));
+        let Ok(entries) = std::fs::read_dir(base.join(dir)) else {
+            return Vec::new();
+        };
+
+        entries
+            .filter_map(Result::ok)
+            .filter_map(|entry| {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                // Hidden entries are only offered once the user types the dot
+                if !starts_with_ignore_case(&name, prefix)
+                    || (name.starts_with('.') && !prefix.starts_with('.'))
+                {
+                    return None;
+                }
+                let mut path = format!("{}{}", dir, name);
+                if entry.path().is_dir() {
+                    path.push(separator);
+                }
+                if path.contains(char::is_whitespace) {
+                    path = format!("'{}'", path);
+                }
+                Some((path, CompletionKind::Path))
+            })
+            .collect()
+    }
+}
+
+fn starts_with_ignore_case(text: &str, prefix: &str) -> bool {
+    text.to_lowercase().starts_with(&prefix.to_lowercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn engine() -> CompletionEngine {
+        let mut engine = CompletionEngine::new();
+        engine.add_command("Get-ChildItem", &["Path", "Recurse", "Filter"]);
+        engine.add_command("Get-Content", &["Path", "Tail"]);
+        engine.add_command("Where-Object", &["Property"]);
+        engine.set_variables(["files".to_string(), "Filter".to_string()]);
+        engine
+    }
+
+    fn texts(completions: &[Completion]) -> Vec<&str> {
+        completions.iter().map(|c| c.text.as_str()).collect()
+    }
+
+    #[test]
+    fn test_complete_commands_in_command_position() {
+        let engine = engine();
+        let completions = engine.complete("$x = get-c", 10);
+        assert_eq!(texts(&completions), vec!["Get-ChildItem", "Get-Content"]);
+        assert_eq!(completions[0].kind, CompletionKind::Command);
+        assert_eq!(completions[0].replace, 5..10);
+
+        let completions = engine.complete("1 | wh", 6);
+        assert_eq!(texts(&completions), vec!["Where-Object"]);
+    }
+
+    #[test]
+    fn test_complete_parameters_of_current_command() {
+        let engine = engine();
+        let source = "Get-Content a.txt | Get-ChildItem -r";
+        let completions = engine.complete(source, source.len());
+        assert_eq!(texts(&completions), vec!["-Recurse"]);
+        assert_eq!(completions[0].kind, CompletionKind::Parameter);
+        assert_eq!(completions[0].replace, 34..36);
+
+        assert_eq!(
+            texts(&engine.complete("Get-Content -", 13)),
+            vec!["-Path", "-Tail"]
+        );
+        assert!(engine.complete("Unknown -", 9).is_empty());
+    }
+
+    #[test]
+    fn test_complete_variables() {
+        let engine = engine();
+        let completions = engine.complete("Write-Output $F", 15);
+        assert_eq!(texts(&completions), vec!["$files", "$Filter"]);
+        assert_eq!(completions[0].kind, CompletionKind::Variable);
+        assert_eq!(completions[0].replace, 13..15);
+    }
+
+    #[test]
+    fn test_complete_paths_in_argument_position() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("scripts")).unwrap();
+        fs::write(dir.path().join("scripts").join("build.ps1"), "").unwrap();
+        fs::write(dir.path().join("Setup notes.txt"), "").unwrap();
+        fs::write(dir.path().join(".hidden"), "").unwrap();
+        let engine = engine().with_current_dir(dir.path());
+
+        let completions = engine.complete("Get-Content s", 13);
+        assert_eq!(
+            texts(&completions),
+            vec!["scripts/", "'Setup notes.txt'"]
+                .into_iter()
+                .map(|t| t.replace('/', std::path::MAIN_SEPARATOR_STR))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(completions[0].kind, CompletionKind::Path);
+
+        let completions = engine.complete("Get-Content scripts/b", 21);
+        assert_eq!(texts(&completions), vec!["scripts/build.ps1"]);
+        assert_eq!(completions[0].replace, 12..21);
+
+        // Hidden files only once a dot is typed; commands may be script paths
+        assert!(!texts(&engine.complete("Get-Content ", 12)).contains(&".hidden"));
+        assert_eq!(texts(&engine.complete("./scr", 5)), vec!["./scripts/"]);
+    }
+
+    #[test]
+    fn test_cursor_in_middle_of_source() {
+        let engine = engine();
+        let completions = engine.complete("get-ch; 1", 6);
+        assert_eq!(texts(&completions), vec!["Get-ChildItem"]);
+        assert_eq!(completions[0].replace, 0..6);
+        assert_eq!(engine.complete("", 10).len(), 3);
+    }
+}
@@ -0,0 +1,358 @@
+/// The interpreter's configuration file, `pwsh.config.json`
+use crate::error::{ErrorCategory, ErrorRecord, RuntimeError};
+use crate::policy::ExecutionPolicy;
+use std::path::{Path, PathBuf};
+
+/// Name of the configuration file, as PowerShell has `powershell.config.json`
+pub const CONFIG_FILE_NAME: &str = "pwsh.config.json";
+
+/// Highest strict mode version; `Latest` means this one
+pub const LATEST_STRICT_MODE: u32 = 3;
+
+/// The 16 console colors, as PowerShell names them
+pub const CONSOLE_COLORS: [&str; 16] = [
+    "Black",
+    "DarkBlue",
+    "DarkGreen",
+    "DarkCyan",
+    "DarkRed",
+    "DarkMagenta",
+    "DarkYellow",
+    "Gray",
+    "DarkGray",
+    "Blue",
+    "Green",
+    "Cyan",
+    "Red",
+    "Magenta",
+    "Yellow",
+    "White",
+];
+
+/// Colors the interactive shell highlights input with, by console color name
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorScheme {
+    pub keyword: String,
+    pub variable: String,
+    pub parameter: String,
+    /// Brackets, pipes and the other punctuation
+    pub operator: String,
+    /// Everything else
+    pub default: String,
+}
+
+impl Default for ColorScheme {
+    fn default() -> Self {
+        ColorScheme {
+            keyword: "DarkMagenta".to_string(),
+            variable: "DarkCyan".to_string(),
+            parameter: "DarkYellow".to_string(),
+            operator: "White".to_string(),
+            default: "Gray".to_string(),
+        }
+    }
+}
+
+impl ColorScheme {
+    /// The scheme's entries by their names in the file
+    pub fn entries(&self) -> [(&'static str, &str); 5] {
+        [
+            ("Keyword", &self.keyword),
+            ("Variable", &self.variable),
+            ("Parameter", &self.parameter),
+            ("Operator", &self.operator),
+            ("Default", &self.default),
+        ]
+    }
+}
+
+/// Settings a session starts with, usually read from `pwsh.config.json`.
+/// Every setting is optional in the file; the defaults are how the
+/// interpreter behaves without one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Configuration {
+    /// The file the settings were read from
+    pub path: Option<PathBuf>,
+    /// Encoding Get-Content reads files in without `-Encoding`; None
+    /// detects it from the file's byte order mark, falling back to UTF-8
+    pub default_encoding: Option<String>,
+    /// Strict mode version the session starts in; 0 is off
+    pub strict_mode: u32,
+    /// How many lines the interactive shell keeps in its history
+    pub history_size: usize,
+    /// Whether the interactive shell shows output that doesn't fit the
+    /// terminal a screen at a time
+    pub paging: bool,
+    /// Colors the interactive shell highlights input with
+    pub color_scheme: ColorScheme,
+    /// Directories to look for modules in
+    pub module_paths: Vec<PathBuf>,
+    /// Which script files may run
+    pub execution_policy: ExecutionPolicy,
+}
+
+impl Default for Configuration {
+    fn default() -> Self {
+        Configuration {
+            path: None,
+            default_encoding: None,
+            strict_mode: 0,
+            history_size: 1000,
+            paging: false,
+            color_scheme: ColorScheme::default(),
+            module_paths: Vec::new(),
+            execution_policy: ExecutionPolicy::default(),
+        }
+    }
+}
+
+fn invalid(message: impl Into<String>) -> RuntimeError {
+    ErrorRecord::new(ErrorCategory::InvalidData, "InvalidConfiguration", message).into()
+}
+
+fn expect_string<'a>(key: &str, value: &'a serde_json::Value) -> Result<&'a str, RuntimeError> {
+    value
+        .as_str()
+        .ok_or_else(|| invalid(format!("{} must be a string, not {}", key, value)))
+}
+
+/// The console color named `name`, ignoring case, as PowerShell spells it
+fn console_color(key: &str, name: &str) -> Result<String, RuntimeError> {
+    CONSOLE_COLORS
+        .iter()
+        .find(|color| color.eq_ignore_ascii_case(name))
+        .map(|color| color.to_string())
+        .ok_or_else(|| {
+            invalid(format!(
+                "{} is '{}', which is not a console color. Use one of: {}",
+                key,
+                name,
+                CONSOLE_COLORS.join(", ")
+            ))
+        })
+}
+
+/// A strict mode version: `Off`, `1.0`, `2.0`, `3.0` or `Latest`
+pub fn parse_strict_mode(text: &str) -> Option<u32> {
+    let text = text.trim();
+    if text.eq_ignore_ascii_case("Off") {
+        return Some(0);
+    }
+    if text.eq_ignore_ascii_case("Latest") {
+        return Some(LATEST_STRICT_MODE);
+    }
+    let major = text.strip_suffix(".0").unwrap_or(text);
+    major
+        .parse()
+        .ok()
+        .filter(|version| (1..=LATEST_STRICT_MODE).contains(version))
+}
+
+/// A strict mode version as `parse_strict_mode` reads it
+pub fn strict_mode_name(version: u32) -> String {
+    match version {
+        0 => "Off".to_string(),
+        version => format!("{}.0", version),
+    }
+}
+
+impl Configuration {
+    /// Read settings from the text of a configuration file. Unknown
+    /// settings and values of the wrong kind are `InvalidConfiguration`
+    /// errors, so a misspelt setting isn't silently ignored.
+    pub fn from_json(text: &str) -> Result<Configuration, RuntimeError> {
+        let json: serde_json::Value = serde_json::from_str(text)
+            .map_err(|e| invalid(format!("The configuration is not valid JSON: {}", e)))?;
+        let serde_json::Value::Object(settings) = json else {
+            return Err(invalid("The configuration must be a JSON object"));
+        };
+
+        let mut config = Configuration::default();
+        for (key, value) in &settings {
+            match key.as_str() {
+                "DefaultEncoding" => {
+                    config.default_encoding = Some(expect_string(key, value)?.to_string());
+                }
+                "StrictMode" => {
+                    let text = match value {
+                        serde_json::Value::Number(n) => n.to_string(),
+                        other => expect_string(key, other)?.to_string(),
+                    };
+                    config.strict_mode = parse_strict_mode(&text).ok_or_else(|| {
+                        invalid(format!(
+                            "StrictMode is '{}'; use Off, 1.0, 2.0, 3.0 or Latest",
+                            text
+                        ))
+                    })?;
+                }
+                "HistorySize" => {
+                    config.history_size = value
+                        .as_u64()
+                        .filter(|size| *size > 0)
+                        .map(|size| size as usize)
+                        .ok_or_else(|| {
+                            invalid(format!(
+                                "HistorySize must be a whole number above 0, not {}",
+                                value
+                            ))
+                        })?;
+                }
+                "Paging" => {
+                    config.paging = value.as_bool().ok_or_else(|| {
+                        invalid(format!("Paging must be true or false, not {}", value))
+                    })?;
+                }
+                "ColorScheme" => {
+                    let serde_json::Value::Object(colors) = value else {
+                        return Err(invalid(format!(
+                            "ColorScheme must be an object of colors, not {}",
+                            value
+                        )));
+                    };
+                    let scheme = &mut config.color_scheme;
+                    for (name, color) in colors {
+                        let entry = match name.as_str() {
+                            "Keyword" => &mut scheme.keyword,
+                            "Variable" => &mut scheme.variable,
+                            "Parameter" => &mut scheme.parameter,
+                            "Operator" => &mut scheme.operator,
+                            "Default" => &mut scheme.default,
+                            other => {
+                                return Err(invalid(format!(
+                                    "ColorScheme has no '{}'; use Keyword, Variable, Parameter, Operator or Default",
+                                    other
+                                )))
+                            }
+                        };
+                        let key = format!("ColorScheme.{}", name);
+                        *entry = console_color(&key, expect_string(&key, color)?)?;
+                    }
+                }
+                "ModulePaths" => {
+                    let serde_json::Value::Array(paths) = value else {
+                        return Err(invalid(format!(
+                            "ModulePaths must be an array of directories, not {}",
+                            value
+                        )));
+                    };
+                    config.module_paths = paths
+                        .iter()
+                        .map(|path| expect_string("ModulePaths", path).map(PathBuf::from))
+                        .collect::<Result<_, _>>()?;
+                }
+                "ExecutionPolicy" => {
+                    let name = expect_string(key, value)?;
+                    config.execution_policy =
+                        ExecutionPolicy::from_name(name).ok_or_else(|| {
+                            invalid(format!("ExecutionPolicy '{}' is not a policy", name))
+                        })?;
+                }
+                other => {
+                    return Err(invalid(format!(
+                        "Unknown setting '{}'. The settings are DefaultEncoding, StrictMode, HistorySize, Paging, ColorScheme, ModulePaths and ExecutionPolicy",
+                        other
+                    )))
+                }
+            }
+        }
+        Ok(config)
+    }
+
+    /// Read the configuration file at `path`
+    pub fn load(path: impl AsRef<Path>) -> Result<Configuration, RuntimeError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|e| {
+            RuntimeError::from(ErrorRecord::io(
+                &e,
+                "ConfigurationNotRead",
+                format!("Cannot read {}: {}", path.display(), e),
+            ))
+        })?;
+        let mut config =
+            Self::from_json(&text).map_err(|e| invalid(format!("{}: {}", path.display(), e)))?;
+        config.path = Some(path.to_path_buf());
+        Ok(config)
+    }
+
+    /// Where the user's configuration file goes: `pwsh/pwsh.config.json` in
+    /// `%APPDATA%` on Windows, elsewhere in `$XDG_CONFIG_HOME` or
+    /// `~/.config`. None when there is no such directory.
+    pub fn user_file() -> Option<PathBuf> {
+        let dir = if cfg!(windows) {
+            std::env::var_os("APPDATA").map(PathBuf::from)
+        } else {
+            std::env::var_os("XDG_CONFIG_HOME")
+                .map(PathBuf::from)
+                .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+        }?;
+        Some(dir.join("pwsh").join(CONFIG_FILE_NAME))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_configuration() {
+        let config = Configuration::from_json(
+            r#"{
+                "DefaultEncoding": "utf16",
+                "StrictMode": "Latest",
+                "HistorySize": 50,
+                "Paging": true,
+                "ColorScheme": { "Keyword": "darkcyan" },
+                "ModulePaths": ["/opt/pwsh/modules"],
+                "ExecutionPolicy": "RemoteSigned"
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(config.default_encoding.as_deref(), Some("utf16"));
+        assert_eq!(config.strict_mode, LATEST_STRICT_MODE);
+        assert_eq!(config.history_size, 50);
+        assert!(config.paging);
+        assert_eq!(config.color_scheme.keyword, "DarkCyan");
+        assert_eq!(config.color_scheme.variable, "DarkCyan");
+        assert_eq!(
+            config.module_paths,
+            vec![PathBuf::from("/opt/pwsh/modules")]
+        );
+        assert_eq!(config.execution_policy, ExecutionPolicy::RemoteSigned);
+
+        assert_eq!(
+            Configuration::from_json("{}").unwrap(),
+            Configuration::default()
+        );
+        assert_eq!(
+            Configuration::from_json(r#"{"StrictMode": 2}"#)
+                .unwrap()
+                .strict_mode,
+            2
+        );
+    }
+
+    #[test]
+    fn test_invalid_configuration_says_what_is_wrong() {
+        let message = |text: &str| Configuration::from_json(text).unwrap_err().to_string();
+        assert!(message("[1]").contains("must be a JSON object"));
+        assert!(message(r#"{"HistroySize": 10}"#).contains("Unknown setting 'HistroySize'"));
+        assert!(message(r#"{"HistorySize": 0}"#).contains("above 0"));
+        assert!(message(r#"{"Paging": "yes"}"#).contains("true or false"));
+        assert!(message(r#"{"StrictMode": "4.0"}"#).contains("use Off, 1.0"));
+        assert!(message(r#"{"ColorScheme": {"Keyword": "Pink"}}"#)
+            .contains("'Pink', which is not a console color"));
+        assert!(message(r#"{"ModulePaths": "/opt"}"#).contains("must be an array"));
+        assert!(message("{").contains("not valid JSON"));
+    }
+
+    #[test]
+    fn test_strict_mode_names() {
+        assert_eq!(parse_strict_mode("off"), Some(0));
+        assert_eq!(parse_strict_mode("1.0"), Some(1));
+        assert_eq!(parse_strict_mode("2"), Some(2));
+        assert_eq!(parse_strict_mode("latest"), Some(LATEST_STRICT_MODE));
+        assert_eq!(parse_strict_mode("5.0"), None);
+        assert_eq!(strict_mode_name(0), "Off");
+        assert_eq!(strict_mode_name(2), "2.0");
+    }
+}
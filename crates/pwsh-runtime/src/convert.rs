@@ -0,0 +1,270 @@
+/// Type conversions for casts: [int]"42", [string]$x, [ordered]@{a = 1}
+use crate::error::{ErrorRecord, RuntimeError};
+use crate::types::TypeRegistry;
+use crate::value::Value;
+
+/// Parse a string as a number the way PowerShell converts one. Surrounding
+/// whitespace is ignored and an empty string is 0. Besides decimals, which
+/// always use `.` whatever the culture, and exponents (`1e3`), it accepts hex
+/// (`0x1F`), binary (`0b101`) and a kb/mb/gb/tb/pb multiplier (`1.5kb`).
+/// Anything else, including `1,000` and `12abc`, is not a number.
+pub fn parse_number(text: &str) -> Option<f64> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Some(0.0);
+    }
+    let (negative, body) = match text.as_bytes()[0] {
+        b'-' => (true, &text[1..]),
+        b'+' => (false, &text[1..]),
+        _ => (false, text),
+    };
+    let body = body.to_ascii_lowercase();
+
+    let (digits, multiplier) = match body.len().checked_sub(2).map(|at| body.split_at(at)) {
+        Some((digits, suffix)) if !digits.is_empty() => match suffix {
+            "kb" => (digits, 1024f64),
+            "mb" => (digits, 1024f64.powi(2)),
+            "gb" => (digits, 1024f64.powi(3)),
+            "tb" => (digits, 1024f64.powi(4)),
+            "pb" => (digits, 1024f64.powi(5)),
+            _ => (body.as_str(), 1.0),
+        },
+        _ => (body.as_str(), 1.0),
+    };
+
+    let radix = |digits: &str, radix: u32| {
+        u64::from_str_radix(digits, radix)
+            .ok()
+            .filter(|_| !digits.starts_with('+'))
+            .map(|n| n as f64)
+    };
+    let magnitude = if let Some(hex) = digits.strip_prefix("0x") {
+        radix(hex, 16)?
+    } else if let Some(binary) = digits.strip_prefix("0b") {
+        radix(binary, 2)?
+    } else if digits == "infinity" {
+        f64::INFINITY
+    } else if digits == "nan" {
+        f64::NAN
+    } else if digits.starts_with(|c: char| c.is_ascii_digit() || c == '.')
+        && digits
+            .chars()
+            .all(|c| c.is_ascii_digit() || matches!(c, '.' | 'e' | '+' | '-'))
+    {
+        digits.parse::<f64>().ok()?
+    } else {
+        return None;
+    };
+
+    let n = magnitude * multiplier;
+    Some(if negative { -n } else { n })
+}
+
+/// Convert a value to the named type (case-insensitive, System. prefix optional)
+/// User-defined enums can be cast from a member name or value: [Color]"Red", [Color]1
+pub fn cast(type_name: &str, value: Value, types: &TypeRegistry) -> Result<Value, RuntimeError> {
+    let lower = type_name.to_lowercase();
+    let target = lower.strip_prefix("system.").unwrap_or(&lower);
+
+    // Typed arrays convert each element: [int[]]@("1", "2")
+    if let Some(element_type) = target.strip_suffix("[]") {
+        let items = match value {
+            Value::Array(items) => items.unwrap_or_clone(),
+            Value::Null => Vec::new(),
+            other => vec![other],
+        };
+        return items
+            .into_iter()
+            .map(|item| cast(element_type, item, types))
+            .collect::<Result<Vec<_>, _>>()
+            .map(|items| Value::Array(items.into()));
+    }
+
+    let invalid = |value: &Value| {
+        RuntimeError::InvalidOperation(format!(
+            "Cannot convert value \"{}\" to type \"{}\"",
+            value, type_name
+        ))
+    };
+
+    match target {
+        "object" | "psobject" => Ok(value),
+        // Hashtables already keep their keys in insertion order; the object
+        // is a new one, so changing it leaves the hashtable alone
+        // A PSTypeName key names the object's type rather than a property
+        "pscustomobject" => match value {
+            Value::Object(props) => {
+                let mut props = props.read().clone();
+                if let Some(type_name) = props.shift_remove("PSTypeName") {
+                    props.insert_type_name(&type_name.to_string());
+                }
+                Ok(Value::Object(props.into()))
+            }
+            other => Err(invalid(&other)),
+        },
+        "ordered" | "hashtable" => match value {
+            Value::Object(_) => Ok(value),
+            other => Err(invalid(&other)),
+        },
+        "string" => Ok(Value::String(
+            match value {
+                // Arrays join with spaces, like "$array"
+                Value::Array(items) => items
+                    .read()
+                    .iter()
+                    .map(|item| item.to_string())
+                    .collect::<Vec<_>>()
+                    .join(" "),
+                other => other.to_string(),
+            }
+            .into(),
+        )),
+        "int" | "int32" | "long" | "int64" | "byte" => match value {
+            Value::Null => Ok(Value::Number(0.0)),
+            // .NET rounds half to even when converting to an integer
+            other => other
+                .to_number()
+                .map(|n| Value::Number(n.round_ties_even()))
+                .ok_or_else(|| invalid(&other)),
+        },
+        "double" | "float" | "single" | "decimal" => match value {
+            Value::Null => Ok(Value::Number(0.0)),
+            other => other
+                .to_number()
+                .map(Value::Number)
+                .ok_or_else(|| invalid(&other)),
+        },
+        "bool" | "boolean" => Ok(Value::Boolean(value.to_bool())),
+        "array" => Ok(match value {
+            Value::Array(items) => Value::Array(items),
+            Value::Null => Value::Array(Vec::new().into()),
+            other => Value::Array(vec![other].into()),
+        }),
+        "datetime" => value
+            .to_datetime()
+            .map(Value::DateTime)
+            .ok_or_else(|| invalid(&value)),
+        _ => {
+            let enum_type = types
+                .enum_type(type_name)
+                .ok_or_else(|| ErrorRecord::type_not_found(type_name))?;
+            let member = match &value {
+                Value::String(name) => enum_type.member(name.trim()),
+                other => other
+                    .to_number()
+                    .and_then(|n| enum_type.member_with_value(n as i64)),
+            };
+            member.ok_or_else(|| invalid(&value))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pwsh_parser::EnumDef;
+
+    fn cast_value(type_name: &str, value: Value) -> Result<Value, RuntimeError> {
+        cast(type_name, value, &TypeRegistry::new())
+    }
+
+    #[test]
+    fn test_cast_to_numbers() {
+        assert_eq!(
+            cast_value("int", Value::String("42".into())),
+            Ok(Value::Number(42.0))
+        );
+        assert_eq!(
+            cast_value("Int32", Value::Number(2.5)),
+            Ok(Value::Number(2.0))
+        );
+        assert_eq!(
+            cast_value("double", Value::String("1.5".into())),
+            Ok(Value::Number(1.5))
+        );
+        assert!(cast_value("int", Value::String("abc".into())).is_err());
+        assert_eq!(
+            cast_value("int", Value::String(" 0x10 ".into())),
+            Ok(Value::Number(16.0))
+        );
+        assert_eq!(
+            cast_value("long", Value::String("2kb".into())),
+            Ok(Value::Number(2048.0))
+        );
+    }
+
+    #[test]
+    fn test_parse_number() {
+        assert_eq!(parse_number("42"), Some(42.0));
+        assert_eq!(parse_number("  -1.5\n"), Some(-1.5));
+        assert_eq!(parse_number("+.5"), Some(0.5));
+        assert_eq!(parse_number("1e3"), Some(1000.0));
+        assert_eq!(parse_number("2.5E-1"), Some(0.25));
+        assert_eq!(parse_number("0xFF"), Some(255.0));
+        assert_eq!(parse_number("-0x10"), Some(-16.0));
+        assert_eq!(parse_number("0b101"), Some(5.0));
+        assert_eq!(parse_number("1KB"), Some(1024.0));
+        assert_eq!(parse_number("1.5mb"), Some(1572864.0));
+        assert_eq!(parse_number("0x10kb"), Some(16384.0));
+        assert_eq!(parse_number(""), Some(0.0));
+        assert_eq!(parse_number("   "), Some(0.0));
+        assert_eq!(parse_number("-Infinity"), Some(f64::NEG_INFINITY));
+
+        for text in [
+            "1,000", "1,5", "12abc", "kb", "0x", "0xg", "inf", "1 2", "--1", "0x+1", "e5",
+        ] {
+            assert_eq!(parse_number(text), None, "{}", text);
+        }
+    }
+
+    #[test]
+    fn test_cast_to_string_and_array() {
+        assert_eq!(
+            cast_value(
+                "string",
+                Value::Array(vec![Value::Number(1.0), Value::Number(2.0)].into())
+            ),
+            Ok(Value::String("1 2".into()))
+        );
+        assert_eq!(
+            cast_value("array", Value::Number(1.0)),
+            Ok(Value::Array(vec![Value::Number(1.0)].into()))
+        );
+        assert_eq!(
+            cast_value(
+                "int[]",
+                Value::Array(vec![Value::String("1".into())].into())
+            ),
+            Ok(Value::Array(vec![Value::Number(1.0)].into()))
+        );
+    }
+
+    #[test]
+    fn test_cast_to_enum() {
+        let mut types = TypeRegistry::new();
+        types.define_enum(&EnumDef {
+            name: "Color".to_string(),
+            members: vec![("Red".to_string(), None), ("Green".to_string(), None)],
+        });
+        let green = Value::EnumValue {
+            type_name: "Color".to_string(),
+            name: "Green".to_string(),
+            value: 1,
+        };
+
+        assert_eq!(
+            cast("Color", Value::String("green".into()), &types),
+            Ok(green.clone())
+        );
+        assert_eq!(cast("Color", Value::Number(1.0), &types), Ok(green));
+        assert!(cast("Color", Value::Number(7.0), &types).is_err());
+    }
+
+    #[test]
+    fn test_cast_to_unknown_type() {
+        let error = cast_value("Nope", Value::Null).unwrap_err();
+        assert_eq!(error.to_string(), "Unable to find type [Nope]");
+        assert_eq!(error.error_id(), "TypeNotFound");
+        assert_eq!(error.target(), Some("Nope"));
+    }
+}
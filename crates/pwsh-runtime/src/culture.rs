@@ -0,0 +1,255 @@
+/// The separators, names and patterns a culture formats numbers and dates
+/// with. Only a few cultures are built in: the invariant culture, en-US,
+/// en-GB, de-DE and fr-FR. Date patterns use the .NET custom format syntax
+/// (`M/d/yyyy`, `dddd, MMMM d, yyyy`) and are expanded by the formatter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Culture {
+    /// The culture name, e.g. "en-US" (empty for the invariant culture)
+    pub name: &'static str,
+    pub decimal_separator: &'static str,
+    pub group_separator: &'static str,
+    pub currency_symbol: &'static str,
+    /// Whether the currency symbol follows the amount, as in `1.234,57 €`
+    pub currency_after: bool,
+    /// What `/` in a custom date pattern stands for
+    pub date_separator: &'static str,
+    pub short_date_pattern: &'static str,
+    pub long_date_pattern: &'static str,
+    pub short_time_pattern: &'static str,
+    pub long_time_pattern: &'static str,
+    pub month_day_pattern: &'static str,
+    pub year_month_pattern: &'static str,
+    pub am_designator: &'static str,
+    pub pm_designator: &'static str,
+    pub month_names: [&'static str; 12],
+    pub abbreviated_month_names: [&'static str; 12],
+    /// Day names, starting with Sunday
+    pub day_names: [&'static str; 7],
+    pub abbreviated_day_names: [&'static str; 7],
+}
+
+const ENGLISH_MONTHS: [&str; 12] = [
+    "January",
+    "February",
+    "March",
+    "April",
+    "May",
+    "June",
+    "July",
+    "August",
+    "September",
+    "October",
+    "November",
+    "December",
+];
+const ENGLISH_MONTHS_SHORT: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+const ENGLISH_DAYS: [&str; 7] = [
+    "Sunday",
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+];
+const ENGLISH_DAYS_SHORT: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+
+const INVARIANT: Culture = Culture {
+    name: "",
+    decimal_separator: ".",
+    group_separator: ",",
+    currency_symbol: "¤",
+    currency_after: false,
+    date_separator: "/",
+    short_date_pattern: "MM/dd/yyyy",
+    long_date_pattern: "dddd, dd MMMM yyyy",
+    short_time_pattern: "HH:mm",
+    long_time_pattern: "HH:mm:ss",
+    month_day_pattern: "MMMM dd",
+    year_month_pattern: "yyyy MMMM",
+    am_designator: "AM",
+    pm_designator: "PM",
+    month_names: ENGLISH_MONTHS,
+    abbreviated_month_names: ENGLISH_MONTHS_SHORT,
+    day_names: ENGLISH_DAYS,
+    abbreviated_day_names: ENGLISH_DAYS_SHORT,
+};
+
+const EN_US: Culture = Culture {
+    name: "en-US",
+    currency_symbol: "$",
+    short_date_pattern: "M/d/yyyy",
+    long_date_pattern: "dddd, MMMM d, yyyy",
+    short_time_pattern: "h:mm tt",
+    long_time_pattern: "h:mm:ss tt",
+    month_day_pattern: "MMMM d",
+    year_month_pattern: "MMMM yyyy",
+    ..INVARIANT
+};
+
+const EN_GB: Culture = Culture {
+    name: "en-GB",
+    currency_symbol: "£",
+    short_date_pattern: "dd/MM/yyyy",
+    long_date_pattern: "dddd, d MMMM yyyy",
+    month_day_pattern: "d MMMM",
+    year_month_pattern: "MMMM yyyy",
+    am_designator: "am",
+    pm_designator: "pm",
+    ..INVARIANT
+};
+
+const DE_DE: Culture = Culture {
+    name: "de-DE",
+    decimal_separator: ",",
+    group_separator: ".",
+    currency_symbol: "€",
+    currency_after: true,
+    date_separator: ".",
+    short_date_pattern: "dd.MM.yyyy",
+    long_date_pattern: "dddd, d. MMMM yyyy",
+    month_day_pattern: "d. MMMM",
+    year_month_pattern: "MMMM yyyy",
+    month_names: [
+        "Januar",
+        "Februar",
+        "März",
+        "April",
+        "Mai",
+        "Juni",
+        "Juli",
+        "August",
+        "September",
+        "Oktober",
+        "November",
+        "Dezember",
+    ],
+    abbreviated_month_names: [
+        "Jan.", "Feb.", "März", "Apr.", "Mai", "Juni", "Juli", "Aug.", "Sept.", "Okt.", "Nov.",
+        "Dez.",
+    ],
+    day_names: [
+        "Sonntag",
+        "Montag",
+        "Dienstag",
+        "Mittwoch",
+        "Donnerstag",
+        "Freitag",
+        "Samstag",
+    ],
+    abbreviated_day_names: ["So.", "Mo.", "Di.", "Mi.", "Do.", "Fr.", "Sa."],
+    ..INVARIANT
+};
+
+const FR_FR: Culture = Culture {
+    name: "fr-FR",
+    decimal_separator: ",",
+    group_separator: "\u{202f}",
+    currency_symbol: "€",
+    currency_after: true,
+    short_date_pattern: "dd/MM/yyyy",
+    long_date_pattern: "dddd d MMMM yyyy",
+    month_day_pattern: "d MMMM",
+    year_month_pattern: "MMMM yyyy",
+    month_names: [
+        "janvier",
+        "février",
+        "mars",
+        "avril",
+        "mai",
+        "juin",
+        "juillet",
+        "août",
+        "septembre",
+        "octobre",
+        "novembre",
+        "décembre",
+    ],
+    abbreviated_month_names: [
+        "janv.", "févr.", "mars", "avr.", "mai", "juin", "juil.", "août", "sept.", "oct.", "nov.",
+        "déc.",
+    ],
+    day_names: [
+        "dimanche", "lundi", "mardi", "mercredi", "jeudi", "vendredi", "samedi",
+    ],
+    abbreviated_day_names: ["dim.", "lun.", "mar.", "mer.", "jeu.", "ven.", "sam."],
+    ..INVARIANT
+};
+
+const CULTURES: [&Culture; 5] = [&INVARIANT, &EN_US, &EN_GB, &DE_DE, &FR_FR];
+
+impl Culture {
+    /// The culture-independent culture, for machine-readable output
+    pub fn invariant() -> Self {
+        INVARIANT
+    }
+
+    /// Look up a built-in culture by name, ignoring case ("de-de" finds
+    /// de-DE). An empty name or "Invariant" gives the invariant culture.
+    pub fn from_name(name: &str) -> Option<Self> {
+        if name.eq_ignore_ascii_case("invariant") {
+            return Some(INVARIANT);
+        }
+        let name = name.replace('_', "-");
+        CULTURES
+            .iter()
+            .find(|culture| culture.name.eq_ignore_ascii_case(&name))
+            .map(|culture| (*culture).clone())
+    }
+
+    /// The names of the built-in cultures
+    pub fn names() -> Vec<&'static str> {
+        CULTURES.iter().map(|culture| culture.name).collect()
+    }
+
+    /// Rewrite a number formatted with `.` and `,` to use this culture's
+    /// decimal and group separators
+    pub(crate) fn localize_number(&self, text: &str) -> String {
+        if self.decimal_separator == "." && self.group_separator == "," {
+            return text.to_string();
+        }
+        let mut localized = String::with_capacity(text.len());
+        for ch in text.chars() {
+            match ch {
+                '.' => localized.push_str(self.decimal_separator),
+                ',' => localized.push_str(self.group_separator),
+                other => localized.push(other),
+            }
+        }
+        localized
+    }
+}
+
+impl Default for Culture {
+    /// en-US, matching how values display when no culture is chosen
+    fn default() -> Self {
+        EN_US
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_name() {
+        assert_eq!(Culture::from_name("de-de").unwrap().name, "de-DE");
+        assert_eq!(Culture::from_name("en_GB").unwrap().name, "en-GB");
+        assert_eq!(Culture::from_name("").unwrap(), Culture::invariant());
+        assert_eq!(
+            Culture::from_name("Invariant").unwrap(),
+            Culture::invariant()
+        );
+        assert!(Culture::from_name("xx-XX").is_none());
+        assert_eq!(Culture::default().name, "en-US");
+    }
+
+    #[test]
+    fn test_localize_number() {
+        let german = Culture::from_name("de-DE").unwrap();
+        assert_eq!(german.localize_number("1,234.5"), "1.234,5");
+        assert_eq!(Culture::default().localize_number("1,234.5"), "1,234.5");
+    }
+}
@@ -0,0 +1,483 @@
+/// The script debugger: breakpoints, stepping, and the commands of the debug prompt
+use crate::callstack::CallFrame;
+use crate::property_map::PropertyMap;
+use crate::value::{ScriptBlock, Value};
+use crate::wildcard::WildcardPattern;
+use pwsh_parser::Span;
+use std::path::{Path, PathBuf};
+
+/// The prompt the debugger reads its commands with
+pub const DEBUG_PROMPT: &str = "[DBG]: PS> ";
+
+/// What the debugger says when it stops
+pub const DEBUG_BANNER: &str = "Entering debug mode. Use h or ? for help.";
+
+/// The debug prompt's help, as `h` or `?` shows it
+pub const DEBUG_HELP: &str = " s, stepInto         Single step (step into functions, scripts, etc.)
+ v, stepOver         Step to next statement (step over functions, scripts, etc.)
+ o, stepOut          Step out of the current function, script, etc.
+
+ c, continue         Continue operation
+ q, quit             Stop operation and exit the debugger
+
+ k, Get-PSCallStack  Display call stack
+
+ l, list             List source code for the current script.
+
+ <enter>             Repeat last command if it was stepInto, stepOver or list
+
+ ?, h                Displays this help message.";
+
+/// Which accesses to a variable stop at its breakpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VariableAccess {
+    Read,
+    Write,
+    ReadWrite,
+}
+
+impl VariableAccess {
+    /// A -Mode value, ignoring case
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "read" => Some(VariableAccess::Read),
+            "write" => Some(VariableAccess::Write),
+            "readwrite" => Some(VariableAccess::ReadWrite),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            VariableAccess::Read => "Read",
+            VariableAccess::Write => "Write",
+            VariableAccess::ReadWrite => "ReadWrite",
+        }
+    }
+
+    /// Whether a breakpoint in this mode stops at `access`
+    fn covers(self, access: VariableAccess) -> bool {
+        self == VariableAccess::ReadWrite || self == access
+    }
+}
+
+/// What a breakpoint stops at
+#[derive(Debug, Clone, PartialEq)]
+pub enum BreakpointKind {
+    /// A statement starting on a line of the script, or at a column of it
+    /// when `column` isn't 0
+    Line { line: usize, column: usize },
+    /// A call to a command whose name matches the wildcard pattern
+    Command(String),
+    /// An access to the variable, named without the `$`
+    Variable { name: String, mode: VariableAccess },
+}
+
+/// A breakpoint, as Set-PSBreakpoint makes it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Breakpoint {
+    pub id: usize,
+    pub kind: BreakpointKind,
+    /// The script it is in; line breakpoints always have one, and the
+    /// others only stop in code from it when they do
+    pub script: Option<PathBuf>,
+    /// Run when the breakpoint is hit, instead of stopping
+    pub action: Option<ScriptBlock>,
+    /// How many times it was hit
+    pub hit_count: usize,
+}
+
+impl Breakpoint {
+    /// Whether code from `script` is in the breakpoint's script. The
+    /// breakpoint's script was resolved when it was set; the code's is
+    /// resolved the same way before comparing.
+    fn in_script(&self, script: Option<&Path>) -> bool {
+        match (&self.script, script) {
+            (None, _) => true,
+            (Some(_), None) => false,
+            (Some(wanted), Some(script)) => {
+                wanted == script || resolve_script(script).is_some_and(|script| &script == wanted)
+            }
+        }
+    }
+
+    /// How the debugger names the breakpoint when it stops there:
+    /// `Line breakpoint on '/scripts/deploy.ps1:12'`
+    pub fn description(&self) -> String {
+        let script = self
+            .script
+            .as_ref()
+            .map(|path| path.display().to_string())
+            .unwrap_or_default();
+        match &self.kind {
+            BreakpointKind::Line { line, column: 0 } => {
+                format!("Line breakpoint on '{}:{}'", script, line)
+            }
+            BreakpointKind::Line { line, column } => {
+                format!("Line breakpoint on '{}:{}, {}'", script, line, column)
+            }
+            BreakpointKind::Command(name) => format!("Command breakpoint on '{}'", name),
+            BreakpointKind::Variable { name, mode } => {
+                format!(
+                    "Variable breakpoint on '${}' ({} access)",
+                    name,
+                    mode.name()
+                )
+            }
+        }
+    }
+
+    /// The breakpoint as Get-PSBreakpoint writes it: its Id, Script,
+    /// Line and Column, Command, or Variable and AccessMode, Action and
+    /// HitCount, typed as PowerShell's LineBreakpoint, CommandBreakpoint
+    /// or VariableBreakpoint
+    pub fn to_value(&self) -> Value {
+        let text = |s: &str| Value::String(s.into());
+        let mut properties = PropertyMap::new();
+        properties.insert("Id", Value::Number(self.id as f64));
+        properties.insert(
+            "Script",
+            self.script
+                .as_ref()
+                .map_or(Value::Null, |path| text(&path.display().to_string())),
+        );
+        let type_name = match &self.kind {
+            BreakpointKind::Line { line, column } => {
+                properties.insert("Line", Value::Number(*line as f64));
+                properties.insert("Column", Value::Number(*column as f64));
+                "LineBreakpoint"
+            }
+            BreakpointKind::Command(name) => {
+                properties.insert("Command", text(name));
+                "CommandBreakpoint"
+            }
+            BreakpointKind::Variable { name, mode } => {
+                properties.insert("Variable", text(name));
+                properties.insert("AccessMode", text(mode.name()));
+                "VariableBreakpoint"
+            }
+        };
+        properties.insert(
+            "Action",
+            self.action.clone().map_or(Value::Null, Value::ScriptBlock),
+        );
+        properties.insert("HitCount", Value::Number(self.hit_count as f64));
+        properties.insert_type_name(BREAKPOINT_TYPE);
+        properties.insert_type_name(&format!("System.Management.Automation.{}", type_name));
+        Value::Object(properties.into())
+    }
+}
+
+/// The type name every breakpoint object has
+pub const BREAKPOINT_TYPE: &str = "System.Management.Automation.Breakpoint";
+
+/// A script path as breakpoints compare them: absolute, with links resolved
+pub fn resolve_script(path: &Path) -> Option<PathBuf> {
+    std::fs::canonicalize(path).ok()
+}
+
+/// Where stepping stops next
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Step {
+    /// At the next statement anywhere
+    Into,
+    /// At the next statement in a frame no deeper than this
+    Over(usize),
+    /// At the next statement in a frame shallower than this
+    Out(usize),
+}
+
+/// A command typed at the debug prompt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DebugCommand {
+    StepInto,
+    StepOver,
+    StepOut,
+    Continue,
+    Quit,
+    CallStack,
+    List,
+    Help,
+}
+
+impl DebugCommand {
+    /// The debugger command a line at the debug prompt is, if it is one;
+    /// anything else runs as PowerShell
+    pub(crate) fn parse(line: &str) -> Option<Self> {
+        let command = match line.trim().to_ascii_lowercase().as_str() {
+            "s" | "stepinto" => DebugCommand::StepInto,
+            "v" | "stepover" => DebugCommand::StepOver,
+            "o" | "stepout" => DebugCommand::StepOut,
+            "c" | "continue" => DebugCommand::Continue,
+            "q" | "quit" | "exit" => DebugCommand::Quit,
+            "k" => DebugCommand::CallStack,
+            "l" | "list" => DebugCommand::List,
+            "h" | "?" => DebugCommand::Help,
+            _ => return None,
+        };
+        Some(command)
+    }
+
+    /// Whether an empty line repeats the command
+    pub(crate) fn repeats(self) -> bool {
+        matches!(
+            self,
+            DebugCommand::StepInto
+                | DebugCommand::StepOver
+                | DebugCommand::StepOut
+                | DebugCommand::List
+        )
+    }
+}
+
+/// The source around `frame`'s position, as the debugger's `l` lists it:
+/// numbered lines from five before it, with the current one starred
+pub(crate) fn list_source(frame: &CallFrame) -> Vec<String> {
+    let Some(source) = &frame.source else {
+        return vec!["There is no source to list.".to_string()];
+    };
+    let current = frame.position.start.line;
+    let first = current.saturating_sub(5).max(1);
+    source
+        .text
+        .lines()
+        .enumerate()
+        .skip(first - 1)
+        .take(16)
+        .map(|(index, line)| {
+            let number = index + 1;
+            let marker = if number == current { "*" } else { " " };
+            format!("{:>5}:{}  {}", number, marker, line)
+        })
+        .collect()
+}
+
+/// The breakpoints set in a session, and where stepping stops next
+#[derive(Debug, Default)]
+pub struct Debugger {
+    breakpoints: Vec<Breakpoint>,
+    next_id: usize,
+    pub(crate) step: Option<Step>,
+    /// Set while the debugger runs code itself, a breakpoint's action or
+    /// a command at the debug prompt, which must not stop again
+    pub(crate) suspended: bool,
+}
+
+impl Debugger {
+    /// Whether the evaluator needs to check for stops: there are
+    /// breakpoints or a step to make, and the debugger isn't running code
+    /// of its own
+    pub(crate) fn is_active(&self) -> bool {
+        !self.suspended && (!self.breakpoints.is_empty() || self.step.is_some())
+    }
+
+    /// Add a breakpoint, numbered after the last one added
+    pub fn add(
+        &mut self,
+        kind: BreakpointKind,
+        script: Option<PathBuf>,
+        action: Option<ScriptBlock>,
+    ) -> &Breakpoint {
+        self.breakpoints.push(Breakpoint {
+            id: self.next_id,
+            kind,
+            script,
+            action,
+            hit_count: 0,
+        });
+        self.next_id += 1;
+        self.breakpoints.last().expect("just added")
+    }
+
+    /// Remove a breakpoint; false when there isn't one with that id
+    pub fn remove(&mut self, id: usize) -> bool {
+        let count = self.breakpoints.len();
+        self.breakpoints.retain(|breakpoint| breakpoint.id != id);
+        self.breakpoints.len() < count
+    }
+
+    pub fn breakpoints(&self) -> &[Breakpoint] {
+        &self.breakpoints
+    }
+
+    /// The line breakpoint a statement at `span` in `script` hits. A
+    /// statement nested in the one before it on the same line, such as
+    /// the body of `if ($ok) { $x = 1 }`, doesn't hit it again.
+    pub(crate) fn line_hit(
+        &mut self,
+        script: Option<&Path>,
+        span: Span,
+        previous: Span,
+    ) -> Option<usize> {
+        let nested = previous.start <= span.start && span.end <= previous.end && previous != span;
+        if nested && previous.start.line == span.start.line {
+            return None;
+        }
+        self.hit(|breakpoint| match breakpoint.kind {
+            BreakpointKind::Line { line, column } => {
+                line == span.start.line
+                    && (column == 0 || column == span.start.column)
+                    && breakpoint.in_script(script)
+            }
+            _ => false,
+        })
+    }
+
+    /// The command breakpoint a call to `name` from `script` hits
+    pub(crate) fn command_hit(&mut self, script: Option<&Path>, name: &str) -> Option<usize> {
+        self.hit(|breakpoint| match &breakpoint.kind {
+            BreakpointKind::Command(pattern) => {
+                WildcardPattern::new(pattern).is_ok_and(|pattern| pattern.is_match(name))
+                    && breakpoint.in_script(script)
+            }
+            _ => false,
+        })
+    }
+
+    /// The variable breakpoint an access to `$name` from `script` hits;
+    /// scope qualifiers such as `script:` don't count
+    pub(crate) fn variable_hit(
+        &mut self,
+        script: Option<&Path>,
+        name: &str,
+        access: VariableAccess,
+    ) -> Option<usize> {
+        let name = name.rsplit(':').next().unwrap_or(name);
+        self.hit(|breakpoint| match &breakpoint.kind {
+            BreakpointKind::Variable {
+                name: watched,
+                mode,
+            } => {
+                watched.eq_ignore_ascii_case(name)
+                    && mode.covers(access)
+                    && breakpoint.in_script(script)
+            }
+            _ => false,
+        })
+    }
+
+    /// The first breakpoint `matches`, counted as hit
+    fn hit(&mut self, matches: impl Fn(&Breakpoint) -> bool) -> Option<usize> {
+        let index = self.breakpoints.iter().position(matches)?;
+        self.breakpoints[index].hit_count += 1;
+        Some(index)
+    }
+
+    pub(crate) fn breakpoint(&self, index: usize) -> &Breakpoint {
+        &self.breakpoints[index]
+    }
+
+    /// Whether stepping stops at a statement `depth` calls deep
+    pub(crate) fn step_stops_at(&self, depth: usize) -> bool {
+        match self.step {
+            Some(Step::Into) => true,
+            Some(Step::Over(stop)) => depth <= stop,
+            Some(Step::Out(stop)) => depth < stop,
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pwsh_lexer::Position;
+
+    fn span(line: usize, start: usize, end: usize) -> Span {
+        Span::new(Position::new(line, start), Position::new(line, end))
+    }
+
+    #[test]
+    fn test_line_breakpoints_hit_once_per_line() {
+        let mut debugger = Debugger::default();
+        let script = Path::new("deploy.ps1");
+        debugger.add(
+            BreakpointKind::Line { line: 3, column: 0 },
+            Some(script.to_path_buf()),
+            None,
+        );
+        assert_eq!(
+            debugger.line_hit(Some(script), span(2, 1, 9), Span::default()),
+            None
+        );
+        let statement = span(3, 1, 30);
+        assert_eq!(
+            debugger.line_hit(Some(script), statement, span(2, 1, 9)),
+            Some(0)
+        );
+        // The body of an if on the same line
+        assert_eq!(
+            debugger.line_hit(Some(script), span(3, 12, 20), statement),
+            None
+        );
+        // Other scripts and code without a script
+        assert_eq!(
+            debugger.line_hit(Some(Path::new("other.ps1")), statement, Span::default()),
+            None
+        );
+        assert_eq!(debugger.line_hit(None, statement, Span::default()), None);
+        assert_eq!(debugger.breakpoint(0).hit_count, 1);
+    }
+
+    #[test]
+    fn test_command_and_variable_breakpoints() {
+        let mut debugger = Debugger::default();
+        debugger.add(BreakpointKind::Command("Get-*".to_string()), None, None);
+        debugger.add(
+            BreakpointKind::Variable {
+                name: "total".to_string(),
+                mode: VariableAccess::Write,
+            },
+            None,
+            None,
+        );
+        assert_eq!(debugger.command_hit(None, "get-item"), Some(0));
+        assert_eq!(debugger.command_hit(None, "Set-Item"), None);
+        assert_eq!(
+            debugger.variable_hit(None, "script:Total", VariableAccess::Write),
+            Some(1)
+        );
+        assert_eq!(
+            debugger.variable_hit(None, "total", VariableAccess::Read),
+            None
+        );
+
+        assert!(debugger.remove(0));
+        assert!(!debugger.remove(0));
+        assert_eq!(debugger.breakpoints().len(), 1);
+        let added = debugger.add(BreakpointKind::Command("f".to_string()), None, None);
+        assert_eq!(added.id, 2);
+    }
+
+    #[test]
+    fn test_stepping_stops_by_depth() {
+        let mut debugger = Debugger {
+            step: Some(Step::Over(2)),
+            ..Debugger::default()
+        };
+        assert!(debugger.step_stops_at(1));
+        assert!(debugger.step_stops_at(2));
+        assert!(!debugger.step_stops_at(3));
+        debugger.step = Some(Step::Out(2));
+        assert!(!debugger.step_stops_at(2));
+        assert!(debugger.step_stops_at(1));
+    }
+
+    #[test]
+    fn test_debug_commands_and_listing() {
+        assert_eq!(DebugCommand::parse(" S "), Some(DebugCommand::StepInto));
+        assert_eq!(
+            DebugCommand::parse("stepOver"),
+            Some(DebugCommand::StepOver)
+        );
+        assert_eq!(DebugCommand::parse("$x"), None);
+
+        let source = crate::callstack::ScriptSource::new(None, "a\nb\nc");
+        let mut frame = CallFrame::new("f", Some(std::sync::Arc::new(source)));
+        frame.position = span(2, 1, 2);
+        assert_eq!(
+            list_source(&frame),
+            vec!["    1:   a", "    2:*  b", "    3:   c"]
+        );
+    }
+}
@@ -0,0 +1,259 @@
+/// Text renderings of nested values for Format-List and Format-Custom
+use crate::value::Value;
+
+/// How much of a nested value to show
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DisplayLimits {
+    /// How many levels of nested objects and collections to expand
+    pub depth: usize,
+    /// How many items of a collection to show before `…`, as
+    /// $FormatEnumerationLimit says; None shows them all
+    pub enumeration_limit: Option<usize>,
+}
+
+impl Default for DisplayLimits {
+    /// PowerShell's defaults: five levels, and four items of a collection
+    fn default() -> Self {
+        DisplayLimits {
+            depth: 5,
+            enumeration_limit: Some(4),
+        }
+    }
+}
+
+impl DisplayLimits {
+    /// A value on one line, as a property value in a list: collections as
+    /// `{1, 2, 3, 4…}` and objects as `@{Name=web; Port=80}`
+    pub fn inline(&self, value: &Value) -> String {
+        inline_at(value, self, self.depth)
+    }
+
+    /// The Format-List lines for a value: one `Name : Value` line per
+    /// property, with the names padded to line up. A value without
+    /// properties is a single line.
+    pub fn list_lines(&self, value: &Value) -> Vec<String> {
+        list_lines(value, self)
+    }
+
+    /// The Format-Custom lines for a value: objects as indented `class`
+    /// blocks and collections as `[` ... `]` blocks, expanded `depth` levels
+    /// deep; deeper values are shown on one line.
+    pub fn custom_lines(&self, value: &Value) -> Vec<String> {
+        let mut lines = Vec::new();
+        custom_at(value, self, self.depth, 0, &mut lines);
+        lines
+    }
+
+    /// The items of a collection to show, and whether some were left out
+    fn shown<'a>(&self, items: &'a [Value]) -> (&'a [Value], bool) {
+        match self.enumeration_limit {
+            Some(limit) if items.len() > limit => (&items[..limit], true),
+            _ => (items, false),
+        }
+    }
+}
+
+fn inline_at(value: &Value, limits: &DisplayLimits, depth: usize) -> String {
+    match value {
+        Value::Array(_) if depth == 0 => "{…}".to_string(),
+        Value::Array(items) => {
+            let items = items.read();
+            let (shown, more) = limits.shown(&items);
+            let parts: Vec<String> = shown
+                .iter()
+                .map(|item| inline_at(item, limits, depth - 1))
+                .collect();
+            format!("{{{}{}}}", parts.join(", "), if more { "…" } else { "" })
+        }
+        Value::Object(_) | Value::Instance { .. } if depth == 0 => "@{…}".to_string(),
+        other => match other.properties() {
+            Some(props) => {
+                let parts: Vec<String> = props
+                    .iter()
+                    .map(|(name, value)| {
+                        format!("{}={}", name, inline_at(value, limits, depth - 1))
+                    })
+                    .collect();
+                format!("@{{{}}}", parts.join("; "))
+            }
+            None => other.to_string(),
+        },
+    }
+}
+
+fn list_lines(value: &Value, limits: &DisplayLimits) -> Vec<String> {
+    let Some(props) = value.properties().filter(|props| !props.is_empty()) else {
+        return value.to_string().lines().map(String::from).collect();
+    };
+
+    let width = props
+        .keys()
+        .map(|name| name.to_string().chars().count())
+        .max()
+        .unwrap_or(0);
+    let mut lines = Vec::new();
+    for (name, value) in props.iter() {
+        let text = inline_at(value, limits, limits.depth);
+        let mut text_lines = text.lines();
+        let first = text_lines.next().unwrap_or("");
+        lines.push(format!(
+            "{:<width$} : {}",
+            name.to_string(),
+            first,
+            width = width
+        ));
+        // Continuation lines of a multi-line value stay in the value column
+        for line in text_lines {
+            lines.push(format!("{:width$}   {}", "", line, width = width));
+        }
+    }
+    lines
+}
+
+fn custom_at(
+    value: &Value,
+    limits: &DisplayLimits,
+    depth: usize,
+    indent: usize,
+    lines: &mut Vec<String>,
+) {
+    let pad = " ".repeat(indent);
+    match value {
+        Value::Object(_) | Value::Instance { .. } | Value::Array(_) if depth == 0 => {
+            lines.push(format!("{}{}", pad, inline_at(value, limits, 0)));
+        }
+        Value::Object(_) | Value::Instance { .. } => {
+            let Some(props) = value.properties() else {
+                return;
+            };
+            let class = match value {
+                Value::Instance { class_name, .. } => class_name.as_str(),
+                _ => "PSCustomObject",
+            };
+            lines.push(format!("{}class {}", pad, class));
+            lines.push(format!("{}{{", pad));
+            for (name, value) in props.iter() {
+                if matches!(
+                    value,
+                    Value::Object(_) | Value::Instance { .. } | Value::Array(_)
+                ) {
+                    lines.push(format!("{}  {} =", pad, name));
+                    custom_at(value, limits, depth - 1, indent + 4, lines);
+                } else {
+                    lines.push(format!("{}  {} = {}", pad, name, value));
+                }
+            }
+            lines.push(format!("{}}}", pad));
+        }
+        Value::Array(items) => {
+            let items = items.read();
+            let (shown, more) = limits.shown(&items);
+            lines.push(format!("{}[", pad));
+            for item in shown {
+                custom_at(item, limits, depth - 1, indent + 2, lines);
+            }
+            if more {
+                lines.push(format!("{}  …", pad));
+            }
+            lines.push(format!("{}]", pad));
+        }
+        other => lines.push(format!("{}{}", pad, other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::property_map::PropertyMap;
+
+    fn object(props: Vec<(&str, Value)>) -> Value {
+        let mut map = PropertyMap::new();
+        for (name, value) in props {
+            map.insert(name.to_string(), value);
+        }
+        Value::Object(map.into())
+    }
+
+    fn numbers(n: usize) -> Value {
+        Value::Array(
+            (1..=n)
+                .map(|i| Value::Number(i as f64))
+                .collect::<Vec<_>>()
+                .into(),
+        )
+    }
+
+    fn sample() -> Value {
+        object(vec![
+            ("Name", Value::String("web".into())),
+            ("Ports", numbers(6)),
+            ("Config", object(vec![("Tls", Value::Boolean(true))])),
+        ])
+    }
+
+    #[test]
+    fn test_inline_truncates_collections() {
+        let limits = DisplayLimits::default();
+        assert_eq!(limits.inline(&numbers(6)), "{1, 2, 3, 4…}");
+        assert_eq!(limits.inline(&numbers(2)), "{1, 2}");
+
+        let unlimited = DisplayLimits {
+            enumeration_limit: None,
+            ..limits
+        };
+        assert_eq!(unlimited.inline(&numbers(6)), "{1, 2, 3, 4, 5, 6}");
+        let shallow = DisplayLimits { depth: 1, ..limits };
+        assert_eq!(
+            shallow.inline(&sample()),
+            "@{Name=web; Ports={…}; Config=@{…}}"
+        );
+    }
+
+    #[test]
+    fn test_list_lines_align_names() {
+        assert_eq!(
+            DisplayLimits::default().list_lines(&sample()),
+            vec![
+                "Name   : web",
+                "Ports  : {1, 2, 3, 4…}",
+                "Config : @{Tls=True}",
+            ]
+        );
+        assert_eq!(
+            DisplayLimits::default().list_lines(&Value::Number(3.0)),
+            vec!["3"]
+        );
+    }
+
+    #[test]
+    fn test_custom_lines_indent_nested_values() {
+        let limits = DisplayLimits {
+            enumeration_limit: Some(2),
+            ..DisplayLimits::default()
+        };
+        assert_eq!(
+            limits.custom_lines(&sample()),
+            vec![
+                "class PSCustomObject",
+                "{",
+                "  Name = web",
+                "  Ports =",
+                "    [",
+                "      1",
+                "      2",
+                "      …",
+                "    ]",
+                "  Config =",
+                "    class PSCustomObject",
+                "    {",
+                "      Tls = True",
+                "    }",
+                "}",
+            ]
+        );
+
+        let shallow = DisplayLimits { depth: 1, ..limits };
+        assert_eq!(shallow.custom_lines(&sample())[3], "  Ports =");
+        assert_eq!(shallow.custom_lines(&sample())[4], "    {…}");
+    }
+}
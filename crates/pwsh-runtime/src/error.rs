@@ -1,8 +1,262 @@
 /// Runtime error types
 use std::fmt;
+use std::io;
 
+use crate::limits::Limit;
+use crate::property_map::PropertyMap;
 use crate::value::Value;
 
+/// What kind of failure an error is, as in PowerShell's ErrorCategory
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCategory {
+    NotSpecified,
+    InvalidArgument,
+    InvalidData,
+    InvalidOperation,
+    InvalidType,
+    ObjectNotFound,
+    PermissionDenied,
+    ResourceExists,
+    ReadError,
+    WriteError,
+    /// A script stopped on purpose, as with `throw`
+    OperationStopped,
+    /// Refused by a security setting, such as the execution policy
+    SecurityError,
+}
+
+impl ErrorCategory {
+    /// Every category, for looking one up by name
+    pub const ALL: [ErrorCategory; 12] = [
+        ErrorCategory::NotSpecified,
+        ErrorCategory::InvalidArgument,
+        ErrorCategory::InvalidData,
+        ErrorCategory::InvalidOperation,
+        ErrorCategory::InvalidType,
+        ErrorCategory::ObjectNotFound,
+        ErrorCategory::PermissionDenied,
+        ErrorCategory::ResourceExists,
+        ErrorCategory::ReadError,
+        ErrorCategory::WriteError,
+        ErrorCategory::OperationStopped,
+        ErrorCategory::SecurityError,
+    ];
+
+    /// The category with this name, ignoring case: `writeerror`
+    pub fn from_name(name: &str) -> Option<ErrorCategory> {
+        ErrorCategory::ALL
+            .into_iter()
+            .find(|category| category.to_string().eq_ignore_ascii_case(name))
+    }
+}
+
+impl fmt::Display for ErrorCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+impl From<io::ErrorKind> for ErrorCategory {
+    fn from(kind: io::ErrorKind) -> Self {
+        match kind {
+            io::ErrorKind::NotFound => ErrorCategory::ObjectNotFound,
+            io::ErrorKind::PermissionDenied => ErrorCategory::PermissionDenied,
+            io::ErrorKind::AlreadyExists => ErrorCategory::ResourceExists,
+            io::ErrorKind::InvalidInput => ErrorCategory::InvalidArgument,
+            io::ErrorKind::InvalidData | io::ErrorKind::UnexpectedEof => ErrorCategory::InvalidData,
+            _ => ErrorCategory::NotSpecified,
+        }
+    }
+}
+
+/// A failure described for scripts and hosts to match on: its category, a
+/// FullyQualifiedErrorId such as `PathNotFound,Get-ChildItem`, what it
+/// happened to, and the io error behind it
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorRecord {
+    pub message: String,
+    pub category: ErrorCategory,
+    pub error_id: String,
+    /// The path, command or value the error is about
+    pub target: Option<String>,
+    /// The object the error is about, when it is more than its text: what
+    /// `throw` was given
+    pub target_object: Option<Value>,
+    /// Kind of the underlying io error, if there was one
+    pub io_kind: Option<io::ErrorKind>,
+    /// The error this one was raised because of, if any
+    pub inner: Option<Box<RuntimeError>>,
+}
+
+impl ErrorRecord {
+    pub fn new(category: ErrorCategory, error_id: &str, message: impl Into<String>) -> Self {
+        ErrorRecord {
+            message: message.into(),
+            category,
+            error_id: error_id.to_string(),
+            target: None,
+            target_object: None,
+            io_kind: None,
+            inner: None,
+        }
+    }
+
+    /// A parameter value the command can't use, e.g. with the id
+    /// `InvalidCount,Get-History`
+    pub fn invalid_argument(error_id: &str, message: impl Into<String>) -> Self {
+        ErrorRecord::new(ErrorCategory::InvalidArgument, error_id, message)
+    }
+
+    /// An io failure; the category follows from the io error kind
+    pub fn io(error: &io::Error, error_id: &str, message: impl Into<String>) -> Self {
+        ErrorRecord {
+            io_kind: Some(error.kind()),
+            ..ErrorRecord::new(error.kind().into(), error_id, message)
+        }
+    }
+
+    /// A file system operation in `command` that failed on `path`; the id
+    /// follows the io error kind, e.g. `PathNotFound,Get-Content`
+    pub fn file_system(
+        error: &io::Error,
+        command: &str,
+        path: impl fmt::Display,
+        message: impl Into<String>,
+    ) -> Self {
+        let id = match error.kind() {
+            io::ErrorKind::NotFound => "PathNotFound",
+            io::ErrorKind::PermissionDenied => "UnauthorizedAccess",
+            io::ErrorKind::AlreadyExists => "ItemExists",
+            _ => "IOError",
+        };
+        ErrorRecord::io(error, &format!("{},{}", id, command), message).with_target(path)
+    }
+
+    pub fn with_target(mut self, target: impl fmt::Display) -> Self {
+        self.target = Some(target.to_string());
+        self
+    }
+
+    /// The error that caused this one, shown as its Exception's
+    /// InnerException
+    pub fn with_inner(mut self, inner: RuntimeError) -> Self {
+        self.inner = Some(Box::new(inner));
+        self
+    }
+
+    /// The error `throw` raises for a value: an error record is thrown
+    /// again as it is; anything else becomes an OperationStopped error with
+    /// the value's text as its message and the value as its TargetObject.
+    /// A string is also the error's id, as in PowerShell.
+    pub fn thrown(value: Value) -> Self {
+        if let Some(record) = ErrorRecord::from_value(&value) {
+            return record;
+        }
+        let message = value.to_string();
+        let error_id = match &value {
+            Value::String(_) => message.as_str(),
+            _ => "RuntimeException",
+        };
+        let mut record = ErrorRecord::new(ErrorCategory::OperationStopped, error_id, &message)
+            .with_target(&message);
+        record.target_object = Some(value);
+        record
+    }
+
+    /// Read back an ErrorRecord object as scripts see it (`$_` in a catch
+    /// block, `$error[0]`), or None if `value` isn't one
+    pub fn from_value(value: &Value) -> Option<Self> {
+        let Value::Instance {
+            class_name,
+            properties,
+        } = value
+        else {
+            return None;
+        };
+        if class_name != "ErrorRecord" {
+            return None;
+        }
+        let properties = properties.read();
+        let text = |value: Option<Value>| value.map(|v| v.to_string()).unwrap_or_default();
+        let category_info = properties.get("CategoryInfo");
+        let category = category_info
+            .and_then(|info| info.get_property("Category"))
+            .and_then(|category| ErrorCategory::from_name(&category.to_string()))
+            .unwrap_or(ErrorCategory::NotSpecified);
+        let mut record = ErrorRecord::new(
+            category,
+            &text(properties.get("FullyQualifiedErrorId").cloned()),
+            text(
+                properties
+                    .get("Exception")
+                    .and_then(|exception| exception.get_property("Message")),
+            ),
+        );
+        match properties.get("TargetObject") {
+            None | Some(Value::Null) => {}
+            Some(target) => {
+                record.target = Some(target.to_string());
+                record.target_object = Some(target.clone());
+            }
+        }
+        Some(record)
+    }
+
+    /// A type name that isn't a built-in, class or enum
+    pub(crate) fn type_not_found(type_name: &str) -> Self {
+        ErrorRecord::new(
+            ErrorCategory::InvalidOperation,
+            "TypeNotFound",
+            format!("Unable to find type [{}]", type_name),
+        )
+        .with_target(type_name)
+    }
+}
+
+/// What a cmdlet does with a non-terminating error, from its -ErrorAction or
+/// $ErrorActionPreference
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ActionPreference {
+    /// Write it to the error stream and $error, then go on (the default)
+    #[default]
+    Continue,
+    /// Add it to $error only
+    SilentlyContinue,
+    /// Drop it
+    Ignore,
+    /// Fail the command with it
+    Stop,
+}
+
+impl ActionPreference {
+    /// Parse a preference name, ignoring case: -ErrorAction SilentlyContinue
+    pub fn from_value(value: &Value) -> Result<Self, RuntimeError> {
+        let name = value.to_string();
+        match name.to_ascii_lowercase().as_str() {
+            "continue" => Ok(ActionPreference::Continue),
+            "silentlycontinue" => Ok(ActionPreference::SilentlyContinue),
+            "ignore" => Ok(ActionPreference::Ignore),
+            "stop" => Ok(ActionPreference::Stop),
+            _ => Err(ErrorRecord::new(
+                ErrorCategory::InvalidArgument,
+                "InvalidActionPreference",
+                format!(
+                    "Cannot convert '{}' to an action preference. Use one of: Continue, SilentlyContinue, Ignore, Stop",
+                    name
+                ),
+            )
+            .with_target(name)
+            .into()),
+        }
+    }
+}
+
+impl From<ErrorRecord> for RuntimeError {
+    fn from(record: ErrorRecord) -> Self {
+        RuntimeError::Record(Box::new(record))
+    }
+}
+
 /// Errors that can occur during runtime evaluation
 #[derive(Debug, Clone, PartialEq)]
 pub enum RuntimeError {
@@ -22,10 +276,153 @@ pub enum RuntimeError {
     ReturnOutsideFunction,
     /// Cannot access property on non-object
     InvalidPropertyAccess(String),
-    /// Cmdlet or function not found
-    UndefinedFunction(String),
+    /// Cmdlet or function not found, with similarly named commands
+    UndefinedFunction {
+        name: String,
+        suggestions: Vec<String>,
+    },
     /// Early return from function (internal use only)
     EarlyReturn(Value),
+    /// `break` out of the innermost loop, carrying the output of the
+    /// iteration so far (internal use only)
+    Break(Value),
+    /// `continue` with the next iteration of the innermost loop, carrying
+    /// the output of this one so far (internal use only)
+    Continue(Value),
+    /// A categorized error, mostly from cmdlets
+    Record(Box<ErrorRecord>),
+    /// The script was stopped from outside, as by quitting at the debug
+    /// prompt. try/catch lets it through, though finally blocks still run.
+    Stopped,
+    /// The script reached one of the host's sandbox limits; like
+    /// `Stopped`, try/catch lets it through
+    LimitExceeded(Limit),
+}
+
+impl RuntimeError {
+    /// The error's category; errors without a record get one from their kind
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            RuntimeError::Record(record) => record.category,
+            RuntimeError::UndefinedVariable(_) | RuntimeError::UndefinedFunction { .. } => {
+                ErrorCategory::ObjectNotFound
+            }
+            RuntimeError::TypeMismatch { .. } => ErrorCategory::InvalidType,
+            RuntimeError::InvalidOperation(_)
+            | RuntimeError::InvalidPropertyAccess(_)
+            | RuntimeError::ReturnOutsideFunction => ErrorCategory::InvalidOperation,
+            RuntimeError::Stopped | RuntimeError::LimitExceeded(_) => {
+                ErrorCategory::OperationStopped
+            }
+            RuntimeError::DivisionByZero
+            | RuntimeError::EarlyReturn(_)
+            | RuntimeError::Break(_)
+            | RuntimeError::Continue(_) => ErrorCategory::NotSpecified,
+        }
+    }
+
+    /// The FullyQualifiedErrorId PowerShell would report
+    pub fn error_id(&self) -> &str {
+        match self {
+            RuntimeError::Record(record) => &record.error_id,
+            RuntimeError::UndefinedVariable(_) => "VariableIsUndefined",
+            RuntimeError::UndefinedFunction { .. } => "CommandNotFoundException",
+            RuntimeError::TypeMismatch { .. } => "InvalidCastException",
+            RuntimeError::DivisionByZero => "RuntimeException",
+            RuntimeError::InvalidOperation(_) => "InvalidOperation",
+            RuntimeError::InvalidPropertyAccess(_) => "PropertyNotFound",
+            RuntimeError::Stopped => "PipelineStopped",
+            RuntimeError::LimitExceeded(limit) => limit.error_id(),
+            RuntimeError::ReturnOutsideFunction
+            | RuntimeError::EarlyReturn(_)
+            | RuntimeError::Break(_)
+            | RuntimeError::Continue(_) => "InvalidOperation",
+        }
+    }
+
+    /// Whether this is a return, break or continue on its way to the
+    /// function or loop it ends, rather than a failure. try/catch lets these
+    /// through, though finally blocks still run.
+    pub fn is_control_flow(&self) -> bool {
+        matches!(
+            self,
+            RuntimeError::EarlyReturn(_) | RuntimeError::Break(_) | RuntimeError::Continue(_)
+        )
+    }
+
+    /// Whether the error stops the script outright, as quitting the
+    /// debugger or reaching a sandbox limit does: try/catch lets it
+    /// through, though finally blocks still run
+    pub fn stops_script(&self) -> bool {
+        matches!(self, RuntimeError::Stopped | RuntimeError::LimitExceeded(_))
+    }
+
+    /// What the error is about: a path, command name or variable
+    pub fn target(&self) -> Option<&str> {
+        match self {
+            RuntimeError::Record(record) => record.target.as_deref(),
+            RuntimeError::UndefinedVariable(name) => Some(name),
+            RuntimeError::UndefinedFunction { name, .. } => Some(name),
+            _ => None,
+        }
+    }
+
+    /// Kind of the io error behind this one, if any
+    pub fn io_kind(&self) -> Option<io::ErrorKind> {
+        match self {
+            RuntimeError::Record(record) => record.io_kind,
+            _ => None,
+        }
+    }
+
+    /// The error as the ErrorRecord object scripts see in $error
+    pub fn to_value(&self) -> Value {
+        let text = |s: &str| Value::String(s.into());
+        let target_name = self.target().map_or(Value::Null, text);
+        let target = match self {
+            RuntimeError::Record(record) => record.target_object.clone(),
+            _ => None,
+        }
+        .unwrap_or_else(|| target_name.clone());
+        Value::Instance {
+            class_name: "ErrorRecord".to_string(),
+            properties: PropertyMap::from([
+                ("Exception".to_string(), self.exception_value()),
+                (
+                    "CategoryInfo".to_string(),
+                    Value::Object(
+                        PropertyMap::from([
+                            ("Category".to_string(), text(&self.category().to_string())),
+                            ("TargetName".to_string(), target_name),
+                        ])
+                        .into(),
+                    ),
+                ),
+                ("FullyQualifiedErrorId".to_string(), text(self.error_id())),
+                ("TargetObject".to_string(), target),
+                // Where it happened and the calls it happened in, when
+                // the evaluator knows
+                ("InvocationInfo".to_string(), Value::Null),
+                ("ScriptStackTrace".to_string(), Value::Null),
+            ])
+            .into(),
+        }
+    }
+
+    /// The error's Exception as scripts see it: its Message, and the
+    /// InnerException of the error that caused it, if any
+    fn exception_value(&self) -> Value {
+        let mut exception = PropertyMap::from([(
+            "Message".to_string(),
+            Value::String(self.to_string().into()),
+        )]);
+        if let RuntimeError::Record(record) = self {
+            if let Some(inner) = &record.inner {
+                exception.insert("InnerException", inner.exception_value());
+            }
+        }
+        Value::Object(exception.into())
+    }
 }
 
 impl fmt::Display for RuntimeError {
@@ -56,8 +453,12 @@ impl fmt::Display for RuntimeError {
             RuntimeError::InvalidPropertyAccess(msg) => {
                 write!(f, "Invalid property access: {msg}")
             }
-            RuntimeError::UndefinedFunction(name) => {
-                write!(f, "The term '{name}' is not recognized as a cmdlet, function, or operable program")
+            RuntimeError::UndefinedFunction { name, suggestions } => {
+                write!(f, "The term '{name}' is not recognized as a cmdlet, function, or operable program")?;
+                if !suggestions.is_empty() {
+                    write!(f, ". Did you mean {}?", suggestions.join(", "))?;
+                }
+                Ok(())
             }
             RuntimeError::EarlyReturn(_) => {
                 write!(
@@ -65,8 +466,74 @@ impl fmt::Display for RuntimeError {
                     "Internal error: EarlyReturn should be handled by function call"
                 )
             }
+            RuntimeError::Break(_) => write!(f, "Break statement outside of a loop"),
+            RuntimeError::Continue(_) => write!(f, "Continue statement outside of a loop"),
+            RuntimeError::Record(record) => write!(f, "{}", record.message),
+            RuntimeError::Stopped => write!(f, "The pipeline has been stopped."),
+            RuntimeError::LimitExceeded(limit) => write!(f, "{}", limit),
         }
     }
 }
 
 impl std::error::Error for RuntimeError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_io_errors_are_categorized_by_kind() {
+        let error: RuntimeError = ErrorRecord::io(
+            &io::Error::from(io::ErrorKind::PermissionDenied),
+            "ItemAccessDenied,Get-Content",
+            "Access denied",
+        )
+        .with_target("/secret")
+        .into();
+
+        assert_eq!(error.category(), ErrorCategory::PermissionDenied);
+        assert_eq!(error.error_id(), "ItemAccessDenied,Get-Content");
+        assert_eq!(error.target(), Some("/secret"));
+        assert_eq!(error.io_kind(), Some(io::ErrorKind::PermissionDenied));
+        assert_eq!(error.to_string(), "Access denied");
+    }
+
+    #[test]
+    fn test_thrown_records_read_back_from_values() {
+        let original: RuntimeError = ErrorRecord::new(
+            ErrorCategory::ObjectNotFound,
+            "VariableNotFound,Get-Variable",
+            "Cannot find a variable with the name 'x'.",
+        )
+        .with_target("x")
+        .into();
+        let thrown: RuntimeError = ErrorRecord::thrown(original.to_value()).into();
+        assert_eq!(thrown.to_value(), original.to_value());
+
+        let thrown: RuntimeError = ErrorRecord::thrown(Value::Number(7.0)).into();
+        assert_eq!(thrown.category(), ErrorCategory::OperationStopped);
+        assert_eq!(thrown.error_id(), "RuntimeException");
+        assert_eq!(thrown.to_string(), "7");
+        assert_eq!(
+            thrown.to_value().get_property("TargetObject"),
+            Some(Value::Number(7.0))
+        );
+        assert_eq!(
+            ErrorCategory::from_name("writeERROR"),
+            Some(ErrorCategory::WriteError)
+        );
+    }
+
+    #[test]
+    fn test_plain_errors_have_a_category_and_id() {
+        let error = RuntimeError::UndefinedVariable("x".to_string());
+        assert_eq!(error.category(), ErrorCategory::ObjectNotFound);
+        assert_eq!(error.error_id(), "VariableIsUndefined");
+        assert_eq!(error.target(), Some("x"));
+        assert_eq!(RuntimeError::DivisionByZero.io_kind(), None);
+        assert_eq!(
+            RuntimeError::DivisionByZero.to_value().to_string(),
+            "Division by zero"
+        );
+    }
+}
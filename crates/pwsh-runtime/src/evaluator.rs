@@ -1,36 +1,787 @@
 /// Evaluator for PowerShell AST
-use crate::cmdlet::CmdletRegistry;
-use crate::error::RuntimeError;
-use crate::scope::ScopeStack;
-use crate::value::Value;
+use crate::ansi::{self, OutputRendering};
+use crate::builtin_types::BuiltinType;
+use crate::callstack::{self, CallFrame, ScriptSource, SCRIPT_BLOCK_COMMAND};
+use crate::clock::{Clock, SystemClock};
+use crate::cmdlet::{Cmdlet, CmdletContext, CmdletRegistry};
+use crate::compile::{CompiledBlock, Op};
+use crate::config::Configuration;
+use crate::convert;
+use crate::culture::Culture;
+use crate::debugger::{self, DebugCommand, Debugger, Step, VariableAccess};
+use crate::display::DisplayLimits;
+use crate::error::{ActionPreference, ErrorCategory, ErrorRecord, RuntimeError};
+use crate::format;
+use crate::hashtable;
+use crate::history::History;
+use crate::host::{ConsoleHost, Host};
+use crate::instrument::Instrumentation;
+use crate::language::LanguageMode;
+use crate::limits::{self, Capability, Limit, Limits};
+use crate::matching;
+use crate::module_path;
+use crate::native;
+use crate::pager;
+use crate::plugin::CmdletPack;
+use crate::policy::{ExecutionPolicy, LocalScripts, ScriptTrust};
+use crate::property_map::PropertyMap;
+use crate::psstyle::{self, FileInfoStyle, FormattingStyle};
+use crate::random::Random;
+use crate::scope::{self, ScopeStack};
+use crate::suggest;
+use crate::types::{self, TypeRegistry};
+use crate::value::{Function, Value};
+use crate::version;
+use crate::views::{FormatContext, FormatRegistry};
+use crate::wildcard::WildcardPattern;
+use pwsh_lexer::Lexer;
 use pwsh_parser::{
-    BinaryOperator, Block, Expression, Literal, Program, Statement, StringPart, UnaryOperator,
+    BinaryOperator, Block, ClassMethod, Expression, ExpressionKind, Literal, Parameter, Parser,
+    Program, Requirements, Span, Statement, StatementKind, StringPart, UnaryOperator,
 };
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Instant, SystemTime};
 
 /// Result type for evaluation
 pub type EvalResult = Result<Value, RuntimeError>;
 
+/// One step in an assignment target chain
+enum Accessor {
+    /// Element access with an evaluated index: [0], ["key"]
+    Index(Value),
+    /// Property access: .Name
+    Member(String),
+}
+
+/// Most errors kept in $error, newest first
+const MAX_ERROR_COUNT: usize = 256;
+
+/// The most memory repeating a string or array with `*` may take
+const MAX_REPEAT_BYTES: usize = 1 << 30;
+
+/// How much repeating with `*` builds between checks of the time limit
+const REPEAT_CHECK_BYTES: usize = 1 << 20;
+
+/// How deeply function, script block and method calls may nest by default,
+/// as in PowerShell
+const DEFAULT_MAX_CALL_DEPTH: usize = 1000;
+
+/// How deeply scopes may nest by default; every call and block adds one
+const DEFAULT_MAX_SCOPE_DEPTH: usize = 10_000;
+
+/// A call starts a new stack segment when less than this is left, which is
+/// more than one call level of a debug build uses
+const STACK_RED_ZONE: usize = 256 * 1024;
+const STACK_SEGMENT_SIZE: usize = 4 * 1024 * 1024;
+
+/// Run `f`, on a new stack segment if the thread's stack is nearly used up,
+/// so runaway recursion ends at the call depth limit instead of overflowing
+/// the stack
+#[cfg(not(target_arch = "wasm32"))]
+fn grow_stack<R>(f: impl FnOnce() -> R) -> R {
+    stacker::maybe_grow(STACK_RED_ZONE, STACK_SEGMENT_SIZE, f)
+}
+
+/// The browser's stack can't be extended; its own overflow check applies
+#[cfg(target_arch = "wasm32")]
+fn grow_stack<R>(f: impl FnOnce() -> R) -> R {
+    f()
+}
+
+/// The error for repeating a value `count` times when the result would
+/// take more memory than repetition may
+fn repeat_too_large(count: impl std::fmt::Display) -> RuntimeError {
+    RuntimeError::InvalidOperation(format!(
+        "Cannot repeat a value {} times: the result would be too large",
+        count
+    ))
+}
+
+/// What a run of statements wrote to the output, as one value: $null for
+/// nothing, a single value as it is, and several flattened into one array
+/// (arrays among them enumerated), as PowerShell streams a script's output
+fn collect_output(values: Vec<Value>) -> Value {
+    let mut values: Vec<Value> = values.into_iter().filter(|v| *v != Value::Null).collect();
+    if values.len() <= 1 {
+        return values.pop().unwrap_or(Value::Null);
+    }
+    let items: Vec<Value> = values
+        .into_iter()
+        .flat_map(|value| match value {
+            Value::Array(items) => items.unwrap_or_clone(),
+            other => vec![other],
+        })
+        .collect();
+    Value::Array(items.into())
+}
+
+/// Change the output a return, break or continue carries; other errors are
+/// passed on as they are
+fn map_carried_output(error: RuntimeError, map: impl FnOnce(Value) -> Value) -> RuntimeError {
+    match error {
+        RuntimeError::EarlyReturn(value) => RuntimeError::EarlyReturn(map(value)),
+        RuntimeError::Break(value) => RuntimeError::Break(map(value)),
+        RuntimeError::Continue(value) => RuntimeError::Continue(map(value)),
+        other => other,
+    }
+}
+
+/// The text of each item of a -join operand (a single value is one item),
+/// joined with `separator`
+fn join_items(value: Value, separator: &str) -> Value {
+    let items = match value {
+        Value::Array(items) => items.unwrap_or_clone(),
+        Value::Null => Vec::new(),
+        other => vec![other],
+    };
+    let text: Vec<String> = items.iter().map(|item| item.to_string()).collect();
+    Value::String(text.join(separator).into())
+}
+
+/// Split the text of each item of a -split operand, as one array of strings
+fn split_items(value: Value, split: impl Fn(&str) -> Vec<String>) -> Value {
+    let items = match value {
+        Value::Array(items) => items.unwrap_or_clone(),
+        other => vec![other],
+    };
+    let parts: Vec<Value> = items
+        .iter()
+        .flat_map(|item| split(&item.to_string()))
+        .map(|part| Value::String(part.into()))
+        .collect();
+    Value::Array(parts.into())
+}
+
+/// The state of `Evaluator::run_compiled`: the value stack, the foreach
+/// loops being run and the next instruction
+#[derive(Default)]
+struct Machine {
+    stack: Vec<Value>,
+    loops: Vec<LoopState>,
+    pc: usize,
+}
+
+/// A foreach loop being run by compiled code
+struct LoopState {
+    /// Items still to go
+    items: std::vec::IntoIter<Value>,
+    /// Output of the iterations so far
+    results: Vec<Value>,
+    /// Stack height and scope depth when the loop started
+    start: usize,
+    scope_depth: usize,
+    /// The loop's ForeachNext instruction and where it jumps when done
+    next: usize,
+    exit: usize,
+}
+
+impl Machine {
+    /// Everything written so far by unfinished statements, for a return out
+    /// of the middle of them: each loop's results come after the statements
+    /// that were pending when it started
+    fn pending_output(&mut self) -> Vec<Value> {
+        let mut pending = std::mem::take(&mut self.stack).into_iter();
+        let mut output = Vec::new();
+        let mut height = 0;
+        for innermost in self.loops.drain(..) {
+            output.extend(pending.by_ref().take(innermost.start - height));
+            output.extend(innermost.results);
+            height = innermost.start;
+        }
+        output.extend(pending);
+        output
+    }
+}
+
+/// Enumerate the arrays a script wrote into their items, so the next stage
+/// gets (and a statement outputs) one element at a time. Cmdlets decide for
+/// themselves: what they return is passed on as it is.
+fn unroll(values: Vec<Value>) -> Vec<Value> {
+    if !values.iter().any(|value| matches!(value, Value::Array(_))) {
+        return values;
+    }
+    values
+        .into_iter()
+        .flat_map(|value| match value {
+            Value::Array(items) => items.unwrap_or_clone(),
+            other => vec![other],
+        })
+        .collect()
+}
+
+/// The text after `--%` given to a cmdlet or function rather than a
+/// program: its words, as strings
+fn verbatim_arguments(text: &str) -> Vec<Value> {
+    native::split_command_line(&native::expand_environment(text))
+        .into_iter()
+        .map(|arg| Value::String(arg.into()))
+        .collect()
+}
+
+/// A value as a number for arithmetic, where $null counts as 0
+fn arithmetic_operand(value: &Value) -> Option<f64> {
+    match value {
+        Value::Null => Some(0.0),
+        other => other.to_number(),
+    }
+}
+
 /// Evaluator executes PowerShell AST
 pub struct Evaluator {
     scope: ScopeStack,
     cmdlet_registry: CmdletRegistry,
+    types: TypeRegistry,
+    /// The views the console shows objects of a type in
+    formats: FormatRegistry,
+    /// The call stack when the last error was raised, its innermost frame
+    /// positioned at the statement or expression that failed; empty when
+    /// that isn't known
+    error_stack: Vec<CallFrame>,
+    /// Where Write-Host text, warnings, progress and prompts go
+    host: Box<dyn Host>,
+    /// Cmdlet packs scripts can load with Import-Module
+    modules: Vec<Box<dyn CmdletPack>>,
+    /// Script module files imported so far, so each is only run once
+    imported_files: Vec<PathBuf>,
+    /// The commands each script module file on the search path exports,
+    /// with when the file was last changed, so auto-loading doesn't parse
+    /// every module on every unknown command
+    module_exports: HashMap<PathBuf, (Option<SystemTime>, Vec<String>)>,
+    /// Run loop bodies and script blocks as compiled instructions
+    compile: bool,
+    /// The script running, then the function, script block and method
+    /// calls it is in, innermost last
+    call_stack: Vec<CallFrame>,
+    max_call_depth: usize,
+    max_scope_depth: usize,
+    /// Errors written so far by each running cmdlet called with -ErrorVariable
+    error_collectors: Vec<Vec<Value>>,
+    /// The errors being handled by the catch blocks that are running,
+    /// innermost last; a bare `throw` throws the last one again
+    caught_errors: Vec<RuntimeError>,
+    /// How -f, ToString and Get-Date -Format format numbers and dates
+    culture: Culture,
+    /// Where Get-Date and [datetime]::Now get the time
+    clock: Box<dyn Clock>,
+    /// The generator behind Get-Random
+    random: Random,
+    /// Whether commands that aren't cmdlets or functions run programs on PATH
+    native_commands: bool,
+    /// Which script files may run
+    execution_policy: ExecutionPolicy,
+    /// Whether the host fixed the policy, so Set-ExecutionPolicy can't change it
+    execution_policy_locked: bool,
+    /// How much of the language scripts may use; only the host sets it
+    language_mode: LanguageMode,
+    /// Where script files come from and whether they are signed
+    script_trust: Box<dyn ScriptTrust>,
+    /// Strict mode version; from 1 on, reading an undefined variable fails
+    strict_mode: u32,
+    /// The settings the session started with
+    configuration: Configuration,
+    /// Breakpoints, and where stepping stops next
+    debugger: Debugger,
+    /// The lines run at the prompt
+    history: History,
+    /// The host's callbacks, in the order they were added
+    instrumentation: Vec<Box<dyn Instrumentation>>,
+    /// What the host lets scripts do
+    limits: Limits,
+    /// When the script running now started, for the time limit; only read
+    /// when there is one, as wasm32 has no clock to read it from
+    script_started: Option<Instant>,
+    /// How many objects the script running now has output
+    script_output: usize,
+    /// Statements run since the values variables hold were last counted
+    statements_uncounted: usize,
+    /// What the last script to fail wrote before its error
+    unfinished_output: Vec<Value>,
 }
 
 impl Evaluator {
     /// Create a new evaluator with empty scope
     pub fn new() -> Self {
-        Evaluator {
-            scope: ScopeStack::new(),
-            cmdlet_registry: CmdletRegistry::new(),
-        }
+        Self::with_registry(CmdletRegistry::new())
     }
 
     /// Create evaluator with a custom cmdlet registry
     pub fn with_registry(registry: CmdletRegistry) -> Self {
+        let mut scope = ScopeStack::new();
+        scope.set_variable("error", Value::Array(Vec::new().into()));
+        scope.set_variable("PSVersionTable", version::version_table());
+        scope.set_variable(
+            "PSStyle",
+            psstyle::ps_style(OutputRendering::from_environment()),
+        );
         Evaluator {
-            scope: ScopeStack::new(),
+            scope,
             cmdlet_registry: registry,
+            types: TypeRegistry::new(),
+            formats: FormatRegistry::new(),
+            error_stack: Vec::new(),
+            host: Box::new(ConsoleHost),
+            modules: Vec::new(),
+            imported_files: Vec::new(),
+            module_exports: HashMap::new(),
+            compile: true,
+            call_stack: vec![CallFrame::new(SCRIPT_BLOCK_COMMAND, None)],
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+            max_scope_depth: DEFAULT_MAX_SCOPE_DEPTH,
+            error_collectors: Vec::new(),
+            caught_errors: Vec::new(),
+            culture: Culture::default(),
+            clock: Box::new(SystemClock),
+            random: Random::from_entropy(),
+            native_commands: false,
+            execution_policy: ExecutionPolicy::default(),
+            execution_policy_locked: false,
+            language_mode: LanguageMode::default(),
+            script_trust: Box::new(LocalScripts),
+            strict_mode: 0,
+            configuration: Configuration::default(),
+            debugger: Debugger::default(),
+            history: History::default(),
+            instrumentation: Vec::new(),
+            limits: Limits::default(),
+            script_started: None,
+            script_output: 0,
+            unfinished_output: Vec::new(),
+            statements_uncounted: 0,
+        }
+    }
+
+    /// Replace the host that receives text output and answers prompts
+    pub fn set_host(&mut self, host: Box<dyn Host>) {
+        self.host = host;
+    }
+
+    /// The host, for cmdlets that ask about it, such as its window's size
+    pub fn host(&self) -> &dyn Host {
+        self.host.as_ref()
+    }
+
+    /// The host, for cmdlets that write text or prompt the user
+    pub fn host_mut(&mut self) -> &mut dyn Host {
+        self.host.as_mut()
+    }
+
+    /// The clock Get-Date and [datetime]::Now read
+    pub fn clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
+
+    /// Replace the system clock, e.g. with a `FixedClock` to freeze time in tests
+    pub fn set_clock(&mut self, clock: Box<dyn Clock>) {
+        self.clock = clock;
+    }
+
+    /// The session's random number generator
+    pub fn random(&mut self) -> &mut Random {
+        &mut self.random
+    }
+
+    /// Restart the random number generator from `seed`, so the random
+    /// choices that follow are the same on every run
+    pub fn set_random_seed(&mut self, seed: u64) {
+        self.random = Random::seeded(seed);
+    }
+
+    /// Let commands that aren't cmdlets or functions run the program of that
+    /// name from PATH, as a shell does. Off unless the host turns it on.
+    /// Turning it on defines $PSNativeCommandArgumentPassing with the
+    /// platform's default mode.
+    pub fn set_native_commands(&mut self, enabled: bool) {
+        self.native_commands = enabled;
+        if enabled
+            && self
+                .get_variable("PSNativeCommandArgumentPassing")
+                .is_none()
+        {
+            self.scope.set_variable_qualified(
+                "global:PSNativeCommandArgumentPassing",
+                Value::String(
+                    native::NativeArgumentPassing::platform_default()
+                        .name()
+                        .into(),
+                ),
+            );
+        }
+    }
+
+    /// Which script files may run
+    pub fn execution_policy(&self) -> ExecutionPolicy {
+        self.execution_policy
+    }
+
+    /// Change which script files may run. Set-ExecutionPolicy checks
+    /// `execution_policy_locked` first; the host can always change it.
+    pub fn set_execution_policy(&mut self, policy: ExecutionPolicy) {
+        self.execution_policy = policy;
+    }
+
+    /// Whether the host fixed the execution policy for the session
+    pub fn execution_policy_locked(&self) -> bool {
+        self.execution_policy_locked
+    }
+
+    /// Fix the execution policy, so scripts can't change it
+    pub fn lock_execution_policy(&mut self) {
+        self.execution_policy_locked = true;
+    }
+
+    /// How much of the language scripts may use
+    pub fn language_mode(&self) -> LanguageMode {
+        self.language_mode
+    }
+
+    /// Limit how much of the language scripts may use, from the next
+    /// script on. No cmdlet calls this: only the host changes the mode.
+    pub fn set_language_mode(&mut self, mode: LanguageMode) {
+        self.language_mode = mode;
+    }
+
+    /// Fail, pointing at it, on the first part of `statements` the
+    /// language mode doesn't allow, before any of them runs
+    fn check_language(&mut self, statements: &[Statement]) -> Result<(), RuntimeError> {
+        match self.language_mode.violation(statements) {
+            Some((span, error)) => {
+                self.locate_error(&error, span);
+                Err(error)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Set what the execution policy asks about script files
+    pub fn set_script_trust(&mut self, trust: Box<dyn ScriptTrust>) {
+        self.script_trust = trust;
+    }
+
+    /// Limit what scripts may do, as a sandbox for code the host doesn't
+    /// trust; the limits apply from the next script on
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+        self.start_script();
+    }
+
+    pub fn limits(&self) -> &Limits {
+        &self.limits
+    }
+
+    /// Start timing and counting the output of a new script, for the
+    /// limits, and forget the output the last one left unfinished;
+    /// `Session` does this before each script it runs
+    pub fn start_script(&mut self) {
+        self.script_started = self.limits.max_duration.map(|_| Instant::now());
+        self.script_output = 0;
+        self.statements_uncounted = 0;
+        self.unfinished_output.clear();
+    }
+
+    /// What the statements of the last script to fail wrote before it
+    /// failed, which the error kept `eval` from returning; a host shows it
+    /// ahead of the error
+    pub fn take_unfinished_output(&mut self) -> Vec<Value> {
+        std::mem::take(&mut self.unfinished_output)
+    }
+
+    fn keep_unfinished_output(&mut self, output: Vec<Value>) {
+        self.unfinished_output = match collect_output(output) {
+            Value::Null => Vec::new(),
+            Value::Array(items) => items.unwrap_or_clone(),
+            value => vec![value],
+        };
+    }
+
+    /// Stop the script if it has run as long as it may. Statements and
+    /// commands check this as they run; cmdlets that work for a long time
+    /// on their own, such as walking a directory tree, call it as they go.
+    pub fn check_time_limit(&self) -> Result<(), RuntimeError> {
+        match self.limits.max_duration {
+            Some(max)
+                if self
+                    .script_started
+                    .is_some_and(|start| start.elapsed() >= max) =>
+            {
+                Err(RuntimeError::LimitExceeded(Limit::Duration(max)))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Stop the script if it has run as long as it may, or, every so many
+    /// statements, if its variables hold more than they may
+    fn check_limits(&mut self) -> Result<(), RuntimeError> {
+        self.check_time_limit()?;
+        if self.limits.max_values.is_some() {
+            self.statements_uncounted += 1;
+            if self.statements_uncounted >= limits::VALUE_COUNT_INTERVAL {
+                self.check_values()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Stop the script if its variables hold more values than they may
+    fn check_values(&mut self) -> Result<(), RuntimeError> {
+        self.statements_uncounted = 0;
+        let Some(max) = self.limits.max_values else {
+            return Ok(());
+        };
+        let held: usize = self.scope.values().map(limits::value_count).sum();
+        if held > max {
+            return Err(RuntimeError::LimitExceeded(Limit::Values(max)));
+        }
+        Ok(())
+    }
+
+    /// Count the output of one of the script's statements against the
+    /// output limit, and what its variables hold now against the memory
+    /// limit; the output is passed on when neither is passed
+    fn count_output(&mut self, output: Value) -> EvalResult {
+        self.script_output += match &output {
+            Value::Null => 0,
+            Value::Array(items) => items.read().len(),
+            _ => 1,
+        };
+        if let Some(max) = self.limits.max_output {
+            if self.script_output > max {
+                return Err(RuntimeError::LimitExceeded(Limit::Output(max)));
+            }
         }
+        self.check_values()?;
+        Ok(output)
+    }
+
+    /// Add callbacks to make as scripts run; each callback of the ones
+    /// added before runs first
+    pub fn add_instrumentation(&mut self, instrumentation: Box<dyn Instrumentation>) {
+        self.instrumentation.push(instrumentation);
+    }
+
+    /// Whether the execution policy lets the script file at `path`, with
+    /// text `source`, run
+    pub fn check_script_file(
+        &self,
+        path: &std::path::Path,
+        source: &str,
+    ) -> Result<(), RuntimeError> {
+        self.execution_policy
+            .check(path, source, self.script_trust.as_ref())
+    }
+
+    /// The strict mode version in force; 0 when it is off
+    pub fn strict_mode(&self) -> u32 {
+        self.strict_mode
+    }
+
+    /// Turn strict mode on at `version`, or off with 0. From 1.0 on,
+    /// reading an undefined variable fails; from 2.0 on, so does expanding
+    /// one in a string.
+    pub fn set_strict_mode(&mut self, version: u32) {
+        self.strict_mode = version;
+    }
+
+    /// The Set-PSDebug trace level in force: 0 when tracing is off, 1 to
+    /// write each statement before it runs, 2 to also write variable
+    /// assignments and function calls
+    pub fn trace_level(&self) -> u32 {
+        self.scope.trace()
+    }
+
+    /// Set the trace level for the current scope and the scopes it
+    /// creates; when the scope ends, the level outside it applies again
+    pub fn set_trace_level(&mut self, level: u32) {
+        self.scope.set_trace(level);
+    }
+
+    /// Write a trace line, as Set-PSDebug's tracing does
+    fn trace(&mut self, text: &str) {
+        self.host.write_debug_line(text);
+    }
+
+    /// The settings the session started with, as read from its
+    /// configuration file
+    pub fn configuration(&self) -> &Configuration {
+        &self.configuration
+    }
+
+    /// Start from `configuration`: its strict mode and execution policy
+    /// take effect, and the rest is there for cmdlets and the host to read
+    pub fn set_configuration(&mut self, configuration: Configuration) {
+        self.strict_mode = configuration.strict_mode;
+        self.execution_policy = configuration.execution_policy;
+        self.history.set_capacity(configuration.history_size);
+        self.configuration = configuration;
+    }
+
+    /// The views the console shows objects of a type in
+    pub fn formats(&self) -> &FormatRegistry {
+        &self.formats
+    }
+
+    /// The views, to add one
+    pub fn formats_mut(&mut self) -> &mut FormatRegistry {
+        &mut self.formats
+    }
+
+    /// The console lines for a command's output, in the views for its
+    /// objects' types, unstyled
+    pub fn format_output(&self, output: &[Value]) -> Vec<String> {
+        self.formats.format(output, &FormatContext::default())
+    }
+
+    /// What happens to ANSI styling in the console's output:
+    /// $OutputRendering, or when it is unset $PSStyle.OutputRendering
+    /// (PlainText from the start if $env:NO_COLOR was set, otherwise Host).
+    /// A value that isn't a rendering is an error.
+    pub fn output_rendering(&self) -> Result<OutputRendering, RuntimeError> {
+        let rendering = match self.get_variable("OutputRendering") {
+            Some(Value::Null) | None => self
+                .get_variable("PSStyle")
+                .and_then(|style| style.get_property("OutputRendering")),
+            value => value,
+        };
+        match rendering {
+            Some(Value::Null) | None => Ok(OutputRendering::from_environment()),
+            Some(value) => OutputRendering::from_value(&value),
+        }
+    }
+
+    /// Whether text for the host keeps its ANSI styling: as the output
+    /// rendering says, for this host
+    pub fn keeps_ansi(&self) -> Result<bool, RuntimeError> {
+        Ok(self
+            .output_rendering()?
+            .keeps_ansi(self.host.supports_ansi()))
+    }
+
+    /// `text` as the host should get it: without its ANSI styling unless
+    /// the output rendering keeps it
+    pub fn host_text(&self, text: &str) -> Result<String, RuntimeError> {
+        if text.contains('\x1b') && !self.keeps_ansi()? {
+            Ok(ansi::strip_ansi(text))
+        } else {
+            Ok(text.to_string())
+        }
+    }
+
+    /// Show output that reached the end of a pipeline, as Out-Default does:
+    /// formatted in the views for its types, tables fitted to the width of
+    /// the host's window if it has one, and handed to the host. When
+    /// the output rendering keeps ANSI styling, file names, table headers
+    /// and list labels are styled as $PSStyle.FileInfo and
+    /// $PSStyle.Formatting say; otherwise any styling is taken out.
+    pub fn out_default(&mut self, output: &[Value]) -> Result<(), RuntimeError> {
+        let lines = self.console_lines(output)?;
+        self.host.write_output_lines(&lines);
+        Ok(())
+    }
+
+    /// Show output as `out_default` does, a screen at a time when it
+    /// doesn't fit the host's window (see `pager::page`)
+    pub fn out_paged(&mut self, output: &[Value]) -> Result<(), RuntimeError> {
+        let lines = self.console_lines(output)?;
+        pager::page(self.host.as_mut(), &lines);
+        Ok(())
+    }
+
+    /// The lines `out_default` shows for `output`
+    fn console_lines(&self, output: &[Value]) -> Result<Vec<String>, RuntimeError> {
+        let keep_ansi = self.keeps_ansi()?;
+        let style = self.get_variable("PSStyle").filter(|_| keep_ansi);
+        let style_of = |name: &str| style.as_ref().and_then(|style| style.get_property(name));
+        let context = FormatContext {
+            file_info: style_of("FileInfo").map(|file_info| FileInfoStyle::from_value(&file_info)),
+            formatting: style_of("Formatting")
+                .map(|formatting| FormattingStyle::from_value(&formatting)),
+            width: self.host.window_size().map(|(columns, _)| columns),
+        };
+        let mut lines = self.formats.format(output, &context);
+        if !keep_ansi {
+            for line in &mut lines {
+                if line.contains('\x1b') {
+                    *line = ansi::strip_ansi(line);
+                }
+            }
+        }
+        Ok(lines)
+    }
+
+    /// The culture numbers and dates are formatted for
+    pub fn culture(&self) -> &Culture {
+        &self.culture
+    }
+
+    /// Format numbers and dates for another culture (en-US by default)
+    pub fn set_culture(&mut self, culture: Culture) {
+        self.culture = culture;
+    }
+
+    /// Format a number or date with a .NET format string ("N2", "yyyy-MM-dd")
+    /// for the session's culture, as ToString and -f do
+    pub fn format_value(&self, value: &Value, spec: &str) -> Result<String, RuntimeError> {
+        format::format_value(value, spec, &self.culture)
+    }
+
+    /// Whether loop bodies and script blocks are compiled before they run (the
+    /// default); turn it off to run everything on the tree-walker, e.g. to
+    /// tell a compiler bug from an evaluator bug
+    pub fn set_compilation(&mut self, enabled: bool) {
+        self.compile = enabled;
+    }
+
+    /// Whether to compile code before running it now: not while the
+    /// debugger may stop, statements are traced, the host is told of each
+    /// or the limits are checked at each, since compiled code has no
+    /// statements to stop at, report or check
+    fn compiles(&self) -> bool {
+        self.compile
+            && !self.debugger.is_active()
+            && self.scope.trace() == 0
+            && self.instrumentation.is_empty()
+            && !self.limits.checks_statements()
+    }
+
+    /// The breakpoints set in the session
+    pub fn debugger(&self) -> &Debugger {
+        &self.debugger
+    }
+
+    /// Set or remove breakpoints, as Set-PSBreakpoint and
+    /// Remove-PSBreakpoint do
+    pub fn debugger_mut(&mut self) -> &mut Debugger {
+        &mut self.debugger
+    }
+
+    /// The lines run at the prompt, for Get-History and `r`
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    /// Add a line run at the prompt, as `Session::invoke_interactive` does
+    pub fn history_mut(&mut self) -> &mut History {
+        &mut self.history
+    }
+
+    /// How deeply function, script block and method calls may nest (1000 by
+    /// default); a deeper call fails with a CallDepthOverflow error
+    pub fn set_max_call_depth(&mut self, depth: usize) {
+        self.max_call_depth = depth;
+    }
+
+    /// How deeply scopes may nest, counting the global scope (10000 by
+    /// default); a deeper one fails with a ScopeDepthOverflow error
+    pub fn set_max_scope_depth(&mut self, depth: usize) {
+        self.max_scope_depth = depth;
+    }
+
+    /// Get the cmdlet registry
+    pub fn registry(&self) -> &CmdletRegistry {
+        &self.cmdlet_registry
     }
 
     /// Get a mutable reference to the cmdlet registry
@@ -38,13 +789,840 @@ impl Evaluator {
         &mut self.cmdlet_registry
     }
 
+    /// Make a cmdlet pack available to Import-Module
+    pub fn add_module(&mut self, pack: Box<dyn CmdletPack>) {
+        self.modules.push(pack);
+    }
+
+    /// Names of the cmdlet packs Import-Module can load
+    pub fn module_names(&self) -> Vec<&str> {
+        self.modules.iter().map(|pack| pack.name()).collect()
+    }
+
+    /// The directories script modules are looked for in: the
+    /// configuration's `ModulePaths`, then those in `$env:PSModulePath`
+    pub fn module_path(&self) -> Vec<PathBuf> {
+        module_path::search_path(&self.configuration.module_paths)
+    }
+
+    /// Whether Import-Module can find the module called `name`: a cmdlet
+    /// pack, or a script module on the search path
+    pub fn module_available(&self, name: &str) -> bool {
+        self.modules
+            .iter()
+            .any(|pack| pack.name().eq_ignore_ascii_case(name))
+            || module_path::find_module(&self.module_path(), name).is_some()
+    }
+
+    /// Load the module called `name`: register the cmdlets of the pack of
+    /// that name, or else run the script module of that name from the
+    /// search path. A name that is a path, such as `./Tools.psm1`, runs
+    /// that file.
+    pub fn import_module(&mut self, name: &str) -> Result<(), RuntimeError> {
+        let not_found = || {
+            RuntimeError::from(
+                ErrorRecord::new(
+                    ErrorCategory::ObjectNotFound,
+                    "Modules_ModuleNotFound,Import-Module",
+                    format!(
+                        "The specified module '{}' was not loaded because no valid module was found",
+                        name
+                    ),
+                )
+                .with_target(name),
+            )
+        };
+
+        if module_path::is_module_file(name) {
+            let path = Path::new(name);
+            // A module's folder stands for the module file inside it
+            let file = match (path.is_dir(), path.file_name()) {
+                (true, Some(folder)) => path.join(format!("{}.psm1", folder.to_string_lossy())),
+                _ => path.to_path_buf(),
+            };
+            if !file.is_file() {
+                return Err(not_found());
+            }
+            return self.import_script_module(&file);
+        }
+        if let Some(pack) = self
+            .modules
+            .iter()
+            .find(|pack| pack.name().eq_ignore_ascii_case(name))
+        {
+            pack.register(&mut self.cmdlet_registry);
+            return Ok(());
+        }
+        match module_path::find_module(&self.module_path(), name) {
+            Some(file) => self.import_script_module(&file),
+            None => Err(not_found()),
+        }
+    }
+
+    /// Run a script module file in a scope of its own, then make the
+    /// functions it defined global. Its output and its variables are
+    /// dropped. The execution policy applies, as to any script file, and a
+    /// file already imported isn't run again.
+    fn import_script_module(&mut self, path: &Path) -> Result<(), RuntimeError> {
+        if self.imported_files.iter().any(|file| file == path) {
+            return Ok(());
+        }
+        self.limits
+            .check_command(&path.display().to_string(), &[Capability::FileSystem])?;
+        let source = std::fs::read_to_string(path).map_err(|e| {
+            RuntimeError::from(
+                ErrorRecord::io(
+                    &e,
+                    "Modules_ModuleFileNotRead,Import-Module",
+                    format!("Cannot read the module file {}: {}", path.display(), e),
+                )
+                .with_target(path.display()),
+            )
+        })?;
+        self.check_script_file(path, &source)?;
+        let program = Lexer::new(&source)
+            .tokenize()
+            .map_err(|e| e.to_string())
+            .and_then(|tokens| Parser::new(tokens).parse().map_err(|e| e.to_string()))
+            .map_err(|message| {
+                RuntimeError::from(
+                    ErrorRecord::new(
+                        ErrorCategory::InvalidData,
+                        "Modules_InvalidModuleFile,Import-Module",
+                        format!(
+                            "The module file {} is not valid: {}",
+                            path.display(),
+                            message
+                        ),
+                    )
+                    .with_target(path.display()),
+                )
+            })?;
+        self.check_requirements(&program.requires)?;
+        self.check_language(&program.statements)?;
+
+        self.check_scope_depth()?;
+        let scope_depth = self.scope.depth();
+        let call_depth = self.call_stack.len();
+        self.scope.push_script_scope();
+        // Its functions remember the module file they came from
+        let source = ScriptSource::new(Some(path), source);
+        self.call_stack
+            .push(CallFrame::new(SCRIPT_BLOCK_COMMAND, Some(Arc::new(source))));
+        let mut result = Ok(());
+        for statement in &program.statements {
+            match self.eval_statement(statement) {
+                Ok(_) => {}
+                Err(RuntimeError::EarlyReturn(_)) => break,
+                Err(e) => {
+                    result = Err(e);
+                    break;
+                }
+            }
+        }
+        self.scope.truncate(scope_depth + 1);
+        let module_scope = self.scope.pop_scope();
+        self.call_stack.truncate(call_depth);
+        // The error says which module failed to load, and why
+        result.map_err(|e| {
+            RuntimeError::from(
+                ErrorRecord::new(
+                    e.category(),
+                    "ModuleLoadFailed",
+                    format!("The module '{}' could not be loaded: {}", path.display(), e),
+                )
+                .with_target(path.display())
+                .with_inner(e),
+            )
+        })?;
+
+        if let Some(module_scope) = module_scope {
+            for function in module_scope.functions() {
+                self.scope.set_variable_in(
+                    0,
+                    &function.name,
+                    Value::Function(Box::new(function.clone())),
+                );
+            }
+        }
+        self.imported_files.push(path.to_path_buf());
+        Ok(())
+    }
+
+    /// The commands a script module file exports, parsing it again only
+    /// when it has changed
+    fn module_exports(&mut self, path: &Path) -> &[String] {
+        let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        let stale = self
+            .module_exports
+            .get(path)
+            .is_none_or(|(seen, _)| *seen != modified || modified.is_none());
+        if stale {
+            let source = std::fs::read_to_string(path).unwrap_or_default();
+            let commands = module_path::exported_commands(&source);
+            self.module_exports
+                .insert(path.to_path_buf(), (modified, commands));
+        }
+        &self.module_exports[path].1
+    }
+
+    /// Module auto-loading: when no command is called `name`, import the
+    /// module that has it, a cmdlet pack or else a script module on the
+    /// search path, as PowerShell does on first use of a command. Returns
+    /// whether a module was imported. `$PSModuleAutoLoadingPreference =
+    /// 'None'` turns it off.
+    fn autoload_command(&mut self, name: &str) -> Result<bool, RuntimeError> {
+        let preference = self.scope.get_variable("PSModuleAutoLoadingPreference");
+        if preference.is_some_and(|value| value.to_string().eq_ignore_ascii_case("None")) {
+            return Ok(false);
+        }
+
+        let pack = self.modules.iter().find(|pack| {
+            let mut cmdlets = CmdletRegistry::new();
+            pack.register(&mut cmdlets);
+            cmdlets.contains(name)
+        });
+        if let Some(pack) = pack {
+            pack.register(&mut self.cmdlet_registry);
+            return Ok(true);
+        }
+
+        // Finding a script module means reading files
+        if self
+            .limits
+            .denied_capabilities
+            .contains(&Capability::FileSystem)
+        {
+            return Ok(false);
+        }
+        for (_, file) in module_path::available_modules(&self.module_path()) {
+            if self.imported_files.contains(&file) {
+                continue;
+            }
+            let exports = self.module_exports(&file);
+            if exports
+                .iter()
+                .any(|command| command.eq_ignore_ascii_case(name))
+            {
+                self.import_script_module(&file)?;
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    /// Enforce a program's `#Requires` statements before it runs: the
+    /// PowerShell version and edition must be met, and every module it
+    /// names must be available. Those modules are then imported.
+    fn check_requirements(&mut self, requires: &Requirements) -> Result<(), RuntimeError> {
+        // An error here is the whole script's, not any statement's
+        self.error_stack.clear();
+        version::check_version(requires)?;
+        let missing: Vec<&str> = requires
+            .modules
+            .iter()
+            .filter(|name| !self.module_available(name))
+            .map(String::as_str)
+            .collect();
+        if !missing.is_empty() {
+            return Err(ErrorRecord::new(
+                ErrorCategory::ObjectNotFound,
+                "ScriptRequiresMissingModules",
+                format!(
+                    "The script cannot be run because the following modules that are specified by the \"#requires\" statements of the script are missing: {}.",
+                    missing.join(", ")
+                ),
+            )
+            .into());
+        }
+        for name in &requires.modules {
+            self.import_module(name)?;
+        }
+        Ok(())
+    }
+
     /// Evaluate a program (list of statements)
+    /// A failing statement stops the program and is recorded in $error;
+    /// the scopes and calls it was inside are left
     pub fn eval(&mut self, program: Program) -> EvalResult {
-        let mut result = Value::Null;
-        for statement in program.statements {
-            result = self.eval_statement(statement)?;
+        self.set_position(Span::default());
+        self.check_requirements(&program.requires)
+            .and_then(|_| self.check_language(&program.statements))
+            .inspect_err(|e| self.record_error(e))?;
+        let scope_depth = self.scope.depth();
+        let call_depth = self.call_stack.len();
+        let mut output = Vec::new();
+        for statement in &program.statements {
+            self.error_stack.clear();
+            let value = self
+                .eval_statement(statement)
+                .and_then(|value| self.count_output(value));
+            match value {
+                Ok(value) => output.push(value),
+                Err(e) => {
+                    self.scope.truncate(scope_depth);
+                    self.call_stack.truncate(call_depth);
+                    self.record_error(&e);
+                    self.keep_unfinished_output(output);
+                    return Err(e);
+                }
+            }
         }
-        Ok(result)
+        Ok(collect_output(output))
+    }
+
+    /// Run a line of script where the command running it is, in its scope,
+    /// as Invoke-History runs a line from the history again. Errors point
+    /// into the line; one that doesn't parse is an InvalidData error.
+    pub fn eval_line(&mut self, line: &str) -> EvalResult {
+        let program = Lexer::new(line)
+            .tokenize()
+            .map_err(|e| e.to_string())
+            .and_then(|tokens| Parser::new(tokens).parse().map_err(|e| e.to_string()))
+            .map_err(|message| {
+                RuntimeError::from(ErrorRecord::new(
+                    ErrorCategory::InvalidData,
+                    "ParseError",
+                    message,
+                ))
+            })?;
+        self.check_requirements(&program.requires)?;
+        self.check_language(&program.statements)?;
+
+        let call_depth = self.call_stack.len();
+        let source = ScriptSource::new(None, line);
+        self.call_stack
+            .push(CallFrame::new(SCRIPT_BLOCK_COMMAND, Some(Arc::new(source))));
+        let result = self.eval_statements(&program.statements);
+        self.call_stack.truncate(call_depth);
+        result
+    }
+
+    /// Run a program as a script file: in a scope of its own, which is its
+    /// `$script:` scope, so what it defines is gone when it ends unless it
+    /// is scoped `$global:`. A `return` ends the script, keeping its output.
+    pub fn eval_script(&mut self, program: Program) -> EvalResult {
+        self.check_scope_depth()?;
+        self.set_position(Span::default());
+        self.check_requirements(&program.requires)
+            .and_then(|_| self.check_language(&program.statements))
+            .inspect_err(|e| self.record_error(e))?;
+        let scope_depth = self.scope.depth();
+        let call_depth = self.call_stack.len();
+        self.scope.push_script_scope();
+
+        let mut output = Vec::new();
+        let mut result = Ok(());
+        for statement in &program.statements {
+            self.error_stack.clear();
+            let value = self
+                .eval_statement(statement)
+                .and_then(|value| self.count_output(value));
+            match value {
+                Ok(value) => output.push(value),
+                Err(RuntimeError::EarlyReturn(value)) => {
+                    output.push(value);
+                    break;
+                }
+                Err(e) => {
+                    self.call_stack.truncate(call_depth);
+                    self.record_error(&e);
+                    self.keep_unfinished_output(std::mem::take(&mut output));
+                    result = Err(e);
+                    break;
+                }
+            }
+        }
+
+        self.scope.truncate(scope_depth);
+        result.map(|_| collect_output(output))
+    }
+
+    /// Enter a new scope, or fail if scopes are already nested as deeply as allowed
+    fn push_scope(&mut self) -> Result<(), RuntimeError> {
+        self.check_scope_depth()?;
+        self.scope.push_scope();
+        Ok(())
+    }
+
+    /// Enter the scope of an if or foreach body
+    fn push_block_scope(&mut self) -> Result<(), RuntimeError> {
+        self.check_scope_depth()?;
+        self.scope.push_block_scope();
+        Ok(())
+    }
+
+    /// Fail if scopes are already nested as deeply as allowed
+    fn check_scope_depth(&self) -> Result<(), RuntimeError> {
+        let depth = self.scope.depth();
+        if depth >= self.max_scope_depth {
+            return Err(ErrorRecord::new(
+                ErrorCategory::InvalidOperation,
+                "ScopeDepthOverflow",
+                format!(
+                    "The scope depth reached {} and the maximum is {}.",
+                    depth + 1,
+                    self.max_scope_depth
+                ),
+            )
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Run a function, script block or method one call level deeper, in
+    /// `frame` on the call stack, or fail if calls are already nested as
+    /// deeply as allowed
+    fn call<T>(
+        &mut self,
+        frame: CallFrame,
+        run: impl FnOnce(&mut Self) -> Result<T, RuntimeError>,
+    ) -> Result<T, RuntimeError> {
+        let depth = self.call_depth();
+        if depth >= self.max_call_depth {
+            return Err(ErrorRecord::new(
+                ErrorCategory::InvalidOperation,
+                "CallDepthOverflow",
+                format!(
+                    "The script failed due to call depth overflow. The call depth reached {} and the maximum is {}.",
+                    depth + 1,
+                    self.max_call_depth
+                ),
+            )
+            .into());
+        }
+        self.call_stack.push(frame);
+        let result = grow_stack(|| run(self));
+        self.call_stack.truncate(depth + 1);
+        result
+    }
+
+    /// How many function, script block and method calls are running
+    fn call_depth(&self) -> usize {
+        self.call_stack.len() - 1
+    }
+
+    /// A frame for calling code from the script running now, such as a
+    /// script block, which has no script of its own
+    fn frame_here(&self, command: &str) -> CallFrame {
+        CallFrame::new(command, self.current_source())
+    }
+
+    /// The script the code running now came from
+    fn current_source(&self) -> Option<Arc<ScriptSource>> {
+        self.call_stack
+            .last()
+            .and_then(|frame| frame.source.clone())
+    }
+
+    /// Note an argument the innermost call was given, for the call stack
+    fn note_argument(&mut self, name: &str, value: &Value) {
+        if let Some(frame) = self.call_stack.last_mut() {
+            frame.arguments.push((name.to_string(), value.clone()));
+        }
+    }
+
+    /// The calls running, from the script out to the innermost, each at
+    /// the statement or command it is running
+    pub fn call_stack(&self) -> &[CallFrame] {
+        &self.call_stack
+    }
+
+    /// Note that the innermost frame has reached `span`
+    fn set_position(&mut self, span: Span) {
+        if let Some(frame) = self.call_stack.last_mut() {
+            frame.position = span;
+        }
+    }
+
+    /// The script the next `eval` or `eval_script` runs: its text, and the
+    /// file it came from when it did. Errors point into it, and functions
+    /// it defines remember it.
+    pub fn set_source(&mut self, path: Option<&Path>, text: &str) {
+        self.call_stack.truncate(1);
+        self.call_stack[0] = CallFrame::new(
+            SCRIPT_BLOCK_COMMAND,
+            Some(Arc::new(ScriptSource::new(path, text))),
+        );
+    }
+
+    /// Where in the source the error returned by the last `eval` happened:
+    /// the innermost statement or expression that failed
+    pub fn error_span(&self) -> Option<Span> {
+        self.error_stack.last().map(|frame| frame.position)
+    }
+
+    /// Where the error returned by the last `eval` happened, in
+    /// PowerShell's words: the script and line, the line itself, and the
+    /// part that failed underlined
+    pub fn error_position(&self) -> Option<String> {
+        let frame = self.error_stack.last()?;
+        Some(frame.source.as_ref()?.position_message(frame.position))
+    }
+
+    /// Remember where an error was raised, and the calls it was raised in;
+    /// the first (innermost) node to see it is the most precise location.
+    /// Returns, breaks and continues are control flow, not errors.
+    fn locate_error(&mut self, error: &RuntimeError, span: Span) {
+        if self.error_stack.is_empty() && span != Span::default() && !error.is_control_flow() {
+            let mut stack = self.call_stack.clone();
+            if let Some(innermost) = stack.last_mut() {
+                innermost.position = span;
+            }
+            self.error_stack = stack;
+        }
+    }
+
+    /// An error as scripts see it in $error and $_: its record, with an
+    /// InvocationInfo saying where it happened and a ScriptStackTrace of
+    /// the calls it happened in. An error without a location of its own,
+    /// such as one a cmdlet writes, happened at the command running now.
+    fn error_value(&self, error: &RuntimeError) -> Value {
+        let value = error.to_value();
+        let stack = if self.error_stack.is_empty() {
+            &self.call_stack
+        } else {
+            &self.error_stack
+        };
+        let Some(frame) = stack
+            .last()
+            .filter(|frame| frame.position != Span::default())
+        else {
+            return value;
+        };
+        let (line, column) = (frame.position.start.line, frame.position.start.column);
+        let script_name = frame
+            .script_name()
+            .map(|path| path.display().to_string())
+            .unwrap_or_default();
+        let (text, position_message) = match &frame.source {
+            Some(source) => (
+                source.line(line).to_string(),
+                source.position_message(frame.position),
+            ),
+            None => (String::new(), format!("At line:{} char:{}", line, column)),
+        };
+        let info = PropertyMap::from([
+            ("ScriptName", Value::String(script_name.into())),
+            ("ScriptLineNumber", Value::Number(line as f64)),
+            ("OffsetInLine", Value::Number(column as f64)),
+            ("Line", Value::String(text.into())),
+            ("PositionMessage", Value::String(position_message.into())),
+        ]);
+        // An error record always takes properties
+        let _ = value.set_property("InvocationInfo", Value::Object(info.into()));
+        let trace = callstack::stack_trace(stack);
+        let _ = value.set_property("ScriptStackTrace", Value::String(trace.into()));
+        value
+    }
+
+    /// Stop before the statement at `span` when it is on a line with a
+    /// breakpoint, or when stepping reaches it
+    fn debug_statement(&mut self, span: Span) -> Result<(), RuntimeError> {
+        let depth = self.call_depth();
+        let frame = self.call_stack.last();
+        let previous = frame.map(|frame| frame.position).unwrap_or_default();
+        let script = frame.and_then(CallFrame::script_name);
+        if let Some(index) = self.debugger.line_hit(script, span, previous) {
+            self.set_position(span);
+            return self.hit_breakpoint(index);
+        }
+        if self.debugger.step_stops_at(depth) {
+            self.set_position(span);
+            return self.debug_stop(None);
+        }
+        Ok(())
+    }
+
+    /// Stop at an access to `$name` when a variable breakpoint watches it
+    fn debug_variable(&mut self, name: &str, access: VariableAccess) -> Result<(), RuntimeError> {
+        let script = self.call_stack.last().and_then(CallFrame::script_name);
+        match self.debugger.variable_hit(script, name, access) {
+            Some(index) => self.hit_breakpoint(index),
+            None => Ok(()),
+        }
+    }
+
+    /// A breakpoint was hit: run its action, or else stop at the debug
+    /// prompt. An action stops there too when it uses `break`.
+    fn hit_breakpoint(&mut self, index: usize) -> Result<(), RuntimeError> {
+        let breakpoint = self.debugger.breakpoint(index).clone();
+        let Some(action) = &breakpoint.action else {
+            return self.debug_stop(Some(&breakpoint.description()));
+        };
+        self.debugger.suspended = true;
+        let result = self.invoke_script_block(action, Vec::new());
+        self.debugger.suspended = false;
+        match result {
+            Ok(output) => self.out_default(&[output]),
+            Err(RuntimeError::Break(output)) => {
+                self.out_default(&[output])?;
+                self.debug_stop(Some(&breakpoint.description()))
+            }
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Stop at the debug prompt: say why (`Hit Line breakpoint on ...`)
+    /// and where, then read debugger commands from the host until one
+    /// goes on. Anything that isn't a debugger command runs as PowerShell
+    /// where the script stopped, so `$total` shows a variable. The end of
+    /// input goes on too. Quitting stops the script.
+    fn debug_stop(&mut self, reason: Option<&str>) -> Result<(), RuntimeError> {
+        self.debugger.step = None;
+        let mut lines = Vec::new();
+        if let Some(reason) = reason {
+            lines.extend([
+                debugger::DEBUG_BANNER.to_string(),
+                String::new(),
+                format!("Hit {}", reason),
+                String::new(),
+            ]);
+        }
+        if let Some(frame) = self.call_stack.last() {
+            if let Some(source) = &frame.source {
+                lines.extend(
+                    source
+                        .position_message(frame.position)
+                        .lines()
+                        .map(str::to_string),
+                );
+            }
+        }
+        self.host.write_output_lines(&lines);
+
+        self.debugger.suspended = true;
+        let result = self.debug_prompt();
+        self.debugger.suspended = false;
+        result
+    }
+
+    fn debug_prompt(&mut self) -> Result<(), RuntimeError> {
+        let depth = self.call_depth();
+        let mut last = None;
+        loop {
+            let Some(line) = self.host.read_line(debugger::DEBUG_PROMPT) else {
+                return Ok(());
+            };
+            let command = match DebugCommand::parse(&line) {
+                None if line.trim().is_empty() => match last {
+                    Some(command) => command,
+                    None => continue,
+                },
+                None => {
+                    self.debug_eval(&line);
+                    last = None;
+                    continue;
+                }
+                Some(command) => command,
+            };
+            last = command.repeats().then_some(command);
+            match command {
+                DebugCommand::StepInto => self.debugger.step = Some(Step::Into),
+                DebugCommand::StepOver => self.debugger.step = Some(Step::Over(depth)),
+                DebugCommand::StepOut => self.debugger.step = Some(Step::Out(depth)),
+                DebugCommand::Continue => {}
+                DebugCommand::Quit => return Err(RuntimeError::Stopped),
+                DebugCommand::CallStack => {
+                    let frames: Vec<Value> = self
+                        .call_stack
+                        .iter()
+                        .rev()
+                        .map(CallFrame::to_value)
+                        .collect();
+                    self.out_default(&frames)?;
+                    continue;
+                }
+                DebugCommand::List => {
+                    if let Some(frame) = self.call_stack.last() {
+                        let lines = debugger::list_source(frame);
+                        self.host.write_output_lines(&lines);
+                    }
+                    continue;
+                }
+                DebugCommand::Help => {
+                    let lines: Vec<String> =
+                        debugger::DEBUG_HELP.lines().map(str::to_string).collect();
+                    self.host.write_output_lines(&lines);
+                    continue;
+                }
+            }
+            return Ok(());
+        }
+    }
+
+    /// Run a line typed at the debug prompt where the script stopped,
+    /// showing its output or its error on the host. It leaves the script
+    /// as it was, but for what the line itself changed.
+    fn debug_eval(&mut self, line: &str) {
+        let program = match Lexer::new(line)
+            .tokenize()
+            .map_err(|e| e.to_string())
+            .and_then(|tokens| Parser::new(tokens).parse().map_err(|e| e.to_string()))
+        {
+            Ok(program) => program,
+            Err(message) => {
+                self.host.write_error_line(&message);
+                return;
+            }
+        };
+        if let Some((_, error)) = self.language_mode.violation(&program.statements) {
+            self.host.write_error_line(&error.to_string());
+            return;
+        }
+        let scope_depth = self.scope.depth();
+        let call_depth = self.call_stack.len();
+        let position = self.call_stack.last().map(|frame| frame.position);
+        let error_stack = std::mem::take(&mut self.error_stack);
+        let result = self
+            .eval_statements(&program.statements)
+            .and_then(|output| self.out_default(&[output]));
+        if let Err(error) = result {
+            self.host.write_error_line(&error.to_string());
+        }
+        self.scope.truncate(scope_depth);
+        self.call_stack.truncate(call_depth);
+        if let Some(position) = position {
+            self.set_position(position);
+        }
+        self.error_stack = error_stack;
+    }
+
+    /// Add an error to the front of $error, dropping the oldest past the limit
+    fn record_error(&mut self, error: &RuntimeError) {
+        self.record_error_value(error, self.error_value(error));
+    }
+
+    /// Put an error in $error, as `value`, and tell the host's
+    /// instrumentation
+    fn record_error_value(&mut self, error: &RuntimeError, value: Value) {
+        for hooks in &mut self.instrumentation {
+            hooks.on_error(error, &value);
+        }
+        let mut errors = match self.scope.get_variable_qualified("global:error") {
+            Some(Value::Array(items)) => items.unwrap_or_clone(),
+            _ => Vec::new(),
+        };
+        errors.insert(0, value);
+        errors.truncate(MAX_ERROR_COUNT);
+        self.scope
+            .set_variable_qualified("global:error", Value::Array(errors.into()));
+    }
+
+    /// Add a non-terminating error to the -ErrorVariable of every cmdlet that
+    /// is running
+    fn collect_error(&mut self, error: &RuntimeError) {
+        if !self.error_collectors.is_empty() {
+            let value = self.error_value(error);
+            for errors in &mut self.error_collectors {
+                errors.push(value.clone());
+            }
+        }
+    }
+
+    /// How cmdlets called without -ErrorAction handle non-terminating errors:
+    /// $ErrorActionPreference, or Continue if it is unset. A value that
+    /// isn't a preference is an error.
+    pub fn error_action_preference(&self) -> Result<ActionPreference, RuntimeError> {
+        match self.get_variable("ErrorActionPreference") {
+            Some(Value::Null) | None => Ok(ActionPreference::default()),
+            Some(value) => ActionPreference::from_value(&value),
+        }
+    }
+
+    /// Settle how a cmdlet about to be invoked handles non-terminating
+    /// errors: its -ErrorAction, or else $ErrorActionPreference, read now so
+    /// each invocation sees the preference of the scope it runs in
+    pub(crate) fn resolve_error_action(
+        &self,
+        context: &mut CmdletContext,
+    ) -> Result<(), RuntimeError> {
+        context.error_action = match context.parameters.shift_remove("ErrorAction") {
+            Some(action) => ActionPreference::from_value(&action)?,
+            None => self.error_action_preference()?,
+        };
+        Ok(())
+    }
+
+    /// How arguments are quoted for native commands: $PSNativeCommandArgumentPassing,
+    /// or the platform's default if it is unset
+    fn native_argument_passing(&self) -> Result<native::NativeArgumentPassing, RuntimeError> {
+        match self.get_variable("PSNativeCommandArgumentPassing") {
+            Some(Value::Null) | None => Ok(native::NativeArgumentPassing::platform_default()),
+            Some(value) => native::NativeArgumentPassing::from_value(&value),
+        }
+    }
+
+    /// How much of nested values Format-List and Format-Custom show:
+    /// $FormatEnumerationLimit items of a collection (all of them if it is
+    /// negative, 4 if it is unset) and `depth` levels deep
+    pub fn display_limits(&self, depth: usize) -> DisplayLimits {
+        let enumeration_limit = match self
+            .get_variable("FormatEnumerationLimit")
+            .and_then(|value| value.to_number())
+        {
+            Some(limit) if limit < 0.0 => None,
+            Some(limit) => Some(limit as usize),
+            None => DisplayLimits::default().enumeration_limit,
+        };
+        DisplayLimits {
+            depth,
+            enumeration_limit,
+        }
+    }
+
+    /// Report a non-terminating error, one a cmdlet can carry on after, as
+    /// `action` says: Continue writes it to the host's error stream and
+    /// $error, SilentlyContinue only to $error, Ignore drops it, and Stop
+    /// returns it for the cmdlet to fail with
+    pub fn write_error(
+        &mut self,
+        error: RuntimeError,
+        action: ActionPreference,
+    ) -> Result<(), RuntimeError> {
+        match action {
+            ActionPreference::Continue => {
+                self.host.write_error_line(&error.to_string());
+                self.record_error(&error);
+                self.collect_error(&error);
+            }
+            ActionPreference::SilentlyContinue => {
+                self.record_error(&error);
+                self.collect_error(&error);
+            }
+            ActionPreference::Ignore => {}
+            ActionPreference::Stop => return Err(error),
+        }
+        Ok(())
+    }
+
+    /// Assign a variable from a script: `$null = <expr>` discards the
+    /// value, and the other automatic constants can't be assigned
+    fn assign_variable(&mut self, name: &str, value: Value) -> Result<(), RuntimeError> {
+        if name.eq_ignore_ascii_case("null") {
+            return Ok(());
+        }
+        if scope::constant(name).is_some() {
+            return Err(ErrorRecord::new(
+                ErrorCategory::WriteError,
+                "VariableNotWritable",
+                format!(
+                    "Cannot overwrite variable {} because it is read-only or constant.",
+                    name
+                ),
+            )
+            .with_target(name)
+            .into());
+        }
+        if self.debugger.is_active() {
+            self.debug_variable(name, VariableAccess::Write)?;
+        }
+        if self.scope.trace() >= 2 {
+            let shown = DisplayLimits::default().inline(&value);
+            self.trace(&format!("    ! SET ${} = '{}'.", name, shown));
+        }
+        self.scope.set_variable_qualified(name, value);
+        Ok(())
     }
 
     /// Set a variable in the current scope
@@ -57,18 +1635,146 @@ impl Evaluator {
         self.scope.get_variable_qualified(name)
     }
 
+    /// Names of the variables visible from the current scope
+    pub fn variable_names(&self) -> Vec<String> {
+        self.scope.variable_names()
+    }
+
+    /// Find a scope as `-Scope` names it: `Global`, `Local`, `Script`, or the
+    /// number of scopes up from the current one (0 is the current scope and
+    /// 1 its caller's)
+    fn resolve_scope(&self, scope: &str) -> Result<usize, RuntimeError> {
+        self.scope.scope_index(scope).ok_or_else(|| {
+            let message = if scope.trim().parse::<usize>().is_ok() {
+                format!(
+                    "The scope number '{}' exceeds the number of active scopes.",
+                    scope.trim()
+                )
+            } else {
+                format!(
+                    "The scope '{}' is not valid. Use Global, Local, Script or a number of scopes up from the current one.",
+                    scope
+                )
+            };
+            ErrorRecord::new(ErrorCategory::InvalidArgument, "ArgumentOutOfRange", message)
+                .with_target(scope)
+                .into()
+        })
+    }
+
+    /// Get a variable from the given scope itself
+    pub fn get_variable_in_scope(
+        &self,
+        name: &str,
+        scope: &str,
+    ) -> Result<Option<Value>, RuntimeError> {
+        let index = self.resolve_scope(scope)?;
+        Ok(self.scope.get_variable_in(index, name))
+    }
+
+    /// Create or update a variable in the given scope itself
+    pub fn set_variable_in_scope(
+        &mut self,
+        name: &str,
+        value: Value,
+        scope: &str,
+    ) -> Result<(), RuntimeError> {
+        let index = self.resolve_scope(scope)?;
+        self.scope.set_variable_in(index, name, value);
+        Ok(())
+    }
+
+    /// Names of the variables in the given scope itself
+    pub fn variable_names_in_scope(&self, scope: &str) -> Result<Vec<String>, RuntimeError> {
+        let index = self.resolve_scope(scope)?;
+        Ok(self.scope.variable_names_in(index))
+    }
+
+    /// User-defined functions visible from the current scope
+    pub fn functions(&self) -> Vec<&Function> {
+        self.scope.functions()
+    }
+
     /// Evaluate a single statement
-    pub fn eval_statement(&mut self, statement: Statement) -> EvalResult {
+    pub fn eval_statement(&mut self, statement: &Statement) -> EvalResult {
+        let span = statement.span;
+        if self.debugger.is_active() {
+            self.debug_statement(span)
+                .inspect_err(|e| self.locate_error(e, span))?;
+        }
+        if self.scope.trace() >= 1 {
+            let text = self
+                .current_source()
+                .map(|source| source.text_at(span))
+                .unwrap_or_default();
+            self.trace(&format!("{:>4}+  >>>> {}", span.start.line, text));
+        }
+        self.set_position(span);
+        if !self.instrumentation.is_empty() {
+            self.report_statement()
+                .inspect_err(|e| self.locate_error(e, span))?;
+        }
+        if self.limits.checks_statements() {
+            self.check_limits()
+                .inspect_err(|e| self.locate_error(e, span))?;
+        }
+        self.eval_statement_kind(&statement.kind)
+            .inspect_err(|e| self.locate_error(e, span))
+    }
+
+    /// Tell the host's instrumentation a statement is starting, at the
+    /// innermost frame's position
+    fn report_statement(&mut self) -> Result<(), RuntimeError> {
+        let frame = self.call_stack.last().expect("the script's frame");
+        for hooks in &mut self.instrumentation {
+            hooks.on_statement_start(frame)?;
+        }
+        Ok(())
+    }
+
+    fn eval_statement_kind(&mut self, statement: &StatementKind) -> EvalResult {
         match statement {
-            Statement::Expression(expr) => self.eval_expression(expr),
+            StatementKind::Expression(expr) => self.eval_expression(expr),
+
+            StatementKind::Assignment { variable, value } => {
+                let val = self.eval_expression(value)?;
+                self.assign_variable(variable, val)?;
+                Ok(Value::Null)
+            }
+
+            StatementKind::MultipleAssignment { variables, value } => {
+                let val = self.eval_expression(value)?;
+                let mut items = match val {
+                    Value::Array(items) => items.unwrap_or_clone(),
+                    other => vec![other],
+                }
+                .into_iter();
+
+                // Each variable but the last takes one value; the last takes the rest
+                let Some((last, leading)) = variables.split_last() else {
+                    return Ok(Value::Null);
+                };
+                for variable in leading {
+                    let assigned = items.next().unwrap_or(Value::Null);
+                    self.assign_variable(variable, assigned)?;
+                }
+                let mut rest: Vec<Value> = items.collect();
+                let assigned = if rest.len() > 1 {
+                    Value::Array(rest.into())
+                } else {
+                    rest.pop().unwrap_or(Value::Null)
+                };
+                self.assign_variable(last, assigned)?;
+                Ok(Value::Null)
+            }
 
-            Statement::Assignment { variable, value } => {
+            StatementKind::TargetAssignment { target, value } => {
                 let val = self.eval_expression(value)?;
-                self.scope.set_variable_qualified(&variable, val);
+                self.assign_to_target(target, val)?;
                 Ok(Value::Null)
             }
 
-            Statement::If {
+            StatementKind::If {
                 condition,
                 then_branch,
                 else_branch,
@@ -83,7 +1789,68 @@ impl Evaluator {
                 }
             }
 
-            Statement::Return(expr) => {
+            StatementKind::Try {
+                body,
+                catch,
+                finally,
+            } => {
+                let scope_depth = self.scope.depth();
+                let call_depth = self.call_stack.len();
+                let result = match (self.eval_block(body), catch) {
+                    // Returns, breaks and continues pass through; they
+                    // aren't errors
+                    (Err(error), Some(catch))
+                        if !error.is_control_flow() && !error.stops_script() =>
+                    {
+                        // Leave the scopes and calls the error came out of
+                        self.scope.truncate(scope_depth);
+                        self.call_stack.truncate(call_depth);
+                        let record = self.error_value(&error);
+                        self.error_stack.clear();
+                        self.record_error_value(&error, record.clone());
+                        self.eval_catch(catch, error, record)
+                    }
+                    (result, _) => result,
+                };
+                let Some(finally) = finally else {
+                    return result;
+                };
+
+                // The finally block runs however the try ended; its output
+                // follows, and an error (or return, break...) out of it
+                // takes the place of the try's
+                if result.is_err() {
+                    self.scope.truncate(scope_depth);
+                    self.call_stack.truncate(call_depth);
+                }
+                let cleanup = self.eval_block(finally)?;
+                match result {
+                    Ok(value) => Ok(collect_output(vec![value, cleanup])),
+                    Err(error) => Err(map_carried_output(error, |value| {
+                        collect_output(vec![value, cleanup])
+                    })),
+                }
+            }
+
+            StatementKind::Break => Err(RuntimeError::Break(Value::Null)),
+
+            StatementKind::Continue => Err(RuntimeError::Continue(Value::Null)),
+
+            StatementKind::Throw(expr) => Err(match expr {
+                Some(expression) => ErrorRecord::thrown(self.eval_expression(expression)?).into(),
+                // Throw the error being handled again, as it is
+                None => match self.caught_errors.last() {
+                    Some(error) => error.clone(),
+                    None => ErrorRecord::new(
+                        ErrorCategory::OperationStopped,
+                        "ScriptHalted",
+                        "ScriptHalted",
+                    )
+                    .into(),
+                },
+            }),
+
+            StatementKind::Return(expr) => {
                 // Throw an EarlyReturn error to propagate up the call stack
                 let value = if let Some(expression) = expr {
                     self.eval_expression(expression)?
@@ -93,24 +1860,94 @@ impl Evaluator {
                 Err(RuntimeError::EarlyReturn(value))
             }
 
-            Statement::FunctionDef {
+            StatementKind::FunctionDef {
                 name,
                 parameters,
                 body,
+                is_filter,
+                begin,
+                process,
+                help,
             } => {
                 // Store the function as a value in the current scope
                 let func = crate::value::Function {
                     name: name.clone(),
-                    parameters,
-                    body,
+                    parameters: parameters.clone(),
+                    body: body.clone(),
+                    is_filter: *is_filter,
+                    begin: begin.clone(),
+                    process: process.clone(),
+                    help: help.clone(),
+                    source: self.current_source(),
                 };
-                self.scope.set_variable(&name, Value::Function(func));
+                self.scope
+                    .set_variable(name, Value::Function(Box::new(func)));
+                Ok(Value::Null)
+            }
+
+            StatementKind::Foreach {
+                variable,
+                collection,
+                body,
+            } => {
+                let items = match self.eval_expression(collection)? {
+                    Value::Array(items) => items.unwrap_or_clone(),
+                    Value::Null => Vec::new(),
+                    other => vec![other],
+                };
+
+                // The loop variable stays set after the loop, as in PowerShell
+                let compiled = self.compiles().then(|| CompiledBlock::new(body));
+                let scope_depth = self.scope.depth();
+                let mut results = Vec::new();
+                for item in items {
+                    self.scope.set_variable_qualified(variable, item);
+                    let result = match &compiled {
+                        Some(code) => self.push_block_scope().and_then(|_| {
+                            let result = self.run_compiled(code)?;
+                            self.scope.pop_scope();
+                            Ok(result)
+                        }),
+                        None => self.eval_block(body),
+                    };
+                    match result {
+                        Ok(result) => results.push(result),
+                        // A break or continue keeps what the iteration wrote
+                        // before it, leaving the scopes it came out of
+                        Err(RuntimeError::Break(value)) => {
+                            self.scope.truncate(scope_depth);
+                            results.push(value);
+                            break;
+                        }
+                        Err(RuntimeError::Continue(value)) => {
+                            self.scope.truncate(scope_depth);
+                            results.push(value);
+                        }
+                        // A return keeps what the earlier iterations wrote
+                        Err(error) => {
+                            return Err(map_carried_output(error, |value| {
+                                results.push(value);
+                                collect_output(results)
+                            }))
+                        }
+                    }
+                }
+                Ok(collect_output(results))
+            }
+
+            StatementKind::ClassDef(class) => {
+                self.types.define_class(class.clone());
+                Ok(Value::Null)
+            }
+
+            StatementKind::EnumDef(def) => {
+                self.types.define_enum(def);
                 Ok(Value::Null)
             }
 
-            Statement::Pipeline(pipeline) => {
+            StatementKind::Pipeline(pipeline) => {
                 // Execute the pipeline
-                let results = self.execute_pipeline(&pipeline)?;
+                let results = self.execute_pipeline(pipeline)?;
                 Ok(Self::pipeline_results_to_value(results))
             }
         }
@@ -130,6 +1967,7 @@ impl Evaluator {
 
         // Execute each stage
         for stage in pipeline.stages.iter() {
+            self.check_time_limit()?;
             current_output = self.execute_pipeline_stage(stage, current_output)?;
         }
 
@@ -142,7 +1980,7 @@ impl Evaluator {
         match results.len() {
             0 => Value::Null,
             1 => results[0].clone(),
-            _ => Value::Array(results),
+            _ => Value::Array(results.into()),
         }
     }
 
@@ -152,26 +1990,30 @@ impl Evaluator {
         stage: &Expression,
         input: Vec<Value>,
     ) -> Result<Vec<Value>, RuntimeError> {
-        match stage {
-            Expression::Call { name, arguments } => {
+        match &stage.kind {
+            ExpressionKind::Call { name, arguments } => {
                 // This is a cmdlet call
+                self.set_position(stage.span);
                 self.execute_cmdlet_call(name, arguments, input)
             }
-            Expression::ScriptBlock(block) => {
+            ExpressionKind::Invoke { command, arguments } => {
+                self.set_position(stage.span);
+                self.invoke_command(command, arguments, input)
+            }
+            ExpressionKind::ScriptBlock(block) => {
                 // Script block in pipeline - execute it for each input item
                 if !input.is_empty() {
                     let mut results = Vec::new();
-                    let script_block = crate::value::ScriptBlock {
-                        body: block.clone(),
-                    };
+                    let script_block = crate::value::ScriptBlock::new(block.clone());
                     for item in input {
+                        self.check_time_limit()?;
                         let result = self.execute_script_block(&script_block, item)?;
                         results.push(result);
                     }
-                    Ok(results)
+                    Ok(unroll(results))
                 } else {
                     // No pipeline input, just return the script block as a value
-                    let result = self.eval_expression(stage.clone())?;
+                    let result = self.eval_expression(stage)?;
                     Ok(vec![result])
                 }
             }
@@ -181,19 +2023,20 @@ impl Evaluator {
                 if !input.is_empty() {
                     let mut results = Vec::new();
                     for item in input {
+                        self.check_time_limit()?;
                         // Set $_ to the current pipeline item
                         self.set_variable("_", item.clone());
-                        let result = self.eval_expression(stage.clone())?;
+                        let result = self.eval_expression(stage)?;
                         results.push(result);
                     }
-                    Ok(results)
+                    Ok(unroll(results))
                 } else {
                     // No pipeline input, just evaluate the expression
-                    let result = self.eval_expression(stage.clone())?;
+                    let result = self.eval_expression(stage)?;
 
                     // If the result is an array, unroll it to the pipeline
                     if let Value::Array(items) = result {
-                        Ok(items)
+                        Ok(items.unwrap_or_clone())
                     } else {
                         Ok(vec![result])
                     }
@@ -202,26 +2045,50 @@ impl Evaluator {
         }
     }
 
-    /// Execute a cmdlet call
+    /// Execute a cmdlet call, once the debugger has had its chance to
+    /// stop at it
     fn execute_cmdlet_call(
         &mut self,
         name: &str,
         arguments: &[pwsh_parser::Argument],
         input: Vec<Value>,
+    ) -> Result<Vec<Value>, RuntimeError> {
+        if self.debugger.is_active() {
+            let script = self.call_stack.last().and_then(CallFrame::script_name);
+            if let Some(index) = self.debugger.command_hit(script, name) {
+                self.hit_breakpoint(index)?;
+            }
+        }
+        self.run_command(name, arguments, input)
+    }
+
+    /// Run a function, cmdlet or program by name
+    fn run_command(
+        &mut self,
+        name: &str,
+        arguments: &[pwsh_parser::Argument],
+        input: Vec<Value>,
     ) -> Result<Vec<Value>, RuntimeError> {
         // First, check if this is a user-defined function
         if let Some(Value::Function(func)) = self.scope.get_variable(name) {
-            // Call the user-defined function
-            let result = self.call_function(&func, arguments)?;
-            return Ok(vec![result]);
+            return Ok(unroll(self.call_function(&func, arguments, input)?));
         }
 
         // If not a function, try cmdlets
-        use crate::cmdlet::CmdletContext;
-
-        // Check if cmdlet exists
+        // Check if cmdlet exists, or else a program of that name
         if !self.cmdlet_registry.contains(name) {
-            return Err(RuntimeError::UndefinedFunction(name.to_string()));
+            if self.native_commands {
+                if let Some(program) = native::find_program(name) {
+                    // A program can do anything, so it needs every capability
+                    self.limits.check_command(name, &Capability::ALL)?;
+                    return self.run_native_command(program, arguments, input);
+                }
+            }
+            // The last resort, as in PowerShell: a module that has it
+            if self.autoload_command(name)? {
+                return self.run_command(name, arguments, input);
+            }
+            return Err(self.undefined_command(name));
         }
 
         // Build cmdlet context by evaluating arguments first
@@ -231,56 +2098,251 @@ impl Evaluator {
         for arg in arguments {
             match arg {
                 pwsh_parser::Argument::Positional(expr) => {
-                    let value = self.eval_expression(expr.clone())?;
-                    positional_args.push(value);
+                    let value = self.eval_expression(expr)?;
+                    positional_args.push(value);
+                }
+                pwsh_parser::Argument::Named {
+                    name: param_name,
+                    value,
+                    ..
+                } => {
+                    let val = self.eval_expression(value)?;
+                    context.parameters.insert(param_name.clone(), val);
+                }
+                pwsh_parser::Argument::StopParsing { text, .. } => {
+                    positional_args.extend(verbatim_arguments(text));
+                }
+            }
+        }
+        context.arguments = positional_args;
+
+        // The common parameters every cmdlet takes, handled here rather than
+        // by the cmdlet
+        self.resolve_error_action(&mut context)?;
+        let out_variable = context.parameters.shift_remove("OutVariable");
+        let error_variable = context.parameters.shift_remove("ErrorVariable");
+
+        // A shared handle leaves the registry free for the cmdlet to change
+        let cmdlet = self
+            .cmdlet_registry
+            .get_shared(name)
+            .ok_or_else(|| self.undefined_command(name))?;
+
+        self.check_cmdlet_allowed(cmdlet.as_ref())?;
+        for hooks in &mut self.instrumentation {
+            hooks.on_cmdlet_invoke(cmdlet.name(), &context)?;
+        }
+        if error_variable.is_some() {
+            self.error_collectors.push(Vec::new());
+        }
+        let result = cmdlet.execute(context, self);
+
+        if let Some(name) = error_variable {
+            let mut errors = self.error_collectors.pop().unwrap_or_default();
+            // An error that stops the cmdlet is one of its errors too
+            if let Err(error) = &result {
+                if !error.is_control_flow() {
+                    errors.push(error.to_value());
+                }
+            }
+            self.write_common_variable(&name, errors);
+        }
+        if let (Some(name), Ok(output)) = (out_variable, &result) {
+            self.write_common_variable(&name, output.clone());
+        }
+        result
+    }
+
+    /// Run what the call operator's operand evaluates to: a script block
+    /// runs with the arguments in $args, or once per pipeline item with $_;
+    /// anything else names a command, looked up now as any command is
+    /// (function, then cmdlet, then program), so `& $name` and
+    /// `& 'C:\Program Files\tool.exe'` work
+    pub(crate) fn invoke_command(
+        &mut self,
+        command: &Expression,
+        arguments: &[pwsh_parser::Argument],
+        input: Vec<Value>,
+    ) -> Result<Vec<Value>, RuntimeError> {
+        match self.eval_expression(command)? {
+            Value::ScriptBlock(block) if !input.is_empty() => {
+                let mut results = Vec::new();
+                for item in input {
+                    results.push(self.execute_script_block(&block, item)?);
+                }
+                Ok(unroll(results))
+            }
+            Value::ScriptBlock(block) => {
+                let mut args = Vec::new();
+                for arg in arguments {
+                    match arg {
+                        pwsh_parser::Argument::Positional(expr) => {
+                            args.push(self.eval_expression(expr)?);
+                        }
+                        // Script blocks have no parameters to bind names to
+                        pwsh_parser::Argument::Named { name, value, .. } => {
+                            args.push(Value::String(format!("-{}", name).into()));
+                            if value.kind != ExpressionKind::Literal(Literal::Boolean(true)) {
+                                args.push(self.eval_expression(value)?);
+                            }
+                        }
+                        pwsh_parser::Argument::StopParsing { text, .. } => {
+                            args.extend(verbatim_arguments(text));
+                        }
+                    }
+                }
+                Ok(unroll(vec![self.invoke_script_block(&block, args)?]))
+            }
+            Value::Function(func) => Ok(unroll(self.call_function(&func, arguments, input)?)),
+            Value::Null => Err(RuntimeError::InvalidOperation(
+                "The expression after '&' in a pipeline element produced an object that was not valid. It must result in a command name, a script block, or a CommandInfo object.".to_string(),
+            )),
+            name => {
+                let name = name.to_string();
+                if name.is_empty() {
+                    return Err(self.undefined_command(&name));
+                }
+                self.execute_cmdlet_call(&name, arguments, input)
+            }
+        }
+    }
+
+    /// Run `program` as a native command: its output lines are the command's
+    /// output, and its exit code goes in $LASTEXITCODE
+    fn run_native_command(
+        &mut self,
+        program: std::path::PathBuf,
+        arguments: &[pwsh_parser::Argument],
+        input: Vec<Value>,
+    ) -> Result<Vec<Value>, RuntimeError> {
+        let passing = self.native_argument_passing()?;
+        let mut args = Vec::new();
+        let mut verbatim = None;
+        for arg in arguments {
+            match arg {
+                pwsh_parser::Argument::Positional(expr) => {
+                    let value = self.eval_expression(expr)?;
+                    args.extend(native::argument_strings(&value));
                 }
-                pwsh_parser::Argument::Named {
-                    name: param_name,
-                    value,
-                } => {
-                    let val = self.eval_expression(value.clone())?;
-                    context.parameters.insert(param_name.clone(), val);
+                // -la is a switch to the parser, but a program wants it back
+                pwsh_parser::Argument::Named { name, value, .. } => {
+                    args.push(format!("-{}", name));
+                    if value.kind != ExpressionKind::Literal(Literal::Boolean(true)) {
+                        let value = self.eval_expression(value)?;
+                        args.extend(native::argument_strings(&value));
+                    }
                 }
+                pwsh_parser::Argument::StopParsing { text, .. } => verbatim = Some(text.clone()),
             }
         }
-        context.arguments = positional_args;
 
-        // Create a raw pointer to self to work around borrow checker
-        // SAFETY: We ensure that the cmdlet execution doesn't invalidate the registry reference
-        // The cmdlet will only mutate the scope/state, not the registry itself
-        let self_ptr = self as *mut Evaluator;
-        let cmdlet = self
+        let command = native::NativeCommand {
+            program,
+            args,
+            passing,
+            verbatim,
+        };
+        let (output, exit_code) = command.run(&input)?;
+        self.scope
+            .set_variable_qualified("global:LASTEXITCODE", Value::Number(exit_code as f64));
+        Ok(output)
+    }
+
+    /// Store the values from -OutVariable or -ErrorVariable in the caller's
+    /// variable, always as an array; a name starting with `+` adds them to
+    /// what the variable holds instead
+    fn write_common_variable(&mut self, name: &Value, values: Vec<Value>) {
+        let name = name.to_string();
+        let (name, values) = match name.strip_prefix('+') {
+            Some(name) => {
+                let mut existing = match self.scope.get_variable_qualified(name) {
+                    Some(Value::Array(items)) => items.unwrap_or_clone(),
+                    Some(Value::Null) | None => Vec::new(),
+                    Some(other) => vec![other],
+                };
+                existing.extend(values);
+                (name, existing)
+            }
+            None => (name.as_str(), values),
+        };
+        self.scope
+            .set_variable_qualified(name, Value::Array(values.into()));
+    }
+
+    /// Refuse to run a cmdlet the language mode or the limits don't allow,
+    /// or any cmdlet once the script has run out of time
+    pub(crate) fn check_cmdlet_allowed(&self, cmdlet: &dyn Cmdlet) -> Result<(), RuntimeError> {
+        if !self.language_mode.allows_command(cmdlet.name()) {
+            return Err(self.language_mode.command_error(cmdlet.name()));
+        }
+        self.limits
+            .check_command(cmdlet.name(), cmdlet.capabilities())?;
+        self.check_time_limit()
+    }
+
+    /// The error for a command that isn't defined, suggesting the cmdlets
+    /// and functions with names like it
+    pub(crate) fn undefined_command(&self, name: &str) -> RuntimeError {
+        let functions = self.scope.function_names();
+        let candidates = self
             .cmdlet_registry
-            .get(name)
-            .ok_or_else(|| RuntimeError::UndefinedFunction(name.to_string()))?;
+            .names()
+            .into_iter()
+            .chain(functions.iter().map(String::as_str));
 
-        // Execute the cmdlet
-        // SAFETY: The cmdlet reference and the mutable self reference don't overlap in memory
-        // as cmdlet points into the registry and the mutable operations affect scope/state
-        unsafe { cmdlet.execute(context, &mut *self_ptr) }
+        RuntimeError::UndefinedFunction {
+            name: name.to_string(),
+            suggestions: suggest::similar_names(name, candidates),
+        }
     }
 
-    /// Call a user-defined function
+    /// Call a user-defined function with the given pipeline input
     fn call_function(
         &mut self,
         func: &crate::value::Function,
         arguments: &[pwsh_parser::Argument],
-    ) -> EvalResult {
-        // Create a new scope for the function
-        self.scope.push_scope();
+        input: Vec<Value>,
+    ) -> Result<Vec<Value>, RuntimeError> {
+        if self.scope.trace() >= 2 {
+            self.trace(&format!("    ! CALL function '{}'", func.name));
+        }
+        let frame = CallFrame::new(&func.name, func.source.clone());
+        self.call(frame, |this| {
+            // Create a new scope for the function
+            this.push_scope()?;
 
+            let result = this
+                .bind_function_parameters(func, arguments)
+                .and_then(|_| this.run_function_blocks(func, input));
+
+            // Pop the function scope
+            this.scope.pop_scope();
+
+            result
+        })
+    }
+
+    /// Bind a function's parameters in the current scope
+    fn bind_function_parameters(
+        &mut self,
+        func: &crate::value::Function,
+        arguments: &[pwsh_parser::Argument],
+    ) -> Result<(), RuntimeError> {
         // Evaluate arguments
         let mut positional_args = Vec::new();
         for arg in arguments {
             match arg {
                 pwsh_parser::Argument::Positional(expr) => {
-                    let value = self.eval_expression(expr.clone())?;
+                    let value = self.eval_expression(expr)?;
                     positional_args.push(value);
                 }
                 pwsh_parser::Argument::Named { name, .. } => {
                     // Named parameters not yet supported for user functions
                     // Log a warning and skip this argument
-                    eprintln!("Warning: Named parameter '-{}' is not yet supported for user-defined functions and will be ignored", name);
+                    self.host.write_warning_line(&format!("Named parameter '-{}' is not yet supported for user-defined functions and will be ignored", name));
+                }
+                pwsh_parser::Argument::StopParsing { text, .. } => {
+                    positional_args.extend(verbatim_arguments(text));
                 }
             }
         }
@@ -289,10 +2351,11 @@ impl Evaluator {
         for (i, param) in func.parameters.iter().enumerate() {
             let value = if i < positional_args.len() {
                 // Use provided argument
+                self.note_argument(&param.name, &positional_args[i]);
                 positional_args[i].clone()
             } else if let Some(default_expr) = &param.default_value {
                 // Use default value
-                self.eval_expression(default_expr.clone())?
+                self.eval_expression(default_expr)?
             } else {
                 // No value provided and no default - use Null
                 Value::Null
@@ -300,21 +2363,74 @@ impl Evaluator {
             self.scope.define_variable(&param.name, value);
         }
 
-        // Execute the function body
-        let result = self.eval_function_body(&func.body);
+        Ok(())
+    }
 
-        // Pop the function scope
-        self.scope.pop_scope();
+    /// Run a function's blocks against its pipeline input:
+    /// - a filter runs its body once per item with $_ bound
+    /// - begin runs once, process once per item with $_ bound, and end
+    ///   (the plain body) once with all input available as $input
+    fn run_function_blocks(
+        &mut self,
+        func: &crate::value::Function,
+        input: Vec<Value>,
+    ) -> Result<Vec<Value>, RuntimeError> {
+        // Only non-null block results are written to the pipeline
+        let mut results = Vec::new();
+        let mut emit = |result: Value| {
+            if result != Value::Null {
+                results.push(result);
+            }
+        };
 
-        result
+        if func.is_filter {
+            if input.is_empty() {
+                emit(self.eval_function_body(&func.body)?);
+            }
+            for item in input {
+                self.scope.define_variable("_", item);
+                emit(self.eval_function_body(&func.body)?);
+            }
+            return Ok(results);
+        }
+
+        if let Some(begin) = &func.begin {
+            emit(self.eval_function_body(begin)?);
+        }
+
+        if let Some(process) = &func.process {
+            if input.is_empty() {
+                emit(self.eval_function_body(process)?);
+            }
+            for item in &input {
+                self.scope.define_variable("_", item.clone());
+                emit(self.eval_function_body(process)?);
+            }
+        }
+
+        self.scope
+            .define_variable("input", Value::Array(input.into()));
+        emit(self.eval_function_body(&func.body)?);
+
+        Ok(results)
     }
 
-    /// Evaluate a function body (handles return statements specially)
+    /// Evaluate a function body: its output is everything its statements
+    /// wrote, up to and including the value of a return
     fn eval_function_body(&mut self, block: &Block) -> EvalResult {
+        match self.eval_statements(&block.statements) {
+            Err(RuntimeError::EarlyReturn(output)) => Ok(output),
+            result => result,
+        }
+    }
+
+    /// Evaluate a method body: unlike a function, only the value of its
+    /// last statement (or of a return) comes out
+    fn eval_method_body(&mut self, block: &Block) -> EvalResult {
         let mut result = Value::Null;
 
         for statement in &block.statements {
-            match self.eval_statement(statement.clone()) {
+            match self.eval_statement(statement) {
                 Ok(val) => result = val,
                 Err(RuntimeError::EarlyReturn(return_value)) => {
                     // Catch early return and return the value
@@ -327,15 +2443,40 @@ impl Evaluator {
         Ok(result)
     }
 
-    /// Evaluate a block of statements
-    fn eval_block(&mut self, block: Block) -> EvalResult {
-        self.scope.push_scope();
-        let mut result = Value::Null;
-
-        for statement in block.statements {
-            result = self.eval_statement(statement)?;
+    /// Evaluate statements, collecting each one's output. A return, break
+    /// or continue ends them, carrying the output so far plus its own.
+    fn eval_statements(&mut self, statements: &[Statement]) -> EvalResult {
+        let mut output = Vec::with_capacity(statements.len());
+        for statement in statements {
+            match self.eval_statement(statement) {
+                Ok(value) => output.push(value),
+                Err(error) => {
+                    return Err(map_carried_output(error, |value| {
+                        output.push(value);
+                        collect_output(output)
+                    }))
+                }
+            }
         }
+        Ok(collect_output(output))
+    }
+
+    /// Evaluate a block of statements
+    /// Run a catch block with the error record in $_
+    fn eval_catch(&mut self, block: &Block, error: RuntimeError, record: Value) -> EvalResult {
+        self.push_block_scope()?;
+        self.scope.set_variable("_", record);
+        self.caught_errors.push(error);
+        let result = self.eval_statements(&block.statements);
+        self.caught_errors.pop();
+        let result = result?;
+        self.scope.pop_scope();
+        Ok(result)
+    }
 
+    fn eval_block(&mut self, block: &Block) -> EvalResult {
+        self.push_block_scope()?;
+        let result = self.eval_statements(&block.statements)?;
         self.scope.pop_scope();
         Ok(result)
     }
@@ -347,129 +2488,844 @@ impl Evaluator {
         script_block: &crate::value::ScriptBlock,
         pipeline_value: Value,
     ) -> EvalResult {
-        self.scope.push_scope();
+        self.run_script_block(script_block, "_", pipeline_value)
+    }
+
+    /// Invoke a script block as `$block.Invoke(...)` does, with the
+    /// arguments in $args
+    pub fn invoke_script_block(
+        &mut self,
+        script_block: &crate::value::ScriptBlock,
+        args: Vec<Value>,
+    ) -> EvalResult {
+        self.run_script_block(script_block, "args", Value::Array(args.into()))
+    }
+
+    /// Run a script block in a new scope holding the variables it closed
+    /// over and `variable` (its input)
+    fn run_script_block(
+        &mut self,
+        script_block: &crate::value::ScriptBlock,
+        variable: &str,
+        value: Value,
+    ) -> EvalResult {
+        self.call(self.frame_here(SCRIPT_BLOCK_COMMAND), |this| {
+            this.push_scope()?;
 
-        // Set $_ to the current pipeline value
-        self.scope.set_variable_qualified("_", pipeline_value);
+            for (name, captured) in script_block.captured() {
+                this.scope.define_variable(name, captured.clone());
+            }
+            this.scope.set_variable_qualified(variable, value);
 
-        let mut result = Value::Null;
-        for statement in &script_block.body.statements {
-            result = self.eval_statement(statement.clone())?;
+            let result = if this.compiles() {
+                this.run_compiled(script_block.compiled())
+            } else {
+                this.eval_statements(&script_block.body.statements)
+            };
+
+            this.scope.pop_scope();
+            // A return ends only this run of the block, keeping its output
+            match result {
+                Err(RuntimeError::EarlyReturn(output)) => Ok(output),
+                result => result,
+            }
+        })
+    }
+
+    /// Run compiled instructions, returning the value they leave
+    fn run_compiled(&mut self, code: &CompiledBlock) -> EvalResult {
+        let ops = code.ops();
+        let mut machine = Machine::default();
+
+        while let Some(op) = ops.get(machine.pc) {
+            machine.pc += 1;
+            let Err(error) = self.run_op(op, ops, &mut machine) else {
+                continue;
+            };
+            let is_break = matches!(error, RuntimeError::Break(_));
+            match (error, machine.loops.last_mut()) {
+                // The iteration's output is whatever its statements wrote
+                // so far; then go on with the loop, or leave it
+                (RuntimeError::Break(value) | RuntimeError::Continue(value), Some(innermost)) => {
+                    let mut output = machine.stack.split_off(innermost.start);
+                    output.push(value);
+                    innermost.results.push(collect_output(output));
+                    self.scope.truncate(innermost.scope_depth);
+                    machine.pc = if is_break {
+                        innermost.exit
+                    } else {
+                        innermost.next
+                    };
+                }
+                (error, _) if error.is_control_flow() => {
+                    let output = machine.pending_output();
+                    return Err(map_carried_output(error, |value| {
+                        let mut output = output;
+                        output.push(value);
+                        collect_output(output)
+                    }));
+                }
+                (error, _) => return Err(error),
+            }
         }
 
-        self.scope.pop_scope();
-        Ok(result)
+        Ok(machine.stack.pop().unwrap_or(Value::Null))
+    }
+
+    /// Run one instruction
+    fn run_op(&mut self, op: &Op, ops: &[Op], machine: &mut Machine) -> Result<(), RuntimeError> {
+        let stack = &mut machine.stack;
+        let pop = |stack: &mut Vec<Value>| stack.pop().unwrap_or(Value::Null);
+        let located = |evaluator: &mut Self, result: EvalResult, span: Span| {
+            result.inspect_err(|e| evaluator.locate_error(e, span))
+        };
+
+        match op {
+            Op::Constant(value) => stack.push(value.clone()),
+            Op::Load(name) => stack.push(self.variable_value(name)?),
+            Op::Store(name) => {
+                let value = pop(stack);
+                self.assign_variable(name, value)?;
+            }
+            Op::Output(count) => {
+                let output = stack.split_off(stack.len() - count);
+                stack.push(collect_output(output));
+            }
+            Op::Binary(operator, span) => {
+                let right = pop(stack);
+                let left = pop(stack);
+                let result = self.apply_binary_op(left, operator, right);
+                stack.push(located(self, result, *span)?);
+            }
+            Op::Unary(operator, span) => {
+                let operand = pop(stack);
+                let result = self.eval_unary_op(operator, operand);
+                stack.push(located(self, result, *span)?);
+            }
+            Op::Member(member, span) => {
+                let object = pop(stack);
+                let result = Self::member_value(&object, member);
+                stack.push(located(self, result, *span)?);
+            }
+            Op::Index(span) => {
+                let index = pop(stack);
+                let object = pop(stack);
+                let result = self.eval_index(object, index);
+                stack.push(located(self, result, *span)?);
+            }
+            Op::Cast(type_name, span) => {
+                let value = pop(stack);
+                let result = convert::cast(type_name, value, &self.types);
+                stack.push(located(self, result, *span)?);
+            }
+            Op::Interpolate(parts) => stack.push(self.interpolate(parts)?),
+            Op::MakeArray(count) => {
+                let items = stack.split_off(stack.len() - count);
+                stack.push(Value::Array(items.into()));
+            }
+            Op::MakeHashtable(keys) => {
+                let values = stack.split_off(stack.len() - keys.len());
+                let map: PropertyMap = keys.iter().cloned().zip(values).collect();
+                stack.push(Value::Object(map.into()));
+            }
+            Op::Jump(to) => machine.pc = *to,
+            Op::JumpIfFalse(to) => {
+                if !pop(stack).to_bool() {
+                    machine.pc = *to;
+                }
+            }
+            Op::PushScope => self.push_block_scope()?,
+            Op::PopScope => {
+                self.scope.pop_scope();
+            }
+            Op::ForeachStart => {
+                let items = match pop(stack) {
+                    Value::Array(items) => items.unwrap_or_clone(),
+                    Value::Null => Vec::new(),
+                    other => vec![other],
+                };
+                // The loop's ForeachNext comes right after
+                let exit = match ops.get(machine.pc) {
+                    Some(Op::ForeachNext { exit, .. }) => *exit,
+                    _ => ops.len(),
+                };
+                machine.loops.push(LoopState {
+                    items: items.into_iter(),
+                    results: Vec::new(),
+                    start: stack.len(),
+                    scope_depth: self.scope.depth(),
+                    next: machine.pc,
+                    exit,
+                });
+            }
+            Op::ForeachNext { variable, exit } => {
+                match machine
+                    .loops
+                    .last_mut()
+                    .and_then(|innermost| innermost.items.next())
+                {
+                    Some(item) => self.scope.set_variable_qualified(variable, item),
+                    None => machine.pc = *exit,
+                }
+            }
+            Op::ForeachCollect => {
+                let result = pop(stack);
+                if let Some(innermost) = machine.loops.last_mut() {
+                    innermost.results.push(result);
+                }
+            }
+            Op::ForeachEnd => {
+                let results = machine
+                    .loops
+                    .pop()
+                    .map(|innermost| innermost.results)
+                    .unwrap_or_default();
+                stack.push(collect_output(results));
+            }
+            Op::Return => return Err(RuntimeError::EarlyReturn(pop(stack))),
+            Op::Expression(expr) => stack.push(self.eval_expression(expr)?),
+            Op::Statement(statement) => stack.push(self.eval_statement(statement)?),
+        }
+        Ok(())
     }
 
     /// Evaluate an expression
-    pub fn eval_expression(&mut self, expr: Expression) -> EvalResult {
+    pub fn eval_expression(&mut self, expr: &Expression) -> EvalResult {
+        let span = expr.span;
+        if let ExpressionKind::Call { .. } | ExpressionKind::Invoke { .. } = expr.kind {
+            self.set_position(span);
+        }
+        self.eval_expression_kind(&expr.kind)
+            .inspect_err(|e| self.locate_error(e, span))
+    }
+
+    fn eval_expression_kind(&mut self, expr: &ExpressionKind) -> EvalResult {
         match expr {
-            Expression::Literal(lit) => self.eval_literal(lit),
+            ExpressionKind::Literal(lit) => self.eval_literal(lit),
 
-            Expression::Variable(name) => Ok(self
-                .scope
-                .get_variable_qualified(&name)
-                .unwrap_or(Value::Number(0.0))),
+            ExpressionKind::Variable(name) => self.variable_value(name),
 
-            Expression::BinaryOp {
+            ExpressionKind::BinaryOp {
                 left,
                 operator,
                 right,
             } => {
-                let left_val = self.eval_expression(*left)?;
-                let right_val = self.eval_expression(*right)?;
-                self.eval_binary_op(left_val, operator, right_val)
+                let left_val = self.eval_expression(left)?;
+                let right_val = self.eval_expression(right)?;
+                self.apply_binary_op(left_val, operator, right_val)
             }
 
-            Expression::UnaryOp { operator, operand } => {
-                let operand_val = self.eval_expression(*operand)?;
+            ExpressionKind::UnaryOp { operator, operand } => {
+                let operand_val = self.eval_expression(operand)?;
                 self.eval_unary_op(operator, operand_val)
             }
 
-            Expression::MemberAccess { object, member } => {
-                let obj_val = self.eval_expression(*object)?;
-                obj_val.get_property(&member).ok_or_else(|| {
-                    RuntimeError::InvalidPropertyAccess(format!("Property '{}' not found", member))
+            ExpressionKind::MemberAccess { object, member } => {
+                let obj_val = self.eval_expression(object)?;
+                Self::member_value(&obj_val, member)
+            }
+
+            ExpressionKind::Index { object, index } => {
+                let obj_val = self.eval_expression(object)?;
+                let index_val = self.eval_expression(index)?;
+                self.eval_index(obj_val, index_val)
+            }
+
+            ExpressionKind::MethodCall {
+                object,
+                method,
+                arguments,
+            } => {
+                // What a method changes, every variable holding $this sees
+                let this = self.eval_expression(object)?;
+                let args = self.eval_arguments(arguments)?;
+                self.invoke_method(&this, method, args)
+            }
+
+            // A bare type literal evaluates to its name: [string]
+            ExpressionKind::TypeLiteral(name) => Ok(Value::String(name.clone().into())),
+
+            ExpressionKind::Cast {
+                type_name,
+                expression,
+            } => {
+                let value = self.eval_expression(expression)?;
+                convert::cast(type_name, value, &self.types)
+            }
+
+            ExpressionKind::StaticMember { type_name, member } => {
+                let Some(enum_type) = self.types.enum_type(type_name) else {
+                    return BuiltinType::from_name(type_name)
+                        .ok_or_else(|| Self::unknown_type(type_name))?
+                        .property(member, self.clock.as_ref());
+                };
+                enum_type.member(member).ok_or_else(|| {
+                    RuntimeError::InvalidPropertyAccess(format!(
+                        "Property '{}' not found on type [{}]",
+                        member, type_name
+                    ))
                 })
             }
 
-            Expression::Call { name, arguments } => {
+            ExpressionKind::StaticMethodCall {
+                type_name,
+                method,
+                arguments,
+            } => {
+                let args = self.eval_arguments(arguments)?;
+                // User-defined classes take precedence over built-in types
+                if self.types.class(type_name).is_none() {
+                    if let Some(builtin) = BuiltinType::from_name(type_name) {
+                        return builtin.call(method, &args, &self.culture);
+                    }
+                }
+                if method.eq_ignore_ascii_case("new") {
+                    return self.construct_instance(type_name, args);
+                }
+
+                let class = self
+                    .types
+                    .class(type_name)
+                    .cloned()
+                    .ok_or_else(|| Self::unknown_type(type_name))?;
+                let method_def = types::find_method(&class.methods, method, args.len(), true)
+                    .ok_or_else(|| {
+                        RuntimeError::InvalidOperation(format!(
+                            "Method invocation failed because [{}] does not contain a method named '{}'",
+                            class.name, method
+                        ))
+                    })?;
+                self.invoke_class_method(method_def, None, args)
+            }
+
+            ExpressionKind::Call { name, arguments } => {
                 // This is a cmdlet call - execute it with empty pipeline input
-                let results = self.execute_cmdlet_call(&name, &arguments, vec![])?;
+                let results = self.execute_cmdlet_call(name, arguments, vec![])?;
                 // Return pipeline-style results: Null, single value, or Array
                 Ok(Self::pipeline_results_to_value(results))
             }
 
-            Expression::ScriptBlock(block) => {
+            ExpressionKind::Invoke { command, arguments } => {
+                let results = self.invoke_command(command, arguments, vec![])?;
+                Ok(Self::pipeline_results_to_value(results))
+            }
+
+            ExpressionKind::ScriptBlock(block) => {
                 // Create a script block value
-                Ok(Value::ScriptBlock(crate::value::ScriptBlock {
-                    body: block.clone(),
-                }))
+                Ok(Value::ScriptBlock(crate::value::ScriptBlock::new(
+                    block.clone(),
+                )))
             }
 
-            Expression::Hashtable(pairs) => {
+            ExpressionKind::Hashtable { entries: pairs, .. } => {
                 // Create a hashtable (Object with properties)
-                let mut map = std::collections::HashMap::new();
+                let mut map = PropertyMap::new();
                 for (key, value_expr) in pairs {
-                    let value = self.eval_expression(value_expr.clone())?;
+                    let value = self.eval_expression(value_expr)?;
                     map.insert(key.clone(), value);
                 }
-                Ok(Value::Object(map))
+                Ok(Value::Object(map.into()))
             }
 
-            Expression::Array(items) => {
+            ExpressionKind::Array(items) => {
                 // Create an array
                 let mut values = Vec::new();
                 for item_expr in items {
-                    let value = self.eval_expression(item_expr.clone())?;
+                    let value = self.eval_expression(item_expr)?;
                     values.push(value);
                 }
-                Ok(Value::Array(values))
+                Ok(Value::Array(values.into()))
             }
 
-            Expression::Pipeline(pipeline) => {
+            ExpressionKind::Pipeline(pipeline) => {
                 // Execute the pipeline and return the results
-                let results = self.execute_pipeline(&pipeline)?;
+                let results = self.execute_pipeline(pipeline)?;
                 Ok(Self::pipeline_results_to_value(results))
             }
         }
     }
 
+    /// The value of a variable; undefined variables read as $null, or
+    /// fail in strict mode
+    fn variable_value(&mut self, name: &str) -> EvalResult {
+        if self.debugger.is_active() {
+            self.debug_variable(name, VariableAccess::Read)?;
+        }
+        match self.scope.get_variable_qualified(name) {
+            Some(value) => Ok(value),
+            None if self.strict_mode >= 1 && !Self::is_environment_variable(name) => {
+                Err(RuntimeError::UndefinedVariable(name.to_string()))
+            }
+            None => Ok(Value::Null),
+        }
+    }
+
+    /// `$env:NAME`, which reads as $null when unset even in strict mode
+    fn is_environment_variable(name: &str) -> bool {
+        name.get(..4)
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case("env:"))
+    }
+
+    /// Read a property, or a member every value has (Count, Length)
+    fn member_value(value: &Value, member: &str) -> EvalResult {
+        value
+            .get_property(member)
+            .or_else(|| Self::intrinsic_member(value, member))
+            .ok_or_else(|| {
+                RuntimeError::InvalidPropertyAccess(format!("Property '{}' not found", member))
+            })
+    }
+
+    /// Evaluate method call arguments in order
+    fn eval_arguments(&mut self, arguments: &[Expression]) -> Result<Vec<Value>, RuntimeError> {
+        arguments
+            .iter()
+            .map(|arg| self.eval_expression(arg))
+            .collect()
+    }
+
+    /// Create an instance of a user-defined class: [Foo]::new(args) or New-Object Foo
+    /// Properties start at their declared defaults, then the constructor
+    /// matching the argument count runs with $this bound to the new instance.
+    pub fn construct_instance(&mut self, type_name: &str, args: Vec<Value>) -> EvalResult {
+        let class = self
+            .types
+            .class(type_name)
+            .cloned()
+            .ok_or_else(|| Self::unknown_type(type_name))?;
+
+        let mut properties = PropertyMap::new();
+        for property in &class.properties {
+            let value = match &property.default_value {
+                Some(expr) => self.eval_expression(expr)?,
+                None => types::default_value(property.type_name.as_deref()),
+            };
+            properties.insert(property.name.clone(), value);
+        }
+        let instance = Value::Instance {
+            class_name: class.name.clone(),
+            properties: properties.into(),
+        };
+
+        if class.constructors.is_empty() && args.is_empty() {
+            return Ok(instance);
+        }
+        let constructor = types::find_method(&class.constructors, &class.name, args.len(), false)
+            .ok_or_else(|| {
+            RuntimeError::InvalidOperation(format!(
+                "Cannot find an overload for \"new\" and the argument count: \"{}\"",
+                args.len()
+            ))
+        })?;
+
+        self.invoke_class_method(constructor, Some(instance.clone()), args)?;
+        Ok(instance)
+    }
+
+    /// Invoke a method on a value
+    fn invoke_method(&mut self, this: &Value, method: &str, args: Vec<Value>) -> EvalResult {
+        match this {
+            Value::Instance { class_name, .. } => {
+                let class = self.types.class(class_name).cloned();
+                let Some(method_def) = class
+                    .as_ref()
+                    .and_then(|c| types::find_method(&c.methods, method, args.len(), false))
+                else {
+                    return Err(Self::missing_method(this, method));
+                };
+                self.invoke_class_method(method_def, Some(this.clone()), args)
+            }
+            // Every variable holding the table sees what the method changes
+            Value::Object(table) => match hashtable::call_method(&mut table.write(), method, &args)
+            {
+                Some(result) => result,
+                None => Err(Self::missing_method(this, method)),
+            },
+            Value::ScriptBlock(block) => match method.to_lowercase().as_str() {
+                // Snapshot the variables of the scope making the closure
+                "getnewclosure" if args.is_empty() => {
+                    let closure = block.with_captured(self.scope.local_variables());
+                    Ok(Value::ScriptBlock(closure))
+                }
+                "invoke" => self.invoke_script_block(block, args),
+                _ => Err(Self::missing_method(this, method)),
+            },
+            // (1234.5).ToString('N1'), $date.ToString('yyyy-MM-dd')
+            Value::Number(_) | Value::DateTime(_)
+                if method.eq_ignore_ascii_case("tostring") && args.len() <= 1 =>
+            {
+                let spec = args
+                    .first()
+                    .map(|spec| spec.to_string())
+                    .unwrap_or_default();
+                let text = format::format_value(this, &spec, &self.culture)?;
+                Ok(Value::String(text.into()))
+            }
+            _ => Err(Self::missing_method(this, method)),
+        }
+    }
+
+    /// Run a class method or constructor in a new scope with $this bound
+    fn invoke_class_method(
+        &mut self,
+        method: &ClassMethod,
+        this: Option<Value>,
+        args: Vec<Value>,
+    ) -> EvalResult {
+        if self.scope.trace() >= 2 {
+            self.trace(&format!("    ! CALL method '{}'", method.name));
+        }
+        let frame = self.frame_here(&method.name);
+        self.call(frame, |evaluator| {
+            evaluator.run_class_method(method, this, args)
+        })
+    }
+
+    fn run_class_method(
+        &mut self,
+        method: &ClassMethod,
+        this: Option<Value>,
+        args: Vec<Value>,
+    ) -> EvalResult {
+        self.push_scope()?;
+
+        if let Some(this) = this {
+            self.scope.define_variable("this", this);
+        }
+        let result = self
+            .bind_method_parameters(&method.parameters, args)
+            .and_then(|_| self.eval_method_body(&method.body));
+
+        self.scope.pop_scope();
+
+        // void methods never produce output
+        let is_void = method
+            .return_type
+            .as_deref()
+            .is_some_and(|t| t.eq_ignore_ascii_case("void"));
+        Ok(if is_void { Value::Null } else { result? })
+    }
+
+    /// Bind method parameters to already evaluated arguments
+    fn bind_method_parameters(
+        &mut self,
+        parameters: &[Parameter],
+        args: Vec<Value>,
+    ) -> Result<(), RuntimeError> {
+        let mut args = args.into_iter();
+        for param in parameters {
+            let value = match (args.next(), &param.default_value) {
+                (Some(value), _) => {
+                    self.note_argument(&param.name, &value);
+                    value
+                }
+                (None, Some(default_expr)) => self.eval_expression(default_expr)?,
+                (None, None) => Value::Null,
+            };
+            self.scope.define_variable(&param.name, value);
+        }
+        Ok(())
+    }
+
+    fn unknown_type(type_name: &str) -> RuntimeError {
+        ErrorRecord::type_not_found(type_name).into()
+    }
+
+    fn missing_method(value: &Value, method: &str) -> RuntimeError {
+        ErrorRecord::new(
+            ErrorCategory::InvalidOperation,
+            "MethodNotFound",
+            format!(
+                "Method invocation failed because [{}] does not contain a method named '{}'",
+                value.type_name(),
+                method
+            ),
+        )
+        .with_target(method)
+        .into()
+    }
+
+    /// Resolve members every value has in PowerShell, like .Count and .Length
+    /// Scalars behave as single-item collections and $null as an empty one.
+    fn intrinsic_member(value: &Value, member: &str) -> Option<Value> {
+        if member.eq_ignore_ascii_case("PSTypeNames") {
+            return Self::type_names(value);
+        }
+        if let Value::Object(table) = value {
+            return hashtable::property(&table.read(), member);
+        }
+
+        match member.to_lowercase().as_str() {
+            "count" | "length" => {
+                let len = match value {
+                    Value::Array(items) => items.read().len(),
+                    Value::String(s) if member.eq_ignore_ascii_case("length") => s.chars().count(),
+                    Value::Null => 0,
+                    _ => 1,
+                };
+                Some(Value::Number(len as f64))
+            }
+            _ => None,
+        }
+    }
+
+    /// An object's type names, most specific first, as `.PSTypeNames` has
+    /// them: those it was tagged with, then what it is
+    fn type_names(value: &Value) -> Option<Value> {
+        let mut names: Vec<String> = value.properties()?.type_names().to_vec();
+        match value {
+            Value::Instance { class_name, .. } => names.push(class_name.to_string()),
+            _ => names.push("System.Management.Automation.PSCustomObject".to_string()),
+        }
+        names.push("System.Object".to_string());
+        Some(Value::Array(
+            names
+                .into_iter()
+                .map(|name| Value::String(name.into()))
+                .collect::<Vec<_>>()
+                .into(),
+        ))
+    }
+
+    /// Evaluate an index operation: $arr[0], $arr[-1], $str[0], $hash["key"]
+    /// Out-of-range indexes produce $null, matching PowerShell.
+    fn eval_index(&self, target: Value, index: Value) -> EvalResult {
+        if let Value::Object(_) = target {
+            return Ok(target
+                .get_property(&index.to_string())
+                .unwrap_or(Value::Null));
+        }
+
+        let i = index
+            .to_number()
+            .ok_or_else(|| RuntimeError::TypeMismatch {
+                expected: "number".to_string(),
+                got: format!("{:?}", index),
+                operation: "index".to_string(),
+            })? as i64;
+
+        let resolve = |len: usize| -> Option<usize> {
+            let idx = if i < 0 { len as i64 + i } else { i };
+            (idx >= 0 && (idx as usize) < len).then_some(idx as usize)
+        };
+
+        match target {
+            Value::Array(items) => {
+                let items = items.read();
+                Ok(resolve(items.len())
+                    .map(|idx| items[idx].clone())
+                    .unwrap_or(Value::Null))
+            }
+            Value::String(s) => {
+                let chars: Vec<char> = s.chars().collect();
+                Ok(resolve(chars.len())
+                    .map(|idx| Value::String(chars[idx].to_string().into()))
+                    .unwrap_or(Value::Null))
+            }
+            Value::Null => Ok(Value::Null),
+            // Scalars index like a single-item collection
+            other => Ok(if resolve(1).is_some() {
+                other
+            } else {
+                Value::Null
+            }),
+        }
+    }
+
+    /// Assign a value to an element or property chain rooted at a variable
+    /// ($arr[0] = v, $hash["key"] = v, $obj.Prop.Inner = v).
+    /// The variable's value is updated in place and written back to its scope.
+    fn assign_to_target(&mut self, target: &Expression, value: Value) -> Result<(), RuntimeError> {
+        // Walk the chain from the outermost accessor down to the root variable,
+        // evaluating index expressions along the way
+        let mut accessors = Vec::new();
+        let mut current = target;
+        let variable = loop {
+            match &current.kind {
+                ExpressionKind::Index { object, index } => {
+                    accessors.push(Accessor::Index(self.eval_expression(index)?));
+                    current = object;
+                }
+                ExpressionKind::MemberAccess { object, member } => {
+                    accessors.push(Accessor::Member(member.clone()));
+                    current = object;
+                }
+                ExpressionKind::Variable(name) => break name,
+                _ => {
+                    return Err(RuntimeError::InvalidOperation(
+                        "The assignment target must be a variable, element, or property"
+                            .to_string(),
+                    ))
+                }
+            }
+        };
+        accessors.reverse();
+
+        let mut root = self
+            .scope
+            .get_variable_qualified(variable)
+            .ok_or_else(|| RuntimeError::UndefinedVariable(variable.clone()))?;
+        Self::assign_path(&mut root, &accessors, value)?;
+        self.scope.set_variable_qualified(variable, root);
+        Ok(())
+    }
+
+    /// Recursively apply an assignment along an accessor path
+    /// Each step works on a copy of the element or property it passes
+    /// through and stores it back, so no lock is held while the rest of
+    /// the path (which may lead back to the same table) is assigned.
+    fn assign_path(
+        target: &mut Value,
+        accessors: &[Accessor],
+        value: Value,
+    ) -> Result<(), RuntimeError> {
+        let Some((accessor, rest)) = accessors.split_first() else {
+            *target = value;
+            return Ok(());
+        };
+
+        let key = match accessor {
+            Accessor::Member(name) => name.clone(),
+            Accessor::Index(index) => match target {
+                Value::Array(items) => {
+                    let len = items.read().len() as i64;
+                    let i = index.to_number().map(|n| n as i64).ok_or_else(|| {
+                        RuntimeError::TypeMismatch {
+                            expected: "number".to_string(),
+                            got: format!("{:?}", index),
+                            operation: "index".to_string(),
+                        }
+                    })?;
+                    let resolved = if i < 0 { len + i } else { i };
+                    if resolved < 0 || resolved >= len {
+                        return Err(RuntimeError::InvalidOperation(format!(
+                            "Index {} was outside the bounds of the array",
+                            i
+                        )));
+                    }
+                    let mut item = items.read()[resolved as usize].clone();
+                    Self::assign_path(&mut item, rest, value)?;
+                    items.write()[resolved as usize] = item;
+                    return Ok(());
+                }
+                _ => index.to_string(),
+            },
+        };
+
+        match target {
+            Value::Object(_) | Value::Instance { .. } if rest.is_empty() => target
+                .set_property(&key, value)
+                .map_err(RuntimeError::InvalidOperation),
+            Value::Object(_) | Value::Instance { .. } => {
+                let mut inner = target.get_property(&key).ok_or_else(|| {
+                    RuntimeError::InvalidPropertyAccess(format!("Property '{}' not found", key))
+                })?;
+                Self::assign_path(&mut inner, rest, value)?;
+                target
+                    .set_property(&key, inner)
+                    .map_err(RuntimeError::InvalidOperation)
+            }
+            Value::Null => Err(RuntimeError::InvalidOperation(
+                "Cannot index into a null value".to_string(),
+            )),
+            other => Err(RuntimeError::InvalidPropertyAccess(format!(
+                "Cannot set '{}' on value '{}'",
+                key, other
+            ))),
+        }
+    }
+
     /// Evaluate a literal value
-    fn eval_literal(&mut self, literal: Literal) -> EvalResult {
+    fn eval_literal(&mut self, literal: &Literal) -> EvalResult {
         match literal {
-            Literal::Number(n) => Ok(Value::Number(n)),
-            Literal::String(s) => Ok(Value::String(s)),
-            Literal::Boolean(b) => Ok(Value::Boolean(b)),
+            Literal::Number(n) => Ok(Value::Number(*n)),
+            Literal::String(s) => Ok(Value::String(s.clone().into())),
+            Literal::Boolean(b) => Ok(Value::Boolean(*b)),
             Literal::Null => Ok(Value::Null),
-            Literal::InterpolatedString(parts) => {
-                let mut result = String::new();
-                for part in parts {
-                    match part {
-                        StringPart::Literal(s) => result.push_str(&s),
-                        StringPart::Variable(name) => {
-                            let value = self
-                                .scope
-                                .get_variable(&name)
-                                .unwrap_or(Value::String("".to_string()));
-                            result.push_str(&value.to_string());
-                        }
+            Literal::InterpolatedString(parts) => self.interpolate(parts),
+        }
+    }
+
+    /// Expand the variables in a double-quoted string. Undefined ones expand
+    /// to nothing, except from strict mode 2.0 on, where they fail.
+    fn interpolate(&self, parts: &[StringPart]) -> EvalResult {
+        let mut result = String::new();
+        for part in parts {
+            match part {
+                StringPart::Literal(s) => result.push_str(s),
+                StringPart::Variable(name) => match self.scope.get_variable_qualified(name) {
+                    Some(value) => result.push_str(&value.to_string()),
+                    None if self.strict_mode >= 2 && !Self::is_environment_variable(name) => {
+                        return Err(RuntimeError::UndefinedVariable(name.clone()));
                     }
-                }
-                Ok(Value::String(result))
+                    None => {}
+                },
             }
         }
+        Ok(Value::String(result.into()))
+    }
+
+    /// Apply a binary operator; -match and -notmatch also set $matches
+    fn apply_binary_op(
+        &mut self,
+        left: Value,
+        operator: &BinaryOperator,
+        right: Value,
+    ) -> EvalResult {
+        if let BinaryOperator::Match | BinaryOperator::NotMatch = operator {
+            return self.eval_match(left, operator, right);
+        }
+        self.eval_binary_op(left, operator, right)
     }
 
     /// Evaluate a binary operation
-    fn eval_binary_op(&self, left: Value, operator: BinaryOperator, right: Value) -> EvalResult {
+    fn eval_binary_op(&self, left: Value, operator: &BinaryOperator, right: Value) -> EvalResult {
         match operator {
             // Arithmetic operators
             BinaryOperator::Add => match (&left, &right) {
                 (Value::Number(l), Value::Number(r)) => Ok(Value::Number(l + r)),
-                (Value::String(l), Value::String(r)) => Ok(Value::String(format!("{}{}", l, r))),
-                (Value::String(l), r) => Ok(Value::String(format!("{}{}", l, r))),
-                (l, Value::String(r)) => Ok(Value::String(format!("{}{}", l, r))),
+                // $null adds nothing, so an unset counter can be incremented
+                (Value::Null, r) => Ok(r.clone()),
+                (Value::Number(_), Value::Null) => Ok(left),
+                // @(1, 2) + @(3) concatenates, @(1, 2) + 3 appends
+                (Value::Array(l), Value::Array(r)) => Ok(Value::Array(
+                    l.read()
+                        .iter()
+                        .chain(r.read().iter())
+                        .cloned()
+                        .collect::<Vec<_>>()
+                        .into(),
+                )),
+                (Value::Array(l), r) => {
+                    let mut items = l.read().to_vec();
+                    items.push(r.clone());
+                    Ok(Value::Array(items.into()))
+                }
+                // The sum is a new table; neither side changes
+                (Value::Object(l), Value::Object(r)) => {
+                    let merged = Value::Object(l.copy());
+                    for (key, value) in r.read().iter() {
+                        if merged.get_property(key).is_some() {
+                            return Err(RuntimeError::InvalidOperation(format!(
+                                "Item has already been added. Key in dictionary: '{}'",
+                                key
+                            )));
+                        }
+                        merged
+                            .set_property(key, value.clone())
+                            .map_err(RuntimeError::InvalidOperation)?;
+                    }
+                    Ok(merged)
+                }
+                (Value::String(l), Value::String(r)) => {
+                    Ok(Value::String(format!("{}{}", l, r).into()))
+                }
+                (Value::String(l), r) => Ok(Value::String(format!("{}{}", l, r).into())),
+                // A number on the left converts the string: 1 + "0x10" is 17
+                (Value::Number(l), Value::String(r)) if convert::parse_number(r).is_some() => Ok(
+                    Value::Number(l + convert::parse_number(r).unwrap_or_default()),
+                ),
+                (l, Value::String(r)) => Ok(Value::String(format!("{}{}", l, r).into())),
                 _ => Err(RuntimeError::TypeMismatch {
                     expected: "number or string".to_string(),
                     got: format!("{:?} and {:?}", left, right),
@@ -481,23 +3337,54 @@ impl Evaluator {
                 self.numeric_binary_op(left, right, "subtraction", |l, r| l - r)
             }
 
-            BinaryOperator::Multiply => {
-                self.numeric_binary_op(left, right, "multiplication", |l, r| l * r)
-            }
+            // "ab" * 3 repeats the string, @(0) * 5 repeats the array
+            BinaryOperator::Multiply => match left {
+                Value::String(s) => {
+                    let count = self.repeat_count(&right, s.len())?;
+                    let mut repeated = String::new();
+                    repeated
+                        .try_reserve_exact(s.len() * count)
+                        .map_err(|_| repeat_too_large(count))?;
+                    let check_every = (REPEAT_CHECK_BYTES / s.len().max(1)).max(1);
+                    for i in 0..count {
+                        if i % check_every == 0 {
+                            self.check_time_limit()?;
+                        }
+                        repeated.push_str(&s);
+                    }
+                    Ok(Value::String(repeated.into()))
+                }
+                Value::Array(items) => {
+                    let items = items.read();
+                    let unit_bytes = items.len() * std::mem::size_of::<Value>();
+                    let count = self.repeat_count(&right, unit_bytes)?;
+                    let mut repeated = Vec::new();
+                    repeated
+                        .try_reserve_exact(items.len() * count)
+                        .map_err(|_| repeat_too_large(count))?;
+                    let check_every = (REPEAT_CHECK_BYTES / unit_bytes.max(1)).max(1);
+                    for i in 0..count {
+                        if i % check_every == 0 {
+                            self.check_time_limit()?;
+                        }
+                        repeated.extend(items.iter().cloned());
+                    }
+                    Ok(Value::Array(repeated.into()))
+                }
+                left => self.numeric_binary_op(left, right, "multiplication", |l, r| l * r),
+            },
 
             BinaryOperator::Divide => {
-                let l = left.to_number().ok_or_else(|| RuntimeError::TypeMismatch {
+                let l = arithmetic_operand(&left).ok_or_else(|| RuntimeError::TypeMismatch {
                     expected: "number".to_string(),
                     got: format!("{:?}", left),
                     operation: "division".to_string(),
                 })?;
-                let r = right
-                    .to_number()
-                    .ok_or_else(|| RuntimeError::TypeMismatch {
-                        expected: "number".to_string(),
-                        got: format!("{:?}", right),
-                        operation: "division".to_string(),
-                    })?;
+                let r = arithmetic_operand(&right).ok_or_else(|| RuntimeError::TypeMismatch {
+                    expected: "number".to_string(),
+                    got: format!("{:?}", right),
+                    operation: "division".to_string(),
+                })?;
 
                 if r == 0.0 {
                     return Err(RuntimeError::DivisionByZero);
@@ -509,23 +3396,78 @@ impl Evaluator {
             BinaryOperator::Modulo => self.numeric_binary_op(left, right, "modulo", |l, r| l % r),
 
             // Comparison operators
-            BinaryOperator::Equal => Ok(Value::Boolean(self.values_equal(&left, &right))),
-
-            BinaryOperator::NotEqual => Ok(Value::Boolean(!self.values_equal(&left, &right))),
-
-            BinaryOperator::Greater => {
-                self.comparison_op(left, right, "greater than", |l, r| l > r)
+            BinaryOperator::Equal
+            | BinaryOperator::NotEqual
+            | BinaryOperator::Greater
+            | BinaryOperator::Less
+            | BinaryOperator::GreaterOrEqual
+            | BinaryOperator::LessOrEqual => self.comparison_op(left, operator, right),
+
+            // Regex operators; eval_match also sets $matches
+            BinaryOperator::Match | BinaryOperator::NotMatch => self
+                .match_op(left, operator, right)
+                .map(|(result, _)| result),
+
+            // Wildcard operators
+            BinaryOperator::Like
+            | BinaryOperator::NotLike
+            | BinaryOperator::CLike
+            | BinaryOperator::CNotLike => self.like_op(left, operator, right),
+
+            // String format operator: "{0} is {1:N2}" -f $name, $value
+            BinaryOperator::Format => {
+                let args = match right {
+                    Value::Array(items) => items.unwrap_or_clone(),
+                    other => vec![other],
+                };
+                let formatted = format::format_composite(&left.to_string(), &args, &self.culture)?;
+                Ok(Value::String(formatted.into()))
             }
 
-            BinaryOperator::Less => self.comparison_op(left, right, "less than", |l, r| l < r),
+            // $parts -join ', '
+            BinaryOperator::Join => Ok(join_items(left, &right.to_string())),
 
-            BinaryOperator::GreaterOrEqual => {
-                self.comparison_op(left, right, "greater or equal", |l, r| l >= r)
+            // $line -split ',\s*': a regex, matched without case
+            BinaryOperator::Split => {
+                let regex = matching::build_regex(&right.to_string())?;
+                Ok(split_items(left, |text| {
+                    regex.split(text).map(str::to_string).collect()
+                }))
             }
+        }
+    }
 
-            BinaryOperator::LessOrEqual => {
-                self.comparison_op(left, right, "less or equal", |l, r| l <= r)
-            }
+    /// Convert the right operand of string/array multiplication to a repeat
+    /// count, for a value taking `unit_bytes` of memory; a count that would
+    /// take more than `MAX_REPEAT_BYTES` is an error
+    fn repeat_count(&self, value: &Value, unit_bytes: usize) -> Result<usize, RuntimeError> {
+        let count = value
+            .to_number()
+            .ok_or_else(|| RuntimeError::TypeMismatch {
+                expected: "number".to_string(),
+                got: format!("{:?}", value),
+                operation: "multiplication".to_string(),
+            })?;
+        if count < 0.0 {
+            return Err(RuntimeError::InvalidOperation(format!(
+                "Cannot repeat a value a negative number of times: {}",
+                count
+            )));
+        }
+        if !count.is_finite() {
+            return Err(RuntimeError::InvalidOperation(format!(
+                "Cannot repeat a value {} times",
+                count
+            )));
+        }
+        let count = count.round();
+        if count > usize::MAX as f64 {
+            return Err(repeat_too_large(count));
+        }
+        let count = count as usize;
+        match count.checked_mul(unit_bytes) {
+            Some(bytes) if bytes <= MAX_REPEAT_BYTES => Ok(count),
+            _ => Err(repeat_too_large(count)),
         }
     }
 
@@ -534,69 +3476,214 @@ impl Evaluator {
     where
         F: FnOnce(f64, f64) -> f64,
     {
-        let l = left.to_number().ok_or_else(|| RuntimeError::TypeMismatch {
+        let l = arithmetic_operand(&left).ok_or_else(|| RuntimeError::TypeMismatch {
             expected: "number".to_string(),
             got: format!("{:?}", left),
             operation: op_name.to_string(),
         })?;
-        let r = right
-            .to_number()
-            .ok_or_else(|| RuntimeError::TypeMismatch {
-                expected: "number".to_string(),
-                got: format!("{:?}", right),
-                operation: op_name.to_string(),
-            })?;
+        let r = arithmetic_operand(&right).ok_or_else(|| RuntimeError::TypeMismatch {
+            expected: "number".to_string(),
+            got: format!("{:?}", right),
+            operation: op_name.to_string(),
+        })?;
 
         Ok(Value::Number(f(l, r)))
     }
 
     /// Helper for comparison operations
-    fn comparison_op<F>(&self, left: Value, right: Value, op_name: &str, f: F) -> EvalResult
-    where
-        F: FnOnce(f64, f64) -> bool,
-    {
-        let l = left.to_number().ok_or_else(|| RuntimeError::TypeMismatch {
-            expected: "number".to_string(),
-            got: format!("{:?}", left),
+    /// A comparison with an array on the left filters the array, returning the
+    /// matching items: @(1, 2, 3) -gt 1 -> @(2, 3)
+    fn comparison_op(&self, left: Value, operator: &BinaryOperator, right: Value) -> EvalResult {
+        if let Value::Array(items) = left {
+            let mut matches = Vec::new();
+            for item in items.unwrap_or_clone() {
+                if self.compare(&item, operator, &right)? {
+                    matches.push(item);
+                }
+            }
+            return Ok(Value::Array(matches.into()));
+        }
+
+        Ok(Value::Boolean(self.compare(&left, operator, &right)?))
+    }
+
+    /// Evaluate -match/-notmatch, setting $matches when a scalar matches
+    fn eval_match(&mut self, left: Value, operator: &BinaryOperator, right: Value) -> EvalResult {
+        let (result, groups) = self.match_op(left, operator, right)?;
+        if let Some(groups) = groups {
+            self.scope
+                .set_variable("matches", Value::Object(groups.into()));
+        }
+        Ok(result)
+    }
+
+    /// Apply a regex operator, returning the result and any capture groups.
+    /// Like comparisons, an array on the left is filtered (and $matches is left alone).
+    fn match_op(
+        &self,
+        left: Value,
+        operator: &BinaryOperator,
+        right: Value,
+    ) -> Result<(Value, Option<PropertyMap>), RuntimeError> {
+        let regex = matching::build_regex(&right.to_string())?;
+        let negate = *operator == BinaryOperator::NotMatch;
+
+        if let Value::Array(items) = left {
+            let filtered: Vec<Value> = items
+                .unwrap_or_clone()
+                .into_iter()
+                .filter(|item| matching::captures(&regex, item).is_some() != negate)
+                .collect();
+            return Ok((Value::Array(filtered.into()), None));
+        }
+
+        let groups = matching::captures(&regex, &left);
+        Ok((Value::Boolean(groups.is_some() != negate), groups))
+    }
+
+    /// Apply a wildcard operator; like comparisons, an array on the left is filtered
+    fn like_op(&self, left: Value, operator: &BinaryOperator, right: Value) -> EvalResult {
+        let case_sensitive = matches!(operator, BinaryOperator::CLike | BinaryOperator::CNotLike);
+        let negate = matches!(operator, BinaryOperator::NotLike | BinaryOperator::CNotLike);
+        let pattern = WildcardPattern::new(&right.to_string())?.case_sensitive(case_sensitive);
+
+        if let Value::Array(items) = left {
+            let filtered: Vec<Value> = items
+                .unwrap_or_clone()
+                .into_iter()
+                .filter(|item| pattern.is_match(&item.to_string()) != negate)
+                .collect();
+            return Ok(Value::Array(filtered.into()));
+        }
+
+        Ok(Value::Boolean(
+            pattern.is_match(&left.to_string()) != negate,
+        ))
+    }
+
+    /// Compare two scalar values with a comparison operator
+    fn compare(
+        &self,
+        left: &Value,
+        operator: &BinaryOperator,
+        right: &Value,
+    ) -> Result<bool, RuntimeError> {
+        let (op_name, accept): (&str, fn(Ordering) -> bool) = match operator {
+            BinaryOperator::Equal => return Ok(self.values_equal(left, right)),
+            BinaryOperator::NotEqual => return Ok(!self.values_equal(left, right)),
+            BinaryOperator::Greater => ("greater than", Ordering::is_gt),
+            BinaryOperator::Less => ("less than", Ordering::is_lt),
+            BinaryOperator::GreaterOrEqual => ("greater or equal", Ordering::is_ge),
+            BinaryOperator::LessOrEqual => ("less or equal", Ordering::is_le),
+            _ => unreachable!("not a comparison operator: {:?}", operator),
+        };
+
+        Ok(accept(self.compare_values(left, right, op_name)?))
+    }
+
+    /// Order two values, converting the right side to the type of the left side
+    /// (PowerShell semantics): numbers compare numerically, strings
+    /// case-insensitively, dates chronologically, and $null sorts first.
+    fn compare_values(
+        &self,
+        left: &Value,
+        right: &Value,
+        op_name: &str,
+    ) -> Result<Ordering, RuntimeError> {
+        let mismatch = |expected: &str| RuntimeError::TypeMismatch {
+            expected: expected.to_string(),
+            got: format!("{:?}", right),
             operation: op_name.to_string(),
-        })?;
-        let r = right
-            .to_number()
-            .ok_or_else(|| RuntimeError::TypeMismatch {
-                expected: "number".to_string(),
-                got: format!("{:?}", right),
-                operation: op_name.to_string(),
-            })?;
+        };
 
-        Ok(Value::Boolean(f(l, r)))
+        match (left, right) {
+            (Value::Null, Value::Null) => Ok(Ordering::Equal),
+            (Value::Null, _) => Ok(Ordering::Less),
+            (_, Value::Null) => Ok(Ordering::Greater),
+            (Value::Number(l), r) => {
+                let r = r.to_number().ok_or_else(|| mismatch("number"))?;
+                Ok(l.partial_cmp(&r).unwrap_or(Ordering::Equal))
+            }
+            (Value::String(l), r) => Ok(l.to_lowercase().cmp(&r.to_string().to_lowercase())),
+            (Value::DateTime(l), r) => {
+                let r = r.to_datetime().ok_or_else(|| mismatch("DateTime"))?;
+                Ok(l.cmp(&r))
+            }
+            (Value::Boolean(l), r) => Ok(l.cmp(&r.to_bool())),
+            _ => Err(RuntimeError::TypeMismatch {
+                expected: "comparable value".to_string(),
+                got: format!("{:?}", left),
+                operation: op_name.to_string(),
+            }),
+        }
     }
 
-    /// Check if two values are equal (case-insensitive for strings, PowerShell default)
+    /// Check if two values are equal, converting the right side to the type of
+    /// the left side (case-insensitive for strings, PowerShell default)
     fn values_equal(&self, left: &Value, right: &Value) -> bool {
         match (left, right) {
             (Value::Null, Value::Null) => true,
-            (Value::Boolean(l), Value::Boolean(r)) => l == r,
-            (Value::Number(l), Value::Number(r)) => l == r,
+            (Value::Null, _) | (_, Value::Null) => false,
+            (Value::Boolean(l), r) => *l == r.to_bool(),
+            (Value::Number(l), r) => r.to_number() == Some(*l),
+            (Value::DateTime(l), r) => r.to_datetime() == Some(*l),
+            // Enum values match their own name or numeric value
+            (
+                Value::EnumValue {
+                    type_name, value, ..
+                },
+                Value::EnumValue {
+                    type_name: r_type,
+                    value: r_value,
+                    ..
+                },
+            ) => type_name.eq_ignore_ascii_case(r_type) && value == r_value,
+            (Value::EnumValue { name, .. }, Value::String(r)) => name.eq_ignore_ascii_case(r),
+            (Value::EnumValue { value, .. }, r) => r.to_number() == Some(*value as f64),
+            // Hashtables and class instances are equal only to themselves,
+            // as .NET reference types are
+            (Value::Object(l), Value::Object(r)) => l.ptr_eq(r),
+            (Value::Instance { properties: l, .. }, Value::Instance { properties: r, .. }) => {
+                l.ptr_eq(r)
+            }
             // PowerShell string comparison is case-insensitive by default
             (Value::String(l), Value::String(r)) => l.to_lowercase() == r.to_lowercase(),
+            (
+                Value::String(l),
+                r @ (Value::Number(_)
+                | Value::Boolean(_)
+                | Value::DateTime(_)
+                | Value::EnumValue { .. }),
+            ) => l.to_lowercase() == r.to_string().to_lowercase(),
             _ => false,
         }
     }
 
     /// Evaluate a unary operation
-    fn eval_unary_op(&self, operator: UnaryOperator, operand: Value) -> EvalResult {
+    fn eval_unary_op(&self, operator: &UnaryOperator, operand: Value) -> EvalResult {
         match operator {
             UnaryOperator::Negate => {
-                let n = operand
-                    .to_number()
-                    .ok_or_else(|| RuntimeError::TypeMismatch {
-                        expected: "number".to_string(),
-                        got: format!("{:?}", operand),
-                        operation: "negation".to_string(),
-                    })?;
+                let n = arithmetic_operand(&operand).ok_or_else(|| RuntimeError::TypeMismatch {
+                    expected: "number".to_string(),
+                    got: format!("{:?}", operand),
+                    operation: "negation".to_string(),
+                })?;
                 Ok(Value::Number(-n))
             }
+            UnaryOperator::Plus => {
+                let n = arithmetic_operand(&operand).ok_or_else(|| RuntimeError::TypeMismatch {
+                    expected: "number".to_string(),
+                    got: format!("{:?}", operand),
+                    operation: "unary plus".to_string(),
+                })?;
+                Ok(Value::Number(n))
+            }
             UnaryOperator::Not => Ok(Value::Boolean(!operand.to_bool())),
+            // -join runs the items together; -split splits at whitespace
+            UnaryOperator::Join => Ok(join_items(operand, "")),
+            UnaryOperator::Split => Ok(split_items(operand, |text| {
+                text.split_whitespace().map(str::to_string).collect()
+            })),
         }
     }
 }
@@ -613,144 +3700,758 @@ mod tests {
     use pwsh_lexer::Lexer;
     use pwsh_parser::Parser;
 
-    fn eval_str(input: &str) -> EvalResult {
-        let mut lexer = Lexer::new(input);
+    fn eval_str(input: &str) -> EvalResult {
+        let mut lexer = Lexer::new(input);
+        let tokens = lexer.tokenize().unwrap();
+        let mut parser = Parser::new(tokens);
+        let program = parser.parse().unwrap();
+        let mut evaluator = Evaluator::new();
+        evaluator.eval(program)
+    }
+
+    #[test]
+    fn test_eval_number() {
+        let result = eval_str("42").unwrap();
+        assert_eq!(result, Value::Number(42.0));
+    }
+
+    #[test]
+    fn test_eval_string() {
+        let result = eval_str("\"hello\"").unwrap();
+        assert_eq!(result, Value::String("hello".into()));
+    }
+
+    #[test]
+    fn test_eval_boolean() {
+        let result = eval_str("$true").unwrap();
+        assert_eq!(result, Value::Boolean(true));
+        let result = eval_str("$FALSE").unwrap();
+        assert_eq!(result, Value::Boolean(false));
+
+        // No scope can change the constants; $null = ... discards
+        let error = eval_str("$true = 0").unwrap_err();
+        assert_eq!(error.error_id(), "VariableNotWritable");
+        let result = eval_str("function f { $null = 5; $null }; f").unwrap();
+        assert_eq!(result, Value::Null);
+        let result = eval_str("$x = 0; foreach ($i in @(1)) { $x = $global:true }; $x").unwrap();
+        assert_eq!(result, Value::Boolean(true));
+    }
+
+    #[test]
+    fn test_eval_addition() {
+        let result = eval_str("5 + 3").unwrap();
+        assert_eq!(result, Value::Number(8.0));
+    }
+
+    #[test]
+    fn test_eval_subtraction() {
+        let result = eval_str("10 - 3").unwrap();
+        assert_eq!(result, Value::Number(7.0));
+    }
+
+    #[test]
+    fn test_eval_multiplication() {
+        let result = eval_str("6 * 7").unwrap();
+        assert_eq!(result, Value::Number(42.0));
+    }
+
+    #[test]
+    fn test_eval_division() {
+        let result = eval_str("20 / 4").unwrap();
+        assert_eq!(result, Value::Number(5.0));
+    }
+
+    #[test]
+    fn test_eval_modulo() {
+        let result = eval_str("10 % 3").unwrap();
+        assert_eq!(result, Value::Number(1.0));
+    }
+
+    #[test]
+    fn test_eval_division_by_zero() {
+        let result = eval_str("10 / 0");
+        assert!(matches!(result, Err(RuntimeError::DivisionByZero)));
+    }
+
+    #[test]
+    fn test_eval_operator_precedence() {
+        let result = eval_str("10 + 20 * 2").unwrap();
+        assert_eq!(result, Value::Number(50.0)); // 10 + (20 * 2)
+    }
+
+    #[test]
+    fn test_eval_parentheses() {
+        let result = eval_str("(10 + 20) * 2").unwrap();
+        assert_eq!(result, Value::Number(60.0));
+    }
+
+    #[test]
+    fn test_eval_unary_minus() {
+        let result = eval_str("-5").unwrap();
+        assert_eq!(result, Value::Number(-5.0));
+    }
+
+    #[test]
+    fn test_eval_comparison_equal() {
+        let result = eval_str("5 -eq 5").unwrap();
+        assert_eq!(result, Value::Boolean(true));
+
+        let result = eval_str("5 -eq 3").unwrap();
+        assert_eq!(result, Value::Boolean(false));
+    }
+
+    #[test]
+    fn test_eval_comparison_not_equal() {
+        let result = eval_str("5 -ne 3").unwrap();
+        assert_eq!(result, Value::Boolean(true));
+    }
+
+    #[test]
+    fn test_eval_comparison_greater() {
+        let result = eval_str("10 -gt 5").unwrap();
+        assert_eq!(result, Value::Boolean(true));
+
+        let result = eval_str("3 -gt 5").unwrap();
+        assert_eq!(result, Value::Boolean(false));
+    }
+
+    #[test]
+    fn test_eval_comparison_less() {
+        let result = eval_str("3 -lt 5").unwrap();
+        assert_eq!(result, Value::Boolean(true));
+    }
+
+    #[test]
+    fn test_eval_not_operator() {
+        assert_eq!(eval_str("-not $true").unwrap(), Value::Boolean(false));
+        assert_eq!(eval_str("!$false").unwrap(), Value::Boolean(true));
+        assert_eq!(eval_str("!(5 -gt 3)").unwrap(), Value::Boolean(false));
+    }
+
+    #[test]
+    fn test_eval_join_split_and_unary_plus() {
+        let strings = |items: &[&str]| {
+            Value::Array(
+                items
+                    .iter()
+                    .map(|s| Value::String((*s).into()))
+                    .collect::<Vec<_>>()
+                    .into(),
+            )
+        };
+        assert_eq!(
+            eval_str("-split ' a  b\tc '").unwrap(),
+            strings(&["a", "b", "c"])
+        );
+        assert_eq!(
+            eval_str("'a, b,C' -split ',\\s*'").unwrap(),
+            strings(&["a", "b", "C"])
+        );
+        // The pattern matches without case, and each item of an array is split
+        assert_eq!(
+            eval_str("@('1x2', '3X4') -split 'x'").unwrap(),
+            strings(&["1", "2", "3", "4"])
+        );
+
+        assert_eq!(
+            eval_str("-join @(1, 'b', 3)").unwrap(),
+            Value::String("1b3".into())
+        );
+        assert_eq!(
+            eval_str("(-split 'c b a') -join ', '").unwrap(),
+            Value::String("c, b, a".into())
+        );
+        assert_eq!(
+            eval_str("'one' -join ','").unwrap(),
+            Value::String("one".into())
+        );
+
+        assert_eq!(eval_str("+'5' + 1").unwrap(), Value::Number(6.0));
+        assert!(eval_str("+'five'").is_err());
+    }
+
+    #[test]
+    fn test_eval_format_operator() {
+        let result = eval_str("$name = 'Pi'\n$value = 3.14159\n'{0} is {1:N2}' -f $name, $value");
+        assert_eq!(result.unwrap(), Value::String("Pi is 3.14".into()));
+
+        let result = eval_str("'[{0,-6}|{1,6}]' -f 'ab', 42").unwrap();
+        assert_eq!(result, Value::String("[ab    |    42]".into()));
+
+        let result = eval_str("$s = 'Total: {0:C}' -f 1234.5\n$s").unwrap();
+        assert_eq!(result, Value::String("Total: $1,234.50".into()));
+    }
+
+    #[test]
+    fn test_eval_format_operator_with_array_variable() {
+        let result = eval_str("$args2 = 'a', 'b'\n'{1}{0}' -f $args2").unwrap();
+        assert_eq!(result, Value::String("ba".into()));
+    }
+
+    #[test]
+    fn test_eval_string_multiplication() {
+        assert_eq!(
+            eval_str("'ab' * 3").unwrap(),
+            Value::String("ababab".into())
+        );
+        assert_eq!(eval_str("'-' * 0").unwrap(), Value::String("".into()));
+        assert!(eval_str("'ab' * -1").is_err());
+    }
+
+    #[test]
+    fn test_the_clock_is_only_read_for_a_time_limit() {
+        // wasm32 panics on reading the time, so an unlimited evaluator mustn't
+        let mut evaluator = Evaluator::new();
+        evaluator.start_script();
+        assert!(evaluator.script_started.is_none());
+        evaluator.set_limits(Limits {
+            max_duration: Some(std::time::Duration::from_secs(1)),
+            ..Limits::default()
+        });
+        assert!(evaluator.script_started.is_some());
+    }
+
+    #[test]
+    fn test_eval_oversized_repetition_is_an_error() {
+        for source in [
+            "'ab' * 100000000000000000000",
+            "'ab' * 4000000000",
+            "@(1, 2) * 4000000000",
+        ] {
+            let error = eval_str(source).unwrap_err();
+            assert!(
+                matches!(error, RuntimeError::InvalidOperation(_)),
+                "{}: {:?}",
+                source,
+                error
+            );
+        }
+        // A number too large for a double is infinite
+        let infinite = format!("'ab' * 1{}", "0".repeat(400));
+        assert_eq!(
+            eval_str(&infinite).unwrap_err().to_string(),
+            "Invalid operation: Cannot repeat a value inf times"
+        );
+        assert_eq!(
+            eval_str("'ab' * 4000000000").unwrap_err().to_string(),
+            "Invalid operation: Cannot repeat a value 4000000000 times: the result would be too large"
+        );
+    }
+
+    #[test]
+    fn test_eval_array_repetition() {
+        let result = eval_str("@(0) * 3").unwrap();
+        assert_eq!(
+            result,
+            Value::Array(vec![Value::Number(0.0), Value::Number(0.0), Value::Number(0.0)].into())
+        );
+
+        let result = eval_str("$a = @(1, 2) * 2\n$a.Count").unwrap();
+        assert_eq!(result, Value::Number(4.0));
+    }
+
+    #[test]
+    fn test_eval_array_concatenation() {
+        let result = eval_str("@(1, 2) + @(3)").unwrap();
+        assert_eq!(
+            result,
+            Value::Array(vec![Value::Number(1.0), Value::Number(2.0), Value::Number(3.0)].into())
+        );
+
+        let result = eval_str("$a = @(1)\n$a = $a + 'x'\n$a").unwrap();
+        assert_eq!(
+            result,
+            Value::Array(vec![Value::Number(1.0), Value::String("x".into())].into())
+        );
+    }
+
+    #[test]
+    fn test_eval_hashtable_merge() {
+        let result = eval_str("$h = @{a=1} + @{b=2}\n$h.b").unwrap();
+        assert_eq!(result, Value::Number(2.0));
+
+        let result = eval_str("@{a=1} + @{A=2}");
+        assert!(matches!(result, Err(RuntimeError::InvalidOperation(_))));
+    }
+
+    #[test]
+    fn test_eval_string_ordering_comparison() {
+        assert_eq!(eval_str("'abc' -lt 'abd'").unwrap(), Value::Boolean(true));
+        assert_eq!(eval_str("'B' -gt 'a'").unwrap(), Value::Boolean(true));
+        assert_eq!(eval_str("'ABC' -ge 'abc'").unwrap(), Value::Boolean(true));
+        // The right operand is converted to the type of the left operand
+        assert_eq!(eval_str("'10' -lt 9").unwrap(), Value::Boolean(true));
+        assert_eq!(eval_str("10 -gt '9'").unwrap(), Value::Boolean(true));
+        assert_eq!(eval_str("5 -eq '5'").unwrap(), Value::Boolean(true));
+        assert!(eval_str("5 -gt 'abc'").is_err());
+    }
+
+    #[test]
+    fn test_eval_datetime_comparison() {
+        let mut evaluator = Evaluator::new();
+        let date = |y, m, d| {
+            chrono::NaiveDate::from_ymd_opt(y, m, d)
+                .unwrap()
+                .and_hms_opt(0, 0, 0)
+                .unwrap()
+        };
+        evaluator.set_variable("early", Value::DateTime(date(2024, 1, 15)));
+        evaluator.set_variable("late", Value::DateTime(date(2024, 6, 1)));
+
+        let mut run = |code: &str| {
+            let mut lexer = Lexer::new(code);
+            let mut parser = Parser::new(lexer.tokenize().unwrap());
+            evaluator.eval(parser.parse().unwrap()).unwrap()
+        };
+        assert_eq!(run("$early -lt $late"), Value::Boolean(true));
+        assert_eq!(run("$late -le $early"), Value::Boolean(false));
+        assert_eq!(run("$early -eq '2024-01-15'"), Value::Boolean(true));
+        assert_eq!(run("$late -gt '2024-05-31'"), Value::Boolean(true));
+    }
+
+    #[test]
+    fn test_eval_comparison_filters_array() {
+        let result = eval_str("@(1, 2, 3) -gt 1").unwrap();
+        assert_eq!(
+            result,
+            Value::Array(vec![Value::Number(2.0), Value::Number(3.0)].into())
+        );
+
+        let result = eval_str("@('a', 'B', 'a') -eq 'A'").unwrap();
+        assert_eq!(
+            result,
+            Value::Array(vec![Value::String("a".into()), Value::String("a".into())].into())
+        );
+
+        let result = eval_str("@(1, 2) -gt 5").unwrap();
+        assert_eq!(result, Value::Array(vec![].into()));
+    }
+
+    #[test]
+    fn test_eval_comparison_with_null() {
+        let evaluator = Evaluator::new();
+        let compare = |left: Value, operator: BinaryOperator, right: Value| {
+            evaluator.eval_binary_op(left, &operator, right).unwrap()
+        };
+        assert_eq!(
+            compare(Value::Null, BinaryOperator::Equal, Value::Null),
+            Value::Boolean(true)
+        );
+        assert_eq!(
+            compare(Value::Null, BinaryOperator::Less, Value::Number(1.0)),
+            Value::Boolean(true)
+        );
+        assert_eq!(
+            compare(Value::Number(0.0), BinaryOperator::Equal, Value::Null),
+            Value::Boolean(false)
+        );
+    }
+
+    #[test]
+    fn test_eval_format_operator_index_out_of_range() {
+        assert!(eval_str("'{2}' -f 1, 2").is_err());
+    }
+
+    #[test]
+    fn test_eval_if_with_not_condition() {
+        let result = eval_str("$found = $false\nif (-not $found) { 1 } else { 2 }").unwrap();
+        assert_eq!(result, Value::Number(1.0));
+
+        let result = eval_str("$x = 0\nif (!$x) { \"empty\" }").unwrap();
+        assert_eq!(result, Value::String("empty".into()));
+    }
+
+    #[test]
+    fn test_eval_assignment() {
+        let result = eval_str("$x = 5").unwrap();
+        assert_eq!(result, Value::Null);
+    }
+
+    #[test]
+    fn test_eval_variable_reference() {
+        let mut lexer = Lexer::new("$x = 5\n$x");
         let tokens = lexer.tokenize().unwrap();
         let mut parser = Parser::new(tokens);
         let program = parser.parse().unwrap();
         let mut evaluator = Evaluator::new();
-        evaluator.eval(program)
+        let result = evaluator.eval(program).unwrap();
+        assert_eq!(result, Value::Number(5.0));
     }
 
     #[test]
-    fn test_eval_number() {
-        let result = eval_str("42").unwrap();
-        assert_eq!(result, Value::Number(42.0));
+    fn test_eval_variable_in_expression() {
+        let result = eval_str("$x = 5\n$y = 10\n$x + $y").unwrap();
+        assert_eq!(result, Value::Number(15.0));
     }
 
     #[test]
-    fn test_eval_string() {
-        let result = eval_str("\"hello\"").unwrap();
-        assert_eq!(result, Value::String("hello".to_string()));
+    fn test_eval_undefined_variable() {
+        let result = eval_str("$undefined").unwrap();
+        assert_eq!(result, Value::Null);
+
+        // In arithmetic $null counts as 0, so an unset counter can be bumped
+        assert_eq!(
+            eval_str(
+                "$count = $count + 1
+$count"
+            )
+            .unwrap(),
+            Value::Number(1.0)
+        );
+        assert_eq!(eval_str("$missing * 2 - 1").unwrap(), Value::Number(-1.0));
+        assert_eq!(eval_str("-$missing").unwrap(), Value::Number(0.0));
+        assert_eq!(
+            eval_str("$missing + 'text'").unwrap(),
+            Value::String("text".into())
+        );
     }
 
     #[test]
-    fn test_eval_boolean() {
-        let result = eval_str("true").unwrap();
-        assert_eq!(result, Value::Boolean(true));
+    fn test_null_comparisons_in_conditions() {
+        let code = "if ($unset -eq $null) { 'unset' } else { 'set' }";
+        assert_eq!(eval_str(code).unwrap(), Value::String("unset".into()));
+        let code = "$x = 0
+if ($x -ne $null) { 'set' } else { 'unset' }";
+        assert_eq!(eval_str(code).unwrap(), Value::String("set".into()));
+
+        // With an array on the left, -ne $null drops the nulls and -eq $null keeps them
+        assert_eq!(
+            eval_str("@(1, $null, 0, $unset) -ne $null").unwrap(),
+            Value::Array(vec![Value::Number(1.0), Value::Number(0.0)].into())
+        );
+        assert_eq!(
+            eval_str("(@(1, $null, $unset) -eq $null).Count").unwrap(),
+            Value::Number(2.0)
+        );
+        // ...while $null on the left is a plain comparison
+        assert_eq!(
+            eval_str("$null -eq @(1, $null)").unwrap(),
+            Value::Boolean(false)
+        );
     }
 
     #[test]
-    fn test_eval_addition() {
-        let result = eval_str("5 + 3").unwrap();
-        assert_eq!(result, Value::Number(8.0));
+    fn test_not_follows_powershell_truthiness() {
+        for (code, expected) in [
+            ("-not $null", true),
+            ("!$unset", true),
+            ("-not ''", true),
+            ("-not 'False'", false),
+            ("-not @()", true),
+            ("-not @(0)", true),
+            ("-not @($null)", true),
+            ("-not @(0, 0)", false),
+            ("-not @{}", false),
+        ] {
+            assert_eq!(
+                eval_str(code).unwrap(),
+                Value::Boolean(expected),
+                "{}",
+                code
+            );
+        }
     }
 
     #[test]
-    fn test_eval_subtraction() {
-        let result = eval_str("10 - 3").unwrap();
-        assert_eq!(result, Value::Number(7.0));
+    fn test_filter_runs_once_per_pipeline_item() {
+        let result = eval_str("filter Double { $_ * 2 }\n@(1, 2, 3) | Double").unwrap();
+        assert_eq!(
+            result,
+            Value::Array(vec![Value::Number(2.0), Value::Number(4.0), Value::Number(6.0)].into())
+        );
     }
 
     #[test]
-    fn test_eval_multiplication() {
-        let result = eval_str("6 * 7").unwrap();
-        assert_eq!(result, Value::Number(42.0));
+    fn test_every_statement_writes_output() {
+        let strings = |items: &[&str]| {
+            Value::Array(
+                items
+                    .iter()
+                    .map(|s| Value::String((*s).into()))
+                    .collect::<Vec<_>>()
+                    .into(),
+            )
+        };
+        let numbers = |items: &[f64]| {
+            Value::Array(
+                items
+                    .iter()
+                    .map(|n| Value::Number(*n))
+                    .collect::<Vec<_>>()
+                    .into(),
+            )
+        };
+
+        assert_eq!(
+            eval_str("1; 2; @(3, 4)").unwrap(),
+            numbers(&[1.0, 2.0, 3.0, 4.0])
+        );
+        assert_eq!(eval_str("$x = 1; $x").unwrap(), Value::Number(1.0));
+        assert_eq!(
+            eval_str("function f { 'a'; 'b' }\nf").unwrap(),
+            strings(&["a", "b"])
+        );
+        // Output written before a return is kept
+        assert_eq!(
+            eval_str("function f { 'a'; return 'b'; 'c' }\nf").unwrap(),
+            strings(&["a", "b"])
+        );
+        assert_eq!(
+            eval_str(
+                "function f { foreach ($i in @(1, 2, 3)) { $i; if ($i -eq 2) { return 0 } } }\nf"
+            )
+            .unwrap(),
+            numbers(&[1.0, 2.0, 0.0])
+        );
     }
 
     #[test]
-    fn test_eval_division() {
-        let result = eval_str("20 / 4").unwrap();
+    fn test_pipeline_enumerates_stage_output() {
+        // Each item a function writes reaches the next stage on its own
+        let result = eval_str("function f { 'a'; 'b' }\nf | { $_ + '!' }").unwrap();
+        assert_eq!(
+            result,
+            Value::Array(vec![Value::String("a!".into()), Value::String("b!".into())].into())
+        );
+
+        let result = eval_str("@(1, 2) | { @($_, 0) } | { $_ + 1 }").unwrap();
+        assert_eq!(
+            result,
+            Value::Array(
+                vec![
+                    Value::Number(2.0),
+                    Value::Number(1.0),
+                    Value::Number(3.0),
+                    Value::Number(1.0)
+                ]
+                .into()
+            )
+        );
+    }
+
+    #[test]
+    fn test_function_process_block_in_pipeline() {
+        let code = "function Add-Suffix($suffix) { process { $_ + $suffix } }\n@('a', 'b') | Add-Suffix '!'";
+        let result = eval_str(code).unwrap();
+        assert_eq!(
+            result,
+            Value::Array(vec![Value::String("a!".into()), Value::String("b!".into())].into())
+        );
+
+        // $_ does not leak out of the function scope
+        let result = eval_str("filter Id { $_ }\n5 | Id\n$_").unwrap();
         assert_eq!(result, Value::Number(5.0));
     }
 
     #[test]
-    fn test_eval_modulo() {
-        let result = eval_str("10 % 3").unwrap();
-        assert_eq!(result, Value::Number(1.0));
+    fn test_function_begin_process_end_blocks() {
+        let code = "function Sum {\n  begin { $total = 0 }\n  process { $total = $total + $_ }\n  end { $total }\n}\n@(1, 2, 3) | Sum";
+        assert_eq!(eval_str(code).unwrap(), Value::Number(6.0));
     }
 
     #[test]
-    fn test_eval_division_by_zero() {
-        let result = eval_str("10 / 0");
-        assert!(matches!(result, Err(RuntimeError::DivisionByZero)));
+    fn test_function_receives_pipeline_input_as_input_variable() {
+        let code = "function Count-Items { $input.Count }\n@(4, 5, 6) | Count-Items";
+        assert_eq!(eval_str(code).unwrap(), Value::Number(3.0));
+
+        // Without pipeline input, $input is empty
+        let code = "function Count-Items { $input.Count }\nCount-Items";
+        assert_eq!(eval_str(code).unwrap(), Value::Number(0.0));
     }
 
     #[test]
-    fn test_eval_operator_precedence() {
-        let result = eval_str("10 + 20 * 2").unwrap();
-        assert_eq!(result, Value::Number(50.0)); // 10 + (20 * 2)
+    fn test_undefined_command_suggests_user_functions() {
+        let result = eval_str("function Get-Greeting { 'hi' }\nGet-Greting");
+        assert_eq!(
+            result,
+            Err(RuntimeError::UndefinedFunction {
+                name: "Get-Greting".to_string(),
+                suggestions: vec!["Get-Greeting".to_string()],
+            })
+        );
     }
 
+    const PERSON_CLASS: &str = "class Person {\n  [string]$Name\n  [int]$Age\n  Person($n) { $this.Name = $n }\n  [string] Greet() { return 'Hi ' + $this.Name }\n  [void] Birthday() { $this.Age = $this.Age + 1 }\n  static [int] Twice($x) { return $x * 2 }\n}\n";
+
     #[test]
-    fn test_eval_parentheses() {
-        let result = eval_str("(10 + 20) * 2").unwrap();
-        assert_eq!(result, Value::Number(60.0));
+    fn test_class_construction_and_methods() {
+        let result = eval_str(&format!(
+            "{}$p = [Person]::new('Ann')\n$p.Greet()",
+            PERSON_CLASS
+        ));
+        assert_eq!(result, Ok(Value::String("Hi Ann".into())));
+
+        let result = eval_str(&format!("{}[Person]::Twice(21)", PERSON_CLASS));
+        assert_eq!(result, Ok(Value::Number(42.0)));
     }
 
     #[test]
-    fn test_eval_unary_minus() {
-        let result = eval_str("-5").unwrap();
-        assert_eq!(result, Value::Number(-5.0));
+    fn test_class_method_updates_this() {
+        let code = format!(
+            "{}$p = [Person]::new('Ann')\n$p.Birthday()\n$p.Birthday()\n$p.Age",
+            PERSON_CLASS
+        );
+        assert_eq!(eval_str(&code), Ok(Value::Number(2.0)));
     }
 
     #[test]
-    fn test_eval_comparison_equal() {
-        let result = eval_str("5 -eq 5").unwrap();
-        assert_eq!(result, Value::Boolean(true));
+    fn test_class_instances_are_shared_by_their_copies() {
+        let code = "class C { [int]$N }
+$c = [C]::new()
+$d = $c
+$d.N = 5
+$c.N";
+        assert_eq!(eval_str(code), Ok(Value::Number(5.0)));
+
+        // Through a parameter, a list, and a method of another variable
+        let code = format!(
+            "{}function Rename($p) {{ $p.Name = 'Bo' }}
+$p = [Person]::new('Ann')
+Rename $p
+$people = @($p)
+$people[0].Birthday()
+$q = [Person]::new('Ann')
+@($p.Name, $p.Age, $p -eq $people[0], $p -eq $q)",
+            PERSON_CLASS
+        );
+        assert_eq!(
+            eval_str(&code),
+            Ok(Value::Array(
+                vec![
+                    Value::String("Bo".into()),
+                    Value::Number(1.0),
+                    Value::Boolean(true),
+                    Value::Boolean(false)
+                ]
+                .into()
+            ))
+        );
+    }
 
-        let result = eval_str("5 -eq 3").unwrap();
-        assert_eq!(result, Value::Boolean(false));
+    #[test]
+    fn test_class_errors() {
+        let missing_method = eval_str(&format!("{}[Person]::new('Ann').Fly()", PERSON_CLASS));
+        let missing_method = missing_method.unwrap_err();
+        assert!(missing_method
+            .to_string()
+            .contains("does not contain a method named 'Fly'"));
+        assert_eq!(missing_method.error_id(), "MethodNotFound");
+        assert_eq!(missing_method.target(), Some("Fly"));
+
+        let bad_overload = eval_str(&format!("{}[Person]::new(1, 2)", PERSON_CLASS));
+        assert!(
+            matches!(bad_overload, Err(RuntimeError::InvalidOperation(msg))
+            if msg.contains("argument count: \"2\""))
+        );
+
+        let unknown = eval_str("[Nope]::new()");
+        let unknown = unknown.unwrap_err();
+        assert_eq!(unknown.to_string(), "Unable to find type [Nope]");
+        assert_eq!(unknown.category(), ErrorCategory::InvalidOperation);
     }
 
     #[test]
-    fn test_eval_comparison_not_equal() {
-        let result = eval_str("5 -ne 3").unwrap();
-        assert_eq!(result, Value::Boolean(true));
+    fn test_enum_values() {
+        let code = "enum Color { Red; Green = 5; Blue }\n";
+        assert_eq!(
+            eval_str(&format!("{}[Color]::Blue", code)),
+            Ok(Value::EnumValue {
+                type_name: "Color".to_string(),
+                name: "Blue".to_string(),
+                value: 6,
+            })
+        );
+        assert_eq!(
+            eval_str(&format!("{}[Color]::green -eq 'Green'", code)),
+            Ok(Value::Boolean(true))
+        );
+        assert_eq!(
+            eval_str(&format!("{}[Color]::Green -eq 5", code)),
+            Ok(Value::Boolean(true))
+        );
+        assert!(eval_str(&format!("{}[Color]::Purple", code)).is_err());
     }
 
     #[test]
-    fn test_eval_comparison_greater() {
-        let result = eval_str("10 -gt 5").unwrap();
-        assert_eq!(result, Value::Boolean(true));
+    fn test_builtin_static_members() {
+        assert_eq!(eval_str("[math]::Round(3.7)"), Ok(Value::Number(4.0)));
+        assert_eq!(
+            eval_str("$s = ''\n[string]::IsNullOrEmpty($s)"),
+            Ok(Value::Boolean(true))
+        );
+        assert_eq!(
+            eval_str("[System.Math]::Max(2, [math]::Abs(-5))"),
+            Ok(Value::Number(5.0))
+        );
+        assert!(matches!(
+            eval_str("[datetime]::Now"),
+            Ok(Value::DateTime(_))
+        ));
+    }
 
-        let result = eval_str("3 -gt 5").unwrap();
-        assert_eq!(result, Value::Boolean(false));
+    #[test]
+    fn test_user_class_shadows_builtin_type() {
+        let code = "class Math { static [int] Round($x) { return 42 } }\n[math]::Round(3.7)";
+        assert_eq!(eval_str(code), Ok(Value::Number(42.0)));
     }
 
     #[test]
-    fn test_eval_comparison_less() {
-        let result = eval_str("3 -lt 5").unwrap();
-        assert_eq!(result, Value::Boolean(true));
+    fn test_foreach_loop() {
+        assert_eq!(
+            eval_str("foreach ($x in @(1, 2, 3)) { $x * 2 }"),
+            Ok(Value::Array(
+                vec![Value::Number(2.0), Value::Number(4.0), Value::Number(6.0)].into()
+            ))
+        );
+        assert_eq!(
+            eval_str("$sum = 0\nforeach ($x in @(1, 2, 3)) { $sum = $sum + $x }\n$sum"),
+            Ok(Value::Number(6.0))
+        );
     }
 
     #[test]
-    fn test_eval_assignment() {
-        let result = eval_str("$x = 5").unwrap();
-        assert_eq!(result, Value::Null);
+    fn test_hashtable_members() {
+        assert_eq!(
+            eval_str("$h = @{b = 2; a = 1}\n$h.Keys"),
+            Ok(Value::Array(
+                vec![Value::String("b".into()), Value::String("a".into())].into()
+            ))
+        );
+        assert_eq!(
+            eval_str("$h = @{a = 1; b = 2}\n$h.Count"),
+            Ok(Value::Number(2.0))
+        );
+        assert_eq!(
+            eval_str("$h = @{a = 1}\n$h.ContainsKey('A')"),
+            Ok(Value::Boolean(true))
+        );
     }
 
     #[test]
-    fn test_eval_variable_reference() {
-        let mut lexer = Lexer::new("$x = 5\n$x");
-        let tokens = lexer.tokenize().unwrap();
-        let mut parser = Parser::new(tokens);
-        let program = parser.parse().unwrap();
-        let mut evaluator = Evaluator::new();
-        let result = evaluator.eval(program).unwrap();
-        assert_eq!(result, Value::Number(5.0));
+    fn test_hashtable_methods_update_variable() {
+        let code = "$h = @{a = 1}\n$h.Add('b', 2)\n$h.Remove('a')\n$h.Keys";
+        assert_eq!(
+            eval_str(code),
+            Ok(Value::Array(vec![Value::String("b".into())].into()))
+        );
     }
 
     #[test]
-    fn test_eval_variable_in_expression() {
-        let result = eval_str("$x = 5\n$y = 10\n$x + $y").unwrap();
-        assert_eq!(result, Value::Number(15.0));
+    fn test_foreach_over_hashtable_enumerator() {
+        let code = "$h = @{a = 1; b = 2}\n$keys = ''\n$total = 0\nforeach ($kv in $h.GetEnumerator()) { $keys = $keys + $kv.Key; $total = $total + $kv.Value }\n$keys + $total";
+        assert_eq!(eval_str(code), Ok(Value::String("ab3".into())));
     }
 
     #[test]
-    fn test_eval_undefined_variable() {
-        let result = eval_str("$undefined").unwrap();
-        assert_eq!(result, Value::Number(0.0));
+    fn test_ordered_hashtable_keeps_insertion_order() {
+        let result = eval_str("$h = [ordered]@{z = 1; a = 2}\n$h.m = 3\n$h").unwrap();
+        assert_eq!(result.to_string(), "@{z=1; a=2; m=3}");
+    }
+
+    #[test]
+    fn test_eval_cast() {
+        assert_eq!(eval_str("[int]'41' + 1"), Ok(Value::Number(42.0)));
+        assert_eq!(eval_str("[string]@(1, 2)"), Ok(Value::String("1 2".into())));
     }
 
     #[test]
@@ -765,24 +4466,24 @@ mod tests {
     #[test]
     fn test_eval_string_interpolation() {
         let result = eval_str("$name = \"World\"\n\"Hello $name\"").unwrap();
-        assert_eq!(result, Value::String("Hello World".to_string()));
+        assert_eq!(result, Value::String("Hello World".into()));
     }
 
     #[test]
     fn test_eval_if_statement_true() {
-        let result = eval_str("if (true) { 42 }").unwrap();
+        let result = eval_str("if ($true) { 42 }").unwrap();
         assert_eq!(result, Value::Number(42.0));
     }
 
     #[test]
     fn test_eval_if_statement_false() {
-        let result = eval_str("if (false) { 42 }").unwrap();
+        let result = eval_str("if ($false) { 42 }").unwrap();
         assert_eq!(result, Value::Null);
     }
 
     #[test]
     fn test_eval_if_else_statement() {
-        let result = eval_str("if (false) { 1 } else { 2 }").unwrap();
+        let result = eval_str("if ($false) { 1 } else { 2 }").unwrap();
         assert_eq!(result, Value::Number(2.0));
     }
 
@@ -801,12 +4502,26 @@ mod tests {
     #[test]
     fn test_eval_string_concatenation() {
         let result = eval_str("\"Hello \" + \"World\"").unwrap();
-        assert_eq!(result, Value::String("Hello World".to_string()));
+        assert_eq!(result, Value::String("Hello World".into()));
+    }
+
+    #[test]
+    fn test_eval_strings_as_numbers() {
+        assert_eq!(eval_str("1 + ' 0x10 '"), Ok(Value::Number(17.0)));
+        assert_eq!(eval_str("'2' + 1"), Ok(Value::String("21".into())));
+        assert_eq!(
+            eval_str("'1.5kb' * 2"),
+            Ok(Value::String("1.5kb1.5kb".into()))
+        );
+        assert_eq!(eval_str("2 * '1e2'"), Ok(Value::Number(200.0)));
+        assert_eq!(eval_str("[int]'1kb' - 24"), Ok(Value::Number(1000.0)));
+        assert_eq!(eval_str("0 -eq ''"), Ok(Value::Boolean(true)));
+        assert_eq!(eval_str("2 -lt '0b11'"), Ok(Value::Boolean(true)));
     }
 
     #[test]
     fn test_eval_nested_scopes() {
-        let result = eval_str("$x = 1\nif (true) { $y = 2\n$x + $y }").unwrap();
+        let result = eval_str("$x = 1\nif ($true) { $y = 2\n$x + $y }").unwrap();
         assert_eq!(result, Value::Number(3.0));
     }
 
@@ -851,14 +4566,14 @@ mod tests {
     #[test]
     fn test_function_with_default_parameter() {
         let result = eval_str("function Greet($name = \"World\") { $name }\nGreet").unwrap();
-        assert_eq!(result, Value::String("World".to_string()));
+        assert_eq!(result, Value::String("World".into()));
     }
 
     #[test]
     fn test_function_override_default_parameter() {
         let result =
             eval_str("function Greet($name = \"World\") { $name }\nGreet \"Alice\"").unwrap();
-        assert_eq!(result, Value::String("Alice".to_string()));
+        assert_eq!(result, Value::String("Alice".into()));
     }
 
     #[test]
@@ -1071,6 +4786,25 @@ mod tests {
         assert_eq!(result, Value::Number(6.0));
     }
 
+    #[test]
+    fn test_env_variables() {
+        std::env::set_var("PWSH_EVAL_ENV_TEST", "from env");
+        let result = eval_str("\"[$env:PWSH_EVAL_ENV_TEST]\"").unwrap();
+        assert_eq!(result, Value::String("[from env]".into()));
+
+        let result = eval_str("$env:PWSH_EVAL_ENV_SET = 42; $env:PWSH_EVAL_ENV_SET").unwrap();
+        assert_eq!(result, Value::String("42".into()));
+        eval_str("$env:PWSH_EVAL_ENV_SET = $null").unwrap();
+        assert!(std::env::var("PWSH_EVAL_ENV_SET").is_err());
+
+        let result = eval_str("$global:n = 3\n\"n=$global:n\"").unwrap();
+        assert_eq!(result, Value::String("n=3".into()));
+
+        std::env::set_var("PWSH EVAL ENV TEST", "spaced");
+        let result = eval_str("${my var} = 2; \"${my var}x ${env:PWSH EVAL ENV TEST}\"").unwrap();
+        assert_eq!(result, Value::String("2x spaced".into()));
+    }
+
     #[test]
     fn test_script_block_creation() {
         let result = eval_str("{ 5 + 10 }").unwrap();
@@ -1164,12 +4898,48 @@ mod tests {
             let result = evaluator
                 .execute_script_block(&sb, Value::Number(42.0))
                 .unwrap();
-            assert_eq!(result, Value::String("Value: 42".to_string()));
+            assert_eq!(result, Value::String("Value: 42".into()));
         } else {
             panic!("Expected script block value");
         }
     }
 
+    #[test]
+    fn test_get_new_closure_captures_variables() {
+        let result = eval_str(
+            r#"
+            function New-Adder($amount) { { $args[0] + $amount }.GetNewClosure() }
+            $addTwo = New-Adder 2
+            $addTen = New-Adder 10
+            $amount = 100
+            $addTwo.Invoke(1)
+            $addTen.Invoke(1)
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            result,
+            Value::Array(vec![Value::Number(3.0), Value::Number(11.0)].into())
+        );
+
+        // Without a closure the block sees the variable as it is when it runs
+        let result = eval_str(
+            r#"
+            $x = 1
+            $late = { $x }
+            $early = { $x }.GetNewClosure()
+            $x = 2
+            $late.Invoke()
+            $early.Invoke()
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            result,
+            Value::Array(vec![Value::Number(2.0), Value::Number(1.0)].into())
+        );
+    }
+
     #[test]
     fn test_week9_success_criteria() {
         // Week 9 Success Criteria from ROADMAP.md:
@@ -1194,6 +4964,7 @@ mod tests {
         let result = eval_str("@{}").unwrap();
         match result {
             Value::Object(map) => {
+                let map = map.read();
                 assert_eq!(map.len(), 0);
             }
             _ => panic!("Expected Object value"),
@@ -1205,8 +4976,9 @@ mod tests {
         let result = eval_str("@{Name=\"John\"; Age=30}").unwrap();
         match result {
             Value::Object(map) => {
+                let map = map.read();
                 assert_eq!(map.len(), 2);
-                assert_eq!(map.get("Name"), Some(&Value::String("John".to_string())));
+                assert_eq!(map.get("Name"), Some(&Value::String("John".into())));
                 assert_eq!(map.get("Age"), Some(&Value::Number(30.0)));
             }
             _ => panic!("Expected Object value"),
@@ -1222,7 +4994,7 @@ mod tests {
             "#,
         )
         .unwrap();
-        assert_eq!(result, Value::String("John".to_string()));
+        assert_eq!(result, Value::String("John".into()));
     }
 
     #[test]
@@ -1239,13 +5011,13 @@ mod tests {
         .unwrap();
 
         let name = eval_str_with_evaluator(&mut evaluator, "$person.Name").unwrap();
-        assert_eq!(name, Value::String("Alice".to_string()));
+        assert_eq!(name, Value::String("Alice".into()));
 
         let age = eval_str_with_evaluator(&mut evaluator, "$person.Age").unwrap();
         assert_eq!(age, Value::Number(25.0));
 
         let city = eval_str_with_evaluator(&mut evaluator, "$person.City").unwrap();
-        assert_eq!(city, Value::String("NYC".to_string()));
+        assert_eq!(city, Value::String("NYC".into()));
     }
 
     #[test]
@@ -1260,7 +5032,7 @@ mod tests {
         eval_str_with_evaluator(&mut evaluator, r#"$obj = @{Name="John"; Age=30}"#).unwrap();
 
         let name = eval_str_with_evaluator(&mut evaluator, "$obj.Name").unwrap();
-        assert_eq!(name, Value::String("John".to_string()));
+        assert_eq!(name, Value::String("John".into()));
 
         let age = eval_str_with_evaluator(&mut evaluator, "$obj.Age").unwrap();
         assert_eq!(age, Value::Number(30.0));
@@ -1296,7 +5068,528 @@ mod tests {
             "#,
         )
         .unwrap();
-        assert_eq!(result3, Value::String("John".to_string()));
+        assert_eq!(result3, Value::String("John".into()));
+    }
+
+    #[test]
+    fn test_array_index_access() {
+        assert_eq!(
+            eval_str("$a = @(10, 20, 30)\n$a[1]").unwrap(),
+            Value::Number(20.0)
+        );
+        assert_eq!(
+            eval_str("$a = @(10, 20, 30)\n$a[-1]").unwrap(),
+            Value::Number(30.0)
+        );
+        assert_eq!(eval_str("$a = @(10, 20, 30)\n$a[5]").unwrap(), Value::Null);
+        assert_eq!(
+            eval_str("$s = \"hello\"\n$s[1]").unwrap(),
+            Value::String("e".into())
+        );
+    }
+
+    #[test]
+    fn test_hashtable_index_access() {
+        let result = eval_str("$h = @{Name=\"John\"}\n$h[\"name\"]").unwrap();
+        assert_eq!(result, Value::String("John".into()));
+    }
+
+    #[test]
+    fn test_count_and_length_members() {
+        assert_eq!(eval_str("@(1, 2, 3).Count").unwrap(), Value::Number(3.0));
+        assert_eq!(
+            eval_str("$s = \"hello\"\n$s.Length").unwrap(),
+            Value::Number(5.0)
+        );
+        assert_eq!(eval_str("(42).Count").unwrap(), Value::Number(1.0));
+        assert_eq!(
+            eval_str("$n = (1 + 1)\n$n.Count * 2").unwrap(),
+            Value::Number(2.0)
+        );
+    }
+
+    #[test]
+    fn test_array_element_assignment() {
+        let result = eval_str("$arr = @(1, 2, 3)\n$arr[0] = 5\n$arr[-1] = 9\n$arr").unwrap();
+        assert_eq!(
+            result,
+            Value::Array(vec![Value::Number(5.0), Value::Number(2.0), Value::Number(9.0)].into())
+        );
+    }
+
+    #[test]
+    fn test_array_element_assignment_out_of_range() {
+        let result = eval_str("$arr = @(1)\n$arr[3] = 1");
+        assert!(matches!(result, Err(RuntimeError::InvalidOperation(_))));
+    }
+
+    #[test]
+    fn test_hashtable_key_and_property_assignment() {
+        let result = eval_str(
+            r#"
+            $hash = @{Name="a"}
+            $hash["key"] = "v"
+            $hash.name = "b"
+            @($hash.Key, $hash.Name)
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            result,
+            Value::Array(vec![Value::String("v".into()), Value::String("b".into())].into())
+        );
+    }
+
+    #[test]
+    fn test_nested_property_assignment() {
+        let result = eval_str(
+            r#"
+            $obj = @{Inner=@{Prop=1}; Items=@(1, 2)}
+            $obj.Inner.Prop = 10
+            $obj.Items[1] = 20
+            $obj.Inner.Prop + $obj.Items[1]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(result, Value::Number(30.0));
+    }
+
+    #[test]
+    fn test_changes_through_a_parameter_reach_the_caller() {
+        let result = eval_str(
+            r#"
+            function Set-X($t) { $t.x = 1 }
+            function Set-First($items) { $items[0] = 'changed' }
+            $h = @{}
+            $a = @('a', 'b')
+            Set-X $h
+            Set-First $a
+            @($h.Count, $h.x, $a[0])
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            result,
+            Value::Array(
+                vec![
+                    Value::Number(1.0),
+                    Value::Number(1.0),
+                    Value::String("changed".into())
+                ]
+                .into()
+            )
+        );
+    }
+
+    #[test]
+    fn test_changes_through_an_alias_are_shared() {
+        let result = eval_str(
+            r#"
+            $a = @(1, 2)
+            $b = $a
+            $b[0] = 99
+            $h = @{Inner = @{v = 1}}
+            $g = $h
+            $g.Inner.v = 7
+            $g.Add('k', 1)
+            $o = [pscustomobject]@{N = 1}
+            $p = $o
+            $p.N = 5
+            @($a[0], $h.Inner.v, $h.Count, $o.N, $g -eq $h)
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            result,
+            Value::Array(
+                vec![
+                    Value::Number(99.0),
+                    Value::Number(7.0),
+                    Value::Number(2.0),
+                    Value::Number(5.0),
+                    Value::Boolean(true)
+                ]
+                .into()
+            )
+        );
+    }
+
+    #[test]
+    fn test_copies_change_on_their_own() {
+        let result = eval_str(
+            r#"
+            $h = @{a = 1}
+            $clone = $h.Clone()
+            $clone.a = 2
+            $sum = $h + @{b = 2}
+            $sum.a = 3
+            $o = [pscustomobject]$h
+            $o.a = 4
+            @($h.a, $h.Count, $clone.a, $h -eq $clone)
+            "#,
+        )
+        .unwrap();
+        assert_eq!(
+            result,
+            Value::Array(
+                vec![
+                    Value::Number(1.0),
+                    Value::Number(1.0),
+                    Value::Number(2.0),
+                    Value::Boolean(false)
+                ]
+                .into()
+            )
+        );
+    }
+
+    #[test]
+    fn test_property_assignment_updates_outer_scope_variable() {
+        let result = eval_str(
+            r#"
+            $obj = @{Count=0}
+            function Bump { $obj.Count = $obj.Count + 1 }
+            Bump
+            Bump
+            $obj.Count
+            "#,
+        )
+        .unwrap();
+        assert_eq!(result, Value::Number(2.0));
+    }
+
+    #[test]
+    fn test_comma_separated_assignment_creates_array() {
+        let result = eval_str("$x = 1, 2, 3\n$x").unwrap();
+        assert_eq!(
+            result,
+            Value::Array(vec![Value::Number(1.0), Value::Number(2.0), Value::Number(3.0)].into())
+        );
+    }
+
+    #[test]
+    fn test_multiple_assignment() {
+        let result = eval_str("$a, $b = 1, 2\n$a * 10 + $b").unwrap();
+        assert_eq!(result, Value::Number(12.0));
+    }
+
+    #[test]
+    fn test_multiple_assignment_remainder_capture() {
+        let result = eval_str("$a, $rest = 1, 2, 3\n$rest").unwrap();
+        assert_eq!(
+            result,
+            Value::Array(vec![Value::Number(2.0), Value::Number(3.0)].into())
+        );
+        assert_eq!(
+            eval_str("$a, $rest = 1, 2, 3\n$a").unwrap(),
+            Value::Number(1.0)
+        );
+    }
+
+    #[test]
+    fn test_multiple_assignment_with_too_few_values() {
+        let result = eval_str("$a, $b, $c = 1, 2\n$c").unwrap();
+        assert_eq!(result, Value::Null);
+
+        let result = eval_str("$a, $b = 7\n@($a, $b)").unwrap();
+        assert_eq!(
+            result,
+            Value::Array(vec![Value::Number(7.0), Value::Null].into())
+        );
+    }
+
+    #[test]
+    fn test_match_sets_matches() {
+        let result = eval_str(
+            "$line = 'Order 42 shipped to Oslo'\n\
+             if ($line -match 'order (\\d+) shipped to (?<city>\\w+)') { $matches[1] + ':' + $matches.city }",
+        )
+        .unwrap();
+        assert_eq!(result, Value::String("42:Oslo".into()));
+
+        // A failed match keeps the previous $matches
+        let result =
+            eval_str("$null = 'ab' -match 'b'\n$found = 'ab' -match 'z'\n@($found, $matches[0])")
+                .unwrap();
+        assert_eq!(
+            result,
+            Value::Array(vec![Value::Boolean(false), Value::String("b".into())].into())
+        );
+    }
+
+    #[test]
+    fn test_match_filters_arrays() {
+        let result = eval_str("@('a1', 'b', 'c2') -match '\\d'").unwrap();
+        assert_eq!(
+            result,
+            Value::Array(vec![Value::String("a1".into()), Value::String("c2".into())].into())
+        );
+        assert_eq!(
+            eval_str("'abc' -notmatch '^A'").unwrap(),
+            Value::Boolean(false)
+        );
+        assert!(eval_str("'abc' -match '('").is_err());
+    }
+
+    #[test]
+    fn test_like_operators() {
+        assert_eq!(
+            eval_str("'Report.TXT' -like '*.txt'").unwrap(),
+            Value::Boolean(true)
+        );
+        assert_eq!(
+            eval_str("'Report.TXT' -clike '*.txt'").unwrap(),
+            Value::Boolean(false)
+        );
+        assert_eq!(
+            eval_str("'file7' -notlike 'file[0-9]'").unwrap(),
+            Value::Boolean(false)
+        );
+        assert_eq!(
+            eval_str("'a*' -cnotlike 'a`*'").unwrap(),
+            Value::Boolean(false)
+        );
+        assert_eq!(
+            eval_str("@('apple', 'Banana', 'cherry') -like '[ab]*'").unwrap(),
+            Value::Array(
+                vec![
+                    Value::String("apple".into()),
+                    Value::String("Banana".into())
+                ]
+                .into()
+            )
+        );
+        assert!(eval_str("'abc' -like 'a[bc'").is_err());
+    }
+
+    #[test]
+    fn test_null_literal_and_discard() {
+        assert_eq!(eval_str("$null").unwrap(), Value::Null);
+        assert_eq!(
+            eval_str("$x = 0\n$x -eq $null").unwrap(),
+            Value::Boolean(false)
+        );
+        assert_eq!(eval_str("$null = 5\n$null").unwrap(), Value::Null);
+    }
+
+    #[test]
+    fn test_error_records_failures() {
+        let mut evaluator = Evaluator::new();
+        assert_eq!(
+            eval_str_with_evaluator(&mut evaluator, "$error.Count"),
+            Ok(Value::Number(0.0))
+        );
+
+        assert!(eval_str_with_evaluator(&mut evaluator, "1 / 0").is_err());
+        assert!(eval_str_with_evaluator(&mut evaluator, "Get-Nothing").is_err());
+        assert_eq!(
+            eval_str_with_evaluator(&mut evaluator, "$error.Count"),
+            Ok(Value::Number(2.0))
+        );
+        let newest = eval_str_with_evaluator(&mut evaluator, "$error[0]").unwrap();
+        assert!(newest.to_string().contains("Get-Nothing"));
+        for (property, expected) in [
+            ("CategoryInfo.Category", "ObjectNotFound"),
+            ("FullyQualifiedErrorId", "CommandNotFoundException"),
+            ("TargetObject", "Get-Nothing"),
+        ] {
+            assert_eq!(
+                eval_str_with_evaluator(&mut evaluator, &format!("$error[0].{}", property)),
+                Ok(Value::String(expected.to_string().into()))
+            );
+        }
+    }
+
+    #[test]
+    fn test_error_span_points_at_innermost_failure() {
+        let mut evaluator = Evaluator::new();
+        let source = "function Divide($n) {\n    return $n / 0\n}\n$r = 1 + (Divide 4)";
+        assert!(eval_str_with_evaluator(&mut evaluator, source).is_err());
+        // The division inside the function, not the call that reached it
+        let span = evaluator.error_span().unwrap();
+        assert_eq!(format!("{:?}", span), "2:12..2:18");
+
+        // Each run starts without a location; returns aren't errors
+        assert!(eval_str_with_evaluator(&mut evaluator, "Divide2").is_err());
+        assert_eq!(format!("{:?}", evaluator.error_span().unwrap()), "1:1..1:8");
+        assert!(eval_str_with_evaluator(&mut evaluator, "function F { return 1 }; F").is_ok());
+        assert_eq!(evaluator.error_span(), None);
+    }
+
+    #[test]
+    fn test_throw_values_and_rethrow() {
+        let result = eval_str(
+            "try { throw 'disk full' }\n\
+             catch { $_.Exception.Message + '|' + $_.FullyQualifiedErrorId + '|' + $_.CategoryInfo.Category }",
+        );
+        assert_eq!(
+            result.unwrap(),
+            Value::String("disk full|disk full|OperationStopped".into())
+        );
+
+        // A thrown object is the error's TargetObject
+        let result = eval_str("try { throw @{ Code = 42 } } catch { $_.TargetObject.Code }");
+        assert_eq!(result.unwrap(), Value::Number(42.0));
+
+        // A bare throw passes the caught error on unchanged
+        let error = eval_str("try { 1 / 0 } catch { throw }").unwrap_err();
+        assert_eq!(error, RuntimeError::DivisionByZero);
+
+        // A caught record thrown later keeps its id and target
+        let error = eval_str(
+            "$saved = $null\n\
+             try { [NoSuchType]'x' } catch { $saved = $_ }\n\
+             throw $saved",
+        )
+        .unwrap_err();
+        assert_eq!(error.error_id(), "TypeNotFound");
+        assert_eq!(error.target(), Some("NoSuchType"));
+        assert_eq!(error.category(), ErrorCategory::InvalidOperation);
+
+        let error = eval_str("throw").unwrap_err();
+        assert_eq!(error.error_id(), "ScriptHalted");
+    }
+
+    #[test]
+    fn test_break_and_continue_in_loops() {
+        let result = eval_str(
+            "foreach ($i in @(1, 2, 3, 4)) { if ($i -eq 2) { continue }; if ($i -eq 4) { break }; $i }",
+        );
+        assert_eq!(
+            result.unwrap(),
+            Value::Array(vec![Value::Number(1.0), Value::Number(3.0)].into())
+        );
+
+        // What the iteration wrote before the break is kept
+        let result = eval_str("foreach ($i in @(1, 2)) { 'a'; break; 'b' }; 'after'");
+        assert_eq!(
+            result.unwrap(),
+            Value::Array(vec![Value::String("a".into()), Value::String("after".into())].into())
+        );
+
+        let error = eval_str("break").unwrap_err();
+        assert_eq!(error.to_string(), "Break statement outside of a loop");
+    }
+
+    #[test]
+    fn test_finally_runs_however_the_try_ends() {
+        // After a return, keeping the returned value first
+        let result = eval_str("function f { try { return 1 } finally { 'cleanup' } }; f");
+        assert_eq!(
+            result.unwrap(),
+            Value::Array(vec![Value::Number(1.0), Value::String("cleanup".into())].into())
+        );
+
+        // After a break and a continue
+        let result = eval_str(
+            "$log = ''\n\
+             foreach ($i in @(1, 2, 3)) { try { if ($i -eq 1) { continue }; break } finally { $log = $log + $i } }\n\
+             $log",
+        );
+        assert_eq!(result.unwrap(), Value::String("12".into()));
+
+        // After an error, with or without a catch
+        let result = eval_str(
+            "$steps = ''\n\
+             try { try { throw 'x' } finally { $steps = $steps + 'inner' } } catch { $steps = $steps + ',caught' } finally { $steps = $steps + ',outer' }\n\
+             $steps",
+        );
+        assert_eq!(result.unwrap(), Value::String("inner,caught,outer".into()));
+
+        // An error in the finally block replaces the try's
+        let error = eval_str("try { throw 'first' } finally { throw 'second' }").unwrap_err();
+        assert_eq!(error.error_id(), "second");
+
+        let result = eval_str("try { 1 } catch { 2 } finally { 3 }");
+        assert_eq!(
+            result.unwrap(),
+            Value::Array(vec![Value::Number(1.0), Value::Number(3.0)].into())
+        );
+    }
+
+    #[test]
+    fn test_runaway_recursion_is_a_call_depth_error() {
+        let countdown = "function f($n) { if ($n -gt 0) { f ($n - 1) } else { 'done' } }";
+        let mut evaluator = Evaluator::new();
+        eval_str_with_evaluator(&mut evaluator, countdown).unwrap();
+
+        // Far deeper than the test thread's stack holds without growing it
+        assert_eq!(
+            eval_str_with_evaluator(&mut evaluator, "f 900"),
+            Ok(Value::String("done".into()))
+        );
+        for script in [
+            "f 5000",
+            "function g { g }; g",
+            "function h { @(1) | { h } }; h",
+        ] {
+            let program = Parser::new(Lexer::new(script).tokenize().unwrap())
+                .parse()
+                .unwrap();
+            let error = evaluator.eval(program).unwrap_err();
+            assert_eq!(error.error_id(), "CallDepthOverflow", "{}", script);
+            assert_eq!(error.category(), ErrorCategory::InvalidOperation);
+        }
+        // The failed calls' scopes are gone
+        assert_eq!(evaluator.scope.depth(), 1);
+        assert_eq!(evaluator.call_depth(), 0);
+    }
+
+    #[test]
+    fn test_call_and_scope_depth_limits_are_configurable() {
+        let mut evaluator = Evaluator::new();
+        evaluator.set_max_call_depth(10);
+        eval_str_with_evaluator(
+            &mut evaluator,
+            "function f($n) { if ($n -gt 0) { f ($n - 1) } }",
+        )
+        .unwrap();
+        assert!(eval_str_with_evaluator(&mut evaluator, "f 9").is_ok());
+        assert_eq!(
+            eval_str_with_evaluator(&mut evaluator, "f 10"),
+            Err("The script failed due to call depth overflow. The call depth reached 11 and the maximum is 10.".to_string())
+        );
+
+        let mut evaluator = Evaluator::new();
+        evaluator.set_max_scope_depth(3);
+        assert_eq!(
+            eval_str_with_evaluator(&mut evaluator, "if (1) { if (1) { 'two deep' } }"),
+            Ok(Value::String("two deep".into()))
+        );
+        assert_eq!(
+            eval_str_with_evaluator(&mut evaluator, "if (1) { if (1) { if (1) { 'three' } } }"),
+            Err("The scope depth reached 4 and the maximum is 3.".to_string())
+        );
+        assert_eq!(evaluator.scope.depth(), 1);
+    }
+
+    #[test]
+    fn test_call_operator() {
+        // A command name from a string or variable, looked up when it runs
+        let result = eval_str(
+            "function Get-Greeting($name) { \"hi $name\" }; $c = 'Get-Greeting'; & $c bob",
+        );
+        assert_eq!(result.unwrap(), Value::String("hi bob".into()));
+        let result = eval_str("function f { 'later' }; & 'f'");
+        assert_eq!(result.unwrap(), Value::String("later".into()));
+
+        // A script block runs with its arguments in $args, or per pipeline item
+        let result = eval_str("$b = { $args[1] }; & $b 1 2; & { 3 }");
+        assert_eq!(
+            result.unwrap(),
+            Value::Array(vec![Value::Number(2.0), Value::Number(3.0)].into())
+        );
+        let result = eval_str("@(1, 2) | & { $_ * 10 }");
+        assert_eq!(
+            result.unwrap(),
+            Value::Array(vec![Value::Number(10.0), Value::Number(20.0)].into())
+        );
+
+        let error = eval_str("& 'No-Such-Command'").unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("'No-Such-Command' is not recognized"));
+        assert!(eval_str("& $nothing").is_err());
     }
 
     // Helper function for tests that need to maintain state
@@ -1312,15 +5605,16 @@ mod tests {
 #[test]
 fn test_array_literal() {
     let mut evaluator = Evaluator::new();
-    let arr_expr = Expression::Array(vec![
-        Expression::Literal(Literal::Number(1.0)),
-        Expression::Literal(Literal::Number(2.0)),
-        Expression::Literal(Literal::Number(3.0)),
+    let arr_expr = ExpressionKind::Array(vec![
+        ExpressionKind::Literal(Literal::Number(1.0)).into(),
+        ExpressionKind::Literal(Literal::Number(2.0)).into(),
+        ExpressionKind::Literal(Literal::Number(3.0)).into(),
     ]);
 
-    let result = evaluator.eval_expression(arr_expr).unwrap();
+    let result = evaluator.eval_expression(&arr_expr.into()).unwrap();
     match result {
         Value::Array(items) => {
+            let items = items.read();
             assert_eq!(items.len(), 3);
             assert_eq!(items[0], Value::Number(1.0));
             assert_eq!(items[1], Value::Number(2.0));
@@ -1333,11 +5627,12 @@ fn test_array_literal() {
 #[test]
 fn test_empty_array() {
     let mut evaluator = Evaluator::new();
-    let arr_expr = Expression::Array(vec![]);
+    let arr_expr = ExpressionKind::Array(vec![]);
 
-    let result = evaluator.eval_expression(arr_expr).unwrap();
+    let result = evaluator.eval_expression(&arr_expr.into()).unwrap();
     match result {
         Value::Array(items) => {
+            let items = items.read();
             assert_eq!(items.len(), 0);
         }
         _ => panic!("Expected array value"),
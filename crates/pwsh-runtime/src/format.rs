@@ -0,0 +1,667 @@
+/// Composite string formatting for the -f operator, ToString and Get-Date -Format
+///
+/// Implements .NET-style composite format strings: `{index[,alignment][:formatString]}`
+/// with `{{` and `}}` as escaped braces. Numeric values support the standard
+/// format specifiers (C, D, E, F, G, N, P, X) and simple custom patterns
+/// such as `0.00` or `#,##0`. Dates support the standard specifiers (d, D, f,
+/// F, g, G, M, o, r, s, t, T, u, U, Y) and custom patterns such as
+/// `yyyy-MM-dd HH:mm`. Separators, currency and names come from a [`Culture`].
+use crate::culture::Culture;
+use crate::error::RuntimeError;
+use crate::value::Value;
+use chrono::{Datelike, Local, NaiveDateTime, Offset, TimeZone, Timelike};
+
+/// Alignments must be below this, as in .NET
+const MAX_ALIGNMENT: u64 = 1_000_000;
+
+/// The most digits a standard numeric format may ask for, as in .NET
+const MAX_PRECISION: usize = 999_999_999;
+
+/// The most decimals `format!` is asked for: more than an f64 has, so any
+/// asked for beyond them are zeros
+const MAX_EXACT_DECIMALS: usize = 1100;
+
+/// Format a composite format string with the given arguments
+pub fn format_composite(
+    format: &str,
+    args: &[Value],
+    culture: &Culture,
+) -> Result<String, RuntimeError> {
+    let chars: Vec<char> = format.chars().collect();
+    let mut result = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '{' if chars.get(i + 1) == Some(&'{') => {
+                result.push('{');
+                i += 2;
+            }
+            '}' if chars.get(i + 1) == Some(&'}') => {
+                result.push('}');
+                i += 2;
+            }
+            '{' => {
+                let end = chars[i + 1..]
+                    .iter()
+                    .position(|&c| c == '}')
+                    .map(|p| i + 1 + p)
+                    .ok_or_else(invalid_format)?;
+                let item: String = chars[i + 1..end].iter().collect();
+                result.push_str(&format_item(&item, args, culture)?);
+                i = end + 1;
+            }
+            '}' => return Err(invalid_format()),
+            ch => {
+                result.push(ch);
+                i += 1;
+            }
+        }
+    }
+
+    Ok(result)
+}
+
+/// Format a single `index[,alignment][:formatString]` item
+fn format_item(item: &str, args: &[Value], culture: &Culture) -> Result<String, RuntimeError> {
+    let (head, spec) = match item.find(':') {
+        Some(pos) => (&item[..pos], Some(&item[pos + 1..])),
+        None => (item, None),
+    };
+    let (index, alignment) = match head.find(',') {
+        Some(pos) => (&head[..pos], Some(&head[pos + 1..])),
+        None => (head, None),
+    };
+
+    let index: usize = index.trim().parse().map_err(|_| invalid_format())?;
+    let value = args.get(index).ok_or_else(|| {
+        RuntimeError::InvalidOperation(
+            "Index (zero based) must be greater than or equal to zero and less than the size of the argument list".to_string(),
+        )
+    })?;
+
+    let text = format_value(value, spec.unwrap_or(""), culture)?;
+
+    let Some(alignment) = alignment else {
+        return Ok(text);
+    };
+    let width: i64 = alignment.trim().parse().map_err(|_| invalid_format())?;
+    if width.unsigned_abs() >= MAX_ALIGNMENT {
+        return Err(invalid_format());
+    }
+    // Padded by hand: format!'s runtime widths stop at u16::MAX
+    let padding = " ".repeat((width.unsigned_abs() as usize).saturating_sub(text.chars().count()));
+    Ok(if width < 0 {
+        text + &padding
+    } else {
+        padding + &text
+    })
+}
+
+/// `digits` with zeros in front to make it at least `width` long
+fn zero_pad(digits: String, width: usize) -> String {
+    match width.checked_sub(digits.len()) {
+        Some(zeros) if zeros > 0 => "0".repeat(zeros) + &digits,
+        _ => digits,
+    }
+}
+
+/// `format!`'s `{:.prec$}` and `{:.prec$e}` for any precision: past the
+/// digits an f64 has, the rest are zeros
+fn with_decimals(n: f64, decimals: usize, exponent: bool) -> String {
+    let exact = decimals.min(MAX_EXACT_DECIMALS);
+    let mut text = if exponent {
+        format!("{:.prec$e}", n, prec = exact)
+    } else {
+        format!("{:.prec$}", n, prec = exact)
+    };
+    let zeros = "0".repeat(decimals - exact);
+    match text.find('e') {
+        Some(e) => text.insert_str(e, &zeros),
+        None => text.push_str(&zeros),
+    }
+    text
+}
+
+/// Format a value using a format string (e.g. "N2", "X4", "0.00", "yyyy-MM-dd");
+/// an empty one gives the culture's general format. Values without format
+/// support (strings, booleans, ...) ignore the specifier.
+pub fn format_value(value: &Value, spec: &str, culture: &Culture) -> Result<String, RuntimeError> {
+    match value {
+        Value::Number(_) if spec.is_empty() => Ok(culture.localize_number(&value.to_string())),
+        Value::Number(n) => format_number(*n, spec, culture),
+        Value::DateTime(dt) if spec.is_empty() => format_date(dt, "G", culture),
+        Value::DateTime(dt) => format_date(dt, spec, culture),
+        other => Ok(other.to_string()),
+    }
+}
+
+/// Format a number using a standard or custom numeric format string
+fn format_number(n: f64, spec: &str, culture: &Culture) -> Result<String, RuntimeError> {
+    let mut chars = spec.chars();
+    let kind = chars.next().unwrap_or('G');
+    let precision_text: String = chars.collect();
+    let is_standard = kind.is_ascii_alphabetic()
+        && (precision_text.is_empty() || precision_text.chars().all(|c| c.is_ascii_digit()));
+
+    if !is_standard {
+        return Ok(culture.localize_number(&format_custom(n, spec)));
+    }
+
+    let precision: Option<usize> = if precision_text.is_empty() {
+        None
+    } else {
+        match precision_text.parse() {
+            Ok(precision) if precision <= MAX_PRECISION => Some(precision),
+            _ => return Err(invalid_format()),
+        }
+    };
+
+    let localized = |text: String| culture.localize_number(&text);
+    match kind.to_ascii_uppercase() {
+        'N' => Ok(localized(format_fixed(n, precision.unwrap_or(2), true))),
+        'F' => Ok(localized(format_fixed(n, precision.unwrap_or(2), false))),
+        'P' => Ok(format!(
+            "{}%",
+            localized(format_fixed(n * 100.0, precision.unwrap_or(2), true))
+        )),
+        'C' => {
+            let text = localized(format_fixed(n.abs(), precision.unwrap_or(2), true));
+            let sign = if n < 0.0 { "-" } else { "" };
+            Ok(if culture.currency_after {
+                format!("{}{}\u{a0}{}", sign, text, culture.currency_symbol)
+            } else {
+                format!("{}{}{}", sign, culture.currency_symbol, text)
+            })
+        }
+        'D' => {
+            let int = require_integer(n)?;
+            let digits = zero_pad(int.unsigned_abs().to_string(), precision.unwrap_or(0));
+            Ok(if int < 0 {
+                format!("-{}", digits)
+            } else {
+                digits
+            })
+        }
+        'X' => {
+            let int = require_integer(n)?;
+            let digits = if kind == 'x' {
+                format!("{:x}", int)
+            } else {
+                format!("{:X}", int)
+            };
+            Ok(zero_pad(digits, precision.unwrap_or(0)))
+        }
+        'E' => {
+            let formatted = with_decimals(n, precision.unwrap_or(6), true);
+            let (mantissa, exponent) = formatted.split_once('e').unwrap_or((&formatted, "0"));
+            let exponent: i32 = exponent.parse().unwrap_or(0);
+            let sign = if exponent < 0 { '-' } else { '+' };
+            let e = if kind == 'e' { 'e' } else { 'E' };
+            Ok(format!(
+                "{}{}{}{:03}",
+                localized(mantissa.to_string()),
+                e,
+                sign,
+                exponent.abs()
+            ))
+        }
+        'G' | 'R' => Ok(localized(Value::Number(n).to_string())),
+        _ => Err(invalid_format()),
+    }
+}
+
+/// Format a number with fixed decimals, optionally grouping thousands
+fn format_fixed(n: f64, decimals: usize, group: bool) -> String {
+    let formatted = with_decimals(n.abs(), decimals, false);
+    let (int_part, frac_part) = match formatted.split_once('.') {
+        Some((i, f)) => (i.to_string(), Some(f.to_string())),
+        None => (formatted.clone(), None),
+    };
+
+    let int_part = if group {
+        group_thousands(&int_part)
+    } else {
+        int_part
+    };
+    let sign = if n < 0.0 && formatted.chars().any(|c| c.is_ascii_digit() && c != '0') {
+        "-"
+    } else {
+        ""
+    };
+
+    match frac_part {
+        Some(frac) => format!("{}{}.{}", sign, int_part, frac),
+        None => format!("{}{}", sign, int_part),
+    }
+}
+
+/// Insert thousands separators into a string of digits
+fn group_thousands(digits: &str) -> String {
+    let mut grouped = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, ch) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(ch);
+    }
+    grouped
+}
+
+/// Format a number with a custom pattern built from 0, #, ',' and '.'
+/// e.g. "0.00" -> 3.14, "#,##0" -> 1,234, "000" -> 007
+fn format_custom(n: f64, pattern: &str) -> String {
+    let (int_pattern, frac_pattern) = match pattern.split_once('.') {
+        Some((i, f)) => (i, f),
+        None => (pattern, ""),
+    };
+
+    let max_decimals = frac_pattern
+        .chars()
+        .filter(|c| *c == '0' || *c == '#')
+        .count();
+    let min_decimals = frac_pattern.chars().filter(|c| *c == '0').count();
+    let min_int_digits = int_pattern.chars().filter(|c| *c == '0').count();
+    let group = int_pattern.contains(',');
+
+    let mut text = format_fixed(n.abs(), max_decimals, false);
+    if let Some(dot) = text.find('.') {
+        // Drop optional (#) trailing zeros beyond the required decimals
+        while text.len() - dot - 1 > min_decimals && text.ends_with('0') {
+            text.pop();
+        }
+        if text.ends_with('.') {
+            text.pop();
+        }
+    }
+
+    let (int_part, frac_part) = match text.split_once('.') {
+        Some((i, f)) => (i.to_string(), Some(f.to_string())),
+        None => (text.clone(), None),
+    };
+    let mut int_part = if int_part == "0" && min_int_digits == 0 {
+        String::new()
+    } else {
+        int_part
+    };
+    while int_part.len() < min_int_digits {
+        int_part.insert(0, '0');
+    }
+    if group {
+        int_part = group_thousands(&int_part);
+    }
+
+    let sign = if n < 0.0 { "-" } else { "" };
+    match frac_part {
+        Some(frac) => format!("{}{}.{}", sign, int_part, frac),
+        None => format!("{}{}", sign, int_part),
+    }
+}
+
+/// Format a date using a standard (single letter) or custom date format string
+pub fn format_date(
+    dt: &NaiveDateTime,
+    spec: &str,
+    culture: &Culture,
+) -> Result<String, RuntimeError> {
+    let mut chars = spec.chars();
+    let (Some(kind), None) = (chars.next(), chars.next()) else {
+        return expand_date_pattern(dt, spec, culture);
+    };
+
+    let invariant = Culture::invariant();
+    let (pattern, culture, dt) = match kind {
+        'd' => (culture.short_date_pattern.to_string(), culture, *dt),
+        'D' => (culture.long_date_pattern.to_string(), culture, *dt),
+        'f' => (
+            format!(
+                "{} {}",
+                culture.long_date_pattern, culture.short_time_pattern
+            ),
+            culture,
+            *dt,
+        ),
+        'F' => (
+            format!(
+                "{} {}",
+                culture.long_date_pattern, culture.long_time_pattern
+            ),
+            culture,
+            *dt,
+        ),
+        'g' => (
+            format!(
+                "{} {}",
+                culture.short_date_pattern, culture.short_time_pattern
+            ),
+            culture,
+            *dt,
+        ),
+        'G' => (
+            format!(
+                "{} {}",
+                culture.short_date_pattern, culture.long_time_pattern
+            ),
+            culture,
+            *dt,
+        ),
+        'm' | 'M' => (culture.month_day_pattern.to_string(), culture, *dt),
+        't' => (culture.short_time_pattern.to_string(), culture, *dt),
+        'T' => (culture.long_time_pattern.to_string(), culture, *dt),
+        'y' | 'Y' => (culture.year_month_pattern.to_string(), culture, *dt),
+        // Round-trip and sortable formats are the same in every culture
+        'o' | 'O' => (
+            "yyyy'-'MM'-'dd'T'HH':'mm':'ss'.'fffffff".to_string(),
+            &invariant,
+            *dt,
+        ),
+        'r' | 'R' => (
+            "ddd, dd MMM yyyy HH':'mm':'ss 'GMT'".to_string(),
+            &invariant,
+            *dt,
+        ),
+        's' => ("yyyy'-'MM'-'dd'T'HH':'mm':'ss".to_string(), &invariant, *dt),
+        'u' => (
+            "yyyy'-'MM'-'dd HH':'mm':'ss'Z'".to_string(),
+            &invariant,
+            *dt,
+        ),
+        // The full date and time, converted from local time to UTC
+        'U' => (
+            format!(
+                "{} {}",
+                culture.long_date_pattern, culture.long_time_pattern
+            ),
+            culture,
+            Local
+                .from_local_datetime(dt)
+                .earliest()
+                .map_or(*dt, |local| local.naive_utc()),
+        ),
+        _ => return Err(invalid_format()),
+    };
+    expand_date_pattern(&dt, &pattern, culture)
+}
+
+/// Expand a custom date pattern such as `dddd, MMMM d, yyyy` or `HH:mm:ss.fff`.
+/// Quoted text and `\`-escaped characters are copied as they are.
+fn expand_date_pattern(
+    dt: &NaiveDateTime,
+    pattern: &str,
+    culture: &Culture,
+) -> Result<String, RuntimeError> {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut result = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let ch = chars[i];
+        let run = chars[i..].iter().take_while(|&&c| c == ch).count();
+        let mut consumed = run;
+        match ch {
+            'd' => match run {
+                1 => result.push_str(&dt.day().to_string()),
+                2 => result.push_str(&format!("{:02}", dt.day())),
+                3 => result.push_str(
+                    culture.abbreviated_day_names[dt.weekday().num_days_from_sunday() as usize],
+                ),
+                _ => {
+                    result.push_str(culture.day_names[dt.weekday().num_days_from_sunday() as usize])
+                }
+            },
+            'M' => match run {
+                1 => result.push_str(&dt.month().to_string()),
+                2 => result.push_str(&format!("{:02}", dt.month())),
+                3 => result.push_str(culture.abbreviated_month_names[dt.month0() as usize]),
+                _ => result.push_str(culture.month_names[dt.month0() as usize]),
+            },
+            'y' => {
+                let year = dt.year();
+                match run {
+                    1 => result.push_str(&(year % 100).to_string()),
+                    2 => result.push_str(&format!("{:02}", year % 100)),
+                    _ => result.push_str(&zero_pad(year.to_string(), run)),
+                }
+            }
+            'h' => push_padded(&mut result, dt.hour12().1, run),
+            'H' => push_padded(&mut result, dt.hour(), run),
+            'm' => push_padded(&mut result, dt.minute(), run),
+            's' => push_padded(&mut result, dt.second(), run),
+            'f' | 'F' => {
+                if run > 7 {
+                    return Err(invalid_format());
+                }
+                let digits = format!("{:09}", dt.nanosecond() % 1_000_000_000);
+                let digits = &digits[..run];
+                if ch == 'f' {
+                    result.push_str(digits);
+                } else {
+                    // F drops trailing zeros, and the separator before an empty fraction
+                    let digits = digits.trim_end_matches('0');
+                    if digits.is_empty() && result.ends_with('.') {
+                        result.pop();
+                    }
+                    result.push_str(digits);
+                }
+            }
+            't' => {
+                let designator = if dt.hour() < 12 {
+                    culture.am_designator
+                } else {
+                    culture.pm_designator
+                };
+                match run {
+                    1 => result.extend(designator.chars().next()),
+                    _ => result.push_str(designator),
+                }
+            }
+            'g' => result.push_str("A.D."),
+            'z' => {
+                let offset = Local
+                    .offset_from_local_datetime(dt)
+                    .earliest()
+                    .map_or(0, |offset| offset.fix().local_minus_utc());
+                let sign = if offset < 0 { '-' } else { '+' };
+                let (hours, minutes) = (offset.abs() / 3600, offset.abs() % 3600 / 60);
+                match run {
+                    1 => result.push_str(&format!("{}{}", sign, hours)),
+                    2 => result.push_str(&format!("{}{:02}", sign, hours)),
+                    _ => result.push_str(&format!("{}{:02}:{:02}", sign, hours, minutes)),
+                }
+            }
+            // The kind of a local date and time without a zone is unspecified
+            'K' => {}
+            '/' => {
+                result.push_str(culture.date_separator);
+                consumed = 1;
+            }
+            '\'' | '"' => {
+                let end = chars[i + 1..]
+                    .iter()
+                    .position(|&c| c == ch)
+                    .ok_or_else(invalid_format)?;
+                result.extend(&chars[i + 1..i + 1 + end]);
+                consumed = end + 2;
+            }
+            '\\' => {
+                let escaped = chars.get(i + 1).ok_or_else(invalid_format)?;
+                result.push(*escaped);
+                consumed = 2;
+            }
+            // `%d` is the custom day specifier rather than the short date format
+            '%' => consumed = 1,
+            other => {
+                result.push(other);
+                consumed = 1;
+            }
+        }
+        i += consumed;
+    }
+
+    Ok(result)
+}
+
+/// Append a date part with one or two digits, as `h` and `hh` ask for
+fn push_padded(result: &mut String, value: u32, run: usize) {
+    if run == 1 {
+        result.push_str(&value.to_string());
+    } else {
+        result.push_str(&format!("{:02}", value));
+    }
+}
+
+fn require_integer(n: f64) -> Result<i64, RuntimeError> {
+    if n.fract() != 0.0 {
+        return Err(RuntimeError::InvalidOperation(
+            "Format specifier was invalid for a non-integer value".to_string(),
+        ));
+    }
+    Ok(n as i64)
+}
+
+fn invalid_format() -> RuntimeError {
+    RuntimeError::InvalidOperation("Input string was not in a correct format".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fmt(format: &str, args: Vec<Value>) -> String {
+        format_composite(format, &args, &Culture::default()).unwrap()
+    }
+
+    fn date(spec: &str, culture: &str) -> String {
+        let dt = chrono::NaiveDate::from_ymd_opt(2024, 3, 5)
+            .unwrap()
+            .and_hms_milli_opt(14, 7, 9, 120)
+            .unwrap();
+        format_date(&dt, spec, &Culture::from_name(culture).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_format_indexes_and_escapes() {
+        let args = vec![Value::String("Alice".into()), Value::Number(30.0)];
+        assert_eq!(fmt("{0} is {1}", args.clone()), "Alice is 30");
+        assert_eq!(fmt("{1}-{0}-{1}", args.clone()), "30-Alice-30");
+        assert_eq!(fmt("{{{0}}}", args), "{Alice}");
+    }
+
+    #[test]
+    fn test_format_alignment() {
+        let args = vec![Value::String("ab".into())];
+        assert_eq!(fmt("[{0,5}]", args.clone()), "[   ab]");
+        assert_eq!(fmt("[{0,-5}]", args), "[ab   ]");
+    }
+
+    #[test]
+    fn test_format_wide_alignment_and_precision() {
+        let one = [Value::Number(1.0)];
+        let culture = Culture::default();
+        assert!(format_composite("{0,-999999999999}", &one, &culture).is_err());
+        assert!(format_composite("{0,1000000}", &one, &culture).is_err());
+        assert!(format_composite("{0:D1000000000}", &one, &culture).is_err());
+
+        let right = fmt("{0,99999}", one.to_vec());
+        assert_eq!(right.len(), 99999);
+        assert!(right.ends_with(" 1"));
+        let left = fmt("{0,-70000}", one.to_vec());
+        assert_eq!(left.len(), 70000);
+        assert!(left.starts_with("1 "));
+
+        let digits = fmt("{0:D99999}", vec![Value::Number(5.0)]);
+        assert_eq!(digits.len(), 99999);
+        assert!(digits.starts_with("00") && digits.ends_with("05"));
+        let hex = fmt("{0:X70000}", vec![Value::Number(255.0)]);
+        assert_eq!(hex.len(), 70000);
+        assert!(hex.ends_with("0FF"));
+
+        let fixed = fmt("{0:N99999}", one.to_vec());
+        assert_eq!(fixed.len(), 2 + 99999);
+        assert!(fixed.starts_with("1.000") && fixed.ends_with("000"));
+        let exponent = fmt("{0:E70000}", vec![Value::Number(1.5)]);
+        assert!(exponent.starts_with("1.5000") && exponent.ends_with("0E+000"));
+        assert_eq!(exponent.len(), 2 + 70000 + 5);
+    }
+
+    #[test]
+    fn test_format_standard_numeric() {
+        let n = vec![Value::Number(1234.5678)];
+        assert_eq!(fmt("{0:N2}", n.clone()), "1,234.57");
+        assert_eq!(fmt("{0:N}", n.clone()), "1,234.57");
+        assert_eq!(fmt("{0:F1}", n.clone()), "1234.6");
+        assert_eq!(fmt("{0:C}", n.clone()), "$1,234.57");
+        assert_eq!(fmt("{0:E2}", n), "1.23E+003");
+        assert_eq!(fmt("{0:P1}", vec![Value::Number(0.256)]), "25.6%");
+        assert_eq!(fmt("{0:D5}", vec![Value::Number(42.0)]), "00042");
+        assert_eq!(fmt("{0:X}", vec![Value::Number(255.0)]), "FF");
+        assert_eq!(fmt("{0:x4}", vec![Value::Number(255.0)]), "00ff");
+    }
+
+    #[test]
+    fn test_format_custom_numeric() {
+        assert_eq!(fmt("{0:0.00}", vec![Value::Number(1.23456)]), "1.23");
+        assert_eq!(
+            fmt("{0:#,##0}", vec![Value::Number(1234567.0)]),
+            "1,234,567"
+        );
+        assert_eq!(fmt("{0:000}", vec![Value::Number(7.0)]), "007");
+        assert_eq!(fmt("{0:0.##}", vec![Value::Number(2.5)]), "2.5");
+    }
+
+    #[test]
+    fn test_format_numbers_for_culture() {
+        let german = Culture::from_name("de-DE").unwrap();
+        let n = [Value::Number(1234.5678)];
+        let fmt = |format: &str| format_composite(format, &n, &german).unwrap();
+        assert_eq!(fmt("{0:N2}"), "1.234,57");
+        assert_eq!(fmt("{0:C}"), "1.234,57\u{a0}€");
+        assert_eq!(fmt("{0}"), "1234,5678");
+        assert_eq!(fmt("{0:#,##0.0}"), "1.234,6");
+        assert_eq!(
+            format_value(&Value::Number(-2.5), "C", &Culture::invariant()).unwrap(),
+            "-¤2.50"
+        );
+    }
+
+    #[test]
+    fn test_format_standard_dates() {
+        assert_eq!(date("d", "en-US"), "3/5/2024");
+        assert_eq!(date("D", "en-US"), "Tuesday, March 5, 2024");
+        assert_eq!(date("G", "en-US"), "3/5/2024 2:07:09 PM");
+        assert_eq!(date("t", "en-US"), "2:07 PM");
+        assert_eq!(date("d", "en-GB"), "05/03/2024");
+        assert_eq!(date("D", "de-DE"), "Dienstag, 5. März 2024");
+        assert_eq!(date("g", "fr-FR"), "05/03/2024 14:07");
+        assert_eq!(date("o", "de-DE"), "2024-03-05T14:07:09.1200000");
+        assert_eq!(date("s", "en-US"), "2024-03-05T14:07:09");
+        assert_eq!(date("r", "fr-FR"), "Tue, 05 Mar 2024 14:07:09 GMT");
+        assert_eq!(date("u", "en-US"), "2024-03-05 14:07:09Z");
+    }
+
+    #[test]
+    fn test_format_custom_dates() {
+        assert_eq!(date("yyyy-MM-dd HH:mm", "en-US"), "2024-03-05 14:07");
+        assert_eq!(date("ddd d MMM yy", "en-US"), "Tue 5 Mar 24");
+        assert_eq!(date("hh:mm:ss.fff tt", "en-GB"), "02:07:09.120 pm");
+        assert_eq!(date("ss.FFFF", "en-US"), "09.12");
+        assert_eq!(date("dd/MM/yyyy", "de-DE"), "05.03.2024");
+        assert_eq!(date("'Day' d 'of' MMMM", "fr-FR"), "Day 5 of mars");
+        assert_eq!(date("\\d\\d %d", "en-US"), "dd 5");
+        assert_eq!(date("%M", "en-US"), "3");
+        let years = date(&"y".repeat(70000), "en-US");
+        assert_eq!(years.len(), 70000);
+        assert!(years.starts_with("000") && years.ends_with("02024"));
+    }
+
+    #[test]
+    fn test_format_errors() {
+        let culture = Culture::default();
+        assert!(format_composite("{1}", &[Value::Number(1.0)], &culture).is_err());
+        assert!(format_composite("{0", &[Value::Number(1.0)], &culture).is_err());
+        assert!(format_composite("{0:D}", &[Value::Number(1.5)], &culture).is_err());
+        let now = Value::DateTime(chrono::Local::now().naive_local());
+        assert!(format_value(&now, "x", &culture).is_err());
+        assert!(format_value(&now, "'open", &culture).is_err());
+    }
+}
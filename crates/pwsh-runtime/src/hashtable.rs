@@ -0,0 +1,167 @@
+/// Hashtable members: .Keys, .Values, ContainsKey(), Add(), Remove(), Clone(), GetEnumerator()
+/// Keys are matched case-insensitively and enumerated in insertion order.
+use crate::error::RuntimeError;
+use crate::property_map::PropertyMap;
+use crate::value::Value;
+
+/// Resolve a hashtable property that isn't a key: .Keys, .Values, .Count
+pub fn property(table: &PropertyMap, member: &str) -> Option<Value> {
+    match member.to_lowercase().as_str() {
+        "keys" => Some(Value::Array(
+            table
+                .keys()
+                .map(|key| Value::String(key.clone().into()))
+                .collect::<Vec<_>>()
+                .into(),
+        )),
+        "values" => Some(Value::Array(
+            table.values().cloned().collect::<Vec<_>>().into(),
+        )),
+        "count" => Some(Value::Number(table.len() as f64)),
+        _ => None,
+    }
+}
+
+/// Call a hashtable method, returning None if there is no method with that name
+pub fn call_method(
+    table: &mut PropertyMap,
+    method: &str,
+    args: &[Value],
+) -> Option<Result<Value, RuntimeError>> {
+    let result = match (method.to_lowercase().as_str(), args) {
+        ("containskey" | "contains", [key]) => {
+            Ok(Value::Boolean(table.contains_key(&key.to_string())))
+        }
+        ("containsvalue", [value]) => Ok(Value::Boolean(table.values().any(|v| v == value))),
+        ("add", [key, value]) => {
+            let key = key.to_string();
+            match table.get_key(&key) {
+                Some(existing) => Err(RuntimeError::InvalidOperation(format!(
+                    "Item has already been added. Key in dictionary: '{}'",
+                    existing
+                ))),
+                None => {
+                    table.insert(key, value.clone());
+                    Ok(Value::Null)
+                }
+            }
+        }
+        ("remove", [key]) => {
+            table.shift_remove(&key.to_string());
+            Ok(Value::Null)
+        }
+        ("clear", []) => {
+            table.clear();
+            Ok(Value::Null)
+        }
+        // A new table with the same entries, which changes on its own
+        ("clone", []) => Ok(Value::Object(table.clone().into())),
+        ("getenumerator", []) => Ok(Value::Array(
+            table
+                .iter()
+                .map(|(key, value)| {
+                    Value::Object(
+                        PropertyMap::from([
+                            ("Key".to_string(), Value::String(key.clone().into())),
+                            ("Value".to_string(), value.clone()),
+                        ])
+                        .into(),
+                    )
+                })
+                .collect::<Vec<_>>()
+                .into(),
+        )),
+        (
+            "containskey" | "contains" | "containsvalue" | "add" | "remove" | "clear" | "clone"
+            | "getenumerator",
+            _,
+        ) => Err(RuntimeError::InvalidOperation(format!(
+            "Cannot find an overload for \"{}\" and the argument count: \"{}\"",
+            method,
+            args.len()
+        ))),
+        _ => return None,
+    };
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table() -> PropertyMap {
+        PropertyMap::from([
+            ("a".to_string(), Value::Number(1.0)),
+            ("b".to_string(), Value::Number(2.0)),
+        ])
+    }
+
+    #[test]
+    fn test_keys_values_and_count() {
+        let table = table();
+        assert_eq!(
+            property(&table, "Keys"),
+            Some(Value::Array(
+                vec![Value::String("a".into()), Value::String("b".into())].into()
+            ))
+        );
+        assert_eq!(
+            property(&table, "values"),
+            Some(Value::Array(
+                vec![Value::Number(1.0), Value::Number(2.0)].into()
+            ))
+        );
+        assert_eq!(property(&table, "Count"), Some(Value::Number(2.0)));
+        assert_eq!(property(&table, "Other"), None);
+    }
+
+    #[test]
+    fn test_contains_add_and_remove() {
+        let mut table = table();
+        let call = |table: &mut PropertyMap, method: &str, args: &[Value]| {
+            call_method(table, method, args).unwrap()
+        };
+
+        assert_eq!(
+            call(&mut table, "ContainsKey", &[Value::String("A".into())]),
+            Ok(Value::Boolean(true))
+        );
+        assert!(call(
+            &mut table,
+            "Add",
+            &[Value::String("A".into()), Value::Number(3.0)]
+        )
+        .is_err());
+
+        call(
+            &mut table,
+            "Add",
+            &[Value::String("c".into()), Value::Number(3.0)],
+        )
+        .unwrap();
+        call(&mut table, "Remove", &[Value::String("B".into())]).unwrap();
+        let keys: Vec<&str> = table.keys().map(|k| k.as_str()).collect();
+        assert_eq!(keys, vec!["a", "c"]);
+
+        assert!(call(&mut table, "Remove", &[]).is_err());
+        assert!(call_method(&mut table, "Frobnicate", &[]).is_none());
+    }
+
+    #[test]
+    fn test_get_enumerator_produces_key_value_pairs() {
+        let mut table = table();
+        let pairs = call_method(&mut table, "GetEnumerator", &[])
+            .unwrap()
+            .unwrap();
+        let Value::Array(pairs) = pairs else {
+            panic!("Expected array of pairs");
+        };
+        let pairs = pairs.read();
+        assert_eq!(pairs.len(), 2);
+        assert_eq!(
+            pairs[0].get_property("Key"),
+            Some(Value::String("a".into()))
+        );
+        assert_eq!(pairs[0].get_property("Value"), Some(Value::Number(1.0)));
+    }
+}
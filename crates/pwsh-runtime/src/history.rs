@@ -0,0 +1,228 @@
+/// Command history: the lines run at the prompt, for Get-History and `r`
+use crate::error::{ErrorCategory, ErrorRecord, RuntimeError};
+use crate::property_map::PropertyMap;
+use crate::value::Value;
+use chrono::NaiveDateTime;
+use std::collections::VecDeque;
+
+/// The type name of the objects Get-History writes
+pub const HISTORY_INFO_TYPE: &str = "Microsoft.PowerShell.Commands.HistoryInfo";
+
+/// How many lines the history keeps unless the configuration says otherwise
+const DEFAULT_CAPACITY: usize = 1000;
+
+/// A line run at the prompt
+#[derive(Debug, Clone, PartialEq)]
+pub struct HistoryEntry {
+    /// Counts from 1 for the session's first line, and goes on counting
+    /// when old lines are dropped
+    pub id: usize,
+    pub command_line: String,
+    /// Whether it ran without a terminating error
+    pub succeeded: bool,
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+}
+
+impl HistoryEntry {
+    /// The entry as Get-History writes it: its Id, CommandLine,
+    /// ExecutionStatus (`Completed` or `Failed`), StartExecutionTime and
+    /// EndExecutionTime
+    pub fn to_value(&self) -> Value {
+        let status = if self.succeeded {
+            "Completed"
+        } else {
+            "Failed"
+        };
+        let mut properties = PropertyMap::from([
+            ("Id", Value::Number(self.id as f64)),
+            (
+                "CommandLine",
+                Value::String(self.command_line.as_str().into()),
+            ),
+            ("ExecutionStatus", Value::String(status.into())),
+            ("StartExecutionTime", Value::DateTime(self.start)),
+            ("EndExecutionTime", Value::DateTime(self.end)),
+        ]);
+        properties.insert_type_name(HISTORY_INFO_TYPE);
+        Value::Object(properties.into())
+    }
+}
+
+/// The lines run at the prompt, oldest first, up to a capacity; the host
+/// adds each line it runs
+#[derive(Debug, Clone)]
+pub struct History {
+    entries: VecDeque<HistoryEntry>,
+    capacity: usize,
+    next_id: usize,
+    /// The line Invoke-History last ran again, which goes in the history in
+    /// place of the line typed at the prompt
+    recalled: Option<String>,
+    /// Whether Invoke-History is running a line now
+    recalling: bool,
+}
+
+impl Default for History {
+    fn default() -> Self {
+        History {
+            entries: VecDeque::new(),
+            capacity: DEFAULT_CAPACITY,
+            next_id: 1,
+            recalled: None,
+            recalling: false,
+        }
+    }
+}
+
+impl History {
+    /// Keep at most `capacity` lines, dropping the oldest past it
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity.max(1);
+        self.trim();
+    }
+
+    /// Add a line that was run, returning its id
+    pub fn add(
+        &mut self,
+        command_line: impl Into<String>,
+        succeeded: bool,
+        start: NaiveDateTime,
+        end: NaiveDateTime,
+    ) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.entries.push_back(HistoryEntry {
+            id,
+            command_line: command_line.into(),
+            succeeded,
+            start,
+            end,
+        });
+        self.trim();
+        id
+    }
+
+    fn trim(&mut self) {
+        while self.entries.len() > self.capacity {
+            self.entries.pop_front();
+        }
+    }
+
+    /// The lines kept, oldest first
+    pub fn entries(&self) -> impl DoubleEndedIterator<Item = &HistoryEntry> {
+        self.entries.iter()
+    }
+
+    /// The line with this id, if it is still kept
+    pub fn get(&self, id: usize) -> Option<&HistoryEntry> {
+        self.entries.iter().find(|entry| entry.id == id)
+    }
+
+    /// Note that Invoke-History is running `line` again, to add it to the
+    /// history instead of the line typed at the prompt. False while another
+    /// recalled line is running, as a line that recalls itself would never
+    /// end.
+    pub fn begin_recall(&mut self, line: &str) -> bool {
+        if self.recalling {
+            return false;
+        }
+        self.recalling = true;
+        self.recalled = Some(line.to_string());
+        true
+    }
+
+    /// The line begin_recall noted has finished running
+    pub fn end_recall(&mut self) {
+        self.recalling = false;
+    }
+
+    /// The line Invoke-History ran again since this was last asked, if any
+    pub fn take_recalled(&mut self) -> Option<String> {
+        self.recalled.take()
+    }
+
+    /// The line `r` runs again, as Invoke-History finds it: the last one
+    /// without `which`, the one with that id when it is a number, or else
+    /// the last one starting with it, ignoring case
+    pub fn recall(&self, which: Option<&str>) -> Result<&HistoryEntry, RuntimeError> {
+        let found = match which.map(str::trim) {
+            None | Some("") => self.entries.back(),
+            Some(text) => match text.parse::<usize>() {
+                Ok(id) => self.get(id),
+                Err(_) => {
+                    let prefix = text.to_lowercase();
+                    self.entries
+                        .iter()
+                        .rev()
+                        .find(|entry| entry.command_line.to_lowercase().starts_with(&prefix))
+                }
+            },
+        };
+        found.ok_or_else(|| {
+            let message = match which {
+                Some(which) => format!("Cannot locate history for commandline {}.", which.trim()),
+                None => "Cannot locate the most recent history.".to_string(),
+            };
+            ErrorRecord::new(
+                ErrorCategory::ObjectNotFound,
+                "InvokeHistoryNotFound,Invoke-History",
+                message,
+            )
+            .into()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn time(second: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 3, 1)
+            .unwrap()
+            .and_hms_opt(9, 0, second)
+            .unwrap()
+    }
+
+    fn history(lines: &[&str]) -> History {
+        let mut history = History::default();
+        for (i, line) in lines.iter().enumerate() {
+            history.add(*line, true, time(i as u32), time(i as u32 + 1));
+        }
+        history
+    }
+
+    #[test]
+    fn test_recall_finds_the_last_line_an_id_or_a_prefix() {
+        let history = history(&["Get-Date", "$x = 5", "Get-ChildItem", "$x * 2"]);
+        assert_eq!(history.recall(None).unwrap().command_line, "$x * 2");
+        assert_eq!(history.recall(Some("2")).unwrap().command_line, "$x = 5");
+        assert_eq!(
+            history.recall(Some("get")).unwrap().command_line,
+            "Get-ChildItem"
+        );
+        let error = history.recall(Some("9")).unwrap_err();
+        assert_eq!(error.error_id(), "InvokeHistoryNotFound,Invoke-History");
+        assert!(History::default().recall(None).is_err());
+    }
+
+    #[test]
+    fn test_history_drops_the_oldest_lines_past_its_capacity_but_keeps_ids() {
+        let mut history = history(&["a", "b", "c"]);
+        history.set_capacity(2);
+        let ids: Vec<usize> = history.entries().map(|entry| entry.id).collect();
+        assert_eq!(ids, vec![2, 3]);
+        assert_eq!(history.add("d", false, time(5), time(6)), 4);
+        assert!(history.get(2).is_none());
+        assert_eq!(
+            history
+                .get(4)
+                .unwrap()
+                .to_value()
+                .get_property("ExecutionStatus"),
+            Some(Value::String("Failed".into()))
+        );
+    }
+}
@@ -0,0 +1,198 @@
+/// Host interface: where the interpreter writes text and asks for input
+///
+/// The evaluator never prints directly. A REPL uses the console host; an
+/// application embedding the interpreter supplies its own to capture output.
+use std::io::{BufRead, IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Cells in the bar a terminal shows for progress
+const PROGRESS_BAR_WIDTH: usize = 20;
+
+/// State of a long-running operation, reported by Write-Progress
+#[derive(Debug, Clone, PartialEq)]
+pub struct Progress {
+    pub activity: String,
+    pub status: String,
+    /// 0 to 100, when known
+    pub percent_complete: Option<u32>,
+    /// The operation finished; hosts showing progress can remove it
+    pub completed: bool,
+}
+
+impl Progress {
+    /// The progress as one line of text, with a bar when the percentage is
+    /// known: `Copying: file.txt [========            ] 40%`
+    pub fn render(&self) -> String {
+        let text = format!("{}: {}", self.activity, self.status);
+        match self.percent_complete {
+            Some(percent) => {
+                let filled = PROGRESS_BAR_WIDTH * percent.min(100) as usize / 100;
+                format!(
+                    "{} [{}{}] {}%",
+                    text,
+                    "=".repeat(filled),
+                    " ".repeat(PROGRESS_BAR_WIDTH - filled),
+                    percent
+                )
+            }
+            None => text,
+        }
+    }
+}
+
+/// Receives text the interpreter writes and answers its prompts
+pub trait Host {
+    /// Write text to the output stream as is
+    fn write(&mut self, text: &str);
+
+    /// Write a line to the output stream
+    fn write_line(&mut self, text: &str) {
+        self.write(text);
+        self.write("\n");
+    }
+
+    /// Write a line to the error stream
+    fn write_error_line(&mut self, text: &str);
+
+    /// Show the output that reached the end of a pipeline, formatted into
+    /// lines by Out-Default; by default each line goes to `write_line`.
+    /// Hosts override this to capture, page or restyle the console's output.
+    fn write_output_lines(&mut self, lines: &[String]) {
+        for line in lines {
+            self.write_line(line);
+        }
+    }
+
+    /// Whether the host shows ANSI escape sequences as styling, so
+    /// `$OutputRendering = 'Host'` keeps them; by default it doesn't
+    fn supports_ansi(&self) -> bool {
+        false
+    }
+
+    /// Write a warning; by default it goes to the error stream
+    fn write_warning_line(&mut self, text: &str) {
+        self.write_error_line(&format!("WARNING: {}", text));
+    }
+
+    /// Write a debug message, such as a Set-PSDebug trace line; by default
+    /// it goes to the error stream
+    fn write_debug_line(&mut self, text: &str) {
+        self.write_error_line(&format!("DEBUG: {}", text));
+    }
+
+    /// Report progress of a long-running operation; ignored by default
+    fn write_progress(&mut self, _progress: &Progress) {}
+
+    /// Show a prompt and read a line of input; None when no input is available
+    fn read_line(&mut self, prompt: &str) -> Option<String>;
+
+    /// The console's width and height in characters; None (the default)
+    /// for hosts without a window, such as one capturing output
+    fn window_size(&self) -> Option<(usize, usize)> {
+        None
+    }
+
+    /// Show a prompt and read a single key, Enter being `'\n'`; None when
+    /// no input is available. By default this reads a line and takes its
+    /// first character.
+    fn read_key(&mut self, prompt: &str) -> Option<char> {
+        self.read_line(prompt)
+            .map(|line| line.chars().next().unwrap_or('\n'))
+    }
+}
+
+/// Whether a progress line is showing on the terminal. There is one
+/// terminal, so every console host shares it.
+static PROGRESS_SHOWN: AtomicBool = AtomicBool::new(false);
+
+/// Host for a terminal: stdout, stderr and stdin
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ConsoleHost;
+
+impl ConsoleHost {
+    /// Erase the progress line, if one is showing, so the next text starts
+    /// on a clean line
+    pub fn clear_progress() {
+        if PROGRESS_SHOWN.swap(false, Ordering::Relaxed) {
+            eprint!("\r\x1b[2K");
+            let _ = std::io::stderr().flush();
+        }
+    }
+}
+
+impl Host for ConsoleHost {
+    fn write(&mut self, text: &str) {
+        Self::clear_progress();
+        print!("{}", text);
+        let _ = std::io::stdout().flush();
+    }
+
+    fn write_line(&mut self, text: &str) {
+        Self::clear_progress();
+        println!("{}", text);
+    }
+
+    fn write_error_line(&mut self, text: &str) {
+        Self::clear_progress();
+        eprintln!("{}", text);
+    }
+
+    /// Styling shows on a terminal, and not when stdout is redirected
+    fn supports_ansi(&self) -> bool {
+        std::io::stdout().is_terminal()
+    }
+
+    /// Progress is drawn over one line of stderr, redrawn on each update and
+    /// erased once the operation completes. When stderr is not a terminal
+    /// (redirected to a file or a pipe) progress is not shown at all.
+    fn write_progress(&mut self, progress: &Progress) {
+        if !std::io::stderr().is_terminal() {
+            return;
+        }
+        if progress.completed {
+            Self::clear_progress();
+            return;
+        }
+        eprint!("\r\x1b[2K{}", progress.render());
+        let _ = std::io::stderr().flush();
+        PROGRESS_SHOWN.store(true, Ordering::Relaxed);
+    }
+
+    fn read_line(&mut self, prompt: &str) -> Option<String> {
+        Self::clear_progress();
+        print!("{}", prompt);
+        std::io::stdout().flush().ok()?;
+        let mut line = String::new();
+        match std::io::stdin().lock().read_line(&mut line) {
+            Ok(0) | Err(_) => None,
+            Ok(_) => Some(line.trim_end_matches(['\r', '\n']).to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn progress(percent_complete: Option<u32>) -> Progress {
+        Progress {
+            activity: "Copying".to_string(),
+            status: "file.txt".to_string(),
+            percent_complete,
+            completed: false,
+        }
+    }
+
+    #[test]
+    fn test_progress_renders_as_a_bar() {
+        assert_eq!(
+            progress(Some(40)).render(),
+            "Copying: file.txt [========            ] 40%"
+        );
+        assert_eq!(
+            progress(Some(100)).render(),
+            "Copying: file.txt [====================] 100%"
+        );
+        assert_eq!(progress(None).render(), "Copying: file.txt");
+    }
+}
@@ -0,0 +1,38 @@
+/// Instrumentation: callbacks the evaluator makes as scripts run, for hosts that profile, audit or meter them
+use crate::callstack::CallFrame;
+use crate::cmdlet::CmdletContext;
+use crate::error::RuntimeError;
+use crate::value::Value;
+
+/// What a host hears about a running script. Every callback does nothing
+/// by default, so a host implements just the ones it needs and installs
+/// them with `SessionBuilder::instrumentation`.
+///
+/// A callback that returns an error fails the statement or command with
+/// it, as if the statement or command had failed itself; a host metering
+/// scripts can end one that has used up its budget this way.
+pub trait Instrumentation {
+    /// A statement is about to run. `frame` is the innermost call, at the
+    /// statement: its command, the script it came from and the statement's
+    /// position.
+    fn on_statement_start(&mut self, _frame: &CallFrame) -> Result<(), RuntimeError> {
+        Ok(())
+    }
+
+    /// The cmdlet `name` is about to run, with the parameters, arguments
+    /// and pipeline input in `context`. Functions and programs aren't
+    /// cmdlets; a function's statements reach `on_statement_start`.
+    fn on_cmdlet_invoke(
+        &mut self,
+        _name: &str,
+        _context: &CmdletContext,
+    ) -> Result<(), RuntimeError> {
+        Ok(())
+    }
+
+    /// An error went into `$error`: one that ended a script, one a catch
+    /// block caught, or a non-terminating one a cmdlet wrote. `record` is
+    /// the error as scripts see it, with its InvocationInfo and
+    /// ScriptStackTrace.
+    fn on_error(&mut self, _error: &RuntimeError, _record: &Value) {}
+}
@@ -0,0 +1,255 @@
+/// Mapping between runtime values and JSON, shared by hosts and JSON cmdlets
+///
+/// Objects keep their property order. Whole numbers become JSON integers,
+/// dates ISO 8601 strings, enum values their number, and values JSON can't
+/// represent (NaN, functions, script blocks) their display text.
+use crate::error::RuntimeError;
+use crate::property_map::PropertyMap;
+use crate::value::Value;
+use serde_json::{Map, Number, Value as Json};
+
+impl From<Json> for Value {
+    fn from(json: Json) -> Self {
+        match json {
+            Json::Null => Value::Null,
+            Json::Bool(b) => Value::Boolean(b),
+            Json::Number(n) => Value::Number(n.as_f64().unwrap_or(f64::NAN)),
+            Json::String(s) => Value::String(s.into()),
+            Json::Array(items) => Value::Array(
+                items
+                    .into_iter()
+                    .map(Value::from)
+                    .collect::<Vec<_>>()
+                    .into(),
+            ),
+            Json::Object(map) => Value::Object(
+                map.into_iter()
+                    .map(|(key, value)| (key, Value::from(value)))
+                    .collect::<PropertyMap>()
+                    .into(),
+            ),
+        }
+    }
+}
+
+impl From<&Value> for Json {
+    fn from(value: &Value) -> Self {
+        match value {
+            Value::Null => Json::Null,
+            Value::Boolean(b) => Json::Bool(*b),
+            Value::Number(n) => number(*n),
+            Value::String(s) => Json::String(s.to_string()),
+            Value::Array(items) => Json::Array(items.read().iter().map(Json::from).collect()),
+            Value::Object(_) | Value::Instance { .. } => Json::Object(
+                value
+                    .properties()
+                    .iter()
+                    .flat_map(|properties| properties.iter())
+                    .map(|(key, value)| (key.to_string(), Json::from(value)))
+                    .collect::<Map<_, _>>(),
+            ),
+            Value::DateTime(dt) => Json::String(dt.format("%Y-%m-%dT%H:%M:%S%.f").to_string()),
+            Value::EnumValue { value, .. } => Json::from(*value),
+            Value::Function(_) | Value::ScriptBlock(_) => Json::String(value.to_string()),
+        }
+    }
+}
+
+impl From<Value> for Json {
+    fn from(value: Value) -> Self {
+        Json::from(&value)
+    }
+}
+
+fn number(n: f64) -> Json {
+    if n.is_nan() {
+        Json::String("NaN".to_string())
+    } else if n.is_infinite() {
+        Json::String(if n > 0.0 { "Infinity" } else { "-Infinity" }.to_string())
+    } else if n.fract() == 0.0 && n.abs() < i64::MAX as f64 {
+        Json::from(n as i64)
+    } else {
+        Number::from_f64(n).map_or(Json::Null, Json::Number)
+    }
+}
+
+/// How ConvertTo-Json writes a value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JsonOptions {
+    /// How many levels of nested objects and arrays to write; deeper ones
+    /// are written as their display text
+    pub depth: usize,
+    /// Write enum values as their names rather than their numbers
+    pub enums_as_strings: bool,
+    /// Leave out the indentation and line breaks
+    pub compress: bool,
+}
+
+impl Default for JsonOptions {
+    /// PowerShell's defaults: two levels, enums as numbers, indented
+    fn default() -> Self {
+        JsonOptions {
+            depth: 2,
+            enums_as_strings: false,
+            compress: false,
+        }
+    }
+}
+
+/// `value` as JSON, at nesting level `level`; sets `truncated` if a value
+/// was too deep to write
+fn to_json_at(value: &Value, options: &JsonOptions, level: usize, truncated: &mut bool) -> Json {
+    match value {
+        Value::Array(_) | Value::Object(_) | Value::Instance { .. } if level > options.depth => {
+            *truncated = true;
+            Json::String(value.to_string())
+        }
+        Value::Array(items) => Json::Array(
+            items
+                .read()
+                .iter()
+                .map(|item| to_json_at(item, options, level + 1, truncated))
+                .collect(),
+        ),
+        Value::Object(_) | Value::Instance { .. } => Json::Object(
+            value
+                .properties()
+                .iter()
+                .flat_map(|properties| properties.iter())
+                .map(|(key, value)| {
+                    let value = to_json_at(value, options, level + 1, truncated);
+                    (key.to_string(), value)
+                })
+                .collect::<Map<_, _>>(),
+        ),
+        Value::EnumValue { name, .. } if options.enums_as_strings => Json::String(name.clone()),
+        other => Json::from(other),
+    }
+}
+
+impl Value {
+    /// Serialize as JSON text the way ConvertTo-Json does, keeping property
+    /// order. Also says whether values nested deeper than `options.depth`
+    /// were cut short.
+    pub fn to_json_with(&self, options: &JsonOptions) -> (String, bool) {
+        let mut truncated = false;
+        let json = to_json_at(self, options, 0, &mut truncated);
+        let text = if options.compress {
+            json.to_string()
+        } else {
+            // serde_json's pretty printer indents by two spaces and writes
+            // `"Name": value`, as PowerShell 7 does
+            serde_json::to_string_pretty(&json).unwrap_or_else(|_| json.to_string())
+        };
+        (text, truncated)
+    }
+
+    /// Serialize as compact JSON text
+    pub fn to_json(&self) -> String {
+        Json::from(self).to_string()
+    }
+
+    /// Parse JSON text
+    pub fn from_json(text: &str) -> Result<Value, RuntimeError> {
+        serde_json::from_str::<Json>(text)
+            .map(Value::from)
+            .map_err(|e| RuntimeError::InvalidOperation(format!("Invalid JSON: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_round_trip_keeps_structure_and_order() {
+        let text = r#"{"Name":"a","Tags":["x",null,true],"Size":{"W":2,"H":1.5},"Empty":[]}"#;
+        let value = Value::from_json(text).unwrap();
+
+        assert_eq!(
+            value.get_property("Tags"),
+            Some(Value::Array(
+                vec![Value::String("x".into()), Value::Null, Value::Boolean(true),].into()
+            ))
+        );
+        assert_eq!(
+            value.get_property("Size").unwrap().get_property("W"),
+            Some(Value::Number(2.0))
+        );
+        assert_eq!(value.to_json(), text);
+    }
+
+    #[test]
+    fn test_values_without_a_json_type() {
+        let date = chrono::NaiveDate::from_ymd_opt(2024, 3, 5)
+            .unwrap()
+            .and_hms_opt(14, 7, 9)
+            .unwrap();
+        let value = Value::Array(
+            vec![
+                Value::DateTime(date),
+                Value::EnumValue {
+                    type_name: "Color".to_string(),
+                    name: "Green".to_string(),
+                    value: 5,
+                },
+                Value::Number(f64::NAN),
+                Value::Number(f64::NEG_INFINITY),
+                Value::Number(-0.25),
+                Value::Instance {
+                    class_name: "Point".to_string(),
+                    properties: PropertyMap::from([("X".to_string(), Value::Number(1.0))]).into(),
+                },
+            ]
+            .into(),
+        );
+
+        assert_eq!(
+            value.to_json(),
+            r#"["2024-03-05T14:07:09",5,"NaN","-Infinity",-0.25,{"X":1}]"#
+        );
+    }
+
+    #[test]
+    fn test_to_json_with_depth_and_enum_names() {
+        let value = Value::from_json(r#"{"A":{"B":{"C":1}},"D":[1,[2]]}"#).unwrap();
+        let options = JsonOptions {
+            depth: 1,
+            compress: true,
+            ..JsonOptions::default()
+        };
+        assert_eq!(
+            value.to_json_with(&options),
+            (r#"{"A":{"B":"@{C=1}"},"D":[1,"@(2)"]}"#.to_string(), true)
+        );
+        let options = JsonOptions::default();
+        assert_eq!(
+            value.to_json_with(&options),
+            (
+                "{\n  \"A\": {\n    \"B\": {\n      \"C\": 1\n    }\n  },\n  \"D\": [\n    1,\n    [\n      2\n    ]\n  ]\n}"
+                    .to_string(),
+                false
+            )
+        );
+
+        let color = Value::EnumValue {
+            type_name: "Color".to_string(),
+            name: "Green".to_string(),
+            value: 5,
+        };
+        let options = JsonOptions {
+            enums_as_strings: true,
+            ..JsonOptions::default()
+        };
+        assert_eq!(color.to_json_with(&options).0, "\"Green\"");
+    }
+
+    #[test]
+    fn test_invalid_json_is_an_error() {
+        assert!(matches!(
+            Value::from_json("{\"a\": "),
+            Err(RuntimeError::InvalidOperation(message)) if message.starts_with("Invalid JSON")
+        ));
+        assert_eq!(Value::from_json("null").unwrap(), Value::Null);
+    }
+}
@@ -0,0 +1,378 @@
+/// Language modes: which parts of the language scripts may use
+use crate::error::{ErrorCategory, ErrorRecord, RuntimeError};
+use pwsh_parser::{
+    Argument, Block, Expression, ExpressionKind, Literal, Span, Statement, StatementKind,
+    StringPart,
+};
+use std::fmt;
+
+/// Cmdlets scripts may not run outside FullLanguage: they change how the
+/// session runs code, which a constrained script has no say in
+pub const CONSTRAINED_COMMANDS: [&str; 4] = [
+    "Set-ExecutionPolicy",
+    "Set-PSBreakpoint",
+    "Set-PSDebug",
+    "Update-FormatData",
+];
+
+/// How much of the language scripts may use, as in PowerShell. Only the
+/// host sets it; nothing a script runs can change it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LanguageMode {
+    /// All of it. The default.
+    #[default]
+    FullLanguage,
+    /// No type casts or type literals, no class or enum definitions, and
+    /// script blocks only as arguments to commands, such as ForEach-Object
+    /// { ... }; the cmdlets in `CONSTRAINED_COMMANDS` don't run
+    ConstrainedLanguage,
+    /// Only commands with literal arguments: no variables, operators,
+    /// script blocks or definitions
+    NoLanguage,
+}
+
+impl LanguageMode {
+    /// Every mode, for looking one up by name
+    pub const ALL: [LanguageMode; 3] = [
+        LanguageMode::FullLanguage,
+        LanguageMode::ConstrainedLanguage,
+        LanguageMode::NoLanguage,
+    ];
+
+    /// The mode with this name, ignoring case: `constrainedlanguage`
+    pub fn from_name(name: &str) -> Option<LanguageMode> {
+        LanguageMode::ALL
+            .into_iter()
+            .find(|mode| mode.to_string().eq_ignore_ascii_case(name))
+    }
+
+    /// The first part of `statements` the mode doesn't allow, with where
+    /// it is and the error for it
+    pub fn violation(self, statements: &[Statement]) -> Option<(Span, RuntimeError)> {
+        let checked = match self {
+            LanguageMode::FullLanguage => Ok(()),
+            LanguageMode::ConstrainedLanguage => statements.iter().try_for_each(constrained),
+            LanguageMode::NoLanguage => statements.iter().try_for_each(commands_only),
+        };
+        checked
+            .err()
+            .map(|(span, refusal)| (span, refusal.into_error()))
+    }
+
+    /// Whether the mode lets scripts run the cmdlet `name`
+    pub fn allows_command(self, name: &str) -> bool {
+        self == LanguageMode::FullLanguage
+            || !CONSTRAINED_COMMANDS
+                .iter()
+                .any(|command| command.eq_ignore_ascii_case(name))
+    }
+
+    /// The error for running the cmdlet `name` when the mode doesn't allow it
+    pub(crate) fn command_error(self, name: &str) -> RuntimeError {
+        ErrorRecord::new(
+            ErrorCategory::SecurityError,
+            "CommandNotAllowed",
+            format!("The command '{}' cannot run in {} mode.", name, self),
+        )
+        .with_target(name)
+        .into()
+    }
+}
+
+impl fmt::Display for LanguageMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// Why a part of a script isn't allowed
+enum Refusal {
+    /// A cast or type literal naming the type
+    Type(String),
+    TypeDefinition,
+    ScriptBlock,
+    /// Anything but a command in NoLanguage mode
+    NotCommand,
+}
+
+impl Refusal {
+    fn into_error(self) -> RuntimeError {
+        let (error_id, message) = match self {
+            Refusal::Type(type_name) => (
+                "ConversionNotAllowed",
+                format!(
+                    "Cannot use the type [{}]: types cannot be named in ConstrainedLanguage mode.",
+                    type_name
+                ),
+            ),
+            Refusal::TypeDefinition => (
+                "TypeDefinitionNotAllowed",
+                "Classes and enums cannot be defined in ConstrainedLanguage mode.".to_string(),
+            ),
+            Refusal::ScriptBlock => (
+                "ScriptBlockNotAllowed",
+                "Script blocks can only be passed to commands in ConstrainedLanguage mode."
+                    .to_string(),
+            ),
+            Refusal::NotCommand => (
+                "ScriptsNotAllowed",
+                "The syntax is not supported by this runspace. This can occur if the runspace is in no-language mode.".to_string(),
+            ),
+        };
+        ErrorRecord::new(ErrorCategory::SecurityError, error_id, message).into()
+    }
+}
+
+type Checked = Result<(), (Span, Refusal)>;
+
+fn constrained_block(block: &Block) -> Checked {
+    block.statements.iter().try_for_each(constrained)
+}
+
+fn constrained(statement: &Statement) -> Checked {
+    let expressions: Vec<&Expression> = match &statement.kind {
+        StatementKind::Expression(value)
+        | StatementKind::Assignment { value, .. }
+        | StatementKind::MultipleAssignment { value, .. } => vec![value],
+        StatementKind::TargetAssignment { target, value } => vec![target, value],
+        StatementKind::FunctionDef {
+            parameters,
+            body,
+            begin,
+            process,
+            ..
+        } => {
+            for block in [Some(body), begin.as_ref(), process.as_ref()]
+                .into_iter()
+                .flatten()
+            {
+                constrained_block(block)?;
+            }
+            parameters
+                .iter()
+                .filter_map(|parameter| parameter.default_value.as_ref())
+                .collect()
+        }
+        StatementKind::If {
+            condition,
+            then_branch,
+            else_branch,
+        } => {
+            constrained_block(then_branch)?;
+            if let Some(block) = else_branch {
+                constrained_block(block)?;
+            }
+            vec![condition]
+        }
+        StatementKind::Foreach {
+            collection, body, ..
+        } => {
+            constrained_block(body)?;
+            vec![collection]
+        }
+        StatementKind::Try {
+            body,
+            catch,
+            finally,
+        } => {
+            for block in [Some(body), catch.as_ref(), finally.as_ref()]
+                .into_iter()
+                .flatten()
+            {
+                constrained_block(block)?;
+            }
+            Vec::new()
+        }
+        StatementKind::ClassDef(_) | StatementKind::EnumDef(_) => {
+            return Err((statement.span, Refusal::TypeDefinition))
+        }
+        StatementKind::Return(value) | StatementKind::Throw(value) => value.iter().collect(),
+        StatementKind::Break | StatementKind::Continue => Vec::new(),
+        StatementKind::Pipeline(pipeline) => pipeline.stages.iter().collect(),
+    };
+    expressions.into_iter().try_for_each(constrained_expression)
+}
+
+fn constrained_expression(expression: &Expression) -> Checked {
+    match &expression.kind {
+        ExpressionKind::Literal(_) | ExpressionKind::Variable(_) => Ok(()),
+        ExpressionKind::Cast { type_name, .. } | ExpressionKind::TypeLiteral(type_name) => {
+            Err((expression.span, Refusal::Type(type_name.clone())))
+        }
+        ExpressionKind::ScriptBlock(_) => Err((expression.span, Refusal::ScriptBlock)),
+        ExpressionKind::BinaryOp { left, right, .. } => {
+            constrained_expression(left)?;
+            constrained_expression(right)
+        }
+        ExpressionKind::UnaryOp { operand, .. } => constrained_expression(operand),
+        ExpressionKind::Call { arguments, .. } => arguments.iter().try_for_each(|argument| {
+            match argument {
+                Argument::Positional(value) | Argument::Named { value, .. } => match &value.kind {
+                    // A command may take a script block
+                    ExpressionKind::ScriptBlock(block) => constrained_block(block),
+                    _ => constrained_expression(value),
+                },
+                Argument::StopParsing { .. } => Ok(()),
+            }
+        }),
+        ExpressionKind::Invoke { command, arguments } => {
+            constrained_expression(command)?;
+            arguments.iter().try_for_each(|argument| match argument {
+                Argument::Positional(value) | Argument::Named { value, .. } => {
+                    constrained_expression(value)
+                }
+                Argument::StopParsing { .. } => Ok(()),
+            })
+        }
+        ExpressionKind::MemberAccess { object, .. } => constrained_expression(object),
+        ExpressionKind::Index { object, index } => {
+            constrained_expression(object)?;
+            constrained_expression(index)
+        }
+        ExpressionKind::MethodCall {
+            object, arguments, ..
+        } => {
+            constrained_expression(object)?;
+            arguments.iter().try_for_each(constrained_expression)
+        }
+        ExpressionKind::StaticMember { .. } => Ok(()),
+        ExpressionKind::StaticMethodCall { arguments, .. } => {
+            arguments.iter().try_for_each(constrained_expression)
+        }
+        ExpressionKind::Hashtable { entries, .. } => entries
+            .iter()
+            .try_for_each(|(_, value)| constrained_expression(value)),
+        ExpressionKind::Array(items) => items.iter().try_for_each(constrained_expression),
+        ExpressionKind::Pipeline(pipeline) => {
+            pipeline.stages.iter().try_for_each(constrained_expression)
+        }
+    }
+}
+
+fn no_language(span: Span) -> Checked {
+    Err((span, Refusal::NotCommand))
+}
+
+/// A statement in NoLanguage mode: commands piped into each other
+fn commands_only(statement: &Statement) -> Checked {
+    let stages: Vec<&Expression> = match &statement.kind {
+        StatementKind::Expression(expression) => match &expression.kind {
+            ExpressionKind::Pipeline(pipeline) => pipeline.stages.iter().collect(),
+            _ => vec![expression],
+        },
+        StatementKind::Pipeline(pipeline) => pipeline.stages.iter().collect(),
+        _ => return no_language(statement.span),
+    };
+    stages.into_iter().try_for_each(|stage| match &stage.kind {
+        ExpressionKind::Call { arguments, .. } => {
+            arguments.iter().try_for_each(|argument| match argument {
+                Argument::Positional(value) | Argument::Named { value, .. } => literal(value),
+                Argument::StopParsing { .. } => Ok(()),
+            })
+        }
+        _ => no_language(stage.span),
+    })
+}
+
+/// An argument in NoLanguage mode: a literal, or an array or hashtable of them
+fn literal(expression: &Expression) -> Checked {
+    match &expression.kind {
+        ExpressionKind::Literal(Literal::InterpolatedString(parts))
+            if parts
+                .iter()
+                .any(|part| matches!(part, StringPart::Variable(_))) =>
+        {
+            no_language(expression.span)
+        }
+        ExpressionKind::Literal(_) => Ok(()),
+        ExpressionKind::Array(items) => items.iter().try_for_each(literal),
+        ExpressionKind::Hashtable { entries, .. } => {
+            entries.iter().try_for_each(|(_, value)| literal(value))
+        }
+        _ => no_language(expression.span),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pwsh_lexer::Lexer;
+    use pwsh_parser::Parser;
+
+    fn check(mode: LanguageMode, source: &str) -> Result<(), String> {
+        let program = Parser::new(Lexer::new(source).tokenize().unwrap())
+            .parse()
+            .unwrap();
+        match mode.violation(&program.statements) {
+            Some((_, error)) => Err(error.error_id().to_string()),
+            None => Ok(()),
+        }
+    }
+
+    #[test]
+    fn test_constrained_language_refuses_types_and_loose_script_blocks() {
+        let mode = LanguageMode::ConstrainedLanguage;
+        assert_eq!(
+            check(
+                mode,
+                "$n = 3; if ($n -gt 2) { Get-Thing -Name 'a' | ForEach-Object { $_ } }"
+            ),
+            Ok(())
+        );
+        assert_eq!(
+            check(mode, "function Get-Size { [int]'3' }"),
+            Err("ConversionNotAllowed".to_string())
+        );
+        assert_eq!(
+            check(mode, "$x -is [string]"),
+            Err("ConversionNotAllowed".to_string())
+        );
+        assert_eq!(
+            check(mode, "$run = { 'hi' }"),
+            Err("ScriptBlockNotAllowed".to_string())
+        );
+        assert_eq!(
+            check(mode, "Get-Thing | Where-Object { $sb = { 1 } }"),
+            Err("ScriptBlockNotAllowed".to_string())
+        );
+        assert_eq!(
+            check(mode, "enum Color { Red }"),
+            Err("TypeDefinitionNotAllowed".to_string())
+        );
+        assert_eq!(check(LanguageMode::FullLanguage, "[int]'3'"), Ok(()));
+    }
+
+    #[test]
+    fn test_no_language_allows_only_commands_with_literal_arguments() {
+        let mode = LanguageMode::NoLanguage;
+        assert_eq!(
+            check(mode, "Get-Thing -Name 'a', 'b' -Force | Sort-Object Name"),
+            Ok(())
+        );
+        for source in [
+            "$x = 1",
+            "Get-Thing -Name $name",
+            "Get-Thing \"$name\"",
+            "1 + 1",
+            "Get-Thing | ForEach-Object { $_ }",
+        ] {
+            assert_eq!(
+                check(mode, source),
+                Err("ScriptsNotAllowed".to_string()),
+                "{}",
+                source
+            );
+        }
+    }
+
+    #[test]
+    fn test_constrained_commands_are_refused_outside_full_language() {
+        assert!(LanguageMode::FullLanguage.allows_command("Set-PSDebug"));
+        assert!(!LanguageMode::ConstrainedLanguage.allows_command("set-psdebug"));
+        assert!(LanguageMode::NoLanguage.allows_command("Get-ChildItem"));
+        assert_eq!(
+            LanguageMode::from_name("nolanguage"),
+            Some(LanguageMode::NoLanguage)
+        );
+    }
+}
@@ -2,17 +2,89 @@
 ///
 /// This module provides the runtime evaluation engine for PowerShell,
 /// including value representation, scope management, and expression/statement evaluation.
+mod ansi;
+mod builtin_types;
+mod callstack;
+mod clock;
 mod cmdlet;
+mod compile;
+mod completion;
+mod config;
+mod convert;
+mod culture;
+mod debugger;
+mod display;
 mod error;
 mod evaluator;
+mod format;
+mod hashtable;
+mod history;
+mod host;
+mod instrument;
+mod json;
+mod language;
+mod limits;
+mod matching;
+mod module_path;
+mod native;
+mod pager;
 mod pipeline;
+mod plugin;
+mod policy;
+mod property_map;
+mod psstyle;
+mod random;
 mod scope;
+mod session;
+mod suggest;
+mod types;
 mod value;
+mod version;
+mod views;
+mod wildcard;
 
 // Public API
-pub use cmdlet::{Cmdlet, CmdletContext, CmdletRegistry};
-pub use error::RuntimeError;
+pub use ansi::{strip_ansi, OutputRendering, NO_COLOR_VARIABLE};
+pub use callstack::{
+    stack_trace, CallFrame, ScriptSource, CALL_STACK_FRAME_TYPE, SCRIPT_BLOCK_COMMAND,
+};
+pub use clock::{Clock, FixedClock, SystemClock};
+pub use cmdlet::{
+    parse_property_list, parse_switch, select_properties, unroll_to_items, Cmdlet, CmdletContext,
+    CmdletRegistry, COMMON_PARAMETERS,
+};
+pub use completion::{Completion, CompletionEngine, CompletionKind};
+pub use config::{
+    parse_strict_mode, strict_mode_name, ColorScheme, Configuration, CONFIG_FILE_NAME,
+    CONSOLE_COLORS, LATEST_STRICT_MODE,
+};
+pub use convert::parse_number;
+pub use culture::Culture;
+pub use debugger::{
+    resolve_script, Breakpoint, BreakpointKind, Debugger, VariableAccess, BREAKPOINT_TYPE,
+};
+pub use display::DisplayLimits;
+pub use error::{ActionPreference, ErrorCategory, ErrorRecord, RuntimeError};
 pub use evaluator::{EvalResult, Evaluator};
+pub use history::{History, HistoryEntry, HISTORY_INFO_TYPE};
+pub use host::{ConsoleHost, Host, Progress};
+pub use instrument::Instrumentation;
+pub use json::JsonOptions;
+pub use language::{LanguageMode, CONSTRAINED_COMMANDS};
+pub use limits::{value_count, Capability, Limit, Limits};
+pub use module_path::MODULE_PATH_VARIABLE;
 pub use pipeline::PipelineExecutor;
+pub use plugin::CmdletPack;
+pub use policy::{ExecutionPolicy, LocalScripts, ScriptTrust};
+pub use property_map::{PropertyMap, PropertyName};
+pub use psstyle::{console_color, styled, FileInfoStyle, FormattingStyle};
+pub use random::Random;
 pub use scope::{Scope, ScopeStack};
-pub use value::{Function, ScriptBlock, Value};
+pub use session::{Session, SessionBuilder, SessionError};
+pub use value::{Function, ScriptBlock, Shared, Value};
+pub use version::{POWERSHELL_EDITION, POWERSHELL_VERSION};
+pub use views::{
+    views_from_json, Alignment, ColumnValue, FormatContext, FormatRegistry, ListView, TableColumn,
+    TableView, View,
+};
+pub use wildcard::WildcardPattern;
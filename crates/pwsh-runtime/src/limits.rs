@@ -0,0 +1,213 @@
+/// Sandbox limits: how long a script may run, how much it may output and hold, and what it may reach
+use crate::error::{ErrorCategory, ErrorRecord, RuntimeError};
+use crate::value::Value;
+use crate::wildcard::WildcardPattern;
+use std::fmt;
+use std::time::Duration;
+
+/// How many statements run between counts of the values variables hold
+pub(crate) const VALUE_COUNT_INTERVAL: usize = 1000;
+
+/// How many characters of a string count as one value
+const CHARACTERS_PER_VALUE: usize = 64;
+
+/// Something outside the interpreter a cmdlet reaches, which a sandbox
+/// can deny
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Capability {
+    /// Reading or changing files and directories
+    FileSystem,
+    /// Talking to other computers
+    Network,
+    /// Looking at or starting processes. A program can reach anything, so
+    /// running one needs every capability.
+    Process,
+}
+
+impl Capability {
+    pub const ALL: [Capability; 3] = [
+        Capability::FileSystem,
+        Capability::Network,
+        Capability::Process,
+    ];
+
+    /// The capability with this name, ignoring case: `filesystem`
+    pub fn from_name(name: &str) -> Option<Capability> {
+        Capability::ALL
+            .into_iter()
+            .find(|capability| capability.to_string().eq_ignore_ascii_case(name))
+    }
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// A limit a script ran into, with the most it allows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    /// How long each script may run
+    Duration(Duration),
+    /// How many objects each script may output
+    Output(usize),
+    /// Roughly how much memory the session's variables may take, counted
+    /// in values (see `value_count`)
+    Values(usize),
+}
+
+impl Limit {
+    /// The FullyQualifiedErrorId of the error for reaching the limit
+    pub fn error_id(self) -> &'static str {
+        match self {
+            Limit::Duration(_) => "ScriptTimeLimitExceeded",
+            Limit::Output(_) => "OutputLimitExceeded",
+            Limit::Values(_) => "MemoryLimitExceeded",
+        }
+    }
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Limit::Duration(duration) => write!(
+                f,
+                "The script was stopped after running for {:?}, as long as it is allowed to.",
+                duration
+            ),
+            Limit::Output(count) => write!(
+                f,
+                "The script was stopped for writing more than the {} objects it is allowed to.",
+                count
+            ),
+            Limit::Values(count) => write!(
+                f,
+                "The script was stopped for holding more than the {} values it is allowed to.",
+                count
+            ),
+        }
+    }
+}
+
+/// What the host lets scripts do, for running code it doesn't trust.
+/// Nothing is limited by default. Reaching a time, output or memory limit
+/// stops the script with `RuntimeError::LimitExceeded`, which try/catch
+/// doesn't catch; a denied command fails as a `CommandDenied` error.
+#[derive(Debug, Clone, Default)]
+pub struct Limits {
+    /// How long each script may run
+    pub max_duration: Option<Duration>,
+    /// How many objects each script may output
+    pub max_output: Option<usize>,
+    /// How many values the session's variables may hold, as `value_count`
+    /// counts them; checked every `VALUE_COUNT_INTERVAL` statements and
+    /// after each statement of the script
+    pub max_values: Option<usize>,
+    /// Wildcard patterns of cmdlets and programs scripts may not run
+    pub denied_commands: Vec<String>,
+    /// What cmdlets scripts run may not reach
+    pub denied_capabilities: Vec<Capability>,
+}
+
+impl Limits {
+    /// Whether statements need checking as they run: for time or memory
+    pub(crate) fn checks_statements(&self) -> bool {
+        self.max_duration.is_some() || self.max_values.is_some()
+    }
+
+    /// The error for running the command `name`, which reaches
+    /// `capabilities`, when the limits deny it
+    pub(crate) fn check_command(
+        &self,
+        name: &str,
+        capabilities: &[Capability],
+    ) -> Result<(), RuntimeError> {
+        let denied_by_name = self.denied_commands.iter().any(|pattern| {
+            WildcardPattern::new(pattern).is_ok_and(|pattern| pattern.is_match(name))
+        });
+        let denied_capability = capabilities
+            .iter()
+            .find(|capability| self.denied_capabilities.contains(capability));
+        let reason = match (denied_by_name, denied_capability) {
+            (true, _) => "it is denied".to_string(),
+            (false, Some(capability)) => format!("{} access is denied", capability),
+            (false, None) => return Ok(()),
+        };
+        Err(ErrorRecord::new(
+            ErrorCategory::SecurityError,
+            "CommandDenied",
+            format!("The command '{}' cannot run here: {}.", name, reason),
+        )
+        .with_target(name)
+        .into())
+    }
+}
+
+/// Roughly how much memory a value takes, in values: one for the value
+/// and each value inside it (array items, properties), and one more for
+/// every 64 characters of a string
+pub fn value_count(value: &Value) -> usize {
+    match value {
+        Value::String(text) => 1 + text.len() / CHARACTERS_PER_VALUE,
+        // A table or array that holds itself counts once
+        Value::Array(items) => {
+            1 + items
+                .visit(|items| items.iter().map(value_count).sum::<usize>())
+                .unwrap_or(0)
+        }
+        Value::Object(properties) | Value::Instance { properties, .. } => {
+            1 + properties
+                .visit(|properties| properties.values().map(value_count).sum::<usize>())
+                .unwrap_or(0)
+        }
+        _ => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::property_map::PropertyMap;
+
+    #[test]
+    fn test_value_count_counts_nested_values_and_long_strings() {
+        assert_eq!(value_count(&Value::Number(1.0)), 1);
+        assert_eq!(value_count(&Value::String("x".repeat(130).into())), 3);
+        let object = Value::Object(
+            PropertyMap::from([
+                ("Name", Value::String("web".into())),
+                (
+                    "Ports",
+                    Value::Array(vec![Value::Number(80.0), Value::Number(443.0)].into()),
+                ),
+            ])
+            .into(),
+        );
+        assert_eq!(value_count(&object), 5);
+    }
+
+    #[test]
+    fn test_commands_are_denied_by_name_or_capability() {
+        let limits = Limits {
+            denied_commands: vec!["Remove-*".to_string()],
+            denied_capabilities: vec![Capability::Process],
+            ..Limits::default()
+        };
+        assert!(limits.check_command("Get-Date", &[]).is_ok());
+        let error = limits.check_command("remove-item", &[]).unwrap_err();
+        assert_eq!(error.error_id(), "CommandDenied");
+        assert_eq!(
+            error.to_string(),
+            "The command 'remove-item' cannot run here: it is denied."
+        );
+        let error = limits
+            .check_command("Get-Process", &[Capability::Process])
+            .unwrap_err();
+        assert_eq!(error.category(), ErrorCategory::SecurityError);
+        assert_eq!(
+            Capability::from_name("filesystem"),
+            Some(Capability::FileSystem)
+        );
+    }
+}
@@ -0,0 +1,61 @@
+/// Regular expression matching for -match and -notmatch
+/// Patterns are case-insensitive, like PowerShell's default comparison operators.
+use crate::error::RuntimeError;
+use crate::property_map::PropertyMap;
+use crate::value::Value;
+use regex::Regex;
+
+/// Compile a -match pattern case-insensitively
+pub fn build_regex(pattern: &str) -> Result<Regex, RuntimeError> {
+    Regex::new(&format!("(?i){}", pattern)).map_err(|_| {
+        RuntimeError::InvalidOperation(format!(
+            "The regular expression pattern {} is not valid",
+            pattern
+        ))
+    })
+}
+
+/// Match a value against a pattern, returning the capture groups for $matches.
+/// Groups are keyed by number ("0" is the whole match) and by name for named groups;
+/// groups that didn't participate in the match are left out.
+pub fn captures(regex: &Regex, input: &Value) -> Option<PropertyMap> {
+    let input = input.to_string();
+    let caps = regex.captures(&input)?;
+
+    let mut groups = PropertyMap::new();
+    for (index, name) in regex.capture_names().enumerate() {
+        if let Some(group) = caps.get(index) {
+            let key = name.map_or_else(|| index.to_string(), str::to_string);
+            groups.insert(key, Value::String(group.as_str().to_string().into()));
+        }
+    }
+    Some(groups)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_captures_numbered_and_named_groups() {
+        let regex = build_regex(r"(\w+)@(?<domain>\w+)\.com").unwrap();
+        let groups = captures(&regex, &Value::String("Mail ANN@Example.com".into()))
+            .expect("Expected a match");
+
+        let keys: Vec<&str> = groups.keys().map(|k| k.as_str()).collect();
+        assert_eq!(keys, vec!["0", "1", "domain"]);
+        assert_eq!(groups.get("domain"), Some(&Value::String("Example".into())));
+    }
+
+    #[test]
+    fn test_match_is_case_insensitive() {
+        let regex = build_regex("^hello").unwrap();
+        assert!(captures(&regex, &Value::String("HELLO world".into())).is_some());
+        assert!(captures(&regex, &Value::Number(42.0)).is_none());
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        assert!(build_regex("(unclosed").is_err());
+    }
+}
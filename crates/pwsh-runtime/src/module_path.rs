@@ -0,0 +1,196 @@
+/// The module search path: finding script modules on disk, as `$env:PSModulePath` does
+///
+/// A script module is a `.psm1` file defining functions. In a directory on
+/// the search path, the module `Tools` is `Tools/Tools.psm1`,
+/// `Tools/<version>/Tools.psm1` (the newest version wins) or `Tools.psm1`.
+use pwsh_lexer::Lexer;
+use pwsh_parser::{Parser, StatementKind};
+use std::path::{Path, PathBuf};
+
+/// The environment variable listing module directories, separated as PATH is
+pub const MODULE_PATH_VARIABLE: &str = "PSModulePath";
+
+/// The directories to look for modules in: the configured ones, then those
+/// in `$env:PSModulePath`, read each time so scripts can change it
+pub(crate) fn search_path(configured: &[PathBuf]) -> Vec<PathBuf> {
+    let mut dirs = configured.to_vec();
+    if let Some(paths) = std::env::var_os(MODULE_PATH_VARIABLE) {
+        dirs.extend(std::env::split_paths(&paths).filter(|dir| !dir.as_os_str().is_empty()));
+    }
+    dirs
+}
+
+/// Whether an Import-Module name is a file rather than a module name
+pub(crate) fn is_module_file(name: &str) -> bool {
+    let path = Path::new(name);
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("psm1") || ext.eq_ignore_ascii_case("ps1"))
+        || name.contains(['/', '\\'])
+}
+
+/// The file of the module called `name` in `dir`, if it has one
+fn module_in(dir: &Path, name: &str) -> Option<PathBuf> {
+    let file_name = format!("{}.psm1", name);
+    let folder = dir.join(name);
+    let direct = folder.join(&file_name);
+    if direct.is_file() {
+        return Some(direct);
+    }
+    let newest_version = std::fs::read_dir(&folder)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let version = parse_version(&entry.file_name().to_string_lossy())?;
+            let file = entry.path().join(&file_name);
+            file.is_file().then_some((version, file))
+        })
+        .max_by(|(a, _), (b, _)| a.cmp(b));
+    if let Some((_, file)) = newest_version {
+        return Some(file);
+    }
+    let flat = dir.join(&file_name);
+    flat.is_file().then_some(flat)
+}
+
+/// A version folder's name: `1.2.0`
+fn parse_version(text: &str) -> Option<Vec<u32>> {
+    text.split('.').map(|part| part.parse().ok()).collect()
+}
+
+/// The module called `name`, from the first directory that has it. Names
+/// are matched case-insensitively, as PowerShell does on every platform.
+pub(crate) fn find_module(dirs: &[PathBuf], name: &str) -> Option<PathBuf> {
+    dirs.iter().find_map(|dir| {
+        module_in(dir, name).or_else(|| {
+            // The folder may be spelt differently from the name asked for
+            let spelt = module_names(dir)
+                .into_iter()
+                .find(|found| found.eq_ignore_ascii_case(name) && found != name)?;
+            module_in(dir, &spelt)
+        })
+    })
+}
+
+/// Names of the modules in `dir`, sorted
+fn module_names(dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let path = entry.path();
+            let name = if path.is_dir() {
+                path.file_name()?.to_string_lossy().into_owned()
+            } else if path
+                .extension()
+                .is_some_and(|ext| ext.eq_ignore_ascii_case("psm1"))
+            {
+                path.file_stem()?.to_string_lossy().into_owned()
+            } else {
+                return None;
+            };
+            module_in(dir, &name).map(|_| name)
+        })
+        .collect();
+    names.sort_by_key(|name| name.to_lowercase());
+    names.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+    names
+}
+
+/// Every module on the search path with its file, by name; where two
+/// directories have a module of the same name, the first one's wins
+pub(crate) fn available_modules(dirs: &[PathBuf]) -> Vec<(String, PathBuf)> {
+    let mut modules: Vec<(String, PathBuf)> = Vec::new();
+    for dir in dirs {
+        for name in module_names(dir) {
+            if !modules
+                .iter()
+                .any(|(found, _)| found.eq_ignore_ascii_case(&name))
+            {
+                if let Some(file) = module_in(dir, &name) {
+                    modules.push((name, file));
+                }
+            }
+        }
+    }
+    modules
+}
+
+/// The commands a module's source exports: the functions it defines at
+/// its top level. A module that doesn't parse exports nothing.
+pub(crate) fn exported_commands(source: &str) -> Vec<String> {
+    let Ok(tokens) = Lexer::new(source).tokenize() else {
+        return Vec::new();
+    };
+    let Ok(program) = Parser::new(tokens).parse() else {
+        return Vec::new();
+    };
+    program
+        .statements
+        .into_iter()
+        .filter_map(|statement| match statement.kind {
+            StatementKind::FunctionDef { name, .. } => Some(name),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn write(path: &Path, text: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, text).unwrap();
+    }
+
+    #[test]
+    fn test_find_modules_in_each_layout() {
+        let root = tempfile::TempDir::new().unwrap();
+        let first = root.path().join("first");
+        let second = root.path().join("second");
+        write(&first.join("Tools/Tools.psm1"), "");
+        write(&first.join("Net/1.2.0/Net.psm1"), "");
+        write(&first.join("Net/1.10.0/Net.psm1"), "");
+        write(&second.join("Flat.psm1"), "");
+        write(&second.join("Tools/Tools.psm1"), "");
+        write(&second.join("NotAModule/readme.txt"), "");
+        let dirs = vec![first.clone(), second.clone()];
+
+        assert_eq!(
+            find_module(&dirs, "Tools"),
+            Some(first.join("Tools/Tools.psm1"))
+        );
+        assert_eq!(
+            find_module(&dirs, "net"),
+            Some(first.join("Net/1.10.0/Net.psm1"))
+        );
+        assert_eq!(find_module(&dirs, "Flat"), Some(second.join("Flat.psm1")));
+        assert_eq!(find_module(&dirs, "NotAModule"), None);
+
+        let names: Vec<String> = available_modules(&dirs)
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(names, vec!["Net", "Tools", "Flat"]);
+    }
+
+    #[test]
+    fn test_exported_commands_are_top_level_functions() {
+        let source =
+            "function Get-Tool { 'tool' }\n$count = 0\nfunction Set-Tool { function Inner {} }";
+        assert_eq!(exported_commands(source), vec!["Get-Tool", "Set-Tool"]);
+        assert!(exported_commands("function {").is_empty());
+    }
+
+    #[test]
+    fn test_module_files() {
+        assert!(is_module_file("./Tools.psm1"));
+        assert!(is_module_file("Tools.PSM1"));
+        assert!(is_module_file("modules/Tools"));
+        assert!(!is_module_file("Tools"));
+    }
+}
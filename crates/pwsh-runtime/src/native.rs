@@ -0,0 +1,476 @@
+/// Native commands: programs run from PATH when no cmdlet or function has the name
+use crate::error::{ErrorCategory, ErrorRecord, RuntimeError};
+use crate::value::Value;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Where the program for a native command is: `name` itself if it has a
+/// directory part (`./build.sh`), otherwise the first match on PATH. On
+/// Windows the extensions in PATHEXT are tried too.
+pub(crate) fn find_program(name: &str) -> Option<PathBuf> {
+    let candidates = |base: PathBuf| {
+        let mut paths = vec![base.clone()];
+        if cfg!(windows) && base.extension().is_none() {
+            let extensions =
+                std::env::var("PATHEXT").unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".into());
+            paths.extend(
+                extensions
+                    .split(';')
+                    .filter(|ext| !ext.is_empty())
+                    .map(|ext| base.with_extension(ext.trim_start_matches('.'))),
+            );
+        }
+        paths
+    };
+
+    if Path::new(name).components().count() > 1 {
+        return candidates(PathBuf::from(name))
+            .into_iter()
+            .find(|path| path.is_file());
+    }
+    let path_var = std::env::var_os("PATH")?;
+    std::env::split_paths(&path_var)
+        .flat_map(|dir| candidates(dir.join(name)))
+        .find(|path| path.is_file())
+}
+
+/// Replace `%NAME%` with the value of the environment variable NAME, as
+/// PowerShell does in the text after `--%`. Names that aren't set are left
+/// as they are.
+pub(crate) fn expand_environment(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('%') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        match after.find('%') {
+            Some(end) if end > 0 => match std::env::var(&after[..end]) {
+                Ok(value) => {
+                    out.push_str(&value);
+                    rest = &after[end + 1..];
+                }
+                Err(_) => {
+                    // Not a variable: keep the first % and look again from
+                    // the second, which may start a name
+                    out.push('%');
+                    rest = after;
+                }
+            },
+            _ => {
+                out.push('%');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Split a command line into arguments the way a Windows program's C
+/// runtime does, for platforms where a program takes an argument list
+/// rather than a command line: whitespace separates arguments, double
+/// quotes group words and are removed, and backslashes are literal unless
+/// they come before a quote (`\"` is a quote, `\\"` a backslash and then a
+/// grouping quote)
+pub(crate) fn split_command_line(text: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut has_arg = false;
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => {
+                let mut backslashes = 1;
+                while chars.next_if_eq(&'\\').is_some() {
+                    backslashes += 1;
+                }
+                if chars.peek() == Some(&'"') {
+                    current.extend(std::iter::repeat_n('\\', backslashes / 2));
+                    if backslashes % 2 == 1 {
+                        current.push('"');
+                        chars.next();
+                    }
+                } else {
+                    current.extend(std::iter::repeat_n('\\', backslashes));
+                }
+                has_arg = true;
+            }
+            '"' => {
+                in_quotes = !in_quotes;
+                has_arg = true;
+            }
+            ch if ch.is_whitespace() && !in_quotes => {
+                if has_arg {
+                    args.push(std::mem::take(&mut current));
+                    has_arg = false;
+                }
+            }
+            ch => {
+                current.push(ch);
+                has_arg = true;
+            }
+        }
+    }
+    if has_arg {
+        args.push(current);
+    }
+    args
+}
+
+/// How arguments are given to a native command, from
+/// $PSNativeCommandArgumentPassing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum NativeArgumentPassing {
+    /// Each argument reaches the program exactly as it was written, empty
+    /// strings and embedded quotes included (the default except on Windows)
+    Standard,
+    /// The PowerShell 5 behavior: an argument with whitespace is wrapped in
+    /// quotes, quotes inside it are not escaped and empty strings are dropped
+    Legacy,
+    /// Standard, except for batch files and script hosts, which parse their
+    /// command line their own way and get Legacy (the default on Windows)
+    Windows,
+}
+
+/// Programs that get Legacy argument passing in Windows mode
+const LEGACY_PROGRAMS: &[&str] = &["cmd", "cscript", "find", "sqlcmd", "wscript"];
+const LEGACY_EXTENSIONS: &[&str] = &["bat", "cmd", "js", "vbs", "wsf"];
+
+impl NativeArgumentPassing {
+    /// The mode used when $PSNativeCommandArgumentPassing isn't set
+    pub fn platform_default() -> Self {
+        if cfg!(windows) {
+            NativeArgumentPassing::Windows
+        } else {
+            NativeArgumentPassing::Standard
+        }
+    }
+
+    /// Parse a mode name, ignoring case
+    pub fn from_value(value: &Value) -> Result<Self, RuntimeError> {
+        let name = value.to_string();
+        match name.to_ascii_lowercase().as_str() {
+            "standard" => Ok(NativeArgumentPassing::Standard),
+            "legacy" => Ok(NativeArgumentPassing::Legacy),
+            "windows" => Ok(NativeArgumentPassing::Windows),
+            _ => Err(ErrorRecord::new(
+                ErrorCategory::InvalidArgument,
+                "InvalidNativeArgumentPassing",
+                format!(
+                    "Cannot convert '{}' to an argument passing mode. Use one of: Standard, Legacy, Windows",
+                    name
+                ),
+            )
+            .with_target(name)
+            .into()),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            NativeArgumentPassing::Standard => "Standard",
+            NativeArgumentPassing::Legacy => "Legacy",
+            NativeArgumentPassing::Windows => "Windows",
+        }
+    }
+
+    /// The mode `program` actually gets: Windows mode is Legacy for the
+    /// programs in LEGACY_PROGRAMS and files with LEGACY_EXTENSIONS
+    fn for_program(self, program: &Path) -> Self {
+        if self != NativeArgumentPassing::Windows {
+            return self;
+        }
+        let is_legacy = |list: &[&str], part: Option<&std::ffi::OsStr>| {
+            part.and_then(|part| part.to_str())
+                .is_some_and(|part| list.iter().any(|name| part.eq_ignore_ascii_case(name)))
+        };
+        if is_legacy(LEGACY_PROGRAMS, program.file_stem())
+            || is_legacy(LEGACY_EXTENSIONS, program.extension())
+        {
+            NativeArgumentPassing::Legacy
+        } else {
+            NativeArgumentPassing::Standard
+        }
+    }
+}
+
+/// The process arguments for one PowerShell argument: each item of an
+/// array is an argument of its own, and $null is no argument at all
+pub(crate) fn argument_strings(value: &Value) -> Vec<String> {
+    match value {
+        Value::Null => Vec::new(),
+        Value::Array(items) => items
+            .read()
+            .iter()
+            .filter(|item| !matches!(item, Value::Null))
+            .map(Value::to_string)
+            .collect(),
+        other => vec![other.to_string()],
+    }
+}
+
+/// Quote an argument so that `split_command_line` (and a Windows program)
+/// reads it back unchanged. Only arguments that are empty or have
+/// whitespace or quotes are quoted.
+pub(crate) fn quote_argument(arg: &str) -> String {
+    if !arg.is_empty() && !arg.chars().any(|ch| ch.is_whitespace() || ch == '"') {
+        return arg.to_string();
+    }
+    let mut quoted = String::with_capacity(arg.len() + 2);
+    quoted.push('"');
+    let mut backslashes = 0;
+    for ch in arg.chars() {
+        match ch {
+            '\\' => backslashes += 1,
+            // Backslashes before a quote are doubled, and the quote escaped
+            '"' => {
+                quoted.extend(std::iter::repeat_n('\\', backslashes + 1));
+                backslashes = 0;
+            }
+            _ => backslashes = 0,
+        }
+        quoted.push(ch);
+    }
+    // Backslashes before the closing quote would escape it
+    quoted.extend(std::iter::repeat_n('\\', backslashes));
+    quoted.push('"');
+    quoted
+}
+
+/// Quote an argument as PowerShell 5 did: wrapped in quotes if it has
+/// whitespace outside quotes, otherwise as it is. Empty arguments are
+/// dropped.
+fn quote_legacy(arg: &str) -> Option<String> {
+    if arg.is_empty() {
+        return None;
+    }
+    let mut needs_quotes = false;
+    let mut in_quotes = false;
+    let mut after_backslash = false;
+    for ch in arg.chars() {
+        if ch == '"' && !after_backslash {
+            in_quotes = !in_quotes;
+        } else if ch.is_whitespace() && !in_quotes {
+            needs_quotes = true;
+        }
+        after_backslash = ch == '\\';
+    }
+    if !needs_quotes {
+        return Some(arg.to_string());
+    }
+    let trailing = arg.len() - arg.trim_end_matches('\\').len();
+    Some(format!("\"{}{}\"", arg, "\\".repeat(trailing)))
+}
+
+/// The command line a program gets for `args`
+pub(crate) fn command_line(args: &[String], passing: NativeArgumentPassing) -> String {
+    let quoted: Vec<String> = match passing {
+        NativeArgumentPassing::Legacy => args.iter().filter_map(|arg| quote_legacy(arg)).collect(),
+        _ => args.iter().map(|arg| quote_argument(arg)).collect(),
+    };
+    quoted.join(" ")
+}
+
+/// A program to run, with its arguments
+pub(crate) struct NativeCommand {
+    pub program: PathBuf,
+    pub args: Vec<String>,
+    /// How `args` are quoted for the program
+    pub passing: NativeArgumentPassing,
+    /// The text after `--%`, passed on unchanged apart from `%NAME%`
+    /// environment variables
+    pub verbatim: Option<String>,
+}
+
+impl NativeCommand {
+    /// Run the program with `input` written to its standard input, one item
+    /// per line. Returns its standard output, one string per line, and its
+    /// exit code. Standard error goes straight to the console.
+    pub fn run(&self, input: &[Value]) -> Result<(Vec<Value>, i32), RuntimeError> {
+        let mut command = Command::new(&self.program);
+        let passing = self.passing.for_program(&self.program);
+        #[cfg(windows)]
+        {
+            use std::os::windows::process::CommandExt;
+            if !self.args.is_empty() {
+                command.raw_arg(command_line(&self.args, passing));
+            }
+            if let Some(text) = &self.verbatim {
+                command.raw_arg(expand_environment(text));
+            }
+        }
+        #[cfg(not(windows))]
+        {
+            // A Unix program gets an argument list, so Legacy quoting is
+            // only seen through what splitting its command line gives back
+            match passing {
+                NativeArgumentPassing::Legacy => {
+                    command.args(split_command_line(&command_line(&self.args, passing)))
+                }
+                _ => command.args(&self.args),
+            };
+            if let Some(text) = &self.verbatim {
+                command.args(split_command_line(&expand_environment(text)));
+            }
+        }
+        command.stdout(Stdio::piped());
+        if !input.is_empty() {
+            command.stdin(Stdio::piped());
+        }
+
+        let name = self.program.display().to_string();
+        let failed = |e: std::io::Error| -> RuntimeError {
+            ErrorRecord::new(
+                ErrorCategory::from(e.kind()),
+                "NativeCommandFailed",
+                format!("Program '{}' failed to run: {}", name, e),
+            )
+            .into()
+        };
+        let mut child = command.spawn().map_err(failed)?;
+
+        // Feed the input from another thread, so a program that writes
+        // before it has read everything can't deadlock with us
+        let writer = child.stdin.take().map(|mut stdin| {
+            let text: String = input.iter().map(|item| format!("{}\n", item)).collect();
+            std::thread::spawn(move || {
+                // A program that exits without reading its input isn't an error
+                let _ = stdin.write_all(text.as_bytes());
+            })
+        });
+
+        let mut output = Vec::new();
+        if let Some(stdout) = child.stdout.take() {
+            for line in BufReader::new(stdout).split(b'\n') {
+                let mut line = line.map_err(failed)?;
+                if line.last() == Some(&b'\r') {
+                    line.pop();
+                }
+                output.push(Value::String(String::from_utf8_lossy(&line).into()));
+            }
+        }
+        if let Some(writer) = writer {
+            let _ = writer.join();
+        }
+        let status = child.wait().map_err(failed)?;
+        Ok((output, status.code().unwrap_or(-1)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_command_line_groups_quoted_words() {
+        assert_eq!(
+            split_command_line(r#"/grant "Domain Users":(F)  $x 'y'"#),
+            vec!["/grant", "Domain Users:(F)", "$x", "'y'"]
+        );
+        assert_eq!(split_command_line(r#"a "" b"#), vec!["a", "", "b"]);
+        assert_eq!(
+            split_command_line(r#"C:\dir\ a\"b "c\\" d"#),
+            vec![r"C:\dir\", r#"a"b"#, r"c\", "d"]
+        );
+    }
+
+    #[test]
+    fn test_quote_argument_round_trips() {
+        let args: Vec<String> = [
+            "plain",
+            "",
+            "two words",
+            r#"say "hi""#,
+            r"C:\Program Files\",
+            r#"a\"b"#,
+        ]
+        .iter()
+        .map(|arg| arg.to_string())
+        .collect();
+        let line = command_line(&args, NativeArgumentPassing::Standard);
+        assert_eq!(
+            line,
+            r#"plain "" "two words" "say \"hi\"" "C:\Program Files\\" "a\\\"b""#
+        );
+        assert_eq!(split_command_line(&line), args);
+    }
+
+    #[test]
+    fn test_legacy_quoting_drops_empty_and_keeps_quotes() {
+        let args: Vec<String> = ["a b", "", r#"say "hi there""#, r#""already quoted""#]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect();
+        let line = command_line(&args, NativeArgumentPassing::Legacy);
+        assert_eq!(line, r#""a b" "say "hi there"" "already quoted""#);
+        // The quotes inside aren't escaped, so the program splits it apart
+        assert_eq!(
+            split_command_line(&line),
+            vec!["a b", "say hi", "there", "already quoted"]
+        );
+    }
+
+    #[test]
+    fn test_windows_mode_is_legacy_for_batch_files() {
+        let windows = NativeArgumentPassing::Windows;
+        assert_eq!(
+            windows.for_program(Path::new("C:/tools/build.CMD")),
+            NativeArgumentPassing::Legacy
+        );
+        assert_eq!(
+            windows.for_program(Path::new("C:/Windows/cscript.exe")),
+            NativeArgumentPassing::Legacy
+        );
+        assert_eq!(
+            windows.for_program(Path::new("/usr/bin/git")),
+            NativeArgumentPassing::Standard
+        );
+        assert_eq!(
+            NativeArgumentPassing::from_value(&Value::String("LEGACY".into())).unwrap(),
+            NativeArgumentPassing::Legacy
+        );
+        assert!(NativeArgumentPassing::from_value(&Value::String("raw".into())).is_err());
+    }
+
+    #[test]
+    fn test_argument_strings_unroll_arrays() {
+        let value =
+            Value::Array(vec![Value::String("a b".into()), Value::Null, Value::Number(1.5)].into());
+        assert_eq!(argument_strings(&value), vec!["a b", "1.5"]);
+        assert!(argument_strings(&Value::Null).is_empty());
+    }
+
+    #[test]
+    fn test_expand_environment_variables() {
+        std::env::set_var("PWSH_NATIVE_TEST", "value");
+        assert_eq!(
+            expand_environment("%PWSH_NATIVE_TEST%/x 100% %NOT_SET_ANYWHERE%"),
+            "value/x 100% %NOT_SET_ANYWHERE%"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_passes_verbatim_text_and_input() {
+        let command = NativeCommand {
+            program: find_program("sh").unwrap(),
+            args: vec!["-c".to_string()],
+            passing: NativeArgumentPassing::Standard,
+            verbatim: Some(r#""cat; echo $0" "a b""#.to_string()),
+        };
+        let (output, code) = command
+            .run(&[Value::String("in".into()), Value::Number(2.0)])
+            .unwrap();
+        assert_eq!(
+            output,
+            vec![
+                Value::String("in".into()),
+                Value::String("2".into()),
+                Value::String("a b".into()),
+            ]
+        );
+        assert_eq!(code, 0);
+    }
+}
@@ -0,0 +1,118 @@
+/// Paging: long output shown a screen at a time, as `more` shows it
+use crate::host::Host;
+
+/// What the pager asks after each screen
+pub const MORE_PROMPT: &str = "<SPACE> next page; <CR> next line; Q quit";
+
+/// Show `lines` on `host` a screen at a time when they don't fit its
+/// window: after each screen, Space shows the next, Enter the next line and
+/// Q (or the end of input) stops. Any other key asks again. Output that
+/// fits, or a host without a window, gets every line at once.
+pub fn page(host: &mut dyn Host, lines: &[String]) {
+    let rows = match host.window_size() {
+        Some((_, rows)) if rows > 1 && lines.len() >= rows => rows,
+        _ => {
+            host.write_output_lines(lines);
+            return;
+        }
+    };
+    // A screen is the window less the prompt's line
+    let screen = rows - 1;
+    let mut shown = 0;
+    let mut next = screen;
+    loop {
+        let end = next.min(lines.len());
+        if end > shown {
+            host.write_output_lines(&lines[shown..end]);
+            shown = end;
+        }
+        if shown == lines.len() {
+            return;
+        }
+        match host.read_key(MORE_PROMPT) {
+            Some(' ') => next = shown + screen,
+            Some('\n' | '\r') => next = shown + 1,
+            Some('q' | 'Q') | None => return,
+            Some(_) => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    /// A window `rows` high answering prompts with `keys`
+    struct Window {
+        rows: usize,
+        keys: VecDeque<char>,
+        shown: Vec<String>,
+        prompts: usize,
+    }
+
+    impl Host for Window {
+        fn write(&mut self, _text: &str) {}
+
+        fn write_error_line(&mut self, _text: &str) {}
+
+        fn write_output_lines(&mut self, lines: &[String]) {
+            self.shown.extend_from_slice(lines);
+        }
+
+        fn read_line(&mut self, _prompt: &str) -> Option<String> {
+            None
+        }
+
+        fn window_size(&self) -> Option<(usize, usize)> {
+            Some((80, self.rows))
+        }
+
+        fn read_key(&mut self, prompt: &str) -> Option<char> {
+            assert_eq!(prompt, MORE_PROMPT);
+            self.prompts += 1;
+            self.keys.pop_front()
+        }
+    }
+
+    fn window(rows: usize, keys: &str) -> Window {
+        Window {
+            rows,
+            keys: keys.chars().collect(),
+            shown: Vec::new(),
+            prompts: 0,
+        }
+    }
+
+    fn lines(count: usize) -> Vec<String> {
+        (1..=count).map(|i| i.to_string()).collect()
+    }
+
+    #[test]
+    fn test_space_shows_a_screen_and_enter_a_line() {
+        let mut host = window(4, " \nx ");
+        page(&mut host, &lines(10));
+        assert_eq!(host.shown, lines(10));
+        // 3, space to 6, enter to 7, x asks again, space to 10
+        assert_eq!(host.prompts, 4);
+    }
+
+    #[test]
+    fn test_q_or_the_end_of_input_stops() {
+        let mut host = window(4, "q");
+        page(&mut host, &lines(10));
+        assert_eq!(host.shown, lines(3));
+
+        let mut host = window(4, " ");
+        page(&mut host, &lines(10));
+        assert_eq!(host.shown, lines(6));
+    }
+
+    #[test]
+    fn test_output_that_fits_is_not_paged() {
+        let mut host = window(4, "");
+        page(&mut host, &lines(3));
+        assert_eq!(host.shown, lines(3));
+        assert_eq!(host.prompts, 0);
+    }
+}
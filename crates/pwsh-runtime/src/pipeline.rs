@@ -3,7 +3,7 @@ use crate::cmdlet::{CmdletContext, CmdletRegistry};
 use crate::error::RuntimeError;
 use crate::evaluator::Evaluator;
 use crate::value::Value;
-use pwsh_parser::{Argument, Expression, Pipeline};
+use pwsh_parser::{Argument, Expression, ExpressionKind, Pipeline};
 
 /// Pipeline executor manages the execution of pipeline stages
 pub struct PipelineExecutor<'a> {
@@ -44,18 +44,19 @@ impl<'a> PipelineExecutor<'a> {
         input: Vec<Value>,
         evaluator: &mut Evaluator,
     ) -> Result<Vec<Value>, RuntimeError> {
-        match stage {
-            Expression::Call { name, arguments } => {
+        match &stage.kind {
+            ExpressionKind::Call { name, arguments } => {
                 // This is a cmdlet call
                 self.execute_cmdlet(name, arguments, input, evaluator)
             }
-            Expression::ScriptBlock(block) => {
+            ExpressionKind::Invoke { command, arguments } => {
+                evaluator.invoke_command(command, arguments, input)
+            }
+            ExpressionKind::ScriptBlock(block) => {
                 // Script block in pipeline - execute it for each input item
                 if !input.is_empty() {
                     let mut results = Vec::new();
-                    let script_block = crate::value::ScriptBlock {
-                        body: block.clone(),
-                    };
+                    let script_block = crate::value::ScriptBlock::new(block.clone());
                     for item in input {
                         let result = evaluator.execute_script_block(&script_block, item)?;
                         results.push(result);
@@ -63,7 +64,7 @@ impl<'a> PipelineExecutor<'a> {
                     Ok(results)
                 } else {
                     // No pipeline input, just return the script block as a value
-                    let result = evaluator.eval_expression(stage.clone())?;
+                    let result = evaluator.eval_expression(stage)?;
                     Ok(vec![result])
                 }
             }
@@ -76,17 +77,17 @@ impl<'a> PipelineExecutor<'a> {
                     for item in input {
                         // Set $_ to the current pipeline item
                         evaluator.set_variable("_", item.clone());
-                        let result = evaluator.eval_expression(stage.clone())?;
+                        let result = evaluator.eval_expression(stage)?;
                         results.push(result);
                     }
                     Ok(results)
                 } else {
                     // No pipeline input, just evaluate the expression
-                    let result = evaluator.eval_expression(stage.clone())?;
+                    let result = evaluator.eval_expression(stage)?;
 
                     // If the result is an array, unroll it to the pipeline
                     if let Value::Array(items) = result {
-                        Ok(items)
+                        Ok(items.unwrap_or_clone())
                     } else {
                         Ok(vec![result])
                     }
@@ -107,7 +108,7 @@ impl<'a> PipelineExecutor<'a> {
         let cmdlet = self
             .registry
             .get(name)
-            .ok_or_else(|| RuntimeError::UndefinedFunction(name.to_string()))?;
+            .ok_or_else(|| evaluator.undefined_command(name))?;
 
         // Build cmdlet context
         let mut context = CmdletContext::with_input(input);
@@ -117,18 +118,27 @@ impl<'a> PipelineExecutor<'a> {
         for arg in arguments {
             match arg {
                 Argument::Positional(expr) => {
-                    let value = evaluator.eval_expression(expr.clone())?;
+                    let value = evaluator.eval_expression(expr)?;
                     positional_args.push(value);
                 }
-                Argument::Named { name, value } => {
-                    let val = evaluator.eval_expression(value.clone())?;
+                Argument::Named { name, value, .. } => {
+                    let val = evaluator.eval_expression(value)?;
                     context.parameters.insert(name.clone(), val);
                 }
+                Argument::StopParsing { text, .. } => {
+                    positional_args.extend(
+                        crate::native::split_command_line(&crate::native::expand_environment(text))
+                            .into_iter()
+                            .map(|arg| Value::String(arg.into())),
+                    );
+                }
             }
         }
         context.arguments = positional_args;
+        evaluator.resolve_error_action(&mut context)?;
 
-        // Execute the cmdlet
+        // Execute the cmdlet, if the sandbox lets it run
+        evaluator.check_cmdlet_allowed(cmdlet)?;
         cmdlet.execute(context, evaluator)
     }
 }
@@ -194,12 +204,13 @@ mod tests {
 
         // Create a simple pipeline: Test-Echo 42
         let pipeline = Pipeline {
-            stages: vec![Expression::Call {
+            stages: vec![ExpressionKind::Call {
                 name: "Test-Echo".to_string(),
-                arguments: vec![Argument::Positional(Expression::Literal(
-                    pwsh_parser::Literal::Number(42.0),
-                ))],
-            }],
+                arguments: vec![Argument::Positional(
+                    ExpressionKind::Literal(pwsh_parser::Literal::Number(42.0)).into(),
+                )],
+            }
+            .into()],
         };
 
         let result = executor.execute(&pipeline, &mut evaluator).unwrap();
@@ -218,16 +229,18 @@ mod tests {
         // Create pipeline: Test-Echo 5 | Test-Double
         let pipeline = Pipeline {
             stages: vec![
-                Expression::Call {
+                ExpressionKind::Call {
                     name: "Test-Echo".to_string(),
-                    arguments: vec![Argument::Positional(Expression::Literal(
-                        pwsh_parser::Literal::Number(5.0),
-                    ))],
-                },
-                Expression::Call {
+                    arguments: vec![Argument::Positional(
+                        ExpressionKind::Literal(pwsh_parser::Literal::Number(5.0)).into(),
+                    )],
+                }
+                .into(),
+                ExpressionKind::Call {
                     name: "Test-Double".to_string(),
                     arguments: vec![],
-                },
+                }
+                .into(),
             ],
         };
 
@@ -245,14 +258,23 @@ mod tests {
         // First stage produces 5, second stage is a script block that adds 10
         let pipeline = Pipeline {
             stages: vec![
-                Expression::Literal(pwsh_parser::Literal::Number(5.0)),
-                Expression::ScriptBlock(pwsh_parser::Block {
-                    statements: vec![pwsh_parser::Statement::Expression(Expression::BinaryOp {
-                        left: Box::new(Expression::Variable("_".to_string())),
-                        operator: pwsh_parser::BinaryOperator::Add,
-                        right: Box::new(Expression::Literal(pwsh_parser::Literal::Number(10.0))),
-                    })],
-                }),
+                ExpressionKind::Literal(pwsh_parser::Literal::Number(5.0)).into(),
+                ExpressionKind::ScriptBlock(pwsh_parser::Block {
+                    statements: vec![pwsh_parser::StatementKind::Expression(
+                        ExpressionKind::BinaryOp {
+                            left: Box::new(ExpressionKind::Variable("_".to_string()).into()),
+                            operator: pwsh_parser::BinaryOperator::Add,
+                            right: Box::new(
+                                ExpressionKind::Literal(pwsh_parser::Literal::Number(10.0)).into(),
+                            ),
+                        }
+                        .into(),
+                    )
+                    .into()],
+                    trivia: vec![],
+                    span: Default::default(),
+                })
+                .into(),
             ],
         };
 
@@ -270,18 +292,73 @@ mod tests {
         use pwsh_parser::Literal;
         let pipeline = Pipeline {
             stages: vec![
-                Expression::Literal(Literal::Number(1.0)),
-                Expression::ScriptBlock(pwsh_parser::Block {
-                    statements: vec![pwsh_parser::Statement::Expression(Expression::BinaryOp {
-                        left: Box::new(Expression::Variable("_".to_string())),
-                        operator: pwsh_parser::BinaryOperator::Multiply,
-                        right: Box::new(Expression::Literal(Literal::Number(2.0))),
-                    })],
-                }),
+                ExpressionKind::Literal(Literal::Number(1.0)).into(),
+                ExpressionKind::ScriptBlock(pwsh_parser::Block {
+                    statements: vec![pwsh_parser::StatementKind::Expression(
+                        ExpressionKind::BinaryOp {
+                            left: Box::new(ExpressionKind::Variable("_".to_string()).into()),
+                            operator: pwsh_parser::BinaryOperator::Multiply,
+                            right: Box::new(ExpressionKind::Literal(Literal::Number(2.0)).into()),
+                        }
+                        .into(),
+                    )
+                    .into()],
+                    trivia: vec![],
+                    span: Default::default(),
+                })
+                .into(),
             ],
         };
 
         let result = executor.execute(&pipeline, &mut evaluator).unwrap();
         assert_eq!(result, vec![Value::Number(2.0)]);
     }
+
+    #[test]
+    fn test_pipeline_suggests_functions_for_an_unknown_command() {
+        let registry = CmdletRegistry::new();
+        let executor = PipelineExecutor::new(&registry);
+        let mut evaluator = Evaluator::new();
+        let tokens = pwsh_lexer::Lexer::new("function Get-Widget { 1 }")
+            .tokenize()
+            .unwrap();
+        let program = pwsh_parser::Parser::new(tokens).parse().unwrap();
+        evaluator.eval(program).unwrap();
+
+        let pipeline = Pipeline {
+            stages: vec![ExpressionKind::Call {
+                name: "Get-Widgit".to_string(),
+                arguments: vec![],
+            }
+            .into()],
+        };
+        match executor.execute(&pipeline, &mut evaluator) {
+            Err(RuntimeError::UndefinedFunction { suggestions, .. }) => {
+                assert_eq!(suggestions, vec!["Get-Widget".to_string()])
+            }
+            other => panic!("expected an undefined command, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_pipeline_cmdlets_obey_the_limits() {
+        let mut registry = CmdletRegistry::new();
+        registry.register(Box::new(EchoCmdlet));
+        let executor = PipelineExecutor::new(&registry);
+        let mut evaluator = Evaluator::new();
+        evaluator.set_limits(crate::limits::Limits {
+            denied_commands: vec!["Test-*".to_string()],
+            ..Default::default()
+        });
+
+        let pipeline = Pipeline {
+            stages: vec![ExpressionKind::Call {
+                name: "Test-Echo".to_string(),
+                arguments: vec![],
+            }
+            .into()],
+        };
+        let error = executor.execute(&pipeline, &mut evaluator).unwrap_err();
+        assert_eq!(error.error_id(), "CommandDenied");
+    }
 }
@@ -0,0 +1,128 @@
+/// Cmdlet packs: sets of cmdlets from other crates, loaded with Import-Module
+///
+/// A pack is registered with a session under a module name; its cmdlets
+/// become available when a script runs `Import-Module <name>`, or first
+/// calls one of them. Packs are
+/// linked in statically; `cmdlet_pack!` declares one from a list of cmdlets.
+use crate::cmdlet::CmdletRegistry;
+
+/// A named set of cmdlets that `Import-Module` can load
+pub trait CmdletPack {
+    /// Module name scripts import the pack by (matched case-insensitively)
+    fn name(&self) -> &str;
+
+    /// Add the pack's cmdlets to the registry
+    fn register(&self, registry: &mut CmdletRegistry);
+}
+
+/// Declare a unit struct implementing `CmdletPack` for a list of cmdlets
+///
+/// ```
+/// # use pwsh_runtime::{Cmdlet, CmdletContext, Evaluator, RuntimeError, Value};
+/// # struct GetGreeting;
+/// # impl Cmdlet for GetGreeting {
+/// #     fn name(&self) -> &str { "Get-Greeting" }
+/// #     fn execute(&self, _: CmdletContext, _: &mut Evaluator) -> Result<Vec<Value>, RuntimeError> {
+/// #         Ok(vec![Value::String("hello".into())])
+/// #     }
+/// # }
+/// pwsh_runtime::cmdlet_pack!(pub GreetingPack, "Greetings", [GetGreeting]);
+///
+/// let mut session = pwsh_runtime::SessionBuilder::new().module(GreetingPack).build();
+/// session.evaluator_mut().import_module("greetings").unwrap();
+/// assert_eq!(session.invoke("Get-Greeting").unwrap(), vec![Value::String("hello".into())]);
+/// ```
+#[macro_export]
+macro_rules! cmdlet_pack {
+    ($(#[$meta:meta])* $vis:vis $pack:ident, $name:expr, [$($cmdlet:expr),* $(,)?]) => {
+        $(#[$meta])*
+        $vis struct $pack;
+
+        impl $crate::CmdletPack for $pack {
+            fn name(&self) -> &str {
+                $name
+            }
+
+            fn register(&self, registry: &mut $crate::CmdletRegistry) {
+                $(registry.register(Box::new($cmdlet));)*
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cmdlet::{Cmdlet, CmdletContext};
+    use crate::{ErrorCategory, Evaluator, RuntimeError, SessionBuilder, Value};
+
+    struct GetAnswer;
+
+    impl Cmdlet for GetAnswer {
+        fn name(&self) -> &str {
+            "Get-Answer"
+        }
+
+        fn execute(
+            &self,
+            _context: CmdletContext,
+            _evaluator: &mut Evaluator,
+        ) -> Result<Vec<Value>, RuntimeError> {
+            Ok(vec![Value::Number(42.0)])
+        }
+    }
+
+    cmdlet_pack!(AnswerPack, "Answers", [GetAnswer]);
+
+    #[test]
+    fn test_pack_cmdlets_load_on_import() {
+        let mut session = SessionBuilder::new().module(AnswerPack).build();
+        assert!(!session.evaluator().registry().contains("Get-Answer"));
+
+        session.evaluator_mut().import_module("answers").unwrap();
+        assert_eq!(
+            session.invoke("Get-Answer").unwrap(),
+            vec![Value::Number(42.0)]
+        );
+        assert_eq!(session.evaluator().module_names(), vec!["Answers"]);
+    }
+
+    #[test]
+    fn test_pack_loads_on_first_use_of_its_cmdlet() {
+        let mut session = SessionBuilder::new().module(AnswerPack).build();
+        assert_eq!(
+            session.invoke("Get-Answer").unwrap(),
+            vec![Value::Number(42.0)]
+        );
+        assert!(session.evaluator().registry().contains("Get-Answer"));
+    }
+
+    #[test]
+    fn test_importing_an_unknown_module_fails() {
+        let mut evaluator = Evaluator::new();
+        let error = evaluator.import_module("Missing").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The specified module 'Missing' was not loaded because no valid module was found"
+        );
+        assert_eq!(error.category(), ErrorCategory::ObjectNotFound);
+    }
+
+    #[test]
+    fn test_requires_modules_imports_them() {
+        let mut session = SessionBuilder::new().module(AnswerPack).build();
+        assert_eq!(
+            session
+                .invoke_script("#Requires -Modules answers\nGet-Answer")
+                .unwrap(),
+            vec![Value::Number(42.0)]
+        );
+
+        let error = session
+            .invoke_script("#Requires -Modules Answers, Missing, Other\n'not run'")
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "The script cannot be run because the following modules that are specified by the \"#requires\" statements of the script are missing: Missing, Other."
+        );
+    }
+}
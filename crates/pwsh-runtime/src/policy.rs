@@ -0,0 +1,171 @@
+/// Execution policies: which script files a session lets run
+use crate::error::{ErrorCategory, ErrorRecord, RuntimeError};
+use std::fmt;
+use std::path::Path;
+
+/// Which script files may run, as in PowerShell. Only script files are
+/// checked; commands typed or handed to `Session::invoke` always run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ExecutionPolicy {
+    /// No script files run
+    Restricted,
+    /// Only signed script files run
+    AllSigned,
+    /// Script files from this computer run; remote ones only when signed
+    RemoteSigned,
+    /// Every script file runs. The default, as in PowerShell outside Windows.
+    #[default]
+    Unrestricted,
+    /// Nothing is checked
+    Bypass,
+}
+
+impl ExecutionPolicy {
+    /// Every policy, for looking one up by name
+    pub const ALL: [ExecutionPolicy; 5] = [
+        ExecutionPolicy::Restricted,
+        ExecutionPolicy::AllSigned,
+        ExecutionPolicy::RemoteSigned,
+        ExecutionPolicy::Unrestricted,
+        ExecutionPolicy::Bypass,
+    ];
+
+    /// The policy with this name, ignoring case: `remotesigned`
+    pub fn from_name(name: &str) -> Option<ExecutionPolicy> {
+        ExecutionPolicy::ALL
+            .into_iter()
+            .find(|policy| policy.to_string().eq_ignore_ascii_case(name))
+    }
+
+    /// Whether the script file at `path` with text `source` may run, asking
+    /// `trust` where it came from and whether it is signed
+    pub fn check(
+        self,
+        path: &Path,
+        source: &str,
+        trust: &dyn ScriptTrust,
+    ) -> Result<(), RuntimeError> {
+        let needs_signature = match self {
+            ExecutionPolicy::Unrestricted | ExecutionPolicy::Bypass => false,
+            ExecutionPolicy::AllSigned => true,
+            ExecutionPolicy::RemoteSigned => trust.is_remote(path),
+            ExecutionPolicy::Restricted => {
+                return Err(unauthorized(
+                    path,
+                    format!(
+                    "File {} cannot be loaded because running scripts is disabled on this system.",
+                    path.display()
+                ),
+                ))
+            }
+        };
+        if needs_signature && !trust.is_signed(path, source) {
+            return Err(unauthorized(
+                path,
+                format!(
+                    "File {} cannot be loaded. The file {} is not digitally signed. You cannot run this script on the current system.",
+                    path.display(),
+                    path.display()
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ExecutionPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+fn unauthorized(path: &Path, message: String) -> RuntimeError {
+    ErrorRecord::new(ErrorCategory::SecurityError, "UnauthorizedAccess", message)
+        .with_target(path.display())
+        .into()
+}
+
+/// What the host knows about a script file: whether it came from another
+/// computer and whether it carries a signature it trusts. The interpreter
+/// can't check signatures itself, so by default every file is local and
+/// unsigned.
+pub trait ScriptTrust {
+    /// Whether the file came from another computer, as a download does
+    fn is_remote(&self, _path: &Path) -> bool {
+        false
+    }
+
+    /// Whether the script is signed by a publisher the host trusts
+    fn is_signed(&self, _path: &Path, _source: &str) -> bool {
+        false
+    }
+}
+
+/// Every script file is local and unsigned
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LocalScripts;
+
+impl ScriptTrust for LocalScripts {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Files under `remote/` are remote, and scripts ending in a
+    /// signature block are signed
+    struct MarkedScripts;
+
+    impl ScriptTrust for MarkedScripts {
+        fn is_remote(&self, path: &Path) -> bool {
+            path.starts_with("remote")
+        }
+
+        fn is_signed(&self, _path: &Path, source: &str) -> bool {
+            source.ends_with("# SIG # End signature block")
+        }
+    }
+
+    fn allowed(policy: ExecutionPolicy, path: &str, source: &str) -> bool {
+        policy
+            .check(Path::new(path), source, &MarkedScripts)
+            .is_ok()
+    }
+
+    #[test]
+    fn test_policies() {
+        let signed = "'hi'\n# SIG # End signature block";
+        for policy in [ExecutionPolicy::Unrestricted, ExecutionPolicy::Bypass] {
+            assert!(allowed(policy, "remote/a.ps1", "'hi'"));
+        }
+        assert!(allowed(ExecutionPolicy::RemoteSigned, "a.ps1", "'hi'"));
+        assert!(!allowed(
+            ExecutionPolicy::RemoteSigned,
+            "remote/a.ps1",
+            "'hi'"
+        ));
+        assert!(allowed(
+            ExecutionPolicy::RemoteSigned,
+            "remote/a.ps1",
+            signed
+        ));
+        assert!(!allowed(ExecutionPolicy::AllSigned, "a.ps1", "'hi'"));
+        assert!(allowed(ExecutionPolicy::AllSigned, "a.ps1", signed));
+        assert!(!allowed(ExecutionPolicy::Restricted, "a.ps1", signed));
+
+        let error = ExecutionPolicy::Restricted
+            .check(Path::new("a.ps1"), "", &LocalScripts)
+            .unwrap_err();
+        assert_eq!(error.error_id(), "UnauthorizedAccess");
+        assert_eq!(error.category(), ErrorCategory::SecurityError);
+    }
+
+    #[test]
+    fn test_policy_names() {
+        assert_eq!(
+            ExecutionPolicy::from_name("remotesigned"),
+            Some(ExecutionPolicy::RemoteSigned)
+        );
+        assert_eq!(ExecutionPolicy::from_name("Sometimes"), None);
+        assert_eq!(ExecutionPolicy::AllSigned.to_string(), "AllSigned");
+    }
+}
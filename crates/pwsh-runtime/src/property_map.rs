@@ -0,0 +1,367 @@
+/// Property and parameter names, and the case-insensitive maps keyed by them
+///
+/// Pipelines build the same few property names ("Name", "Length") over and
+/// over, and look them up case-insensitively. A `PropertyName` keeps its text
+/// and its lowercase form in a process-wide intern table, so every object
+/// with a "Name" property shares the same two strings, and comparing names
+/// compares pointers. Lookups by `&str` lowercase on the fly without
+/// allocating.
+use crate::value::Value;
+use indexmap::{Equivalent, IndexMap};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashSet;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+
+/// The shared copy of `text`, added to the intern table the first time it is seen
+fn intern(text: &str) -> Arc<str> {
+    static NAMES: OnceLock<Mutex<HashSet<Arc<str>>>> = OnceLock::new();
+    let mut names = NAMES
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner);
+    if let Some(name) = names.get(text) {
+        return Arc::clone(name);
+    }
+    let name: Arc<str> = text.into();
+    names.insert(Arc::clone(&name));
+    name
+}
+
+/// Feed the lowercase form of `text` to a hasher, one byte at a time so that
+/// a name and a `&str` lookup hash the same
+fn hash_lowercase<H: Hasher>(text: &str, state: &mut H) {
+    for c in text.chars() {
+        if c.is_ascii() {
+            state.write_u8(c.to_ascii_lowercase() as u8);
+        } else {
+            let mut buf = [0; 4];
+            for lower in c.to_lowercase() {
+                for &b in lower.encode_utf8(&mut buf).as_bytes() {
+                    state.write_u8(b);
+                }
+            }
+        }
+    }
+    state.write_u8(0xff);
+}
+
+/// An interned property or parameter name, compared case-insensitively;
+/// displays (and dereferences to) the spelling it was created with
+#[derive(Clone)]
+pub struct PropertyName {
+    text: Arc<str>,
+    lowercase: Arc<str>,
+}
+
+impl PropertyName {
+    pub fn new(text: &str) -> Self {
+        let text = intern(text);
+        let lowercase = if text.chars().flat_map(char::to_lowercase).ne(text.chars()) {
+            intern(
+                &text
+                    .chars()
+                    .flat_map(char::to_lowercase)
+                    .collect::<String>(),
+            )
+        } else {
+            Arc::clone(&text)
+        };
+        PropertyName { text, lowercase }
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+}
+
+impl Deref for PropertyName {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.text
+    }
+}
+
+/// Both lowercase forms are interned, so equal names share one
+impl PartialEq for PropertyName {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.lowercase, &other.lowercase)
+    }
+}
+
+impl Eq for PropertyName {}
+
+impl Hash for PropertyName {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_lowercase(&self.lowercase, state);
+    }
+}
+
+impl PartialEq<str> for PropertyName {
+    fn eq(&self, other: &str) -> bool {
+        *self.text == *other
+    }
+}
+
+impl PartialEq<&str> for PropertyName {
+    fn eq(&self, other: &&str) -> bool {
+        *self.text == **other
+    }
+}
+
+impl fmt::Display for PropertyName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+impl fmt::Debug for PropertyName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&*self.text, f)
+    }
+}
+
+impl From<&str> for PropertyName {
+    fn from(text: &str) -> Self {
+        PropertyName::new(text)
+    }
+}
+
+impl From<String> for PropertyName {
+    fn from(text: String) -> Self {
+        PropertyName::new(&text)
+    }
+}
+
+impl From<&String> for PropertyName {
+    fn from(text: &String) -> Self {
+        PropertyName::new(text)
+    }
+}
+
+impl From<PropertyName> for String {
+    fn from(name: PropertyName) -> Self {
+        name.text.to_string()
+    }
+}
+
+/// String values made from names share the interned text
+impl From<PropertyName> for Arc<str> {
+    fn from(name: PropertyName) -> Self {
+        name.text
+    }
+}
+
+/// A `&str` looked up in a map of names, ignoring case
+struct Lookup<'a>(&'a str);
+
+impl Hash for Lookup<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_lowercase(self.0, state);
+    }
+}
+
+impl Equivalent<PropertyName> for Lookup<'_> {
+    fn equivalent(&self, key: &PropertyName) -> bool {
+        self.0
+            .chars()
+            .flat_map(char::to_lowercase)
+            .eq(key.lowercase.chars())
+    }
+}
+
+/// Properties of an object or hashtable, or the named parameters of a cmdlet
+/// call: kept in insertion order, with names matched case-insensitively.
+/// Setting an existing name keeps the spelling it was first added with.
+/// An object's map also carries its type names, as a PSObject's
+/// PSTypeNames, which pick how the console shows it.
+#[derive(Clone, Default, PartialEq)]
+pub struct PropertyMap {
+    entries: IndexMap<PropertyName, Value>,
+    /// Most specific first: `System.IO.FileInfo`, `System.IO.FileSystemInfo`
+    type_names: Vec<String>,
+}
+
+impl PropertyMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        PropertyMap {
+            entries: IndexMap::with_capacity(capacity),
+            type_names: Vec::new(),
+        }
+    }
+
+    /// The object's type names, most specific first; empty for a plain
+    /// hashtable or custom object
+    pub fn type_names(&self) -> &[String] {
+        &self.type_names
+    }
+
+    /// Make `name` the object's most specific type name
+    pub fn insert_type_name(&mut self, name: &str) {
+        self.type_names.retain(|existing| existing != name);
+        self.type_names.insert(0, name.to_string());
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.entries.get(&Lookup(name))
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut Value> {
+        self.entries.get_mut(&Lookup(name))
+    }
+
+    pub fn contains_key(&self, name: &str) -> bool {
+        self.entries.contains_key(&Lookup(name))
+    }
+
+    /// The stored spelling of a name
+    pub fn get_key(&self, name: &str) -> Option<&PropertyName> {
+        self.entries
+            .get_key_value(&Lookup(name))
+            .map(|(key, _)| key)
+    }
+
+    /// The name and value at a position in insertion order
+    pub fn get_index(&self, index: usize) -> Option<(&PropertyName, &Value)> {
+        self.entries.get_index(index)
+    }
+
+    /// Add or replace a value, returning the one it replaced
+    pub fn insert(&mut self, name: impl Into<PropertyName>, value: Value) -> Option<Value> {
+        self.entries.insert(name.into(), value)
+    }
+
+    /// Remove a value, keeping the order of the rest
+    pub fn shift_remove(&mut self, name: &str) -> Option<Value> {
+        self.entries.shift_remove(&Lookup(name))
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn keys(&self) -> indexmap::map::Keys<'_, PropertyName, Value> {
+        self.entries.keys()
+    }
+
+    pub fn values(&self) -> indexmap::map::Values<'_, PropertyName, Value> {
+        self.entries.values()
+    }
+
+    pub fn iter(&self) -> indexmap::map::Iter<'_, PropertyName, Value> {
+        self.entries.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> indexmap::map::IterMut<'_, PropertyName, Value> {
+        self.entries.iter_mut()
+    }
+}
+
+impl fmt::Debug for PropertyMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.entries.iter()).finish()
+    }
+}
+
+impl<K: Into<PropertyName>> FromIterator<(K, Value)> for PropertyMap {
+    fn from_iter<I: IntoIterator<Item = (K, Value)>>(iter: I) -> Self {
+        let mut map = PropertyMap::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: Into<PropertyName>> Extend<(K, Value)> for PropertyMap {
+    fn extend<I: IntoIterator<Item = (K, Value)>>(&mut self, iter: I) {
+        for (name, value) in iter {
+            self.insert(name, value);
+        }
+    }
+}
+
+impl<K: Into<PropertyName>, const N: usize> From<[(K, Value); N]> for PropertyMap {
+    fn from(entries: [(K, Value); N]) -> Self {
+        entries.into_iter().collect()
+    }
+}
+
+impl IntoIterator for PropertyMap {
+    type Item = (PropertyName, Value);
+    type IntoIter = indexmap::map::IntoIter<PropertyName, Value>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a PropertyMap {
+    type Item = (&'a PropertyName, &'a Value);
+    type IntoIter = indexmap::map::Iter<'a, PropertyName, Value>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.iter()
+    }
+}
+
+impl Serialize for PropertyMap {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.iter().map(|(name, value)| (name.as_str(), value)))
+    }
+}
+
+impl<'de> Deserialize<'de> for PropertyMap {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let entries = IndexMap::<String, Value>::deserialize(deserializer)?;
+        Ok(entries.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names_are_interned_and_compare_ignoring_case() {
+        let a = PropertyName::new("Length");
+        let b = PropertyName::new("LENGTH");
+        assert_eq!(a, b);
+        assert_eq!(a.as_str(), "Length");
+        assert!(Arc::ptr_eq(&a.lowercase, &b.lowercase));
+        assert!(Arc::ptr_eq(&a.text, &PropertyName::new("Length").text));
+        assert_ne!(a, PropertyName::new("Name"));
+    }
+
+    #[test]
+    fn test_map_lookups_ignore_case_and_keep_the_first_spelling() {
+        let mut map = PropertyMap::from([("Name", Value::Number(1.0))]);
+        map.insert("ÄPFEL", Value::Number(2.0));
+
+        assert_eq!(map.get("NAME"), Some(&Value::Number(1.0)));
+        assert_eq!(map.get("äpfel"), Some(&Value::Number(2.0)));
+        assert!(!map.contains_key("Nam"));
+
+        map.insert("name", Value::Number(3.0));
+        assert_eq!(map.len(), 2);
+        assert_eq!(map.get_key("NAME").unwrap().as_str(), "Name");
+        assert_eq!(map.shift_remove("NaMe"), Some(Value::Number(3.0)));
+        assert_eq!(
+            map.keys().map(|k| k.as_str()).collect::<Vec<_>>(),
+            ["ÄPFEL"]
+        );
+    }
+}
@@ -0,0 +1,397 @@
+/// `$PSStyle`: the ANSI escape sequences scripts and the console style text with
+use crate::ansi::OutputRendering;
+use crate::property_map::{PropertyMap, PropertyName};
+use crate::value::Value;
+
+/// Ends any styling: `$PSStyle.Reset`
+pub const RESET: &str = "\x1b[0m";
+
+/// The text accents, each with the sequence that turns it on and the one
+/// that turns it off again (`$PSStyle.Bold`, `$PSStyle.BoldOff`)
+const ACCENTS: [(&str, &str, &str); 8] = [
+    ("Bold", "\x1b[1m", "\x1b[22m"),
+    ("Dim", "\x1b[2m", "\x1b[22m"),
+    ("Italic", "\x1b[3m", "\x1b[23m"),
+    ("Underline", "\x1b[4m", "\x1b[24m"),
+    ("Blink", "\x1b[5m", "\x1b[25m"),
+    ("Reverse", "\x1b[7m", "\x1b[27m"),
+    ("Hidden", "\x1b[8m", "\x1b[28m"),
+    ("Strikethrough", "\x1b[9m", "\x1b[29m"),
+];
+
+/// The eight colors of `$PSStyle.Foreground` and `.Background`, in the
+/// order of their codes; each has a Bright variant too
+const COLORS: [&str; 8] = [
+    "Black", "Red", "Green", "Yellow", "Blue", "Magenta", "Cyan", "White",
+];
+
+/// The console colors Write-Host's -ForegroundColor and -BackgroundColor
+/// take, with their foreground codes
+const CONSOLE_COLORS: [(&str, u8); 16] = [
+    ("Black", 30),
+    ("DarkRed", 31),
+    ("DarkGreen", 32),
+    ("DarkYellow", 33),
+    ("DarkBlue", 34),
+    ("DarkMagenta", 35),
+    ("DarkCyan", 36),
+    ("Gray", 37),
+    ("DarkGray", 90),
+    ("Red", 91),
+    ("Green", 92),
+    ("Yellow", 93),
+    ("Blue", 94),
+    ("Magenta", 95),
+    ("Cyan", 96),
+    ("White", 97),
+];
+
+/// The sequence that turns console color `name` (ignoring case) on, as
+/// the text's color or, with `background`, behind it
+pub fn console_color(name: &str, background: bool) -> Option<String> {
+    CONSOLE_COLORS
+        .iter()
+        .find(|(color, _)| color.eq_ignore_ascii_case(name))
+        .map(|(_, code)| format!("\x1b[{}m", code + if background { 10 } else { 0 }))
+}
+
+/// Foreground (from code 30) or background (from 40) colors by name
+fn colors(base: u8) -> Value {
+    let mut map = PropertyMap::new();
+    for (i, name) in (0u8..).zip(COLORS) {
+        map.insert(name, Value::String(format!("\x1b[{}m", base + i).into()));
+    }
+    for (i, name) in (0u8..).zip(COLORS) {
+        map.insert(
+            format!("Bright{}", name).as_str(),
+            Value::String(format!("\x1b[{}m", base + 60 + i).into()),
+        );
+    }
+    Value::Object(map.into())
+}
+
+/// How the console styles parts of its output, as `$PSStyle.Formatting`
+/// says; an empty sequence leaves them unstyled
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormattingStyle {
+    /// The labels of list views
+    pub format_accent: String,
+    /// The header of table views
+    pub table_header: String,
+    pub error_accent: String,
+    pub error: String,
+    pub warning: String,
+    pub verbose: String,
+    pub debug: String,
+}
+
+impl Default for FormattingStyle {
+    /// PowerShell's: accents bold green, errors bold red, the other
+    /// streams bold yellow
+    fn default() -> Self {
+        FormattingStyle {
+            format_accent: "\x1b[32;1m".to_string(),
+            table_header: "\x1b[32;1m".to_string(),
+            error_accent: "\x1b[36;1m".to_string(),
+            error: "\x1b[31;1m".to_string(),
+            warning: "\x1b[33;1m".to_string(),
+            verbose: "\x1b[33;1m".to_string(),
+            debug: "\x1b[33;1m".to_string(),
+        }
+    }
+}
+
+impl FormattingStyle {
+    fn fields(&self) -> [(&'static str, &String); 7] {
+        [
+            ("FormatAccent", &self.format_accent),
+            ("TableHeader", &self.table_header),
+            ("ErrorAccent", &self.error_accent),
+            ("Error", &self.error),
+            ("Warning", &self.warning),
+            ("Verbose", &self.verbose),
+            ("Debug", &self.debug),
+        ]
+    }
+
+    /// `$PSStyle.Formatting` as scripts see it
+    pub fn to_value(&self) -> Value {
+        let map: PropertyMap = self
+            .fields()
+            .into_iter()
+            .map(|(name, style)| {
+                (
+                    PropertyName::new(name),
+                    Value::String(style.as_str().into()),
+                )
+            })
+            .collect();
+        Value::Object(map.into())
+    }
+
+    /// The styles in `$PSStyle.Formatting` as a script left it; a style
+    /// that is missing or `$null` is empty
+    pub fn from_value(value: &Value) -> Self {
+        let text = |name: &str| match value.get_property(name) {
+            Some(Value::Null) | None => String::new(),
+            Some(style) => style.to_string(),
+        };
+        FormattingStyle {
+            format_accent: text("FormatAccent"),
+            table_header: text("TableHeader"),
+            error_accent: text("ErrorAccent"),
+            error: text("Error"),
+            warning: text("Warning"),
+            verbose: text("Verbose"),
+            debug: text("Debug"),
+        }
+    }
+}
+
+/// `text` in `style`, reset after it; text without a style is left alone
+pub fn styled(style: &str, text: &str) -> String {
+    if style.is_empty() || text.is_empty() {
+        text.to_string()
+    } else {
+        format!("{}{}{}", style, text, RESET)
+    }
+}
+
+/// How the console's file listing styles names, as `$PSStyle.FileInfo`
+/// says; an empty sequence leaves a name unstyled
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileInfoStyle {
+    pub directory: String,
+    pub symbolic_link: String,
+    pub executable: String,
+    /// Styles for files by extension (`.zip`), matched ignoring case
+    pub extensions: Vec<(String, String)>,
+}
+
+impl Default for FileInfoStyle {
+    /// PowerShell's: directories bold on blue, links bold cyan, executables
+    /// bold green, archives bold red and PowerShell files bold yellow
+    fn default() -> Self {
+        let archive = "\x1b[31;1m";
+        let powershell = "\x1b[33;1m";
+        FileInfoStyle {
+            directory: "\x1b[44;1m".to_string(),
+            symbolic_link: "\x1b[36;1m".to_string(),
+            executable: "\x1b[32;1m".to_string(),
+            extensions: [
+                (".zip", archive),
+                (".tgz", archive),
+                (".gz", archive),
+                (".tar", archive),
+                (".nupkg", archive),
+                (".cab", archive),
+                (".7z", archive),
+                (".ps1", powershell),
+                (".psd1", powershell),
+                (".psm1", powershell),
+                (".ps1xml", powershell),
+            ]
+            .into_iter()
+            .map(|(extension, style)| (extension.to_string(), style.to_string()))
+            .collect(),
+        }
+    }
+}
+
+impl FileInfoStyle {
+    /// `$PSStyle.FileInfo` as scripts see it
+    pub fn to_value(&self) -> Value {
+        let extensions: PropertyMap = self
+            .extensions
+            .iter()
+            .map(|(extension, style)| {
+                (
+                    PropertyName::new(extension),
+                    Value::String(style.as_str().into()),
+                )
+            })
+            .collect();
+        Value::Object(
+            PropertyMap::from([
+                ("Directory", Value::String(self.directory.as_str().into())),
+                (
+                    "SymbolicLink",
+                    Value::String(self.symbolic_link.as_str().into()),
+                ),
+                ("Executable", Value::String(self.executable.as_str().into())),
+                ("Extension", Value::Object(extensions.into())),
+            ])
+            .into(),
+        )
+    }
+
+    /// The styles in `$PSStyle.FileInfo` as a script left it; a style
+    /// that is missing or `$null` is empty
+    pub fn from_value(value: &Value) -> Self {
+        let text = |name: &str| match value.get_property(name) {
+            Some(Value::Null) | None => String::new(),
+            Some(style) => style.to_string(),
+        };
+        let extensions = value
+            .get_property("Extension")
+            .and_then(|extensions| {
+                extensions.properties().map(|map| {
+                    map.iter()
+                        .map(|(extension, style)| (extension.to_string(), style.to_string()))
+                        .collect()
+                })
+            })
+            .unwrap_or_default();
+        FileInfoStyle {
+            directory: text("Directory"),
+            symbolic_link: text("SymbolicLink"),
+            executable: text("Executable"),
+            extensions,
+        }
+    }
+
+    /// The style for a Get-ChildItem item's name: a link's, a directory's,
+    /// its extension's or an executable's, in that order
+    pub fn style_for(&self, item: &Value) -> &str {
+        let is = |name: &str| item.get_property(name).is_some_and(|value| value.to_bool());
+        if item
+            .get_property("LinkType")
+            .is_some_and(|link| link != Value::Null)
+        {
+            return &self.symbolic_link;
+        }
+        if is("Directory") {
+            return &self.directory;
+        }
+        let extension = item
+            .get_property("Extension")
+            .map(|extension| extension.to_string())
+            .unwrap_or_default();
+        if let Some((_, style)) = self
+            .extensions
+            .iter()
+            .find(|(known, _)| !extension.is_empty() && known.eq_ignore_ascii_case(&extension))
+        {
+            return style;
+        }
+        let executable = item
+            .get_property("Mode")
+            .is_some_and(|mode| mode.to_string().contains('x'));
+        if executable {
+            return &self.executable;
+        }
+        ""
+    }
+
+    /// `text` in the style for `item`, reset after it
+    pub fn apply(&self, item: &Value, text: &str) -> String {
+        styled(self.style_for(item), text)
+    }
+}
+
+/// The `$PSStyle` object a session starts with, its OutputRendering
+/// `rendering`
+pub fn ps_style(rendering: OutputRendering) -> Value {
+    let mut map = PropertyMap::from([
+        ("Reset", Value::String(RESET.into())),
+        ("OutputRendering", Value::String(rendering.name().into())),
+    ]);
+    for (name, on, off) in ACCENTS {
+        map.insert(name, Value::String(on.into()));
+        map.insert(format!("{}Off", name).as_str(), Value::String(off.into()));
+    }
+    map.insert("Formatting", FormattingStyle::default().to_value());
+    map.insert("Foreground", colors(30));
+    map.insert("Background", colors(40));
+    map.insert("FileInfo", FileInfoStyle::default().to_value());
+    Value::Object(map.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(name: &str, mode: &str, directory: bool, link: bool) -> Value {
+        let extension = name.rfind('.').map(|i| &name[i..]).unwrap_or("");
+        Value::Object(
+            PropertyMap::from([
+                ("Name", Value::String(name.into())),
+                ("Extension", Value::String(extension.into())),
+                ("Mode", Value::String(mode.into())),
+                ("Directory", Value::Boolean(directory)),
+                (
+                    "LinkType",
+                    if link {
+                        Value::String("SymbolicLink".into())
+                    } else {
+                        Value::Null
+                    },
+                ),
+            ])
+            .into(),
+        )
+    }
+
+    #[test]
+    fn test_file_names_are_styled_by_kind() {
+        let style = FileInfoStyle::default();
+        let dir = item("src", "drwxr-xr-x", true, false);
+        assert_eq!(style.apply(&dir, "src"), "\x1b[44;1msrc\x1b[0m");
+        let link = item("latest", "lrwxrwxrwx", true, true);
+        assert_eq!(style.style_for(&link), "\x1b[36;1m");
+        let script = item("build.PS1", "-rwxr-xr-x", false, false);
+        assert_eq!(style.style_for(&script), "\x1b[33;1m");
+        let tool = item("run", "-rwxr-xr-x", false, false);
+        assert_eq!(style.style_for(&tool), "\x1b[32;1m");
+        let text = item("notes.txt", "-rw-r--r--", false, false);
+        assert_eq!(style.apply(&text, "notes.txt"), "notes.txt");
+    }
+
+    #[test]
+    fn test_ps_style_has_colors_and_accents() {
+        let style = ps_style(OutputRendering::PlainText);
+        let get = |path: &[&str]| {
+            path.iter()
+                .fold(style.clone(), |value, name| {
+                    value.get_property(name).unwrap()
+                })
+                .to_string()
+        };
+        assert_eq!(get(&["Bold"]), "\x1b[1m");
+        assert_eq!(get(&["StrikethroughOff"]), "\x1b[29m");
+        assert_eq!(get(&["Foreground", "Red"]), "\x1b[31m");
+        assert_eq!(get(&["Foreground", "BrightCyan"]), "\x1b[96m");
+        assert_eq!(get(&["Background", "BrightWhite"]), "\x1b[107m");
+        assert_eq!(get(&["Formatting", "TableHeader"]), "\x1b[32;1m");
+        assert_eq!(get(&["OutputRendering"]), "PlainText");
+    }
+
+    #[test]
+    fn test_console_colors() {
+        assert_eq!(console_color("darkred", false).unwrap(), "\x1b[31m");
+        assert_eq!(console_color("White", true).unwrap(), "\x1b[107m");
+        assert_eq!(console_color("Pink", false), None);
+        assert_eq!(styled("", "text"), "text");
+        assert_eq!(styled("\x1b[1m", "text"), "\x1b[1mtext\x1b[0m");
+    }
+
+    #[test]
+    fn test_formatting_round_trips_through_its_value() {
+        let style = FormattingStyle::default();
+        assert_eq!(FormattingStyle::from_value(&style.to_value()), style);
+    }
+
+    #[test]
+    fn test_file_info_round_trips_through_its_value() {
+        let style = FileInfoStyle::default();
+        assert_eq!(FileInfoStyle::from_value(&style.to_value()), style);
+
+        let value = style.to_value();
+        value
+            .set_property("Directory", Value::String("".into()))
+            .unwrap();
+        let changed = FileInfoStyle::from_value(&value);
+        let dir = item("src", "drwxr-xr-x", true, false);
+        assert_eq!(changed.apply(&dir, "src"), "src");
+    }
+}
@@ -0,0 +1,109 @@
+/// The session's random number generator, behind Get-Random
+use uuid::Uuid;
+
+/// A small seedable random number generator (xoshiro256**). A session
+/// starts with a random seed; seeding it makes a script's random choices
+/// repeat from run to run. It is not suitable for cryptography.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Random {
+    state: [u64; 4],
+}
+
+impl Random {
+    /// A generator whose numbers follow from `seed`
+    pub fn seeded(seed: u64) -> Self {
+        // Spread the seed over the whole state with splitmix64, so that
+        // nearby seeds give unrelated sequences
+        let mut x = seed;
+        let mut next = || {
+            x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z = x;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            z ^ (z >> 31)
+        };
+        Random {
+            state: [next(), next(), next(), next()],
+        }
+    }
+
+    /// A generator with an unpredictable seed
+    pub fn from_entropy() -> Self {
+        let (high, low) = Uuid::new_v4().as_u64_pair();
+        Self::seeded(high ^ low.rotate_left(32))
+    }
+
+    /// The next 64 random bits
+    pub fn next_u64(&mut self) -> u64 {
+        let s = &mut self.state;
+        let result = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = s[1] << 17;
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+        result
+    }
+
+    /// A number in `[0, 1)`
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+
+    /// A whole number in `[0, bound)`, without the bias of a plain modulo.
+    /// A bound of 0 gives 0.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        if bound == 0 {
+            return 0;
+        }
+        // Reject the top partial block of values so every result is equally likely
+        let zone = u64::MAX - u64::MAX % bound;
+        loop {
+            let value = self.next_u64();
+            if value < zone {
+                return value % bound;
+            }
+        }
+    }
+
+    /// Put `items` in a random order
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.below(i as u64 + 1) as usize;
+            items.swap(i, j);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_sequence() {
+        let mut a = Random::seeded(42);
+        let mut b = Random::seeded(42);
+        let first: Vec<u64> = (0..5).map(|_| a.next_u64()).collect();
+        assert_eq!(first, (0..5).map(|_| b.next_u64()).collect::<Vec<_>>());
+        assert_ne!(Random::seeded(43).next_u64(), first[0]);
+    }
+
+    #[test]
+    fn test_ranges_and_shuffle() {
+        let mut random = Random::seeded(7);
+        for _ in 0..1000 {
+            let x = random.next_f64();
+            assert!((0.0..1.0).contains(&x));
+            assert!(random.below(6) < 6);
+        }
+        assert_eq!(random.below(0), 0);
+
+        let mut items: Vec<u32> = (0..20).collect();
+        random.shuffle(&mut items);
+        assert_ne!(items, (0..20).collect::<Vec<_>>());
+        items.sort();
+        assert_eq!(items, (0..20).collect::<Vec<_>>());
+    }
+}
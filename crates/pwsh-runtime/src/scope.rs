@@ -1,11 +1,14 @@
 /// Scope management for variable storage
-use crate::value::Value;
+use crate::value::{Function, Value};
 use std::collections::HashMap;
 
 /// A single scope containing variable bindings
 #[derive(Debug, Clone)]
 pub struct Scope {
     variables: HashMap<String, Value>,
+    /// The body of an if or foreach rather than a function, script block or
+    /// script; numbered scope references (`-Scope 1`) pass over it
+    block: bool,
 }
 
 impl Scope {
@@ -13,6 +16,7 @@ impl Scope {
     pub fn new() -> Self {
         Scope {
             variables: HashMap::new(),
+            block: false,
         }
     }
 
@@ -37,6 +41,14 @@ impl Scope {
             .and_then(|key| self.variables.get(key))
     }
 
+    /// The functions defined in this scope
+    pub fn functions(&self) -> impl Iterator<Item = &Function> {
+        self.variables.values().filter_map(|value| match value {
+            Value::Function(func) => Some(func.as_ref()),
+            _ => None,
+        })
+    }
+
     /// Set a variable in this scope (case-insensitive for PowerShell compatibility)
     pub fn set(&mut self, name: &str, value: Value) {
         // Try exact match first
@@ -73,10 +85,28 @@ impl Default for Scope {
     }
 }
 
+/// The automatic constants $true, $false and $null. Every scope sees them
+/// and none can hide or change them.
+pub fn constant(name: &str) -> Option<Value> {
+    match name.to_ascii_lowercase().as_str() {
+        "true" => Some(Value::Boolean(true)),
+        "false" => Some(Value::Boolean(false)),
+        "null" => Some(Value::Null),
+        _ => None,
+    }
+}
+
 /// Stack of scopes for nested contexts (functions, blocks, etc.)
 #[derive(Debug)]
 pub struct ScopeStack {
     scopes: Vec<Scope>,
+    /// Indices of the scopes a script file or module runs in, innermost
+    /// last; `$script:` names the innermost one, or the global scope when
+    /// no script is running (input typed at the prompt)
+    script_scopes: Vec<usize>,
+    /// The Set-PSDebug trace levels set, innermost last: the index of the
+    /// scope each was set in, and the level
+    traces: Vec<(usize, u32)>,
 }
 
 impl ScopeStack {
@@ -84,6 +114,8 @@ impl ScopeStack {
     pub fn new() -> Self {
         ScopeStack {
             scopes: vec![Scope::new()],
+            script_scopes: Vec::new(),
+            traces: Vec::new(),
         }
     }
 
@@ -92,18 +124,148 @@ impl ScopeStack {
         self.scopes.push(Scope::new());
     }
 
+    /// Push the scope of an if or foreach body: it holds the variables
+    /// created in it, but doesn't count as a scope of its own for numbered
+    /// scope references
+    pub fn push_block_scope(&mut self) {
+        self.scopes.push(Scope {
+            block: true,
+            ..Scope::new()
+        });
+    }
+
+    /// Push the scope a script runs in; it is the script's `$script:` scope
+    /// until it is popped
+    pub fn push_script_scope(&mut self) {
+        self.script_scopes.push(self.scopes.len());
+        self.push_scope();
+    }
+
     /// Pop the current scope from the stack
     /// Returns None if trying to pop the global scope
     pub fn pop_scope(&mut self) -> Option<Scope> {
         if self.scopes.len() > 1 {
-            self.scopes.pop()
+            let scope = self.scopes.pop();
+            self.forget_popped_script_scopes();
+            scope
         } else {
             None // Don't allow popping the global scope
         }
     }
 
+    /// Pop scopes until `depth` are left, e.g. those an error unwound past
+    /// without popping; the global scope always stays
+    pub fn truncate(&mut self, depth: usize) {
+        self.scopes.truncate(depth.max(1));
+        self.forget_popped_script_scopes();
+    }
+
+    fn forget_popped_script_scopes(&mut self) {
+        while self
+            .script_scopes
+            .last()
+            .is_some_and(|&index| index >= self.scopes.len())
+        {
+            self.script_scopes.pop();
+        }
+        while self
+            .traces
+            .last()
+            .is_some_and(|&(index, _)| index >= self.scopes.len())
+        {
+            self.traces.pop();
+        }
+    }
+
+    /// The trace level in force: the one set in the innermost scope that
+    /// set one, or 0 (off)
+    pub fn trace(&self) -> u32 {
+        self.traces.last().map_or(0, |&(_, level)| level)
+    }
+
+    /// Set the trace level for the local scope and the scopes it creates;
+    /// when the scope ends, the level set outside it applies again
+    pub fn set_trace(&mut self, level: u32) {
+        let index = self.local_index();
+        match self.traces.last_mut() {
+            Some(last) if last.0 == index => last.1 = level,
+            _ => self.traces.push((index, level)),
+        }
+    }
+
+    /// Index of the scope `$script:` refers to
+    fn script_index(&self) -> usize {
+        self.script_scopes.last().copied().unwrap_or(0)
+    }
+
+    /// Index of the scope `$local:` refers to: the innermost one that isn't
+    /// an if or foreach body
+    fn local_index(&self) -> usize {
+        self.scopes
+            .iter()
+            .rposition(|scope| !scope.block)
+            .unwrap_or(0)
+    }
+
+    /// Find a scope the way `-Scope` names it: `Global`, `Local`, `Script`, or
+    /// a number counting outward from the current scope (0) to its parent (1)
+    /// and on up to the global scope. Returns its index, or None when the name
+    /// isn't a scope or the number is past the global scope.
+    pub fn scope_index(&self, scope: &str) -> Option<usize> {
+        let scope = scope.trim();
+        if scope.eq_ignore_ascii_case("global") {
+            return Some(0);
+        }
+        if scope.eq_ignore_ascii_case("local") {
+            return Some(self.local_index());
+        }
+        if scope.eq_ignore_ascii_case("script") {
+            return Some(self.script_index());
+        }
+        let up: usize = scope.parse().ok()?;
+        self.scopes
+            .iter()
+            .enumerate()
+            .rev()
+            .filter(|(_, scope)| !scope.block)
+            .nth(up)
+            .map(|(index, _)| index)
+    }
+
+    /// The scope at `index` and the if or foreach bodies open inside it,
+    /// innermost last: the variables that scope holds itself
+    fn frame(&self, index: usize) -> &[Scope] {
+        let Some(after) = self.scopes.get(index + 1..) else {
+            return &[];
+        };
+        let end = after
+            .iter()
+            .position(|scope| !scope.block)
+            .map_or(self.scopes.len(), |n| index + 1 + n);
+        &self.scopes[index..end]
+    }
+
+    /// Get a variable from the scope at `index` itself, not the ones it is
+    /// nested in
+    pub fn get_variable_in(&self, index: usize, name: &str) -> Option<Value> {
+        self.frame(index)
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name).cloned())
+    }
+
+    /// Create or update a variable in the scope at `index` itself
+    pub fn set_variable_in(&mut self, index: usize, name: &str, value: Value) {
+        if let Some(scope) = self.scopes.get_mut(index) {
+            scope.set(name, value);
+        }
+    }
+
     /// Get a variable, searching from innermost to outermost scope
     pub fn get_variable(&self, name: &str) -> Option<Value> {
+        if let Some(value) = constant(name) {
+            return Some(value);
+        }
         // Search from innermost (last) to outermost (first)
         for scope in self.scopes.iter().rev() {
             if let Some(value) = scope.get(name) {
@@ -140,7 +302,7 @@ impl ScopeStack {
     }
 
     /// Parse a variable name into scope qualifier and base name
-    /// Returns (scope_qualifier, base_name) where scope_qualifier is Some("global"|"local"|"script") or None
+    /// Returns (scope_qualifier, base_name) where scope_qualifier is Some("global"|"local"|"script"|"env") or None
     /// The qualifier is normalized to lowercase for consistent matching
     fn parse_scope_qualifier(name: &str) -> (Option<String>, &str) {
         if let Some(colon_pos) = name.find(':') {
@@ -157,7 +319,7 @@ impl ScopeStack {
 
             // Only recognize valid scope qualifiers
             match qualifier_lower.as_str() {
-                "global" | "local" | "script" => (Some(qualifier_lower), base_name),
+                "global" | "local" | "script" | "env" => (Some(qualifier_lower), base_name),
                 _ => (None, name), // Invalid qualifier, treat as regular variable name
             }
         } else {
@@ -166,23 +328,34 @@ impl ScopeStack {
     }
 
     /// Get a variable with scope qualifier support
-    /// Supports $global:x, $local:y, $script:z
+    /// Supports $global:x, $local:y, $script:z, and $env:NAME for the
+    /// process environment
     pub fn get_variable_qualified(&self, name: &str) -> Option<Value> {
         let (qualifier, base_name) = Self::parse_scope_qualifier(name);
+        if qualifier.as_deref() != Some("env") {
+            if let Some(value) = constant(base_name) {
+                return Some(value);
+            }
+        }
 
         match qualifier.as_deref() {
+            Some("env") => std::env::var(base_name)
+                .ok()
+                .map(|value| Value::String(value.into())),
             Some("global") => {
                 // Get from global scope (first scope)
                 self.scopes.first()?.get(base_name).cloned()
             }
             Some("local") => {
-                // Get from current/local scope (last scope)
-                self.scopes.last()?.get(base_name).cloned()
+                // Get from current/local scope
+                self.scopes.get(self.local_index())?.get(base_name).cloned()
             }
             Some("script") => {
-                // For now, treat script scope as global scope
-                // In a full implementation, script scope would be the top-level scope of the current script file
-                self.scopes.first()?.get(base_name).cloned()
+                // Get from the scope of the script being run
+                self.scopes
+                    .get(self.script_index())?
+                    .get(base_name)
+                    .cloned()
             }
             _ => {
                 // No qualifier or invalid qualifier - use normal lookup
@@ -192,11 +365,17 @@ impl ScopeStack {
     }
 
     /// Set a variable with scope qualifier support
-    /// Supports $global:x = value, $local:y = value, $script:z = value
+    /// Supports $global:x = value, $local:y = value, $script:z = value, and
+    /// $env:NAME = value, which sets (or, with $null, removes) an
+    /// environment variable of the process
     pub fn set_variable_qualified(&mut self, name: &str, value: Value) {
         let (qualifier, base_name) = Self::parse_scope_qualifier(name);
 
         match qualifier.as_deref() {
+            Some("env") => match value {
+                Value::Null => std::env::remove_var(base_name),
+                value => std::env::set_var(base_name, value.to_string()),
+            },
             Some("global") => {
                 // Set in global scope (first scope)
                 if let Some(global_scope) = self.scopes.first_mut() {
@@ -204,16 +383,12 @@ impl ScopeStack {
                 }
             }
             Some("local") => {
-                // Set in current/local scope (last scope)
-                if let Some(local_scope) = self.scopes.last_mut() {
-                    local_scope.set(base_name, value);
-                }
+                // Set in current/local scope
+                self.set_variable_in(self.local_index(), base_name, value);
             }
             Some("script") => {
-                // For now, treat script scope as global scope
-                if let Some(global_scope) = self.scopes.first_mut() {
-                    global_scope.set(base_name, value);
-                }
+                // Set in the scope of the script being run
+                self.set_variable_in(self.script_index(), base_name, value);
             }
             _ => {
                 // No qualifier or invalid qualifier - use normal set
@@ -222,6 +397,76 @@ impl ScopeStack {
         }
     }
 
+    /// Names of all user-defined functions visible from the current scope
+    pub fn function_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .functions()
+            .into_iter()
+            .map(|func| func.name.clone())
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// All user-defined functions visible from the current scope
+    pub fn functions(&self) -> Vec<&Function> {
+        self.scopes
+            .iter()
+            .flat_map(|scope| scope.variables.values())
+            .filter_map(|value| match value {
+                Value::Function(func) => Some(func.as_ref()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Names of all variables (not functions) visible from the current scope, sorted
+    pub fn variable_names(&self) -> Vec<String> {
+        Self::names_in(&self.scopes)
+    }
+
+    /// Names of the variables (not functions) in the scope at `index`
+    /// itself, sorted
+    pub fn variable_names_in(&self, index: usize) -> Vec<String> {
+        Self::names_in(self.frame(index))
+    }
+
+    fn names_in(scopes: &[Scope]) -> Vec<String> {
+        let mut names: Vec<String> = scopes
+            .iter()
+            .flat_map(|scope| scope.variables.iter())
+            .filter(|(_, value)| !matches!(value, Value::Function(_)))
+            .map(|(name, _)| name.clone())
+            .collect();
+        names.sort_by_key(|name| name.to_lowercase());
+        names.dedup_by(|a, b| a.eq_ignore_ascii_case(b));
+        names
+    }
+
+    /// The variables (not functions) of the local scope and the if or foreach
+    /// bodies inside it, sorted by name: what a closure made here captures
+    pub fn local_variables(&self) -> Vec<(String, Value)> {
+        let mut variables: Vec<(String, Value)> = Vec::new();
+        for scope in self.scopes[self.local_index()..].iter().rev() {
+            for (name, value) in &scope.variables {
+                let shadowed = variables.iter().any(|(n, _)| n.eq_ignore_ascii_case(name));
+                if !shadowed && !matches!(value, Value::Function(_)) {
+                    variables.push((name.clone(), value.clone()));
+                }
+            }
+        }
+        variables.sort_by_key(|(name, _)| name.to_lowercase());
+        variables
+    }
+
+    /// Every variable's value in every scope, shadowed ones included
+    pub(crate) fn values(&self) -> impl Iterator<Item = &Value> {
+        self.scopes
+            .iter()
+            .flat_map(|scope| scope.variables.values())
+    }
+
     /// Get the depth of the scope stack
     pub fn depth(&self) -> usize {
         self.scopes.len()
@@ -408,7 +653,7 @@ mod tests {
     fn test_script_scope_qualifier() {
         let mut stack = ScopeStack::new();
 
-        // Script scope currently behaves like global scope
+        // With no script running, the script scope is the global scope
         stack.set_variable_qualified("script:z", Value::Number(100.0));
         assert_eq!(
             stack.get_variable_qualified("script:z"),
@@ -420,6 +665,92 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_script_scope_is_the_running_scripts_own() {
+        let mut stack = ScopeStack::new();
+        stack.push_script_scope();
+        stack.push_scope(); // a function the script calls
+
+        stack.set_variable_qualified("script:counter", Value::Number(1.0));
+        stack.pop_scope();
+        assert_eq!(
+            stack.get_variable_qualified("local:counter"),
+            Some(Value::Number(1.0))
+        );
+        assert_eq!(stack.get_variable_qualified("global:counter"), None);
+
+        // A script run by the script has a script scope of its own
+        stack.push_script_scope();
+        assert_eq!(stack.get_variable_qualified("script:counter"), None);
+        stack.pop_scope();
+        assert_eq!(
+            stack.get_variable_qualified("script:counter"),
+            Some(Value::Number(1.0))
+        );
+
+        // Once the script ends, $script: is the global scope again
+        stack.truncate(1);
+        assert_eq!(stack.get_variable_qualified("script:counter"), None);
+        stack.set_variable_qualified("script:counter", Value::Number(2.0));
+        assert_eq!(
+            stack.get_variable_qualified("global:counter"),
+            Some(Value::Number(2.0))
+        );
+    }
+
+    #[test]
+    fn test_numbered_scopes_count_up_from_the_current_one() {
+        let mut stack = ScopeStack::new();
+        stack.push_script_scope(); // 2: a script
+        stack.push_scope(); // 1: a function it calls
+        stack.push_block_scope(); // an if inside that function
+        stack.push_scope(); // 0: a function called from the if
+
+        assert_eq!(stack.scope_index("0"), Some(4));
+        assert_eq!(stack.scope_index("local"), Some(4));
+        assert_eq!(stack.scope_index("1"), Some(2)); // passes over the if
+        assert_eq!(stack.scope_index("2"), Some(1));
+        assert_eq!(stack.scope_index("Script"), Some(1));
+        assert_eq!(stack.scope_index("3"), Some(0));
+        assert_eq!(stack.scope_index("GLOBAL"), Some(0));
+        assert_eq!(stack.scope_index("4"), None);
+        assert_eq!(stack.scope_index("parent"), None);
+
+        // A numbered scope holds its own variables and those of the if
+        // bodies open in it, not those of the scopes around it
+        stack.set_variable_in(2, "x", Value::Number(1.0));
+        stack.set_variable_in(3, "y", Value::Number(2.0));
+        stack.set_variable_in(4, "z", Value::Number(3.0));
+        assert_eq!(stack.get_variable_in(2, "x"), Some(Value::Number(1.0)));
+        assert_eq!(stack.get_variable_in(2, "y"), Some(Value::Number(2.0)));
+        assert_eq!(stack.get_variable_in(4, "x"), None);
+        assert_eq!(stack.get_variable_in(1, "x"), None);
+        assert_eq!(
+            stack.variable_names_in(2),
+            vec!["x".to_string(), "y".to_string()]
+        );
+        assert_eq!(stack.variable_names_in(4), vec!["z".to_string()]);
+    }
+
+    #[test]
+    fn test_local_variables_are_what_a_closure_captures() {
+        let mut stack = ScopeStack::new();
+        stack.set_variable("outer", Value::Number(1.0));
+        stack.push_scope(); // a function
+        stack.define_variable("start", Value::Number(2.0));
+        stack.define_variable("item", Value::Number(3.0));
+        stack.push_block_scope(); // a foreach inside it
+        stack.define_variable("Item", Value::Number(4.0));
+
+        assert_eq!(
+            stack.local_variables(),
+            vec![
+                ("Item".to_string(), Value::Number(4.0)),
+                ("start".to_string(), Value::Number(2.0)),
+            ]
+        );
+    }
+
     #[test]
     fn test_scope_qualifier_parsing() {
         let mut stack = ScopeStack::new();
@@ -481,4 +812,24 @@ mod tests {
             Some(Value::Number(2.0))
         );
     }
+
+    #[test]
+    fn test_trace_level_lasts_as_long_as_its_scope() {
+        let mut stack = ScopeStack::new();
+        assert_eq!(stack.trace(), 0);
+        stack.set_trace(1);
+
+        stack.push_scope();
+        assert_eq!(stack.trace(), 1);
+        stack.push_block_scope();
+        stack.set_trace(2);
+        stack.pop_scope();
+        // The if body's Set-PSDebug set it for the function around it
+        assert_eq!(stack.trace(), 2);
+        stack.set_trace(0);
+        assert_eq!(stack.trace(), 0);
+        stack.pop_scope();
+
+        assert_eq!(stack.trace(), 1);
+    }
 }
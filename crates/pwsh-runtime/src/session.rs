@@ -0,0 +1,1126 @@
+/// Embedding API: run scripts from source text in a configured session
+///
+/// ```
+/// use pwsh_runtime::{SessionBuilder, Value};
+///
+/// let mut session = SessionBuilder::new()
+///     .variable("name", Value::String("World".into()))
+///     .build();
+/// let output = session.invoke("\"Hello $name\"").unwrap();
+/// assert_eq!(output, vec![Value::String("Hello World".into())]);
+/// ```
+use crate::clock::Clock;
+use crate::cmdlet::{Cmdlet, CmdletRegistry};
+use crate::config::Configuration;
+use crate::culture::Culture;
+use crate::error::RuntimeError;
+use crate::evaluator::Evaluator;
+use crate::host::Host;
+use crate::instrument::Instrumentation;
+use crate::language::LanguageMode;
+use crate::limits::Limits;
+use crate::plugin::CmdletPack;
+use crate::policy::{ExecutionPolicy, ScriptTrust};
+use crate::value::Value;
+use pwsh_lexer::{LexError, Lexer};
+use pwsh_parser::{ParseError, Parser, Program, Span};
+use std::path::Path;
+
+/// Why a script could not be run
+#[derive(Debug, Clone, PartialEq)]
+pub enum SessionError {
+    /// The script could not be tokenized
+    Lex(LexError),
+    /// The script could not be parsed
+    Parse(ParseError),
+    /// The script failed while running; `Session::error_span` tells where
+    Runtime(RuntimeError),
+}
+
+impl std::fmt::Display for SessionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SessionError::Lex(e) => write!(f, "{}", e),
+            SessionError::Parse(e) => write!(f, "{}", e),
+            SessionError::Runtime(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for SessionError {}
+
+impl From<LexError> for SessionError {
+    fn from(e: LexError) -> Self {
+        SessionError::Lex(e)
+    }
+}
+
+impl From<ParseError> for SessionError {
+    fn from(e: ParseError) -> Self {
+        SessionError::Parse(e)
+    }
+}
+
+/// Configures a `Session`: its cmdlets, starting variables and host
+#[derive(Default)]
+pub struct SessionBuilder {
+    registry: CmdletRegistry,
+    variables: Vec<(String, Value)>,
+    host: Option<Box<dyn Host>>,
+    modules: Vec<Box<dyn CmdletPack>>,
+    tree_walker_only: bool,
+    max_call_depth: Option<usize>,
+    max_scope_depth: Option<usize>,
+    culture: Option<Culture>,
+    clock: Option<Box<dyn Clock>>,
+    random_seed: Option<u64>,
+    native_commands: bool,
+    execution_policy: Option<ExecutionPolicy>,
+    lock_execution_policy: bool,
+    language_mode: Option<LanguageMode>,
+    script_trust: Option<Box<dyn ScriptTrust>>,
+    configuration: Option<Configuration>,
+    instrumentation: Vec<Box<dyn Instrumentation>>,
+    limits: Option<Limits>,
+}
+
+impl SessionBuilder {
+    /// A session with no cmdlets, no variables and the console host
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Make a cmdlet available to scripts
+    pub fn cmdlet(mut self, cmdlet: impl Cmdlet + 'static) -> Self {
+        self.registry.register(Box::new(cmdlet));
+        self
+    }
+
+    /// Register a set of cmdlets, e.g. `pwsh_cmdlets::register_all`
+    pub fn cmdlets(mut self, register: impl FnOnce(&mut CmdletRegistry)) -> Self {
+        register(&mut self.registry);
+        self
+    }
+
+    /// Make a cmdlet pack available for scripts to load with Import-Module
+    pub fn module(mut self, pack: impl CmdletPack + 'static) -> Self {
+        self.modules.push(Box::new(pack));
+        self
+    }
+
+    /// Define a variable before any script runs
+    pub fn variable(mut self, name: &str, value: Value) -> Self {
+        self.variables.push((name.to_string(), value));
+        self
+    }
+
+    /// Send text output, warnings and prompts to `host` instead of the console
+    pub fn host(mut self, host: impl Host + 'static) -> Self {
+        self.host = Some(Box::new(host));
+        self
+    }
+
+    /// Run every script on the tree-walker instead of compiling loop bodies
+    /// and script blocks first, for debugging
+    pub fn without_compilation(mut self) -> Self {
+        self.tree_walker_only = true;
+        self
+    }
+
+    /// Fail scripts whose function, script block and method calls nest
+    /// deeper than `depth` (1000 by default)
+    pub fn max_call_depth(mut self, depth: usize) -> Self {
+        self.max_call_depth = Some(depth);
+        self
+    }
+
+    /// Fail scripts whose scopes nest deeper than `depth` (10000 by default)
+    pub fn max_scope_depth(mut self, depth: usize) -> Self {
+        self.max_scope_depth = Some(depth);
+        self
+    }
+
+    /// Format numbers and dates for `culture` instead of en-US
+    pub fn culture(mut self, culture: Culture) -> Self {
+        self.culture = Some(culture);
+        self
+    }
+
+    /// Read the time from `clock` instead of the system clock, e.g. a
+    /// `FixedClock` to freeze or advance time in tests
+    pub fn clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Some(Box::new(clock));
+        self
+    }
+
+    /// Seed the random number generator, so Get-Random gives the same
+    /// results every time the session runs the same scripts
+    pub fn random_seed(mut self, seed: u64) -> Self {
+        self.random_seed = Some(seed);
+        self
+    }
+
+    /// Run the program of that name from PATH for a command that isn't a
+    /// cmdlet or function, as a shell does; its exit code goes in
+    /// $LASTEXITCODE
+    pub fn native_commands(mut self) -> Self {
+        self.native_commands = true;
+        self
+    }
+
+    /// Start from these settings, e.g. `Configuration::load` of the user's
+    /// `pwsh.config.json`. Settings given to the builder directly, such as
+    /// `execution_policy`, take precedence over the configuration's.
+    pub fn configuration(mut self, configuration: Configuration) -> Self {
+        self.configuration = Some(configuration);
+        self
+    }
+
+    /// Start with this execution policy instead of `Unrestricted`
+    pub fn execution_policy(mut self, policy: ExecutionPolicy) -> Self {
+        self.execution_policy = Some(policy);
+        self
+    }
+
+    /// Keep the execution policy as the host set it: Set-ExecutionPolicy
+    /// fails instead of changing it
+    pub fn lock_execution_policy(mut self) -> Self {
+        self.lock_execution_policy = true;
+        self
+    }
+
+    /// Limit how much of the language scripts may use, e.g.
+    /// `ConstrainedLanguage` for scripts the host doesn't fully trust.
+    /// Scripts can't change the mode.
+    pub fn language_mode(mut self, mode: LanguageMode) -> Self {
+        self.language_mode = Some(mode);
+        self
+    }
+
+    /// Tell the execution policy which script files are remote and which
+    /// are signed; without it every file is local and unsigned
+    pub fn script_trust(mut self, trust: impl ScriptTrust + 'static) -> Self {
+        self.script_trust = Some(Box::new(trust));
+        self
+    }
+
+    /// Make the callbacks of `instrumentation` as scripts run, e.g. to
+    /// profile, audit or meter them. Each call adds more; callbacks run in
+    /// the order they were added. Scripts run uncompiled while any are
+    /// installed, so every statement is reported.
+    pub fn instrumentation(mut self, instrumentation: impl Instrumentation + 'static) -> Self {
+        self.instrumentation.push(Box::new(instrumentation));
+        self
+    }
+
+    /// Limit what scripts may do, to run code the host doesn't trust: how
+    /// long each script may run, how much it may output, roughly how much
+    /// memory its variables may take, and which commands and capabilities
+    /// it may not use
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.limits = Some(limits);
+        self
+    }
+
+    /// Create the session
+    pub fn build(self) -> Session {
+        let mut evaluator = Evaluator::with_registry(self.registry);
+        if let Some(configuration) = self.configuration {
+            evaluator.set_configuration(configuration);
+        }
+        for (name, value) in self.variables {
+            evaluator.set_variable(&name, value);
+        }
+        for pack in self.modules {
+            evaluator.add_module(pack);
+        }
+        if let Some(host) = self.host {
+            evaluator.set_host(host);
+        }
+        evaluator.set_compilation(!self.tree_walker_only);
+        if let Some(depth) = self.max_call_depth {
+            evaluator.set_max_call_depth(depth);
+        }
+        if let Some(depth) = self.max_scope_depth {
+            evaluator.set_max_scope_depth(depth);
+        }
+        if let Some(culture) = self.culture {
+            evaluator.set_culture(culture);
+        }
+        if let Some(clock) = self.clock {
+            evaluator.set_clock(clock);
+        }
+        if let Some(seed) = self.random_seed {
+            evaluator.set_random_seed(seed);
+        }
+        evaluator.set_native_commands(self.native_commands);
+        if let Some(policy) = self.execution_policy {
+            evaluator.set_execution_policy(policy);
+        }
+        if self.lock_execution_policy {
+            evaluator.lock_execution_policy();
+        }
+        if let Some(mode) = self.language_mode {
+            evaluator.set_language_mode(mode);
+        }
+        if let Some(trust) = self.script_trust {
+            evaluator.set_script_trust(trust);
+        }
+        for instrumentation in self.instrumentation {
+            evaluator.add_instrumentation(instrumentation);
+        }
+        if let Some(limits) = self.limits {
+            evaluator.set_limits(limits);
+        }
+        Session { evaluator }
+    }
+}
+
+/// A running interpreter: variables, functions and types defined by one
+/// script stay visible to the next
+pub struct Session {
+    evaluator: Evaluator,
+}
+
+impl Session {
+    /// Run a script and return its output: what each of its statements
+    /// wrote, with arrays unrolled into their items. It runs like input at
+    /// the prompt, so what it defines stays in the session.
+    pub fn invoke(&mut self, script: &str) -> Result<Vec<Value>, SessionError> {
+        self.run(None, script, Evaluator::eval)
+    }
+
+    /// Run a line typed at the prompt, as `invoke` does, and add it to the
+    /// session's history for Get-History and `r`, whether or not it failed.
+    /// When the line ran an earlier one again with Invoke-History, that one
+    /// is added instead.
+    pub fn invoke_interactive(&mut self, line: &str) -> Result<Vec<Value>, SessionError> {
+        self.evaluator.history_mut().take_recalled();
+        let start = self.evaluator.clock().now();
+        let result = self.invoke(line);
+        let end = self.evaluator.clock().now();
+        let history = self.evaluator.history_mut();
+        let line = history.take_recalled().unwrap_or_else(|| line.to_string());
+        history.add(line, result.is_ok(), start, end);
+        result
+    }
+
+    /// Run a script as a script file, in a scope of its own: its variables
+    /// and functions are gone when it ends unless it sets them `$global:`,
+    /// and `$script:` names its own scope rather than the global one
+    pub fn invoke_script(&mut self, script: &str) -> Result<Vec<Value>, SessionError> {
+        self.run(None, script, Evaluator::eval_script)
+    }
+
+    /// Run the script file at `path`, whose text is `source`, as
+    /// `invoke_script` does, once the execution policy allows it
+    pub fn invoke_file(
+        &mut self,
+        path: impl AsRef<Path>,
+        source: &str,
+    ) -> Result<Vec<Value>, SessionError> {
+        self.evaluator
+            .check_script_file(path.as_ref(), source)
+            .map_err(SessionError::Runtime)?;
+        self.run(Some(path.as_ref()), source, Evaluator::eval_script)
+    }
+
+    /// Run `script`, from the file at `path` if it has one
+    fn run(
+        &mut self,
+        path: Option<&Path>,
+        script: &str,
+        eval: fn(&mut Evaluator, Program) -> Result<Value, RuntimeError>,
+    ) -> Result<Vec<Value>, SessionError> {
+        let tokens = Lexer::new(script).tokenize()?;
+        let program = Parser::new(tokens).parse()?;
+        self.evaluator.set_source(path, script);
+        self.evaluator.start_script();
+        match eval(&mut self.evaluator, program) {
+            Ok(Value::Null) => Ok(Vec::new()),
+            Ok(Value::Array(items)) => Ok(items.unwrap_or_clone()),
+            Ok(value) => Ok(vec![value]),
+            Err(e) => Err(SessionError::Runtime(e)),
+        }
+    }
+
+    /// What the last script to fail wrote before its error: the output
+    /// `invoke` would have returned up to there, for the host to show
+    /// ahead of the error
+    pub fn take_unfinished_output(&mut self) -> Vec<Value> {
+        self.evaluator.take_unfinished_output()
+    }
+
+    /// Where in its script the last runtime error happened, when known
+    pub fn error_span(&self) -> Option<Span> {
+        self.evaluator.error_span()
+    }
+
+    /// Where the last runtime error happened, as PowerShell shows it:
+    /// `At deploy.ps1:12 char:5`, the line, and the failing part underlined
+    pub fn error_position(&self) -> Option<String> {
+        self.evaluator.error_position()
+    }
+
+    /// Get a variable's current value
+    pub fn get_variable(&self, name: &str) -> Option<Value> {
+        self.evaluator.get_variable(name)
+    }
+
+    /// Set a variable for later scripts to use
+    pub fn set_variable(&mut self, name: &str, value: Value) {
+        self.evaluator.set_variable(name, value);
+    }
+
+    /// The underlying evaluator, for completion and other tooling
+    pub fn evaluator(&self) -> &Evaluator {
+        &self.evaluator
+    }
+
+    /// Mutable access to the underlying evaluator
+    pub fn evaluator_mut(&mut self) -> &mut Evaluator {
+        &mut self.evaluator
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::callstack::CallFrame;
+    use crate::clock::FixedClock;
+    use crate::cmdlet::CmdletContext;
+    use crate::error::ErrorCategory;
+    use crate::limits::Capability;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::time::Duration;
+
+    /// Host that keeps everything written to it
+    #[derive(Clone, Default)]
+    struct CaptureHost {
+        errors: Rc<RefCell<Vec<String>>>,
+    }
+
+    impl Host for CaptureHost {
+        fn write(&mut self, _text: &str) {}
+
+        fn write_error_line(&mut self, text: &str) {
+            self.errors.borrow_mut().push(text.to_string());
+        }
+
+        fn read_line(&mut self, _prompt: &str) -> Option<String> {
+            None
+        }
+    }
+
+    struct Double;
+
+    impl Cmdlet for Double {
+        fn name(&self) -> &str {
+            "Get-Double"
+        }
+
+        fn execute(
+            &self,
+            context: CmdletContext,
+            _evaluator: &mut Evaluator,
+        ) -> Result<Vec<Value>, RuntimeError> {
+            Ok(context
+                .pipeline_input
+                .iter()
+                .map(|v| Value::Number(v.to_number().unwrap_or(0.0) * 2.0))
+                .collect())
+        }
+    }
+
+    /// Reports each input item as a non-terminating error and outputs "done"
+    struct Complain;
+
+    impl Cmdlet for Complain {
+        fn name(&self) -> &str {
+            "Write-Complaint"
+        }
+
+        fn execute(
+            &self,
+            context: CmdletContext,
+            evaluator: &mut Evaluator,
+        ) -> Result<Vec<Value>, RuntimeError> {
+            let action = context.error_action;
+            for item in &context.pipeline_input {
+                evaluator.write_error(RuntimeError::InvalidOperation(item.to_string()), action)?;
+            }
+            Ok(vec![Value::String("done".into())])
+        }
+    }
+
+    #[test]
+    fn test_invoke_unrolls_output_and_keeps_state() {
+        let mut session = SessionBuilder::new()
+            .cmdlet(Double)
+            .variable("base", Value::Number(1.0))
+            .build();
+
+        assert_eq!(
+            session.invoke("@($base, 2) | Get-Double").unwrap(),
+            vec![Value::Number(2.0), Value::Number(4.0)]
+        );
+        assert_eq!(session.invoke("$x = 5").unwrap(), vec![]);
+        assert_eq!(session.invoke("$x + 1").unwrap(), vec![Value::Number(6.0)]);
+        assert_eq!(session.get_variable("x"), Some(Value::Number(5.0)));
+        // Every statement's output comes back, in order
+        assert_eq!(
+            session.invoke("'a'\n$x = 6\n@($x, 7)").unwrap(),
+            vec![
+                Value::String("a".into()),
+                Value::Number(6.0),
+                Value::Number(7.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invoke_script_runs_in_its_own_script_scope() {
+        let mut session = SessionBuilder::new().build();
+        session.invoke("$script:counter = 100").unwrap();
+
+        let script = "$script:counter = 0\n\
+                      function Step { $script:counter = $script:counter + 1 }\n\
+                      Step\nStep\n\
+                      $global:last = $script:counter\n\
+                      return $script:counter\n\
+                      'not reached'";
+        assert_eq!(
+            session.invoke_script(script).unwrap(),
+            vec![Value::Number(2.0)]
+        );
+
+        // The script's state is private to it: the session's is untouched
+        assert_eq!(session.get_variable("counter"), Some(Value::Number(100.0)));
+        assert_eq!(session.get_variable("last"), Some(Value::Number(2.0)));
+        assert!(session.invoke("Step").is_err());
+    }
+
+    #[test]
+    fn test_requires_version_is_checked_before_running() {
+        let mut session = SessionBuilder::new().build();
+        assert_eq!(
+            session
+                .invoke_script("#Requires -Version 5.1 -PSEdition Core\n$PSVersionTable.PSVersion")
+                .unwrap(),
+            vec![Value::String("7.4.0".into())]
+        );
+
+        // Nothing runs when a requirement isn't met
+        let error = session
+            .invoke_script("$global:ran = $true\n#requires -version 7.5")
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("for PowerShell 7.5. The version of PowerShell"));
+        assert_eq!(session.get_variable("ran"), None);
+        assert!(session
+            .invoke_script("#Requires -PSEdition Desktop")
+            .is_err());
+        assert!(matches!(
+            session.invoke("#Requires -Version"),
+            Err(SessionError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn test_invoke_reports_errors_by_stage() {
+        let mut session = SessionBuilder::new().build();
+
+        assert!(matches!(session.invoke("'open"), Err(SessionError::Lex(_))));
+        assert!(matches!(
+            session.invoke("$x = (1 +"),
+            Err(SessionError::Parse(_))
+        ));
+        match session.invoke("$y = 1\n$z = $y / 0") {
+            Err(SessionError::Runtime(error)) => {
+                assert_eq!(error, RuntimeError::DivisionByZero);
+                assert_eq!(session.error_span().unwrap().start.line, 2);
+            }
+            other => panic!("expected a runtime error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_errors_say_where_they_happened_and_in_which_calls() {
+        let mut session = SessionBuilder::new().build();
+        let script =
+            "function Inner {\n    $x = 1\n    throw 'bad'\n}\nfunction Outer { Inner }\nOuter";
+        assert!(session.invoke_file("deploy.ps1", script).is_err());
+        assert_eq!(
+            session.error_position().unwrap(),
+            "At deploy.ps1:3 char:5\n+     throw 'bad'\n+     ~~~~~~~~~~~"
+        );
+        assert_eq!(
+            session
+                .invoke("$error[0].ScriptStackTrace; $error[0].InvocationInfo.Line")
+                .unwrap(),
+            vec![
+                Value::String(
+                    "at Inner, deploy.ps1: line 3\nat Outer, deploy.ps1: line 5\nat <ScriptBlock>, deploy.ps1: line 6".into()
+                ),
+                Value::String("    throw 'bad'".into()),
+            ]
+        );
+
+        // Functions keep the script they came from; the prompt has no file
+        session
+            .invoke("function Fail {\n\n    throw 'again'\n}")
+            .unwrap();
+        assert!(session.invoke("'first'\nFail").is_err());
+        assert_eq!(
+            session.error_position().unwrap(),
+            "At line:3 char:5\n+     throw 'again'\n+     ~~~~~~~~~~~~~"
+        );
+        assert_eq!(
+            session.invoke("$error[0].ScriptStackTrace").unwrap(),
+            vec![Value::String(
+                "at Fail, <No file>: line 3\nat <ScriptBlock>, <No file>: line 2".into()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_depth_limits_fail_the_script_not_the_session() {
+        let mut session = SessionBuilder::new().max_call_depth(5).build();
+
+        match session.invoke("function f { f }; f") {
+            Err(SessionError::Runtime(error)) => assert_eq!(error.error_id(), "CallDepthOverflow"),
+            other => panic!("expected a runtime error, got {:?}", other),
+        }
+        assert_eq!(
+            session.invoke("$x = 2; $x").unwrap(),
+            vec![Value::Number(2.0)]
+        );
+    }
+
+    #[test]
+    fn test_non_terminating_errors_follow_the_error_action() {
+        let host = CaptureHost::default();
+        let mut session = SessionBuilder::new()
+            .cmdlet(Complain)
+            .host(host.clone())
+            .build();
+        let error_count = |session: &Session| match session.get_variable("error") {
+            Some(Value::Array(errors)) => errors.read().len(),
+            other => panic!("expected $error to be an array, got {:?}", other),
+        };
+        let done = vec![Value::String("done".into())];
+
+        assert_eq!(
+            session.invoke("@('a', 'b') | Write-Complaint").unwrap(),
+            done
+        );
+        assert_eq!(
+            *host.errors.borrow(),
+            ["Invalid operation: a", "Invalid operation: b"]
+        );
+        assert_eq!(error_count(&session), 2);
+
+        let quiet = "@('c') | Write-Complaint -ErrorAction SilentlyContinue";
+        assert_eq!(session.invoke(quiet).unwrap(), done);
+        assert_eq!(host.errors.borrow().len(), 2);
+        assert_eq!(error_count(&session), 3);
+
+        let ignored = "@('d') | Write-Complaint -ErrorAction ignore";
+        assert_eq!(session.invoke(ignored).unwrap(), done);
+        assert_eq!(error_count(&session), 3);
+
+        session.invoke("$ErrorActionPreference = 'Stop'").unwrap();
+        assert!(session.invoke("@('e') | Write-Complaint").is_err());
+        assert_eq!(
+            session
+                .invoke("@('f') | Write-Complaint -ErrorAction Ignore")
+                .unwrap(),
+            done
+        );
+
+        match session.invoke("@('g') | Write-Complaint -ErrorAction Sometimes") {
+            Err(SessionError::Runtime(error)) => {
+                assert_eq!(error.error_id(), "InvalidActionPreference")
+            }
+            other => panic!("expected a runtime error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_out_variable_and_error_variable() {
+        let host = CaptureHost::default();
+        let mut session = SessionBuilder::new()
+            .cmdlet(Complain)
+            .host(host.clone())
+            .build();
+
+        let script = "@('a', 'b') | Write-Complaint -ErrorVariable problems -OutVariable result -ErrorAction SilentlyContinue";
+        session.invoke(script).unwrap();
+        assert!(host.errors.borrow().is_empty());
+        assert_eq!(
+            session.invoke("$problems.Count; $result.Count").unwrap(),
+            vec![Value::Number(2.0), Value::Number(1.0)]
+        );
+        assert_eq!(
+            session.invoke("$problems[1].Exception.Message").unwrap(),
+            vec![Value::String("Invalid operation: b".into())]
+        );
+
+        // A `+` adds to the variable; Ignore keeps errors out of it; the error
+        // that stops a cmdlet is collected before it propagates
+        let quiet =
+            "@('c') | Write-Complaint -ErrorVariable '+problems' -ErrorAction SilentlyContinue";
+        let ignored = "@('d') | Write-Complaint -ErrorVariable '+problems' -ErrorAction Ignore";
+        session.invoke(quiet).unwrap();
+        session.invoke(ignored).unwrap();
+        assert_eq!(
+            session.invoke("$problems.Count").unwrap(),
+            vec![Value::Number(3.0)]
+        );
+        // The `+` needs no quotes
+        session
+            .invoke("@('f') | Write-Complaint -OutVariable +result -ErrorAction Ignore")
+            .unwrap();
+        assert_eq!(
+            session.invoke("$result.Count").unwrap(),
+            vec![Value::Number(2.0)]
+        );
+        assert!(session
+            .invoke("@('e') | Write-Complaint -ErrorVariable stopped -ErrorAction Stop")
+            .is_err());
+        assert_eq!(
+            session.invoke("$stopped.Count").unwrap(),
+            vec![Value::Number(1.0)]
+        );
+        assert_eq!(
+            session
+                .invoke("Write-Complaint -ErrorVariable none\n$none.Count")
+                .unwrap(),
+            vec![Value::String("done".into()), Value::Number(0.0)]
+        );
+    }
+
+    #[test]
+    fn test_warnings_go_to_the_host() {
+        let host = CaptureHost::default();
+        let mut session = SessionBuilder::new().host(host.clone()).build();
+
+        session
+            .invoke("function Greet($name) { $name }\nGreet -Name 'x'")
+            .unwrap();
+        let errors = host.errors.borrow();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].starts_with("WARNING: Named parameter '-Name'"));
+    }
+
+    #[test]
+    fn test_culture_formats_numbers_and_dates() {
+        let date = chrono::NaiveDate::from_ymd_opt(2024, 3, 5)
+            .unwrap()
+            .and_hms_opt(14, 7, 9)
+            .unwrap();
+        let mut session = SessionBuilder::new()
+            .culture(Culture::from_name("de-DE").unwrap())
+            .variable("date", Value::DateTime(date))
+            .build();
+
+        let text = |s: &str| vec![Value::String(s.into())];
+        assert_eq!(
+            session.invoke("'{0:N1} {1:d}' -f 1234.56, $date").unwrap(),
+            text("1.234,6 05.03.2024")
+        );
+        assert_eq!(
+            session.invoke("$date.ToString('dddd HH:mm')").unwrap(),
+            text("Dienstag 14:07")
+        );
+        assert_eq!(
+            session.invoke("[string]::Format('{0}', $date)").unwrap(),
+            text("05.03.2024 14:07:09")
+        );
+
+        session
+            .evaluator_mut()
+            .set_culture(Culture::from_name("en-US").unwrap());
+        assert_eq!(
+            session.invoke("$date.ToString()").unwrap(),
+            text("3/5/2024 2:07:09 PM")
+        );
+        assert_eq!(session.invoke("(0.5).ToString('P0')").unwrap(), text("50%"));
+    }
+
+    #[test]
+    fn test_fixed_clock_freezes_and_advances_time() {
+        let start = chrono::NaiveDate::from_ymd_opt(2024, 12, 31)
+            .unwrap()
+            .and_hms_opt(23, 59, 0)
+            .unwrap();
+        let clock = FixedClock::new(start);
+        let mut session = SessionBuilder::new().clock(clock.clone()).build();
+
+        assert_eq!(
+            session.invoke("[datetime]::Now").unwrap(),
+            vec![Value::DateTime(start)]
+        );
+        clock.advance(chrono::Duration::minutes(2));
+        assert_eq!(
+            session
+                .invoke("[datetime]::Today.ToString('yyyy-MM-dd')")
+                .unwrap(),
+            vec![Value::String("2025-01-01".into())]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_native_commands_run_only_when_enabled() {
+        let mut session = SessionBuilder::new().build();
+        assert!(matches!(
+            session.invoke("sh -c 'exit 3'"),
+            Err(SessionError::Runtime(
+                RuntimeError::UndefinedFunction { .. }
+            ))
+        ));
+
+        let mut session = SessionBuilder::new().native_commands().build();
+        std::env::set_var("PWSH_SESSION_TEST", "from env");
+        assert_eq!(
+            session
+                .invoke("sh -c --% \"echo $0 %PWSH_SESSION_TEST%\" a")
+                .unwrap(),
+            vec![Value::String("a from env".into())]
+        );
+        session.invoke("sh -c 'exit 3'").unwrap();
+        assert_eq!(
+            session.invoke("$LASTEXITCODE").unwrap(),
+            vec![Value::Number(3.0)]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_native_argument_passing_modes() {
+        let mut session = SessionBuilder::new().native_commands().build();
+        let script =
+            "$list = 'a b', ''\nsh -c 'for a; do echo \"[$a]\"; done' 'x' 'say \"hi there\"' $list";
+        assert_eq!(
+            session
+                .invoke(&format!("$PSNativeCommandArgumentPassing\n{}", script))
+                .unwrap(),
+            ["Standard", "[say \"hi there\"]", "[a b]", "[]"]
+                .iter()
+                .map(|line| Value::String((*line).into()))
+                .collect::<Vec<_>>()
+        );
+
+        session
+            .invoke("$PSNativeCommandArgumentPassing = 'Legacy'")
+            .unwrap();
+        assert_eq!(
+            session.invoke(script).unwrap(),
+            ["[say hi]", "[there]", "[a b]"]
+                .iter()
+                .map(|line| Value::String((*line).into()))
+                .collect::<Vec<_>>()
+        );
+
+        session
+            .invoke("$PSNativeCommandArgumentPassing = 'Verbatim'")
+            .unwrap();
+        assert!(session.invoke(script).is_err());
+    }
+
+    /// Notes what the evaluator reports, and fails statements past a budget
+    #[derive(Clone, Default)]
+    struct Recorder {
+        events: Rc<RefCell<Vec<String>>>,
+        budget: Option<usize>,
+    }
+
+    impl Instrumentation for Recorder {
+        fn on_statement_start(&mut self, frame: &CallFrame) -> Result<(), RuntimeError> {
+            let mut events = self.events.borrow_mut();
+            events.push(format!(
+                "{} line {}",
+                frame.command, frame.position.start.line
+            ));
+            if self.budget.is_some_and(|budget| events.len() > budget) {
+                return Err(RuntimeError::InvalidOperation("over budget".to_string()));
+            }
+            Ok(())
+        }
+
+        fn on_cmdlet_invoke(
+            &mut self,
+            name: &str,
+            context: &CmdletContext,
+        ) -> Result<(), RuntimeError> {
+            self.events.borrow_mut().push(format!(
+                "{} with {} input",
+                name,
+                context.pipeline_input.len()
+            ));
+            Ok(())
+        }
+
+        fn on_error(&mut self, error: &RuntimeError, record: &Value) {
+            let line = record
+                .get_property("InvocationInfo")
+                .and_then(|info| info.get_property("ScriptLineNumber"))
+                .unwrap_or(Value::Null);
+            self.events
+                .borrow_mut()
+                .push(format!("error '{}' at line {}", error, line));
+        }
+    }
+
+    #[test]
+    fn test_instrumentation_hears_statements_cmdlets_and_errors() {
+        let recorder = Recorder::default();
+        let mut session = SessionBuilder::new()
+            .cmdlet(Double)
+            .cmdlet(Complain)
+            .host(CaptureHost::default())
+            .instrumentation(recorder.clone())
+            .build();
+
+        let script = "function Twice($n) {\n    $n * 2\n}\n$x = Twice 3\n@($x) | Get-Double\ntry { throw 'boom' } catch { }\n@('bad') | Write-Complaint";
+        session.invoke(script).unwrap();
+        assert_eq!(
+            *recorder.events.borrow(),
+            vec![
+                "<ScriptBlock> line 1",
+                "<ScriptBlock> line 4",
+                "Twice line 2",
+                "<ScriptBlock> line 5",
+                "Get-Double with 1 input",
+                "<ScriptBlock> line 6",
+                "<ScriptBlock> line 6",
+                "error 'boom' at line 6",
+                "<ScriptBlock> line 7",
+                "Write-Complaint with 1 input",
+                "error 'Invalid operation: bad' at line 7",
+            ]
+        );
+
+        let metered = Recorder {
+            budget: Some(3),
+            ..Recorder::default()
+        };
+        let mut session = SessionBuilder::new()
+            .instrumentation(metered.clone())
+            .build();
+        match session.invoke("foreach ($i in @(1, 2, 3, 4, 5)) { $i }") {
+            Err(SessionError::Runtime(error)) => {
+                assert_eq!(error.to_string(), "Invalid operation: over budget")
+            }
+            other => panic!("expected the budget to stop the script, got {:?}", other),
+        }
+        assert_eq!(metered.events.borrow().len(), 5);
+    }
+
+    /// Stands in for a cmdlet that reads files
+    struct ReadDisk;
+
+    impl Cmdlet for ReadDisk {
+        fn name(&self) -> &str {
+            "Read-Disk"
+        }
+
+        fn capabilities(&self) -> &[Capability] {
+            &[Capability::FileSystem]
+        }
+
+        fn execute(
+            &self,
+            _context: CmdletContext,
+            _evaluator: &mut Evaluator,
+        ) -> Result<Vec<Value>, RuntimeError> {
+            Ok(vec![Value::String("sector".into())])
+        }
+    }
+
+    #[test]
+    fn test_interactive_lines_go_in_the_history() {
+        let mut session = SessionBuilder::new().build();
+        session.invoke_interactive("$x = 2").unwrap();
+        assert!(session.invoke_interactive("throw 'no'").is_err());
+        session.invoke("$x = 3").unwrap();
+        let history: Vec<(usize, &str, bool)> = session
+            .evaluator()
+            .history()
+            .entries()
+            .map(|entry| (entry.id, entry.command_line.as_str(), entry.succeeded))
+            .collect();
+        assert_eq!(history, vec![(1, "$x = 2", true), (2, "throw 'no'", false)]);
+    }
+
+    /// Stands in for Set-PSDebug, which constrained scripts may not run
+    struct Trace;
+
+    impl Cmdlet for Trace {
+        fn name(&self) -> &str {
+            "Set-PSDebug"
+        }
+
+        fn execute(
+            &self,
+            _context: CmdletContext,
+            _evaluator: &mut Evaluator,
+        ) -> Result<Vec<Value>, RuntimeError> {
+            Ok(Vec::new())
+        }
+    }
+
+    #[test]
+    fn test_constrained_language_is_checked_before_the_script_runs() {
+        let mut session = SessionBuilder::new()
+            .cmdlet(Double)
+            .cmdlet(Trace)
+            .language_mode(LanguageMode::ConstrainedLanguage)
+            .build();
+        assert_eq!(
+            session.invoke("@(2) | Get-Double").unwrap(),
+            vec![Value::Number(4.0)]
+        );
+
+        let error = runtime_error(&mut session, "$ran = 'yes'\n$n = [int]'3'");
+        assert_eq!(error.error_id(), "ConversionNotAllowed");
+        assert_eq!(
+            session.error_position().unwrap(),
+            "At line:2 char:6\n+ $n = [int]'3'\n+      ~~~~~~~~"
+        );
+        assert_eq!(session.invoke("$ran").unwrap(), vec![]);
+
+        let error = runtime_error(&mut session, "Set-PSDebug -Trace 1");
+        assert_eq!(error.error_id(), "CommandNotAllowed");
+        assert_eq!(
+            session.evaluator().language_mode(),
+            LanguageMode::ConstrainedLanguage
+        );
+    }
+
+    fn runtime_error(session: &mut Session, script: &str) -> RuntimeError {
+        match session.invoke(script) {
+            Err(SessionError::Runtime(error)) => error,
+            other => panic!("expected a runtime error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_limits_stop_scripts_that_run_output_or_hold_too_much() {
+        let mut session = SessionBuilder::new()
+            .limits(Limits {
+                max_duration: Some(Duration::ZERO),
+                ..Limits::default()
+            })
+            .build();
+        let error = runtime_error(&mut session, "try { 'working' } catch { 'caught' }");
+        assert_eq!(error.error_id(), "ScriptTimeLimitExceeded");
+        assert_eq!(error.category(), ErrorCategory::OperationStopped);
+
+        let mut session = SessionBuilder::new()
+            .limits(Limits {
+                max_output: Some(3),
+                ..Limits::default()
+            })
+            .build();
+        assert_eq!(session.invoke("1; 2; 3").unwrap().len(), 3);
+        let error = runtime_error(&mut session, "@(1, 2); @(3, 4)");
+        assert_eq!(
+            error.to_string(),
+            "The script was stopped for writing more than the 3 objects it is allowed to."
+        );
+
+        let mut session = SessionBuilder::new()
+            .limits(Limits {
+                max_values: Some(100),
+                ..Limits::default()
+            })
+            .build();
+        let script = "$d = @(1, 2, 3, 4, 5, 6, 7, 8, 9, 10)\n$all = @()\nforeach ($i in $d) { foreach ($j in $d) { $all = $all + $j } }";
+        let error = runtime_error(&mut session, script);
+        assert_eq!(error.error_id(), "MemoryLimitExceeded");
+        let error = runtime_error(&mut session, "$big = 'x' * 10000");
+        assert_eq!(error.error_id(), "MemoryLimitExceeded");
+    }
+
+    #[test]
+    fn test_time_limit_stops_a_long_running_statement() {
+        let mut session = SessionBuilder::new()
+            .limits(Limits {
+                max_duration: Some(Duration::from_millis(50)),
+                ..Limits::default()
+            })
+            .build();
+        let error = runtime_error(&mut session, "$s = 'ab' * 300000000");
+        assert_eq!(error.error_id(), "ScriptTimeLimitExceeded");
+    }
+
+    #[test]
+    fn test_a_failed_script_keeps_the_output_written_before_its_error() {
+        let mut session = SessionBuilder::new().build();
+        runtime_error(&mut session, "'before'; @(1, 2); $x = 1 / 0; 'after'");
+        assert_eq!(
+            session.take_unfinished_output(),
+            vec![
+                Value::String("before".into()),
+                Value::Number(1.0),
+                Value::Number(2.0)
+            ]
+        );
+        assert!(session.take_unfinished_output().is_empty());
+
+        assert!(session.invoke_script("'early'; throw 'stop'").is_err());
+        assert_eq!(
+            session.take_unfinished_output(),
+            vec![Value::String("early".into())]
+        );
+        session.invoke_script("throw 'again'").unwrap_err();
+        session.invoke("'fine'").unwrap();
+        assert!(session.take_unfinished_output().is_empty());
+    }
+
+    #[test]
+    fn test_limits_deny_commands_by_name_and_capability() {
+        let mut session = SessionBuilder::new()
+            .cmdlet(Double)
+            .cmdlet(ReadDisk)
+            .host(CaptureHost::default())
+            .limits(Limits {
+                denied_commands: vec!["Get-Doub*".to_string()],
+                denied_capabilities: vec![Capability::FileSystem],
+                ..Limits::default()
+            })
+            .build();
+        let error = runtime_error(&mut session, "@(2) | Get-Double");
+        assert_eq!(error.error_id(), "CommandDenied");
+        let output = session
+            .invoke("try { Read-Disk } catch { $_.Exception.Message }")
+            .unwrap();
+        assert_eq!(
+            output,
+            vec![Value::String(
+                "The command 'Read-Disk' cannot run here: FileSystem access is denied.".into()
+            )]
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_programs_need_every_capability() {
+        let mut session = SessionBuilder::new()
+            .native_commands()
+            .limits(Limits {
+                denied_capabilities: vec![Capability::FileSystem],
+                ..Limits::default()
+            })
+            .build();
+        let error = runtime_error(&mut session, "sh -c 'exit 0'");
+        assert_eq!(
+            error.to_string(),
+            "The command 'sh' cannot run here: FileSystem access is denied."
+        );
+    }
+}
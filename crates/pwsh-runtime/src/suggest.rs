@@ -0,0 +1,82 @@
+/// "Did you mean ...?" suggestions for unknown command names
+/// Candidates are ranked by case-insensitive edit distance; at most this many are returned.
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Find the candidates closest to `name`, best match first
+pub fn similar_names<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let name_lower = name.to_lowercase();
+    // Allow roughly one typo per three characters, but always at least two
+    let threshold = (name_lower.chars().count() / 3).max(2);
+
+    let mut scored: Vec<(usize, &str)> = candidates
+        .into_iter()
+        .map(|candidate| {
+            (
+                edit_distance(&name_lower, &candidate.to_lowercase()),
+                candidate,
+            )
+        })
+        .filter(|(distance, _)| *distance <= threshold)
+        .collect();
+    scored.sort_by(|a, b| a.0.cmp(&b.0).then_with(|| a.1.cmp(b.1)));
+    scored.dedup_by(|a, b| a.1.eq_ignore_ascii_case(b.1));
+
+    scored
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| candidate.to_string())
+        .collect()
+}
+
+/// Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, ca) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+        assert_eq!(edit_distance("same", "same"), 0);
+    }
+
+    #[test]
+    fn test_similar_names_ranks_closest_first() {
+        let candidates = [
+            "Get-ChildItem",
+            "Get-Content",
+            "Set-Content",
+            "Where-Object",
+        ];
+        assert_eq!(
+            similar_names("Get-ChildItm", candidates),
+            vec!["Get-ChildItem".to_string()]
+        );
+        assert_eq!(
+            similar_names("get-contnt", candidates),
+            vec!["Get-Content".to_string(), "Set-Content".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_similar_names_ignores_distant_candidates() {
+        assert!(similar_names("Foo", ["Get-ChildItem", "Where-Object"]).is_empty());
+    }
+}
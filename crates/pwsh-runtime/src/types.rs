@@ -0,0 +1,221 @@
+/// Registry of user-defined classes and enums
+use crate::value::Value;
+use pwsh_parser::{ClassDef, ClassMethod, EnumDef};
+use std::collections::HashMap;
+
+/// An enum with every member's value resolved
+#[derive(Debug, Clone, PartialEq)]
+pub struct EnumType {
+    pub name: String,
+    pub members: Vec<(String, i64)>,
+}
+
+impl EnumType {
+    /// Resolve member values: members without an explicit value continue
+    /// from the previous member, starting at 0
+    pub fn from_def(def: &EnumDef) -> Self {
+        let mut next = 0;
+        let members = def
+            .members
+            .iter()
+            .map(|(name, value)| {
+                let value = value.unwrap_or(next);
+                next = value + 1;
+                (name.clone(), value)
+            })
+            .collect();
+
+        EnumType {
+            name: def.name.clone(),
+            members,
+        }
+    }
+
+    /// Look up a member by name (case-insensitive)
+    pub fn member(&self, name: &str) -> Option<Value> {
+        self.members
+            .iter()
+            .find(|(member, _)| member.eq_ignore_ascii_case(name))
+            .map(|(member, value)| self.enum_value(member, *value))
+    }
+
+    /// Look up a member by its numeric value
+    pub fn member_with_value(&self, value: i64) -> Option<Value> {
+        self.members
+            .iter()
+            .find(|(_, v)| *v == value)
+            .map(|(member, value)| self.enum_value(member, *value))
+    }
+
+    fn enum_value(&self, name: &str, value: i64) -> Value {
+        Value::EnumValue {
+            type_name: self.name.clone(),
+            name: name.to_string(),
+            value,
+        }
+    }
+}
+
+/// User-defined types, keyed by lowercase name
+#[derive(Debug, Clone, Default)]
+pub struct TypeRegistry {
+    classes: HashMap<String, ClassDef>,
+    enums: HashMap<String, EnumType>,
+}
+
+impl TypeRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Define (or redefine) a class
+    pub fn define_class(&mut self, class: ClassDef) {
+        self.classes.insert(class.name.to_lowercase(), class);
+    }
+
+    /// Define (or redefine) an enum
+    pub fn define_enum(&mut self, def: &EnumDef) {
+        self.enums
+            .insert(def.name.to_lowercase(), EnumType::from_def(def));
+    }
+
+    /// Get a class by name (case-insensitive)
+    pub fn class(&self, name: &str) -> Option<&ClassDef> {
+        self.classes.get(&name.to_lowercase())
+    }
+
+    /// Get an enum by name (case-insensitive)
+    pub fn enum_type(&self, name: &str) -> Option<&EnumType> {
+        self.enums.get(&name.to_lowercase())
+    }
+}
+
+/// Find the method overload to call for the given argument count.
+/// Prefers an exact parameter count, then one whose extra parameters have defaults.
+pub fn find_method<'a>(
+    methods: &'a [ClassMethod],
+    name: &str,
+    arg_count: usize,
+    is_static: bool,
+) -> Option<&'a ClassMethod> {
+    let candidates: Vec<&ClassMethod> = methods
+        .iter()
+        .filter(|m| m.is_static == is_static && m.name.eq_ignore_ascii_case(name))
+        .collect();
+
+    candidates
+        .iter()
+        .find(|m| m.parameters.len() == arg_count)
+        .or_else(|| {
+            candidates.iter().find(|m| {
+                m.parameters.len() > arg_count
+                    && m.parameters[arg_count..]
+                        .iter()
+                        .all(|p| p.default_value.is_some())
+            })
+        })
+        .copied()
+}
+
+/// Initial value of a property declared with a type but no default:
+/// numeric types start at 0, [bool] at $false, everything else at $null
+pub fn default_value(type_name: Option<&str>) -> Value {
+    match type_name.map(|t| t.to_lowercase()).as_deref() {
+        Some("int" | "long" | "double" | "float" | "decimal" | "byte" | "int32" | "int64") => {
+            Value::Number(0.0)
+        }
+        Some("bool" | "boolean") => Value::Boolean(false),
+        _ => Value::Null,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pwsh_parser::{Block, Parameter};
+
+    fn method(name: &str, params: &[(&str, bool)]) -> ClassMethod {
+        ClassMethod {
+            name: name.to_string(),
+            return_type: None,
+            parameters: params
+                .iter()
+                .map(|(p, has_default)| Parameter {
+                    name: p.to_string(),
+                    type_name: None,
+                    default_value: has_default.then(|| {
+                        pwsh_parser::ExpressionKind::Literal(pwsh_parser::Literal::Number(1.0))
+                            .into()
+                    }),
+                    span: Default::default(),
+                })
+                .collect(),
+            body: Block {
+                statements: vec![],
+                trivia: vec![],
+                span: Default::default(),
+            },
+            is_static: false,
+            span: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_enum_values_continue_from_previous() {
+        let def = EnumDef {
+            name: "Color".to_string(),
+            members: vec![
+                ("Red".to_string(), None),
+                ("Green".to_string(), Some(5)),
+                ("Blue".to_string(), None),
+            ],
+        };
+        let color = EnumType::from_def(&def);
+        assert_eq!(
+            color.members,
+            vec![
+                ("Red".to_string(), 0),
+                ("Green".to_string(), 5),
+                ("Blue".to_string(), 6)
+            ]
+        );
+        assert_eq!(
+            color.member("blue"),
+            Some(Value::EnumValue {
+                type_name: "Color".to_string(),
+                name: "Blue".to_string(),
+                value: 6
+            })
+        );
+        assert_eq!(color.member("Purple"), None);
+    }
+
+    #[test]
+    fn test_find_method_overloads() {
+        let methods = vec![
+            method("Greet", &[]),
+            method("Greet", &[("name", false)]),
+            method("Scale", &[("x", false), ("factor", true)]),
+        ];
+
+        assert_eq!(
+            find_method(&methods, "greet", 1, false).map(|m| m.parameters.len()),
+            Some(1)
+        );
+        assert_eq!(
+            find_method(&methods, "Scale", 1, false).map(|m| m.name.as_str()),
+            Some("Scale")
+        );
+        assert!(find_method(&methods, "Greet", 3, false).is_none());
+        assert!(find_method(&methods, "Greet", 0, true).is_none());
+    }
+
+    #[test]
+    fn test_default_value_by_type() {
+        assert_eq!(default_value(Some("int")), Value::Number(0.0));
+        assert_eq!(default_value(Some("Bool")), Value::Boolean(false));
+        assert_eq!(default_value(Some("string")), Value::Null);
+        assert_eq!(default_value(None), Value::Null);
+    }
+}
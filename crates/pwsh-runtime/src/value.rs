@@ -1,23 +1,195 @@
 /// PowerShell Value types
-use std::collections::HashMap;
+use crate::callstack::ScriptSource;
+use crate::compile::CompiledBlock;
+use crate::property_map::PropertyMap;
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
 use std::fmt;
+use std::sync::{Arc, OnceLock, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// A hashtable, array or object, shared by every copy of the value that
+/// holds it: like a .NET reference type, a change made through one variable
+/// is seen through all the others
+pub struct Shared<T>(Arc<RwLock<T>>);
+
+impl<T> Shared<T> {
+    pub fn new(value: T) -> Self {
+        Shared(Arc::new(RwLock::new(value)))
+    }
+
+    pub fn read(&self) -> RwLockReadGuard<'_, T> {
+        self.0.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub fn write(&self) -> RwLockWriteGuard<'_, T> {
+        self.0.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Whether both are the same hashtable, array or object
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+
+    /// Run `f` on the contents, unless this is already being walked further
+    /// up the stack: a table can hold itself ($h.Self = $h), and showing,
+    /// comparing or counting it must stop there rather than recurse forever
+    pub fn visit<R>(&self, f: impl FnOnce(&T) -> R) -> Option<R> {
+        let id = Arc::as_ptr(&self.0) as *const () as usize;
+        if WALKING.with(|walking| walking.borrow().contains(&id)) {
+            return None;
+        }
+        WALKING.with(|walking| walking.borrow_mut().push(id));
+        let _walked = Walked;
+        Some(f(&self.read()))
+    }
+}
+
+thread_local! {
+    /// The shared values being walked by `Shared::visit` on this thread
+    static WALKING: RefCell<Vec<usize>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Takes a value off `WALKING` when its walk ends, even by a panic
+struct Walked;
+
+impl Drop for Walked {
+    fn drop(&mut self) {
+        WALKING.with(|walking| walking.borrow_mut().pop());
+    }
+}
+
+impl<T: Clone> Shared<T> {
+    /// The contents, taken if nothing else shares them and copied if something does
+    pub fn unwrap_or_clone(self) -> T {
+        match Arc::try_unwrap(self.0) {
+            Ok(lock) => lock.into_inner().unwrap_or_else(PoisonError::into_inner),
+            Err(shared) => Shared(shared).read().clone(),
+        }
+    }
+
+    /// A new, unshared copy of the contents (the items themselves are not copied)
+    pub fn copy(&self) -> Self {
+        Shared::new(self.read().clone())
+    }
+}
+
+impl<T> Clone for Shared<T> {
+    fn clone(&self) -> Self {
+        Shared(Arc::clone(&self.0))
+    }
+}
+
+impl<T> From<T> for Shared<T> {
+    fn from(value: T) -> Self {
+        Shared::new(value)
+    }
+}
+
+impl<T: PartialEq> PartialEq for Shared<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ptr_eq(other)
+            || self
+                .visit(|this| other.visit(|other| this == other))
+                .flatten()
+                .unwrap_or(false)
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Shared<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.visit(|this| this.fmt(f))
+            .unwrap_or_else(|| f.write_str("…"))
+    }
+}
+
+impl<T: Serialize> Serialize for Shared<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.visit(|this| this.serialize(serializer)) {
+            Some(result) => result,
+            None => Err(serde::ser::Error::custom("a value contains itself")),
+        }
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Shared<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(Shared::new)
+    }
+}
 
 /// Function definition stored as a value
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Function {
     pub name: String,
     pub parameters: Vec<pwsh_parser::Parameter>,
     pub body: pwsh_parser::Block,
+    /// Declared with `filter`: the body runs once per pipeline item
+    pub is_filter: bool,
+    /// Named `begin` block, run once before pipeline input
+    pub begin: Option<pwsh_parser::Block>,
+    /// Named `process` block, run once per pipeline item
+    pub process: Option<pwsh_parser::Block>,
+    /// Comment-based help written with the function
+    pub help: Option<Box<pwsh_parser::CommentHelp>>,
+    /// The script it was defined in, for error positions and the call stack
+    #[serde(skip)]
+    pub source: Option<Arc<ScriptSource>>,
 }
 
 /// Script block stored as a value (anonymous code block)
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ScriptBlock {
     pub body: pwsh_parser::Block,
+    /// Variables captured by `GetNewClosure()`, defined afresh each time
+    /// the block runs
+    captured: Option<Arc<Vec<(String, Value)>>>,
+    /// The body compiled on its first run; clones share it
+    #[serde(skip)]
+    compiled: Arc<OnceLock<CompiledBlock>>,
+}
+
+impl ScriptBlock {
+    pub fn new(body: pwsh_parser::Block) -> Self {
+        ScriptBlock {
+            body,
+            captured: None,
+            compiled: Arc::default(),
+        }
+    }
+
+    /// The same block closed over `variables`: it sees them as they are now,
+    /// whichever scope it is later run from
+    pub fn with_captured(&self, variables: Vec<(String, Value)>) -> Self {
+        ScriptBlock {
+            captured: Some(Arc::new(variables)),
+            ..self.clone()
+        }
+    }
+
+    /// The variables this block closed over, empty unless it is a closure
+    pub fn captured(&self) -> &[(String, Value)] {
+        self.captured.as_deref().map_or(&[], Vec::as_slice)
+    }
+
+    /// The body as instructions, compiled the first time it is asked for
+    pub(crate) fn compiled(&self) -> &CompiledBlock {
+        self.compiled.get_or_init(|| CompiledBlock::new(&self.body))
+    }
+}
+
+impl PartialEq for ScriptBlock {
+    fn eq(&self, other: &Self) -> bool {
+        self.body == other.body && self.captured() == other.captured()
+    }
 }
 
 /// A value in the PowerShell runtime
-#[derive(Debug, Clone, PartialEq)]
+/// Strings, arrays and objects are shared: cloning a value doesn't copy
+/// them. Strings never change; arrays, hashtables and class instances are
+/// reference types, so changing one through any copy changes it for all
+/// (`Shared`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Value {
     /// Null value
     Null,
@@ -26,15 +198,28 @@ pub enum Value {
     /// Numeric value (PowerShell uses double precision floats)
     Number(f64),
     /// String value
-    String(String),
+    String(Arc<str>),
     /// Object with properties
-    Object(HashMap<String, Value>),
+    Object(Shared<PropertyMap>),
     /// Array of values
-    Array(Vec<Value>),
-    /// Function definition
-    Function(Function),
+    Array(Shared<Vec<Value>>),
+    /// Date and time (local wall-clock time, like a .NET DateTime)
+    DateTime(NaiveDateTime),
+    /// Function definition (boxed to keep Value small)
+    Function(Box<Function>),
     /// Script block (anonymous code block)
     ScriptBlock(ScriptBlock),
+    /// Instance of a user-defined class
+    Instance {
+        class_name: String,
+        properties: Shared<PropertyMap>,
+    },
+    /// Value of a user-defined enum: [Color]::Red
+    EnumValue {
+        type_name: String,
+        name: String,
+        value: i64,
+    },
 }
 
 impl Value {
@@ -57,24 +242,63 @@ impl Value {
                     n.to_string()
                 }
             }
-            Value::String(s) => s.clone(),
-            Value::Object(props) => {
-                // Simple object representation
-                let mut parts: Vec<String> = props
-                    .iter()
-                    .map(|(k, v)| format!("{}={}", k, v.display_string()))
-                    .collect();
-                parts.sort();
-                format!("@{{{}}}", parts.join("; "))
-            }
-            Value::Array(items) => {
-                let parts: Vec<String> = items.iter().map(|v| v.display_string()).collect();
-                format!("@({})", parts.join(", "))
-            }
+            Value::String(s) => s.to_string(),
+            // An error record reads as its message, so "$_" in a catch
+            // block is the error text
+            Value::Instance {
+                class_name,
+                properties,
+            } if class_name == "ErrorRecord" => properties
+                .read()
+                .get("Exception")
+                .and_then(|exception| exception.get_property("Message"))
+                .map(|message| message.display_string())
+                .unwrap_or_default(),
+            // A table or array that holds itself shows itself as …
+            Value::Object(props)
+            | Value::Instance {
+                properties: props, ..
+            } => props
+                .visit(Self::properties_string)
+                .unwrap_or_else(|| "@{…}".to_string()),
+            Value::Array(items) => items
+                .visit(|items| {
+                    let parts: Vec<String> = items.iter().map(|v| v.display_string()).collect();
+                    format!("@({})", parts.join(", "))
+                })
+                .unwrap_or_else(|| "@(…)".to_string()),
             Value::Function(func) => {
                 format!("function {}", func.name)
             }
             Value::ScriptBlock(_) => "{ script block }".to_string(),
+            Value::DateTime(dt) => dt.format("%-m/%-d/%Y %-I:%M:%S %p").to_string(),
+            Value::EnumValue { name, .. } => name.clone(),
+        }
+    }
+
+    /// Simple object representation: @{Name=a; Size=1}
+    fn properties_string(props: &PropertyMap) -> String {
+        let parts: Vec<String> = props
+            .iter()
+            .map(|(k, v)| format!("{}={}", k, v.display_string()))
+            .collect();
+        format!("@{{{}}}", parts.join("; "))
+    }
+
+    /// Type name used in error messages: [string], [Object[]], [Person]
+    pub fn type_name(&self) -> &str {
+        match self {
+            Value::Null => "null",
+            Value::Boolean(_) => "bool",
+            Value::Number(_) => "double",
+            Value::String(_) => "string",
+            Value::Object(_) => "hashtable",
+            Value::Array(_) => "Object[]",
+            Value::DateTime(_) => "datetime",
+            Value::Function(_) => "FunctionInfo",
+            Value::ScriptBlock(_) => "scriptblock",
+            Value::Instance { class_name, .. } => class_name,
+            Value::EnumValue { type_name, .. } => type_name,
         }
     }
 
@@ -86,70 +310,111 @@ impl Value {
             Value::Number(n) => *n != 0.0,
             Value::String(s) => !s.is_empty(),
             Value::Object(_) => true,
-            Value::Array(items) => !items.is_empty(),
+            // A one-item array is as true as its item, as in PowerShell
+            Value::Array(items) => match items.read().as_slice() {
+                [] => false,
+                [item] => item.to_bool(),
+                _ => true,
+            },
             Value::Function(_) => true,
             Value::ScriptBlock(_) => true,
+            Value::DateTime(_) => true,
+            Value::Instance { .. } => true,
+            Value::EnumValue { value, .. } => *value != 0,
         }
     }
 
-    /// Try to convert value to number
+    /// Try to convert value to number; strings convert as PowerShell reads
+    /// them, so " 0x1F " and "1kb" are numbers (see `parse_number`)
     pub fn to_number(&self) -> Option<f64> {
         match self {
             Value::Number(n) => Some(*n),
-            Value::String(s) => s.parse::<f64>().ok(),
+            Value::String(s) => crate::convert::parse_number(s),
             Value::Boolean(true) => Some(1.0),
             Value::Boolean(false) => Some(0.0),
+            Value::EnumValue { value, .. } => Some(*value as f64),
             _ => None,
         }
     }
 
-    /// Get a property from an object (case-insensitive)
-    pub fn get_property(&self, name: &str) -> Option<Value> {
-        match self {
-            Value::Object(props) => {
-                // Try exact match first for performance
-                if let Some(value) = props.get(name) {
-                    return Some(value.clone());
-                }
+    /// Try to convert value to a date/time (DateTime values and date-like strings)
+    pub fn to_datetime(&self) -> Option<NaiveDateTime> {
+        const DATETIME_FORMATS: &[&str] = &[
+            "%Y-%m-%dT%H:%M:%S%.f",
+            "%Y-%m-%d %H:%M:%S%.f",
+            "%Y-%m-%dT%H:%M",
+            "%Y-%m-%d %H:%M",
+            "%m/%d/%Y %H:%M:%S",
+            "%m/%d/%Y %I:%M:%S %p",
+        ];
+        const DATE_FORMATS: &[&str] = &["%Y-%m-%d", "%m/%d/%Y"];
 
-                // Fall back to case-insensitive property lookup
-                let name_lower = name.to_lowercase();
-                props
+        match self {
+            Value::DateTime(dt) => Some(*dt),
+            Value::String(s) => {
+                let s = s.trim();
+                DATETIME_FORMATS
                     .iter()
-                    .find(|(k, _)| k.to_lowercase() == name_lower)
-                    .map(|(_, v)| v.clone())
+                    .find_map(|f| NaiveDateTime::parse_from_str(s, f).ok())
+                    .or_else(|| {
+                        DATE_FORMATS.iter().find_map(|f| {
+                            chrono::NaiveDate::parse_from_str(s, f)
+                                .ok()
+                                .and_then(|d| d.and_hms_opt(0, 0, 0))
+                        })
+                    })
             }
             _ => None,
         }
     }
 
-    /// Set a property on an object (case-insensitive - updates existing key or adds new)
-    pub fn set_property(&mut self, name: &str, value: Value) -> Result<(), String> {
+    /// The shared property map of an object or class instance
+    pub fn shared_properties(&self) -> Option<&Shared<PropertyMap>> {
         match self {
-            Value::Object(props) => {
-                // Try exact match first for performance
-                if props.contains_key(name) {
-                    props.insert(name.to_string(), value);
-                    return Ok(());
-                }
+            Value::Object(props)
+            | Value::Instance {
+                properties: props, ..
+            } => Some(props),
+            _ => None,
+        }
+    }
 
-                // Fall back to case-insensitive property update: find existing key or add new one
-                let name_lower = name.to_lowercase();
-                if let Some(existing_key) = props
-                    .keys()
-                    .find(|k| k.to_lowercase() == name_lower)
-                    .cloned()
-                {
-                    // Update existing property with original key name
-                    props.insert(existing_key, value);
-                } else {
-                    // Add new property
-                    props.insert(name.to_string(), value);
-                }
-                Ok(())
-            }
-            _ => Err("Cannot set property on non-object value".to_string()),
+    /// Property map of an object or class instance
+    pub fn properties(&self) -> Option<RwLockReadGuard<'_, PropertyMap>> {
+        self.shared_properties().map(Shared::read)
+    }
+
+    /// Mutable property map of an object or class instance, the one every
+    /// copy of it shares
+    pub fn properties_mut(&self) -> Option<RwLockWriteGuard<'_, PropertyMap>> {
+        self.shared_properties().map(Shared::write)
+    }
+
+    /// Get a property from an object (case-insensitive)
+    pub fn get_property(&self, name: &str) -> Option<Value> {
+        self.properties()?.get(name).cloned()
+    }
+
+    /// Set a property on an object (case-insensitive - updates existing key or adds new)
+    /// Class instances only have the properties their class declares.
+    pub fn set_property(&self, name: &str, value: Value) -> Result<(), String> {
+        let is_instance = matches!(self, Value::Instance { .. });
+        let Some(mut props) = self.properties_mut() else {
+            return Err("Cannot set property on non-object value".to_string());
+        };
+
+        // An existing property keeps the spelling it was added with
+        if let Some(existing) = props.get_mut(name) {
+            *existing = value;
+        } else if is_instance {
+            return Err(format!(
+                "Property '{}' cannot be found on this object",
+                name
+            ));
+        } else {
+            props.insert(name, value);
         }
+        Ok(())
     }
 }
 
@@ -169,7 +434,27 @@ mod tests {
         assert_eq!(Value::Boolean(true).to_string(), "True");
         assert_eq!(Value::Number(42.0).to_string(), "42");
         assert_eq!(Value::Number(3.15).to_string(), "3.15");
-        assert_eq!(Value::String("hello".to_string()).to_string(), "hello");
+        assert_eq!(Value::String("hello".into()).to_string(), "hello");
+    }
+
+    #[test]
+    fn test_datetime_display_and_parse() {
+        let dt = chrono::NaiveDate::from_ymd_opt(2024, 3, 5)
+            .unwrap()
+            .and_hms_opt(14, 7, 9)
+            .unwrap();
+        assert_eq!(Value::DateTime(dt).to_string(), "3/5/2024 2:07:09 PM");
+
+        assert_eq!(
+            Value::String("2024-03-05 14:07:09".into()).to_datetime(),
+            Some(dt)
+        );
+        assert_eq!(
+            Value::String("3/5/2024".into()).to_datetime(),
+            dt.date().and_hms_opt(0, 0, 0)
+        );
+        assert_eq!(Value::String("soon".into()).to_datetime(), None);
+        assert_eq!(Value::Number(1.0).to_datetime(), None);
     }
 
     #[test]
@@ -179,14 +464,14 @@ mod tests {
         assert!(!Value::Boolean(false).to_bool());
         assert!(!Value::Number(0.0).to_bool());
         assert!(Value::Number(42.0).to_bool());
-        assert!(!Value::String("".to_string()).to_bool());
-        assert!(Value::String("hello".to_string()).to_bool());
+        assert!(!Value::String("".into()).to_bool());
+        assert!(Value::String("hello".into()).to_bool());
     }
 
     #[test]
     fn test_value_to_number() {
         assert_eq!(Value::Number(42.0).to_number(), Some(42.0));
-        assert_eq!(Value::String("3.15".to_string()).to_number(), Some(3.15));
+        assert_eq!(Value::String("3.15".into()).to_number(), Some(3.15));
         assert_eq!(Value::Boolean(true).to_number(), Some(1.0));
         assert_eq!(Value::Boolean(false).to_number(), Some(0.0));
         assert_eq!(Value::Null.to_number(), None);
@@ -194,43 +479,43 @@ mod tests {
 
     #[test]
     fn test_object_properties() {
-        let mut obj = Value::Object(HashMap::new());
+        let obj = Value::Object(PropertyMap::new().into());
         assert!(obj
-            .set_property("name", Value::String("test".to_string()))
+            .set_property("name", Value::String("test".into()))
             .is_ok());
-        assert_eq!(
-            obj.get_property("name"),
-            Some(Value::String("test".to_string()))
-        );
+        assert_eq!(obj.get_property("name"), Some(Value::String("test".into())));
         assert_eq!(obj.get_property("missing"), None);
     }
 
     #[test]
     fn test_object_properties_case_insensitive() {
-        let mut obj = Value::Object(HashMap::new());
+        let obj = Value::Object(PropertyMap::new().into());
         // Set property with mixed case
         assert!(obj
-            .set_property("Name", Value::String("test".to_string()))
+            .set_property("Name", Value::String("test".into()))
             .is_ok());
 
         // Should retrieve with different case variations
-        assert_eq!(
-            obj.get_property("name"),
-            Some(Value::String("test".to_string()))
-        );
-        assert_eq!(
-            obj.get_property("NAME"),
-            Some(Value::String("test".to_string()))
-        );
-        assert_eq!(
-            obj.get_property("Name"),
-            Some(Value::String("test".to_string()))
-        );
+        assert_eq!(obj.get_property("name"), Some(Value::String("test".into())));
+        assert_eq!(obj.get_property("NAME"), Some(Value::String("test".into())));
+        assert_eq!(obj.get_property("Name"), Some(Value::String("test".into())));
+    }
+
+    #[test]
+    fn test_properties_mut_case_insensitive() {
+        let obj = Value::Object(PropertyMap::new().into());
+        obj.set_property("Count", Value::Number(1.0)).unwrap();
+
+        if let Some(count) = obj.properties_mut().unwrap().get_mut("count") {
+            *count = Value::Number(2.0);
+        }
+        assert_eq!(obj.get_property("Count"), Some(Value::Number(2.0)));
+        assert!(obj.properties_mut().unwrap().get_mut("missing").is_none());
     }
 
     #[test]
     fn test_object_properties_update_preserves_case() {
-        let mut obj = Value::Object(HashMap::new());
+        let obj = Value::Object(PropertyMap::new().into());
         // Set property with specific case
         obj.set_property("Age", Value::Number(30.0)).unwrap();
 
@@ -239,6 +524,7 @@ mod tests {
 
         // Should still have only one key (the original)
         if let Value::Object(map) = &obj {
+            let map = map.read();
             assert_eq!(map.len(), 1);
             assert!(map.contains_key("Age"));
         } else {
@@ -248,4 +534,94 @@ mod tests {
         // Value should be updated
         assert_eq!(obj.get_property("age"), Some(Value::Number(31.0)));
     }
+
+    #[test]
+    fn test_clones_share_changes() {
+        let original = Value::Object(PropertyMap::new().into());
+        original
+            .set_property("Name", Value::String("a".into()))
+            .unwrap();
+
+        let alias = original.clone();
+        let (Value::Object(a), Value::Object(b)) = (&original, &alias) else {
+            panic!("Expected objects");
+        };
+        assert!(a.ptr_eq(b));
+
+        alias
+            .set_property("Name", Value::String("b".into()))
+            .unwrap();
+        assert_eq!(
+            original.get_property("Name"),
+            Some(Value::String("b".into()))
+        );
+
+        let Value::Object(a) = &original else {
+            panic!("Expected an object");
+        };
+        let copy = Value::Object(a.copy());
+        alias
+            .set_property("Name", Value::String("c".into()))
+            .unwrap();
+        assert_eq!(copy.get_property("Name"), Some(Value::String("b".into())));
+    }
+
+    #[test]
+    fn test_values_that_hold_themselves() {
+        let table = Value::Object(PropertyMap::new().into());
+        let items = Value::Array(vec![Value::Number(1.0)].into());
+        let Value::Array(shared) = &items else {
+            panic!("Expected an array");
+        };
+        shared.write().push(items.clone());
+        table.clone().set_property("Self", table.clone()).unwrap();
+
+        assert_eq!(table.to_string(), "@{Self=@{…}}");
+        assert_eq!(items.to_string(), "@(1, @(…))");
+        assert!(format!("{:?}", table).contains('…'));
+        assert_eq!(items, items.clone());
+        assert_eq!(crate::limits::value_count(&table), 2);
+    }
+
+    #[test]
+    fn test_values_round_trip_through_json() {
+        let value = Value::Array(
+            vec![
+                Value::Null,
+                Value::Number(1.5),
+                Value::Object(
+                    PropertyMap::from([
+                        ("Name".to_string(), Value::String("a".into())),
+                        (
+                            "Tags".to_string(),
+                            Value::Array(vec![Value::Boolean(true)].into()),
+                        ),
+                    ])
+                    .into(),
+                ),
+                Value::DateTime(
+                    chrono::NaiveDate::from_ymd_opt(2024, 3, 5)
+                        .unwrap()
+                        .and_hms_opt(14, 7, 9)
+                        .unwrap(),
+                ),
+                Value::EnumValue {
+                    type_name: "Color".to_string(),
+                    name: "Red".to_string(),
+                    value: 0,
+                },
+            ]
+            .into(),
+        );
+
+        let json = serde_json::to_string(&value).unwrap();
+        let back: Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, value);
+        // Property order is kept
+        assert!(
+            json.contains(r#"{"Object":{"Name":{"String":"a"},"Tags":"#),
+            "{}",
+            json
+        );
+    }
 }
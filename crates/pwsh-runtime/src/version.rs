@@ -0,0 +1,81 @@
+/// The PowerShell version the interpreter follows: `$PSVersionTable` and the `#Requires` checks
+use crate::error::{ErrorCategory, ErrorRecord, RuntimeError};
+use crate::property_map::PropertyMap;
+use crate::value::Value;
+use pwsh_parser::Requirements;
+
+/// The version of PowerShell whose language the interpreter follows
+pub const POWERSHELL_VERSION: [u32; 3] = [7, 4, 0];
+
+/// The PowerShell edition the interpreter is, like PowerShell 7
+pub const POWERSHELL_EDITION: &str = "Core";
+
+fn version_text(parts: &[u32]) -> String {
+    let parts: Vec<String> = parts.iter().map(u32::to_string).collect();
+    parts.join(".")
+}
+
+/// `$PSVersionTable`: the PowerShell version and edition, and the version
+/// of this interpreter
+pub(crate) fn version_table() -> Value {
+    let mut table = PropertyMap::with_capacity(4);
+    table.insert(
+        "PSVersion".to_string(),
+        Value::String(version_text(&POWERSHELL_VERSION).into()),
+    );
+    table.insert(
+        "PSEdition".to_string(),
+        Value::String(POWERSHELL_EDITION.into()),
+    );
+    table.insert(
+        "InterpreterVersion".to_string(),
+        Value::String(env!("CARGO_PKG_VERSION").into()),
+    );
+    let platform = if cfg!(windows) { "Win32NT" } else { "Unix" };
+    table.insert("Platform".to_string(), Value::String(platform.into()));
+    Value::Object(table.into())
+}
+
+/// Check a script's `#Requires -Version` and `-PSEdition` against the
+/// interpreter. A version is met by any later one, as in PowerShell.
+pub(crate) fn check_version(requires: &Requirements) -> Result<(), RuntimeError> {
+    if let Some(required) = &requires.version {
+        // Missing parts are 0, so 7 and 7.0.0 are the same version
+        let padded = |parts: &[u32]| {
+            let mut parts = parts.to_vec();
+            parts.resize(4, 0);
+            parts
+        };
+        if padded(required) > padded(&POWERSHELL_VERSION) {
+            let mut shown = required.clone();
+            if shown.len() == 1 {
+                shown.push(0);
+            }
+            return Err(ErrorRecord::new(
+                ErrorCategory::InvalidOperation,
+                "ScriptRequiresUnmatchedPSVersion",
+                format!(
+                    "The script cannot be run because it contained a \"#requires\" statement for PowerShell {}. The version of PowerShell that is required by the script does not match the currently running version of PowerShell {}.",
+                    version_text(&shown),
+                    version_text(&POWERSHELL_VERSION)
+                ),
+            )
+            .into());
+        }
+    }
+
+    if let Some(edition) = &requires.edition {
+        if !edition.eq_ignore_ascii_case(POWERSHELL_EDITION) {
+            return Err(ErrorRecord::new(
+                ErrorCategory::InvalidOperation,
+                "ScriptRequiresUnmatchedPSEdition",
+                format!(
+                    "The script cannot be run because it contained a \"#requires\" statement for PowerShell edition '{}'. The edition of PowerShell that is required by the script does not match the currently running PowerShell {}.",
+                    edition, POWERSHELL_EDITION
+                ),
+            )
+            .into());
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,1195 @@
+/// Default views: how the console shows objects of a type, as PowerShell's format definitions do
+///
+/// An object whose type names (`PropertyMap::type_names`) have a view is
+/// shown as a row of that view's table; consecutive objects with the same
+/// view share one table, under one header. Everything else is shown as its
+/// text, as before.
+use crate::ansi::strip_ansi;
+use crate::callstack::CALL_STACK_FRAME_TYPE;
+use crate::debugger::BREAKPOINT_TYPE;
+use crate::display::DisplayLimits;
+use crate::error::{ErrorCategory, ErrorRecord, RuntimeError};
+use crate::history::HISTORY_INFO_TYPE;
+use crate::psstyle::{styled, FileInfoStyle, FormattingStyle};
+use crate::value::Value;
+use chrono::{Local, TimeZone};
+
+/// What formatting may use besides the objects themselves
+#[derive(Debug, Clone, Default)]
+pub struct FormatContext {
+    /// How to style file names; None when output is plain text
+    pub file_info: Option<FileInfoStyle>,
+    /// How to style table headers and list labels; None when output is
+    /// plain text
+    pub formatting: Option<FormattingStyle>,
+    /// How many characters wide the console is; None for no limit
+    pub width: Option<usize>,
+}
+
+/// Which side of its column a cell's text keeps to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Alignment {
+    #[default]
+    Left,
+    Right,
+}
+
+/// What a column shows for an object
+#[derive(Debug, Clone)]
+pub enum ColumnValue {
+    /// A property, as its text
+    Property(String),
+    /// Text worked out from the whole object
+    Computed(fn(&Value, &FormatContext) -> String),
+}
+
+impl ColumnValue {
+    fn text(&self, item: &Value, context: &FormatContext) -> String {
+        match self {
+            ColumnValue::Property(name) => item
+                .get_property(name)
+                .map(|value| DisplayLimits::default().inline(&value))
+                .unwrap_or_default(),
+            ColumnValue::Computed(text) => text(item, context),
+        }
+    }
+}
+
+/// A column of a table view
+#[derive(Debug, Clone)]
+pub struct TableColumn {
+    pub label: String,
+    pub value: ColumnValue,
+    /// Fixed width; None fits the widest cell
+    pub width: Option<usize>,
+    pub alignment: Alignment,
+}
+
+impl TableColumn {
+    /// A left-aligned column showing the property of that name
+    pub fn property(name: &str) -> Self {
+        TableColumn {
+            label: name.to_string(),
+            value: ColumnValue::Property(name.to_string()),
+            width: None,
+            alignment: Alignment::Left,
+        }
+    }
+}
+
+/// How objects of some types are shown: as rows of a table, optionally in
+/// groups under a `Label: value` heading whenever the group changes
+#[derive(Debug, Clone)]
+pub struct TableView {
+    /// The type names the view is for
+    pub type_names: Vec<String>,
+    pub columns: Vec<TableColumn>,
+    /// The heading label and the value objects are grouped by
+    pub group_by: Option<(String, ColumnValue)>,
+    /// Carry cells too long for their column onto more lines instead of
+    /// cutting them short
+    pub wrap: bool,
+    /// Size every column to its widest cell, ignoring the columns' widths
+    pub auto_size: bool,
+}
+
+impl TableView {
+    /// The lines of a table of `items`: a heading per group, the column
+    /// labels underlined, and a row per item
+    pub fn render(&self, items: &[&Value], context: &FormatContext) -> Vec<String> {
+        let mut lines = Vec::new();
+        let mut start = 0;
+        while start < items.len() {
+            let group = self
+                .group_by
+                .as_ref()
+                .map(|(_, value)| value.text(items[start], context));
+            let end = match (&self.group_by, &group) {
+                (Some((_, value)), Some(group)) => {
+                    start
+                        + items[start..]
+                            .iter()
+                            .take_while(|item| value.text(item, context) == *group)
+                            .count()
+                }
+                _ => items.len(),
+            };
+            if let (Some((label, _)), Some(group)) = (&self.group_by, &group) {
+                lines.push(String::new());
+                lines.push(format!("    {}: {}", label, group));
+            }
+            lines.push(String::new());
+            lines.extend(self.table(&items[start..end], context));
+            lines.push(String::new());
+            start = end;
+        }
+        lines
+    }
+
+    /// The header, underline and rows of one table. Columns are as wide as
+    /// their widest cell, or as the view says unless it is auto-sized. On a
+    /// console of limited width they narrow to fit (see `fit_columns`), and
+    /// cells too long for their column are cut short or, when the view
+    /// wraps, carried onto more lines.
+    fn table(&self, items: &[&Value], context: &FormatContext) -> Vec<String> {
+        let cells: Vec<Vec<String>> = items
+            .iter()
+            .map(|item| {
+                self.columns
+                    .iter()
+                    .map(|column| column.value.text(item, context))
+                    .collect()
+            })
+            .collect();
+        let mut widths: Vec<usize> = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(i, column)| match column.width {
+                Some(width) if !self.auto_size => width,
+                _ => cells
+                    .iter()
+                    .map(|row| display_width(&row[i]))
+                    .chain([column.label.chars().count()])
+                    .max()
+                    .unwrap_or(0),
+            })
+            .collect();
+        if let Some(limit) = context.width {
+            let floors: Vec<usize> = self
+                .columns
+                .iter()
+                .zip(&widths)
+                .map(|(column, width)| column.label.chars().count().clamp(1, (*width).max(1)))
+                .collect();
+            fit_columns(&mut widths, &floors, limit);
+        }
+        // Without a limit the last column takes whatever room it needs
+        let last = widths.len() - 1;
+        let cut_last = context.width.is_some();
+
+        let header_style = context
+            .formatting
+            .as_ref()
+            .map_or("", |formatting| formatting.table_header.as_str());
+        let line = |texts: &[&str], style: &str| {
+            let parts: Vec<String> = texts
+                .iter()
+                .enumerate()
+                .map(|(i, text)| {
+                    styled(
+                        style,
+                        &fit(
+                            text,
+                            widths[i],
+                            self.columns[i].alignment,
+                            i == last && !cut_last,
+                        ),
+                    )
+                })
+                .collect();
+            parts.join(" ").trim_end().to_string()
+        };
+        let labels: Vec<&str> = self.columns[..=last]
+            .iter()
+            .map(|c| c.label.as_str())
+            .collect();
+        let underlines: Vec<String> = self.columns[..=last]
+            .iter()
+            .zip(&widths)
+            .map(|(c, width)| "-".repeat(c.label.chars().count().min(*width)))
+            .collect();
+        let underlines: Vec<&str> = underlines.iter().map(String::as_str).collect();
+        let mut lines = vec![line(&labels, header_style), line(&underlines, "")];
+        for row in &cells {
+            let row = &row[..=last];
+            if !self.wrap {
+                let texts: Vec<&str> = row.iter().map(String::as_str).collect();
+                lines.push(line(&texts, ""));
+                continue;
+            }
+            let wrapped: Vec<Vec<String>> = row
+                .iter()
+                .enumerate()
+                .map(|(i, text)| {
+                    if i == last && !cut_last {
+                        vec![text.clone()]
+                    } else {
+                        wrap(text, widths[i])
+                    }
+                })
+                .collect();
+            let height = wrapped.iter().map(Vec::len).max().unwrap_or(1);
+            for k in 0..height {
+                let texts: Vec<&str> = wrapped
+                    .iter()
+                    .map(|cell| cell.get(k).map_or("", String::as_str))
+                    .collect();
+                lines.push(line(&texts, ""));
+            }
+        }
+        lines
+    }
+}
+
+/// How many characters `text` takes up on the console, its ANSI styling
+/// taking none
+fn display_width(text: &str) -> usize {
+    if text.contains('\x1b') {
+        strip_ansi(text).chars().count()
+    } else {
+        text.chars().count()
+    }
+}
+
+/// Narrow columns of `widths` to fit a line `limit` characters wide, with
+/// a space between columns. Columns that don't fit even at their `floors`
+/// width (their label's) are left off the right, then the widest of the
+/// rest narrow first, a character at a time. A column alone is cut to the
+/// limit.
+fn fit_columns(widths: &mut Vec<usize>, floors: &[usize], limit: usize) {
+    let total = |widths: &[usize]| widths.iter().sum::<usize>() + widths.len() - 1;
+    while widths.len() > 1 && total(&floors[..widths.len()]) > limit {
+        widths.pop();
+    }
+    while total(widths) > limit {
+        let widest = (0..widths.len())
+            .filter(|&i| widths[i] > floors[i])
+            .max_by_key(|&i| (widths[i], std::cmp::Reverse(i)));
+        match widest {
+            Some(i) => widths[i] -= 1,
+            None => break,
+        }
+    }
+    if widths.len() == 1 {
+        widths[0] = widths[0].min(limit.max(1));
+    }
+}
+
+/// A cell's text in a column `width` wide: padded to the width, or cut
+/// short with `…` (losing any styling, which can't be cut part way). The
+/// `open` last column of an unlimited line is only padded when
+/// right-aligned, and never cut.
+fn fit(text: &str, width: usize, alignment: Alignment, open: bool) -> String {
+    let length = display_width(text);
+    if length > width && !open {
+        let kept: String = strip_ansi(text)
+            .chars()
+            .take(width.saturating_sub(1))
+            .collect();
+        return format!("{}…", kept);
+    }
+    let padding = " ".repeat(width.saturating_sub(length));
+    match alignment {
+        Alignment::Right => format!("{}{}", padding, text),
+        Alignment::Left if open => text.to_string(),
+        Alignment::Left => format!("{}{}", text, padding),
+    }
+}
+
+/// `text` as lines at most `width` characters wide, broken between words
+/// where it can be and inside a word that is too long on its own
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    if display_width(text) <= width {
+        return vec![text.to_string()];
+    }
+    let width = width.max(1);
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for word in strip_ansi(text).split(' ') {
+        let length = current.chars().count();
+        if length > 0 && length + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut current));
+        } else if length > 0 {
+            current.push(' ');
+        }
+        current.push_str(word);
+        while current.chars().count() > width {
+            let rest: String = current.chars().skip(width).collect();
+            lines.push(current.chars().take(width).collect());
+            current = rest;
+        }
+    }
+    if !current.is_empty() || lines.is_empty() {
+        lines.push(current);
+    }
+    lines
+}
+
+/// How objects of some types are shown as `Label : value` lines, one
+/// object after another, as Format-List shows them
+#[derive(Debug, Clone)]
+pub struct ListView {
+    /// The type names the view is for
+    pub type_names: Vec<String>,
+    /// The lines' labels and values
+    pub entries: Vec<(String, ColumnValue)>,
+}
+
+impl ListView {
+    /// The lines of `items`, with an empty line around each
+    pub fn render(&self, items: &[&Value], context: &FormatContext) -> Vec<String> {
+        let width = self
+            .entries
+            .iter()
+            .map(|(label, _)| label.chars().count())
+            .max()
+            .unwrap_or(0);
+        let accent = context
+            .formatting
+            .as_ref()
+            .map_or("", |formatting| formatting.format_accent.as_str());
+        let mut lines = vec![String::new()];
+        for item in items {
+            for (label, value) in &self.entries {
+                let line = format!(
+                    "{}{}",
+                    styled(accent, &format!("{:<width$} : ", label, width = width)),
+                    value.text(item, context),
+                );
+                lines.push(line.trim_end().to_string());
+            }
+            lines.push(String::new());
+        }
+        lines
+    }
+}
+
+/// A view of objects of some types
+#[derive(Debug, Clone)]
+pub enum View {
+    Table(TableView),
+    List(ListView),
+}
+
+impl View {
+    /// The type names the view is for
+    pub fn type_names(&self) -> &[String] {
+        match self {
+            View::Table(view) => &view.type_names,
+            View::List(view) => &view.type_names,
+        }
+    }
+
+    /// The lines showing `items`
+    pub fn render(&self, items: &[&Value], context: &FormatContext) -> Vec<String> {
+        match self {
+            View::Table(view) => view.render(items, context),
+            View::List(view) => view.render(items, context),
+        }
+    }
+
+    /// A view from its JSON text, as a view file's `Views` have them
+    pub fn from_json(text: &str) -> Result<View, RuntimeError> {
+        let json: serde_json::Value = serde_json::from_str(text)
+            .map_err(|e| invalid(format!("The view is not valid JSON: {}", e)))?;
+        view_from_json(&json)
+    }
+
+    fn applies_to(&self, type_name: &str) -> bool {
+        self.type_names()
+            .iter()
+            .any(|own| own.eq_ignore_ascii_case(type_name))
+    }
+}
+
+impl From<TableView> for View {
+    fn from(view: TableView) -> Self {
+        View::Table(view)
+    }
+}
+
+impl From<ListView> for View {
+    fn from(view: ListView) -> Self {
+        View::List(view)
+    }
+}
+
+fn invalid(message: impl Into<String>) -> RuntimeError {
+    ErrorRecord::new(ErrorCategory::InvalidData, "InvalidFormatData", message).into()
+}
+
+/// A column or list entry of a view file: a property name, or
+/// `{"Property": ..., "Label": ..., "Width": ..., "Alignment": "Right"}`
+fn column_from_json(json: &serde_json::Value) -> Result<TableColumn, RuntimeError> {
+    let entry = match json {
+        serde_json::Value::String(name) => return Ok(TableColumn::property(name)),
+        serde_json::Value::Object(entry) => entry,
+        other => {
+            return Err(invalid(format!(
+                "A column is a property name or an object with a Property, not {}",
+                other
+            )))
+        }
+    };
+    let mut column = match entry.get("Property").and_then(|name| name.as_str()) {
+        Some(name) => TableColumn::property(name),
+        None => return Err(invalid("Each column needs a Property")),
+    };
+    for (key, value) in entry {
+        match key.as_str() {
+            "Property" => {}
+            "Label" => {
+                column.label = value
+                    .as_str()
+                    .ok_or_else(|| invalid(format!("Label must be a string, not {}", value)))?
+                    .to_string();
+            }
+            "Width" => {
+                column.width = Some(value.as_u64().filter(|width| *width > 0).ok_or_else(|| {
+                    invalid(format!(
+                        "Width must be a whole number above 0, not {}",
+                        value
+                    ))
+                })? as usize);
+            }
+            "Alignment" => {
+                column.alignment = match value.as_str() {
+                    Some(side) if side.eq_ignore_ascii_case("Left") => Alignment::Left,
+                    Some(side) if side.eq_ignore_ascii_case("Right") => Alignment::Right,
+                    _ => {
+                        return Err(invalid(format!(
+                            "Alignment is Left or Right, not {}",
+                            value
+                        )))
+                    }
+                };
+            }
+            other => {
+                return Err(invalid(format!(
+                    "Unknown column setting '{}'; use Property, Label, Width or Alignment",
+                    other
+                )))
+            }
+        }
+    }
+    Ok(column)
+}
+
+fn columns_from_json(
+    key: &str,
+    json: &serde_json::Value,
+) -> Result<Vec<TableColumn>, RuntimeError> {
+    let serde_json::Value::Array(columns) = json else {
+        return Err(invalid(format!(
+            "{} must be an array of columns, not {}",
+            key, json
+        )));
+    };
+    columns.iter().map(column_from_json).collect()
+}
+
+/// A view of a view file: a `TypeName` (or `TypeNames`), and its `Table`
+/// columns (with an optional `GroupBy` property and `Wrap` and `AutoSize`
+/// switches) or its `List` entries
+fn view_from_json(json: &serde_json::Value) -> Result<View, RuntimeError> {
+    let serde_json::Value::Object(entry) = json else {
+        return Err(invalid(format!("A view must be an object, not {}", json)));
+    };
+    let mut type_names = Vec::new();
+    let mut table = None;
+    let mut list = None;
+    let mut group_by = None;
+    let mut switches = Vec::new();
+    for (key, value) in entry {
+        match key.as_str() {
+            "TypeName" | "TypeNames" => {
+                let names = match value {
+                    serde_json::Value::Array(names) => names.iter().collect(),
+                    name => vec![name],
+                };
+                for name in names {
+                    type_names.push(
+                        name.as_str()
+                            .ok_or_else(|| {
+                                invalid(format!("{} must be type names, not {}", key, value))
+                            })?
+                            .to_string(),
+                    );
+                }
+            }
+            "Table" => table = Some(columns_from_json(key, value)?),
+            "List" => list = Some(columns_from_json(key, value)?),
+            "GroupBy" => {
+                let name = value.as_str().ok_or_else(|| {
+                    invalid(format!("GroupBy must be a property name, not {}", value))
+                })?;
+                group_by = Some((name.to_string(), ColumnValue::Property(name.to_string())));
+            }
+            "Wrap" | "AutoSize" => {
+                let on = value.as_bool().ok_or_else(|| {
+                    invalid(format!("{} must be true or false, not {}", key, value))
+                })?;
+                switches.push((key.as_str(), on));
+            }
+            other => {
+                return Err(invalid(format!(
+                "Unknown view setting '{}'; use TypeName, Table, List, GroupBy, Wrap or AutoSize",
+                other
+            )))
+            }
+        }
+    }
+    if type_names.is_empty() {
+        return Err(invalid("Each view needs a TypeName"));
+    }
+    let switch = |name: &str| switches.iter().any(|(key, on)| *key == name && *on);
+    match (table, list) {
+        (Some(columns), None) => Ok(View::Table(TableView {
+            type_names,
+            columns,
+            group_by,
+            wrap: switch("Wrap"),
+            auto_size: switch("AutoSize"),
+        })),
+        (None, Some(columns)) if group_by.is_none() && switches.is_empty() => {
+            Ok(View::List(ListView {
+                type_names,
+                entries: columns
+                    .into_iter()
+                    .map(|column| (column.label, column.value))
+                    .collect(),
+            }))
+        }
+        (None, Some(_)) => Err(invalid(
+            "GroupBy, Wrap and AutoSize only apply to a Table view",
+        )),
+        _ => Err(invalid(format!(
+            "The view for {} needs either Table columns or List entries",
+            type_names.join(", ")
+        ))),
+    }
+}
+
+/// The views in the text of a view file: `{"Views": [view, ...]}`, each
+/// view as `view_from_json` reads it. A view with a mistake is an
+/// `InvalidFormatData` error, so a misspelt setting isn't silently ignored.
+pub fn views_from_json(text: &str) -> Result<Vec<View>, RuntimeError> {
+    let json: serde_json::Value = serde_json::from_str(text)
+        .map_err(|e| invalid(format!("The view file is not valid JSON: {}", e)))?;
+    let views = match &json {
+        serde_json::Value::Object(file) => match (file.get("Views"), file.len()) {
+            (Some(serde_json::Value::Array(views)), 1) => views,
+            _ => return Err(invalid("A view file is an object with just a Views array")),
+        },
+        _ => return Err(invalid("A view file is an object with just a Views array")),
+    };
+    views.iter().map(view_from_json).collect()
+}
+
+/// The views the console knows: built in, and added with `register`
+#[derive(Debug, Clone)]
+pub struct FormatRegistry {
+    views: Vec<View>,
+}
+
+impl Default for FormatRegistry {
+    fn default() -> Self {
+        FormatRegistry {
+            views: vec![
+                file_system_view().into(),
+                process_view().into(),
+                measure_view().into(),
+                call_stack_view().into(),
+                breakpoint_view().into(),
+                history_view().into(),
+            ],
+        }
+    }
+}
+
+impl FormatRegistry {
+    /// The built-in views
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a view; it wins over earlier views for the same types
+    pub fn register(&mut self, view: impl Into<View>) {
+        self.views.insert(0, view.into());
+    }
+
+    /// Add a view that only applies to types no other view is for
+    pub fn append(&mut self, view: impl Into<View>) {
+        self.views.push(view.into());
+    }
+
+    /// Add the views in the text of a view file (see `views_from_json`);
+    /// when one is wrong, none are added
+    pub fn register_json(&mut self, text: &str) -> Result<(), RuntimeError> {
+        for view in views_from_json(text)? {
+            self.register(view);
+        }
+        Ok(())
+    }
+
+    /// The view for an object, from its most specific type name that has one
+    pub fn view_for(&self, item: &Value) -> Option<&View> {
+        let type_names = item.properties()?.type_names().to_vec();
+        type_names
+            .iter()
+            .find_map(|name| self.views.iter().find(|view| view.applies_to(name)))
+    }
+
+    /// The console lines for a command's output. Objects with a view are
+    /// shown in it, one table for each run of objects with the same view;
+    /// anything else is shown as its text. $null shows nothing.
+    pub fn format(&self, output: &[Value], context: &FormatContext) -> Vec<String> {
+        let mut lines = Vec::new();
+        let mut index = 0;
+        while index < output.len() {
+            let item = &output[index];
+            let Some(view) = self.view_for(item) else {
+                if *item != Value::Null {
+                    lines.push(item.to_string());
+                }
+                index += 1;
+                continue;
+            };
+            let run: Vec<&Value> = output[index..]
+                .iter()
+                .take_while(|other| {
+                    self.view_for(other)
+                        .is_some_and(|other_view| std::ptr::eq(other_view, view))
+                })
+                .collect();
+            index += run.len();
+            lines.extend(view.render(&run, context));
+        }
+        lines
+    }
+}
+
+/// Files and directories, as Get-ChildItem lists them: grouped by the
+/// directory they are in
+fn file_system_view() -> TableView {
+    TableView {
+        type_names: vec![
+            "System.IO.FileInfo".to_string(),
+            "System.IO.DirectoryInfo".to_string(),
+        ],
+        columns: vec![
+            // Get-ChildItem's modes are Unix ones: drwxr-xr-x
+            TableColumn {
+                width: Some(10),
+                ..TableColumn::property("Mode")
+            },
+            TableColumn {
+                label: "LastWriteTime".to_string(),
+                value: ColumnValue::Computed(last_write_time),
+                width: Some(26),
+                alignment: Alignment::Right,
+            },
+            TableColumn {
+                label: "Length".to_string(),
+                value: ColumnValue::Computed(file_length),
+                width: Some(14),
+                alignment: Alignment::Right,
+            },
+            TableColumn {
+                label: "Name".to_string(),
+                value: ColumnValue::Computed(file_name),
+                width: None,
+                alignment: Alignment::Left,
+            },
+        ],
+        group_by: Some(("Directory".to_string(), ColumnValue::Computed(parent_path))),
+        wrap: false,
+        auto_size: false,
+    }
+}
+
+/// Processes, as Get-Process lists them: memory in megabytes and
+/// processor time in seconds
+fn process_view() -> TableView {
+    TableView {
+        type_names: vec!["System.Diagnostics.Process".to_string()],
+        columns: vec![
+            TableColumn {
+                label: "WS(M)".to_string(),
+                value: ColumnValue::Computed(working_set_megabytes),
+                width: Some(10),
+                alignment: Alignment::Right,
+            },
+            TableColumn {
+                label: "CPU(s)".to_string(),
+                value: ColumnValue::Computed(cpu_seconds),
+                width: Some(10),
+                alignment: Alignment::Right,
+            },
+            TableColumn {
+                width: Some(7),
+                alignment: Alignment::Right,
+                ..TableColumn::property("Id")
+            },
+            TableColumn {
+                label: "ProcessName".to_string(),
+                ..TableColumn::property("Name")
+            },
+        ],
+        group_by: None,
+        wrap: false,
+        auto_size: false,
+    }
+}
+
+/// Get-PSCallStack's frames: each call and where it is
+fn call_stack_view() -> TableView {
+    TableView {
+        type_names: vec![CALL_STACK_FRAME_TYPE.to_string()],
+        columns: ["Command", "Arguments", "Location"]
+            .into_iter()
+            .map(TableColumn::property)
+            .collect(),
+        group_by: None,
+        wrap: false,
+        auto_size: false,
+    }
+}
+
+/// Get-PSBreakpoint's breakpoints: where each stops, by the script's file
+/// name, and its action
+fn breakpoint_view() -> TableView {
+    TableView {
+        type_names: vec![BREAKPOINT_TYPE.to_string()],
+        columns: vec![
+            TableColumn {
+                label: "ID".to_string(),
+                alignment: Alignment::Right,
+                ..TableColumn::property("Id")
+            },
+            TableColumn {
+                label: "Script".to_string(),
+                value: ColumnValue::Computed(script_file_name),
+                width: None,
+                alignment: Alignment::Left,
+            },
+            TableColumn {
+                alignment: Alignment::Right,
+                ..TableColumn::property("Line")
+            },
+            TableColumn::property("Command"),
+            TableColumn::property("Variable"),
+            TableColumn::property("Action"),
+        ],
+        group_by: None,
+        wrap: false,
+        auto_size: false,
+    }
+}
+
+/// Get-History's lines: each one's id, how long it ran in seconds, and
+/// the line
+fn history_view() -> TableView {
+    TableView {
+        type_names: vec![HISTORY_INFO_TYPE.to_string()],
+        columns: vec![
+            TableColumn {
+                alignment: Alignment::Right,
+                ..TableColumn::property("Id")
+            },
+            TableColumn {
+                label: "Duration".to_string(),
+                value: ColumnValue::Computed(history_duration),
+                width: None,
+                alignment: Alignment::Right,
+            },
+            TableColumn::property("CommandLine"),
+        ],
+        group_by: None,
+        wrap: false,
+        auto_size: false,
+    }
+}
+
+fn history_duration(item: &Value, _: &FormatContext) -> String {
+    match (
+        item.get_property("StartExecutionTime"),
+        item.get_property("EndExecutionTime"),
+    ) {
+        (Some(Value::DateTime(start)), Some(Value::DateTime(end))) => {
+            format!("{:.2}", (end - start).num_milliseconds() as f64 / 1000.0)
+        }
+        _ => String::new(),
+    }
+}
+
+fn script_file_name(item: &Value, _: &FormatContext) -> String {
+    match item.get_property("Script") {
+        Some(Value::String(path)) => std::path::Path::new(&*path)
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+        _ => String::new(),
+    }
+}
+
+fn working_set_megabytes(item: &Value, _: &FormatContext) -> String {
+    match item.get_property("WorkingSet") {
+        Some(Value::Number(bytes)) => format!("{:.2}", bytes / (1024.0 * 1024.0)),
+        _ => String::new(),
+    }
+}
+
+fn cpu_seconds(item: &Value, _: &FormatContext) -> String {
+    match item.get_property("CPU") {
+        Some(Value::Number(seconds)) => format!("{:.2}", seconds),
+        _ => String::new(),
+    }
+}
+
+/// Measure-Object's results, as a list
+fn measure_view() -> ListView {
+    ListView {
+        type_names: vec!["Microsoft.PowerShell.Commands.GenericMeasureInfo".to_string()],
+        entries: [
+            "Count",
+            "Average",
+            "Sum",
+            "Maximum",
+            "Minimum",
+            "StandardDeviation",
+            "Property",
+        ]
+        .into_iter()
+        .map(|name| (name.to_string(), ColumnValue::Property(name.to_string())))
+        .collect(),
+    }
+}
+
+/// `10/16/2026  9:15 AM`, from a date or from seconds since 1970
+fn last_write_time(item: &Value, _: &FormatContext) -> String {
+    let time = match item.get_property("LastWriteTime") {
+        Some(Value::DateTime(time)) => Some(time),
+        Some(Value::Number(seconds)) => Local
+            .timestamp_opt(seconds as i64, 0)
+            .single()
+            .map(|time| time.naive_local()),
+        _ => None,
+    };
+    time.map(|time| {
+        format!(
+            "{:>10} {:>8}",
+            time.format("%-m/%-d/%Y"),
+            time.format("%-I:%M %p")
+        )
+    })
+    .unwrap_or_default()
+}
+
+/// A file's length; nothing for a directory
+fn file_length(item: &Value, _: &FormatContext) -> String {
+    if item
+        .get_property("Directory")
+        .is_some_and(|directory| directory.to_bool())
+    {
+        return String::new();
+    }
+    item.get_property("Length")
+        .map(|length| length.to_string())
+        .unwrap_or_default()
+}
+
+/// The name, styled as `$PSStyle.FileInfo` says when output is styled, and
+/// where a link points: `latest -> releases/2.0`
+fn file_name(item: &Value, context: &FormatContext) -> String {
+    let name = item
+        .get_property("Name")
+        .map(|name| name.to_string())
+        .unwrap_or_default();
+    let name = match &context.file_info {
+        Some(style) => style.apply(item, &name),
+        None => name,
+    };
+    match item.get_property("LinkTarget") {
+        Some(Value::Null) | None => name,
+        Some(target) => format!("{} -> {}", name, target),
+    }
+}
+
+/// The directory an item is in
+fn parent_path(item: &Value, _: &FormatContext) -> String {
+    let full_name = item
+        .get_property("FullName")
+        .map(|name| name.to_string())
+        .unwrap_or_default();
+    std::path::Path::new(&full_name)
+        .parent()
+        .map(|parent| parent.display().to_string())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::property_map::PropertyMap;
+
+    fn file(full_name: &str, length: f64, directory: bool) -> Value {
+        let name = full_name.rsplit('/').next().unwrap();
+        let mut props = PropertyMap::from([
+            ("Name", Value::String(name.into())),
+            ("FullName", Value::String(full_name.into())),
+            ("Length", Value::Number(length)),
+            (
+                "LastWriteTime",
+                Value::DateTime(
+                    chrono::NaiveDate::from_ymd_opt(2026, 10, 6)
+                        .unwrap()
+                        .and_hms_opt(9, 5, 0)
+                        .unwrap(),
+                ),
+            ),
+            (
+                "Mode",
+                Value::String(
+                    if directory {
+                        "drwxr-xr-x"
+                    } else {
+                        "-rw-r--r--"
+                    }
+                    .into(),
+                ),
+            ),
+            ("Directory", Value::Boolean(directory)),
+            ("LinkTarget", Value::Null),
+        ]);
+        props.insert_type_name(if directory {
+            "System.IO.DirectoryInfo"
+        } else {
+            "System.IO.FileInfo"
+        });
+        Value::Object(props.into())
+    }
+
+    #[test]
+    fn test_files_show_as_a_table_per_directory() {
+        let output = vec![
+            file("/src/docs", 0.0, true),
+            file("/src/README.md", 1234.0, false),
+            file("/src/docs/guide.md", 56.0, false),
+        ];
+        let lines = FormatRegistry::new().format(&output, &FormatContext::default());
+        assert_eq!(
+            lines,
+            vec![
+                "",
+                "    Directory: /src",
+                "",
+                "Mode                    LastWriteTime         Length Name",
+                "----                    -------------         ------ ----",
+                "drwxr-xr-x         10/6/2026  9:05 AM                docs",
+                "-rw-r--r--         10/6/2026  9:05 AM           1234 README.md",
+                "",
+                "",
+                "    Directory: /src/docs",
+                "",
+                "Mode                    LastWriteTime         Length Name",
+                "----                    -------------         ------ ----",
+                "-rw-r--r--         10/6/2026  9:05 AM             56 guide.md",
+                "",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_values_without_a_view_show_as_text() {
+        let plain = Value::Object(PropertyMap::from([("A", Value::Number(1.0))]).into());
+        let output = vec![
+            Value::String("before".into()),
+            Value::Null,
+            plain,
+            file("/a.txt", 1.0, false),
+            Value::Number(2.0),
+        ];
+        let lines = FormatRegistry::new().format(&output, &FormatContext::default());
+        assert_eq!(lines[..2], ["before", "@{A=1}"]);
+        assert_eq!(lines[3], "    Directory: /");
+        assert_eq!(lines.last().unwrap(), "2");
+    }
+
+    #[test]
+    fn test_processes_and_measurements_have_views() {
+        let mut process = PropertyMap::from([
+            ("Name", Value::String("pwsh".into())),
+            ("Id", Value::Number(4242.0)),
+            ("CPU", Value::Number(1.5)),
+            ("WorkingSet", Value::Number(52_428_800.0)),
+        ]);
+        process.insert_type_name("System.Diagnostics.Process");
+        let lines = FormatRegistry::new()
+            .format(&[Value::Object(process.into())], &FormatContext::default());
+        assert_eq!(lines[1], "     WS(M)     CPU(s)      Id ProcessName");
+        assert_eq!(lines[3], "     50.00       1.50    4242 pwsh");
+
+        let mut measure = PropertyMap::from([
+            ("Count", Value::Number(3.0)),
+            ("Sum", Value::Number(6.0)),
+            ("Property", Value::Null),
+        ]);
+        measure.insert_type_name("Microsoft.PowerShell.Commands.GenericMeasureInfo");
+        let lines = FormatRegistry::new()
+            .format(&[Value::Object(measure.into())], &FormatContext::default());
+        assert_eq!(
+            lines,
+            vec![
+                "",
+                "Count             : 3",
+                "Average           :",
+                "Sum               : 6",
+                "Maximum           :",
+                "Minimum           :",
+                "StandardDeviation :",
+                "Property          :",
+                "",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_views_from_json() {
+        let mut formats = FormatRegistry::new();
+        formats
+            .register_json(
+                r#"{"Views": [
+                    {"TypeName": "Demo.Server",
+                     "Table": ["Name", {"Property": "Port", "Width": 6, "Alignment": "right"}]},
+                    {"TypeNames": ["Demo.Config"], "List": [{"Property": "Path", "Label": "Where"}]}
+                ]}"#,
+            )
+            .unwrap();
+        let mut server = PropertyMap::from([
+            ("Name", Value::String("web".into())),
+            ("Port", Value::Number(80.0)),
+        ]);
+        server.insert_type_name("Demo.Server");
+        let mut config = PropertyMap::from([("Path", Value::String("/etc".into()))]);
+        config.insert_type_name("demo.config");
+        let lines = formats.format(
+            &[Value::Object(server.into()), Value::Object(config.into())],
+            &FormatContext::default(),
+        );
+        assert_eq!(
+            lines,
+            vec![
+                "",
+                "Name   Port",
+                "----   ----",
+                "web      80",
+                "",
+                "",
+                "Where : /etc",
+                ""
+            ]
+        );
+
+        let message = |text: &str| views_from_json(text).unwrap_err().to_string();
+        assert!(message("[]").contains("just a Views array"));
+        assert!(message(r#"{"Views": [{"Table": ["A"]}]}"#).contains("needs a TypeName"));
+        assert!(message(r#"{"Views": [{"TypeName": "T"}]}"#).contains("either Table"));
+        assert!(message(r#"{"Views": [{"TypeName": "T", "Tabel": []}]}"#)
+            .contains("Unknown view setting 'Tabel'"));
+        assert!(message(
+            r#"{"Views": [{"TypeName": "T", "Table": [{"Property": "A", "Width": 0}]}]}"#
+        )
+        .contains("above 0"));
+        assert!(
+            message(r#"{"Views": [{"TypeName": "T", "List": ["A"], "GroupBy": "B"}]}"#)
+                .contains("only apply to a Table")
+        );
+    }
+
+    #[test]
+    fn test_cells_are_fitted_to_their_columns() {
+        assert_eq!(fit("abc", 5, Alignment::Left, false), "abc  ");
+        assert_eq!(fit("abc", 5, Alignment::Right, false), "  abc");
+        assert_eq!(fit("abcdef", 4, Alignment::Left, false), "abc…");
+        assert_eq!(fit("abcdef", 4, Alignment::Left, true), "abcdef");
+        assert_eq!(
+            fit("\x1b[1mab\x1b[0m", 3, Alignment::Left, false),
+            "\x1b[1mab\x1b[0m "
+        );
+        assert_eq!(fit("\x1b[1mabcd\x1b[0m", 3, Alignment::Left, false), "ab…");
+    }
+
+    fn server_table(wrap: bool, auto_size: bool) -> TableView {
+        TableView {
+            type_names: Vec::new(),
+            columns: vec![
+                TableColumn {
+                    width: Some(8),
+                    ..TableColumn::property("Name")
+                },
+                TableColumn::property("Notes"),
+            ],
+            group_by: None,
+            wrap,
+            auto_size,
+        }
+    }
+
+    fn server(name: &str, notes: &str) -> Value {
+        Value::Object(
+            PropertyMap::from([
+                ("Name", Value::String(name.into())),
+                ("Notes", Value::String(notes.into())),
+            ])
+            .into(),
+        )
+    }
+
+    #[test]
+    fn test_tables_fit_the_console_width() {
+        let web = server("web", "serves the public site from three regions");
+        let narrow = FormatContext {
+            width: Some(24),
+            ..FormatContext::default()
+        };
+        let lines = server_table(false, false).render(&[&web], &narrow);
+        assert_eq!(
+            lines,
+            vec![
+                "",
+                "Name     Notes",
+                "----     -----",
+                "web      serves the pub…",
+                ""
+            ]
+        );
+        assert!(lines.iter().all(|line| line.chars().count() <= 24));
+
+        // Without a limit the last column is as long as it needs to be
+        let lines = server_table(false, false).render(&[&web], &FormatContext::default());
+        assert_eq!(
+            lines[3],
+            "web      serves the public site from three regions"
+        );
+
+        // Columns that don't fit at all are left off
+        let tiny = FormatContext {
+            width: Some(8),
+            ..FormatContext::default()
+        };
+        let lines = server_table(false, false).render(&[&web], &tiny);
+        assert_eq!(lines[1..4], ["Name", "----", "web"]);
+    }
+
+    #[test]
+    fn test_wrap_and_auto_size() {
+        let web = server("webserver01", "serves the public site");
+        let narrow = FormatContext {
+            width: Some(20),
+            ..FormatContext::default()
+        };
+        assert_eq!(
+            server_table(true, false).render(&[&web], &narrow)[3..6],
+            ["webserve serves the", "r01      public site", ""]
+        );
+        assert_eq!(
+            server_table(false, true).render(&[&web], &FormatContext::default())[3],
+            "webserver01 serves the public site"
+        );
+        // Wrapping breaks a word too long for its column
+        assert_eq!(wrap("abcdefgh ij", 3), vec!["abc", "def", "gh", "ij"]);
+        assert_eq!(wrap("short", 8), vec!["short"]);
+    }
+}
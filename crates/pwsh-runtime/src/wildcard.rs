@@ -0,0 +1,227 @@
+/// Wildcard patterns for -like, -Name, -Include and the like
+///
+/// `*` matches any run of characters, `?` exactly one, and `[a-z0-9_]` one
+/// character from a set of characters and ranges. A backtick makes the next
+/// character literal, so `` `* `` matches a star and `` `[ `` a bracket.
+/// Patterns match the whole text, case-insensitively unless asked otherwise.
+use crate::error::{ErrorCategory, ErrorRecord, RuntimeError};
+
+/// One step of a parsed pattern
+#[derive(Debug, Clone, PartialEq)]
+enum Element {
+    Literal(char),
+    /// `?`
+    AnyChar,
+    /// `*`
+    AnyRun,
+    /// `[...]`: inclusive character ranges, a single character being `(c, c)`
+    Class(Vec<(char, char)>),
+}
+
+/// A parsed wildcard pattern
+#[derive(Debug, Clone, PartialEq)]
+pub struct WildcardPattern {
+    elements: Vec<Element>,
+    case_sensitive: bool,
+}
+
+impl WildcardPattern {
+    /// Parse a pattern that matches case-insensitively, like PowerShell's
+    /// default comparisons. An unclosed `[` makes the pattern invalid.
+    pub fn new(pattern: &str) -> Result<Self, RuntimeError> {
+        let invalid = || -> RuntimeError {
+            ErrorRecord::new(
+                ErrorCategory::InvalidArgument,
+                "WildcardPatternInvalid",
+                format!(
+                    "The specified wildcard character pattern is not valid: {}",
+                    pattern
+                ),
+            )
+            .with_target(pattern)
+            .into()
+        };
+
+        let mut elements = Vec::new();
+        let mut chars = pattern.chars();
+        while let Some(c) = chars.next() {
+            let element = match c {
+                '*' => Element::AnyRun,
+                '?' => Element::AnyChar,
+                // A trailing backtick has nothing to escape and stands for itself
+                '`' => Element::Literal(chars.next().unwrap_or('`')),
+                '[' => {
+                    let mut ranges = Vec::new();
+                    loop {
+                        let start = match chars.next().ok_or_else(invalid)? {
+                            ']' => break,
+                            '`' => chars.next().ok_or_else(invalid)?,
+                            c => c,
+                        };
+                        // '-' between two characters makes a range; first or
+                        // last in the class it is literal
+                        let mut lookahead = chars.clone();
+                        if lookahead.next() == Some('-') {
+                            match lookahead.next().ok_or_else(invalid)? {
+                                ']' => {}
+                                end => {
+                                    let end = if end == '`' {
+                                        lookahead.next().ok_or_else(invalid)?
+                                    } else {
+                                        end
+                                    };
+                                    chars = lookahead;
+                                    ranges.push((start, end));
+                                    continue;
+                                }
+                            }
+                        }
+                        ranges.push((start, start));
+                    }
+                    Element::Class(ranges)
+                }
+                c => Element::Literal(c),
+            };
+            elements.push(element);
+        }
+
+        Ok(WildcardPattern {
+            elements,
+            case_sensitive: false,
+        })
+    }
+
+    /// Match case-sensitively (as -clike does) or not
+    pub fn case_sensitive(mut self, case_sensitive: bool) -> Self {
+        self.case_sensitive = case_sensitive;
+        self
+    }
+
+    /// Whether the pattern has no wildcards, so it matches one text only
+    /// (`Name` or ``Total`*``, but not `*Time`)
+    pub fn is_literal(&self) -> bool {
+        self.elements
+            .iter()
+            .all(|element| matches!(element, Element::Literal(_)))
+    }
+
+    /// Whether `text` matches the whole pattern
+    pub fn is_match(&self, text: &str) -> bool {
+        let text: Vec<char> = text.chars().collect();
+        let p = &self.elements;
+
+        // Greedy matching that backtracks to the most recent '*'
+        let mut pi: usize = 0;
+        let mut ti: usize = 0;
+        let mut star_idx: Option<usize> = None;
+        let mut match_ti: usize = 0;
+
+        while ti < text.len() {
+            if pi < p.len() && p[pi] != Element::AnyRun && self.matches_one(&p[pi], text[ti]) {
+                pi += 1;
+                ti += 1;
+            } else if pi < p.len() && p[pi] == Element::AnyRun {
+                star_idx = Some(pi);
+                pi += 1;
+                match_ti = ti;
+            } else if let Some(si) = star_idx {
+                pi = si + 1;
+                match_ti += 1;
+                ti = match_ti;
+            } else {
+                return false;
+            }
+        }
+
+        while pi < p.len() && p[pi] == Element::AnyRun {
+            pi += 1;
+        }
+
+        pi == p.len()
+    }
+
+    fn matches_one(&self, element: &Element, c: char) -> bool {
+        match element {
+            Element::AnyChar => true,
+            Element::AnyRun => false,
+            Element::Literal(l) => self.chars_equal(*l, c),
+            Element::Class(ranges) => ranges.iter().any(|&(start, end)| {
+                let in_range = |c: char| start <= c && c <= end;
+                in_range(c)
+                    || (!self.case_sensitive
+                        && (c.to_lowercase().any(in_range) || c.to_uppercase().any(in_range)))
+            }),
+        }
+    }
+
+    fn chars_equal(&self, a: char, b: char) -> bool {
+        a == b || (!self.case_sensitive && a.to_lowercase().eq(b.to_lowercase()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_match(pattern: &str, text: &str) -> bool {
+        WildcardPattern::new(pattern).unwrap().is_match(text)
+    }
+
+    #[test]
+    fn test_star_and_question_mark() {
+        assert!(is_match("*.rs", "main.rs"));
+        assert!(is_match("*.RS", "Main.rs"));
+        assert!(is_match("ma?n*", "main.rs"));
+        assert!(is_match("*", ""));
+        assert!(!is_match("*.rs", "main.rs.bak"));
+        assert!(!is_match("?", ""));
+        assert!(is_match("a*b*c", "aXbYbc"));
+    }
+
+    #[test]
+    fn test_character_classes() {
+        assert!(is_match("file[0-9].txt", "file7.txt"));
+        assert!(!is_match("file[0-9].txt", "fileX.txt"));
+        assert!(is_match("[abc]*", "banana"));
+        assert!(is_match("[a-c]*", "Banana"));
+        assert!(is_match("[-x]", "-"));
+        assert!(is_match("[x-]", "-"));
+        assert!(!is_match("[a-c]", "d"));
+    }
+
+    #[test]
+    fn test_backtick_escapes() {
+        assert!(is_match("a`*", "a*"));
+        assert!(!is_match("a`*", "ab"));
+        assert!(is_match("`[x`]", "[x]"));
+        assert!(is_match("[`]]", "]"));
+        assert!(is_match("a`", "a`"));
+    }
+
+    #[test]
+    fn test_is_literal() {
+        let literal = |pattern: &str| WildcardPattern::new(pattern).unwrap().is_literal();
+        assert!(literal("Name"));
+        assert!(literal("Total`*"));
+        assert!(!literal("*Time"));
+        assert!(!literal("[ab]c"));
+        assert!(!literal("?"));
+    }
+
+    #[test]
+    fn test_case_sensitive() {
+        let pattern = WildcardPattern::new("Foo*").unwrap().case_sensitive(true);
+        assert!(pattern.is_match("Foobar"));
+        assert!(!pattern.is_match("foobar"));
+
+        let class = WildcardPattern::new("[A-Z]").unwrap().case_sensitive(true);
+        assert!(!class.is_match("a"));
+    }
+
+    #[test]
+    fn test_unclosed_class_is_invalid() {
+        let error = WildcardPattern::new("a[bc").unwrap_err();
+        assert_eq!(error.error_id(), "WildcardPatternInvalid");
+        assert!(WildcardPattern::new("a[b-").is_err());
+    }
+}
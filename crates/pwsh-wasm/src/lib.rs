@@ -0,0 +1,141 @@
+/// Browser bindings for the PowerShell interpreter
+///
+/// Built for `wasm32-unknown-unknown` with wasm-bindgen, e.g.
+/// `wasm-pack build crates/pwsh-wasm --target web`. Scripts run without file
+/// system or process cmdlets. Results come back as JSON:
+///
+/// ```json
+/// { "output": [3], "display": ["3"], "host": "", "error": null }
+/// ```
+///
+/// `output` holds the values the script produced, mapped to JSON the way
+/// `Value::to_json` does; `display` the same values as the REPL would print
+/// them; `host` everything written to the host (Write-Host, warnings); and
+/// `error` the error message, if the script failed.
+use pwsh_runtime::{Host, Session, SessionBuilder, SessionError, Value};
+use std::cell::RefCell;
+use std::rc::Rc;
+use wasm_bindgen::prelude::*;
+
+/// Collects host text for the page to show; there is no input to read
+#[derive(Clone, Default)]
+struct BufferHost {
+    text: Rc<RefCell<String>>,
+}
+
+impl Host for BufferHost {
+    fn write(&mut self, text: &str) {
+        self.text.borrow_mut().push_str(text);
+    }
+
+    fn write_error_line(&mut self, text: &str) {
+        self.write_line(text);
+    }
+
+    fn read_line(&mut self, _prompt: &str) -> Option<String> {
+        None
+    }
+}
+
+/// An interpreter session that keeps its variables and functions between
+/// calls, for a browser REPL
+#[wasm_bindgen]
+pub struct Repl {
+    session: Session,
+    host: BufferHost,
+}
+
+#[wasm_bindgen]
+impl Repl {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Repl {
+        let host = BufferHost::default();
+        let session = SessionBuilder::new()
+            .cmdlets(pwsh_cmdlets::register_all)
+            .host(host.clone())
+            .build();
+        Repl { session, host }
+    }
+
+    /// Run a script and describe the result as JSON
+    pub fn evaluate(&mut self, script: &str) -> String {
+        let result = self.session.invoke(script);
+        let host = self.host.text.take();
+        let (output, error) = match result {
+            Ok(output) => (output, None),
+            Err(e) => (Vec::new(), Some(error_message(&e))),
+        };
+        let display: Vec<String> = output
+            .iter()
+            .filter(|value| **value != Value::Null)
+            .map(|value| value.to_string())
+            .collect();
+
+        let output: Vec<serde_json::Value> = output.iter().map(serde_json::Value::from).collect();
+        serde_json::json!({
+            "output": output,
+            "display": display,
+            "host": host,
+            "error": error,
+        })
+        .to_string()
+    }
+}
+
+impl Default for Repl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Run a script in a fresh session and describe the result as JSON
+#[wasm_bindgen]
+pub fn evaluate(script: &str) -> String {
+    Repl::new().evaluate(script)
+}
+
+fn error_message(error: &SessionError) -> String {
+    match error {
+        SessionError::Lex(e) => format!("Lexer error: {}", e),
+        SessionError::Parse(e) => format!("Parse error: {}", e),
+        SessionError::Runtime(e) => format!("Runtime error: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn evaluate_json(repl: &mut Repl, script: &str) -> serde_json::Value {
+        serde_json::from_str(&repl.evaluate(script)).unwrap()
+    }
+
+    #[test]
+    fn test_evaluate_returns_output_and_host_text() {
+        let result: serde_json::Value =
+            serde_json::from_str(&evaluate("Write-Host 'hi'; @(1, 'a') | Sort-Object")).unwrap();
+        assert_eq!(
+            result,
+            serde_json::json!({
+                "output": [1, "a"],
+                "display": ["1", "a"],
+                "host": "hi\n",
+                "error": null,
+            })
+        );
+    }
+
+    #[test]
+    fn test_repl_keeps_state_and_reports_errors() {
+        let mut repl = Repl::new();
+        evaluate_json(&mut repl, "$x = 2");
+        assert_eq!(evaluate_json(&mut repl, "$x * 3")["display"][0], "6");
+
+        let result = evaluate_json(&mut repl, "$x / 0");
+        assert!(result["error"]
+            .as_str()
+            .unwrap()
+            .starts_with("Runtime error:"));
+        assert_eq!(result["output"], serde_json::json!([]));
+    }
+}
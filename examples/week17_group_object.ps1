@@ -8,8 +8,11 @@
 Get-ChildItem -Path ./examples | Group-Object Extension | Select-Object Name, Count | Write-Output
 
 # Hashtable output
-$groups = @("a", "b", "b", "c") | Group-Object -AsHashTable true
+$groups = @("a", "b", "b", "c") | Group-Object -AsHashTable
 $groups | Write-Output
 
 # NoElement output (omit Group arrays)
-@(1, 1, 2, 2, 2) | Group-Object -NoElement true | Write-Output
+@(1, 1, 2, 2, 2) | Group-Object -NoElement | Write-Output
+
+# Most common first, instead of piping to Sort-Object Count -Descending
+@("a", "b", "b", "c", "b", "c") | Group-Object -SortByCount | Select-Object Name, Count | Write-Output
@@ -4,7 +4,7 @@
 @(3, 1, 4, 1, 5, 9) | Sort-Object | Write-Output
 
 # Descending (switch params are passed as explicit booleans in this interpreter)
-@(3, 1, 4, 1, 5, 9) | Sort-Object -Descending true | Write-Output
+@(3, 1, 4, 1, 5, 9) | Sort-Object -Descending | Write-Output
 
 # Sort objects by a property
 $procs = @(
@@ -16,4 +16,4 @@ $procs = @(
 $procs | Sort-Object CPU | Select-Object Name, CPU | Write-Output
 
 # Sort files by length
-Get-ChildItem | Sort-Object Length -Descending true | Select-Object Name, Length | Write-Output
+Get-ChildItem | Sort-Object Length -Descending | Select-Object Name, Length | Write-Output
@@ -86,7 +86,7 @@ Write-Output "Final total: $global:total"
 $GLOBAL:config = "Production"
 Write-Output "Config (GLOBAL): $global:config"
 
-$Global:debug = true
+$Global:debug = $true
 Write-Output "Debug (Global): $GLOBAL:debug"
 
 # ===================================